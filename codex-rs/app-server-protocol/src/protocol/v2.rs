@@ -93,6 +93,8 @@ pub enum CodexErrorInfo {
         #[ts(rename = "httpStatusCode")]
         http_status_code: Option<u16>,
     },
+    /// A task limit such as `codex exec --max-turns` stopped the task.
+    RunLimitReached,
     Other,
 }
 
@@ -117,6 +119,7 @@ impl From<CoreCodexErrorInfo> for CodexErrorInfo {
             CoreCodexErrorInfo::ResponseTooManyFailedAttempts { http_status_code } => {
                 CodexErrorInfo::ResponseTooManyFailedAttempts { http_status_code }
             }
+            CoreCodexErrorInfo::RunLimitReached => CodexErrorInfo::RunLimitReached,
             CoreCodexErrorInfo::Other => CodexErrorInfo::Other,
         }
     }
@@ -850,7 +850,9 @@ async fn handle_token_count_event(
     token_count_event: TokenCountEvent,
     outgoing: &OutgoingMessageSender,
 ) {
-    let TokenCountEvent { info, rate_limits } = token_count_event;
+    let TokenCountEvent {
+        info, rate_limits, ..
+    } = token_count_event;
     if let Some(token_usage) = info.map(ThreadTokenUsage::from) {
         let notification = ThreadTokenUsageUpdatedNotification {
             thread_id: conversation_id.to_string(),
@@ -1493,6 +1495,7 @@ mod tests {
                 unlimited: false,
                 balance: Some("5".to_string()),
             }),
+            request_limits: None,
         };
 
         handle_token_count_event(
@@ -1501,6 +1504,7 @@ mod tests {
             TokenCountEvent {
                 info: Some(info),
                 rate_limits: Some(rate_limits),
+                cost: None,
             },
             &outgoing,
         )
@@ -1554,6 +1558,7 @@ mod tests {
             TokenCountEvent {
                 info: None,
                 rate_limits: None,
+                cost: None,
             },
             &outgoing,
         )
@@ -3025,6 +3025,17 @@ impl CodexMessageProcessor {
             include_logs,
         } = params;
 
+        if let Err(err) = codex_core::default_client::check_non_provider_network("Feedback upload")
+        {
+            let error = JSONRPCErrorError {
+                code: INVALID_REQUEST_ERROR_CODE,
+                message: err.to_string(),
+                data: None,
+            };
+            self.outgoing.send_error(request_id, error).await;
+            return;
+        }
+
         let conversation_id = match thread_id.as_deref() {
             Some(thread_id) => match ConversationId::from_string(thread_id) {
                 Ok(conversation_id) => Some(conversation_id),
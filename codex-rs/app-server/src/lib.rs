@@ -3,6 +3,8 @@
 use codex_common::CliConfigOverrides;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
+use codex_core::default_client::apply_privacy;
+use codex_core::redact::RedactingWriter;
 use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
 use std::io::ErrorKind;
 use std::io::Result as IoResult;
@@ -88,6 +90,7 @@ pub async fn run_main(
             .map_err(|e| {
                 std::io::Error::new(ErrorKind::InvalidData, format!("error loading config: {e}"))
             })?;
+    apply_privacy(&config.privacy);
 
     let feedback = CodexFeedback::new();
 
@@ -102,7 +105,7 @@ pub async fn run_main(
     // Install a simple subscriber so `tracing` output is visible.  Users can
     // control the log level with `RUST_LOG`.
     let stderr_fmt = tracing_subscriber::fmt::layer()
-        .with_writer(std::io::stderr)
+        .with_writer(|| RedactingWriter::new(std::io::stderr()))
         .with_filter(EnvFilter::from_default_env());
 
     let feedback_layer = tracing_subscriber::fmt::layer()
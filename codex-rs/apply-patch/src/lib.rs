@@ -10,6 +10,7 @@ use std::sync::LazyLock;
 
 use anyhow::Context;
 use anyhow::Result;
+use parser::ADD_FILE_MARKER;
 pub use parser::Hunk;
 pub use parser::ParseError;
 use parser::ParseError::*;
@@ -237,6 +238,44 @@ impl ApplyPatchAction {
         &self.changes
     }
 
+    /// Replaces the contents of a file this patch adds and rewrites `patch` so
+    /// that applying it writes the new contents. Returns `false`, leaving the
+    /// action untouched, when the patch does not add `path`.
+    pub fn replace_added_file(&mut self, path: &Path, content: &str) -> bool {
+        let Some(ApplyPatchFileChange::Add { content: existing }) = self.changes.get_mut(path)
+        else {
+            return false;
+        };
+
+        let mut rewritten = Vec::new();
+        let mut lines = self.patch.lines().peekable();
+        let mut found = false;
+        while let Some(line) = lines.next() {
+            rewritten.push(line.to_string());
+            let adds_path = line
+                .trim()
+                .strip_prefix(ADD_FILE_MARKER)
+                .is_some_and(|added| self.cwd.join(added) == path);
+            if adds_path && !found {
+                found = true;
+                while lines.next_if(|line| line.starts_with('+')).is_some() {}
+                rewritten.extend(content.lines().map(|line| format!("+{line}")));
+            }
+        }
+        if !found {
+            return false;
+        }
+
+        // Match what the parser produces: every added line ends with '\n'.
+        *existing = content.lines().map(|line| format!("{line}\n")).collect();
+        let trailing_newline = self.patch.ends_with('\n');
+        self.patch = rewritten.join("\n");
+        if trailing_newline {
+            self.patch.push('\n');
+        }
+        true
+    }
+
     /// Should be used exclusively for testing. (Not worth the overhead of
     /// creating a feature flag for this.)
     pub fn new_add_for_test(path: &Path, content: String) -> Self {
@@ -698,8 +737,22 @@ fn derive_new_contents_from_chunks(
             }));
         }
     };
+    let new_contents = apply_update_chunks(path, &original_contents, chunks)?;
+    Ok(AppliedPatch {
+        original_contents,
+        new_contents,
+    })
+}
 
-    let mut original_lines: Vec<String> = original_contents.split('\n').map(String::from).collect();
+/// Applies the chunks of an update of `path` to `contents` rather than to
+/// the file on disk, e.g. to the version of the file the patch was written
+/// against.
+pub fn apply_update_chunks(
+    path: &Path,
+    contents: &str,
+    chunks: &[UpdateFileChunk],
+) -> std::result::Result<String, ApplyPatchError> {
+    let mut original_lines: Vec<String> = contents.split('\n').map(String::from).collect();
 
     // Drop the trailing empty element that results from the final newline so
     // that line counts match the behaviour of standard `diff`.
@@ -713,11 +766,7 @@ fn derive_new_contents_from_chunks(
     if !new_lines.last().is_some_and(String::is_empty) {
         new_lines.push(String::new());
     }
-    let new_contents = new_lines.join("\n");
-    Ok(AppliedPatch {
-        original_contents,
-        new_contents,
-    })
+    Ok(new_lines.join("\n"))
 }
 
 /// Compute a list of replacements needed to transform `original_lines` into the
@@ -1696,6 +1745,43 @@ g
         );
     }
 
+    #[test]
+    fn test_replace_added_file_rewrites_patch() {
+        let session_dir = tempdir().unwrap();
+        let argv = vec![
+            "apply_patch".to_string(),
+            wrap_patch("*** Add File: new.rs\n+fn main() {}\n*** Add File: other.rs\n+// other"),
+        ];
+        let MaybeApplyPatchVerified::Body(mut action) =
+            maybe_parse_apply_patch_verified(&argv, session_dir.path())
+        else {
+            panic!("expected verified body");
+        };
+
+        let path = session_dir.path().join("new.rs");
+        assert!(action.replace_added_file(&path, "// Licensed under MIT.\n\nfn main() {}\n"));
+        assert!(!action.replace_added_file(&session_dir.path().join("missing.rs"), ""));
+        assert_eq!(
+            action.patch,
+            wrap_patch(
+                "*** Add File: new.rs\n+// Licensed under MIT.\n+\n+fn main() {}\n*** Add File: other.rs\n+// other"
+            )
+        );
+        assert_eq!(
+            action.changes().get(&path),
+            Some(&ApplyPatchFileChange::Add {
+                content: "// Licensed under MIT.\n\nfn main() {}\n".to_string(),
+            })
+        );
+
+        // The rewritten patch must still parse to the same change.
+        let reparsed = maybe_parse_apply_patch_verified(
+            &["apply_patch".to_string(), action.patch.clone()],
+            session_dir.path(),
+        );
+        assert_eq!(reparsed, MaybeApplyPatchVerified::Body(action));
+    }
+
     #[test]
     fn test_apply_patch_resolves_move_path_with_effective_cwd() {
         let session_dir = tempdir().unwrap();
@@ -17,7 +17,7 @@ pub(crate) async fn chatgpt_get_request<T: DeserializeOwned>(
         .await?;
 
     // Make direct HTTP request to ChatGPT backend API with the token
-    let client = create_client();
+    let client = create_client()?;
     let url = format!("{chatgpt_base_url}{path}");
 
     let token =
@@ -0,0 +1,185 @@
+//! `codex audit`: the logins, token refreshes, credential switches, limit
+//! hits, commands, and redactions recorded in `~/.codex/audit.log`, oldest
+//! first.
+
+use anyhow::Result;
+use chrono::DateTime;
+use chrono::Local;
+use chrono::NaiveDate;
+use chrono::TimeZone;
+use chrono::Utc;
+use codex_core::audit_log::AuditEntry;
+use codex_core::audit_log::read_audit_log;
+use codex_core::config::Config;
+use codex_core::state_dir::state_dir;
+
+/// Event names accepted by `--event`, as written to the log.
+pub(crate) const EVENT_NAMES: [&str; 7] = [
+    "login",
+    "logout",
+    "token_refresh",
+    "auth_switch",
+    "limit_hit",
+    "command",
+    "redaction",
+];
+
+/// Which recorded events to show.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct AuditFilter {
+    /// Event names to keep; empty keeps all of them.
+    pub(crate) events: Vec<String>,
+    /// Only events whose credentials contain this text.
+    pub(crate) auth: Option<String>,
+    /// First local day to include.
+    pub(crate) since: Option<NaiveDate>,
+    /// Last local day to include.
+    pub(crate) until: Option<NaiveDate>,
+}
+
+pub(crate) fn run(config: &Config, filter: &AuditFilter, json: bool) -> Result<()> {
+    let entries = read_audit_log(&state_dir(&config.codex_home))?;
+    let entries = select(&entries, filter, &Local);
+    if json {
+        for entry in entries {
+            println!("{}", serde_json::to_string(entry)?);
+        }
+    } else if entries.is_empty() {
+        println!("No audit events recorded for this period.");
+    } else {
+        for entry in entries {
+            println!("{}", format_entry(entry, &Local));
+        }
+    }
+    Ok(())
+}
+
+fn select<'a, Tz: TimeZone>(
+    entries: &'a [AuditEntry],
+    filter: &AuditFilter,
+    tz: &Tz,
+) -> Vec<&'a AuditEntry> {
+    entries
+        .iter()
+        .filter(|entry| {
+            let date = DateTime::<Utc>::from_timestamp(entry.ts, 0)
+                .map(|at| at.with_timezone(tz).date_naive());
+            filter.since.is_none_or(|since| date >= Some(since))
+                && filter.until.is_none_or(|until| date <= Some(until))
+                && (filter.events.is_empty()
+                    || filter
+                        .events
+                        .iter()
+                        .any(|event| event == entry.event.name()))
+                && filter
+                    .auth
+                    .as_deref()
+                    .is_none_or(|auth| entry.auth.contains(auth))
+        })
+        .collect()
+}
+
+/// One line per event, e.g.
+/// `2024-03-01 12:00:00  command        me@example.com  cargo test  (agent, in /src/app)`.
+fn format_entry<Tz: TimeZone>(entry: &AuditEntry, tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let when = DateTime::<Utc>::from_timestamp(entry.ts, 0)
+        .map(|at| at.with_timezone(tz).format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| entry.ts.to_string());
+    let auth = if entry.auth.is_empty() {
+        "-"
+    } else {
+        entry.auth.as_str()
+    };
+    let mut line = format!("{when}  {:<13}  {auth}", entry.event.name());
+    if let Some(detail) = &entry.detail {
+        line.push_str("  ");
+        line.push_str(detail);
+    }
+    let context: Vec<String> = entry
+        .reason
+        .iter()
+        .cloned()
+        .chain(entry.cwd.iter().map(|cwd| format!("in {}", cwd.display())))
+        .collect();
+    if !context.is_empty() {
+        line.push_str(&format!("  ({})", context.join(", ")));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+    use codex_core::audit_log::AuditEvent;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn sample() -> (FixedOffset, Vec<AuditEntry>) {
+        let tz = FixedOffset::east_opt(0).expect("offset");
+        let at = |day, hour| {
+            tz.with_ymd_and_hms(2024, 3, day, hour, 0, 0)
+                .single()
+                .expect("time")
+                .timestamp()
+        };
+        let entries = vec![
+            AuditEntry {
+                ts: at(1, 9),
+                ..AuditEntry::now(AuditEvent::Login, "me@example.com")
+            }
+            .detail("ChatGPT"),
+            AuditEntry {
+                ts: at(2, 10),
+                ..AuditEntry::now(AuditEvent::AuthSwitch, "API key ending in 1234")
+            }
+            .reason("usage limit reached")
+            .detail("from me@example.com"),
+            AuditEntry {
+                ts: at(2, 11),
+                cwd: Some(PathBuf::from("/src/app")),
+                ..AuditEntry::now(AuditEvent::Command, "API key ending in 1234")
+            }
+            .reason("agent")
+            .detail("cargo test"),
+        ];
+        (tz, entries)
+    }
+
+    #[test]
+    fn events_are_filtered_by_day_kind_and_credentials() {
+        let (tz, entries) = sample();
+        let filter = AuditFilter {
+            since: NaiveDate::from_ymd_opt(2024, 3, 2),
+            ..Default::default()
+        };
+        assert_eq!(select(&entries, &filter, &tz).len(), 2);
+
+        let filter = AuditFilter {
+            events: vec!["command".to_string(), "login".to_string()],
+            auth: Some("1234".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(select(&entries, &filter, &tz), vec![&entries[2]]);
+    }
+
+    #[test]
+    fn entries_are_shown_one_per_line() {
+        let (tz, entries) = sample();
+        let lines: Vec<String> = entries
+            .iter()
+            .map(|entry| format_entry(entry, &tz))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "2024-03-01 09:00:00  login          me@example.com  ChatGPT",
+                "2024-03-02 10:00:00  auth_switch    API key ending in 1234  from me@example.com  (usage limit reached)",
+                "2024-03-02 11:00:00  command        API key ending in 1234  cargo test  (agent, in /src/app)",
+            ]
+        );
+    }
+}
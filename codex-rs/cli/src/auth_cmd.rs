@@ -0,0 +1,341 @@
+//! `codex auth status`: which credentials Codex would use and why, for
+//! working out why a session picked up the "wrong" account or key. Only
+//! local state is read; tokens are not refreshed and nothing is sent.
+
+use anyhow::Result;
+use chrono::DateTime;
+use chrono::Utc;
+use codex_app_server_protocol::AuthMode;
+use codex_core::CodexAuth;
+use codex_core::auth::AuthCredentialsStoreMode;
+use codex_core::auth::CODEX_API_KEY_ENV_VAR;
+use codex_core::auth::OPENAI_API_KEY_ENV_VAR;
+use codex_core::auth::OPENAI_API_KEYS_ENV_VAR;
+use codex_core::config::Config;
+use codex_core::limit_history::LimitHitEntry;
+use codex_core::limit_history::read_limit_history;
+use codex_core::rate_limits::format_countdown;
+use codex_core::rate_limits::unix_now;
+use codex_core::state_dir::state_dir;
+use serde_json::json;
+
+struct AuthStatusReport {
+    store: AuthCredentialsStoreMode,
+    stored: Option<StoredAuth>,
+    provider: ProviderCredentials,
+    env: EnvCredentials,
+    fallback: Vec<String>,
+    /// Recorded limit hits that have not reset yet, oldest first.
+    active_limits: Vec<LimitHitEntry>,
+    now: i64,
+}
+
+/// The credentials in `auth.json` or the OS keyring.
+struct StoredAuth {
+    mode: AuthMode,
+    identity: String,
+    account_id: Option<String>,
+    plan: Option<String>,
+    access_token_expires_at: Option<i64>,
+    last_refresh: Option<i64>,
+}
+
+/// How the configured provider authenticates.
+struct ProviderCredentials {
+    id: String,
+    /// Environment variable the provider reads its key from, if any, and
+    /// whether it is set.
+    env_key: Option<(String, bool)>,
+    requires_openai_auth: bool,
+}
+
+struct EnvCredentials {
+    openai_api_key: bool,
+    openai_api_keys: bool,
+    codex_api_key: bool,
+}
+
+pub(crate) fn run_status(config: &Config, json_output: bool) -> Result<()> {
+    let auth =
+        CodexAuth::from_auth_storage(&config.codex_home, config.cli_auth_credentials_store_mode)?;
+    let now = unix_now();
+    let active_limits = read_limit_history(
+        &state_dir(&config.codex_home),
+        config.shared_state_dir.as_deref(),
+    )?
+    .into_iter()
+    .filter(|hit| hit.resets_at.is_some_and(|at| at > now))
+    .collect();
+    let provider = &config.model_provider;
+    let report = AuthStatusReport {
+        store: config.cli_auth_credentials_store_mode,
+        stored: auth.as_ref().map(stored_auth),
+        provider: ProviderCredentials {
+            id: config.model_provider_id.clone(),
+            env_key: provider.env_key.clone().map(|key| {
+                let set = env_is_set(&key);
+                (key, set)
+            }),
+            requires_openai_auth: provider.requires_openai_auth,
+        },
+        env: EnvCredentials {
+            openai_api_key: env_is_set(OPENAI_API_KEY_ENV_VAR),
+            openai_api_keys: env_is_set(OPENAI_API_KEYS_ENV_VAR),
+            codex_api_key: env_is_set(CODEX_API_KEY_ENV_VAR),
+        },
+        fallback: config
+            .fallback
+            .iter()
+            .map(|entry| entry.name.clone())
+            .collect(),
+        active_limits,
+        now,
+    };
+
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&report.to_json())?);
+    } else {
+        print!("{}", report.render());
+    }
+    Ok(())
+}
+
+fn stored_auth(auth: &CodexAuth) -> StoredAuth {
+    StoredAuth {
+        mode: auth.mode,
+        identity: auth.identity_label(),
+        account_id: auth.get_account_id(),
+        plan: auth.raw_plan_type(),
+        access_token_expires_at: auth.access_token_expires_at().map(|at| at.timestamp()),
+        last_refresh: auth.last_refresh().map(|at| at.timestamp()),
+    }
+}
+
+impl AuthStatusReport {
+    fn to_json(&self) -> serde_json::Value {
+        let stored = self.stored.as_ref().map(|stored| {
+            json!({
+                "auth_mode": auth_mode_str(stored.mode),
+                "identity": stored.identity,
+                "account_id": stored.account_id,
+                "plan": stored.plan,
+                "access_token_expires_at": stored.access_token_expires_at,
+                "access_token_expires_in_seconds": stored
+                    .access_token_expires_at
+                    .map(|at| at - self.now),
+                "last_refresh": stored.last_refresh,
+            })
+        });
+        let active_limits: Vec<_> = self
+            .active_limits
+            .iter()
+            .map(|hit| {
+                json!({
+                    "auth": hit.auth,
+                    "model": hit.model,
+                    "hit_at": hit.ts,
+                    "resets_at": hit.resets_at,
+                })
+            })
+            .collect();
+        json!({
+            "credentials_store": store_str(self.store),
+            "stored": stored,
+            "provider": {
+                "id": self.provider.id,
+                "env_key": self.provider.env_key.as_ref().map(|(key, _)| key),
+                "env_key_set": self.provider.env_key.as_ref().map(|(_, set)| set),
+                "requires_openai_auth": self.provider.requires_openai_auth,
+            },
+            "env": {
+                OPENAI_API_KEY_ENV_VAR: self.env.openai_api_key,
+                OPENAI_API_KEYS_ENV_VAR: self.env.openai_api_keys,
+                CODEX_API_KEY_ENV_VAR: self.env.codex_api_key,
+            },
+            "fallback": self.fallback,
+            "active_limits": active_limits,
+        })
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::new();
+        match &self.stored {
+            Some(stored) => {
+                out.push_str(&format!(
+                    "Stored credentials: {} ({})\n",
+                    auth_mode_label(stored.mode),
+                    stored.identity
+                ));
+                if let Some(account_id) = &stored.account_id {
+                    out.push_str(&format!("Account: {account_id}\n"));
+                }
+                if let Some(plan) = &stored.plan {
+                    out.push_str(&format!("Plan: {plan}\n"));
+                }
+                if let Some(at) = stored.access_token_expires_at {
+                    let phrase = if at > self.now {
+                        format!("expires in {}", format_countdown(at - self.now))
+                    } else {
+                        "expired; it is refreshed on next use".to_string()
+                    };
+                    out.push_str(&format!("Access token: {phrase}\n"));
+                }
+                if let Some(at) = stored.last_refresh {
+                    out.push_str(&format!("Last refreshed: {}\n", format_timestamp(at)));
+                }
+            }
+            None => out.push_str("Stored credentials: none (run `codex login`)\n"),
+        }
+        out.push_str(&format!("Credentials store: {}\n", store_str(self.store)));
+
+        let provider = &self.provider;
+        let source = match &provider.env_key {
+            Some((key, true)) => format!("{key} from the environment"),
+            Some((key, false)) => format!("{key} from the environment (not set)"),
+            None if provider.requires_openai_auth => "stored credentials".to_string(),
+            None => "none".to_string(),
+        };
+        out.push_str(&format!(
+            "Provider: {} (authenticates with {source})\n",
+            provider.id
+        ));
+
+        let set = |is_set: bool| if is_set { "set" } else { "not set" };
+        out.push_str(&format!(
+            "{OPENAI_API_KEY_ENV_VAR}: {}\n",
+            set(self.env.openai_api_key)
+        ));
+        out.push_str(&format!(
+            "{OPENAI_API_KEYS_ENV_VAR}: {}\n",
+            set(self.env.openai_api_keys)
+        ));
+        out.push_str(&format!(
+            "{CODEX_API_KEY_ENV_VAR}: {}{}\n",
+            set(self.env.codex_api_key),
+            if self.env.codex_api_key {
+                " (`codex exec` uses it instead of the stored credentials)"
+            } else {
+                ""
+            }
+        ));
+
+        if !self.fallback.is_empty() {
+            out.push_str(&format!("Fallback chain: {}\n", self.fallback.join(" -> ")));
+        }
+        if self.active_limits.is_empty() {
+            out.push_str("Active limits: none\n");
+        } else {
+            out.push_str("Active limits:\n");
+            for hit in &self.active_limits {
+                let resets = hit
+                    .resets_at
+                    .map(|at| format!("resets in {}", format_countdown(at - self.now)))
+                    .unwrap_or_default();
+                out.push_str(&format!("  {}  {}  {resets}\n", hit.auth, hit.model));
+            }
+        }
+        out
+    }
+}
+
+fn env_is_set(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|value| !value.trim().is_empty())
+}
+
+fn auth_mode_str(mode: AuthMode) -> &'static str {
+    match mode {
+        AuthMode::ApiKey => "api_key",
+        AuthMode::ChatGPT => "chatgpt",
+    }
+}
+
+fn auth_mode_label(mode: AuthMode) -> &'static str {
+    match mode {
+        AuthMode::ApiKey => "API key",
+        AuthMode::ChatGPT => "ChatGPT",
+    }
+}
+
+fn store_str(store: AuthCredentialsStoreMode) -> &'static str {
+    match store {
+        AuthCredentialsStoreMode::File => "file",
+        AuthCredentialsStoreMode::Keyring => "keyring",
+        AuthCredentialsStoreMode::Auto => "auto",
+    }
+}
+
+fn format_timestamp(seconds: i64) -> String {
+    DateTime::<Utc>::from_timestamp(seconds, 0)
+        .map(|at| at.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| "?".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn report() -> AuthStatusReport {
+        AuthStatusReport {
+            store: AuthCredentialsStoreMode::File,
+            stored: Some(StoredAuth {
+                mode: AuthMode::ChatGPT,
+                identity: "me@example.com".to_string(),
+                account_id: Some("acct_123".to_string()),
+                plan: Some("Pro".to_string()),
+                access_token_expires_at: Some(NOW + 45 * 60),
+                last_refresh: Some(NOW - 3600),
+            }),
+            provider: ProviderCredentials {
+                id: "openai".to_string(),
+                env_key: None,
+                requires_openai_auth: true,
+            },
+            env: EnvCredentials {
+                openai_api_key: true,
+                openai_api_keys: false,
+                codex_api_key: false,
+            },
+            fallback: vec!["chatgpt".to_string(), "openai_api".to_string()],
+            active_limits: vec![LimitHitEntry {
+                ts: NOW - 600,
+                auth: "chatgpt".to_string(),
+                model: "gpt-5.1-codex".to_string(),
+                window_minutes: None,
+                resets_at: Some(NOW + 2 * 3600),
+            }],
+            now: NOW,
+        }
+    }
+
+    #[test]
+    fn render_explains_where_credentials_come_from() {
+        assert_eq!(
+            report().render(),
+            "Stored credentials: ChatGPT (me@example.com)\n\
+             Account: acct_123\n\
+             Plan: Pro\n\
+             Access token: expires in 45m\n\
+             Last refreshed: 2023-11-14 21:13 UTC\n\
+             Credentials store: file\n\
+             Provider: openai (authenticates with stored credentials)\n\
+             OPENAI_API_KEY: set\n\
+             OPENAI_API_KEYS: not set\n\
+             CODEX_API_KEY: not set\n\
+             Fallback chain: chatgpt -> openai_api\n\
+             Active limits:\n  chatgpt  gpt-5.1-codex  resets in 2h 0m\n"
+        );
+    }
+
+    #[test]
+    fn json_reports_expiry_and_environment() {
+        let value = report().to_json();
+        assert_eq!(value["stored"]["auth_mode"], "chatgpt");
+        assert_eq!(value["stored"]["access_token_expires_in_seconds"], 45 * 60);
+        assert_eq!(value["env"]["OPENAI_API_KEY"], true);
+        assert_eq!(value["provider"]["env_key"], serde_json::Value::Null);
+        assert_eq!(value["active_limits"][0]["resets_at"], NOW + 2 * 3600);
+    }
+}
@@ -0,0 +1,207 @@
+//! `codex completion --install/--uninstall`: write completion scripts to a
+//! location the shell loads automatically, editing the shell rc file only
+//! when the shell has no such location.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use clap_complete::Shell;
+use codex_core::config::find_codex_home;
+
+const BLOCK_START: &str = "# >>> codex completions >>>";
+const BLOCK_END: &str = "# <<< codex completions <<<";
+
+/// Where the completion script lives and, if needed, which rc file sources it.
+#[derive(Debug, PartialEq, Eq)]
+struct InstallPlan {
+    script_path: PathBuf,
+    rc_file: Option<(PathBuf, String)>,
+}
+
+pub(crate) fn detect_shell() -> Result<Shell> {
+    Shell::from_env().context("could not detect your shell from $SHELL; pass it explicitly")
+}
+
+pub(crate) fn install(shell: Shell, script: &[u8]) -> Result<()> {
+    let plan = install_plan(shell)?;
+    if let Some(parent) = plan.script_path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    std::fs::write(&plan.script_path, script)
+        .with_context(|| format!("failed to write {}", plan.script_path.display()))?;
+    println!(
+        "Wrote {shell} completions to {}",
+        plan.script_path.display()
+    );
+
+    if let Some((rc_file, block)) = plan.rc_file {
+        let contents = read_optional(&rc_file)?;
+        let updated = upsert_block(&contents, &block);
+        if updated != contents {
+            std::fs::write(&rc_file, updated)
+                .with_context(|| format!("failed to update {}", rc_file.display()))?;
+            println!("Updated {}", rc_file.display());
+        }
+    }
+    println!("Restart your shell to enable completions.");
+    Ok(())
+}
+
+pub(crate) fn uninstall(shell: Shell) -> Result<()> {
+    let plan = install_plan(shell)?;
+    match std::fs::remove_file(&plan.script_path) {
+        Ok(()) => println!("Removed {}", plan.script_path.display()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => {
+            return Err(err)
+                .with_context(|| format!("failed to remove {}", plan.script_path.display()));
+        }
+    }
+
+    if let Some((rc_file, _)) = plan.rc_file {
+        let contents = read_optional(&rc_file)?;
+        let updated = remove_block(&contents);
+        if updated != contents {
+            std::fs::write(&rc_file, updated)
+                .with_context(|| format!("failed to update {}", rc_file.display()))?;
+            println!("Updated {}", rc_file.display());
+        }
+    }
+    Ok(())
+}
+
+fn install_plan(shell: Shell) -> Result<InstallPlan> {
+    let home = std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .context("HOME is not set")?;
+    let completions_dir = find_codex_home()
+        .context("failed to locate the Codex home directory")?
+        .join("completions");
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".config"));
+
+    let plan = match shell {
+        Shell::Bash => {
+            let script_path = completions_dir.join("codex.bash");
+            let block = source_block(&format!("source \"{}\"", script_path.display()));
+            InstallPlan {
+                script_path,
+                rc_file: Some((home.join(".bashrc"), block)),
+            }
+        }
+        Shell::Zsh => {
+            let script_path = completions_dir.join("_codex");
+            let block = source_block(&format!(
+                "autoload -Uz compinit\n(( $+functions[compdef] )) || compinit\nsource \"{}\"",
+                script_path.display()
+            ));
+            let zdotdir = std::env::var_os("ZDOTDIR")
+                .map(PathBuf::from)
+                .unwrap_or(home);
+            InstallPlan {
+                script_path,
+                rc_file: Some((zdotdir.join(".zshrc"), block)),
+            }
+        }
+        // fish loads everything in its completions directory on demand.
+        Shell::Fish => InstallPlan {
+            script_path: config_home
+                .join("fish")
+                .join("completions")
+                .join("codex.fish"),
+            rc_file: None,
+        },
+        other => bail!(
+            "installing completions for {other} is not supported; run `codex completion {other}` and add the output to your shell profile"
+        ),
+    };
+    Ok(plan)
+}
+
+fn source_block(body: &str) -> String {
+    format!("{BLOCK_START}\n{body}\n{BLOCK_END}\n")
+}
+
+fn read_optional(path: &Path) -> Result<String> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+    }
+}
+
+/// Replace an existing managed block, or append one, leaving the rest of the file untouched.
+fn upsert_block(contents: &str, block: &str) -> String {
+    if let Some((before, after)) = split_block(contents) {
+        return format!("{before}{block}{after}");
+    }
+    let mut updated = contents.to_string();
+    if !updated.is_empty() && !updated.ends_with('\n') {
+        updated.push('\n');
+    }
+    updated.push_str(block);
+    updated
+}
+
+fn remove_block(contents: &str) -> String {
+    match split_block(contents) {
+        Some((before, after)) => format!("{before}{after}"),
+        None => contents.to_string(),
+    }
+}
+
+/// Returns the text before and after the managed block (including its trailing newline).
+fn split_block(contents: &str) -> Option<(&str, &str)> {
+    let start = contents.find(BLOCK_START)?;
+    let end_marker = start + contents[start..].find(BLOCK_END)?;
+    let mut end = end_marker + BLOCK_END.len();
+    if contents[end..].starts_with('\n') {
+        end += 1;
+    }
+    Some((&contents[..start], &contents[end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn upsert_block_appends_once() {
+        let block = source_block("source \"/tmp/codex.bash\"");
+        let first = upsert_block("export PATH=/bin", &block);
+        assert_eq!(
+            first,
+            format!("export PATH=/bin\n{block}"),
+            "block is appended on a new line"
+        );
+        assert_eq!(upsert_block(&first, &block), first);
+    }
+
+    #[test]
+    fn upsert_block_replaces_existing_block_in_place() {
+        let old = source_block("source \"/old/codex.bash\"");
+        let new = source_block("source \"/new/codex.bash\"");
+        let contents = format!("alias ll='ls -l'\n{old}export EDITOR=vim\n");
+        assert_eq!(
+            upsert_block(&contents, &new),
+            format!("alias ll='ls -l'\n{new}export EDITOR=vim\n")
+        );
+    }
+
+    #[test]
+    fn remove_block_leaves_other_lines() {
+        let block = source_block("source \"/tmp/codex.bash\"");
+        let contents = format!("alias ll='ls -l'\n{block}export EDITOR=vim\n");
+        assert_eq!(
+            remove_block(&contents),
+            "alias ll='ls -l'\nexport EDITOR=vim\n"
+        );
+        assert_eq!(remove_block("no block here\n"), "no block here\n");
+    }
+}
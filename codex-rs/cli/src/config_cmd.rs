@@ -0,0 +1,233 @@
+//! `codex config`: locate and safely edit `CODEX_HOME/config.toml`.
+
+use std::io::BufRead;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use clap::Parser;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::config::ConfigToml;
+use codex_core::config::find_codex_home;
+
+#[derive(Debug, Parser)]
+pub struct ConfigCli {
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    sub: ConfigSubcommand,
+}
+
+#[derive(Debug, Parser)]
+enum ConfigSubcommand {
+    /// Open config.toml in $VISUAL or $EDITOR and validate it before saving.
+    Edit {
+        /// Edit common settings in a form instead of the raw file.
+        #[arg(long)]
+        tui: bool,
+    },
+
+    /// Print the path of config.toml.
+    Path,
+
+    /// Print the system prompt a session would start with, after
+    /// `system_prompt`, the profile, and `--system-prompt-file` are applied.
+    SystemPrompt,
+}
+
+/// `overrides` carries the root flags, such as `--profile`, that affect the
+/// loaded config.
+pub async fn run(cli: ConfigCli, overrides: ConfigOverrides) -> Result<()> {
+    let config_path = find_codex_home()?.join("config.toml");
+    match cli.sub {
+        ConfigSubcommand::Path => {
+            println!("{}", config_path.display());
+            Ok(())
+        }
+        ConfigSubcommand::Edit { tui: false } => edit(&config_path),
+        ConfigSubcommand::Edit { tui: true } => edit_with_form(&config_path),
+        ConfigSubcommand::SystemPrompt => {
+            let cli_kv_overrides = cli
+                .config_overrides
+                .parse_overrides()
+                .map_err(anyhow::Error::msg)?;
+            let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides).await?;
+            println!("{}", config.effective_base_instructions());
+            Ok(())
+        }
+    }
+}
+
+/// Edits a scratch copy so the real file is only replaced with a config that
+/// parses, and only if nobody else changed it while the editor was open.
+fn edit(config_path: &Path) -> Result<()> {
+    if let Some(parent) = config_path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let original = read_optional(config_path)?;
+    let scratch_path = scratch_path(config_path);
+    if scratch_path.exists() {
+        // Left behind by a session that was invalid, conflicted, or killed.
+        eprintln!(
+            "{} holds edits from an earlier session.",
+            scratch_path.display()
+        );
+        if !confirm("Resume editing it?")? {
+            bail!(
+                "remove {} to start over from config.toml",
+                scratch_path.display()
+            );
+        }
+    } else {
+        std::fs::write(&scratch_path, &original)
+            .with_context(|| format!("failed to write {}", scratch_path.display()))?;
+    }
+
+    loop {
+        open_editor(&scratch_path)?;
+        let edited = std::fs::read_to_string(&scratch_path)
+            .with_context(|| format!("failed to read {}", scratch_path.display()))?;
+
+        if let Err(err) = validate(&edited) {
+            eprintln!("config.toml is invalid: {err}");
+            if confirm("Re-open the editor?")? {
+                continue;
+            }
+            bail!(
+                "config.toml was not changed; your edits are in {}",
+                scratch_path.display()
+            );
+        }
+
+        if edited == original {
+            std::fs::remove_file(&scratch_path).ok();
+            println!("No changes.");
+            return Ok(());
+        }
+        if read_optional(config_path)? != original {
+            bail!(
+                "{} changed while you were editing; your edits are in {}",
+                config_path.display(),
+                scratch_path.display()
+            );
+        }
+
+        std::fs::rename(&scratch_path, config_path)
+            .with_context(|| format!("failed to replace {}", config_path.display()))?;
+        println!("Saved {}", config_path.display());
+        return Ok(());
+    }
+}
+
+/// The form validates the result itself; this only guards the write.
+fn edit_with_form(config_path: &Path) -> Result<()> {
+    let original = read_optional(config_path)?;
+    let Some(updated) = codex_tui::config_editor::run_config_editor(&original)? else {
+        println!("No changes.");
+        return Ok(());
+    };
+    if read_optional(config_path)? != original {
+        bail!(
+            "{} changed while the editor was open; nothing was saved",
+            config_path.display()
+        );
+    }
+
+    let parent = config_path
+        .parent()
+        .context("config.toml has no parent directory")?;
+    std::fs::create_dir_all(parent)
+        .with_context(|| format!("failed to create {}", parent.display()))?;
+    let tmp = tempfile::NamedTempFile::new_in(parent)?;
+    std::fs::write(tmp.path(), updated)
+        .with_context(|| format!("failed to write {}", tmp.path().display()))?;
+    tmp.persist(config_path)
+        .with_context(|| format!("failed to replace {}", config_path.display()))?;
+    println!("Saved {}", config_path.display());
+    Ok(())
+}
+
+fn validate(contents: &str) -> Result<(), toml::de::Error> {
+    toml::from_str::<ConfigToml>(contents).map(|_| ())
+}
+
+fn scratch_path(config_path: &Path) -> PathBuf {
+    let mut name = config_path
+        .file_name()
+        .map(std::ffi::OsStr::to_os_string)
+        .unwrap_or_default();
+    name.push(".edit");
+    config_path.with_file_name(name)
+}
+
+fn read_optional(path: &Path) -> Result<String> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+    }
+}
+
+fn open_editor(path: &Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| default_editor().to_string());
+    // Allow values such as `code --wait`.
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().context("editor command is empty")?;
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .with_context(|| format!("failed to launch editor `{editor}`"))?;
+    if !status.success() {
+        bail!("editor `{editor}` exited with {status}");
+    }
+    Ok(())
+}
+
+fn default_editor() -> &'static str {
+    if cfg!(windows) { "notepad" } else { "vi" }
+}
+
+fn confirm(prompt: &str) -> Result<bool> {
+    eprint!("{prompt} [Y/n] ");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    if std::io::stdin().lock().read_line(&mut answer)? == 0 {
+        // stdin is closed; do not loop forever.
+        return Ok(false);
+    }
+    let answer = answer.trim();
+    Ok(answer.is_empty() || answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn validate_rejects_unknown_enum_values() {
+        assert!(validate("model = \"gpt-5.1\"\n").is_ok());
+        assert!(validate("approval_policy = \"sometimes\"\n").is_err());
+        assert!(validate("model = \n").is_err());
+    }
+
+    #[test]
+    fn scratch_path_sits_next_to_config() {
+        assert_eq!(
+            scratch_path(Path::new("/home/me/.codex/config.toml")),
+            PathBuf::from("/home/me/.codex/config.toml.edit")
+        );
+    }
+}
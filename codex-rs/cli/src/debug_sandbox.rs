@@ -2,6 +2,7 @@
 mod pid_tracker;
 #[cfg(target_os = "macos")]
 mod seatbelt;
+mod selftest;
 
 use std::path::PathBuf;
 
@@ -22,6 +23,8 @@ use crate::exit_status::handle_exit_status;
 
 #[cfg(target_os = "macos")]
 use seatbelt::DenialLogger;
+pub use selftest::run_sandbox_probe;
+pub use selftest::run_sandbox_selftest;
 
 #[cfg(target_os = "macos")]
 pub async fn run_command_under_seatbelt(
@@ -0,0 +1,486 @@
+//! `codex sandbox selftest`: re-invoke this binary under the platform sandbox
+//! with a set of probe actions (write outside the writable roots, open a
+//! socket, ...) and report whether each one was blocked as expected.
+
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Output;
+use std::time::Duration;
+
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::exec_env::create_env;
+use codex_core::protocol::SandboxPolicy;
+use serde_json::json;
+
+use super::SandboxType;
+use crate::ProbeAction;
+use crate::SandboxProbeCommand;
+use crate::SandboxSelftestCommand;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    Allowed,
+    Denied,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Pass,
+    Fail,
+    /// The action also fails outside the sandbox, so the result says nothing
+    /// about the sandbox.
+    Skip,
+}
+
+impl Outcome {
+    fn label(self) -> &'static str {
+        match self {
+            Outcome::Pass => "PASS",
+            Outcome::Fail => "FAIL",
+            Outcome::Skip => "SKIP",
+        }
+    }
+}
+
+struct Probe {
+    name: &'static str,
+    policy: SandboxPolicy,
+    action: ProbeAction,
+    expect: Expect,
+}
+
+struct ProbeResult {
+    name: &'static str,
+    expect: Expect,
+    outcome: Outcome,
+    detail: String,
+}
+
+/// Runs one probe action in this process and exits 0 if it succeeded.
+pub fn run_sandbox_probe(command: SandboxProbeCommand) -> ! {
+    let result = match command.action {
+        ProbeAction::Write { path } => std::fs::write(&path, b"codex sandbox selftest\n")
+            .map_err(|err| format!("write {}: {err}", path.display())),
+        ProbeAction::Connect { addr } => addr
+            .parse()
+            .map_err(|err| format!("invalid address {addr}: {err}"))
+            .and_then(|addr| {
+                TcpStream::connect_timeout(&addr, Duration::from_secs(2))
+                    .map(drop)
+                    .map_err(|err| format!("connect {addr}: {err}"))
+            }),
+        ProbeAction::NoNewPrivs => match std::fs::read_to_string("/proc/self/status")
+            .ok()
+            .as_deref()
+            .and_then(no_new_privs_set)
+        {
+            Some(false) => Ok(()),
+            Some(true) => Err("no_new_privs is set; setuid binaries cannot gain privileges".into()),
+            None => Err("could not read NoNewPrivs from /proc/self/status".into()),
+        },
+    };
+    match result {
+        Ok(()) => std::process::exit(0),
+        Err(message) => {
+            eprintln!("{message}");
+            std::process::exit(1);
+        }
+    }
+}
+
+pub async fn run_sandbox_selftest(
+    command: SandboxSelftestCommand,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let SandboxSelftestCommand {
+        json,
+        config_overrides,
+    } = command;
+    let sandbox_type = current_sandbox_type()?;
+    let config = Config::load_with_cli_overrides(
+        config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?,
+        ConfigOverrides {
+            codex_linux_sandbox_exe,
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    let scratch =
+        std::env::temp_dir().join(format!("codex-sandbox-selftest-{}", std::process::id()));
+    let workspace = scratch.join("workspace");
+    let outside = scratch.join("outside");
+    std::fs::create_dir_all(workspace.join(".git"))?;
+    std::fs::create_dir_all(&outside)?;
+    // Any port works: the probe only needs a local peer that would accept.
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+
+    let probe_exe = std::env::current_exe()?;
+    let mut env = create_env(&config.shell_environment_policy);
+    // Keep scratch files (e.g. arg0's PATH aliases) inside the workspace.
+    env.insert(
+        "TMPDIR".to_string(),
+        workspace.to_string_lossy().to_string(),
+    );
+    let runner = ProbeRunner {
+        sandbox_type,
+        probe_exe,
+        codex_linux_sandbox_exe: config.codex_linux_sandbox_exe.clone(),
+        workspace: workspace.clone(),
+        env,
+    };
+
+    let mut results = Vec::new();
+    for probe in probes(&workspace, &outside, &listener.local_addr()?.to_string()) {
+        results.push(runner.run(probe).await);
+    }
+    drop(listener);
+    std::fs::remove_dir_all(&scratch).ok();
+
+    let failed = results
+        .iter()
+        .filter(|result| result.outcome == Outcome::Fail)
+        .count();
+    if json {
+        let probes: Vec<_> = results
+            .iter()
+            .map(|result| {
+                json!({
+                    "name": result.name,
+                    "expected": match result.expect {
+                        Expect::Allowed => "allowed",
+                        Expect::Denied => "denied",
+                    },
+                    "outcome": result.outcome.label().to_lowercase(),
+                    "detail": result.detail,
+                })
+            })
+            .collect();
+        let report = json!({
+            "platform": std::env::consts::OS,
+            "sandbox": runner.sandbox_type.name(),
+            "passed": failed == 0,
+            "probes": probes,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!(
+            "Sandbox self-test ({}, {}, codex {})",
+            std::env::consts::OS,
+            runner.sandbox_type.name(),
+            env!("CARGO_PKG_VERSION")
+        );
+        for result in &results {
+            println!(
+                "  {}  {:<34} {}",
+                result.outcome.label(),
+                result.name,
+                result.detail
+            );
+        }
+        let ran = results
+            .iter()
+            .filter(|result| result.outcome != Outcome::Skip)
+            .count();
+        println!("{}/{ran} probes passed", ran - failed);
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{failed} sandbox probe(s) did not behave as expected");
+    }
+    Ok(())
+}
+
+fn current_sandbox_type() -> anyhow::Result<SandboxType> {
+    #[cfg(target_os = "macos")]
+    {
+        Ok(SandboxType::Seatbelt)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        Ok(SandboxType::Landlock)
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        anyhow::bail!("`codex sandbox selftest` is only available on macOS and Linux")
+    }
+}
+
+impl SandboxType {
+    fn name(&self) -> &'static str {
+        match self {
+            #[cfg(target_os = "macos")]
+            SandboxType::Seatbelt => "seatbelt",
+            SandboxType::Landlock => "landlock+seccomp",
+            SandboxType::Windows => "windows restricted token",
+        }
+    }
+}
+
+fn probes(workspace: &Path, outside: &Path, loopback_addr: &str) -> Vec<Probe> {
+    let workspace_write = SandboxPolicy::WorkspaceWrite {
+        writable_roots: Vec::new(),
+        network_access: false,
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+    };
+    let mut probes = vec![
+        Probe {
+            name: "write inside workspace",
+            policy: workspace_write.clone(),
+            action: ProbeAction::Write {
+                path: workspace.join("probe"),
+            },
+            expect: Expect::Allowed,
+        },
+        Probe {
+            name: "write outside writable roots",
+            policy: workspace_write.clone(),
+            action: ProbeAction::Write {
+                path: outside.join("probe"),
+            },
+            expect: Expect::Denied,
+        },
+        Probe {
+            name: "write to .git in workspace",
+            policy: workspace_write.clone(),
+            action: ProbeAction::Write {
+                path: workspace.join(".git").join("probe"),
+            },
+            expect: Expect::Denied,
+        },
+        Probe {
+            name: "write under read-only policy",
+            policy: SandboxPolicy::new_read_only_policy(),
+            action: ProbeAction::Write {
+                path: workspace.join("read-only-probe"),
+            },
+            expect: Expect::Denied,
+        },
+        Probe {
+            name: "network (loopback TCP connect)",
+            policy: workspace_write.clone(),
+            action: ProbeAction::Connect {
+                addr: loopback_addr.to_string(),
+            },
+            expect: Expect::Denied,
+        },
+    ];
+    if cfg!(target_os = "linux") {
+        probes.push(Probe {
+            name: "gain privileges via setuid",
+            policy: workspace_write,
+            action: ProbeAction::NoNewPrivs,
+            expect: Expect::Denied,
+        });
+    }
+    probes
+}
+
+struct ProbeRunner {
+    sandbox_type: SandboxType,
+    probe_exe: PathBuf,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+    workspace: PathBuf,
+    env: HashMap<String, String>,
+}
+
+impl ProbeRunner {
+    async fn run(&self, probe: Probe) -> ProbeResult {
+        let Probe {
+            name,
+            policy,
+            action,
+            expect,
+        } = probe;
+        let args = probe_args(&action);
+
+        // For probes that should be denied, first make sure the action works at
+        // all; otherwise a "denied" result would prove nothing.
+        let control = if expect == Expect::Denied {
+            match tokio::process::Command::new(&self.probe_exe)
+                .args(&args)
+                .env_clear()
+                .envs(&self.env)
+                .current_dir(&self.workspace)
+                .output()
+                .await
+            {
+                Ok(output) => Some(output),
+                Err(err) => {
+                    return ProbeResult {
+                        name,
+                        expect,
+                        outcome: Outcome::Fail,
+                        detail: format!("failed to run probe: {err}"),
+                    };
+                }
+            }
+        } else {
+            None
+        };
+        // Undo any side effect of the control run before the sandboxed one.
+        if let ProbeAction::Write { path } = &action {
+            std::fs::remove_file(path).ok();
+        }
+        if let Some(control) = control.filter(|output| !output.status.success()) {
+            return ProbeResult {
+                name,
+                expect,
+                outcome: Outcome::Skip,
+                detail: format!(
+                    "fails outside the sandbox too: {}",
+                    last_stderr_line(&control)
+                ),
+            };
+        }
+
+        let sandboxed = match self.run_sandboxed(args, &policy).await {
+            Ok(output) => output,
+            Err(err) => {
+                return ProbeResult {
+                    name,
+                    expect,
+                    outcome: Outcome::Fail,
+                    detail: format!("failed to start sandbox: {err}"),
+                };
+            }
+        };
+        let allowed = sandboxed.status.success();
+        let (outcome, detail) = match (expect, allowed) {
+            (Expect::Allowed, true) => (Outcome::Pass, "allowed, as expected".to_string()),
+            (Expect::Denied, false) => (Outcome::Pass, "denied, as expected".to_string()),
+            (Expect::Allowed, false) => (
+                Outcome::Fail,
+                format!(
+                    "denied (expected allowed): {}",
+                    last_stderr_line(&sandboxed)
+                ),
+            ),
+            (Expect::Denied, true) => (Outcome::Fail, "allowed (expected denied)".to_string()),
+        };
+        ProbeResult {
+            name,
+            expect,
+            outcome,
+            detail,
+        }
+    }
+
+    async fn run_sandboxed(
+        &self,
+        args: Vec<String>,
+        policy: &SandboxPolicy,
+    ) -> std::io::Result<Output> {
+        let mut command = vec![self.probe_exe.to_string_lossy().to_string()];
+        command.extend(args);
+        let child = match self.sandbox_type {
+            #[cfg(target_os = "macos")]
+            SandboxType::Seatbelt => {
+                codex_core::seatbelt::spawn_command_under_seatbelt(
+                    command,
+                    self.workspace.clone(),
+                    policy,
+                    self.workspace.as_path(),
+                    codex_core::spawn::StdioPolicy::RedirectForShellTool,
+                    self.env.clone(),
+                )
+                .await?
+            }
+            SandboxType::Landlock => {
+                let Some(codex_linux_sandbox_exe) = &self.codex_linux_sandbox_exe else {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        "codex-linux-sandbox executable not found",
+                    ));
+                };
+                codex_core::landlock::spawn_command_under_linux_sandbox(
+                    codex_linux_sandbox_exe,
+                    command,
+                    self.workspace.clone(),
+                    policy,
+                    self.workspace.as_path(),
+                    codex_core::spawn::StdioPolicy::RedirectForShellTool,
+                    self.env.clone(),
+                )
+                .await?
+            }
+            SandboxType::Windows => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    "the Windows sandbox is not covered by the self-test",
+                ));
+            }
+        };
+        child.wait_with_output().await
+    }
+}
+
+fn probe_args(action: &ProbeAction) -> Vec<String> {
+    let mut args = vec!["sandbox".to_string(), "probe".to_string()];
+    match action {
+        ProbeAction::Write { path } => {
+            args.push("write".to_string());
+            args.push(path.to_string_lossy().to_string());
+        }
+        ProbeAction::Connect { addr } => {
+            args.push("connect".to_string());
+            args.push(addr.clone());
+        }
+        ProbeAction::NoNewPrivs => args.push("no-new-privs".to_string()),
+    }
+    args
+}
+
+fn last_stderr_line(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("no error output")
+        .to_string()
+}
+
+/// Parses the `NoNewPrivs:` field of `/proc/<pid>/status`.
+fn no_new_privs_set(status: &str) -> Option<bool> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("NoNewPrivs:"))
+        .map(|value| value.trim() == "1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_no_new_privs_field() {
+        assert_eq!(
+            no_new_privs_set("Name:\tcodex\nNoNewPrivs:\t1\nSeccomp:\t2\n"),
+            Some(true)
+        );
+        assert_eq!(no_new_privs_set("NoNewPrivs:\t0\n"), Some(false));
+        assert_eq!(no_new_privs_set("Name:\tcodex\n"), None);
+    }
+
+    #[test]
+    fn probe_args_round_trip_through_the_hidden_subcommand() {
+        assert_eq!(
+            probe_args(&ProbeAction::Connect {
+                addr: "127.0.0.1:9".to_string()
+            }),
+            vec!["sandbox", "probe", "connect", "127.0.0.1:9"]
+        );
+        assert_eq!(
+            probe_args(&ProbeAction::NoNewPrivs),
+            vec!["sandbox", "probe", "no-new-privs"]
+        );
+    }
+}
@@ -0,0 +1,175 @@
+//! `codex doctor`: report the effective configuration that security reviews
+//! ask about, starting with which features may reach the network.
+
+use anyhow::Result;
+use codex_core::config::Config;
+use codex_core::config::types::OtelExporterKind;
+use serde_json::json;
+
+/// One line of the report: a feature and whether it may use the network.
+struct NetworkUse {
+    feature: &'static str,
+    enabled: bool,
+    detail: String,
+}
+
+pub(crate) fn run(config: &Config, json_output: bool) -> Result<()> {
+    let kill_switch = config.privacy.disable_all_network_except_providers;
+    let report = network_uses(config);
+    if json_output {
+        let features: Vec<_> = report
+            .iter()
+            .map(|entry| {
+                json!({
+                    "feature": entry.feature,
+                    "enabled": entry.enabled,
+                    "detail": entry.detail,
+                })
+            })
+            .collect();
+        let value = json!({
+            "privacy": {
+                "disable_all_network_except_providers": kill_switch,
+                "network": features,
+            },
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    println!("Privacy");
+    println!(
+        "  privacy.disable_all_network_except_providers = {kill_switch}{}",
+        if kill_switch {
+            " (only the model provider and configured MCP servers are contacted)"
+        } else {
+            ""
+        }
+    );
+    for entry in &report {
+        println!(
+            "  {:<22} {:<4} {}",
+            entry.feature,
+            if entry.enabled { "on" } else { "off" },
+            entry.detail
+        );
+    }
+    Ok(())
+}
+
+fn network_uses(config: &Config) -> Vec<NetworkUse> {
+    let kill_switch = config.privacy.disable_all_network_except_providers;
+    let blocked_reason = |configured_off: &str| {
+        if kill_switch {
+            "blocked by the privacy kill switch".to_string()
+        } else {
+            configured_off.to_string()
+        }
+    };
+    let otel_enabled = !matches!(config.otel.exporter, OtelExporterKind::None);
+    vec![
+        NetworkUse {
+            feature: "Model provider",
+            enabled: true,
+            detail: format!(
+                "{} ({})",
+                config.model_provider_id,
+                config
+                    .model_provider
+                    .base_url
+                    .as_deref()
+                    .unwrap_or("default endpoint")
+            ),
+        },
+        NetworkUse {
+            feature: "MCP servers",
+            enabled: !config.mcp_servers.is_empty(),
+            detail: format!("{} configured", config.mcp_servers.len()),
+        },
+        NetworkUse {
+            feature: "Update checks",
+            enabled: config.check_for_update_on_startup,
+            detail: if config.check_for_update_on_startup {
+                "GitHub releases / Homebrew on startup".to_string()
+            } else {
+                blocked_reason("check_for_update_on_startup = false")
+            },
+        },
+        NetworkUse {
+            feature: "Telemetry export",
+            enabled: otel_enabled,
+            detail: if otel_enabled {
+                "[otel] exporter is configured".to_string()
+            } else {
+                blocked_reason("no [otel] exporter")
+            },
+        },
+        NetworkUse {
+            feature: "Feedback upload",
+            enabled: !kill_switch,
+            detail: if kill_switch {
+                blocked_reason("")
+            } else {
+                "only when you send /feedback".to_string()
+            },
+        },
+        NetworkUse {
+            feature: "Web search tool",
+            enabled: config.tools_web_search_request,
+            detail: if config.tools_web_search_request {
+                "model may search the web".to_string()
+            } else {
+                blocked_reason("features.web_search_request is off")
+            },
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::config::ConfigOverrides;
+    use codex_core::config::ConfigToml;
+    use pretty_assertions::assert_eq;
+
+    fn load(toml: &str) -> Config {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let cfg: ConfigToml = toml::from_str(toml).expect("valid config");
+        Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )
+        .expect("load config")
+    }
+
+    #[test]
+    fn kill_switch_turns_off_every_non_provider_feature() {
+        let config = load(
+            r#"
+check_for_update_on_startup = true
+
+[features]
+web_search_request = true
+
+[privacy]
+disable_all_network_except_providers = true
+"#,
+        );
+        let enabled: Vec<_> = network_uses(&config)
+            .into_iter()
+            .filter(|entry| entry.enabled)
+            .map(|entry| entry.feature)
+            .collect();
+        assert_eq!(enabled, vec!["Model provider"]);
+
+        // Diagnosing a config with the switch on does not change the report
+        // for one without it.
+        let config = load("");
+        let feedback = network_uses(&config)
+            .into_iter()
+            .find(|entry| entry.feature == "Feedback upload")
+            .expect("feedback entry");
+        assert!(feedback.enabled);
+    }
+}
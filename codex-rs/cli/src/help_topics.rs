@@ -0,0 +1,605 @@
+//! `codex help [COMMAND...|TOPIC]`: command help plus short concept pages.
+//!
+//! The clap definitions plus [`COMMAND_NOTES`] are the single source for
+//! `--help`, the manpages (`codex help --man <DIR>`), and the Markdown CLI
+//! reference (`codex help --markdown`).
+
+use std::fmt::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use clap::Command;
+
+/// Examples and related config keys for one command, shown at the end of its
+/// `--help`, on its manpage, and in the Markdown reference.
+struct CommandNotes {
+    /// Subcommand path below `codex`, such as `mcp add`; empty for `codex`.
+    path: &'static str,
+    /// Command lines, each with what it does.
+    examples: &'static [(&'static str, &'static str)],
+    /// config.toml keys that change how the command behaves.
+    config: &'static [&'static str],
+}
+
+const COMMAND_NOTES: &[CommandNotes] = &[
+    CommandNotes {
+        path: "",
+        examples: &[
+            (
+                "codex",
+                "Start an interactive session in the current directory",
+            ),
+            (
+                "codex \"explain this codebase\"",
+                "Start a session with a first prompt",
+            ),
+            (
+                "codex --full-auto -m gpt-5.1-codex",
+                "Work without asking, inside the workspace-write sandbox",
+            ),
+        ],
+        config: &["model", "approval_policy", "sandbox_mode", "profile"],
+    },
+    CommandNotes {
+        path: "exec",
+        examples: &[
+            (
+                "codex exec \"fix the failing test\"",
+                "Run one task to completion and print the final message",
+            ),
+            (
+                "codex exec --full-auto -o summary.md \"update the changelog\"",
+                "Let the task write files and save its final message",
+            ),
+            (
+                "codex exec --json \"list the TODOs\" > events.jsonl",
+                "Record every event as JSON Lines",
+            ),
+        ],
+        config: &["model", "sandbox_mode", "sandbox_workspace_write.*"],
+    },
+    CommandNotes {
+        path: "resume",
+        examples: &[
+            ("codex resume", "Pick a recorded session to continue"),
+            ("codex resume --last", "Continue the most recent session"),
+        ],
+        config: &["history.*"],
+    },
+    CommandNotes {
+        path: "login",
+        examples: &[
+            ("codex login", "Sign in with ChatGPT in the browser"),
+            (
+                "codex login --device-auth",
+                "Sign in from a machine without a browser",
+            ),
+            (
+                "printenv OPENAI_API_KEY | codex login --with-api-key",
+                "Store an API key read from stdin",
+            ),
+        ],
+        config: &["forced_login_method", "cli_auth_credentials_store"],
+    },
+    CommandNotes {
+        path: "mcp add",
+        examples: &[
+            (
+                "codex mcp add docs -- npx -y docs-mcp-server",
+                "Add a server Codex launches over stdio",
+            ),
+            (
+                "codex mcp add tracker --url https://mcp.example.com/mcp",
+                "Add a streamable HTTP server",
+            ),
+        ],
+        config: &["mcp_servers.<name>.*"],
+    },
+    CommandNotes {
+        path: "sandbox linux",
+        examples: &[(
+            "codex sandbox linux --full-auto -- cargo test",
+            "See what a command can do in the workspace-write sandbox",
+        )],
+        config: &["sandbox_workspace_write.*"],
+    },
+    CommandNotes {
+        path: "config edit",
+        examples: &[
+            (
+                "codex config edit",
+                "Edit config.toml in $VISUAL or $EDITOR",
+            ),
+            ("codex config edit --tui", "Edit common settings in a form"),
+        ],
+        config: &[],
+    },
+    CommandNotes {
+        path: "doctor",
+        examples: &[(
+            "codex doctor --json",
+            "Print the privacy posture for scripts",
+        )],
+        config: &["privacy.disable_all_network_except_providers"],
+    },
+];
+
+impl CommandNotes {
+    fn help_text(&self) -> String {
+        let mut text = String::from("Examples:\n");
+        for (command, description) in self.examples {
+            let _ = write!(text, "  # {description}\n  {command}\n");
+        }
+        if !self.config.is_empty() {
+            let _ = write!(text, "\nRelated config: {}", self.config.join(", "));
+        }
+        text.trim_end().to_string()
+    }
+}
+
+fn notes_for(path: &str) -> Option<&'static CommandNotes> {
+    COMMAND_NOTES.iter().find(|notes| notes.path == path)
+}
+
+/// Adds the examples and related config keys from [`COMMAND_NOTES`] to the
+/// long help (`--help`) of the commands they describe.
+pub(crate) fn annotate(mut root: Command) -> Command {
+    for notes in COMMAND_NOTES {
+        if let Some(cmd) = find_command_mut(&mut root, notes.path) {
+            *cmd = std::mem::take(cmd).after_long_help(notes.help_text());
+        }
+    }
+    root
+}
+
+fn find_command_mut<'a>(root: &'a mut Command, path: &str) -> Option<&'a mut Command> {
+    path.split_whitespace()
+        .try_fold(root, |cmd, name| cmd.find_subcommand_mut(name))
+}
+
+struct Topic {
+    name: &'static str,
+    summary: &'static str,
+    body: &'static str,
+}
+
+const TOPICS: &[Topic] = &[
+    Topic {
+        name: "sandbox",
+        summary: "What commands run by Codex may touch",
+        body: "\
+Sandbox modes limit what commands run by Codex can do without asking:
+
+  read-only           Read files anywhere; no writes, no network.
+  workspace-write     Also write inside the working directory, extra --add-dir roots,
+                      and temporary directories. Network is off unless
+                      [sandbox_workspace_write].network_access = true.
+  danger-full-access  No sandbox at all.
+
+Select a mode with --sandbox/-s or `sandbox_mode` in config.toml. Enforcement uses
+Seatbelt on macOS, Landlock + seccomp on Linux, and a restricted token on Windows.
+Try it directly with `codex sandbox macos|linux|windows -- <command>`.
+
+Related config: sandbox_mode, sandbox_workspace_write.*
+See also: codex help approvals",
+    },
+    Topic {
+        name: "approvals",
+        summary: "When Codex stops to ask before acting",
+        body: "\
+Approval policies control when Codex asks before running a command:
+
+  untrusted   Only known-safe read-only commands run without asking.
+  on-failure  Run everything in the sandbox; ask to retry outside it when a command fails.
+  on-request  The model decides when to ask (default for trusted directories).
+  never       Never ask; failures are returned to the model.
+
+Select a policy with --ask-for-approval/-a or `approval_policy` in config.toml.
+--full-auto is shorthand for `-a on-request -s workspace-write`.
+--dangerously-bypass-approvals-and-sandbox (--yolo) skips both approvals and sandboxing.
+
+Related config: approval_policy
+See also: codex help sandbox",
+    },
+    Topic {
+        name: "profiles",
+        summary: "Named bundles of config values",
+        body: "\
+A profile is a named set of config values defined under [profiles.<name>] in
+~/.codex/config.toml:
+
+  [profiles.ci]
+  model = \"gpt-5.1\"
+  approval_policy = \"never\"
+  sandbox_mode = \"read-only\"
+
+Select one with --profile/-p <name>, or set `profile = \"<name>\"` at the top level to
+make it the default. Precedence, highest first: command-line flags (including -c),
+the active profile, config.toml, built-in defaults.
+
+Related config: profile, profiles.<name>.*",
+    },
+];
+
+/// Prints help for the command path in `args` (e.g. `["mcp", "add"]`) or for a
+/// concept topic. With no arguments, prints the root help and the topic list.
+pub(crate) fn print_help(mut root: Command, args: &[String]) -> Result<()> {
+    if let [name] = args
+        && let Some(topic) = TOPICS.iter().find(|topic| topic.name == name.as_str())
+    {
+        println!("{}", topic.body);
+        return Ok(());
+    }
+
+    // Build first so subcommand usage lines carry the full `codex <sub>` path.
+    root.build();
+    let mut cmd = &mut root;
+    for name in args {
+        let Some(sub) = cmd.find_subcommand_mut(name) else {
+            bail!(
+                "no command or help topic named `{}`; topics: {}",
+                args.join(" "),
+                topic_names()
+            );
+        };
+        cmd = sub;
+    }
+    cmd.print_long_help()?;
+    if args.is_empty() {
+        println!("\nHelp topics (codex help <topic>):");
+        for topic in TOPICS {
+            println!("  {:<10}  {}", topic.name, topic.summary);
+        }
+    }
+    Ok(())
+}
+
+fn topic_names() -> String {
+    TOPICS
+        .iter()
+        .map(|topic| topic.name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Renders a Markdown reference for `root` and all visible subcommands.
+pub(crate) fn render_markdown(root: &Command) -> String {
+    let mut out = String::from("# Codex CLI reference\n");
+    render_command(&mut out, root, root.get_name(), "");
+    out.push_str("\n## Help topics\n");
+    for topic in TOPICS {
+        let _ = write!(
+            out,
+            "\n### `codex help {}`\n\n```text\n{}\n```\n",
+            topic.name, topic.body
+        );
+    }
+    out
+}
+
+fn render_command(out: &mut String, cmd: &Command, path: &str, notes_path: &str) {
+    let _ = write!(out, "\n## `{path}`\n");
+    if let Some(about) = cmd.get_long_about().or_else(|| cmd.get_about()) {
+        let _ = write!(out, "\n{about}\n");
+    }
+
+    let args: Vec<_> = cmd
+        .get_arguments()
+        .filter(|arg| !arg.is_hide_set() && arg.get_id() != "help" && arg.get_id() != "version")
+        .collect();
+    if !args.is_empty() {
+        out.push_str("\n| Argument | Description |\n| --- | --- |\n");
+        for arg in args {
+            let name = match (arg.get_long(), arg.get_short()) {
+                (Some(long), Some(short)) => format!("`-{short}`, `--{long}`"),
+                (Some(long), None) => format!("`--{long}`"),
+                (None, Some(short)) => format!("`-{short}`"),
+                (None, None) => format!("`<{}>`", arg.get_id().as_str().to_uppercase()),
+            };
+            let help = arg
+                .get_help()
+                .map(|help| help.to_string().replace('\n', " "))
+                .unwrap_or_default();
+            let _ = writeln!(out, "| {name} | {help} |");
+        }
+    }
+
+    if let Some(notes) = notes_for(notes_path) {
+        out.push_str("\nExamples:\n");
+        for (command, description) in notes.examples {
+            let _ = write!(out, "\n{description}:\n\n```shell\n{command}\n```\n");
+        }
+        if !notes.config.is_empty() {
+            let keys: Vec<_> = notes.config.iter().map(|key| format!("`{key}`")).collect();
+            let _ = write!(out, "\nRelated config: {}\n", keys.join(", "));
+        }
+    }
+
+    for sub in cmd.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+        render_command(
+            out,
+            sub,
+            &format!("{path} {}", sub.get_name()),
+            &join_path(notes_path, sub.get_name()),
+        );
+    }
+}
+
+fn join_path(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{parent} {name}")
+    }
+}
+
+/// Writes a section 1 manpage for `root` and each visible subcommand, named
+/// after the command path (`codex.1`, `codex-exec.1`, `codex-mcp-add.1`, ...),
+/// into `dir`. Returns the files written.
+pub(crate) fn write_manpages(root: Command, dir: &Path) -> Result<Vec<PathBuf>> {
+    // clap's generated `help` subcommands would each get a page; leave them out.
+    let mut root = without_help_subcommands(root);
+    // Build first so subcommand usage lines carry the full `codex <sub>` path.
+    root.build();
+    std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let mut pages = Vec::new();
+    collect_manpages(&root, root.get_name(), "", &mut pages);
+    let mut written = Vec::new();
+    for (name, page) in pages {
+        let file = dir.join(format!("{name}.1"));
+        std::fs::write(&file, page)
+            .with_context(|| format!("failed to write {}", file.display()))?;
+        written.push(file);
+    }
+    Ok(written)
+}
+
+fn without_help_subcommands(cmd: Command) -> Command {
+    cmd.disable_help_subcommand(true)
+        .mut_subcommands(without_help_subcommands)
+}
+
+fn collect_manpages(
+    cmd: &Command,
+    name: &str,
+    notes_path: &str,
+    pages: &mut Vec<(String, String)>,
+) {
+    pages.push((name.to_string(), render_manpage(cmd, name, notes_path)));
+    for sub in cmd.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+        collect_manpages(
+            sub,
+            &format!("{name}-{}", sub.get_name()),
+            &join_path(notes_path, sub.get_name()),
+            pages,
+        );
+    }
+}
+
+fn render_manpage(cmd: &Command, name: &str, notes_path: &str) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        ".TH {} 1 \"\" \"Codex CLI\" \"Codex CLI\"",
+        roff(&name.to_uppercase())
+    );
+    out.push_str(".SH NAME\n");
+    let about = cmd.get_about().map(ToString::to_string).unwrap_or_default();
+    let _ = writeln!(out, "{} \\- {}", roff(name), roff(first_line(&about)));
+
+    out.push_str(".SH SYNOPSIS\n");
+    let usage = cmd.clone().render_usage().to_string();
+    let usage = usage.strip_prefix("Usage: ").unwrap_or(&usage);
+    for line in usage.lines() {
+        let _ = writeln!(out, "{}\n.br", roff(line.trim()));
+    }
+
+    if let Some(long_about) = cmd.get_long_about().or_else(|| cmd.get_about()) {
+        out.push_str(".SH DESCRIPTION\n");
+        push_paragraphs(&mut out, &long_about.to_string());
+    }
+
+    let args: Vec<_> = cmd
+        .get_arguments()
+        .filter(|arg| !arg.is_hide_set() && arg.get_id() != "help" && arg.get_id() != "version")
+        .collect();
+    if !args.is_empty() {
+        out.push_str(".SH OPTIONS\n");
+        for arg in args {
+            let flag = match (arg.get_long(), arg.get_short()) {
+                (Some(long), Some(short)) => {
+                    format!("\\fB\\-{short}\\fR, \\fB\\-\\-{}\\fR", roff(long))
+                }
+                (Some(long), None) => format!("\\fB\\-\\-{}\\fR", roff(long)),
+                (None, Some(short)) => format!("\\fB\\-{short}\\fR"),
+                (None, None) => {
+                    format!("\\fI<{}>\\fR", roff(&arg.get_id().as_str().to_uppercase()))
+                }
+            };
+            let help = arg
+                .get_long_help()
+                .or_else(|| arg.get_help())
+                .map(ToString::to_string)
+                .unwrap_or_default();
+            let _ = writeln!(out, ".TP\n{flag}");
+            push_paragraphs(&mut out, &help);
+        }
+    }
+
+    let subcommands: Vec<_> = cmd
+        .get_subcommands()
+        .filter(|sub| !sub.is_hide_set())
+        .collect();
+    if !subcommands.is_empty() {
+        out.push_str(".SH COMMANDS\n");
+        for sub in &subcommands {
+            let _ = writeln!(
+                out,
+                ".TP\n\\fB{}\\-{}\\fR(1)",
+                roff(name),
+                roff(sub.get_name())
+            );
+            if let Some(about) = sub.get_about() {
+                let _ = writeln!(out, "{}", roff(first_line(&about.to_string())));
+            }
+        }
+    }
+
+    if let Some(notes) = notes_for(notes_path) {
+        out.push_str(".SH EXAMPLES\n");
+        for (command, description) in notes.examples {
+            let _ = writeln!(
+                out,
+                ".PP\n{}:\n.PP\n.RS 4\n.nf\n{}\n.fi\n.RE",
+                roff(description),
+                roff(command)
+            );
+        }
+        if !notes.config.is_empty() {
+            out.push_str(".SH CONFIGURATION\nThese keys in ~/.codex/config.toml change how the command behaves:\n");
+            for key in notes.config {
+                let _ = writeln!(out, ".TP\n\\fB{}\\fR", roff(key));
+            }
+        }
+    }
+
+    if notes_path.is_empty() {
+        out.push_str(".SH HELP TOPICS\n");
+        for topic in TOPICS {
+            let _ = writeln!(
+                out,
+                ".TP\n\\fBcodex help {}\\fR\n{}",
+                roff(topic.name),
+                roff(topic.summary)
+            );
+        }
+    }
+    out
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default().trim()
+}
+
+fn push_paragraphs(out: &mut String, text: &str) {
+    for (index, paragraph) in text.split("\n\n").enumerate() {
+        if index > 0 {
+            out.push_str(".PP\n");
+        }
+        for line in paragraph
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+        {
+            out.push_str(&roff(line));
+            out.push('\n');
+        }
+    }
+}
+
+/// Escapes `text` for roff: backslashes and hyphens are written as escapes,
+/// and a line may not start with a control character.
+fn roff(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{escaped}")
+    } else {
+        escaped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn sample() -> Command {
+        Command::new("codex")
+            .about("Codex CLI")
+            .arg(
+                clap::Arg::new("model")
+                    .long("model")
+                    .short('m')
+                    .help("Model to use"),
+            )
+            .subcommand(Command::new("exec").about("Run non-interactively"))
+            .subcommand(Command::new("secret").hide(true))
+    }
+
+    #[test]
+    fn markdown_lists_visible_commands_and_args() {
+        let markdown = render_markdown(&sample());
+        assert!(markdown.contains("## `codex`"));
+        assert!(markdown.contains("| `-m`, `--model` | Model to use |"));
+        assert!(markdown.contains("## `codex exec`"));
+        assert!(!markdown.contains("secret"));
+        assert!(markdown.contains("### `codex help sandbox`"));
+    }
+
+    #[test]
+    fn manpages_cover_visible_commands() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let written = write_manpages(sample(), dir.path()).expect("write manpages");
+        let names: Vec<_> = written
+            .iter()
+            .filter_map(|path| path.file_name()?.to_str())
+            .collect();
+        assert_eq!(names, vec!["codex.1", "codex-exec.1"]);
+
+        let page = std::fs::read_to_string(dir.path().join("codex.1")).expect("read codex.1");
+        assert!(page.starts_with(".TH CODEX 1 "));
+        assert!(page.contains(".SH NAME\ncodex \\- Codex CLI\n"));
+        assert!(page.contains(".TP\n\\fB\\-m\\fR, \\fB\\-\\-model\\fR\nModel to use\n"));
+        assert!(page.contains(".TP\n\\fBcodex\\-exec\\fR(1)\nRun non\\-interactively\n"));
+        assert!(page.contains(".SH EXAMPLES\n"));
+        assert!(page.contains(".SH CONFIGURATION\n"));
+        assert!(page.contains("\\fBcodex help sandbox\\fR"));
+
+        let exec = std::fs::read_to_string(dir.path().join("codex-exec.1")).expect("read exec");
+        assert!(exec.contains(".SH SYNOPSIS\ncodex exec\n"));
+        assert!(exec.contains("codex exec \\-\\-json"));
+    }
+
+    #[test]
+    fn annotate_adds_examples_to_long_help() {
+        let mut root = annotate(sample());
+        let help = root
+            .find_subcommand_mut("exec")
+            .expect("exec")
+            .render_long_help()
+            .to_string();
+        assert!(help.contains("Examples:\n  # Run one task to completion"));
+        assert!(help.contains("Related config: model, sandbox_mode"));
+    }
+
+    #[test]
+    fn command_notes_name_real_commands() {
+        let mut root = crate::MultitoolCli::command();
+        for notes in COMMAND_NOTES {
+            assert!(
+                find_command_mut(&mut root, notes.path).is_some(),
+                "no command `codex {}`",
+                notes.path
+            );
+        }
+    }
+
+    #[test]
+    fn roff_escapes_control_characters() {
+        assert_eq!(roff("--full-auto"), "\\-\\-full\\-auto");
+        assert_eq!(roff("C:\\dir"), "C:\\edir");
+        assert_eq!(roff(".codex"), "\\&.codex");
+    }
+
+    #[test]
+    fn unknown_help_target_is_an_error() {
+        let err = print_help(sample(), &["nope".to_string()]).expect_err("unknown target");
+        assert!(
+            err.to_string()
+                .contains("topics: sandbox, approvals, profiles")
+        );
+    }
+}
@@ -4,6 +4,7 @@ pub mod login;
 
 use clap::Parser;
 use codex_common::CliConfigOverrides;
+use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
 pub struct SeatbeltCommand {
@@ -50,3 +51,32 @@ pub struct WindowsCommand {
     #[arg(trailing_var_arg = true)]
     pub command: Vec<String>,
 }
+
+#[derive(Debug, Parser)]
+pub struct SandboxSelftestCommand {
+    /// Print the report as JSON.
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+}
+
+#[derive(Debug, Parser)]
+pub struct SandboxProbeCommand {
+    #[command(subcommand)]
+    pub action: ProbeAction,
+}
+
+/// A single action attempted by `codex sandbox selftest` inside the sandbox.
+#[derive(Debug, clap::Subcommand)]
+pub enum ProbeAction {
+    /// Create or overwrite a file.
+    Write { path: PathBuf },
+
+    /// Open a TCP connection.
+    Connect { addr: String },
+
+    /// Succeed if setuid binaries could still gain privileges (Linux).
+    NoNewPrivs,
+}
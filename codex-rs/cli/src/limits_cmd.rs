@@ -0,0 +1,295 @@
+//! `codex limits`: report the ChatGPT usage windows for the signed-in account
+//! and how long until an exhausted window resets. `codex limits history`
+//! lists the limit hits recorded in `~/.codex/state.db`.
+
+use anyhow::Result;
+use chrono::DateTime;
+use chrono::Local;
+use chrono::TimeZone;
+use chrono::Utc;
+use codex_app_server_protocol::AuthMode;
+use codex_backend_client::Client as BackendClient;
+use codex_core::CodexAuth;
+use codex_core::config::Config;
+use codex_core::limit_history::LimitHitEntry;
+use codex_core::limit_history::read_limit_history;
+use codex_core::limit_history::typical_reset_hour;
+use codex_core::rate_limits::binding_window;
+use codex_core::rate_limits::format_countdown;
+use codex_core::rate_limits::unix_now;
+use codex_core::rate_limits::window_label;
+use codex_protocol::protocol::RateLimitSnapshot;
+use codex_protocol::protocol::RateLimitWindow;
+use serde_json::json;
+
+/// Auth mode, usage windows, and the moment the report was taken.
+struct LimitsReport {
+    auth_mode: Option<AuthMode>,
+    snapshot: Option<RateLimitSnapshot>,
+    now: i64,
+}
+
+pub(crate) async fn run(config: &Config, json_output: bool) -> Result<()> {
+    let auth =
+        CodexAuth::from_auth_storage(&config.codex_home, config.cli_auth_credentials_store_mode)?;
+    let snapshot = match &auth {
+        Some(auth) if auth.mode == AuthMode::ChatGPT => {
+            let client = BackendClient::from_auth(config.chatgpt_base_url.clone(), auth).await?;
+            Some(client.get_rate_limits().await?)
+        }
+        _ => None,
+    };
+    let report = LimitsReport {
+        auth_mode: auth.map(|auth| auth.mode),
+        snapshot,
+        now: unix_now(),
+    };
+
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&report.to_json())?);
+    } else {
+        print!("{}", report.render());
+    }
+    Ok(())
+}
+
+pub(crate) fn run_history(config: &Config, json_output: bool) -> Result<()> {
+    let entries = read_limit_history(&config.codex_home, config.shared_state_dir.as_deref())?;
+    let typical_hour = typical_reset_hour(&entries, &Local);
+    if json_output {
+        let value = json!({
+            "hits": entries,
+            "typical_reset_hour": typical_hour,
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+    } else {
+        print!("{}", render_history(&entries, typical_hour, &Local));
+    }
+    Ok(())
+}
+
+/// Newest hit first, followed by the typical reset hour when there is one.
+fn render_history<Tz>(entries: &[LimitHitEntry], typical_hour: Option<u32>, tz: &Tz) -> String
+where
+    Tz: TimeZone,
+    Tz::Offset: std::fmt::Display,
+{
+    if entries.is_empty() {
+        return "No usage limits recorded yet.\n".to_string();
+    }
+    let timestamp = |seconds: i64| {
+        DateTime::<Utc>::from_timestamp(seconds, 0)
+            .map(|at| at.with_timezone(tz).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "?".to_string())
+    };
+    let mut out = String::new();
+    for entry in entries.iter().rev() {
+        let window = RateLimitWindow {
+            used_percent: 100.0,
+            window_minutes: entry.window_minutes,
+            resets_at: entry.resets_at,
+        };
+        let resets = entry
+            .resets_at
+            .map(|at| format!("resets {}", timestamp(at)))
+            .unwrap_or_else(|| "reset time unknown".to_string());
+        out.push_str(&format!(
+            "{}  {} limit  {}  {}  {resets}\n",
+            timestamp(entry.ts),
+            heading(&window),
+            entry.auth,
+            entry.model,
+        ));
+    }
+    if let Some(hour) = typical_hour {
+        out.push_str(&format!("Limits usually reset around {hour:02}:00.\n"));
+    }
+    out
+}
+
+impl LimitsReport {
+    fn windows(&self) -> Vec<(&'static str, &RateLimitWindow)> {
+        let Some(snapshot) = &self.snapshot else {
+            return Vec::new();
+        };
+        [
+            ("primary", snapshot.primary.as_ref()),
+            ("secondary", snapshot.secondary.as_ref()),
+        ]
+        .into_iter()
+        .filter_map(|(name, window)| window.map(|window| (name, window)))
+        .collect()
+    }
+
+    fn binding_window(&self) -> Option<&RateLimitWindow> {
+        binding_window(self.snapshot.as_ref()?)
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let windows: Vec<_> = self
+            .windows()
+            .into_iter()
+            .map(|(name, window)| {
+                json!({
+                    "name": name,
+                    "used_percent": window.used_percent,
+                    "window_minutes": window.window_minutes,
+                    "resets_at": window.resets_at,
+                    "resets_in_seconds": window.resets_at.map(|at| (at - self.now).max(0)),
+                })
+            })
+            .collect();
+        json!({
+            "auth_mode": self.auth_mode.map(auth_mode_str),
+            "limit_reached": self.binding_window().is_some(),
+            "resets_at": self.binding_window().and_then(|window| window.resets_at),
+            "windows": windows,
+        })
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::new();
+        let Some(auth_mode) = self.auth_mode else {
+            out.push_str("Not logged in. Run `codex login` first.\n");
+            return out;
+        };
+        out.push_str(&format!("Auth: {}\n", auth_mode_label(auth_mode)));
+        if self.snapshot.is_none() {
+            out.push_str(
+                "Usage limits are only reported for ChatGPT sign-in; API key usage follows your organization's rate limits.\n",
+            );
+            return out;
+        }
+
+        match self.binding_window() {
+            Some(window) => {
+                out.push_str(&format!("Limit: reached, {}\n", self.reset_phrase(window)))
+            }
+            None => out.push_str("Limit: not reached\n"),
+        }
+        for (_, window) in self.windows() {
+            out.push_str(&format!(
+                "{} limit: {:.0}% used, {}\n",
+                heading(window),
+                window.used_percent.clamp(0.0, 100.0),
+                self.reset_phrase(window)
+            ));
+        }
+        out
+    }
+
+    fn reset_phrase(&self, window: &RateLimitWindow) -> String {
+        match window.resets_at {
+            Some(at) => format!("resets in {}", format_countdown(at - self.now)),
+            None => "reset time unknown".to_string(),
+        }
+    }
+}
+
+fn auth_mode_str(mode: AuthMode) -> &'static str {
+    match mode {
+        AuthMode::ApiKey => "api_key",
+        AuthMode::ChatGPT => "chatgpt",
+    }
+}
+
+fn auth_mode_label(mode: AuthMode) -> &'static str {
+    match mode {
+        AuthMode::ApiKey => "API key",
+        AuthMode::ChatGPT => "ChatGPT",
+    }
+}
+
+/// "Weekly", "Daily", "5-hour", or "Usage" when the window length is unknown.
+fn heading(window: &RateLimitWindow) -> String {
+    let Some(label) = window_label(window) else {
+        return "Usage".to_string();
+    };
+    let mut chars = label.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn report(primary_used: f64, secondary_used: f64) -> LimitsReport {
+        LimitsReport {
+            auth_mode: Some(AuthMode::ChatGPT),
+            snapshot: Some(RateLimitSnapshot {
+                primary: Some(RateLimitWindow {
+                    used_percent: primary_used,
+                    window_minutes: Some(300),
+                    resets_at: Some(NOW + 3 * 3600 + 12 * 60),
+                }),
+                secondary: Some(RateLimitWindow {
+                    used_percent: secondary_used,
+                    window_minutes: Some(7 * 24 * 60),
+                    resets_at: Some(NOW + 2 * 86_400 + 4 * 3600),
+                }),
+                credits: None,
+                request_limits: None,
+            }),
+            now: NOW,
+        }
+    }
+
+    #[test]
+    fn render_reports_binding_window_countdown() {
+        assert_eq!(
+            report(40.0, 100.0).render(),
+            "Auth: ChatGPT\n\
+             Limit: reached, resets in 2d 4h\n\
+             5-hour limit: 40% used, resets in 3h 12m\n\
+             Weekly limit: 100% used, resets in 2d 4h\n"
+        );
+    }
+
+    #[test]
+    fn json_reports_limit_state() {
+        let value = report(100.0, 20.0).to_json();
+        assert_eq!(value["auth_mode"], "chatgpt");
+        assert_eq!(value["limit_reached"], true);
+        assert_eq!(value["resets_at"], NOW + 3 * 3600 + 12 * 60);
+        assert_eq!(value["windows"][0]["resets_in_seconds"], 3 * 3600 + 12 * 60);
+    }
+
+    #[test]
+    fn history_lists_newest_hit_first() {
+        let hit = |ts: i64, model: &str| LimitHitEntry {
+            ts,
+            auth: "me@example.com".to_string(),
+            model: model.to_string(),
+            window_minutes: Some(7 * 24 * 60),
+            resets_at: Some(ts + 3600),
+        };
+        let entries = vec![hit(NOW, "gpt-5.1-codex"), hit(NOW + 86_400, "gpt-5.1")];
+        assert_eq!(
+            render_history(&entries, Some(23), &Utc),
+            "2023-11-15 22:13  Weekly limit  me@example.com  gpt-5.1  resets 2023-11-15 23:13\n\
+             2023-11-14 22:13  Weekly limit  me@example.com  gpt-5.1-codex  resets 2023-11-14 23:13\n\
+             Limits usually reset around 23:00.\n"
+        );
+        assert_eq!(
+            render_history(&[], None, &Utc),
+            "No usage limits recorded yet.\n"
+        );
+    }
+
+    #[test]
+    fn api_key_auth_has_no_windows() {
+        let report = LimitsReport {
+            auth_mode: Some(AuthMode::ApiKey),
+            snapshot: None,
+            now: NOW,
+        };
+        assert_eq!(report.to_json()["limit_reached"], false);
+        assert!(report.render().starts_with("Auth: API key\n"));
+    }
+}
@@ -3,16 +3,28 @@ use codex_common::CliConfigOverrides;
 use codex_core::CodexAuth;
 use codex_core::auth::AuthCredentialsStoreMode;
 use codex_core::auth::CLIENT_ID;
+use codex_core::auth::account_home;
+use codex_core::auth::list_accounts;
+use codex_core::auth::list_projects;
 use codex_core::auth::login_with_api_key;
 use codex_core::auth::logout;
+use codex_core::auth::migrate_auth;
+use codex_core::auth::register_project;
+use codex_core::auth::unregister_project;
+use codex_core::auth::validate_account_name;
+use codex_core::auth::validate_api_key;
+use codex_core::built_in_model_providers;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
+use codex_core::config::edit::ConfigEditsBuilder;
+use codex_core::git_info::get_git_repo_root;
 use codex_login::ServerOptions;
-use codex_login::run_device_code_login;
+use codex_login::run_device_code_login_with_qr_code;
 use codex_login::run_login_server;
 use codex_protocol::config_types::ForcedLoginMethod;
 use std::io::IsTerminal;
 use std::io::Read;
+use std::path::Path;
 use std::path::PathBuf;
 
 pub async fn login_with_chatgpt(
@@ -36,18 +48,28 @@ pub async fn login_with_chatgpt(
     server.block_until_done().await
 }
 
-pub async fn run_login_with_chatgpt(cli_config_overrides: CliConfigOverrides) -> ! {
+pub async fn run_login_with_chatgpt(
+    cli_config_overrides: CliConfigOverrides,
+    account: Option<String>,
+) -> ! {
     let config = load_config_or_exit(cli_config_overrides).await;
+    let codex_home = credentials_home_or_exit(&config, account.as_deref());
 
     if matches!(config.forced_login_method, Some(ForcedLoginMethod::Api)) {
         eprintln!("ChatGPT login is disabled. Use API key login instead.");
         std::process::exit(1);
     }
 
+    if is_ssh_session() {
+        eprintln!(
+            "It looks like you are connected over SSH. If the browser cannot reach this machine, run `codex login --device-auth` instead, or `codex login --qr` to sign in from your phone."
+        );
+    }
+
     let forced_chatgpt_workspace_id = config.forced_chatgpt_workspace_id.clone();
 
     match login_with_chatgpt(
-        config.codex_home,
+        codex_home,
         forced_chatgpt_workspace_id,
         config.cli_auth_credentials_store_mode,
     )
@@ -67,6 +89,8 @@ pub async fn run_login_with_chatgpt(cli_config_overrides: CliConfigOverrides) ->
 pub async fn run_login_with_api_key(
     cli_config_overrides: CliConfigOverrides,
     api_key: String,
+    skip_validation: bool,
+    project: Option<PathBuf>,
 ) -> ! {
     let config = load_config_or_exit(cli_config_overrides).await;
 
@@ -75,13 +99,54 @@ pub async fn run_login_with_api_key(
         std::process::exit(1);
     }
 
+    if !skip_validation {
+        // Keys are checked against the configured provider when it uses
+        // OpenAI credentials (e.g. a gateway), and against OpenAI otherwise.
+        let provider = if config.model_provider.requires_openai_auth {
+            Some(config.model_provider.clone())
+        } else {
+            config
+                .model_providers
+                .get("openai")
+                .cloned()
+                .or_else(|| built_in_model_providers().remove("openai"))
+        };
+        if let Some(provider) = provider
+            && let Err(e) = validate_api_key(&provider, &api_key).await
+        {
+            if e.is_rejected() {
+                eprintln!("Error logging in: {e}");
+                std::process::exit(1);
+            }
+            eprintln!("Warning: {e}; storing the key anyway");
+        }
+    }
+
+    let project_root = project.as_deref().map(project_root_or_exit);
+    let codex_home = match &project_root {
+        Some(root) => match register_project(&config.codex_home, root) {
+            Ok(home) => home,
+            Err(e) => {
+                eprintln!("Error logging in: {e}");
+                std::process::exit(1);
+            }
+        },
+        None => config.codex_home.clone(),
+    };
+
     match login_with_api_key(
-        &config.codex_home,
+        &codex_home,
         &api_key,
         config.cli_auth_credentials_store_mode,
     ) {
         Ok(_) => {
-            eprintln!("Successfully logged in");
+            match project_root {
+                Some(root) => eprintln!(
+                    "Successfully logged in; sessions in {} will use this key",
+                    root.display()
+                ),
+                None => eprintln!("Successfully logged in"),
+            }
             std::process::exit(0);
         }
         Err(e) => {
@@ -123,15 +188,19 @@ pub async fn run_login_with_device_code(
     cli_config_overrides: CliConfigOverrides,
     issuer_base_url: Option<String>,
     client_id: Option<String>,
+    account: Option<String>,
+    show_qr_code: bool,
+    color: bool,
 ) -> ! {
     let config = load_config_or_exit(cli_config_overrides).await;
+    let codex_home = credentials_home_or_exit(&config, account.as_deref());
     if matches!(config.forced_login_method, Some(ForcedLoginMethod::Api)) {
         eprintln!("ChatGPT login is disabled. Use API key login instead.");
         std::process::exit(1);
     }
     let forced_chatgpt_workspace_id = config.forced_chatgpt_workspace_id.clone();
     let mut opts = ServerOptions::new(
-        config.codex_home,
+        codex_home,
         client_id.unwrap_or(CLIENT_ID.to_string()),
         forced_chatgpt_workspace_id,
         config.cli_auth_credentials_store_mode,
@@ -139,7 +208,7 @@ pub async fn run_login_with_device_code(
     if let Some(iss) = issuer_base_url {
         opts.issuer = iss;
     }
-    match run_device_code_login(opts).await {
+    match run_device_code_login_with_qr_code(opts, show_qr_code, color).await {
         Ok(()) => {
             eprintln!("Successfully logged in");
             std::process::exit(0);
@@ -159,6 +228,7 @@ pub async fn run_login_status(cli_config_overrides: CliConfigOverrides) -> ! {
             AuthMode::ApiKey => match auth.get_token().await {
                 Ok(api_key) => {
                     eprintln!("Logged in using an API key - {}", safe_format_key(&api_key));
+                    print_project_keys(&config);
                     std::process::exit(0);
                 }
                 Err(e) => {
@@ -168,6 +238,8 @@ pub async fn run_login_status(cli_config_overrides: CliConfigOverrides) -> ! {
             },
             AuthMode::ChatGPT => {
                 eprintln!("Logged in using ChatGPT");
+                print_additional_accounts(&config);
+                print_project_keys(&config);
                 std::process::exit(0);
             }
         },
@@ -182,10 +254,32 @@ pub async fn run_login_status(cli_config_overrides: CliConfigOverrides) -> ! {
     }
 }
 
-pub async fn run_logout(cli_config_overrides: CliConfigOverrides) -> ! {
+pub async fn run_logout(
+    cli_config_overrides: CliConfigOverrides,
+    account: Option<String>,
+    project: Option<PathBuf>,
+) -> ! {
     let config = load_config_or_exit(cli_config_overrides).await;
+    if let Some(project) = project {
+        let root = project_root_or_exit(&project);
+        match unregister_project(&config.codex_home, &root) {
+            Ok(true) => {
+                eprintln!("Removed the API key for {}", root.display());
+                std::process::exit(0);
+            }
+            Ok(false) => {
+                eprintln!("No API key is stored for {}", root.display());
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Error logging out: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+    let codex_home = credentials_home_or_exit(&config, account.as_deref());
 
-    match logout(&config.codex_home, config.cli_auth_credentials_store_mode) {
+    match logout(&codex_home, config.cli_auth_credentials_store_mode) {
         Ok(true) => {
             eprintln!("Successfully logged out");
             std::process::exit(0);
@@ -201,6 +295,40 @@ pub async fn run_logout(cli_config_overrides: CliConfigOverrides) -> ! {
     }
 }
 
+/// Moves stored credentials into `to` and makes it the configured
+/// `cli_auth_credentials_store`, so later runs read them from there.
+pub async fn run_login_migrate(
+    cli_config_overrides: CliConfigOverrides,
+    to: AuthCredentialsStoreMode,
+) -> ! {
+    let config = load_config_or_exit(cli_config_overrides).await;
+    let (from_name, to_name) = match to {
+        AuthCredentialsStoreMode::File => ("the OS keyring", "auth.json"),
+        AuthCredentialsStoreMode::Keyring | AuthCredentialsStoreMode::Auto => {
+            ("auth.json", "the OS keyring")
+        }
+    };
+
+    match migrate_auth(&config.codex_home, to) {
+        Ok(true) => eprintln!("Moved credentials from {from_name} to {to_name}"),
+        Ok(false) => eprintln!("No credentials in {from_name} to move"),
+        Err(e) => {
+            eprintln!("Error moving credentials to {to_name}: {e}");
+            std::process::exit(1);
+        }
+    }
+
+    if let Err(e) = ConfigEditsBuilder::new(&config.codex_home)
+        .set_cli_auth_credentials_store(to)
+        .apply()
+        .await
+    {
+        eprintln!("Error updating cli_auth_credentials_store in config.toml: {e}");
+        std::process::exit(1);
+    }
+    std::process::exit(0);
+}
+
 async fn load_config_or_exit(cli_config_overrides: CliConfigOverrides) -> Config {
     let cli_overrides = match cli_config_overrides.parse_overrides() {
         Ok(v) => v,
@@ -220,6 +348,60 @@ async fn load_config_or_exit(cli_config_overrides: CliConfigOverrides) -> Config
     }
 }
 
+/// Where the credentials of `account` are stored; `None` is the default
+/// sign-in in CODEX_HOME.
+fn credentials_home_or_exit(config: &Config, account: Option<&str>) -> PathBuf {
+    let Some(account) = account else {
+        return config.codex_home.clone();
+    };
+    if let Err(e) = validate_account_name(account) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+    account_home(&config.codex_home, account)
+}
+
+/// Lists the ChatGPT accounts added with `codex login --account`, which
+/// Codex switches to when the active one reaches its usage limit.
+fn print_additional_accounts(config: &Config) {
+    let signed_in: Vec<String> = list_accounts(&config.codex_home)
+        .into_iter()
+        .filter(|name| {
+            let home = account_home(&config.codex_home, name);
+            matches!(
+                CodexAuth::from_auth_storage(&home, config.cli_auth_credentials_store_mode),
+                Ok(Some(auth)) if auth.mode == AuthMode::ChatGPT
+            )
+        })
+        .collect();
+    if !signed_in.is_empty() {
+        eprintln!("Additional ChatGPT accounts: {}", signed_in.join(", "));
+    }
+}
+
+fn print_project_keys(config: &Config) {
+    for root in list_projects(&config.codex_home) {
+        eprintln!("Project API key for {}", root.display());
+    }
+}
+
+/// The repository containing `path`, or `path` itself outside one.
+fn project_root_or_exit(path: &Path) -> PathBuf {
+    match std::fs::canonicalize(path) {
+        Ok(path) => get_git_repo_root(&path).unwrap_or(path),
+        Err(e) => {
+            eprintln!("Error resolving project {}: {e}", path.display());
+            std::process::exit(1);
+        }
+    }
+}
+
+fn is_ssh_session() -> bool {
+    ["SSH_CONNECTION", "SSH_TTY"]
+        .iter()
+        .any(|var| std::env::var_os(var).is_some_and(|value| !value.is_empty()))
+}
+
 fn safe_format_key(key: &str) -> String {
     if key.len() <= 13 {
         return "***".to_string();
@@ -1,5 +1,6 @@
 use clap::Args;
 use clap::CommandFactory;
+use clap::FromArgMatches;
 use clap::Parser;
 use clap_complete::Shell;
 use clap_complete::generate;
@@ -7,9 +8,12 @@ use codex_arg0::arg0_dispatch_or_else;
 use codex_chatgpt::apply_command::ApplyCommand;
 use codex_chatgpt::apply_command::run_apply_command;
 use codex_cli::LandlockCommand;
+use codex_cli::SandboxProbeCommand;
+use codex_cli::SandboxSelftestCommand;
 use codex_cli::SeatbeltCommand;
 use codex_cli::WindowsCommand;
 use codex_cli::login::read_api_key_from_stdin;
+use codex_cli::login::run_login_migrate;
 use codex_cli::login::run_login_status;
 use codex_cli::login::run_login_with_api_key;
 use codex_cli::login::run_login_with_chatgpt;
@@ -18,25 +22,48 @@ use codex_cli::login::run_logout;
 use codex_cloud_tasks::Cli as CloudTasksCli;
 use codex_common::CliConfigOverrides;
 use codex_exec::Cli as ExecCli;
+use codex_exec::Color;
 use codex_exec::Command as ExecCommand;
 use codex_exec::ReviewArgs;
 use codex_execpolicy::ExecPolicyCheckCommand;
 use codex_responses_api_proxy::Args as ResponsesApiProxyArgs;
 use codex_tui::AppExitInfo;
 use codex_tui::Cli as TuiCli;
-use codex_tui::update_action::UpdateAction;
 use owo_colors::OwoColorize;
 use std::path::PathBuf;
-use supports_color::Stream;
 
+mod audit_cmd;
+mod auth_cmd;
+mod completion;
+mod config_cmd;
+mod doctor;
+mod help_topics;
+mod limits_cmd;
 mod mcp_cmd;
+mod output_policy;
+mod queue_cmd;
+mod replay;
+mod restore_cmd;
+mod run_cmd;
+mod self_update;
+mod session_export;
+mod sessions_cmd;
+mod simulate_limit;
+mod undo_cmd;
+mod usage_cmd;
 #[cfg(not(windows))]
 mod wsl_paths;
 
+use crate::config_cmd::ConfigCli;
 use crate::mcp_cmd::McpCli;
+use crate::output_policy::OutputPolicy;
+use crate::usage_cmd::UsageFormat;
+use crate::usage_cmd::UsageGroup;
 
+use codex_core::auth::AuthCredentialsStoreMode;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
+use codex_core::config::find_codex_home;
 use codex_core::features::is_known_feature_key;
 
 /// Codex CLI
@@ -52,6 +79,8 @@ use codex_core::features::is_known_feature_key;
     // `codex-x86_64-unknown-linux-musl`, but the help output should always use
     // the generic `codex` command name that users run.
     bin_name = "codex",
+    // `codex help` is implemented below so it can also serve concept topics.
+    disable_help_subcommand = true,
     override_usage = "codex [OPTIONS] [PROMPT]\n       codex [OPTIONS] <COMMAND> [ARGS]"
 )]
 struct MultitoolCli {
@@ -61,9 +90,18 @@ struct MultitoolCli {
     #[clap(flatten)]
     pub feature_toggles: FeatureToggles,
 
+    #[clap(flatten)]
+    pub session_tags: SessionTags,
+
     #[clap(flatten)]
     interactive: TuiCli,
 
+    /// Color settings for Codex's own output, including the login prompt. `auto` honors
+    /// `NO_COLOR` and disables color and emoji when stdout is not a terminal; `never` disables
+    /// both.
+    #[arg(long = "color", value_enum, default_value_t = Color::Auto)]
+    color: Color,
+
     #[clap(subcommand)]
     subcommand: Option<Subcommand>,
 }
@@ -77,12 +115,18 @@ enum Subcommand {
     /// Run a code review non-interactively.
     Review(ReviewArgs),
 
+    /// Run a saved prompt template from ~/.codex/prompts non-interactively.
+    Run(RunCommand),
+
     /// Manage login.
     Login(LoginCommand),
 
     /// Remove stored authentication credentials.
     Logout(LogoutCommand),
 
+    /// Inspect which credentials Codex uses.
+    Auth(AuthCommand),
+
     /// [experimental] Run Codex as an MCP server and manage MCP servers.
     Mcp(McpCli),
 
@@ -110,6 +154,24 @@ enum Subcommand {
     /// Resume a previous interactive session (picker by default; use --last to continue the most recent).
     Resume(ResumeCommand),
 
+    /// Start a new session from the first turns of a recorded one, leaving it untouched.
+    Fork(ForkCommand),
+
+    /// Roll the conversation and working tree back to a named checkpoint (lists them when no name is given).
+    Restore(RestoreCommand),
+
+    /// List past sessions, newest first, to find one to resume.
+    Sessions(SessionsCommand),
+
+    /// Step through a recorded session turn by turn.
+    Replay(ReplayCommand),
+
+    /// Revert the file changes of a session's last turn and drop the turn from it.
+    Undo(UndoCommand),
+
+    /// Queue prompts and run them back to back in fresh non-interactive sessions.
+    Queue(QueueCommand),
+
     /// [EXPERIMENTAL] Browse tasks from Codex Cloud and apply changes locally.
     #[clap(name = "cloud", alias = "cloud-tasks")]
     Cloud(CloudTasksCli),
@@ -124,13 +186,245 @@ enum Subcommand {
 
     /// Inspect feature flags.
     Features(FeaturesCli),
+
+    /// Locate or edit config.toml.
+    Config(ConfigCli),
+
+    /// Show ChatGPT usage limits and when they reset.
+    Limits(LimitsCommand),
+
+    /// Report recorded token usage and estimated cost.
+    Usage(UsageCommand),
+
+    /// Show recorded logins, credential switches, limit hits, and commands.
+    Audit(AuditCommand),
+
+    /// Report the effective privacy posture (which features may use the network).
+    Doctor(DoctorCommand),
+
+    /// Print help for a command, or for a topic: sandbox, approvals, profiles.
+    Help(HelpCommand),
+}
+
+#[derive(Debug, Parser)]
+struct HelpCommand {
+    /// Command path (e.g. `mcp add`) or topic name.
+    #[arg(value_name = "COMMAND|TOPIC")]
+    target: Vec<String>,
+
+    /// Print a Markdown reference of every command instead.
+    #[arg(long, hide = true, conflicts_with = "man")]
+    markdown: bool,
+
+    /// Write a manpage for every command into DIR instead.
+    #[arg(long, hide = true, value_name = "DIR")]
+    man: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+struct RunCommand {
+    /// Name of the template: a file in ~/.codex/prompts without `.md`.
+    #[arg(value_name = "TEMPLATE")]
+    template: String,
+
+    /// Value for a `{{key}}` or `$KEY` variable of the template (repeatable).
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = run_cmd::parse_var)]
+    vars: Vec<(String, String)>,
+
+    /// Options for `codex exec`, after `--` (e.g. `-- --full-auto`).
+    #[arg(last = true, value_name = "EXEC_ARGS")]
+    exec_args: Vec<String>,
+}
+
+#[derive(Debug, Parser)]
+struct LimitsCommand {
+    #[clap(skip)]
+    config_overrides: CliConfigOverrides,
+
+    /// Print the report as JSON.
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    action: Option<LimitsSubcommand>,
+}
+
+#[derive(Debug, Parser)]
+struct SessionsCommand {
+    #[clap(skip)]
+    config_overrides: CliConfigOverrides,
+
+    /// Only sessions started on or after this day (YYYY-MM-DD, local time).
+    #[arg(long)]
+    since: Option<chrono::NaiveDate>,
+
+    /// Only sessions started in this directory or below it.
+    #[arg(long, value_name = "DIR")]
+    cwd: Option<PathBuf>,
+
+    /// Only sessions whose prompts, replies, commands, or patches match this
+    /// regular expression (case-insensitive).
+    #[arg(long, value_name = "PATTERN")]
+    grep: Option<String>,
+
+    /// Most sessions to list.
+    #[arg(long, short = 'n', default_value_t = 20)]
+    limit: usize,
+
+    /// Print the sessions as JSON.
+    #[arg(long)]
+    json: bool,
+
+    #[command(subcommand)]
+    action: Option<SessionsSubcommand>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum SessionsSubcommand {
+    /// Render a session's transcript as Markdown, HTML, or JSON to share it.
+    Export(SessionsExportCommand),
+}
+
+#[derive(Debug, Parser)]
+struct SessionsExportCommand {
+    /// Conversation/session id (UUID) of the session to export.
+    #[arg(value_name = "SESSION_ID")]
+    session_id: String,
+
+    /// Document format.
+    #[arg(long, value_enum, default_value_t = session_export::ExportFormat::Md)]
+    format: session_export::ExportFormat,
+
+    /// Replace secrets with [REDACTED]: values of environment variables
+    /// named like secrets (`*_KEY`, `*_TOKEN`, ...), assignments to such
+    /// names, and strings shaped like API keys or tokens.
+    #[arg(long)]
+    redact: bool,
+
+    /// Write the document to FILE instead of stdout.
+    #[arg(long, short = 'o', value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+struct UsageCommand {
+    #[clap(skip)]
+    config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    action: UsageSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum UsageSubcommand {
+    /// Total tokens and estimated cost by day, project, model, and account.
+    Report(UsageReportCommand),
+}
+
+#[derive(Debug, Parser)]
+struct UsageReportCommand {
+    /// Columns to group by, comma-separated. Defaults to all of them.
+    #[arg(
+        long = "by",
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [UsageGroup::Day, UsageGroup::Project, UsageGroup::Model, UsageGroup::Auth]
+    )]
+    group_by: Vec<UsageGroup>,
+
+    /// First day to include (YYYY-MM-DD, local time).
+    #[arg(long)]
+    since: Option<chrono::NaiveDate>,
+
+    /// Last day to include (YYYY-MM-DD, local time).
+    #[arg(long)]
+    until: Option<chrono::NaiveDate>,
+
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = UsageFormat::Table)]
+    format: UsageFormat,
+}
+
+#[derive(Debug, Parser)]
+struct AuditCommand {
+    #[clap(skip)]
+    config_overrides: CliConfigOverrides,
+
+    /// First day to include (YYYY-MM-DD, local time).
+    #[arg(long)]
+    since: Option<chrono::NaiveDate>,
+
+    /// Last day to include (YYYY-MM-DD, local time).
+    #[arg(long)]
+    until: Option<chrono::NaiveDate>,
+
+    /// Only these events, comma-separated.
+    #[arg(long = "event", value_delimiter = ',', value_parser = audit_cmd::EVENT_NAMES)]
+    events: Vec<String>,
+
+    /// Only events under credentials containing this text, e.g. an email or
+    /// the last digits of an API key.
+    #[arg(long)]
+    auth: Option<String>,
+
+    /// Print one JSON object per event.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Parser)]
+struct AuthCommand {
+    #[clap(skip)]
+    config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    action: AuthSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum AuthSubcommand {
+    /// Show the stored credentials, token expiry, API keys in the environment, and active limits.
+    Status(AuthStatusCommand),
+}
+
+#[derive(Debug, Parser)]
+struct AuthStatusCommand {
+    /// Print the report as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum LimitsSubcommand {
+    /// List past usage-limit hits and when limits typically reset.
+    History,
+}
+
+#[derive(Debug, Parser)]
+struct DoctorCommand {
+    #[clap(skip)]
+    config_overrides: CliConfigOverrides,
+
+    /// Print the report as JSON.
+    #[arg(long)]
+    json: bool,
 }
 
 #[derive(Debug, Parser)]
 struct CompletionCommand {
-    /// Shell to generate completions for
-    #[clap(value_enum, default_value_t = Shell::Bash)]
-    shell: Shell,
+    /// Shell to generate completions for. Defaults to bash when printing and to the shell in
+    /// `$SHELL` when installing.
+    #[clap(value_enum)]
+    shell: Option<Shell>,
+
+    /// Install the completion script where the shell will load it, updating the shell rc file
+    /// if needed. Safe to re-run after upgrading Codex.
+    #[arg(long, conflicts_with = "uninstall")]
+    install: bool,
+
+    /// Remove completions previously set up with `--install`.
+    #[arg(long)]
+    uninstall: bool,
 }
 
 #[derive(Debug, Parser)]
@@ -152,6 +446,103 @@ struct ResumeCommand {
     config_overrides: TuiCli,
 }
 
+#[derive(Debug, Parser)]
+struct ForkCommand {
+    /// Conversation/session id (UUID) of the session to fork.
+    #[arg(value_name = "SESSION_ID")]
+    session_id: String,
+
+    /// Keep the session's first N turns; the fork continues from there.
+    #[arg(long = "at-turn", value_name = "N")]
+    at_turn: usize,
+
+    #[clap(flatten)]
+    config_overrides: TuiCli,
+}
+
+#[derive(Debug, Parser)]
+struct RestoreCommand {
+    /// Checkpoint saved with /checkpoint in a session.
+    #[arg(value_name = "CHECKPOINT")]
+    checkpoint: Option<String>,
+
+    #[clap(flatten)]
+    config_overrides: TuiCli,
+}
+
+#[derive(Debug, Parser)]
+struct ReplayCommand {
+    #[clap(skip)]
+    config_overrides: CliConfigOverrides,
+
+    /// Conversation/session id (UUID) of the session to replay.
+    #[arg(value_name = "SESSION_ID")]
+    session_id: String,
+
+    /// Turn to start from.
+    #[arg(long = "from-turn", value_name = "N", default_value_t = 1)]
+    from_turn: usize,
+
+    /// Print the whole session instead of pausing after each turn.
+    #[arg(long = "no-pause")]
+    no_pause: bool,
+}
+
+#[derive(Debug, Parser)]
+struct UndoCommand {
+    #[clap(skip)]
+    config_overrides: CliConfigOverrides,
+
+    /// Conversation/session id (UUID); defaults to the most recent session.
+    #[arg(value_name = "SESSION_ID")]
+    session_id: Option<String>,
+}
+
+#[derive(Debug, Parser)]
+struct QueueCommand {
+    #[clap(skip)]
+    config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    action: QueueSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum QueueSubcommand {
+    /// Add a prompt to the end of the queue.
+    Add {
+        #[arg(value_name = "PROMPT")]
+        prompt: String,
+
+        /// Directory to run the task in; defaults to the current one.
+        #[arg(long = "cd", short = 'C', value_name = "DIR")]
+        cwd: Option<PathBuf>,
+    },
+
+    /// List the queued, running, and finished tasks.
+    List {
+        /// Print the queue as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Run the pending tasks one after another until the queue is empty.
+    Run {
+        /// Options for each `codex exec`, after `--` (e.g. `-- --full-auto`).
+        #[arg(last = true, value_name = "EXEC_ARGS")]
+        exec_args: Vec<String>,
+    },
+
+    /// Remove a task that is not running.
+    Remove {
+        #[arg(value_name = "ID")]
+        id: u64,
+    },
+
+    /// Remove the finished tasks.
+    Clear,
+}
+
 #[derive(Debug, Parser)]
 struct SandboxArgs {
     #[command(subcommand)]
@@ -170,6 +561,41 @@ enum SandboxCommand {
 
     /// Run a command under Windows restricted token (Windows only).
     Windows(WindowsCommand),
+
+    /// Check that the sandbox blocks writes outside the workspace, network access,
+    /// and privilege gain on this machine, and print a pass/fail report.
+    Selftest(SandboxSelftestCommand),
+
+    /// Internal: a single action attempted by `selftest` inside the sandbox.
+    #[clap(hide = true)]
+    Probe(SandboxProbeCommand),
+
+    /// Record a simulated limit hit and print the credential switches it causes.
+    #[clap(hide = true)]
+    SimulateLimit(SimulateLimitCommand),
+}
+
+#[derive(Debug, Parser)]
+struct SimulateLimitCommand {
+    #[clap(skip)]
+    config_overrides: CliConfigOverrides,
+
+    /// Error the simulated request fails with.
+    #[arg(long, value_enum, default_value_t = simulate_limit::SimulatedLimit::UsageLimit)]
+    kind: simulate_limit::SimulatedLimit,
+
+    /// When the simulated limit resets (RFC 3339, e.g. 2024-03-01T12:00:00Z).
+    #[arg(long, conflicts_with = "resets_in")]
+    resets_at: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Minutes until the simulated limit resets. Defaults to 1, so the
+    /// recorded hit stops affecting real sessions right away.
+    #[arg(long, value_name = "MINUTES")]
+    resets_in: Option<u32>,
+
+    /// Print the result as JSON.
+    #[arg(long)]
+    json: bool,
 }
 
 #[derive(Debug, Parser)]
@@ -196,6 +622,22 @@ struct LoginCommand {
     )]
     with_api_key: bool,
 
+    /// Store the API key without first checking that it is accepted.
+    #[arg(long = "skip-validation", requires = "with_api_key")]
+    skip_validation: bool,
+
+    /// Store the API key for one project only: sessions started inside PATH
+    /// (default: the current repository) use it instead of the other
+    /// credentials.
+    #[arg(
+        long = "project",
+        value_name = "PATH",
+        requires = "with_api_key",
+        num_args = 0..=1,
+        default_missing_value = "."
+    )]
+    project: Option<PathBuf>,
+
     #[arg(
         long = "api-key",
         value_name = "API_KEY",
@@ -204,9 +646,21 @@ struct LoginCommand {
     )]
     api_key: Option<String>,
 
+    /// Sign in with a one-time code entered on another device instead of a
+    /// browser redirect to this machine (for SSH sessions and containers).
     #[arg(long = "device-auth")]
     use_device_code: bool,
 
+    /// Like `--device-auth`, and also print the sign-in link as a QR code to
+    /// scan with a phone, so this machine never needs a browser.
+    #[arg(long = "qr")]
+    qr_code: bool,
+
+    /// Add another ChatGPT account under this name. Codex switches to the
+    /// next signed-in account when the active one reaches its usage limit.
+    #[arg(long = "account", value_name = "NAME", conflicts_with_all = ["with_api_key", "api_key"])]
+    account: Option<String>,
+
     /// EXPERIMENTAL: Use custom OAuth issuer base URL (advanced)
     /// Override the OAuth issuer base URL (advanced)
     #[arg(long = "experimental_issuer", value_name = "URL", hide = true)]
@@ -224,12 +678,48 @@ struct LoginCommand {
 enum LoginSubcommand {
     /// Show login status.
     Status,
+
+    /// Move stored credentials between auth.json and the OS keyring (macOS
+    /// Keychain, Windows Credential Manager, or Secret Service) and use that
+    /// store from now on.
+    Migrate(LoginMigrateCommand),
+}
+
+#[derive(Debug, Args)]
+struct LoginMigrateCommand {
+    /// Where to keep the credentials.
+    #[arg(long = "to", value_enum, default_value_t = CredentialsStore::Keyring)]
+    to: CredentialsStore,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum CredentialsStore {
+    /// The OS keyring.
+    Keyring,
+    /// `auth.json` in CODEX_HOME.
+    File,
 }
 
 #[derive(Debug, Parser)]
 struct LogoutCommand {
     #[clap(skip)]
     config_overrides: CliConfigOverrides,
+
+    /// Sign out of the ChatGPT account added with `codex login --account
+    /// NAME` instead of the default sign-in.
+    #[arg(long = "account", value_name = "NAME")]
+    account: Option<String>,
+
+    /// Remove the API key stored for the project at PATH (default: the
+    /// current repository) with `codex login --with-api-key --project`.
+    #[arg(
+        long = "project",
+        value_name = "PATH",
+        conflicts_with = "account",
+        num_args = 0..=1,
+        default_missing_value = "."
+    )]
+    project: Option<PathBuf>,
 }
 
 #[derive(Debug, Parser)]
@@ -303,50 +793,19 @@ fn format_exit_messages(exit_info: AppExitInfo, color_enabled: bool) -> Vec<Stri
 }
 
 /// Handle the app exit and print the results. Optionally run the update action.
-fn handle_app_exit(exit_info: AppExitInfo) -> anyhow::Result<()> {
+fn handle_app_exit(exit_info: AppExitInfo, output: OutputPolicy) -> anyhow::Result<()> {
     let update_action = exit_info.update_action;
-    let color_enabled = supports_color::on(Stream::Stdout).is_some();
-    for line in format_exit_messages(exit_info, color_enabled) {
+    let update_version = exit_info.update_version.clone();
+    let exit_signal = exit_info.exit_signal;
+    for line in format_exit_messages(exit_info, output.color) {
         println!("{line}");
     }
-    if let Some(action) = update_action {
-        run_update_action(action)?;
+    if let Some(signal) = exit_signal {
+        std::process::exit(128 + signal);
     }
-    Ok(())
-}
-
-/// Run the update action and print the result.
-fn run_update_action(action: UpdateAction) -> anyhow::Result<()> {
-    println!();
-    let cmd_str = action.command_str();
-    println!("Updating Codex via `{cmd_str}`...");
-
-    let status = {
-        #[cfg(windows)]
-        {
-            // On Windows, run via cmd.exe so .CMD/.BAT are correctly resolved (PATHEXT semantics).
-            std::process::Command::new("cmd")
-                .args(["/C", &cmd_str])
-                .status()?
-        }
-        #[cfg(not(windows))]
-        {
-            let (cmd, args) = action.command_args();
-            let command_path = crate::wsl_paths::normalize_for_wsl(cmd);
-            let normalized_args: Vec<String> = args
-                .iter()
-                .map(crate::wsl_paths::normalize_for_wsl)
-                .collect();
-            std::process::Command::new(&command_path)
-                .args(&normalized_args)
-                .status()?
-        }
-    };
-    if !status.success() {
-        anyhow::bail!("`{cmd_str}` failed with status {status}");
+    if let Some(action) = update_action {
+        self_update::run_update_action(action, update_version.as_deref(), output)?;
     }
-    println!();
-    println!("🎉 Update ran successfully! Please restart Codex.");
     Ok(())
 }
 
@@ -388,6 +847,39 @@ impl FeatureToggles {
     }
 }
 
+#[derive(Debug, Default, Parser, Clone)]
+struct SessionTags {
+    /// Tag the session for spend attribution (repeatable), e.g. `--tag team=payments`.
+    /// Equivalent to `-c tags.<key>=<value>`.
+    #[arg(long = "tag", value_name = "KEY=VALUE", action = clap::ArgAction::Append, global = true)]
+    tags: Vec<String>,
+}
+
+impl SessionTags {
+    fn to_overrides(&self) -> anyhow::Result<Vec<String>> {
+        self.tags
+            .iter()
+            .map(|tag| {
+                let Some((key, value)) = tag.split_once('=') else {
+                    anyhow::bail!("Invalid tag (expected KEY=VALUE): {tag}");
+                };
+                let key = key.trim();
+                if key.is_empty()
+                    || !key
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                {
+                    anyhow::bail!(
+                        "Invalid tag key `{key}`: use letters, digits, `_`, and `-` only"
+                    );
+                }
+                let value = toml::Value::String(value.trim().to_string());
+                Ok(format!("tags.{key}={value}"))
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Parser)]
 struct FeaturesCli {
     #[command(subcommand)]
@@ -427,16 +919,33 @@ fn main() -> anyhow::Result<()> {
 }
 
 async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()> {
+    // Checked before parsing so that clap does not exit on `--version` first.
+    if std::env::args_os()
+        .skip(1)
+        .any(|arg| arg == self_update::SELFTEST_FLAG)
+    {
+        return self_update::run_selftest().await;
+    }
+
     let MultitoolCli {
         config_overrides: mut root_config_overrides,
         feature_toggles,
+        session_tags,
         mut interactive,
+        color,
         subcommand,
-    } = MultitoolCli::parse();
+    } = MultitoolCli::from_arg_matches(
+        &help_topics::annotate(MultitoolCli::command()).get_matches(),
+    )
+    .unwrap_or_else(|err| err.exit());
+    let output = OutputPolicy::resolve(color);
 
     // Fold --enable/--disable into config overrides so they flow to all subcommands.
     let toggle_overrides = feature_toggles.to_overrides()?;
     root_config_overrides.raw_overrides.extend(toggle_overrides);
+    root_config_overrides
+        .raw_overrides
+        .extend(session_tags.to_overrides()?);
 
     match subcommand {
         None => {
@@ -445,9 +954,12 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
                 root_config_overrides.clone(),
             );
             let exit_info = codex_tui::run_main(interactive, codex_linux_sandbox_exe).await?;
-            handle_app_exit(exit_info)?;
+            handle_app_exit(exit_info, output)?;
         }
         Some(Subcommand::Exec(mut exec_cli)) => {
+            if exec_cli.color == Color::Auto {
+                exec_cli.color = color;
+            }
             prepend_config_flags(
                 &mut exec_cli.config_overrides,
                 root_config_overrides.clone(),
@@ -457,6 +969,27 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
         Some(Subcommand::Review(review_args)) => {
             let mut exec_cli = ExecCli::try_parse_from(["codex", "exec"])?;
             exec_cli.command = Some(ExecCommand::Review(review_args));
+            exec_cli.color = color;
+            prepend_config_flags(
+                &mut exec_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            codex_exec::run_main(exec_cli, codex_linux_sandbox_exe).await?;
+        }
+        Some(Subcommand::Run(run_cli)) => {
+            let prompts_dir = find_codex_home()?.join("prompts");
+            let prompt =
+                run_cmd::expand_template(&prompts_dir, &run_cli.template, &run_cli.vars).await?;
+            let mut exec_cli = ExecCli::try_parse_from(
+                ["codex", "exec"]
+                    .into_iter()
+                    .map(String::from)
+                    .chain(run_cli.exec_args),
+            )?;
+            exec_cli.prompt = Some(prompt);
+            if exec_cli.color == Color::Auto {
+                exec_cli.color = color;
+            }
             prepend_config_flags(
                 &mut exec_cli.config_overrides,
                 root_config_overrides.clone(),
@@ -500,7 +1033,54 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
                 config_overrides,
             );
             let exit_info = codex_tui::run_main(interactive, codex_linux_sandbox_exe).await?;
-            handle_app_exit(exit_info)?;
+            handle_app_exit(exit_info, output)?;
+        }
+        Some(Subcommand::Fork(ForkCommand {
+            session_id,
+            at_turn,
+            config_overrides,
+        })) => {
+            interactive = finalize_resume_interactive(
+                interactive,
+                root_config_overrides.clone(),
+                Some(session_id),
+                false,
+                false,
+                config_overrides,
+            );
+            interactive.fork_at_turn = Some(at_turn);
+            let exit_info = codex_tui::run_main(interactive, codex_linux_sandbox_exe).await?;
+            handle_app_exit(exit_info, output)?;
+        }
+        Some(Subcommand::Restore(RestoreCommand {
+            checkpoint: None, ..
+        })) => {
+            restore_cmd::print_checkpoints(&find_codex_home()?);
+        }
+        Some(Subcommand::Restore(RestoreCommand {
+            checkpoint: Some(name),
+            config_overrides,
+        })) => {
+            let codex_home = find_codex_home()?;
+            let checkpoint = restore_cmd::restore_files(&codex_home, &name).await?;
+            // A checkpoint taken before the first turn restores to a new session.
+            let session_id = (checkpoint.turns > 0).then(|| checkpoint.conversation_id.to_string());
+            interactive = finalize_resume_interactive(
+                interactive,
+                root_config_overrides.clone(),
+                session_id,
+                false,
+                false,
+                config_overrides,
+            );
+            if checkpoint.turns > 0 {
+                interactive.fork_at_turn = Some(checkpoint.turns);
+            } else {
+                interactive.resume_picker = false;
+                interactive.cwd = Some(checkpoint.cwd);
+            }
+            let exit_info = codex_tui::run_main(interactive, codex_linux_sandbox_exe).await?;
+            handle_app_exit(exit_info, output)?;
         }
         Some(Subcommand::Login(mut login_cli)) => {
             prepend_config_flags(
@@ -511,12 +1091,22 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
                 Some(LoginSubcommand::Status) => {
                     run_login_status(login_cli.config_overrides).await;
                 }
+                Some(LoginSubcommand::Migrate(migrate)) => {
+                    let to = match migrate.to {
+                        CredentialsStore::Keyring => AuthCredentialsStoreMode::Keyring,
+                        CredentialsStore::File => AuthCredentialsStoreMode::File,
+                    };
+                    run_login_migrate(login_cli.config_overrides, to).await;
+                }
                 None => {
-                    if login_cli.use_device_code {
+                    if login_cli.use_device_code || login_cli.qr_code {
                         run_login_with_device_code(
                             login_cli.config_overrides,
                             login_cli.issuer_base_url,
                             login_cli.client_id,
+                            login_cli.account,
+                            login_cli.qr_code,
+                            output.color,
                         )
                         .await;
                     } else if login_cli.api_key.is_some() {
@@ -526,9 +1116,15 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
                         std::process::exit(1);
                     } else if login_cli.with_api_key {
                         let api_key = read_api_key_from_stdin();
-                        run_login_with_api_key(login_cli.config_overrides, api_key).await;
+                        run_login_with_api_key(
+                            login_cli.config_overrides,
+                            api_key,
+                            login_cli.skip_validation,
+                            login_cli.project,
+                        )
+                        .await;
                     } else {
-                        run_login_with_chatgpt(login_cli.config_overrides).await;
+                        run_login_with_chatgpt(login_cli.config_overrides, login_cli.account).await;
                     }
                 }
             }
@@ -538,10 +1134,263 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
                 &mut logout_cli.config_overrides,
                 root_config_overrides.clone(),
             );
-            run_logout(logout_cli.config_overrides).await;
+            run_logout(
+                logout_cli.config_overrides,
+                logout_cli.account,
+                logout_cli.project,
+            )
+            .await;
         }
         Some(Subcommand::Completion(completion_cli)) => {
-            print_completion(completion_cli);
+            run_completion(completion_cli)?;
+        }
+        Some(Subcommand::Config(mut config_cli)) => {
+            prepend_config_flags(
+                &mut config_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            let overrides = ConfigOverrides {
+                config_profile: interactive.config_profile.clone(),
+                cwd: interactive.cwd.clone(),
+                model: interactive.model.clone(),
+                system_prompt: interactive.system_prompt(),
+                ..Default::default()
+            };
+            config_cmd::run(config_cli, overrides).await?;
+        }
+        Some(Subcommand::Limits(mut limits_cli)) => {
+            prepend_config_flags(
+                &mut limits_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            let cli_kv_overrides = limits_cli
+                .config_overrides
+                .parse_overrides()
+                .map_err(anyhow::Error::msg)?;
+            let overrides = ConfigOverrides {
+                config_profile: interactive.config_profile.clone(),
+                ..Default::default()
+            };
+            let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides).await?;
+            match limits_cli.action {
+                Some(LimitsSubcommand::History) => {
+                    limits_cmd::run_history(&config, limits_cli.json)?;
+                }
+                None => limits_cmd::run(&config, limits_cli.json).await?,
+            }
+        }
+        Some(Subcommand::Sessions(mut sessions_cli)) => {
+            prepend_config_flags(
+                &mut sessions_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            let cli_kv_overrides = sessions_cli
+                .config_overrides
+                .parse_overrides()
+                .map_err(anyhow::Error::msg)?;
+            let overrides = ConfigOverrides {
+                config_profile: interactive.config_profile.clone(),
+                ..Default::default()
+            };
+            let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides).await?;
+            match sessions_cli.action {
+                Some(SessionsSubcommand::Export(export_cli)) => {
+                    session_export::run(
+                        &config,
+                        &export_cli.session_id,
+                        export_cli.format,
+                        export_cli.redact,
+                        export_cli.output,
+                    )
+                    .await?;
+                }
+                None => {
+                    let grep = sessions_cli
+                        .grep
+                        .map(|pattern| {
+                            regex_lite::RegexBuilder::new(&pattern)
+                                .case_insensitive(true)
+                                .build()
+                                .map_err(|err| anyhow::anyhow!("invalid --grep pattern: {err}"))
+                        })
+                        .transpose()?;
+                    let filter = sessions_cmd::SessionFilter {
+                        since: sessions_cli.since,
+                        cwd: sessions_cli
+                            .cwd
+                            .map(|dir| dir.canonicalize().unwrap_or(dir)),
+                        grep,
+                        limit: sessions_cli.limit,
+                    };
+                    sessions_cmd::run(&config, &filter, sessions_cli.json)?;
+                }
+            }
+        }
+        Some(Subcommand::Replay(mut replay_cli)) => {
+            prepend_config_flags(
+                &mut replay_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            let cli_kv_overrides = replay_cli
+                .config_overrides
+                .parse_overrides()
+                .map_err(anyhow::Error::msg)?;
+            let overrides = ConfigOverrides {
+                config_profile: interactive.config_profile.clone(),
+                ..Default::default()
+            };
+            let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides).await?;
+            replay::run(
+                &config,
+                &replay_cli.session_id,
+                replay_cli.from_turn,
+                !replay_cli.no_pause,
+                output,
+            )
+            .await?;
+        }
+        Some(Subcommand::Undo(mut undo_cli)) => {
+            prepend_config_flags(
+                &mut undo_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            let cli_kv_overrides = undo_cli
+                .config_overrides
+                .parse_overrides()
+                .map_err(anyhow::Error::msg)?;
+            let overrides = ConfigOverrides {
+                config_profile: interactive.config_profile.clone(),
+                ..Default::default()
+            };
+            let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides).await?;
+            undo_cmd::run(&config, undo_cli.session_id.as_deref()).await?;
+        }
+        Some(Subcommand::Queue(mut queue_cli)) => {
+            prepend_config_flags(
+                &mut queue_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            let cli_kv_overrides = queue_cli
+                .config_overrides
+                .parse_overrides()
+                .map_err(anyhow::Error::msg)?;
+            let overrides = ConfigOverrides {
+                config_profile: interactive.config_profile.clone(),
+                ..Default::default()
+            };
+            let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides).await?;
+            match queue_cli.action {
+                QueueSubcommand::Add { prompt, cwd } => queue_cmd::add(&config, prompt, cwd)?,
+                QueueSubcommand::List { json } => queue_cmd::list(&config, json)?,
+                QueueSubcommand::Run { mut exec_args } => {
+                    if let Some(profile) = &interactive.config_profile {
+                        exec_args.splice(0..0, ["--profile".to_string(), profile.clone()]);
+                    }
+                    queue_cmd::run(
+                        &config,
+                        &queue_cli.config_overrides.raw_overrides,
+                        &exec_args,
+                    )
+                    .await?;
+                }
+                QueueSubcommand::Remove { id } => queue_cmd::remove(&config, Some(id))?,
+                QueueSubcommand::Clear => queue_cmd::remove(&config, None)?,
+            }
+        }
+        Some(Subcommand::Usage(mut usage_cli)) => {
+            prepend_config_flags(
+                &mut usage_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            let cli_kv_overrides = usage_cli
+                .config_overrides
+                .parse_overrides()
+                .map_err(anyhow::Error::msg)?;
+            let overrides = ConfigOverrides {
+                config_profile: interactive.config_profile.clone(),
+                ..Default::default()
+            };
+            let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides).await?;
+            match usage_cli.action {
+                UsageSubcommand::Report(report_cli) => {
+                    let options = usage_cmd::ReportOptions {
+                        group_by: report_cli.group_by,
+                        since: report_cli.since,
+                        until: report_cli.until,
+                    };
+                    usage_cmd::run_report(&config, &options, report_cli.format)?;
+                }
+            }
+        }
+        Some(Subcommand::Audit(mut audit_cli)) => {
+            prepend_config_flags(
+                &mut audit_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            let cli_kv_overrides = audit_cli
+                .config_overrides
+                .parse_overrides()
+                .map_err(anyhow::Error::msg)?;
+            let overrides = ConfigOverrides {
+                config_profile: interactive.config_profile.clone(),
+                ..Default::default()
+            };
+            let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides).await?;
+            let filter = audit_cmd::AuditFilter {
+                events: audit_cli.events,
+                auth: audit_cli.auth,
+                since: audit_cli.since,
+                until: audit_cli.until,
+            };
+            audit_cmd::run(&config, &filter, audit_cli.json)?;
+        }
+        Some(Subcommand::Auth(mut auth_cli)) => {
+            prepend_config_flags(
+                &mut auth_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            let cli_kv_overrides = auth_cli
+                .config_overrides
+                .parse_overrides()
+                .map_err(anyhow::Error::msg)?;
+            let overrides = ConfigOverrides {
+                config_profile: interactive.config_profile.clone(),
+                ..Default::default()
+            };
+            let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides).await?;
+            match auth_cli.action {
+                AuthSubcommand::Status(status_cli) => {
+                    auth_cmd::run_status(&config, status_cli.json)?;
+                }
+            }
+        }
+        Some(Subcommand::Doctor(mut doctor_cli)) => {
+            prepend_config_flags(
+                &mut doctor_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            let cli_kv_overrides = doctor_cli
+                .config_overrides
+                .parse_overrides()
+                .map_err(anyhow::Error::msg)?;
+            let overrides = ConfigOverrides {
+                config_profile: interactive.config_profile.clone(),
+                ..Default::default()
+            };
+            let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides).await?;
+            doctor::run(&config, doctor_cli.json)?;
+        }
+        Some(Subcommand::Help(help_cli)) => {
+            let root = help_topics::annotate(MultitoolCli::command());
+            if help_cli.markdown {
+                print!("{}", help_topics::render_markdown(&root));
+            } else if let Some(dir) = help_cli.man {
+                for page in help_topics::write_manpages(root, &dir)? {
+                    println!("{}", page.display());
+                }
+            } else {
+                help_topics::print_help(root, &help_cli.target)?;
+            }
         }
         Some(Subcommand::Cloud(mut cloud_cli)) => {
             prepend_config_flags(
@@ -573,6 +1422,40 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
                 )
                 .await?;
             }
+            SandboxCommand::Selftest(mut selftest_cli) => {
+                prepend_config_flags(
+                    &mut selftest_cli.config_overrides,
+                    root_config_overrides.clone(),
+                );
+                codex_cli::debug_sandbox::run_sandbox_selftest(
+                    selftest_cli,
+                    codex_linux_sandbox_exe,
+                )
+                .await?;
+            }
+            SandboxCommand::Probe(probe_cli) => {
+                codex_cli::debug_sandbox::run_sandbox_probe(probe_cli);
+            }
+            SandboxCommand::SimulateLimit(mut simulate_cli) => {
+                prepend_config_flags(
+                    &mut simulate_cli.config_overrides,
+                    root_config_overrides.clone(),
+                );
+                let cli_kv_overrides = simulate_cli
+                    .config_overrides
+                    .parse_overrides()
+                    .map_err(anyhow::Error::msg)?;
+                let overrides = ConfigOverrides {
+                    config_profile: interactive.config_profile.clone(),
+                    ..Default::default()
+                };
+                let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides).await?;
+                let resets_at = simulate_cli.resets_at.unwrap_or_else(|| {
+                    chrono::Utc::now()
+                        + chrono::Duration::minutes(i64::from(simulate_cli.resets_in.unwrap_or(1)))
+                });
+                simulate_limit::run(&config, simulate_cli.kind, resets_at, simulate_cli.json)?;
+            }
             SandboxCommand::Windows(mut windows_cli) => {
                 prepend_config_flags(
                     &mut windows_cli.config_overrides,
@@ -713,6 +1596,13 @@ fn merge_resume_cli_flags(interactive: &mut TuiCli, resume_cli: TuiCli) {
     if !resume_cli.add_dir.is_empty() {
         interactive.add_dir.extend(resume_cli.add_dir);
     }
+    if !resume_cli.root.is_empty() {
+        interactive.root.extend(resume_cli.root);
+    }
+    if let Some(file) = resume_cli.system_prompt_file {
+        interactive.system_prompt_file = Some(file);
+        interactive.append_system_prompt = resume_cli.append_system_prompt;
+    }
     if let Some(prompt) = resume_cli.prompt {
         interactive.prompt = Some(prompt);
     }
@@ -723,10 +1613,29 @@ fn merge_resume_cli_flags(interactive: &mut TuiCli, resume_cli: TuiCli) {
         .extend(resume_cli.config_overrides.raw_overrides);
 }
 
-fn print_completion(cmd: CompletionCommand) {
+fn run_completion(cmd: CompletionCommand) -> anyhow::Result<()> {
     let mut app = MultitoolCli::command();
     let name = "codex";
-    generate(cmd.shell, &mut app, name, &mut std::io::stdout());
+    if !cmd.install && !cmd.uninstall {
+        generate(
+            cmd.shell.unwrap_or(Shell::Bash),
+            &mut app,
+            name,
+            &mut std::io::stdout(),
+        );
+        return Ok(());
+    }
+
+    let shell = match cmd.shell {
+        Some(shell) => shell,
+        None => completion::detect_shell()?,
+    };
+    if cmd.uninstall {
+        return completion::uninstall(shell);
+    }
+    let mut script = Vec::new();
+    generate(shell, &mut app, name, &mut script);
+    completion::install(shell, &script)
 }
 
 #[cfg(test)]
@@ -744,6 +1653,8 @@ mod tests {
             config_overrides: root_overrides,
             subcommand,
             feature_toggles: _,
+            session_tags: _,
+            color: _,
         } = cli;
 
         let Subcommand::Resume(ResumeCommand {
@@ -778,6 +1689,8 @@ mod tests {
                 .map(ConversationId::from_string)
                 .map(Result::unwrap),
             update_action: None,
+            update_version: None,
+            exit_signal: None,
         }
     }
 
@@ -787,6 +1700,8 @@ mod tests {
             token_usage: TokenUsage::default(),
             conversation_id: None,
             update_action: None,
+            update_version: None,
+            exit_signal: None,
         };
         let lines = format_exit_messages(exit_info, false);
         assert!(lines.is_empty());
@@ -814,6 +1729,47 @@ mod tests {
         assert!(lines[1].contains("\u{1b}[36m"));
     }
 
+    #[test]
+    fn output_policy_respects_explicit_color_choice() {
+        assert_eq!(
+            OutputPolicy::resolve(Color::Always),
+            OutputPolicy {
+                color: true,
+                ascii_only: false,
+            }
+        );
+        assert_eq!(
+            OutputPolicy::resolve(Color::Never),
+            OutputPolicy {
+                color: false,
+                ascii_only: true,
+            }
+        );
+    }
+
+    #[test]
+    fn completion_install_flags_parse() {
+        let cli = MultitoolCli::try_parse_from(["codex", "completion", "--install", "zsh"])
+            .expect("parse");
+        let Some(Subcommand::Completion(cmd)) = cli.subcommand else {
+            panic!("expected completion subcommand");
+        };
+        assert!(cmd.install);
+        assert_eq!(cmd.shell, Some(Shell::Zsh));
+
+        assert!(
+            MultitoolCli::try_parse_from(["codex", "completion", "--install", "--uninstall"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn root_color_flag_parses() {
+        let cli = MultitoolCli::try_parse_from(["codex", "--color", "never", "exec", "hi"])
+            .expect("parse");
+        assert_eq!(cli.color, Color::Never);
+    }
+
     #[test]
     fn resume_model_flag_applies_when_no_root_flags() {
         let interactive = finalize_from_args(["codex", "resume", "-m", "gpt-5.1-test"].as_ref());
@@ -958,4 +1914,28 @@ mod tests {
             .expect_err("feature should be rejected");
         assert_eq!(err.to_string(), "Unknown feature flag: does_not_exist");
     }
+
+    #[test]
+    fn session_tags_generate_overrides() {
+        let tags = SessionTags {
+            tags: vec!["team=payments".to_string(), "ticket=PAY-123".to_string()],
+        };
+        assert_eq!(
+            tags.to_overrides().expect("valid tags"),
+            vec![
+                r#"tags.team="payments""#.to_string(),
+                r#"tags.ticket="PAY-123""#.to_string(),
+            ]
+        );
+
+        let err = SessionTags {
+            tags: vec!["team.name=payments".to_string()],
+        }
+        .to_overrides()
+        .expect_err("dotted key should be rejected");
+        assert_eq!(
+            err.to_string(),
+            "Invalid tag key `team.name`: use letters, digits, `_`, and `-` only"
+        );
+    }
 }
@@ -18,6 +18,7 @@ use codex_core::features::Feature;
 use codex_core::mcp::auth::compute_auth_statuses;
 use codex_core::protocol::McpAuthStatus;
 use codex_rmcp_client::delete_oauth_tokens;
+use codex_rmcp_client::perform_device_code_login;
 use codex_rmcp_client::perform_oauth_login;
 use codex_rmcp_client::supports_oauth_login;
 
@@ -139,6 +140,10 @@ pub struct AddMcpStreamableHttpArgs {
         requires = "url"
     )]
     pub bearer_token_env_var: Option<String>,
+
+    /// Connect with the older HTTP+SSE transport instead of streamable HTTP.
+    #[arg(long, requires = "url")]
+    pub sse: bool,
 }
 
 #[derive(Debug, clap::Parser)]
@@ -155,6 +160,11 @@ pub struct LoginArgs {
     /// Comma-separated list of OAuth scopes to request.
     #[arg(long, value_delimiter = ',', value_name = "SCOPE,SCOPE")]
     pub scopes: Vec<String>,
+
+    /// Log in by entering a code on another device instead of through a
+    /// local browser redirect, e.g. over SSH.
+    #[arg(long)]
+    pub device_code: bool,
 }
 
 #[derive(Debug, clap::Parser)]
@@ -244,6 +254,21 @@ async fn run_add(config_overrides: &CliConfigOverrides, add_args: AddArgs) -> Re
                 Some(AddMcpStreamableHttpArgs {
                     url,
                     bearer_token_env_var,
+                    sse: true,
+                }),
+            ..
+        } => McpServerTransportConfig::Sse {
+            url,
+            bearer_token_env_var,
+            http_headers: None,
+            env_http_headers: None,
+        },
+        AddMcpTransportArgs {
+            streamable_http:
+                Some(AddMcpStreamableHttpArgs {
+                    url,
+                    bearer_token_env_var,
+                    sse: false,
                 }),
             ..
         } => McpServerTransportConfig::StreamableHttp {
@@ -359,7 +384,11 @@ async fn run_login(config_overrides: &CliConfigOverrides, login_args: LoginArgs)
         );
     }
 
-    let LoginArgs { name, scopes } = login_args;
+    let LoginArgs {
+        name,
+        scopes,
+        device_code,
+    } = login_args;
 
     let Some(server) = config.mcp_servers.get(&name) else {
         bail!("No MCP server named '{name}' found.");
@@ -375,15 +404,27 @@ async fn run_login(config_overrides: &CliConfigOverrides, login_args: LoginArgs)
         _ => bail!("OAuth login is only supported for streamable HTTP servers."),
     };
 
-    perform_oauth_login(
-        &name,
-        &url,
-        config.mcp_oauth_credentials_store_mode,
-        http_headers,
-        env_http_headers,
-        &scopes,
-    )
-    .await?;
+    if device_code {
+        perform_device_code_login(
+            &name,
+            &url,
+            config.mcp_oauth_credentials_store_mode,
+            http_headers,
+            env_http_headers,
+            &scopes,
+        )
+        .await?;
+    } else {
+        perform_oauth_login(
+            &name,
+            &url,
+            config.mcp_oauth_credentials_store_mode,
+            http_headers,
+            env_http_headers,
+            &scopes,
+        )
+        .await?;
+    }
     println!("Successfully logged in to MCP server '{name}'.");
     Ok(())
 }
@@ -461,9 +502,15 @@ async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) ->
                         bearer_token_env_var,
                         http_headers,
                         env_http_headers,
+                    }
+                    | McpServerTransportConfig::Sse {
+                        url,
+                        bearer_token_env_var,
+                        http_headers,
+                        env_http_headers,
                     } => {
                         serde_json::json!({
-                            "type": "streamable_http",
+                            "type": transport_type(&cfg.transport),
                             "url": url,
                             "bearer_token_env_var": bearer_token_env_var,
                             "http_headers": http_headers,
@@ -543,6 +590,11 @@ async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) ->
                 url,
                 bearer_token_env_var,
                 ..
+            }
+            | McpServerTransportConfig::Sse {
+                url,
+                bearer_token_env_var,
+                ..
             } => {
                 let status = if cfg.enabled {
                     "enabled".to_string()
@@ -707,8 +759,14 @@ async fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Re
                 bearer_token_env_var,
                 http_headers,
                 env_http_headers,
+            }
+            | McpServerTransportConfig::Sse {
+                url,
+                bearer_token_env_var,
+                http_headers,
+                env_http_headers,
             } => serde_json::json!({
-                "type": "streamable_http",
+                "type": transport_type(&server.transport),
                 "url": url,
                 "bearer_token_env_var": bearer_token_env_var,
                 "http_headers": http_headers,
@@ -784,8 +842,14 @@ async fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Re
             bearer_token_env_var,
             http_headers,
             env_http_headers,
+        }
+        | McpServerTransportConfig::Sse {
+            url,
+            bearer_token_env_var,
+            http_headers,
+            env_http_headers,
         } => {
-            println!("  transport: streamable_http");
+            println!("  transport: {}", transport_type(&server.transport));
             println!("  url: {url}");
             let bearer_token_display = bearer_token_env_var.as_deref().unwrap_or("-");
             println!("  bearer_token_env_var: {bearer_token_display}");
@@ -828,6 +892,14 @@ async fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Re
     Ok(())
 }
 
+fn transport_type(transport: &McpServerTransportConfig) -> &'static str {
+    match transport {
+        McpServerTransportConfig::Stdio { .. } => "stdio",
+        McpServerTransportConfig::StreamableHttp { .. } => "streamable_http",
+        McpServerTransportConfig::Sse { .. } => "sse",
+    }
+}
+
 fn parse_env_pair(raw: &str) -> Result<(String, String), String> {
     let mut parts = raw.splitn(2, '=');
     let key = parts
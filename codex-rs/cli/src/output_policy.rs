@@ -0,0 +1,40 @@
+//! Decides whether the CLI's own (non-TUI) output may use ANSI colors and emoji.
+
+use codex_exec::Color;
+use std::io::IsTerminal;
+use supports_color::Stream;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct OutputPolicy {
+    /// Emit ANSI color escapes.
+    pub color: bool,
+    /// Avoid emoji and other non-ASCII decorations (e.g. when output is captured by CI logs).
+    pub ascii_only: bool,
+}
+
+impl OutputPolicy {
+    /// `auto` defers to `supports_color`, which honors `NO_COLOR`, `FORCE_COLOR`, and whether
+    /// stdout is a terminal, and drops decorations when stdout is not a terminal. `always` keeps
+    /// both and `never` drops both.
+    pub(crate) fn resolve(color: Color) -> Self {
+        match color {
+            Color::Always => Self {
+                color: true,
+                ascii_only: false,
+            },
+            Color::Never => Self {
+                color: false,
+                ascii_only: true,
+            },
+            Color::Auto => Self {
+                color: supports_color::on(Stream::Stdout).is_some(),
+                ascii_only: !std::io::stdout().is_terminal(),
+            },
+        }
+    }
+
+    /// Returns `decorated` unless the policy asks for plain ASCII output.
+    pub(crate) fn decoration<'a>(&self, decorated: &'a str) -> &'a str {
+        if self.ascii_only { "" } else { decorated }
+    }
+}
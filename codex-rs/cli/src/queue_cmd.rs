@@ -0,0 +1,501 @@
+//! `codex queue`: prompts saved with `codex queue add` and run back to back
+//! by `codex queue run`, each in a fresh non-interactive session like
+//! `codex exec`, such as a series of refactors left to run overnight.
+//!
+//! The queue is kept in `queue.json` in the state directory, and each task
+//! records how it ended, the agent's last message, and the session it ran
+//! in, which `codex resume` can continue. Tasks run with `--wait-for-reset`,
+//! so a usage limit pauses the queue until the limit resets instead of
+//! failing the remaining tasks, and the run stops before a task when the
+//! daily or monthly `[budget]` cap is used up.
+
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use chrono::Local;
+use chrono::TimeZone;
+use codex_core::config::Config;
+use codex_core::recorded_cap_reached;
+use codex_core::state_dir::state_dir;
+use codex_exec::exec_events::ThreadEvent;
+use codex_exec::exec_events::ThreadItemDetails;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::BufReader;
+
+const QUEUE_FILE: &str = "queue.json";
+/// Characters of a prompt shown in the list and progress lines.
+const PROMPT_PREVIEW_CHARS: usize = 60;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum TaskStatus {
+    #[default]
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl TaskStatus {
+    fn label(self) -> &'static str {
+        match self {
+            TaskStatus::Pending => "pending",
+            TaskStatus::Running => "running",
+            TaskStatus::Succeeded => "succeeded",
+            TaskStatus::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct QueuedTask {
+    id: u64,
+    prompt: String,
+    cwd: PathBuf,
+    /// Unix seconds.
+    added_at: i64,
+    #[serde(default)]
+    status: TaskStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    finished_at: Option<i64>,
+    /// The agent's last message, or why the task failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Queue {
+    tasks: Vec<QueuedTask>,
+}
+
+impl Queue {
+    fn path(config: &Config) -> PathBuf {
+        state_dir(&config.codex_home).join(QUEUE_FILE)
+    }
+
+    fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .with_context(|| format!("failed to parse {}", path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    /// Writes the queue through a temporary file, so an interrupted write
+    /// cannot leave it truncated.
+    fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    fn add(&mut self, prompt: String, cwd: PathBuf, now: i64) -> u64 {
+        let id = self.tasks.iter().map(|task| task.id).max().unwrap_or(0) + 1;
+        self.tasks.push(QueuedTask {
+            id,
+            prompt,
+            cwd,
+            added_at: now,
+            status: TaskStatus::Pending,
+            session_id: None,
+            finished_at: None,
+            result: None,
+        });
+        id
+    }
+
+    fn next_pending(&self) -> Option<usize> {
+        self.tasks
+            .iter()
+            .position(|task| task.status == TaskStatus::Pending)
+    }
+}
+
+/// Adds `prompt` to the queue, to run in `cwd`.
+pub(crate) fn add(config: &Config, prompt: String, cwd: Option<PathBuf>) -> Result<()> {
+    if prompt.trim().is_empty() {
+        bail!("the prompt is empty");
+    }
+    let cwd = match cwd {
+        Some(dir) => dir
+            .canonicalize()
+            .with_context(|| format!("no such directory: {}", dir.display()))?,
+        None => config.cwd.clone(),
+    };
+    let path = Queue::path(config);
+    let mut queue = Queue::load(&path)?;
+    let id = queue.add(prompt, cwd, Local::now().timestamp());
+    queue.save(&path)?;
+    let pending = queue
+        .tasks
+        .iter()
+        .filter(|task| task.status == TaskStatus::Pending)
+        .count();
+    println!("Queued task {id} ({pending} pending). Start the queue with `codex queue run`.");
+    Ok(())
+}
+
+/// Prints the queue.
+pub(crate) fn list(config: &Config, json: bool) -> Result<()> {
+    let queue = Queue::load(&Queue::path(config))?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&queue.tasks)?);
+    } else {
+        print!("{}", render_list(&queue.tasks, &Local));
+    }
+    Ok(())
+}
+
+/// Removes task `id`, or every finished task with `id` of `None`.
+pub(crate) fn remove(config: &Config, id: Option<u64>) -> Result<()> {
+    let path = Queue::path(config);
+    let mut queue = Queue::load(&path)?;
+    let before = queue.tasks.len();
+    match id {
+        Some(id) => {
+            let Some(task) = queue.tasks.iter().find(|task| task.id == id) else {
+                bail!("there is no task {id} in the queue");
+            };
+            if task.status == TaskStatus::Running {
+                bail!("task {id} is running; stop `codex queue run` first");
+            }
+            queue.tasks.retain(|task| task.id != id);
+        }
+        None => queue
+            .tasks
+            .retain(|task| matches!(task.status, TaskStatus::Pending | TaskStatus::Running)),
+    }
+    queue.save(&path)?;
+    println!("Removed {} task(s).", before - queue.tasks.len());
+    Ok(())
+}
+
+/// Runs the pending tasks one after another with `codex exec`, passing
+/// `config_flags` (`-c key=value`) and `exec_args` on to each.
+pub(crate) async fn run(
+    config: &Config,
+    config_flags: &[String],
+    exec_args: &[String],
+) -> Result<()> {
+    let path = Queue::path(config);
+    let codex = std::env::current_exe().context("failed to locate the codex executable")?;
+
+    // A task still marked running was cut off by an earlier run; start it
+    // again.
+    let mut queue = Queue::load(&path)?;
+    for task in &mut queue.tasks {
+        if task.status == TaskStatus::Running {
+            task.status = TaskStatus::Pending;
+        }
+    }
+    queue.save(&path)?;
+
+    let interrupted = Arc::new(AtomicBool::new(false));
+    {
+        let interrupted = Arc::clone(&interrupted);
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                interrupted.store(true, Ordering::SeqCst);
+            }
+        });
+    }
+
+    let (mut succeeded, mut failed) = (0, 0);
+    loop {
+        // Reload before each task, so tasks added meanwhile are picked up.
+        let mut queue = Queue::load(&path)?;
+        let Some(index) = queue.next_pending() else {
+            break;
+        };
+        if let Some(message) = recorded_cap_reached(config) {
+            eprintln!("Stopping the queue: {message}");
+            break;
+        }
+        let remaining = queue
+            .tasks
+            .iter()
+            .filter(|task| task.status == TaskStatus::Pending)
+            .count();
+        queue.tasks[index].status = TaskStatus::Running;
+        queue.save(&path)?;
+        let task = queue.tasks[index].clone();
+        eprintln!(
+            "Task {} ({remaining} left) in {}: {}",
+            task.id,
+            task.cwd.display(),
+            preview(&task.prompt)
+        );
+
+        let started = Instant::now();
+        let mut outcome = run_task(&codex, &task, config_flags, exec_args).await;
+        let stopped = interrupted.load(Ordering::SeqCst);
+        if stopped {
+            outcome.success = false;
+            outcome.result = Some("interrupted".to_string());
+        }
+        let elapsed = format_elapsed(started.elapsed());
+
+        let mut queue = Queue::load(&path)?;
+        if let Some(entry) = queue.tasks.iter_mut().find(|entry| entry.id == task.id) {
+            entry.finished_at = Some(Local::now().timestamp());
+            entry.session_id = outcome.session_id.clone();
+            entry.result = outcome.result.clone();
+            entry.status = if outcome.success {
+                TaskStatus::Succeeded
+            } else {
+                TaskStatus::Failed
+            };
+            queue.save(&path)?;
+        }
+
+        let session = outcome
+            .session_id
+            .as_deref()
+            .map(|id| format!(" (session {id})"))
+            .unwrap_or_default();
+        if outcome.success {
+            succeeded += 1;
+            eprintln!("Task {} succeeded in {elapsed}{session}.", task.id);
+        } else {
+            failed += 1;
+            eprintln!(
+                "Task {} failed after {elapsed}{session}: {}",
+                task.id,
+                outcome.result.as_deref().unwrap_or("no result")
+            );
+        }
+        if stopped {
+            eprintln!("Interrupted; the remaining tasks stay queued.");
+            break;
+        }
+    }
+    eprintln!("Queue finished: {succeeded} succeeded, {failed} failed.");
+    std::io::stderr().flush()?;
+    Ok(())
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct TaskOutcome {
+    success: bool,
+    session_id: Option<String>,
+    result: Option<String>,
+}
+
+async fn run_task(
+    codex: &Path,
+    task: &QueuedTask,
+    config_flags: &[String],
+    exec_args: &[String],
+) -> TaskOutcome {
+    let mut command = tokio::process::Command::new(codex);
+    for flag in config_flags {
+        command.arg("-c").arg(flag);
+    }
+    command
+        .arg("exec")
+        .arg("--json")
+        .arg("--wait-for-reset")
+        .arg("--cd")
+        .arg(&task.cwd)
+        .args(exec_args)
+        .arg("--")
+        .arg(&task.prompt)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit());
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(err) => {
+            return TaskOutcome {
+                result: Some(format!("failed to start codex exec: {err}")),
+                ..Default::default()
+            };
+        }
+    };
+
+    let mut outcome = TaskOutcome::default();
+    let mut error = None;
+    if let Some(stdout) = child.stdout.take() {
+        let mut lines = BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let Ok(event) = serde_json::from_str::<ThreadEvent>(&line) else {
+                continue;
+            };
+            observe(&mut outcome, &mut error, event);
+        }
+    }
+    let status = child.wait().await;
+    outcome.success = matches!(&status, Ok(status) if status.success()) && error.is_none();
+    if !outcome.success {
+        outcome.result = error.or_else(|| match status {
+            Ok(status) => Some(format!("codex exec exited with {status}")),
+            Err(err) => Some(format!("failed to wait for codex exec: {err}")),
+        });
+    }
+    outcome
+}
+
+/// Records what `event` tells about the task: the session it runs in, the
+/// agent's last message, or an error.
+fn observe(outcome: &mut TaskOutcome, error: &mut Option<String>, event: ThreadEvent) {
+    match event {
+        ThreadEvent::ThreadStarted(started) => outcome.session_id = Some(started.thread_id),
+        ThreadEvent::ItemCompleted(completed) => {
+            if let ThreadItemDetails::AgentMessage(message) = completed.item.details {
+                outcome.result = Some(message.text);
+            }
+        }
+        ThreadEvent::TurnFailed(failed) => *error = Some(failed.error.message),
+        ThreadEvent::Error(err) => *error = Some(err.message),
+        _ => {}
+    }
+}
+
+fn render_list<Tz: TimeZone>(tasks: &[QueuedTask], tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    if tasks.is_empty() {
+        return "The queue is empty. Add a task with `codex queue add \"<prompt>\"`.\n".to_string();
+    }
+    let mut out = String::new();
+    for task in tasks {
+        let added = tz
+            .timestamp_opt(task.added_at, 0)
+            .single()
+            .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "{:>3}  {:<9}  {added}  {}  {}",
+            task.id,
+            task.status.label(),
+            task.cwd.display(),
+            preview(&task.prompt)
+        );
+        if let Some(session_id) = &task.session_id {
+            let _ = writeln!(out, "     session {session_id}");
+        }
+    }
+    out
+}
+
+fn preview(prompt: &str) -> String {
+    let line = prompt.trim().lines().next().unwrap_or_default();
+    if line.chars().count() > PROMPT_PREVIEW_CHARS {
+        let mut preview: String = line.chars().take(PROMPT_PREVIEW_CHARS - 1).collect();
+        preview.push('…');
+        preview
+    } else {
+        line.to_string()
+    }
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use codex_exec::exec_events::AgentMessageItem;
+    use codex_exec::exec_events::ItemCompletedEvent;
+    use codex_exec::exec_events::SCHEMA_VERSION;
+    use codex_exec::exec_events::ThreadErrorEvent;
+    use codex_exec::exec_events::ThreadItem;
+    use codex_exec::exec_events::ThreadStartedEvent;
+    use codex_exec::exec_events::TurnFailedEvent;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn queue_round_trips_and_numbers_tasks() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join(QUEUE_FILE);
+        let mut queue = Queue::load(&path).expect("load");
+        assert_eq!(queue, Queue::default());
+
+        assert_eq!(queue.add("Bump serde".to_string(), "/repo".into(), 0), 1);
+        assert_eq!(
+            queue.add("Port the CLI to clap 4".to_string(), "/repo".into(), 60),
+            2
+        );
+        queue.tasks[0].status = TaskStatus::Succeeded;
+        queue.save(&path).expect("save");
+
+        let queue = Queue::load(&path).expect("load");
+        assert_eq!(queue.next_pending(), Some(1));
+        assert_eq!(
+            render_list(&queue.tasks, &Utc),
+            "  1  succeeded  1970-01-01 00:00  /repo  Bump serde\n  2  pending    1970-01-01 00:01  /repo  Port the CLI to clap 4\n"
+        );
+    }
+
+    #[test]
+    fn exec_events_give_the_session_and_result() {
+        let mut outcome = TaskOutcome::default();
+        let mut error = None;
+        observe(
+            &mut outcome,
+            &mut error,
+            ThreadEvent::ThreadStarted(ThreadStartedEvent {
+                schema_version: SCHEMA_VERSION,
+                thread_id: "7f9f9a2e".to_string(),
+                tags: Default::default(),
+            }),
+        );
+        observe(
+            &mut outcome,
+            &mut error,
+            ThreadEvent::ItemCompleted(ItemCompletedEvent {
+                item: ThreadItem {
+                    id: "item_0".to_string(),
+                    details: ThreadItemDetails::AgentMessage(AgentMessageItem {
+                        text: "Bumped serde to 1.0.210.".to_string(),
+                    }),
+                },
+            }),
+        );
+        assert_eq!(outcome.session_id.as_deref(), Some("7f9f9a2e"));
+        assert_eq!(outcome.result.as_deref(), Some("Bumped serde to 1.0.210."));
+        assert_eq!(error, None);
+
+        observe(
+            &mut outcome,
+            &mut error,
+            ThreadEvent::TurnFailed(TurnFailedEvent {
+                error: ThreadErrorEvent {
+                    message: "stream disconnected".to_string(),
+                },
+            }),
+        );
+        assert_eq!(error.as_deref(), Some("stream disconnected"));
+    }
+}
@@ -0,0 +1,214 @@
+//! `codex replay`: steps through a recorded session one turn at a time,
+//! showing the prompt and everything the agent did in response, to review a
+//! session or write up a regression. On a terminal it pauses after each
+//! turn; otherwise, or with `--no-pause`, it prints the whole session.
+
+use std::io::BufRead;
+use std::io::IsTerminal;
+use std::io::Write;
+
+use anyhow::Result;
+use codex_core::config::Config;
+use owo_colors::OwoColorize;
+
+use crate::output_policy::OutputPolicy;
+use crate::session_export::Entry;
+use crate::session_export::load_transcript;
+
+/// Lines of a command's output shown before it is cut short.
+const OUTPUT_PREVIEW_LINES: usize = 20;
+
+pub(crate) async fn run(
+    config: &Config,
+    session_id: &str,
+    from_turn: usize,
+    pause: bool,
+    output: OutputPolicy,
+) -> Result<()> {
+    let transcript = load_transcript(config, session_id).await?;
+    let turns = turns(&transcript.entries);
+    if from_turn == 0 || from_turn > turns.len().max(1) {
+        anyhow::bail!(
+            "cannot start at turn {from_turn}: the session has {} turns",
+            turns.len()
+        );
+    }
+    let pause = pause && std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+
+    println!("Session {}", transcript.id);
+    for (label, value) in transcript.details() {
+        println!("{label}: {value}");
+    }
+    let total = turns.len();
+    for (index, turn) in turns.iter().enumerate().skip(from_turn - 1) {
+        println!();
+        for line in render_turn(turn, output.color) {
+            println!("{line}");
+        }
+        if pause && index + 1 < total {
+            eprint!(
+                "\n-- turn {}/{total}: Enter for the next turn, q to quit -- ",
+                index + 1
+            );
+            std::io::stderr().flush()?;
+            let mut answer = String::new();
+            std::io::stdin().lock().read_line(&mut answer)?;
+            if answer.trim().eq_ignore_ascii_case("q") {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `entries` split at each prompt, one slice per turn.
+fn turns(entries: &[Entry]) -> Vec<&[Entry]> {
+    let mut starts: Vec<usize> = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| matches!(entry, Entry::Prompt { .. }))
+        .map(|(index, _)| index)
+        .collect();
+    if starts.is_empty() {
+        return Vec::new();
+    }
+    // Anything recorded before the first prompt belongs to the first turn.
+    starts[0] = 0;
+    let ends = starts.iter().skip(1).copied().chain([entries.len()]);
+    starts
+        .iter()
+        .zip(ends)
+        .map(|(&start, end)| &entries[start..end])
+        .collect()
+}
+
+fn render_turn(entries: &[Entry], color: bool) -> Vec<String> {
+    let paint = |text: String, style: fn(&str) -> String| {
+        if color { style(&text) } else { text }
+    };
+    let indented = |text: &str| -> Vec<String> {
+        text.trim_end()
+            .lines()
+            .map(|line| format!("  {line}").trim_end().to_string())
+            .collect()
+    };
+
+    let mut lines = Vec::new();
+    for entry in entries {
+        match entry {
+            Entry::Prompt { turn, text } => {
+                lines.push(paint(format!("== Turn {turn} =="), |s| {
+                    s.bold().to_string()
+                }));
+                lines.push(paint("user".to_string(), |s| s.cyan().bold().to_string()));
+                lines.extend(indented(text));
+            }
+            Entry::Reasoning(text) => {
+                lines.push(paint("thinking".to_string(), |s| {
+                    s.dimmed().italic().to_string()
+                }));
+                lines.extend(
+                    indented(text)
+                        .into_iter()
+                        .map(|line| paint(line, |s| s.dimmed().italic().to_string())),
+                );
+            }
+            Entry::Reply(text) => {
+                lines.push(paint("codex".to_string(), |s| {
+                    s.magenta().bold().to_string()
+                }));
+                lines.extend(indented(text));
+            }
+            Entry::Command(command) => {
+                let mut command_lines = command.trim_end().lines();
+                if let Some(first) = command_lines.next() {
+                    lines.push(paint(format!("$ {first}"), |s| s.bold().to_string()));
+                }
+                lines.extend(
+                    command_lines.map(|line| paint(format!("  {line}"), |s| s.bold().to_string())),
+                );
+            }
+            Entry::Output(output) => {
+                let output_lines: Vec<&str> = output.trim_end().lines().collect();
+                for line in output_lines.iter().take(OUTPUT_PREVIEW_LINES) {
+                    lines.push(paint(format!("  {line}").trim_end().to_string(), |s| {
+                        s.dimmed().to_string()
+                    }));
+                }
+                if output_lines.len() > OUTPUT_PREVIEW_LINES {
+                    lines.push(paint(
+                        format!(
+                            "  ... {} more lines",
+                            output_lines.len() - OUTPUT_PREVIEW_LINES
+                        ),
+                        |s| s.dimmed().to_string(),
+                    ));
+                }
+            }
+            Entry::Patch(patch) => {
+                lines.push(paint("patch".to_string(), |s| s.bold().to_string()));
+                for line in patch.trim_end().lines() {
+                    let line = format!("  {line}");
+                    let styled = match line.trim_start().chars().next() {
+                        Some('+') => paint(line, |s| s.green().to_string()),
+                        Some('-') => paint(line, |s| s.red().to_string()),
+                        _ => line,
+                    };
+                    lines.push(styled);
+                }
+            }
+            Entry::ToolCall { name, input } => {
+                lines.push(paint(format!("tool {name}"), |s| s.bold().to_string()));
+                lines.extend(indented(input));
+            }
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn sessions_replay_turn_by_turn() {
+        let entries = vec![
+            Entry::Prompt {
+                turn: 1,
+                text: "Why does `make` fail?".to_string(),
+            },
+            Entry::Reasoning("Checking the build.".to_string()),
+            Entry::Command("make".to_string()),
+            Entry::Output((1..=22).map(|n| format!("line {n}\n")).collect()),
+            Entry::Patch("*** Add File: Makefile\n+all:".to_string()),
+            Entry::Reply("Added an `all` target.".to_string()),
+            Entry::Prompt {
+                turn: 2,
+                text: "Thanks".to_string(),
+            },
+        ];
+        let turns = turns(&entries);
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[1], &entries[6..]);
+
+        let mut expected = vec![
+            "== Turn 1 ==".to_string(),
+            "user".to_string(),
+            "  Why does `make` fail?".to_string(),
+            "thinking".to_string(),
+            "  Checking the build.".to_string(),
+            "$ make".to_string(),
+        ];
+        expected.extend((1..=20).map(|n| format!("  line {n}")));
+        expected.extend([
+            "  ... 2 more lines".to_string(),
+            "patch".to_string(),
+            "  *** Add File: Makefile".to_string(),
+            "  +all:".to_string(),
+            "codex".to_string(),
+            "  Added an `all` target.".to_string(),
+        ]);
+        assert_eq!(render_turn(turns[0], false), expected);
+    }
+}
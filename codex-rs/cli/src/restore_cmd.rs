@@ -0,0 +1,129 @@
+//! `codex restore <checkpoint>`: rolls the working tree back to a named
+//! checkpoint's snapshot; the caller then forks the conversation at the
+//! checkpoint's turn. Without a name it lists the checkpoints.
+
+use std::path::Path;
+
+use anyhow::Context;
+use anyhow::Result;
+use chrono::DateTime;
+use chrono::Local;
+use chrono::TimeZone;
+use codex_core::checkpoints::Checkpoint;
+use codex_core::checkpoints::list_checkpoints;
+use codex_core::checkpoints::load_checkpoint;
+use codex_core::checkpoints::restore_checkpoint_files;
+
+pub(crate) fn print_checkpoints(codex_home: &Path) {
+    print!("{}", render_table(&list_checkpoints(codex_home), &Local));
+}
+
+/// Loads checkpoint `name` and restores the files it snapshotted.
+pub(crate) async fn restore_files(codex_home: &Path, name: &str) -> Result<Checkpoint> {
+    let checkpoint = load_checkpoint(codex_home, name)?.with_context(|| {
+        format!("no checkpoint named `{name}`; run `codex restore` to list them")
+    })?;
+    let restored = tokio::task::spawn_blocking({
+        let checkpoint = checkpoint.clone();
+        move || restore_checkpoint_files(&checkpoint)
+    })
+    .await?
+    .with_context(|| format!("failed to restore files in {}", checkpoint.cwd.display()))?;
+    if restored {
+        eprintln!(
+            "Restored the working tree in {} to checkpoint {name}.",
+            checkpoint.cwd.display()
+        );
+    } else {
+        eprintln!(
+            "Checkpoint {name} has no snapshot of the working tree; restoring the conversation only."
+        );
+    }
+    Ok(checkpoint)
+}
+
+fn render_table<Tz: TimeZone>(checkpoints: &[Checkpoint], tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    if checkpoints.is_empty() {
+        return "No checkpoints found. Save one with /checkpoint in a session.\n".to_string();
+    }
+    let mut lines: Vec<Vec<String>> = vec![
+        ["name", "created", "turns", "files", "session", "cwd"]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+    ];
+    lines.extend(checkpoints.iter().map(|checkpoint| {
+        vec![
+            checkpoint.name.clone(),
+            DateTime::from_timestamp(checkpoint.created_at, 0)
+                .map(|at| at.with_timezone(tz).format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "?".to_string()),
+            checkpoint.turns.to_string(),
+            if checkpoint.ghost_commit.is_some() {
+                "yes"
+            } else {
+                "no"
+            }
+            .to_string(),
+            checkpoint.conversation_id.to_string(),
+            checkpoint.cwd.display().to_string(),
+        ]
+    }));
+    let mut widths = vec![0; lines[0].len()];
+    for line in &lines {
+        for (width, cell) in widths.iter_mut().zip(line) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    for line in &lines {
+        let cells: Vec<String> = line
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+    out.push_str("\nRoll back to one with `codex restore <name>`.\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use codex_protocol::ConversationId;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    #[test]
+    fn checkpoints_table_lists_each_checkpoint() {
+        let conversation_id =
+            ConversationId::from_string("7f9f9a2e-1b3c-4c7a-9b0e-123456789abc").expect("id");
+        let checkpoint = Checkpoint {
+            name: "before-refactor".to_string(),
+            conversation_id,
+            rollout_path: PathBuf::from("/sessions/rollout.jsonl"),
+            turns: 3,
+            cwd: PathBuf::from("/src/app"),
+            ghost_commit: None,
+            created_at: 1_760_000_000,
+        };
+        assert_eq!(
+            render_table(&[checkpoint], &Utc),
+            "name             created           turns  files  session                               cwd\n\
+             before-refactor  2025-10-09 08:53  3      no     7f9f9a2e-1b3c-4c7a-9b0e-123456789abc  /src/app\n\
+             \n\
+             Roll back to one with `codex restore <name>`.\n"
+        );
+        assert_eq!(
+            render_table(&[], &Utc),
+            "No checkpoints found. Save one with /checkpoint in a session.\n"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! `codex run <template>`: fill in a saved prompt from `~/.codex/prompts/`
+//! with `--var key=value` pairs and run it non-interactively, like
+//! `codex exec`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Result;
+use anyhow::bail;
+use codex_core::custom_prompts::discover_prompts_in;
+use codex_core::custom_prompts::expand_prompt_variables;
+use codex_core::custom_prompts::prompt_variable_names;
+
+/// Parses a `--var key=value` argument.
+pub(crate) fn parse_var(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected key=value but found `{arg}`")),
+    }
+}
+
+/// Returns the prompt for the template `name` in `prompts_dir` with its
+/// variables replaced by `vars`.
+pub(crate) async fn expand_template(
+    prompts_dir: &Path,
+    name: &str,
+    vars: &[(String, String)],
+) -> Result<String> {
+    let name = name.strip_suffix(".md").unwrap_or(name);
+    let prompts = discover_prompts_in(prompts_dir).await;
+    let Some(prompt) = prompts.iter().find(|prompt| prompt.name == name) else {
+        if prompts.is_empty() {
+            bail!(
+                "no template named `{name}`: {} has no templates",
+                prompts_dir.display()
+            );
+        }
+        let available: Vec<&str> = prompts.iter().map(|prompt| prompt.name.as_str()).collect();
+        bail!(
+            "no template named `{name}`; available: {}",
+            available.join(", ")
+        );
+    };
+    render(&prompt.content, vars)
+}
+
+fn render(content: &str, vars: &[(String, String)]) -> Result<String> {
+    let required = prompt_variable_names(content);
+    let unknown: Vec<&str> = vars
+        .iter()
+        .map(|(key, _)| key.as_str())
+        .filter(|key| !required.iter().any(|name| name == key))
+        .collect();
+    if !unknown.is_empty() {
+        bail!(
+            "the template has no variable {}; it uses: {}",
+            unknown.join(", "),
+            if required.is_empty() {
+                "none".to_string()
+            } else {
+                required.join(", ")
+            }
+        );
+    }
+    let vars: HashMap<String, String> = vars.iter().cloned().collect();
+    let missing: Vec<&str> = required
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !vars.contains_key(*name))
+        .collect();
+    if !missing.is_empty() {
+        let flags: Vec<String> = missing
+            .iter()
+            .map(|name| format!("--var {name}=..."))
+            .collect();
+        bail!(
+            "missing variables {}; pass {}",
+            missing.join(", "),
+            flags.join(" ")
+        );
+    }
+    Ok(expand_prompt_variables(content, &vars))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn var(key: &str, value: &str) -> (String, String) {
+        (key.to_string(), value.to_string())
+    }
+
+    #[tokio::test]
+    async fn templates_are_expanded_with_vars() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(
+            dir.path().join("endpoint.md"),
+            "---\ndescription: Add an endpoint\n---\nAdd a {{method}} {{ path }} endpoint to $SERVICE.",
+        )
+        .expect("write template");
+
+        let vars = [
+            var("method", "GET"),
+            var("path", "/health"),
+            var("SERVICE", "api"),
+        ];
+        assert_eq!(
+            expand_template(dir.path(), "endpoint", &vars)
+                .await
+                .expect("expand"),
+            "Add a GET /health endpoint to api."
+        );
+
+        let err = expand_template(dir.path(), "endpoint", &vars[..1])
+            .await
+            .expect_err("missing vars");
+        assert_eq!(
+            err.to_string(),
+            "missing variables path, SERVICE; pass --var path=... --var SERVICE=..."
+        );
+        let err = expand_template(dir.path(), "endpoint", &[var("methd", "GET")])
+            .await
+            .expect_err("unknown var");
+        assert_eq!(
+            err.to_string(),
+            "the template has no variable methd; it uses: method, path, SERVICE"
+        );
+        let err = expand_template(dir.path(), "migration", &[])
+            .await
+            .expect_err("unknown template");
+        assert_eq!(
+            err.to_string(),
+            "no template named `migration`; available: endpoint"
+        );
+
+        assert_eq!(parse_var("path=/a=b"), Ok(var("path", "/a=b")));
+        assert!(parse_var("=x").is_err());
+        assert!(parse_var("path").is_err());
+    }
+}
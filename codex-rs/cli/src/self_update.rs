@@ -0,0 +1,250 @@
+//! Running the update chosen from the TUI's update prompt.
+//!
+//! A new release that cannot start leaves users without a working `codex`
+//! until they reinstall by hand, so the new binary has to pass
+//! `codex --version --selftest` before it is trusted. Where the package
+//! manager can install into a separate directory (npm), the new version is
+//! staged in a temporary directory and checked there before the real update
+//! runs. The installed binary is checked again afterwards, and the previous
+//! version is reinstalled if it fails.
+
+use std::io::Read;
+use std::path::Path;
+use std::process::Command;
+use std::process::Stdio;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Context;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_tui::update_action::UpdateAction;
+
+use crate::output_policy::OutputPolicy;
+
+/// Hidden flag that makes `codex --version` also load the configuration.
+pub(crate) const SELFTEST_FLAG: &str = "--selftest";
+
+const SELFTEST_TIMEOUT: Duration = Duration::from_secs(30);
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// `codex --version --selftest`: loads the current configuration and prints
+/// the version, so a freshly downloaded binary can show that it starts, can
+/// read the user's config, and is the version that was asked for.
+pub(crate) async fn run_selftest() -> anyhow::Result<()> {
+    Config::load_with_cli_overrides(Vec::new(), ConfigOverrides::default())
+        .await
+        .context("failed to load the Codex configuration")?;
+    println!("codex-cli {CURRENT_VERSION}");
+    Ok(())
+}
+
+/// Runs `action`, checking the new binary before and after when
+/// `expected_version` is known.
+pub(crate) fn run_update_action(
+    action: UpdateAction,
+    expected_version: Option<&str>,
+    output: OutputPolicy,
+) -> anyhow::Result<()> {
+    println!();
+    if let Some(version) = expected_version {
+        stage_and_verify(action, version)?;
+    }
+
+    let cmd_str = action.command_str();
+    println!("Updating Codex via `{cmd_str}`...");
+    let (command, args) = action.command_args();
+    let command_line: Vec<String> = std::iter::once(command)
+        .chain(args.iter().copied())
+        .map(str::to_string)
+        .collect();
+    run_command(&command_line)?;
+
+    if let Some(version) = expected_version {
+        verify_installed(action, version)?;
+    }
+    println!();
+    println!(
+        "{}Update ran successfully! Please restart Codex.",
+        output.decoration("🎉 ")
+    );
+    Ok(())
+}
+
+/// Installs `version` into a temporary directory and self-tests it there.
+/// Does nothing for package managers that cannot stage an install.
+fn stage_and_verify(action: UpdateAction, version: &str) -> anyhow::Result<()> {
+    let staging_dir = tempfile::tempdir().context("failed to create a staging directory")?;
+    let Some((command_line, staged_codex)) = action.staging_command(version, staging_dir.path())
+    else {
+        return Ok(());
+    };
+    println!("Checking Codex {version} before installing it...");
+    run_command(&command_line)
+        .with_context(|| format!("failed to download Codex {version} for checking"))?;
+    run_selftest_of(&staged_codex, version).map_err(|err| {
+        anyhow::anyhow!(
+            "Codex {version} failed its self-test, so it was not installed and Codex {CURRENT_VERSION} is unchanged: {err}"
+        )
+    })
+}
+
+/// Self-tests the `codex` on `PATH` after the update and reinstalls the
+/// current version if it does not start.
+fn verify_installed(action: UpdateAction, version: &str) -> anyhow::Result<()> {
+    let err = match run_selftest_of(Path::new("codex"), version) {
+        Ok(()) => return Ok(()),
+        Err(SelftestError::WrongVersion(reported)) => {
+            // The binary works; `PATH` most likely finds another install first.
+            eprintln!(
+                "Warning: `codex` on your PATH reports version {reported}, not {version}. Check for another Codex installation earlier on your PATH."
+            );
+            return Ok(());
+        }
+        Err(err) => err,
+    };
+
+    let Some(command_line) = action.rollback_command(CURRENT_VERSION) else {
+        anyhow::bail!(
+            "Codex {version} failed its self-test after installing: {err}. Reinstall Codex {CURRENT_VERSION} to recover."
+        );
+    };
+    eprintln!("Codex {version} failed its self-test ({err}); reinstalling {CURRENT_VERSION}...");
+    run_command(&command_line).with_context(|| {
+        format!("failed to reinstall Codex {CURRENT_VERSION}; reinstall it manually")
+    })?;
+    anyhow::bail!(
+        "Codex {version} failed its self-test after installing, so Codex {CURRENT_VERSION} was reinstalled: {err}"
+    )
+}
+
+#[derive(Debug, PartialEq)]
+enum SelftestError {
+    /// The binary could not be run, crashed, timed out, or could not load
+    /// the configuration.
+    Failed(String),
+    /// The binary works but reports this version instead of the expected one.
+    WrongVersion(String),
+}
+
+impl std::fmt::Display for SelftestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SelftestError::Failed(reason) => f.write_str(reason),
+            SelftestError::WrongVersion(reported) => write!(f, "it reports version {reported}"),
+        }
+    }
+}
+
+fn run_selftest_of(codex: &Path, expected_version: &str) -> Result<(), SelftestError> {
+    let codex = codex.display().to_string();
+    let mut child = command_for(&[codex, "--version".to_string(), SELFTEST_FLAG.to_string()])
+        .map_err(|err| SelftestError::Failed(err.to_string()))?
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| SelftestError::Failed(format!("it could not be started: {err}")))?;
+
+    let deadline = Instant::now() + SELFTEST_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(100)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(SelftestError::Failed(format!(
+                    "it did not finish within {} seconds",
+                    SELFTEST_TIMEOUT.as_secs()
+                )));
+            }
+            Err(err) => return Err(SelftestError::Failed(err.to_string())),
+        }
+    };
+
+    let mut stdout = String::new();
+    let mut stderr = String::new();
+    if let Some(mut out) = child.stdout.take() {
+        let _ = out.read_to_string(&mut stdout);
+    }
+    if let Some(mut err) = child.stderr.take() {
+        let _ = err.read_to_string(&mut stderr);
+    }
+    if !status.success() {
+        return Err(SelftestError::Failed(format!(
+            "it exited with {status}: {}",
+            stderr.trim()
+        )));
+    }
+    check_selftest_output(&stdout, expected_version)
+}
+
+/// The self-test prints `codex-cli <version>` once the configuration has
+/// loaded.
+fn check_selftest_output(stdout: &str, expected_version: &str) -> Result<(), SelftestError> {
+    let reported = stdout
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("codex-cli "))
+        .map(str::trim)
+        .ok_or_else(|| {
+            SelftestError::Failed(format!("unexpected self-test output: {}", stdout.trim()))
+        })?;
+    if reported == expected_version {
+        Ok(())
+    } else {
+        Err(SelftestError::WrongVersion(reported.to_string()))
+    }
+}
+
+fn run_command(command_line: &[String]) -> anyhow::Result<()> {
+    let status = command_for(command_line)?.status()?;
+    if !status.success() {
+        anyhow::bail!("`{}` failed with status {status}", command_line.join(" "));
+    }
+    Ok(())
+}
+
+fn command_for(command_line: &[String]) -> std::io::Result<Command> {
+    let Some((program, args)) = command_line.split_first() else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "empty command",
+        ));
+    };
+    #[cfg(windows)]
+    {
+        // On Windows, run via cmd.exe so .CMD/.BAT are correctly resolved (PATHEXT semantics).
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(program).args(args);
+        Ok(command)
+    }
+    #[cfg(not(windows))]
+    {
+        let mut command = Command::new(crate::wsl_paths::normalize_for_wsl(program));
+        command.args(args.iter().map(crate::wsl_paths::normalize_for_wsl));
+        Ok(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn selftest_output_must_report_the_expected_version() {
+        assert_eq!(
+            check_selftest_output("codex-cli 0.60.0\n", "0.60.0"),
+            Ok(())
+        );
+        assert_eq!(
+            check_selftest_output("codex-cli 0.59.0\n", "0.60.0"),
+            Err(SelftestError::WrongVersion("0.59.0".to_string()))
+        );
+        assert!(matches!(
+            check_selftest_output("", "0.60.0"),
+            Err(SelftestError::Failed(_))
+        ));
+    }
+}
@@ -0,0 +1,371 @@
+//! `codex sessions`: past sessions recorded under `~/.codex/sessions`, newest
+//! first, with enough of each one (where it ran, on which model, how many
+//! turns, and how it started) to find the conversation behind a change and
+//! resume it. Forks name the session and turn they branched from.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use chrono::DateTime;
+use chrono::Local;
+use chrono::NaiveDate;
+use chrono::TimeZone;
+use chrono::Utc;
+use codex_core::SESSIONS_SUBDIR;
+use codex_core::config::Config;
+use codex_protocol::models::LocalShellAction;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::ForkedFrom;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use regex_lite::Regex;
+use serde_json::Value as JsonValue;
+use serde_json::json;
+
+/// Characters of the first prompt shown in the table.
+const PROMPT_PREVIEW_CHARS: usize = 60;
+
+/// Which recorded sessions to list.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionFilter {
+    /// First local day a session may have started on.
+    pub(crate) since: Option<NaiveDate>,
+    /// Only sessions started in this directory or below it.
+    pub(crate) cwd: Option<PathBuf>,
+    /// Only sessions whose prompts, replies, commands, or patches match.
+    pub(crate) grep: Option<Regex>,
+    pub(crate) limit: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct SessionSummary {
+    id: String,
+    started_at: Option<DateTime<Utc>>,
+    cwd: PathBuf,
+    /// Model of the last turn; `None` before the first one.
+    model: Option<String>,
+    turns: usize,
+    first_prompt: Option<String>,
+    forked_from: Option<ForkedFrom>,
+}
+
+pub(crate) fn run(config: &Config, filter: &SessionFilter, json: bool) -> Result<()> {
+    let sessions = list_sessions(&config.codex_home.join(SESSIONS_SUBDIR), filter)?;
+    if json {
+        let sessions: Vec<JsonValue> = sessions.iter().map(SessionSummary::to_json).collect();
+        println!("{}", serde_json::to_string_pretty(&sessions)?);
+    } else {
+        print!("{}", render_table(&sessions, &Local));
+    }
+    Ok(())
+}
+
+fn list_sessions(sessions_dir: &Path, filter: &SessionFilter) -> Result<Vec<SessionSummary>> {
+    let mut files = Vec::new();
+    collect_rollout_files(sessions_dir, &mut files)
+        .with_context(|| format!("failed to read {}", sessions_dir.display()))?;
+    // `YYYY/MM/DD/rollout-<timestamp>-<id>.jsonl`: newest last.
+    files.sort();
+
+    let mut sessions = Vec::new();
+    for path in files.iter().rev() {
+        if sessions.len() >= filter.limit {
+            break;
+        }
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        if let Some(summary) = summarize(&contents, filter, &Local) {
+            sessions.push(summary);
+        }
+    }
+    Ok(sessions)
+}
+
+/// Rollout files under `dir`, at any depth. A missing directory has none.
+fn collect_rollout_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_rollout_files(&path, files)?;
+        } else if path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("rollout-") && name.ends_with(".jsonl"))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Summary of the rollout `contents`, or `None` when it is not a session or
+/// `filter` leaves it out.
+fn summarize<Tz: TimeZone>(
+    contents: &str,
+    filter: &SessionFilter,
+    tz: &Tz,
+) -> Option<SessionSummary> {
+    let mut summary: Option<SessionSummary> = None;
+    let mut matched = filter.grep.is_none();
+    for line in contents.lines() {
+        let Ok(line) = serde_json::from_str::<RolloutLine>(line) else {
+            continue;
+        };
+        if let RolloutItem::SessionMeta(meta) = &line.item {
+            // Forked sessions repeat their parent's meta; the first is ours.
+            if summary.is_none() {
+                summary = Some(SessionSummary {
+                    id: meta.meta.id.to_string(),
+                    started_at: DateTime::parse_from_rfc3339(&meta.meta.timestamp)
+                        .ok()
+                        .map(|at| at.with_timezone(&Utc)),
+                    cwd: meta.meta.cwd.clone(),
+                    model: None,
+                    turns: 0,
+                    first_prompt: None,
+                    forked_from: meta.meta.forked_from.clone(),
+                });
+            }
+            continue;
+        }
+        let Some(summary) = summary.as_mut() else {
+            continue;
+        };
+        match &line.item {
+            RolloutItem::TurnContext(ctx) => summary.model = Some(ctx.model.clone()),
+            RolloutItem::EventMsg(EventMsg::UserMessage(event)) => {
+                summary.turns += 1;
+                if summary.first_prompt.is_none() {
+                    summary.first_prompt = Some(event.message.clone());
+                }
+            }
+            _ => {}
+        }
+        if !matched && let Some(grep) = &filter.grep {
+            matched = searchable_text(&line.item).is_some_and(|text| grep.is_match(&text));
+        }
+    }
+
+    let summary = summary?;
+    let started_on = summary
+        .started_at
+        .map(|at| at.with_timezone(tz).date_naive());
+    let in_period = filter.since.is_none_or(|since| started_on >= Some(since));
+    let in_cwd = filter
+        .cwd
+        .as_ref()
+        .is_none_or(|cwd| summary.cwd.starts_with(cwd));
+    (matched && in_period && in_cwd).then_some(summary)
+}
+
+/// Text `--grep` looks at: what the user and the model wrote, the commands
+/// run, and the patches applied. Instructions and command output are left
+/// out; they would match nearly everything.
+fn searchable_text(item: &RolloutItem) -> Option<String> {
+    match item {
+        RolloutItem::EventMsg(EventMsg::UserMessage(event)) => Some(event.message.clone()),
+        RolloutItem::EventMsg(EventMsg::AgentMessage(event)) => Some(event.message.clone()),
+        RolloutItem::ResponseItem(ResponseItem::FunctionCall { arguments, .. }) => {
+            Some(arguments.clone())
+        }
+        RolloutItem::ResponseItem(ResponseItem::CustomToolCall { input, .. }) => {
+            Some(input.clone())
+        }
+        RolloutItem::ResponseItem(ResponseItem::LocalShellCall {
+            action: LocalShellAction::Exec(exec),
+            ..
+        }) => Some(exec.command.join(" ")),
+        _ => None,
+    }
+}
+
+/// First line of `prompt`, cut to [`PROMPT_PREVIEW_CHARS`].
+fn preview(prompt: &str) -> String {
+    let line = prompt.trim().lines().next().unwrap_or_default();
+    if line.chars().count() <= PROMPT_PREVIEW_CHARS {
+        return line.to_string();
+    }
+    let mut cut: String = line.chars().take(PROMPT_PREVIEW_CHARS - 1).collect();
+    cut.push('…');
+    cut
+}
+
+fn render_table<Tz: TimeZone>(sessions: &[SessionSummary], tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    if sessions.is_empty() {
+        return "No sessions found.\n".to_string();
+    }
+    // Only listings that include a fork say where sessions came from.
+    let lineage = sessions.iter().any(|session| session.forked_from.is_some());
+    let mut header = vec!["id"];
+    if lineage {
+        header.push("forked from");
+    }
+    header.extend(["started", "turns", "model", "cwd", "first prompt"]);
+    let mut lines: Vec<Vec<String>> = vec![header.into_iter().map(str::to_string).collect()];
+    lines.extend(sessions.iter().map(|session| {
+        let mut cells = vec![session.id.clone()];
+        if lineage {
+            cells.push(
+                session
+                    .forked_from
+                    .as_ref()
+                    .map(|from| format!("{} at turn {}", from.id, from.turn))
+                    .unwrap_or_default(),
+            );
+        }
+        cells.extend([
+            session
+                .started_at
+                .map(|at| at.with_timezone(tz).format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "?".to_string()),
+            session.turns.to_string(),
+            session.model.clone().unwrap_or_else(|| "-".to_string()),
+            session.cwd.display().to_string(),
+            session
+                .first_prompt
+                .as_deref()
+                .map(preview)
+                .unwrap_or_default(),
+        ]);
+        cells
+    }));
+    let mut widths = vec![0; lines[0].len()];
+    for line in &lines {
+        for (width, cell) in widths.iter_mut().zip(line) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    for line in &lines {
+        let cells: Vec<String> = line
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+    out.push_str("\nResume one with `codex resume <id>`.\n");
+    out
+}
+
+impl SessionSummary {
+    fn to_json(&self) -> JsonValue {
+        json!({
+            "id": self.id,
+            "started_at": self.started_at.map(|at| at.to_rfc3339()),
+            "cwd": self.cwd,
+            "model": self.model,
+            "turns": self.turns,
+            "first_prompt": self.first_prompt,
+            "forked_from": self.forked_from,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+    use codex_protocol::ConversationId;
+    use pretty_assertions::assert_eq;
+
+    const ROLLOUT: &str = r#"{"timestamp":"2024-03-01T12:00:00.000Z","type":"session_meta","payload":{"id":"7f9f9a2e-1b3c-4c7a-9b0e-123456789abc","timestamp":"2024-03-01T12:00:00.000Z","cwd":"/src/app","originator":"codex_cli_rs","cli_version":"0.0.0","instructions":"mention parser.rs","source":"cli","model_provider":"openai"}}
+{"timestamp":"2024-03-01T12:00:01.000Z","type":"event_msg","payload":{"type":"user_message","message":"Fix the flaky test\nin the scheduler"}}
+{"timestamp":"2024-03-01T12:00:02.000Z","type":"turn_context","payload":{"cwd":"/src/app","approval_policy":"on-request","sandbox_policy":{"type":"read-only"},"model":"gpt-5-codex","summary":"auto"}}
+{"timestamp":"2024-03-01T12:00:03.000Z","type":"response_item","payload":{"type":"custom_tool_call","call_id":"c1","name":"apply_patch","input":"*** Begin Patch\n*** Update File: src/scheduler.rs\n*** End Patch"}}
+{"timestamp":"2024-03-01T12:00:04.000Z","type":"event_msg","payload":{"type":"user_message","message":"Thanks"}}
+"#;
+
+    fn filter() -> SessionFilter {
+        SessionFilter {
+            since: None,
+            cwd: None,
+            grep: None,
+            limit: 20,
+        }
+    }
+
+    #[test]
+    fn sessions_are_summarized_and_filtered() {
+        let tz = FixedOffset::east_opt(0).expect("offset");
+        let summary = summarize(ROLLOUT, &filter(), &tz).expect("summary");
+        assert_eq!(
+            summary,
+            SessionSummary {
+                id: "7f9f9a2e-1b3c-4c7a-9b0e-123456789abc".to_string(),
+                started_at: DateTime::parse_from_rfc3339("2024-03-01T12:00:00Z")
+                    .ok()
+                    .map(|at| at.with_timezone(&Utc)),
+                cwd: PathBuf::from("/src/app"),
+                model: Some("gpt-5-codex".to_string()),
+                turns: 2,
+                first_prompt: Some("Fix the flaky test\nin the scheduler".to_string()),
+                forked_from: None,
+            }
+        );
+
+        let matching = |filter: SessionFilter| summarize(ROLLOUT, &filter, &tz).is_some();
+        let grep = |pattern: &str| SessionFilter {
+            grep: Some(Regex::new(pattern).expect("regex")),
+            ..filter()
+        };
+        assert!(matching(grep("(?i)SCHEDULER\\.rs")));
+        assert!(matching(grep("flaky")));
+        // Instructions are not searched.
+        assert!(!matching(grep("parser\\.rs")));
+
+        let since = |date: &str| SessionFilter {
+            since: Some(date.parse().expect("date")),
+            ..filter()
+        };
+        assert!(matching(since("2024-03-01")));
+        assert!(!matching(since("2024-03-02")));
+
+        let cwd = |dir: &str| SessionFilter {
+            cwd: Some(PathBuf::from(dir)),
+            ..filter()
+        };
+        assert!(matching(cwd("/src")));
+        assert!(!matching(cwd("/src/application")));
+
+        assert_eq!(
+            render_table(std::slice::from_ref(&summary), &tz),
+            "id                                    started           turns  model        cwd       first prompt\n\
+             7f9f9a2e-1b3c-4c7a-9b0e-123456789abc  2024-03-01 12:00  2      gpt-5-codex  /src/app  Fix the flaky test\n\
+             \n\
+             Resume one with `codex resume <id>`.\n"
+        );
+
+        let fork = SessionSummary {
+            id: "a1b2c3d4-0000-4000-8000-000000000001".to_string(),
+            forked_from: Some(ForkedFrom {
+                id: ConversationId::from_string(&summary.id).expect("id"),
+                turn: 1,
+            }),
+            ..summary.clone()
+        };
+        assert_eq!(
+            render_table(&[fork, summary], &tz),
+            "id                                    forked from                                     started           turns  model        cwd       first prompt\n\
+             a1b2c3d4-0000-4000-8000-000000000001  7f9f9a2e-1b3c-4c7a-9b0e-123456789abc at turn 1  2024-03-01 12:00  2      gpt-5-codex  /src/app  Fix the flaky test\n\
+             7f9f9a2e-1b3c-4c7a-9b0e-123456789abc                                                  2024-03-01 12:00  2      gpt-5-codex  /src/app  Fix the flaky test\n\
+             \n\
+             Resume one with `codex resume <id>`.\n"
+        );
+    }
+}
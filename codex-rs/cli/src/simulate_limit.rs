@@ -0,0 +1,337 @@
+//! `codex debug simulate-limit`: records a made-up limit hit for the active
+//! credentials and makes the switch a real one would trigger, so the API key
+//! pool, account rotation, and `fallback` chain can be checked without
+//! exhausting a real limit.
+
+use anyhow::Result;
+use chrono::DateTime;
+use chrono::Local;
+use chrono::TimeZone;
+use chrono::Utc;
+use codex_app_server_protocol::AuthMode;
+use codex_core::AuthManager;
+use codex_core::audit_log;
+use codex_core::audit_log::AuditEntry;
+use codex_core::audit_log::AuditEvent;
+use codex_core::config::Config;
+use codex_core::config::types::LimitCapAction;
+use codex_core::limit_history::LimitHitEntry;
+use codex_core::limit_history::append_limit_hit;
+use codex_core::state_dir::state_dir;
+use codex_protocol::ConversationId;
+use codex_protocol::protocol::AuthSwitchReason;
+use codex_protocol::protocol::AuthSwitchedEvent;
+use serde_json::Value as JsonValue;
+use serde_json::json;
+
+/// Error the simulated request fails with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum SimulatedLimit {
+    /// ChatGPT plan usage limit.
+    UsageLimit,
+    /// API key out of quota.
+    QuotaExceeded,
+    /// Too many requests, after retries.
+    RateLimited,
+    /// The provider kept failing with server errors. Not a limit, so only
+    /// the `fallback` chain reacts to it.
+    ProviderError,
+}
+
+impl SimulatedLimit {
+    fn reason(self) -> AuthSwitchReason {
+        match self {
+            SimulatedLimit::UsageLimit => AuthSwitchReason::UsageLimitReached,
+            SimulatedLimit::QuotaExceeded => AuthSwitchReason::QuotaExceeded,
+            SimulatedLimit::RateLimited => AuthSwitchReason::RateLimited,
+            SimulatedLimit::ProviderError => AuthSwitchReason::ProviderError,
+        }
+    }
+}
+
+/// What the session would do next.
+#[derive(Debug, Clone, PartialEq)]
+enum Outcome {
+    /// Retry the request on the credentials switched to.
+    Switched,
+    /// `limits.on_cap = "downgrade_model"`: carry on with this model.
+    DowngradeModel(String),
+    /// `limits.on_cap = "wait"`: sleep until the limit resets.
+    Wait(DateTime<Utc>),
+    /// Nothing left to switch to.
+    Fail,
+}
+
+struct Simulation {
+    before: String,
+    recorded: Option<LimitHitEntry>,
+    switches: Vec<AuthSwitchedEvent>,
+    after: String,
+    outcome: Outcome,
+}
+
+pub(crate) fn run(
+    config: &Config,
+    kind: SimulatedLimit,
+    resets_at: DateTime<Utc>,
+    json: bool,
+) -> Result<()> {
+    let manager = AuthManager::shared(
+        config.codex_home.clone(),
+        false,
+        config.cli_auth_credentials_store_mode,
+    );
+    manager.apply_policy(&config.policy);
+    manager.set_shared_state_dir(config.shared_state_dir.clone());
+    if !config.fallback.is_empty() {
+        manager.configure_failover(config.fallback.clone());
+    }
+    // The simulation stands in for a single conversation; switches made
+    // while setting up, e.g. skipping a limited fallback entry, are not part
+    // of it.
+    let conversation_id = ConversationId::new();
+    manager.take_auth_switches(conversation_id);
+
+    let simulation = simulate(config, &manager, conversation_id, kind, resets_at)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&simulation.to_json())?);
+    } else {
+        for line in simulation.render(&Local) {
+            println!("{line}");
+        }
+    }
+    Ok(())
+}
+
+fn simulate(
+    config: &Config,
+    manager: &AuthManager,
+    conversation_id: ConversationId,
+    kind: SimulatedLimit,
+    resets_at: DateTime<Utc>,
+) -> Result<Simulation> {
+    let before = credentials_label(manager, conversation_id);
+    let reason = kind.reason();
+    let recorded = (kind != SimulatedLimit::ProviderError).then(|| LimitHitEntry {
+        ts: Utc::now().timestamp(),
+        auth: manager
+            .auth()
+            .map(|auth| auth.identity_label())
+            .unwrap_or_default(),
+        model: config.model.clone(),
+        window_minutes: None,
+        resets_at: Some(resets_at.timestamp()),
+    });
+    if let Some(entry) = &recorded {
+        append_limit_hit(
+            &state_dir(&config.codex_home),
+            config.shared_state_dir.as_deref(),
+            entry,
+        )?;
+        audit_log::record(
+            &config.codex_home,
+            &AuditEntry::now(AuditEvent::LimitHit, entry.auth.clone())
+                .reason("simulated")
+                .detail(entry.model.clone()),
+        );
+    }
+
+    // Same order as a real request: the pool or the other signed-in
+    // accounts first, then the `fallback` chain.
+    let limits = &config.limits;
+    let downgrade = limits.downgrade_model.as_ref().filter(|model| {
+        kind == SimulatedLimit::UsageLimit
+            && limits.on_cap == LimitCapAction::DowngradeModel
+            && **model != config.model
+    });
+    let outcome = if let Some(model) = downgrade {
+        Outcome::DowngradeModel(model.clone())
+    } else if rotate(manager, conversation_id, kind, resets_at)
+        || manager.fail_over(
+            Some(conversation_id),
+            Some(resets_at),
+            reason,
+            &config.model,
+        )
+    {
+        Outcome::Switched
+    } else if kind == SimulatedLimit::UsageLimit && limits.on_cap == LimitCapAction::Wait {
+        Outcome::Wait(resets_at)
+    } else {
+        Outcome::Fail
+    };
+
+    Ok(Simulation {
+        before,
+        recorded,
+        switches: manager.take_auth_switches(conversation_id),
+        after: credentials_label(manager, conversation_id),
+        outcome,
+    })
+}
+
+fn rotate(
+    manager: &AuthManager,
+    conversation_id: ConversationId,
+    kind: SimulatedLimit,
+    resets_at: DateTime<Utc>,
+) -> bool {
+    let conversation_id = Some(conversation_id);
+    if kind == SimulatedLimit::ProviderError {
+        return false;
+    }
+    match manager.auth().map(|auth| auth.mode) {
+        Some(AuthMode::ApiKey) => {
+            manager.rotate_api_key(conversation_id, Some(resets_at), kind.reason())
+        }
+        Some(AuthMode::ChatGPT) => {
+            manager.rotate_chatgpt_account(conversation_id, Some(resets_at), kind.reason())
+        }
+        None => false,
+    }
+}
+
+fn credentials_label(manager: &AuthManager, conversation_id: ConversationId) -> String {
+    manager
+        .failover_credentials_label(Some(conversation_id))
+        .or_else(|| manager.auth().map(|auth| auth.identity_label()))
+        .unwrap_or_else(|| "not signed in".to_string())
+}
+
+fn format_time<Tz: TimeZone>(at: DateTime<Utc>, tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    at.with_timezone(tz).format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+impl Simulation {
+    fn render<Tz: TimeZone>(&self, tz: &Tz) -> Vec<String>
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        let mut lines = vec![format!("Before:   {}", self.before)];
+        if let Some(entry) = &self.recorded {
+            let resets = entry
+                .resets_at
+                .and_then(|ts| DateTime::<Utc>::from_timestamp(ts, 0))
+                .map(|at| format!(", resets {}", format_time(at, tz)))
+                .unwrap_or_default();
+            let auth = if entry.auth.is_empty() {
+                "-"
+            } else {
+                entry.auth.as_str()
+            };
+            lines.push(format!(
+                "Recorded: limit hit for {auth} on {}{resets}",
+                entry.model
+            ));
+        }
+        if self.switches.is_empty() {
+            lines.push("Switched: nothing".to_string());
+        }
+        for switch in &self.switches {
+            lines.push(format!(
+                "Switched: {} -> {} ({})",
+                switch.from,
+                switch.to,
+                switch.reason.description()
+            ));
+        }
+        lines.push(format!("After:    {}", self.after));
+        let outcome = match &self.outcome {
+            Outcome::Switched => "the request would be retried on the new credentials".to_string(),
+            Outcome::DowngradeModel(model) => {
+                format!("the session would continue on `{model}`")
+            }
+            Outcome::Wait(until) => format!(
+                "the turn would wait until {} and retry",
+                format_time(*until, tz)
+            ),
+            Outcome::Fail => "nothing left to switch to; the turn would fail".to_string(),
+        };
+        lines.push(format!("Outcome:  {outcome}"));
+        lines
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let outcome = match &self.outcome {
+            Outcome::Switched => json!({ "action": "switched" }),
+            Outcome::DowngradeModel(model) => {
+                json!({ "action": "downgrade_model", "model": model })
+            }
+            Outcome::Wait(until) => json!({ "action": "wait", "until": until.timestamp() }),
+            Outcome::Fail => json!({ "action": "fail" }),
+        };
+        json!({
+            "before": self.before,
+            "recorded": self.recorded,
+            "switches": self.switches,
+            "after": self.after,
+            "outcome": outcome,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn transitions_are_reported_in_order() {
+        let tz = FixedOffset::east_opt(0).expect("offset");
+        let resets_at = tz
+            .with_ymd_and_hms(2024, 3, 1, 12, 1, 0)
+            .single()
+            .expect("time")
+            .with_timezone(&Utc);
+        let simulation = Simulation {
+            before: "me@example.com".to_string(),
+            recorded: Some(LimitHitEntry {
+                ts: resets_at.timestamp() - 60,
+                auth: "me@example.com".to_string(),
+                model: "gpt-5".to_string(),
+                window_minutes: None,
+                resets_at: Some(resets_at.timestamp()),
+            }),
+            switches: vec![AuthSwitchedEvent {
+                from: "me@example.com".to_string(),
+                to: "API key ending in 1234".to_string(),
+                reason: AuthSwitchReason::UsageLimitReached,
+            }],
+            after: "API key ending in 1234".to_string(),
+            outcome: Outcome::Switched,
+        };
+        assert_eq!(
+            simulation.render(&tz),
+            vec![
+                "Before:   me@example.com",
+                "Recorded: limit hit for me@example.com on gpt-5, resets 2024-03-01 12:01:00",
+                "Switched: me@example.com -> API key ending in 1234 (usage limit reached)",
+                "After:    API key ending in 1234",
+                "Outcome:  the request would be retried on the new credentials",
+            ]
+        );
+
+        let stuck = Simulation {
+            switches: Vec::new(),
+            after: "me@example.com".to_string(),
+            outcome: Outcome::Wait(resets_at),
+            ..simulation
+        };
+        assert_eq!(
+            stuck.render(&tz)[2..],
+            [
+                "Switched: nothing",
+                "After:    me@example.com",
+                "Outcome:  the turn would wait until 2024-03-01 12:01:00 and retry",
+            ]
+        );
+        assert_eq!(
+            stuck.to_json()["outcome"],
+            json!({ "action": "wait", "until": resets_at.timestamp() })
+        );
+    }
+}
@@ -0,0 +1,40 @@
+//! `codex undo`: reverts the file changes of a recorded session's last turn
+//! and drops the turn, so `codex resume` continues from the turn before.
+
+use anyhow::Context;
+use anyhow::Result;
+use codex_core::INTERACTIVE_SESSION_SOURCES;
+use codex_core::RolloutRecorder;
+use codex_core::config::Config;
+use codex_core::find_conversation_path_by_id_str;
+use codex_core::undo_last_recorded_turn;
+
+/// Undoes the last turn of `session_id`, or of the most recent session.
+pub(crate) async fn run(config: &Config, session_id: Option<&str>) -> Result<()> {
+    let path = match session_id {
+        Some(session_id) => find_conversation_path_by_id_str(&config.codex_home, session_id)
+            .await?
+            .with_context(|| format!("no saved session found with id {session_id}"))?,
+        None => {
+            let provider_filter = vec![config.model_provider_id.clone()];
+            RolloutRecorder::list_conversations(
+                &config.codex_home,
+                1,
+                None,
+                INTERACTIVE_SESSION_SOURCES,
+                Some(provider_filter.as_slice()),
+                &config.model_provider_id,
+            )
+            .await?
+            .items
+            .into_iter()
+            .next()
+            .map(|item| item.path)
+            .context("no saved sessions found")?
+        }
+    };
+    let undone = undo_last_recorded_turn(config, &path).await?;
+    println!("{}", undone.message);
+    println!("Files restored in {}.", undone.cwd.display());
+    Ok(())
+}
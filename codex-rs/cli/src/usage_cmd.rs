@@ -0,0 +1,381 @@
+//! `codex usage report`: totals of the model usage recorded in
+//! `~/.codex/usage_history.jsonl`, grouped by day, project, model, and the
+//! signed-in identity, as a table, CSV, or JSON.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::DateTime;
+use chrono::Local;
+use chrono::NaiveDate;
+use chrono::TimeZone;
+use chrono::Utc;
+use codex_core::config::Config;
+use codex_core::state_dir::state_dir;
+use codex_core::usage_history::UsageEntry;
+use codex_core::usage_history::read_usage_history;
+use serde_json::Value as JsonValue;
+use serde_json::json;
+
+/// Column a report is grouped by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum UsageGroup {
+    /// Local calendar day.
+    Day,
+    /// Git repository root, or working directory outside a repository.
+    Project,
+    Model,
+    /// Signed-in account or API key.
+    Auth,
+}
+
+impl UsageGroup {
+    fn name(self) -> &'static str {
+        match self {
+            UsageGroup::Day => "day",
+            UsageGroup::Project => "project",
+            UsageGroup::Model => "model",
+            UsageGroup::Auth => "auth",
+        }
+    }
+
+    fn key<Tz: TimeZone>(self, entry: &UsageEntry, tz: &Tz) -> String {
+        match self {
+            UsageGroup::Day => local_date(entry.ts, tz)
+                .map(|date| date.to_string())
+                .unwrap_or_else(|| "?".to_string()),
+            UsageGroup::Project => entry.project.clone().unwrap_or_default(),
+            UsageGroup::Model => entry.model.clone(),
+            UsageGroup::Auth if entry.auth.is_empty() => entry.provider.clone(),
+            UsageGroup::Auth => entry.auth.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum UsageFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+/// Which recorded requests to report and how to group them.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ReportOptions {
+    pub(crate) group_by: Vec<UsageGroup>,
+    /// First local day to include.
+    pub(crate) since: Option<NaiveDate>,
+    /// Last local day to include.
+    pub(crate) until: Option<NaiveDate>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Totals {
+    requests: u64,
+    input_tokens: i64,
+    cached_input_tokens: i64,
+    output_tokens: i64,
+    reasoning_output_tokens: i64,
+    cost_usd: f64,
+    /// Requests to models without a known price, not included in `cost_usd`.
+    unpriced_requests: u64,
+}
+
+impl Totals {
+    fn add(&mut self, entry: &UsageEntry) {
+        self.requests += 1;
+        self.input_tokens += entry.input_tokens;
+        self.cached_input_tokens += entry.cached_input_tokens;
+        self.output_tokens += entry.output_tokens;
+        self.reasoning_output_tokens += entry.reasoning_output_tokens;
+        match entry.usd {
+            Some(usd) => self.cost_usd += usd,
+            None => self.unpriced_requests += 1,
+        }
+    }
+}
+
+const TOTAL_COLUMNS: [&str; 7] = [
+    "requests",
+    "input_tokens",
+    "cached_input_tokens",
+    "output_tokens",
+    "reasoning_output_tokens",
+    "cost_usd",
+    "unpriced_requests",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+struct UsageReport {
+    group_by: Vec<UsageGroup>,
+    /// One row per distinct combination of the grouped columns, sorted.
+    rows: Vec<(Vec<String>, Totals)>,
+}
+
+pub(crate) fn run_report(
+    config: &Config,
+    options: &ReportOptions,
+    format: UsageFormat,
+) -> Result<()> {
+    let entries = read_usage_history(
+        &state_dir(&config.codex_home),
+        config.shared_state_dir.as_deref(),
+    )?;
+    let report = aggregate(&entries, options, &Local);
+    match format {
+        UsageFormat::Table => print!("{}", report.render_table()),
+        UsageFormat::Csv => print!("{}", report.to_csv()),
+        UsageFormat::Json => println!("{}", serde_json::to_string_pretty(&report.to_json())?),
+    }
+    Ok(())
+}
+
+fn local_date<Tz: TimeZone>(ts: i64, tz: &Tz) -> Option<NaiveDate> {
+    DateTime::<Utc>::from_timestamp(ts, 0).map(|at| at.with_timezone(tz).date_naive())
+}
+
+fn aggregate<Tz: TimeZone>(
+    entries: &[UsageEntry],
+    options: &ReportOptions,
+    tz: &Tz,
+) -> UsageReport {
+    let mut rows: BTreeMap<Vec<String>, Totals> = BTreeMap::new();
+    for entry in entries {
+        let date = local_date(entry.ts, tz);
+        let after_since = options.since.is_none_or(|since| date >= Some(since));
+        let before_until = options.until.is_none_or(|until| date <= Some(until));
+        if !after_since || !before_until {
+            continue;
+        }
+        let key = options
+            .group_by
+            .iter()
+            .map(|group| group.key(entry, tz))
+            .collect();
+        rows.entry(key).or_default().add(entry);
+    }
+    UsageReport {
+        group_by: options.group_by.clone(),
+        rows: rows.into_iter().collect(),
+    }
+}
+
+impl UsageReport {
+    fn header(&self) -> Vec<&'static str> {
+        self.group_by
+            .iter()
+            .map(|group| group.name())
+            .chain(TOTAL_COLUMNS)
+            .collect()
+    }
+
+    fn cells(key: &[String], totals: &Totals) -> Vec<String> {
+        key.iter()
+            .cloned()
+            .chain([
+                totals.requests.to_string(),
+                totals.input_tokens.to_string(),
+                totals.cached_input_tokens.to_string(),
+                totals.output_tokens.to_string(),
+                totals.reasoning_output_tokens.to_string(),
+                format!("{:.4}", totals.cost_usd),
+                totals.unpriced_requests.to_string(),
+            ])
+            .collect()
+    }
+
+    fn render_table(&self) -> String {
+        if self.rows.is_empty() {
+            return "No usage recorded for this period.\n".to_string();
+        }
+        let mut lines: Vec<Vec<String>> = vec![
+            self.header()
+                .into_iter()
+                .map(str::to_string)
+                .collect::<Vec<_>>(),
+        ];
+        lines.extend(
+            self.rows
+                .iter()
+                .map(|(key, totals)| Self::cells(key, totals)),
+        );
+        let mut widths = vec![0; lines[0].len()];
+        for line in &lines {
+            for (width, cell) in widths.iter_mut().zip(line) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let grouped = self.group_by.len();
+        let mut out = String::new();
+        for line in &lines {
+            let cells: Vec<String> = line
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(i, (cell, width))| {
+                    if i < grouped {
+                        format!("{cell:<width$}")
+                    } else {
+                        format!("{cell:>width$}")
+                    }
+                })
+                .collect();
+            out.push_str(cells.join("  ").trim_end());
+            out.push('\n');
+        }
+        out
+    }
+
+    fn to_csv(&self) -> String {
+        let mut out = self.header().join(",");
+        out.push('\n');
+        for (key, totals) in &self.rows {
+            let cells: Vec<String> = Self::cells(key, totals)
+                .iter()
+                .map(|cell| csv_field(cell))
+                .collect();
+            out.push_str(&cells.join(","));
+            out.push('\n');
+        }
+        out
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let rows: Vec<JsonValue> = self
+            .rows
+            .iter()
+            .map(|(key, totals)| {
+                let mut row = serde_json::Map::new();
+                for (group, value) in self.group_by.iter().zip(key) {
+                    row.insert(group.name().to_string(), json!(value));
+                }
+                row.insert("requests".to_string(), json!(totals.requests));
+                row.insert("input_tokens".to_string(), json!(totals.input_tokens));
+                row.insert(
+                    "cached_input_tokens".to_string(),
+                    json!(totals.cached_input_tokens),
+                );
+                row.insert("output_tokens".to_string(), json!(totals.output_tokens));
+                row.insert(
+                    "reasoning_output_tokens".to_string(),
+                    json!(totals.reasoning_output_tokens),
+                );
+                row.insert("cost_usd".to_string(), json!(totals.cost_usd));
+                row.insert(
+                    "unpriced_requests".to_string(),
+                    json!(totals.unpriced_requests),
+                );
+                JsonValue::Object(row)
+            })
+            .collect();
+        JsonValue::Array(rows)
+    }
+}
+
+/// Quotes `field` when it contains a comma, quote, or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+    use pretty_assertions::assert_eq;
+
+    fn entry(ts: i64, model: &str, project: &str, usd: Option<f64>) -> UsageEntry {
+        UsageEntry {
+            ts,
+            model: model.to_string(),
+            provider: "OpenAI".to_string(),
+            auth: "me@example.com".to_string(),
+            project: Some(project.to_string()),
+            input_tokens: 1000,
+            cached_input_tokens: 200,
+            output_tokens: 100,
+            reasoning_output_tokens: 10,
+            usd,
+        }
+    }
+
+    fn sample() -> (FixedOffset, Vec<UsageEntry>) {
+        let tz = FixedOffset::east_opt(0).expect("offset");
+        let day = |d| {
+            tz.with_ymd_and_hms(2024, 3, d, 12, 0, 0)
+                .single()
+                .expect("time")
+                .timestamp()
+        };
+        let entries = vec![
+            entry(day(1), "gpt-5.1-codex", "/src/app", Some(0.5)),
+            entry(day(2), "gpt-5.1-codex", "/src/app", Some(0.25)),
+            entry(day(2), "llama3.2", "/src/a,b", None),
+            entry(day(3), "gpt-5.1-codex", "/src/app", Some(1.0)),
+        ];
+        (tz, entries)
+    }
+
+    #[test]
+    fn requests_are_filtered_by_day_and_grouped() {
+        let (tz, entries) = sample();
+        let options = ReportOptions {
+            group_by: vec![UsageGroup::Model],
+            since: NaiveDate::from_ymd_opt(2024, 3, 2),
+            until: NaiveDate::from_ymd_opt(2024, 3, 2),
+        };
+        let report = aggregate(&entries, &options, &tz);
+        assert_eq!(
+            report.to_json(),
+            json!([
+                {
+                    "model": "gpt-5.1-codex",
+                    "requests": 1,
+                    "input_tokens": 1000,
+                    "cached_input_tokens": 200,
+                    "output_tokens": 100,
+                    "reasoning_output_tokens": 10,
+                    "cost_usd": 0.25,
+                    "unpriced_requests": 0,
+                },
+                {
+                    "model": "llama3.2",
+                    "requests": 1,
+                    "input_tokens": 1000,
+                    "cached_input_tokens": 200,
+                    "output_tokens": 100,
+                    "reasoning_output_tokens": 10,
+                    "cost_usd": 0.0,
+                    "unpriced_requests": 1,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn csv_quotes_fields_and_table_aligns_columns() {
+        let (tz, entries) = sample();
+        let options = ReportOptions {
+            group_by: vec![UsageGroup::Project, UsageGroup::Auth],
+            since: None,
+            until: None,
+        };
+        let report = aggregate(&entries, &options, &tz);
+        assert_eq!(
+            report.to_csv(),
+            "project,auth,requests,input_tokens,cached_input_tokens,output_tokens,reasoning_output_tokens,cost_usd,unpriced_requests\n\
+             \"/src/a,b\",me@example.com,1,1000,200,100,10,0.0000,1\n\
+             /src/app,me@example.com,3,3000,600,300,30,1.7500,0\n"
+        );
+        assert_eq!(
+            report.render_table(),
+            "project   auth            requests  input_tokens  cached_input_tokens  output_tokens  reasoning_output_tokens  cost_usd  unpriced_requests\n\
+             /src/a,b  me@example.com         1          1000                  200            100                       10    0.0000                  1\n\
+             /src/app  me@example.com         3          3000                  600            300                       30    1.7500                  0\n"
+        );
+    }
+}
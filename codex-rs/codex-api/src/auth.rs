@@ -10,13 +10,19 @@ pub trait AuthProvider: Send + Sync {
     fn account_id(&self) -> Option<String> {
         None
     }
+    /// Header that carries the credential. Defaults to
+    /// `Authorization: Bearer <token>`; gateways may expect another shape.
+    fn auth_header(&self) -> Option<(http::HeaderName, String)> {
+        self.bearer_token()
+            .map(|token| (http::header::AUTHORIZATION, format!("Bearer {token}")))
+    }
 }
 
 pub(crate) fn add_auth_headers<A: AuthProvider>(auth: &A, mut req: Request) -> Request {
-    if let Some(token) = auth.bearer_token()
-        && let Ok(header) = format!("Bearer {token}").parse()
+    if let Some((name, value)) = auth.auth_header()
+        && let Ok(header) = value.parse()
     {
-        let _ = req.headers.insert(http::header::AUTHORIZATION, header);
+        let _ = req.headers.insert(name, header);
     }
     if let Some(account_id) = auth.account_id()
         && let Ok(header) = account_id.parse()
@@ -1,8 +1,12 @@
 use codex_protocol::protocol::CreditsSnapshot;
 use codex_protocol::protocol::RateLimitSnapshot;
 use codex_protocol::protocol::RateLimitWindow;
+use codex_protocol::protocol::RequestRateLimits;
 use http::HeaderMap;
 use std::fmt::Display;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 #[derive(Debug)]
 pub struct RateLimitError {
@@ -15,7 +19,8 @@ impl Display for RateLimitError {
     }
 }
 
-/// Parses the bespoke Codex rate-limit headers into a `RateLimitSnapshot`.
+/// Parses the bespoke Codex rate-limit headers, and the `x-ratelimit-*`
+/// headers of API-key providers, into a `RateLimitSnapshot`.
 pub fn parse_rate_limit(headers: &HeaderMap) -> Option<RateLimitSnapshot> {
     let primary = parse_rate_limit_window(
         headers,
@@ -32,11 +37,13 @@ pub fn parse_rate_limit(headers: &HeaderMap) -> Option<RateLimitSnapshot> {
     );
 
     let credits = parse_credits_snapshot(headers);
+    let request_limits = parse_request_rate_limits(headers, SystemTime::now());
 
     Some(RateLimitSnapshot {
         primary,
         secondary,
         credits,
+        request_limits,
     })
 }
 
@@ -78,6 +85,56 @@ fn parse_credits_snapshot(headers: &HeaderMap) -> Option<CreditsSnapshot> {
     })
 }
 
+fn parse_request_rate_limits(headers: &HeaderMap, now: SystemTime) -> Option<RequestRateLimits> {
+    let remaining_requests = parse_header_i64(headers, "x-ratelimit-remaining-requests");
+    let remaining_tokens = parse_header_i64(headers, "x-ratelimit-remaining-tokens");
+    if remaining_requests.is_none() && remaining_tokens.is_none() {
+        return None;
+    }
+    let resets_at = ["x-ratelimit-reset-requests", "x-ratelimit-reset-tokens"]
+        .into_iter()
+        .filter_map(|name| parse_header_str(headers, name).and_then(parse_reset_duration))
+        .max()
+        .and_then(|after| (now + after).duration_since(UNIX_EPOCH).ok())
+        .map(|at| at.as_secs_f64().ceil() as i64);
+    Some(RequestRateLimits {
+        remaining_requests,
+        limit_requests: parse_header_i64(headers, "x-ratelimit-limit-requests"),
+        remaining_tokens,
+        limit_tokens: parse_header_i64(headers, "x-ratelimit-limit-tokens"),
+        resets_at,
+    })
+}
+
+/// Parses reset times such as `"20ms"`, `"6m0s"`, or `"1h2m3.5s"`.
+fn parse_reset_duration(raw: &str) -> Option<Duration> {
+    let mut rest = raw.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    let mut total = 0.0;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .filter(|end| *end > 0)?;
+        let value: f64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let seconds = match &rest[..unit_len] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            _ => return None,
+        };
+        total += value * seconds;
+        rest = &rest[unit_len..];
+    }
+    Duration::try_from_secs_f64(total).ok()
+}
+
 fn parse_header_f64(headers: &HeaderMap, name: &str) -> Option<f64> {
     parse_header_str(headers, name)?
         .parse::<f64>()
@@ -103,3 +160,41 @@ fn parse_header_bool(headers: &HeaderMap, name: &str) -> Option<bool> {
 fn parse_header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
     headers.get(name)?.to_str().ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn request_budgets_are_read_from_ratelimit_headers() {
+        let mut headers = HeaderMap::new();
+        for (name, value) in [
+            ("x-ratelimit-limit-requests", "5000"),
+            ("x-ratelimit-remaining-requests", "4999"),
+            ("x-ratelimit-reset-requests", "12ms"),
+            ("x-ratelimit-limit-tokens", "800000"),
+            ("x-ratelimit-remaining-tokens", "612000"),
+            ("x-ratelimit-reset-tokens", "1m30.5s"),
+        ] {
+            headers.insert(name, HeaderValue::from_static(value));
+        }
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        assert_eq!(
+            parse_request_rate_limits(&headers, now),
+            Some(RequestRateLimits {
+                remaining_requests: Some(4999),
+                limit_requests: Some(5000),
+                remaining_tokens: Some(612_000),
+                limit_tokens: Some(800_000),
+                resets_at: Some(1_700_000_091),
+            })
+        );
+        assert_eq!(parse_request_rate_limits(&HeaderMap::new(), now), None);
+        assert_eq!(parse_reset_duration("6m0s"), Some(Duration::from_secs(360)));
+        assert_eq!(parse_reset_duration("1h"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_reset_duration("soon"), None);
+    }
+}
@@ -70,6 +70,22 @@ pub async fn process_chat_sse<S>(
                         .await;
                 }
 
+                // Some gateways end the stream without a finish_reason; keep
+                // any tool call that at least got a name.
+                for call_id in tool_call_order.drain(..) {
+                    let state = tool_calls.remove(&call_id).unwrap_or_default();
+                    let Some(name) = state.name else {
+                        continue;
+                    };
+                    let item = ResponseItem::FunctionCall {
+                        id: None,
+                        name,
+                        arguments: state.arguments,
+                        call_id,
+                    };
+                    let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
+                }
+
                 if let Some(assistant) = assistant_item {
                     let _ = tx_event
                         .send(Ok(ResponseEvent::OutputItemDone(assistant)))
@@ -185,8 +201,14 @@ pub async fn process_chat_sse<S>(
                 }
             }
 
-            let finish_reason = choice.get("finish_reason").and_then(|r| r.as_str());
-            if finish_reason == Some("stop") {
+            let finish_reason = choice
+                .get("finish_reason")
+                .and_then(|r| r.as_str())
+                .and_then(FinishReason::parse);
+            if finish_reason == Some(FinishReason::Stop) {
+                // A stop ends the turn; tool calls that never finished are dropped.
+                tool_call_order.clear();
+                tool_calls.clear();
                 if let Some(reasoning) = reasoning_item.take() {
                     let _ = tx_event
                         .send(Ok(ResponseEvent::OutputItemDone(reasoning)))
@@ -210,12 +232,12 @@ pub async fn process_chat_sse<S>(
                 continue;
             }
 
-            if finish_reason == Some("length") {
+            if finish_reason == Some(FinishReason::Length) {
                 let _ = tx_event.send(Err(ApiError::ContextWindowExceeded)).await;
                 return;
             }
 
-            if finish_reason == Some("tool_calls") {
+            if finish_reason == Some(FinishReason::ToolCalls) {
                 if let Some(reasoning) = reasoning_item.take() {
                     let _ = tx_event
                         .send(Ok(ResponseEvent::OutputItemDone(reasoning)))
@@ -237,6 +259,28 @@ pub async fn process_chat_sse<S>(
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FinishReason {
+    Stop,
+    Length,
+    ToolCalls,
+}
+
+impl FinishReason {
+    /// Accepts the OpenAI values plus the spellings other backends and
+    /// gateways pass through (`end_turn`, `tool_use`, `max_tokens`, ...).
+    fn parse(reason: &str) -> Option<Self> {
+        match reason.to_ascii_lowercase().as_str() {
+            "stop" | "end_turn" | "stop_sequence" | "eos" | "complete" | "completed" => {
+                Some(FinishReason::Stop)
+            }
+            "length" | "max_tokens" | "max_output_tokens" => Some(FinishReason::Length),
+            "tool_calls" | "tool_use" | "function_call" => Some(FinishReason::ToolCalls),
+            _ => None,
+        }
+    }
+}
+
 async fn append_assistant_text(
     tx_event: &mpsc::Sender<Result<ResponseEvent, ApiError>>,
     assistant_item: &mut Option<ResponseItem>,
@@ -501,4 +545,57 @@ mod tests {
         }));
         assert_matches!(events.last(), Some(ResponseEvent::Completed { .. }));
     }
+
+    #[tokio::test]
+    async fn accepts_nonstandard_finish_reasons() {
+        let delta_tool = json!({
+            "choices": [{
+                "delta": {
+                    "tool_calls": [{
+                        "id": "call_a",
+                        "function": { "name": "do_a", "arguments": "{}" }
+                    }]
+                }
+            }]
+        });
+        let finish = json!({
+            "choices": [{
+                "finish_reason": "TOOL_USE"
+            }]
+        });
+
+        let body = build_body(&[delta_tool, finish]);
+        let events = collect_events(&body).await;
+        assert_matches!(
+            &events[..],
+            [
+                ResponseEvent::OutputItemDone(ResponseItem::FunctionCall { call_id, .. }),
+                ResponseEvent::Completed { .. }
+            ] if call_id == "call_a"
+        );
+    }
+
+    #[tokio::test]
+    async fn keeps_named_tool_calls_when_finish_reason_is_missing() {
+        let delta_tool = json!({
+            "choices": [{
+                "delta": {
+                    "tool_calls": [
+                        { "id": "call_a", "function": { "name": "do_a", "arguments": "{}" } },
+                        { "id": "call_b", "function": { "arguments": "{}" } }
+                    ]
+                }
+            }]
+        });
+
+        let body = build_body(&[delta_tool]);
+        let events = collect_events(&body).await;
+        assert_matches!(
+            &events[..],
+            [
+                ResponseEvent::OutputItemDone(ResponseItem::FunctionCall { call_id, name, .. }),
+                ResponseEvent::Completed { .. }
+            ] if call_id == "call_a" && name == "do_a"
+        );
+    }
 }
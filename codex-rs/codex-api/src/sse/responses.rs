@@ -80,13 +80,18 @@ struct ResponseCompleted {
     usage: Option<ResponseCompletedUsage>,
 }
 
+// Gateways do not always report every usage field; a partial block is not
+// worth failing the turn over.
 #[derive(Debug, Deserialize)]
 struct ResponseCompletedUsage {
+    #[serde(default)]
     input_tokens: i64,
     input_tokens_details: Option<ResponseCompletedInputTokensDetails>,
+    #[serde(default)]
     output_tokens: i64,
     output_tokens_details: Option<ResponseCompletedOutputTokensDetails>,
-    total_tokens: i64,
+    #[serde(default)]
+    total_tokens: Option<i64>,
 }
 
 impl From<ResponseCompletedUsage> for TokenUsage {
@@ -102,18 +107,22 @@ impl From<ResponseCompletedUsage> for TokenUsage {
                 .output_tokens_details
                 .map(|d| d.reasoning_tokens)
                 .unwrap_or(0),
-            total_tokens: val.total_tokens,
+            total_tokens: val
+                .total_tokens
+                .unwrap_or(val.input_tokens + val.output_tokens),
         }
     }
 }
 
 #[derive(Debug, Deserialize)]
 struct ResponseCompletedInputTokensDetails {
+    #[serde(default)]
     cached_tokens: i64,
 }
 
 #[derive(Debug, Deserialize)]
 struct ResponseCompletedOutputTokensDetails {
+    #[serde(default)]
     reasoning_tokens: i64,
 }
 
@@ -463,6 +472,23 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn tolerates_partial_usage() {
+        let events = run_sse(vec![json!({
+            "type": "response.completed",
+            "response": { "id": "resp1", "usage": { "input_tokens": 10, "output_tokens": 5 } }
+        })])
+        .await;
+
+        assert_matches!(
+            &events[..],
+            [ResponseEvent::Completed {
+                response_id,
+                token_usage: Some(usage),
+            }] if response_id == "resp1" && usage.total_tokens == 15 && usage.cached_input_tokens == 0
+        );
+    }
+
     #[tokio::test]
     async fn error_when_missing_completed() {
         let item1 = json!({
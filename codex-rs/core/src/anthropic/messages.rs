@@ -0,0 +1,494 @@
+//! Translation between Codex's conversation items and the Anthropic
+//! Messages API request and streamed events.
+
+use std::collections::BTreeMap;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::TokenUsage;
+use serde_json::Map;
+use serde_json::Value;
+use serde_json::json;
+
+use crate::client_common::ResponseEvent;
+
+/// `max_tokens` is required by the Messages API. Claude 3.x models accept
+/// at most 8192 output tokens; later models accept at least 32000.
+const LEGACY_MAX_OUTPUT_TOKENS: u64 = 8_192;
+const DEFAULT_MAX_OUTPUT_TOKENS: u64 = 32_000;
+
+pub(crate) fn build_request(
+    model: &str,
+    instructions: &str,
+    input: &[ResponseItem],
+    tools: &[Value],
+) -> Value {
+    let mut system = vec![json!({ "type": "text", "text": instructions })];
+    let mut messages: Vec<(&'static str, Vec<Value>)> = Vec::new();
+    let mut push = |role: &'static str, block: Value| match messages.last_mut() {
+        Some((last_role, blocks)) if *last_role == role => blocks.push(block),
+        _ => messages.push((role, vec![block])),
+    };
+
+    for item in input {
+        match item {
+            ResponseItem::Message { role, content, .. } => {
+                let role = match role.as_str() {
+                    "assistant" => "assistant",
+                    "user" => "user",
+                    _ => {
+                        system.extend(content.iter().filter_map(text_block));
+                        continue;
+                    }
+                };
+                for block in content.iter().filter_map(content_block) {
+                    push(role, block);
+                }
+            }
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => push(
+                "assistant",
+                json!({
+                    "type": "tool_use",
+                    "id": call_id,
+                    "name": name,
+                    "input": tool_input(arguments),
+                }),
+            ),
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                let mut block = json!({
+                    "type": "tool_result",
+                    "tool_use_id": call_id,
+                    "content": output.content,
+                });
+                if output.success == Some(false) {
+                    block["is_error"] = json!(true);
+                }
+                push("user", block);
+            }
+            _ => {}
+        }
+    }
+
+    // Cache the system prompt and the conversation so far; the next turn
+    // re-reads both at the cached rate.
+    if let Some(block) = system.last_mut() {
+        block["cache_control"] = json!({ "type": "ephemeral" });
+    }
+    if let Some(block) = messages
+        .last_mut()
+        .and_then(|(_, blocks)| blocks.last_mut())
+    {
+        block["cache_control"] = json!({ "type": "ephemeral" });
+    }
+
+    let mut body = Map::new();
+    body.insert("model".to_string(), json!(model));
+    body.insert("max_tokens".to_string(), json!(max_output_tokens(model)));
+    body.insert("stream".to_string(), json!(true));
+    body.insert("system".to_string(), Value::Array(system));
+    body.insert(
+        "messages".to_string(),
+        messages
+            .into_iter()
+            .map(|(role, content)| json!({ "role": role, "content": content }))
+            .collect(),
+    );
+    let tools: Vec<Value> = tools.iter().filter_map(tool_definition).collect();
+    if !tools.is_empty() {
+        body.insert("tools".to_string(), Value::Array(tools));
+    }
+    Value::Object(body)
+}
+
+fn max_output_tokens(model: &str) -> u64 {
+    if model.contains("claude-3-") {
+        LEGACY_MAX_OUTPUT_TOKENS
+    } else {
+        DEFAULT_MAX_OUTPUT_TOKENS
+    }
+}
+
+fn text_block(item: &ContentItem) -> Option<Value> {
+    match item {
+        ContentItem::InputText { text } | ContentItem::OutputText { text }
+            if !text.trim().is_empty() =>
+        {
+            Some(json!({ "type": "text", "text": text }))
+        }
+        _ => None,
+    }
+}
+
+fn content_block(item: &ContentItem) -> Option<Value> {
+    match item {
+        ContentItem::InputImage { image_url } => {
+            let inline = image_url
+                .strip_prefix("data:")
+                .and_then(|rest| rest.split_once(";base64,"));
+            Some(match inline {
+                Some((media_type, data)) => json!({
+                    "type": "image",
+                    "source": { "type": "base64", "media_type": media_type, "data": data },
+                }),
+                None => json!({
+                    "type": "image",
+                    "source": { "type": "url", "url": image_url },
+                }),
+            })
+        }
+        _ => text_block(item),
+    }
+}
+
+/// Tool arguments are a JSON string; `tool_use.input` must be an object.
+fn tool_input(arguments: &str) -> Value {
+    match serde_json::from_str::<Value>(arguments) {
+        Ok(value @ Value::Object(_)) => value,
+        _ => json!({ "arguments": arguments }),
+    }
+}
+
+/// Converts a Responses API function tool into an Anthropic tool definition.
+fn tool_definition(tool: &Value) -> Option<Value> {
+    if tool.get("type").and_then(Value::as_str) != Some("function") {
+        return None;
+    }
+    Some(json!({
+        "name": tool.get("name")?,
+        "description": tool.get("description").cloned().unwrap_or_else(|| json!("")),
+        "input_schema": tool
+            .get("parameters")
+            .cloned()
+            .unwrap_or_else(|| json!({ "type": "object", "properties": {} })),
+    }))
+}
+
+/// A `tool_use` block whose JSON input is still streaming in.
+struct PendingToolUse {
+    id: String,
+    name: String,
+    input: String,
+}
+
+/// Accumulates Messages API stream events into response events. Text is
+/// streamed as deltas; tool calls are emitted once the turn ends.
+#[derive(Default)]
+pub(crate) struct StreamState {
+    message: Option<Vec<ContentItem>>,
+    /// Tool calls keyed by content block index, so they keep their order.
+    tool_uses: BTreeMap<u64, PendingToolUse>,
+    response_id: String,
+    input_tokens: i64,
+    cache_read_tokens: i64,
+    cache_creation_tokens: i64,
+    output_tokens: i64,
+}
+
+impl StreamState {
+    pub(crate) fn handle_event(&mut self, event: &Value) -> Vec<ResponseEvent> {
+        let mut events = Vec::new();
+        let index = event.get("index").and_then(Value::as_u64).unwrap_or(0);
+        match event.get("type").and_then(Value::as_str) {
+            Some("message_start") => {
+                if let Some(id) = event.pointer("/message/id").and_then(Value::as_str) {
+                    self.response_id = id.to_string();
+                }
+                if let Some(usage) = event.pointer("/message/usage") {
+                    self.record_usage(usage);
+                }
+            }
+            Some("content_block_start") => {
+                let block = event.get("content_block").unwrap_or(&Value::Null);
+                if block.get("type").and_then(Value::as_str) == Some("tool_use") {
+                    let field = |name: &str| {
+                        block
+                            .get(name)
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_string()
+                    };
+                    self.tool_uses.insert(
+                        index,
+                        PendingToolUse {
+                            id: field("id"),
+                            name: field("name"),
+                            input: String::new(),
+                        },
+                    );
+                }
+            }
+            Some("content_block_delta") => {
+                let delta = event.get("delta").unwrap_or(&Value::Null);
+                match delta.get("type").and_then(Value::as_str) {
+                    Some("text_delta") => {
+                        if let Some(text) = delta.get("text").and_then(Value::as_str)
+                            && !text.is_empty()
+                        {
+                            let message = self.message.get_or_insert_with(|| {
+                                events.push(ResponseEvent::OutputItemAdded(
+                                    ResponseItem::Message {
+                                        id: None,
+                                        role: "assistant".to_string(),
+                                        content: Vec::new(),
+                                    },
+                                ));
+                                Vec::new()
+                            });
+                            match message.last_mut() {
+                                Some(ContentItem::OutputText { text: existing }) => {
+                                    existing.push_str(text)
+                                }
+                                _ => message.push(ContentItem::OutputText {
+                                    text: text.to_string(),
+                                }),
+                            }
+                            events.push(ResponseEvent::OutputTextDelta(text.to_string()));
+                        }
+                    }
+                    Some("input_json_delta") => {
+                        if let Some(tool_use) = self.tool_uses.get_mut(&index)
+                            && let Some(partial) = delta.get("partial_json").and_then(Value::as_str)
+                        {
+                            tool_use.input.push_str(partial);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Some("message_delta") => {
+                if let Some(usage) = event.get("usage") {
+                    self.record_usage(usage);
+                }
+            }
+            _ => {}
+        }
+        events
+    }
+
+    pub(crate) fn finish(self) -> Vec<ResponseEvent> {
+        let mut events = Vec::new();
+        if let Some(content) = self.message {
+            events.push(ResponseEvent::OutputItemDone(ResponseItem::Message {
+                id: None,
+                role: "assistant".to_string(),
+                content,
+            }));
+        }
+        events.extend(self.tool_uses.into_values().map(|tool_use| {
+            ResponseEvent::OutputItemDone(ResponseItem::FunctionCall {
+                id: None,
+                name: tool_use.name,
+                // A tool without parameters streams no input at all.
+                arguments: if tool_use.input.trim().is_empty() {
+                    "{}".to_string()
+                } else {
+                    tool_use.input
+                },
+                call_id: tool_use.id,
+            })
+        }));
+        let input_tokens = self.input_tokens + self.cache_read_tokens + self.cache_creation_tokens;
+        events.push(ResponseEvent::Completed {
+            response_id: self.response_id,
+            token_usage: Some(TokenUsage {
+                input_tokens,
+                cached_input_tokens: self.cache_read_tokens,
+                output_tokens: self.output_tokens,
+                reasoning_output_tokens: 0,
+                total_tokens: input_tokens + self.output_tokens,
+            }),
+        });
+        events
+    }
+
+    /// Usage arrives in `message_start` and again, cumulatively, in
+    /// `message_delta`; later values replace earlier ones.
+    fn record_usage(&mut self, usage: &Value) {
+        let field = |name: &str| usage.get(name).and_then(Value::as_i64);
+        if let Some(tokens) = field("input_tokens") {
+            self.input_tokens = tokens;
+        }
+        if let Some(tokens) = field("cache_read_input_tokens") {
+            self.cache_read_tokens = tokens;
+        }
+        if let Some(tokens) = field("cache_creation_input_tokens") {
+            self.cache_creation_tokens = tokens;
+        }
+        if let Some(tokens) = field("output_tokens") {
+            self.output_tokens = tokens;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn builds_messages_with_tool_round_trip() {
+        let input = vec![
+            ResponseItem::Message {
+                id: None,
+                role: "developer".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "<environment_context />".to_string(),
+                }],
+            },
+            ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "list files".to_string(),
+                }],
+            },
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: r#"{"command":["ls"]}"#.to_string(),
+                call_id: "toolu_1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "toolu_1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "ls: permission denied".to_string(),
+                    content_items: None,
+                    success: Some(false),
+                },
+            },
+        ];
+        let tools = vec![
+            json!({
+                "type": "function",
+                "name": "shell",
+                "description": "Runs a command",
+                "strict": false,
+                "parameters": {
+                    "type": "object",
+                    "properties": { "command": { "type": "array", "items": { "type": "string" } } },
+                    "required": ["command"],
+                },
+            }),
+            json!({ "type": "custom", "name": "apply_patch" }),
+        ];
+
+        assert_eq!(
+            build_request("claude-sonnet-4-5", "You are Codex.", &input, &tools),
+            json!({
+                "model": "claude-sonnet-4-5",
+                "max_tokens": 32000,
+                "stream": true,
+                "system": [
+                    { "type": "text", "text": "You are Codex." },
+                    {
+                        "type": "text",
+                        "text": "<environment_context />",
+                        "cache_control": { "type": "ephemeral" },
+                    },
+                ],
+                "messages": [
+                    { "role": "user", "content": [{ "type": "text", "text": "list files" }] },
+                    { "role": "assistant", "content": [{
+                        "type": "tool_use",
+                        "id": "toolu_1",
+                        "name": "shell",
+                        "input": { "command": ["ls"] },
+                    }] },
+                    { "role": "user", "content": [{
+                        "type": "tool_result",
+                        "tool_use_id": "toolu_1",
+                        "content": "ls: permission denied",
+                        "is_error": true,
+                        "cache_control": { "type": "ephemeral" },
+                    }] },
+                ],
+                "tools": [{
+                    "name": "shell",
+                    "description": "Runs a command",
+                    "input_schema": {
+                        "type": "object",
+                        "properties": { "command": { "type": "array", "items": { "type": "string" } } },
+                        "required": ["command"],
+                    },
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn streams_text_and_collects_tool_uses() {
+        let mut state = StreamState::default();
+        assert!(
+            state
+                .handle_event(&json!({
+                    "type": "message_start",
+                    "message": {
+                        "id": "msg_1",
+                        "usage": { "input_tokens": 10, "cache_read_input_tokens": 90, "output_tokens": 1 },
+                    },
+                }))
+                .is_empty()
+        );
+        let text = state.handle_event(&json!({
+            "type": "content_block_delta",
+            "index": 0,
+            "delta": { "type": "text_delta", "text": "Listing" },
+        }));
+        assert!(matches!(
+            text.as_slice(),
+            [
+                ResponseEvent::OutputItemAdded(ResponseItem::Message { .. }),
+                ResponseEvent::OutputTextDelta(delta),
+            ] if delta == "Listing"
+        ));
+        state.handle_event(&json!({
+            "type": "content_block_start",
+            "index": 1,
+            "content_block": { "type": "tool_use", "id": "toolu_1", "name": "shell", "input": {} },
+        }));
+        for partial in [r#"{"command":"#, r#"["ls"]}"#] {
+            state.handle_event(&json!({
+                "type": "content_block_delta",
+                "index": 1,
+                "delta": { "type": "input_json_delta", "partial_json": partial },
+            }));
+        }
+        state.handle_event(&json!({
+            "type": "message_delta",
+            "delta": { "stop_reason": "tool_use" },
+            "usage": { "output_tokens": 25 },
+        }));
+
+        let events = state.finish();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            &events[1],
+            ResponseEvent::OutputItemDone(ResponseItem::FunctionCall { name, arguments, call_id, .. })
+                if name == "shell" && arguments == r#"{"command":["ls"]}"# && call_id == "toolu_1"
+        ));
+        let ResponseEvent::Completed {
+            response_id,
+            token_usage,
+        } = &events[2]
+        else {
+            panic!("expected Completed, got {:?}", events[2]);
+        };
+        assert_eq!(response_id, "msg_1");
+        assert_eq!(
+            token_usage,
+            &Some(TokenUsage {
+                input_tokens: 100,
+                cached_input_tokens: 90,
+                output_tokens: 25,
+                reasoning_output_tokens: 0,
+                total_tokens: 125,
+            })
+        );
+    }
+}
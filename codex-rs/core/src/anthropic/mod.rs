@@ -0,0 +1,259 @@
+//! Anthropic provider (`wire_api = "anthropic"`).
+//!
+//! Turns are streamed from the Messages API at `{base_url}/messages`,
+//! authenticated with the provider's `env_key` (or
+//! `experimental_bearer_token`) sent as `x-api-key`.
+
+mod messages;
+
+use codex_api::ResponseStream as ApiResponseStream;
+use codex_api::TransportError;
+use codex_api::error::ApiError;
+use codex_protocol::models::ResponseItem;
+use eventsource_stream::Eventsource;
+use futures::StreamExt;
+use http::StatusCode;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tracing::debug;
+
+use crate::api_bridge::map_api_error;
+use crate::client_common::ResponseEvent;
+use crate::error::CodexErr;
+use crate::error::EnvVarError;
+use crate::error::Result;
+use crate::model_provider_info::ANTHROPIC_API_KEY_ENV_VAR;
+use crate::model_provider_info::ModelProviderInfo;
+
+pub(crate) const ANTHROPIC_API_BASE_URL: &str = "https://api.anthropic.com/v1";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Opens a streaming Messages API request for one turn.
+pub(crate) async fn stream_messages(
+    provider: &ModelProviderInfo,
+    model: &str,
+    instructions: &str,
+    input: &[ResponseItem],
+    tools: &[Value],
+) -> Result<ApiResponseStream> {
+    let api_key = provider
+        .api_key()?
+        .or_else(|| provider.experimental_bearer_token.clone())
+        .ok_or_else(|| {
+            CodexErr::EnvVar(EnvVarError {
+                var: ANTHROPIC_API_KEY_ENV_VAR.to_string(),
+                instructions: provider.env_key_instructions.clone(),
+            })
+        })?;
+    let base_url = provider
+        .base_url
+        .as_deref()
+        .unwrap_or(ANTHROPIC_API_BASE_URL)
+        .trim_end_matches('/');
+    let body = messages::build_request(model, instructions, input, tools);
+
+    let mut request = provider
+        .build_reqwest_client()?
+        .post(format!("{base_url}/messages"))
+        .header("x-api-key", api_key)
+        .header("anthropic-version", ANTHROPIC_VERSION)
+        .json(&body);
+    if let Some(query_params) = &provider.query_params {
+        request = request.query(query_params);
+    }
+    for (name, value) in &provider.build_header_map()? {
+        request = request.header(name, value);
+    }
+
+    let response = request.send().await.map_err(|err| {
+        map_api_error(ApiError::Transport(if err.is_timeout() {
+            TransportError::Timeout
+        } else {
+            TransportError::Network(err.to_string())
+        }))
+    })?;
+    let status = response.status();
+    if !status.is_success() {
+        let headers = response.headers().clone();
+        let text = response.text().await.unwrap_or_default();
+        return Err(map_api_error(classify_error(status, headers, text)));
+    }
+
+    let idle_timeout = provider.stream_idle_timeout();
+    let (tx_event, rx_event) = mpsc::channel::<std::result::Result<ResponseEvent, ApiError>>(1600);
+    tokio::spawn(async move {
+        let mut stream = response.bytes_stream().eventsource();
+        let mut state = messages::StreamState::default();
+        loop {
+            let event = match timeout(idle_timeout, stream.next()).await {
+                Ok(Some(Ok(event))) => event,
+                Ok(Some(Err(err))) => {
+                    let _ = tx_event.send(Err(ApiError::Stream(err.to_string()))).await;
+                    return;
+                }
+                Ok(None) => {
+                    let _ = tx_event
+                        .send(Err(ApiError::Stream(
+                            "stream closed before message_stop".into(),
+                        )))
+                        .await;
+                    return;
+                }
+                Err(_) => {
+                    let _ = tx_event
+                        .send(Err(ApiError::Stream("idle timeout waiting for SSE".into())))
+                        .await;
+                    return;
+                }
+            };
+            let data: Value = match serde_json::from_str(&event.data) {
+                Ok(data) => data,
+                Err(err) => {
+                    debug!(
+                        "Failed to parse Anthropic SSE event: {err}, data: {}",
+                        event.data
+                    );
+                    continue;
+                }
+            };
+            match data.get("type").and_then(Value::as_str) {
+                Some("error") => {
+                    let _ = tx_event.send(Err(stream_error(&data))).await;
+                    return;
+                }
+                Some("message_stop") => break,
+                _ => {}
+            }
+            for event in state.handle_event(&data) {
+                if tx_event.send(Ok(event)).await.is_err() {
+                    return;
+                }
+            }
+        }
+        for event in state.finish() {
+            if tx_event.send(Ok(event)).await.is_err() {
+                return;
+            }
+        }
+    });
+    Ok(ApiResponseStream { rx_event })
+}
+
+/// A prompt over the model's limit is reported as a full context window.
+/// Rate limits (429) and overload (503, Anthropic's 529) stay HTTP errors so
+/// they are recorded as limit hits and move the `fallback` chain.
+fn classify_error(status: StatusCode, headers: http::HeaderMap, body: String) -> ApiError {
+    match status.as_u16() {
+        400 if body.contains("prompt is too long") => ApiError::ContextWindowExceeded,
+        _ => ApiError::Transport(TransportError::Http {
+            status,
+            headers: Some(headers),
+            body: Some(body),
+        }),
+    }
+}
+
+/// Maps an `error` event sent mid-stream.
+fn stream_error(data: &Value) -> ApiError {
+    let kind = data
+        .pointer("/error/type")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let message = data
+        .pointer("/error/message")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    match kind {
+        "overloaded_error" | "rate_limit_error" | "api_error" => ApiError::Retryable {
+            message: format!("Anthropic {kind}: {message}"),
+            delay: None,
+        },
+        _ if message.contains("prompt is too long") => ApiError::ContextWindowExceeded,
+        _ => ApiError::Stream(format!("Anthropic {kind}: {message}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AuthManager;
+    use crate::auth::AuthCredentialsStoreMode;
+    use crate::auth::FailoverEntry;
+    use crate::client::failover_trigger;
+    use crate::limit_history::read_limit_history;
+    use crate::model_provider_info::WireApi;
+    use crate::model_provider_info::built_in_model_providers;
+    use codex_protocol::protocol::AuthSwitchReason;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn classifies_overload_and_prompt_too_long() {
+        assert!(matches!(
+            failover_trigger(&map_api_error(classify_error(
+                StatusCode::from_u16(529).expect("status"),
+                http::HeaderMap::new(),
+                r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#
+                    .to_string(),
+            ))),
+            Some((_, AuthSwitchReason::ProviderError))
+        ));
+        assert!(matches!(
+            classify_error(
+                StatusCode::BAD_REQUEST,
+                http::HeaderMap::new(),
+                r#"{"type":"error","error":{"type":"invalid_request_error","message":"prompt is too long: 210000 tokens > 200000 maximum"}}"#.to_string(),
+            ),
+            ApiError::ContextWindowExceeded
+        ));
+        assert!(matches!(
+            stream_error(&json!({
+                "type": "error",
+                "error": { "type": "overloaded_error", "message": "Overloaded" },
+            })),
+            ApiError::Retryable { .. }
+        ));
+    }
+
+    #[test]
+    fn rate_limits_advance_the_fallback_chain() {
+        let providers = built_in_model_providers();
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let manager = AuthManager::new(
+            codex_home.path().to_path_buf(),
+            false,
+            AuthCredentialsStoreMode::File,
+        );
+        manager.configure_failover(
+            ["anthropic", "gemini"]
+                .iter()
+                .map(|name| FailoverEntry::resolve(name, &providers).expect("known entry"))
+                .collect(),
+        );
+
+        let err = map_api_error(classify_error(
+            StatusCode::TOO_MANY_REQUESTS,
+            http::HeaderMap::new(),
+            r#"{"type":"error","error":{"type":"rate_limit_error","message":"Rate limited"}}"#
+                .to_string(),
+        ));
+        let (exhausted_until, reason) = failover_trigger(&err).expect("429 triggers failover");
+        assert_eq!(reason, AuthSwitchReason::RateLimited);
+        assert!(manager.fail_over(None, exhausted_until, reason, "claude-sonnet-4-5"));
+        assert_eq!(
+            manager
+                .failover_provider(None)
+                .map(|provider| provider.wire_api),
+            Some(WireApi::Gemini)
+        );
+        let history = read_limit_history(codex_home.path(), None).expect("limit history");
+        assert_eq!(
+            history
+                .iter()
+                .map(|hit| hit.auth.as_str())
+                .collect::<Vec<_>>(),
+            vec!["anthropic"]
+        );
+    }
+}
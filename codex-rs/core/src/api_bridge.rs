@@ -5,14 +5,22 @@ use codex_api::TransportError;
 use codex_api::error::ApiError;
 use codex_api::rate_limits::parse_rate_limit;
 use http::HeaderMap;
+use http::HeaderName;
+use http::header::AUTHORIZATION;
 use serde::Deserialize;
+use tracing::warn;
 
 use crate::auth::CodexAuth;
+use crate::auth::entra_id_token;
 use crate::error::CodexErr;
+use crate::error::RequestTooLargeError;
 use crate::error::RetryLimitReachedError;
 use crate::error::UnexpectedResponseError;
 use crate::error::UsageLimitReachedError;
+use crate::model_provider_info::AZURE_OPENAI_API_KEY_ENV_VAR;
+use crate::model_provider_info::GatewayConfig;
 use crate::model_provider_info::ModelProviderInfo;
+use crate::model_provider_info::non_empty_env;
 use crate::token_data::PlanType;
 
 pub(crate) fn map_api_error(err: ApiError) -> CodexErr {
@@ -43,21 +51,35 @@ pub(crate) fn map_api_error(err: ApiError) -> CodexErr {
                     } else {
                         CodexErr::InvalidRequest(body_text)
                     }
+                } else if status == http::StatusCode::PAYLOAD_TOO_LARGE {
+                    CodexErr::RequestTooLarge(RequestTooLargeError {
+                        size: None,
+                        limit: None,
+                    })
                 } else if status == http::StatusCode::INTERNAL_SERVER_ERROR {
                     CodexErr::InternalServerError
                 } else if status == http::StatusCode::TOO_MANY_REQUESTS {
                     if let Ok(err) = serde_json::from_str::<UsageErrorResponse>(&body_text) {
                         if err.error.error_type.as_deref() == Some("usage_limit_reached") {
-                            let rate_limits = headers.as_ref().and_then(parse_rate_limit);
-                            let resets_at = err
-                                .error
-                                .resets_at
-                                .and_then(|seconds| DateTime::<Utc>::from_timestamp(seconds, 0));
-                            return CodexErr::UsageLimitReached(UsageLimitReachedError {
+                            let mut usage_err = UsageLimitReachedError {
                                 plan_type: err.error.plan_type,
-                                resets_at,
-                                rate_limits,
-                            });
+                                resets_at: err.error.resets_at.and_then(timestamp_to_utc),
+                                rate_limits: headers.as_ref().and_then(parse_rate_limit),
+                            };
+                            // Prefer the reset of the window that was actually exhausted so a
+                            // weekly cap is not reported with the 5-hour window's reset time.
+                            if usage_err.resets_at.is_none() {
+                                usage_err.resets_at = usage_err
+                                    .binding_window()
+                                    .and_then(|window| window.resets_at)
+                                    .and_then(timestamp_to_utc)
+                                    .or_else(|| {
+                                        headers.as_ref().and_then(|map| {
+                                            reset_time_from_headers(map, Utc::now())
+                                        })
+                                    });
+                            }
+                            return CodexErr::UsageLimitReached(usage_err);
                         } else if err.error.error_type.as_deref() == Some("usage_not_included") {
                             return CodexErr::UsageNotIncluded;
                         }
@@ -100,14 +122,67 @@ fn extract_request_id(headers: Option<&HeaderMap>) -> Option<String> {
     })
 }
 
+fn timestamp_to_utc(seconds: i64) -> Option<DateTime<Utc>> {
+    DateTime::<Utc>::from_timestamp(seconds, 0)
+}
+
+/// `x-ratelimit-reset` values below this (September 2001) are seconds until
+/// the reset rather than a Unix timestamp.
+const RATELIMIT_RESET_DELTA_MAX: i64 = 1_000_000_000;
+
+/// Derives the usage-limit reset time from standard rate-limit headers when the
+/// error body does not carry one. `x-ratelimit-reset` is an absolute Unix
+/// timestamp, or a delay in seconds as many gateways send it; `Retry-After` is
+/// either a delay in seconds or an HTTP date.
+fn reset_time_from_headers(headers: &HeaderMap, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let header_str = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    };
+
+    if let Some(reset) = header_str("x-ratelimit-reset")
+        .and_then(|v| v.parse::<i64>().ok())
+        .and_then(|seconds| {
+            if seconds < RATELIMIT_RESET_DELTA_MAX {
+                now.checked_add_signed(chrono::Duration::seconds(seconds.max(0)))
+            } else {
+                DateTime::<Utc>::from_timestamp(seconds, 0)
+            }
+        })
+    {
+        return Some(reset);
+    }
+
+    let retry_after = header_str("retry-after")?;
+    if let Ok(seconds) = retry_after.parse::<i64>() {
+        return now.checked_add_signed(chrono::Duration::seconds(seconds.max(0)));
+    }
+    DateTime::parse_from_rfc2822(retry_after)
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
 pub(crate) async fn auth_provider_from_auth(
     auth: Option<CodexAuth>,
     provider: &ModelProviderInfo,
+) -> crate::error::Result<CoreAuthProvider> {
+    let mut auth_provider = credential_for_provider(auth, provider).await?;
+    auth_provider.gateway = provider.gateway.clone();
+    Ok(auth_provider)
+}
+
+async fn credential_for_provider(
+    auth: Option<CodexAuth>,
+    provider: &ModelProviderInfo,
 ) -> crate::error::Result<CoreAuthProvider> {
     if let Some(api_key) = provider.api_key()? {
         return Ok(CoreAuthProvider {
             token: Some(api_key),
             account_id: None,
+            gateway: None,
         });
     }
 
@@ -115,6 +190,21 @@ pub(crate) async fn auth_provider_from_auth(
         return Ok(CoreAuthProvider {
             token: Some(token),
             account_id: None,
+            gateway: None,
+        });
+    }
+
+    // Azure never receives the OpenAI credentials from auth.json: it uses its
+    // own key when one is exported and an Entra ID token otherwise.
+    if let Some(azure) = &provider.azure {
+        let token = match non_empty_env(AZURE_OPENAI_API_KEY_ENV_VAR) {
+            Some(api_key) => api_key,
+            None => entra_id_token(&azure.entra_id.clone().unwrap_or_default()).await?,
+        };
+        return Ok(CoreAuthProvider {
+            token: Some(token),
+            account_id: None,
+            gateway: None,
         });
     }
 
@@ -123,11 +213,13 @@ pub(crate) async fn auth_provider_from_auth(
         Ok(CoreAuthProvider {
             token: Some(token),
             account_id: auth.get_account_id(),
+            gateway: None,
         })
     } else {
         Ok(CoreAuthProvider {
             token: None,
             account_id: None,
+            gateway: None,
         })
     }
 }
@@ -149,6 +241,7 @@ struct UsageErrorBody {
 pub(crate) struct CoreAuthProvider {
     token: Option<String>,
     account_id: Option<String>,
+    gateway: Option<GatewayConfig>,
 }
 
 impl ApiAuthProvider for CoreAuthProvider {
@@ -159,4 +252,160 @@ impl ApiAuthProvider for CoreAuthProvider {
     fn account_id(&self) -> Option<String> {
         self.account_id.clone()
     }
+
+    fn auth_header(&self) -> Option<(HeaderName, String)> {
+        let token = self.token.as_ref()?;
+        let gateway = self.gateway.as_ref();
+        let name = match gateway.and_then(|gateway| gateway.auth_header.as_deref()) {
+            Some(name) => match HeaderName::try_from(name) {
+                Ok(name) => name,
+                Err(err) => {
+                    warn!("ignoring invalid gateway auth_header `{name}`: {err}");
+                    AUTHORIZATION
+                }
+            },
+            None => AUTHORIZATION,
+        };
+        let value = match gateway.and_then(|gateway| gateway.auth_value.as_deref()) {
+            Some(template) => template.replace("{token}", token),
+            None => format!("Bearer {token}"),
+        };
+        Some((name, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+    use pretty_assertions::assert_eq;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, HeaderValue::from_static(value));
+        }
+        map
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::<Utc>::from_timestamp(1_700_000_000, 0).expect("valid timestamp")
+    }
+
+    #[test]
+    fn reset_time_prefers_ratelimit_reset_header() {
+        let map = headers(&[("x-ratelimit-reset", "1700003600"), ("retry-after", "60")]);
+        assert_eq!(
+            reset_time_from_headers(&map, now()),
+            DateTime::<Utc>::from_timestamp(1_700_003_600, 0)
+        );
+    }
+
+    #[test]
+    fn reset_time_from_ratelimit_reset_delta() {
+        let map = headers(&[("x-ratelimit-reset", "60")]);
+        assert_eq!(
+            reset_time_from_headers(&map, now()),
+            Some(now() + chrono::Duration::seconds(60))
+        );
+    }
+
+    #[test]
+    fn reset_time_from_retry_after_seconds() {
+        let map = headers(&[("retry-after", "120")]);
+        assert_eq!(
+            reset_time_from_headers(&map, now()),
+            Some(now() + chrono::Duration::seconds(120))
+        );
+    }
+
+    #[test]
+    fn reset_time_from_retry_after_http_date() {
+        let map = headers(&[("retry-after", "Tue, 14 Nov 2023 23:13:20 GMT")]);
+        assert_eq!(
+            reset_time_from_headers(&map, now()),
+            DateTime::<Utc>::from_timestamp(1_700_003_600, 0)
+        );
+    }
+
+    #[test]
+    fn reset_time_missing_headers() {
+        assert_eq!(reset_time_from_headers(&HeaderMap::new(), now()), None);
+    }
+
+    #[test]
+    fn usage_limit_uses_reset_of_exhausted_weekly_window() {
+        let map = headers(&[
+            ("x-codex-primary-used-percent", "40"),
+            ("x-codex-primary-window-minutes", "300"),
+            ("x-codex-primary-reset-at", "1700003600"),
+            ("x-codex-secondary-used-percent", "100"),
+            ("x-codex-secondary-window-minutes", "10080"),
+            ("x-codex-secondary-reset-at", "1700400000"),
+            ("retry-after", "60"),
+        ]);
+        let err = map_api_error(ApiError::Transport(TransportError::Http {
+            status: http::StatusCode::TOO_MANY_REQUESTS,
+            headers: Some(map),
+            body: Some(r#"{"error":{"type":"usage_limit_reached"}}"#.to_string()),
+        }));
+
+        let err = match err {
+            CodexErr::UsageLimitReached(err) => err,
+            other => panic!("expected usage limit error, got {other:?}"),
+        };
+        assert_eq!(
+            err.resets_at,
+            DateTime::<Utc>::from_timestamp(1_700_400_000, 0)
+        );
+        assert!(
+            err.to_string()
+                .starts_with("You've hit your weekly usage limit.")
+        );
+    }
+
+    #[test]
+    fn payload_too_large_maps_to_request_too_large() {
+        let err = map_api_error(ApiError::Transport(TransportError::Http {
+            status: http::StatusCode::PAYLOAD_TOO_LARGE,
+            headers: None,
+            body: Some("request entity too large".to_string()),
+        }));
+        assert!(matches!(
+            err,
+            CodexErr::RequestTooLarge(RequestTooLargeError {
+                size: None,
+                limit: None
+            })
+        ));
+    }
+
+    #[test]
+    fn gateway_templates_the_auth_header() {
+        let plain = CoreAuthProvider {
+            token: Some("sk-test".to_string()),
+            account_id: None,
+            gateway: None,
+        };
+        assert_eq!(
+            plain.auth_header(),
+            Some((AUTHORIZATION, "Bearer sk-test".to_string()))
+        );
+
+        let gateway = CoreAuthProvider {
+            gateway: Some(GatewayConfig {
+                auth_header: Some("X-Api-Key".to_string()),
+                auth_value: Some("team-a:{token}".to_string()),
+                model_rewrites: Vec::new(),
+            }),
+            ..plain
+        };
+        assert_eq!(
+            gateway.auth_header(),
+            Some((
+                HeaderName::from_static("x-api-key"),
+                "team-a:sk-test".to_string()
+            ))
+        );
+    }
 }
@@ -1,12 +1,20 @@
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::features::Feature;
+use crate::file_templates::apply_file_templates;
 use crate::function_tool::FunctionCallError;
+use crate::patch_review::ReviewedPatch;
+use crate::patch_review::review_patch;
+use crate::protocol::EventMsg;
 use crate::protocol::FileChange;
+use crate::protocol::PatchHunkDecision;
 use crate::protocol::ReviewDecision;
+use crate::protocol::WarningEvent;
 use crate::safety::SafetyCheck;
 use crate::safety::assess_patch_safety;
 use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::ApplyPatchFileChange;
+use codex_apply_patch::MaybeApplyPatchVerified;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
@@ -31,14 +39,35 @@ pub(crate) enum InternalApplyPatchInvocation {
 pub(crate) struct ApplyPatchExec {
     pub(crate) action: ApplyPatchAction,
     pub(crate) user_explicitly_approved_this_action: bool,
+    /// Set when the user reviewed the patch hunk by hunk; appended to the
+    /// tool output.
+    pub(crate) review_feedback: Option<String>,
+}
+
+impl ApplyPatchExec {
+    pub(crate) fn with_review_feedback(&self, content: String) -> String {
+        match self.review_feedback.as_deref() {
+            Some(feedback) if !feedback.is_empty() => format!("{content}\n\n{feedback}"),
+            _ => content,
+        }
+    }
 }
 
 pub(crate) async fn apply_patch(
     sess: &Session,
     turn_context: &TurnContext,
     call_id: &str,
-    action: ApplyPatchAction,
+    mut action: ApplyPatchAction,
 ) -> InternalApplyPatchInvocation {
+    if sess.enabled(Feature::DetectExternalEdits)
+        && let Some(refused) = refuse_external_edits(sess, turn_context, &action).await
+    {
+        return refused;
+    }
+    // Applied before approval so the user reviews the files as written.
+    if sess.enabled(Feature::FileTemplates) {
+        apply_file_templates(&mut action);
+    }
     match assess_patch_safety(
         &action,
         turn_context.approval_policy,
@@ -51,6 +80,7 @@ pub(crate) async fn apply_patch(
         } => InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
             action,
             user_explicitly_approved_this_action: user_explicitly_approved,
+            review_feedback: None,
         }),
         SafetyCheck::AskUser => {
             // Compute a readable summary of path changes to include in the
@@ -71,10 +101,14 @@ pub(crate) async fn apply_patch(
                 .await;
             match rx_approve.await.unwrap_or_default() {
                 ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {
-                    InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
-                        action,
-                        user_explicitly_approved_this_action: true,
-                    })
+                    match sess.take_hunk_review(&turn_context.sub_id).await {
+                        Some(decisions) => apply_hunk_review(&action, &decisions),
+                        None => InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
+                            action,
+                            user_explicitly_approved_this_action: true,
+                            review_feedback: None,
+                        }),
+                    }
                 }
                 ReviewDecision::Denied | ReviewDecision::Abort => {
                     InternalApplyPatchInvocation::Output(Err(FunctionCallError::RespondToModel(
@@ -89,6 +123,89 @@ pub(crate) async fn apply_patch(
     }
 }
 
+/// Refuses a patch to files changed outside the session since the model last
+/// saw them, telling both the user and the model which ones.
+async fn refuse_external_edits(
+    sess: &Session,
+    turn_context: &TurnContext,
+    action: &ApplyPatchAction,
+) -> Option<InternalApplyPatchInvocation> {
+    let targets = action.changes().iter().flat_map(|(path, change)| {
+        let moved_to = match change {
+            ApplyPatchFileChange::Update { move_path, .. } => move_path.as_deref(),
+            _ => None,
+        };
+        std::iter::once(path.as_path()).chain(moved_to)
+    });
+    let changed = sess.services.external_edits.take_changed(targets);
+    if changed.is_empty() {
+        return None;
+    }
+    let files = changed
+        .iter()
+        .map(|path| {
+            path.strip_prefix(&turn_context.cwd)
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    sess.send_event(
+        turn_context,
+        EventMsg::Warning(WarningEvent {
+            message: format!(
+                "{files} changed outside Codex; the agent will read the new version before patching it."
+            ),
+        }),
+    )
+    .await;
+    Some(InternalApplyPatchInvocation::Output(Err(
+        FunctionCallError::RespondToModel(format!(
+            "patch not applied: {files} changed outside this session (for example, in the user's editor) since you last saw it, so the patch may undo those changes. Read the current contents and write the patch again."
+        )),
+    )))
+}
+
+/// Narrows `action` to the hunks the user accepted or edited.
+fn apply_hunk_review(
+    action: &ApplyPatchAction,
+    decisions: &[PatchHunkDecision],
+) -> InternalApplyPatchInvocation {
+    let respond = |message: String| {
+        InternalApplyPatchInvocation::Output(Err(FunctionCallError::RespondToModel(message)))
+    };
+    match review_patch(action, decisions) {
+        Ok(ReviewedPatch {
+            patch: Some(patch),
+            feedback,
+        }) => match codex_apply_patch::maybe_parse_apply_patch_verified(
+            &["apply_patch".to_string(), patch],
+            &action.cwd,
+        ) {
+            MaybeApplyPatchVerified::Body(action) => {
+                InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
+                    action,
+                    user_explicitly_approved_this_action: true,
+                    review_feedback: Some(feedback),
+                })
+            }
+            MaybeApplyPatchVerified::CorrectnessError(err) => respond(format!(
+                "the hunks the user accepted no longer apply: {err}\n\n{feedback}"
+            )),
+            MaybeApplyPatchVerified::ShellParseError(_)
+            | MaybeApplyPatchVerified::NotApplyPatch => respond(format!(
+                "the hunks the user accepted do not form a patch\n\n{feedback}"
+            )),
+        },
+        Ok(ReviewedPatch {
+            patch: None,
+            feedback,
+        }) => respond(format!("patch rejected by user. {feedback}")),
+        Err(err) => respond(format!("patch review failed: {err}")),
+    }
+}
+
 pub(crate) fn convert_apply_patch_to_protocol(
     action: &ApplyPatchAction,
 ) -> HashMap<PathBuf, FileChange> {
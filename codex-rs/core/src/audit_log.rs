@@ -0,0 +1,199 @@
+//! Append-only record of authentication events, of the commands Codex runs,
+//! and of the secrets it redacts, stored at `~/.codex/audit.log` with one
+//! JSON object per line:
+//!
+//! ````text
+//! {"ts":<unix_seconds>,"event":"auth_switch","auth":"API key ending in 1234","reason":"usage limit reached","detail":"from me@example.com"}
+//! {"ts":<unix_seconds>,"event":"command","auth":"API key ending in 1234","detail":"cargo test","cwd":"/src/app","thread_id":"…"}
+//! {"ts":<unix_seconds>,"event":"redaction","auth":"API key ending in 1234","reason":"shell_command","detail":"aws_access_key_id as [REDACTED:aws_access_key_id:1a5d44a2]","thread_id":"…"}
+//! ````
+//!
+//! `auth` names the credentials that were active, so every command can be
+//! traced back to the account or key it ran under. `codex audit` reads this
+//! file. Lines are written the same way as the limit history, so concurrent
+//! sessions do not interleave records.
+
+use std::fs::OpenOptions;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Result;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::warn;
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+/// Filename that stores the audit log inside `~/.codex`.
+const AUDIT_LOG_FILENAME: &str = "audit.log";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEvent {
+    Login,
+    Logout,
+    /// A ChatGPT access token was refreshed, or the refresh failed.
+    TokenRefresh,
+    /// Codex moved to different credentials.
+    AuthSwitch,
+    /// A usage limit was reached.
+    LimitHit,
+    /// The agent ran a command.
+    Command,
+    /// A secret was replaced before the model saw it; see [`crate::redact`].
+    Redaction,
+}
+
+impl AuditEvent {
+    pub fn name(self) -> &'static str {
+        match self {
+            AuditEvent::Login => "login",
+            AuditEvent::Logout => "logout",
+            AuditEvent::TokenRefresh => "token_refresh",
+            AuditEvent::AuthSwitch => "auth_switch",
+            AuditEvent::LimitHit => "limit_hit",
+            AuditEvent::Command => "command",
+            AuditEvent::Redaction => "redaction",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    /// When the event happened (Unix seconds).
+    pub ts: i64,
+    pub event: AuditEvent,
+    /// Credentials in use, e.g. the account email or "API key ending in
+    /// 1234"; the provider name for providers that do not use Codex's
+    /// credentials.
+    #[serde(default)]
+    pub auth: String,
+    /// Why it happened, e.g. the switch reason or the refresh error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// What happened, e.g. the command line or the previous credentials.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    /// Conversation the event belongs to, when it happened in a session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
+}
+
+impl AuditEntry {
+    /// An entry stamped with the current time.
+    pub fn now(event: AuditEvent, auth: impl Into<String>) -> Self {
+        Self {
+            ts: chrono::Utc::now().timestamp(),
+            event,
+            auth: auth.into(),
+            reason: None,
+            detail: None,
+            cwd: None,
+            thread_id: None,
+        }
+    }
+
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+pub fn audit_log_path(codex_home: &Path) -> PathBuf {
+    codex_home.join(AUDIT_LOG_FILENAME)
+}
+
+pub(crate) fn append_audit_entry(codex_home: &Path, entry: &AuditEntry) -> Result<()> {
+    std::fs::create_dir_all(codex_home)?;
+    let mut line = serde_json::to_string(entry).map_err(std::io::Error::other)?;
+    line.push('\n');
+
+    let mut options = OpenOptions::new();
+    options.append(true).create(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(audit_log_path(codex_home))?;
+    file.write_all(line.as_bytes())
+}
+
+/// Appends `entry` to the audit log in the state directory of `codex_home`,
+/// logging instead of failing when it cannot be written.
+pub fn record(codex_home: &Path, entry: &AuditEntry) {
+    let state_dir = crate::state_dir::state_dir(codex_home);
+    if let Err(err) = append_audit_entry(&state_dir, entry) {
+        warn!("failed to append to the audit log: {err}");
+    }
+}
+
+/// Reads every recorded event, oldest first. A missing file is an empty
+/// log; lines that fail to parse are skipped.
+pub fn read_audit_log(codex_home: &Path) -> Result<Vec<AuditEntry>> {
+    let file = match std::fs::File::open(audit_log_path(codex_home)) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        if let Ok(entry) = serde_json::from_str::<AuditEntry>(&line?) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn appended_entries_are_read_back_and_bad_lines_skipped() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        assert_eq!(read_audit_log(tmp.path()).expect("read"), Vec::new());
+
+        let switch = AuditEntry {
+            ts: 1_700_000_000,
+            ..AuditEntry::now(AuditEvent::AuthSwitch, "API key ending in 1234")
+        }
+        .reason("usage limit reached")
+        .detail("from me@example.com");
+        let command = AuditEntry {
+            ts: 1_700_000_060,
+            cwd: Some(PathBuf::from("/src/app")),
+            thread_id: Some("thread-1".to_string()),
+            ..AuditEntry::now(AuditEvent::Command, "API key ending in 1234")
+        }
+        .detail("cargo test");
+        append_audit_entry(tmp.path(), &switch).expect("append");
+        append_audit_entry(tmp.path(), &command).expect("append");
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(audit_log_path(tmp.path()))
+            .and_then(|mut file| file.write_all(b"not json\n"))
+            .expect("write");
+
+        let contents = std::fs::read_to_string(audit_log_path(tmp.path())).expect("read file");
+        assert_eq!(
+            contents.lines().next(),
+            Some(
+                r#"{"ts":1700000000,"event":"auth_switch","auth":"API key ending in 1234","reason":"usage limit reached","detail":"from me@example.com"}"#
+            )
+        );
+        assert_eq!(
+            read_audit_log(tmp.path()).expect("read"),
+            vec![switch, command]
+        );
+    }
+}
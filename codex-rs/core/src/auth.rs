@@ -1,11 +1,22 @@
+mod accounts;
+mod api_key_pool;
+mod encryption;
+mod entra_id;
+mod failover;
+mod projects;
+mod refresh;
+mod retry_delay;
 mod storage;
+mod validation;
 
+use chrono::DateTime;
 use chrono::Utc;
 use reqwest::StatusCode;
 use serde::Deserialize;
 use serde::Serialize;
 #[cfg(test)]
 use serial_test::serial;
+use std::collections::HashMap;
 use std::env;
 use std::fmt::Debug;
 use std::io::ErrorKind;
@@ -13,22 +24,62 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 use codex_app_server_protocol::AuthMode;
+use codex_protocol::ConversationId;
 use codex_protocol::config_types::ForcedLoginMethod;
-
+use codex_protocol::protocol::AuthSwitchReason;
+use codex_protocol::protocol::AuthSwitchedEvent;
+
+use crate::audit_log;
+use crate::audit_log::AuditEntry;
+use crate::audit_log::AuditEvent;
+use crate::auth::accounts::AccountRotation;
+pub use crate::auth::accounts::DEFAULT_ACCOUNT;
+pub use crate::auth::accounts::account_home;
+pub use crate::auth::accounts::list_accounts;
+pub use crate::auth::accounts::owning_codex_home;
+pub use crate::auth::accounts::validate_account_name;
+use crate::auth::api_key_pool::ApiKeyPool;
+pub use crate::auth::api_key_pool::OPENAI_API_KEYS_ENV_VAR;
+use crate::auth::api_key_pool::describe_api_key;
+pub use crate::auth::encryption::CODEX_AUTH_KEY_FILE_ENV_VAR;
+pub use crate::auth::encryption::CODEX_AUTH_PASSPHRASE_ENV_VAR;
+pub(crate) use crate::auth::entra_id::entra_id_token;
+pub use crate::auth::failover::CHATGPT_FAILOVER_ENTRY;
+use crate::auth::failover::FailoverChain;
+pub use crate::auth::failover::FailoverEntry;
+pub use crate::auth::failover::OPENAI_API_FAILOVER_ENTRY;
+pub use crate::auth::projects::list_projects;
+pub use crate::auth::projects::project_for;
+pub use crate::auth::projects::register_project;
+pub use crate::auth::projects::unregister_project;
+use crate::auth::retry_delay::RetryDelay;
 pub use crate::auth::storage::AuthCredentialsStoreMode;
 pub use crate::auth::storage::AuthDotJson;
 use crate::auth::storage::AuthStorageBackend;
 use crate::auth::storage::create_auth_storage;
+use crate::auth::storage::migrate_auth_storage;
+pub use crate::auth::validation::ApiKeyValidationError;
+pub use crate::auth::validation::validate_api_key;
 use crate::config::Config;
+use crate::config::types::Limits;
+use crate::config::types::Policy;
 use crate::default_client::CodexHttpClient;
 use crate::error::RefreshTokenFailedError;
 use crate::error::RefreshTokenFailedReason;
+use crate::limit_history::LimitHitEntry;
+use crate::limit_history::append_limit_hit;
+use crate::limit_history::estimated_reset;
+use crate::limit_history::read_limit_history;
+use crate::model_provider_info::ModelProviderInfo;
 use crate::token_data::KnownPlan as InternalKnownPlan;
 use crate::token_data::PlanType as InternalPlanType;
 use crate::token_data::TokenData;
+use crate::token_data::jwt_expiry;
 use crate::token_data::parse_id_token;
 use crate::util::try_parse_error_message;
 use codex_protocol::account::PlanType as AccountPlanType;
@@ -204,6 +255,33 @@ impl CodexAuth {
         self.get_current_token_data().and_then(|t| t.id_token.email)
     }
 
+    /// When the ChatGPT access token expires, from its `exp` claim.
+    pub fn access_token_expires_at(&self) -> Option<DateTime<Utc>> {
+        let tokens = self.get_current_token_data()?;
+        jwt_expiry(&tokens.access_token).and_then(|exp| DateTime::<Utc>::from_timestamp(exp, 0))
+    }
+
+    /// When the ChatGPT tokens were last refreshed.
+    pub fn last_refresh(&self) -> Option<DateTime<Utc>> {
+        self.get_current_auth_json()
+            .and_then(|auth| auth.last_refresh)
+    }
+
+    /// Short, non-secret description of who is signed in: the ChatGPT account
+    /// email, or the last characters of the API key.
+    pub fn identity_label(&self) -> String {
+        match self.mode {
+            AuthMode::ChatGPT => self
+                .get_account_email()
+                .unwrap_or_else(|| "ChatGPT account".to_string()),
+            AuthMode::ApiKey => self
+                .api_key
+                .as_deref()
+                .map(describe_api_key)
+                .unwrap_or_else(|| "API key".to_string()),
+        }
+    }
+
     /// Account-facing plan classification derived from the current token.
     /// Returns a high-level `AccountPlanType` (e.g., Free/Plus/Pro/Team/…)
     /// mapped from the ID token's internal plan value. Prefer this when you
@@ -272,7 +350,7 @@ impl CodexAuth {
             mode: AuthMode::ChatGPT,
             storage: create_auth_storage(PathBuf::new(), AuthCredentialsStoreMode::File),
             auth_dot_json,
-            client: crate::default_client::create_client(),
+            client: crate::default_client::create_provider_client(),
         }
     }
 
@@ -287,7 +365,7 @@ impl CodexAuth {
     }
 
     pub fn from_api_key(api_key: &str) -> Self {
-        Self::from_api_key_with_client(api_key, crate::default_client::create_client())
+        Self::from_api_key_with_client(api_key, crate::default_client::create_provider_client())
     }
 }
 
@@ -314,8 +392,26 @@ pub fn logout(
     codex_home: &Path,
     auth_credentials_store_mode: AuthCredentialsStoreMode,
 ) -> std::io::Result<bool> {
+    let identity = CodexAuth::from_auth_storage(codex_home, auth_credentials_store_mode)
+        .ok()
+        .flatten()
+        .map(|auth| auth.identity_label());
     let storage = create_auth_storage(codex_home.to_path_buf(), auth_credentials_store_mode);
-    storage.delete()
+    let removed = storage.delete()?;
+    if removed {
+        audit_log::record(
+            owning_codex_home(codex_home),
+            &AuditEntry::now(AuditEvent::Logout, identity.unwrap_or_default()),
+        );
+    }
+    Ok(removed)
+}
+
+/// Moves stored credentials into the `to` backend (see
+/// [`AuthCredentialsStoreMode`]) and removes them from the other one.
+/// Returns `Ok(false)` when there were no credentials to move.
+pub fn migrate_auth(codex_home: &Path, to: AuthCredentialsStoreMode) -> std::io::Result<bool> {
+    migrate_auth_storage(codex_home.to_path_buf(), to)
 }
 
 /// Writes an `auth.json` that contains only the API key.
@@ -329,7 +425,12 @@ pub fn login_with_api_key(
         tokens: None,
         last_refresh: None,
     };
-    save_auth(codex_home, &auth_dot_json, auth_credentials_store_mode)
+    save_auth(codex_home, &auth_dot_json, auth_credentials_store_mode)?;
+    audit_log::record(
+        owning_codex_home(codex_home),
+        &AuditEntry::now(AuditEvent::Login, describe_api_key(api_key)).detail("API key"),
+    );
+    Ok(())
 }
 
 /// Persist the provided auth payload using the specified backend.
@@ -447,7 +548,7 @@ fn load_auth(
     auth_credentials_store_mode: AuthCredentialsStoreMode,
 ) -> std::io::Result<Option<CodexAuth>> {
     if enable_codex_api_key_env && let Some(api_key) = read_codex_api_key_from_env() {
-        let client = crate::default_client::create_client();
+        let client = crate::default_client::create_provider_client();
         return Ok(Some(CodexAuth::from_api_key_with_client(
             api_key.as_str(),
             client,
@@ -456,7 +557,7 @@ fn load_auth(
 
     let storage = create_auth_storage(codex_home.to_path_buf(), auth_credentials_store_mode);
 
-    let client = crate::default_client::create_client();
+    let client = crate::default_client::create_provider_client();
     let auth_dot_json = match storage.load()? {
         Some(auth) => auth,
         None => return Ok(None),
@@ -486,6 +587,43 @@ fn load_auth(
     }))
 }
 
+/// Loads credentials of one kind for a failover entry, without the usual
+/// preference for an API key over ChatGPT tokens.
+fn load_auth_for_mode(
+    codex_home: &Path,
+    auth_credentials_store_mode: AuthCredentialsStoreMode,
+    mode: AuthMode,
+) -> Option<CodexAuth> {
+    let storage = create_auth_storage(codex_home.to_path_buf(), auth_credentials_store_mode);
+    let auth_dot_json = storage.load().ok().flatten();
+    let client = crate::default_client::create_provider_client();
+    match mode {
+        AuthMode::ApiKey => auth_dot_json
+            .and_then(|auth| auth.openai_api_key)
+            .or_else(read_openai_api_key_from_env)
+            .map(|api_key| CodexAuth::from_api_key_with_client(&api_key, client)),
+        AuthMode::ChatGPT => {
+            let AuthDotJson {
+                tokens,
+                last_refresh,
+                ..
+            } = auth_dot_json?;
+            tokens.as_ref()?;
+            Some(CodexAuth {
+                api_key: None,
+                mode: AuthMode::ChatGPT,
+                storage: storage.clone(),
+                auth_dot_json: Arc::new(Mutex::new(Some(AuthDotJson {
+                    openai_api_key: None,
+                    tokens,
+                    last_refresh,
+                }))),
+                client,
+            })
+        }
+    }
+}
+
 async fn update_tokens(
     storage: &Arc<dyn AuthStorageBackend>,
     id_token: Option<String>,
@@ -1070,6 +1208,135 @@ mod tests {
             Some(InternalPlanType::Unknown("mystery-tier".to_string()))
         );
     }
+
+    #[test]
+    fn usage_limit_rotates_to_the_next_chatgpt_account() {
+        let codex_home = tempdir().unwrap();
+        let work_home = account_home(codex_home.path(), "work");
+        std::fs::create_dir_all(&work_home).expect("create account dir");
+        for (home, account_id) in [
+            (codex_home.path(), "org_personal"),
+            (&work_home, "org_work"),
+        ] {
+            write_auth_file(
+                AuthFileParams {
+                    openai_api_key: None,
+                    chatgpt_plan_type: "plus".to_string(),
+                    chatgpt_account_id: Some(account_id.to_string()),
+                },
+                home,
+            )
+            .expect("failed to write auth file");
+        }
+
+        let manager = AuthManager::new(
+            codex_home.path().to_path_buf(),
+            false,
+            AuthCredentialsStoreMode::File,
+        );
+        let account_id = |manager: &AuthManager| {
+            manager
+                .auth()
+                .and_then(|auth| auth.get_current_token_data())
+                .and_then(|tokens| tokens.id_token.chatgpt_account_id)
+        };
+        assert_eq!(account_id(&manager), Some("org_personal".to_string()));
+
+        let conversation = ConversationId::new();
+        let other_conversation = ConversationId::new();
+        assert!(manager.take_auth_switches(other_conversation).is_empty());
+        assert!(manager.rotate_chatgpt_account(
+            Some(conversation),
+            None,
+            AuthSwitchReason::UsageLimitReached
+        ));
+        assert_eq!(account_id(&manager), Some("org_work".to_string()));
+        assert!(manager.take_auth_switches(other_conversation).is_empty());
+        assert_eq!(manager.take_auth_switches(conversation).len(), 1);
+
+        manager.reload();
+        assert_eq!(account_id(&manager), Some("org_work".to_string()));
+
+        // The first account stays exhausted, so there is nowhere left to go.
+        assert!(!manager.rotate_chatgpt_account(
+            Some(conversation),
+            None,
+            AuthSwitchReason::UsageLimitReached
+        ));
+        assert_eq!(account_id(&manager), Some("org_work".to_string()));
+    }
+
+    #[test]
+    fn limits_without_a_reset_time_use_the_plan_retry_delay() {
+        let codex_home = tempdir().unwrap();
+        write_auth_file(
+            AuthFileParams {
+                openai_api_key: None,
+                chatgpt_plan_type: "pro".to_string(),
+                chatgpt_account_id: None,
+            },
+            codex_home.path(),
+        )
+        .expect("failed to write auth file");
+        let manager = AuthManager::new(
+            codex_home.path().to_path_buf(),
+            false,
+            AuthCredentialsStoreMode::File,
+        );
+        let hours_until_reset = |manager: &AuthManager| {
+            let reset = manager.estimated_limit_reset();
+            ((reset - Utc::now()).num_minutes() as f64 / 60.0).round() as i64
+        };
+        assert_eq!(hours_until_reset(&manager), 5);
+
+        manager.configure_limits(Limits {
+            retry_delay_hours: Some(3.0),
+            plans: HashMap::from([(
+                "pro".to_string(),
+                crate::config::types::PlanLimits {
+                    retry_delay_hours: Some(168.0),
+                },
+            )]),
+            ..Default::default()
+        });
+        assert_eq!(hours_until_reset(&manager), 168);
+
+        manager.configure_limits(Limits {
+            retry_delay_hours: Some(3.0),
+            ..Default::default()
+        });
+        assert_eq!(hours_until_reset(&manager), 3);
+    }
+
+    #[test]
+    fn unattributed_switches_reach_every_conversation() {
+        let manager = AuthManager::from_auth_for_testing(CodexAuth::from_api_key("sk-test"));
+        let first = ConversationId::new();
+        let second = ConversationId::new();
+        assert!(manager.take_auth_switches(first).is_empty());
+        assert!(manager.take_auth_switches(second).is_empty());
+
+        let switch = AuthSwitchedEvent {
+            from: "a".to_string(),
+            to: "b".to_string(),
+            reason: AuthSwitchReason::RefreshFailed,
+        };
+        manager.record_switch(None, switch.clone());
+        manager.record_switch(Some(first), switch);
+
+        assert_eq!(manager.take_auth_switches(first).len(), 2);
+        assert_eq!(manager.take_auth_switches(second).len(), 1);
+        manager.forget_auth_switches(second);
+        manager.record_switch(
+            None,
+            AuthSwitchedEvent {
+                from: "b".to_string(),
+                to: "c".to_string(),
+                reason: AuthSwitchReason::RefreshFailed,
+            },
+        );
+        assert_eq!(manager.take_auth_switches(first).len(), 1);
+    }
 }
 
 /// Central manager providing a single source of truth for auth.json derived
@@ -1086,6 +1353,28 @@ pub struct AuthManager {
     inner: RwLock<CachedAuth>,
     enable_codex_api_key_env: bool,
     auth_credentials_store_mode: AuthCredentialsStoreMode,
+    api_key_pool: Mutex<ApiKeyPool>,
+    /// Which of the signed-in ChatGPT accounts is in use.
+    accounts: Mutex<AccountRotation>,
+    /// Switches not yet reported to the user, per conversation; see
+    /// `take_auth_switches`.
+    pending_switches: Mutex<HashMap<ConversationId, Vec<AuthSwitchedEvent>>>,
+    /// The `fallback` chain, once a session configures one.
+    failover: Mutex<Option<FailoverChain>>,
+    /// Set once `start_background_refresh` has spawned its task.
+    background_refresh_started: AtomicBool,
+    /// From the administrator's policy; see `apply_policy`.
+    api_key_auth_disallowed: AtomicBool,
+    auto_switching_disabled: AtomicBool,
+    /// Where limit hits are shared with other machines; see
+    /// `set_shared_state_dir`.
+    shared_state_dir: Mutex<Option<PathBuf>>,
+    /// Credentials directory of the project whose own API key is in use; see
+    /// `use_project_credentials`.
+    project_home: Mutex<Option<PathBuf>>,
+    /// How long limited credentials are skipped when the provider gives no
+    /// reset time; see `configure_limits`.
+    retry_delay: Mutex<RetryDelay>,
 }
 
 impl AuthManager {
@@ -1105,11 +1394,22 @@ impl AuthManager {
         )
         .ok()
         .flatten();
+        let retry_delay = RetryDelay::new(codex_home.clone());
         Self {
             codex_home,
             inner: RwLock::new(CachedAuth { auth }),
             enable_codex_api_key_env,
             auth_credentials_store_mode,
+            api_key_pool: Mutex::new(ApiKeyPool::from_env()),
+            accounts: Mutex::new(AccountRotation::default()),
+            pending_switches: Mutex::new(HashMap::new()),
+            failover: Mutex::new(None),
+            background_refresh_started: AtomicBool::new(false),
+            api_key_auth_disallowed: AtomicBool::new(false),
+            auto_switching_disabled: AtomicBool::new(false),
+            shared_state_dir: Mutex::new(None),
+            project_home: Mutex::new(None),
+            retry_delay: Mutex::new(retry_delay),
         }
     }
 
@@ -1121,6 +1421,16 @@ impl AuthManager {
             inner: RwLock::new(cached),
             enable_codex_api_key_env: false,
             auth_credentials_store_mode: AuthCredentialsStoreMode::File,
+            api_key_pool: Mutex::new(ApiKeyPool::default()),
+            accounts: Mutex::new(AccountRotation::default()),
+            pending_switches: Mutex::new(HashMap::new()),
+            failover: Mutex::new(None),
+            background_refresh_started: AtomicBool::new(false),
+            api_key_auth_disallowed: AtomicBool::new(false),
+            auto_switching_disabled: AtomicBool::new(false),
+            shared_state_dir: Mutex::new(None),
+            project_home: Mutex::new(None),
+            retry_delay: Mutex::new(RetryDelay::new(PathBuf::new())),
         })
     }
 
@@ -1132,13 +1442,41 @@ impl AuthManager {
     /// Force a reload of the auth information from auth.json. Returns
     /// whether the auth value changed.
     pub fn reload(&self) -> bool {
-        let new_auth = load_auth(
-            &self.codex_home,
-            self.enable_codex_api_key_env,
-            self.auth_credentials_store_mode,
-        )
-        .ok()
-        .flatten();
+        let new_auth = self.project_auth().or_else(|| {
+            load_auth(
+                &self.codex_home,
+                self.enable_codex_api_key_env,
+                self.auth_credentials_store_mode,
+            )
+            .ok()
+            .flatten()
+        });
+        // Keep the credentials the active failover entry asked for.
+        let new_auth = match self.failover_auth_mode() {
+            Some(mode) if new_auth.as_ref().is_some_and(|auth| auth.mode != mode) => {
+                load_auth_for_mode(
+                    &self.home_for_mode(mode),
+                    self.auth_credentials_store_mode,
+                    mode,
+                )
+                .or(new_auth)
+            }
+            _ => new_auth,
+        };
+        // Keep the ChatGPT account rotation switched to.
+        let chatgpt_home = self.chatgpt_home();
+        let new_auth = match new_auth {
+            Some(auth) if auth.mode == AuthMode::ChatGPT && chatgpt_home != self.codex_home => {
+                load_auth_for_mode(
+                    &chatgpt_home,
+                    self.auth_credentials_store_mode,
+                    AuthMode::ChatGPT,
+                )
+                .or(Some(auth))
+            }
+            new_auth => new_auth,
+        };
+        let new_auth = self.allowed_by_policy(new_auth);
         if let Ok(mut guard) = self.inner.write() {
             let changed = !AuthManager::auths_equal(&guard.auth, &new_auth);
             guard.auth = new_auth;
@@ -1148,6 +1486,176 @@ impl AuthManager {
         }
     }
 
+    /// Enforces the administrator's policy: API-key credentials, including
+    /// `CODEX_API_KEY`, are replaced by the stored ChatGPT sign-in (or none)
+    /// when API keys are not allowed, and neither `OPENAI_API_KEYS` nor the
+    /// signed-in ChatGPT accounts are rotated when automatic switching is
+    /// disabled.
+    pub fn apply_policy(&self, policy: &Policy) {
+        self.api_key_auth_disallowed
+            .store(policy.disallow_api_key_auth, Ordering::SeqCst);
+        self.auto_switching_disabled
+            .store(policy.disable_auto_switching, Ordering::SeqCst);
+        if policy.disallow_api_key_auth {
+            self.reload();
+        }
+    }
+
+    /// Switches to the API key registered with `codex login --with-api-key
+    /// --project` for the innermost project containing `cwd`, if any, in
+    /// place of the stored credentials (but not of `CODEX_API_KEY`). The key
+    /// is redacted from transcripts, command output, and logs. Returns
+    /// whether a project key is now in use.
+    pub fn use_project_credentials(&self, cwd: &Path) -> bool {
+        let home = project_for(&self.codex_home, cwd)
+            .map(|root| projects::project_credentials_home(&self.codex_home, &root));
+        let changed = match self.project_home.lock() {
+            Ok(mut project_home) if *project_home != home => {
+                *project_home = home;
+                true
+            }
+            _ => false,
+        };
+        // Outside any project nothing changes, so keep the current credentials
+        // rather than re-reading auth.json.
+        if changed {
+            self.reload();
+        }
+        self.project_auth().is_some()
+    }
+
+    /// The active project's API key, unless `CODEX_API_KEY` overrides it.
+    fn project_auth(&self) -> Option<CodexAuth> {
+        if self.enable_codex_api_key_env && read_codex_api_key_from_env().is_some() {
+            return None;
+        }
+        let home = self.project_home.lock().ok()?.clone()?;
+        let api_key = load_auth_dot_json(&home, self.auth_credentials_store_mode)
+            .ok()
+            .flatten()?
+            .openai_api_key?;
+        crate::redact::register_secret(&api_key);
+        Some(CodexAuth::from_api_key_with_client(
+            &api_key,
+            crate::default_client::create_provider_client(),
+        ))
+    }
+
+    /// Shares limit hits through `shared_state_dir`, so accounts and
+    /// `fallback` entries another machine found limited are skipped here
+    /// too. Call before `configure_failover`.
+    pub fn set_shared_state_dir(&self, dir: Option<PathBuf>) {
+        if let Ok(mut shared) = self.shared_state_dir.lock() {
+            *shared = dir;
+        }
+    }
+
+    fn shared_state_dir(&self) -> Option<PathBuf> {
+        self.shared_state_dir
+            .lock()
+            .ok()
+            .and_then(|dir| dir.clone())
+    }
+
+    /// Takes `limits.retry_delay_hours` and its per-plan overrides from the
+    /// session's config. Later edits to `config.toml` still apply.
+    pub fn configure_limits(&self, limits: Limits) {
+        self.retry_delay
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .configure(limits);
+    }
+
+    /// When the active credentials' usage limit is expected to reset if the
+    /// provider did not say: after `limits.retry_delay_hours`, or sooner at
+    /// the hour the limit history shows their limits usually reset.
+    pub fn estimated_limit_reset(&self) -> DateTime<Utc> {
+        let auth = self.auth();
+        let identity = auth
+            .as_ref()
+            .map(CodexAuth::identity_label)
+            .unwrap_or_default();
+        estimated_reset(
+            &identity,
+            Utc::now(),
+            self.retry_delay_for(auth.as_ref()),
+            &self.limit_history(),
+        )
+    }
+
+    /// When credentials that failed for `reason` without a reported reset
+    /// time are tried again: never when they were refused, otherwise as for
+    /// [`Self::estimated_limit_reset`], with `identity` as recorded in the
+    /// limit history and the retry delay of `auth`'s plan.
+    fn unreported_reset(
+        &self,
+        reason: AuthSwitchReason,
+        identity: &str,
+        auth: Option<&CodexAuth>,
+    ) -> DateTime<Utc> {
+        if matches!(
+            reason,
+            AuthSwitchReason::RefreshFailed | AuthSwitchReason::CredentialsRejected
+        ) {
+            return DateTime::<Utc>::MAX_UTC;
+        }
+        estimated_reset(
+            identity,
+            Utc::now(),
+            self.retry_delay_for(auth),
+            &self.limit_history(),
+        )
+    }
+
+    /// The retry delay for the plan of `auth`, when it is a ChatGPT sign-in.
+    fn retry_delay_for(&self, auth: Option<&CodexAuth>) -> chrono::Duration {
+        let plan = auth.and_then(CodexAuth::raw_plan_type);
+        self.retry_delay
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .for_plan(plan.as_deref())
+    }
+
+    fn limit_history(&self) -> Vec<LimitHitEntry> {
+        let state_dir = crate::state_dir::state_dir(&self.codex_home);
+        read_limit_history(&state_dir, self.shared_state_dir().as_deref()).unwrap_or_default()
+    }
+
+    fn allowed_by_policy(&self, auth: Option<CodexAuth>) -> Option<CodexAuth> {
+        if !self.api_key_auth_disallowed.load(Ordering::SeqCst)
+            || auth
+                .as_ref()
+                .is_none_or(|auth| auth.mode != AuthMode::ApiKey)
+        {
+            return auth;
+        }
+        tracing::warn!(
+            "ignoring API key credentials: the Codex policy only allows ChatGPT sign-in"
+        );
+        load_auth_for_mode(
+            &self.chatgpt_home(),
+            self.auth_credentials_store_mode,
+            AuthMode::ChatGPT,
+        )
+    }
+
+    /// Where the ChatGPT account currently in use stores its credentials.
+    fn chatgpt_home(&self) -> PathBuf {
+        let active = self
+            .accounts
+            .lock()
+            .map(|accounts| accounts.active().to_string())
+            .unwrap_or_else(|_| DEFAULT_ACCOUNT.to_string());
+        account_home(&self.codex_home, &active)
+    }
+
+    fn home_for_mode(&self, mode: AuthMode) -> PathBuf {
+        match mode {
+            AuthMode::ChatGPT => self.chatgpt_home(),
+            AuthMode::ApiKey => self.codex_home.clone(),
+        }
+    }
+
     fn auths_equal(a: &Option<CodexAuth>, b: &Option<CodexAuth>) -> bool {
         match (a, b) {
             (None, None) => true,
@@ -1182,15 +1690,376 @@ impl AuthManager {
             Ok(token) => {
                 // Reload to pick up persisted changes.
                 self.reload();
+                self.audit(AuditEntry::now(
+                    AuditEvent::TokenRefresh,
+                    auth.identity_label(),
+                ));
                 Ok(Some(token))
             }
             Err(e) => {
                 tracing::error!("Failed to refresh token: {}", e);
+                self.audit(
+                    AuditEntry::now(AuditEvent::TokenRefresh, auth.identity_label())
+                        .reason(e.to_string()),
+                );
                 Err(e)
             }
         }
     }
 
+    /// Appends `entry` to the audit log. Managers made with
+    /// `from_auth_for_testing` have no CODEX_HOME and record nothing.
+    fn audit(&self, entry: AuditEntry) {
+        if !self.codex_home.as_os_str().is_empty() {
+            audit_log::record(&self.codex_home, &entry);
+        }
+    }
+
+    /// Starts refreshing the ChatGPT access token shortly before it expires,
+    /// for as long as this manager is alive. Later calls do nothing.
+    pub fn start_background_refresh(self: &Arc<Self>) {
+        if self.background_refresh_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        tokio::spawn(refresh::run(Arc::downgrade(self)));
+    }
+
+    /// Switch to the next key from the `OPENAI_API_KEYS` pool after the active
+    /// API key hit a quota or rate limit. `exhausted_until` is when the active
+    /// key is expected to recover; `None` skips it for
+    /// `limits.retry_delay_hours`, or for good when `reason` is a rejection.
+    /// `conversation_id` is the conversation whose request hit the limit; see
+    /// `take_auth_switches`. Returns whether a different key is now active.
+    pub fn rotate_api_key(
+        &self,
+        conversation_id: Option<ConversationId>,
+        exhausted_until: Option<DateTime<Utc>>,
+        reason: AuthSwitchReason,
+    ) -> bool {
+        if self.auto_switching_disabled.load(Ordering::SeqCst) {
+            return false;
+        }
+        let Some(current) = self
+            .auth()
+            .filter(|auth| auth.mode == AuthMode::ApiKey)
+            .and_then(|auth| auth.api_key)
+        else {
+            return false;
+        };
+        let exhausted_until = exhausted_until
+            .unwrap_or_else(|| self.unreported_reset(reason, &describe_api_key(&current), None));
+        let next = match self.api_key_pool.lock() {
+            Ok(mut pool) => pool.rotate(&current, exhausted_until, Utc::now()),
+            Err(_) => None,
+        };
+        let Some(next) = next.filter(|next| *next != current) else {
+            return false;
+        };
+        let Ok(mut guard) = self.inner.write() else {
+            return false;
+        };
+        guard.auth = Some(CodexAuth::from_api_key(&next));
+        self.record_switch(
+            conversation_id,
+            AuthSwitchedEvent {
+                from: describe_api_key(&current),
+                to: describe_api_key(&next),
+                reason,
+            },
+        );
+        true
+    }
+
+    /// Switch to the next ChatGPT account signed in with `codex login
+    /// --account` after the active one hit its usage limit. Accounts the limit
+    /// history shows as still limited are skipped. `exhausted_until` is when
+    /// the active account is expected to recover; `None` skips it for its
+    /// plan's `limits.retry_delay_hours`. `conversation_id` is as for
+    /// [`Self::rotate_api_key`]. Returns whether a different account is now
+    /// active.
+    pub fn rotate_chatgpt_account(
+        &self,
+        conversation_id: Option<ConversationId>,
+        exhausted_until: Option<DateTime<Utc>>,
+        reason: AuthSwitchReason,
+    ) -> bool {
+        if self.auto_switching_disabled.load(Ordering::SeqCst) {
+            return false;
+        }
+        let Some(current) = self.auth().filter(|auth| auth.mode == AuthMode::ChatGPT) else {
+            return false;
+        };
+        let Ok(mut accounts) = self.accounts.lock() else {
+            return false;
+        };
+        let now = Utc::now();
+        let history = self.limit_history();
+        let retry_delay = self.retry_delay_for(Some(&current));
+        let active = accounts.active().to_string();
+        accounts.mark_exhausted(
+            &active,
+            exhausted_until.unwrap_or_else(|| {
+                estimated_reset(&current.identity_label(), now, retry_delay, &history)
+            }),
+        );
+
+        let still_limited = |identity: &str| {
+            history.iter().any(|entry| {
+                entry.auth == identity
+                    && entry
+                        .limited_until(retry_delay, &history)
+                        .is_some_and(|until| until > now)
+            })
+        };
+        let additional = list_accounts(&self.codex_home);
+        let next = accounts
+            .candidates(&additional, now)
+            .into_iter()
+            .find_map(|name| {
+                let auth = load_auth_for_mode(
+                    &account_home(&self.codex_home, &name),
+                    self.auth_credentials_store_mode,
+                    AuthMode::ChatGPT,
+                )?;
+                (!still_limited(&auth.identity_label())).then_some((name, auth))
+            });
+        let Some((name, next)) = next else {
+            return false;
+        };
+        let Ok(mut guard) = self.inner.write() else {
+            return false;
+        };
+        tracing::warn!("switching to ChatGPT account `{name}`");
+        accounts.activate(&name);
+        let to = next.identity_label();
+        guard.auth = Some(next);
+        self.record_switch(
+            conversation_id,
+            AuthSwitchedEvent {
+                from: current.identity_label(),
+                to,
+                reason,
+            },
+        );
+        true
+    }
+
+    /// Installs the `fallback` chain from config. Entries the limit history
+    /// shows as still limited are skipped. Sessions sharing this manager keep
+    /// the existing chain state when they configure the same chain.
+    pub fn configure_failover(&self, entries: Vec<FailoverEntry>) {
+        let Ok(mut failover) = self.failover.lock() else {
+            return;
+        };
+        if entries.is_empty() {
+            *failover = None;
+            return;
+        }
+        if failover
+            .as_ref()
+            .is_some_and(|chain| chain.names().eq(entries.iter().map(|e| e.name.as_str())))
+        {
+            return;
+        }
+        let retry_delay = self.retry_delay_for(self.auth().as_ref());
+        let mut chain = FailoverChain::new(entries, &self.limit_history(), Utc::now(), retry_delay);
+        self.activate_failover_entry(&mut chain);
+        *failover = Some(chain);
+    }
+
+    /// Provider the `fallback` chain currently routes requests to, or `None`
+    /// when no chain is configured. Climbs back to a higher entry first when
+    /// its limit has expired; the climb is reported to `conversation_id`.
+    pub fn failover_provider(
+        &self,
+        conversation_id: Option<ConversationId>,
+    ) -> Option<ModelProviderInfo> {
+        let mut failover = self.failover.lock().ok()?;
+        let chain = failover.as_mut()?;
+        let previous = chain.active().name.clone();
+        let from = self.describe_failover_entry(chain.active());
+        if chain.climb(Utc::now()).is_some()
+            && self.activate_failover_entry(chain)
+            && chain.active().name != previous
+        {
+            self.push_failover_switch(conversation_id, from, chain, AuthSwitchReason::LimitReset);
+        }
+        Some(chain.active().provider.clone())
+    }
+
+    /// Describes the active `fallback` entry, e.g. `chatgpt (me@example.com)`,
+    /// after climbing back to a higher entry whose limit has reset. `None`
+    /// when no chain is configured.
+    pub fn failover_credentials_label(
+        &self,
+        conversation_id: Option<ConversationId>,
+    ) -> Option<String> {
+        self.failover_provider(conversation_id)?;
+        self.active_failover_label()
+    }
+
+    /// Like [`Self::failover_credentials_label`], without climbing the chain.
+    pub fn active_failover_label(&self) -> Option<String> {
+        let failover = self.failover.lock().ok()?;
+        failover
+            .as_ref()
+            .map(|chain| self.describe_failover_entry(chain.active()))
+    }
+
+    /// Moves the `fallback` chain to its next available entry after the
+    /// active one failed; limits are recorded in the limit history under the
+    /// entry's name. `exhausted_until` is when the failed entry is expected
+    /// to recover; `None` skips it as for [`Self::rotate_api_key`].
+    /// `conversation_id` is as for [`Self::rotate_api_key`]. Returns whether
+    /// requests should be retried on a different entry.
+    pub fn fail_over(
+        &self,
+        conversation_id: Option<ConversationId>,
+        exhausted_until: Option<DateTime<Utc>>,
+        reason: AuthSwitchReason,
+        model: &str,
+    ) -> bool {
+        let Ok(mut failover) = self.failover.lock() else {
+            return false;
+        };
+        let Some(chain) = failover.as_mut() else {
+            return false;
+        };
+        let failed = chain.active().name.clone();
+        let from = self.describe_failover_entry(chain.active());
+        if !matches!(
+            reason,
+            AuthSwitchReason::ProviderError
+                | AuthSwitchReason::RefreshFailed
+                | AuthSwitchReason::CredentialsRejected
+        ) {
+            let entry = LimitHitEntry {
+                ts: Utc::now().timestamp(),
+                auth: failed.clone(),
+                model: model.to_string(),
+                window_minutes: None,
+                resets_at: exhausted_until.map(|at| at.timestamp()),
+            };
+            let state_dir = crate::state_dir::state_dir(&self.codex_home);
+            if let Err(err) =
+                append_limit_hit(&state_dir, self.shared_state_dir().as_deref(), &entry)
+            {
+                tracing::warn!("failed to record limit hit: {err}");
+            }
+        }
+        let exhausted_until = exhausted_until.unwrap_or_else(|| {
+            let auth = self
+                .auth()
+                .filter(|_| chain.active().provider.requires_openai_auth);
+            self.unreported_reset(reason, &failed, auth.as_ref())
+        });
+        if chain.advance(exhausted_until, Utc::now()).is_none()
+            || !self.activate_failover_entry(chain)
+        {
+            return false;
+        }
+        tracing::warn!(
+            "fallback entry `{failed}` failed; switching to `{}`",
+            chain.active().name
+        );
+        self.push_failover_switch(conversation_id, from, chain, reason);
+        true
+    }
+
+    /// Loads the credentials the active entry asks for, skipping entries
+    /// whose credentials are missing for the rest of the process. Returns
+    /// `false` when no remaining entry can be used.
+    fn activate_failover_entry(&self, chain: &mut FailoverChain) -> bool {
+        loop {
+            let Some(mode) = chain.active().auth_mode else {
+                return true;
+            };
+            if self.auth().is_some_and(|auth| auth.mode == mode) {
+                return true;
+            }
+            if let Some(auth) = load_auth_for_mode(
+                &self.home_for_mode(mode),
+                self.auth_credentials_store_mode,
+                mode,
+            ) {
+                if let Ok(mut guard) = self.inner.write() {
+                    guard.auth = Some(auth);
+                }
+                return true;
+            }
+            tracing::warn!(
+                "skipping fallback entry `{}`: no credentials",
+                chain.active().name
+            );
+            if chain
+                .advance(DateTime::<Utc>::MAX_UTC, Utc::now())
+                .is_none()
+            {
+                return false;
+            }
+        }
+    }
+
+    fn failover_auth_mode(&self) -> Option<AuthMode> {
+        self.failover
+            .lock()
+            .ok()?
+            .as_ref()
+            .and_then(|chain| chain.active().auth_mode)
+    }
+
+    fn describe_failover_entry(&self, entry: &FailoverEntry) -> String {
+        match (entry.auth_mode, self.auth()) {
+            (Some(_), Some(auth)) => format!("{} ({})", entry.name, auth.identity_label()),
+            _ => entry.name.clone(),
+        }
+    }
+
+    fn push_failover_switch(
+        &self,
+        conversation_id: Option<ConversationId>,
+        from: String,
+        chain: &FailoverChain,
+        reason: AuthSwitchReason,
+    ) {
+        let to = self.describe_failover_entry(chain.active());
+        self.record_switch(conversation_id, AuthSwitchedEvent { from, to, reason });
+    }
+
+    /// Queues `switch` for the conversation whose request caused it, or for
+    /// every conversation that has called `take_auth_switches` when no request
+    /// did (e.g. a failed background refresh).
+    fn record_switch(&self, conversation_id: Option<ConversationId>, switch: AuthSwitchedEvent) {
+        let Ok(mut pending) = self.pending_switches.lock() else {
+            return;
+        };
+        match conversation_id {
+            Some(conversation_id) => pending.entry(conversation_id).or_default().push(switch),
+            None => {
+                for queue in pending.values_mut() {
+                    queue.push(switch.clone());
+                }
+            }
+        }
+    }
+
+    /// Drains the credential switches queued for `conversation_id` since its
+    /// last call so the session can tell the user about them. Other sessions
+    /// sharing this manager keep their own switches.
+    pub fn take_auth_switches(&self, conversation_id: ConversationId) -> Vec<AuthSwitchedEvent> {
+        self.pending_switches
+            .lock()
+            .map(|mut pending| std::mem::take(pending.entry(conversation_id).or_default()))
+            .unwrap_or_default()
+    }
+
+    /// Stops queueing switches for a conversation that has ended.
+    pub fn forget_auth_switches(&self, conversation_id: ConversationId) {
+        if let Ok(mut pending) = self.pending_switches.lock() {
+            pending.remove(&conversation_id);
+        }
+    }
+
     /// Log out by deleting the on‑disk auth.json (if present). Returns Ok(true)
     /// if a file was removed, Ok(false) if no auth file existed. On success,
     /// reloads the in‑memory auth cache so callers immediately observe the
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use crate::auth::projects::PROJECTS_SUBDIR;
+
+/// Name of the ChatGPT sign-in stored directly in `CODEX_HOME`.
+pub const DEFAULT_ACCOUNT: &str = "default";
+
+/// Directory under `CODEX_HOME` holding one subdirectory per additional
+/// account signed in with `codex login --account <name>`.
+const ACCOUNTS_SUBDIR: &str = "accounts";
+
+/// Where the credentials of account `name` are stored. Each account keeps its
+/// own `auth.json` (or keyring entry) rooted at this directory, so refreshed
+/// tokens are written back to the account they belong to.
+pub fn account_home(codex_home: &Path, name: &str) -> PathBuf {
+    if name == DEFAULT_ACCOUNT {
+        codex_home.to_path_buf()
+    } else {
+        codex_home.join(ACCOUNTS_SUBDIR).join(name)
+    }
+}
+
+/// The `CODEX_HOME` a credentials directory from [`account_home`] or
+/// `project_credentials_home` belongs to, for state shared by all
+/// credentials such as the audit log.
+pub fn owning_codex_home(credentials_home: &Path) -> &Path {
+    match credentials_home.parent() {
+        Some(parent)
+            if parent
+                .file_name()
+                .is_some_and(|name| name == ACCOUNTS_SUBDIR || name == PROJECTS_SUBDIR) =>
+        {
+            parent.parent().unwrap_or(credentials_home)
+        }
+        _ => credentials_home,
+    }
+}
+
+/// Account names become directory names, so only letters, digits, `-`, and
+/// `_` are allowed.
+pub fn validate_account_name(name: &str) -> io::Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid account name `{name}`: use letters, digits, `-`, and `_`"),
+        ))
+    }
+}
+
+/// Additional accounts, sorted by name. A missing directory means none.
+pub fn list_accounts(codex_home: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(codex_home.join(ACCOUNTS_SUBDIR)) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name != DEFAULT_ACCOUNT && validate_account_name(name).is_ok())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Which ChatGPT account requests use. A limited account is skipped until
+/// its recovery time while the others are tried in order: the default
+/// account first, then the additional ones by name.
+#[derive(Debug)]
+pub(crate) struct AccountRotation {
+    active: String,
+    /// Account -> time it becomes usable again.
+    exhausted: HashMap<String, DateTime<Utc>>,
+}
+
+impl Default for AccountRotation {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_ACCOUNT.to_string(),
+            exhausted: HashMap::new(),
+        }
+    }
+}
+
+impl AccountRotation {
+    pub(crate) fn active(&self) -> &str {
+        &self.active
+    }
+
+    pub(crate) fn activate(&mut self, name: &str) {
+        self.active = name.to_string();
+    }
+
+    /// Marks `name` as unusable until `until`.
+    pub(crate) fn mark_exhausted(&mut self, name: &str, until: DateTime<Utc>) {
+        self.exhausted.insert(name.to_string(), until);
+    }
+
+    /// Accounts to try after the active one, in rotation order, leaving out
+    /// those still exhausted at `now`. `additional` comes from
+    /// [`list_accounts`].
+    pub(crate) fn candidates(&self, additional: &[String], now: DateTime<Utc>) -> Vec<String> {
+        let all: Vec<&str> = std::iter::once(DEFAULT_ACCOUNT)
+            .chain(additional.iter().map(String::as_str))
+            .collect();
+        let start = all
+            .iter()
+            .position(|name| *name == self.active)
+            .map_or(0, |idx| idx + 1);
+        (0..all.len())
+            .map(|offset| all[(start + offset) % all.len()])
+            .filter(|name| *name != self.active && self.is_available(name, now))
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn is_available(&self, name: &str, now: DateTime<Utc>) -> bool {
+        self.exhausted.get(name).is_none_or(|until| *until <= now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::<Utc>::from_timestamp(1_700_000_000, 0).expect("valid timestamp")
+    }
+
+    #[test]
+    fn accounts_are_listed_by_name_and_stored_apart() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        assert_eq!(list_accounts(tmp.path()), Vec::<String>::new());
+
+        for name in ["work", "personal", "not valid"] {
+            std::fs::create_dir_all(tmp.path().join(ACCOUNTS_SUBDIR).join(name)).expect("mkdir");
+        }
+        assert_eq!(list_accounts(tmp.path()), vec!["personal", "work"]);
+        assert_eq!(account_home(tmp.path(), DEFAULT_ACCOUNT), tmp.path());
+        assert_eq!(
+            account_home(tmp.path(), "work"),
+            tmp.path().join("accounts").join("work")
+        );
+        assert_eq!(
+            owning_codex_home(&account_home(tmp.path(), "work")),
+            tmp.path()
+        );
+        assert_eq!(owning_codex_home(tmp.path()), tmp.path());
+        assert!(validate_account_name("work-2").is_ok());
+        assert!(validate_account_name("../work").is_err());
+        assert!(validate_account_name("").is_err());
+    }
+
+    #[test]
+    fn rotation_skips_exhausted_accounts_until_they_recover() {
+        let additional = vec!["personal".to_string(), "work".to_string()];
+        let mut rotation = AccountRotation::default();
+        let recovers_at = now() + chrono::Duration::hours(1);
+        let later = now() + chrono::Duration::hours(5);
+
+        rotation.mark_exhausted(DEFAULT_ACCOUNT, recovers_at);
+        assert_eq!(
+            rotation.candidates(&additional, now()),
+            vec!["personal", "work"]
+        );
+
+        rotation.activate("personal");
+        rotation.mark_exhausted("personal", later);
+        assert_eq!(rotation.candidates(&additional, now()), vec!["work"]);
+
+        rotation.activate("work");
+        rotation.mark_exhausted("work", later);
+        assert_eq!(
+            rotation.candidates(&additional, now()),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            rotation.candidates(&additional, recovers_at),
+            vec![DEFAULT_ACCOUNT]
+        );
+    }
+}
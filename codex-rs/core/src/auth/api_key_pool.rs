@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+use chrono::DateTime;
+use chrono::Utc;
+
+pub const OPENAI_API_KEYS_ENV_VAR: &str = "OPENAI_API_KEYS";
+
+/// Spare API keys to fall back to when the active key hits a quota or rate
+/// limit. Exhausted keys are skipped until their recorded recovery time.
+#[derive(Default)]
+pub(crate) struct ApiKeyPool {
+    keys: Vec<String>,
+    /// Key -> time it becomes usable again.
+    exhausted: HashMap<String, DateTime<Utc>>,
+}
+
+impl std::fmt::Debug for ApiKeyPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the keys themselves.
+        f.debug_struct("ApiKeyPool")
+            .field("keys", &self.keys.len())
+            .field("exhausted", &self.exhausted.len())
+            .finish()
+    }
+}
+
+impl ApiKeyPool {
+    pub(crate) fn from_env() -> Self {
+        std::env::var(OPENAI_API_KEYS_ENV_VAR)
+            .map(|raw| Self::parse(&raw))
+            .unwrap_or_default()
+    }
+
+    /// Keys may be separated by commas or whitespace; duplicates are dropped.
+    pub(crate) fn parse(raw: &str) -> Self {
+        let mut keys: Vec<String> = Vec::new();
+        for key in raw.split(|c: char| c == ',' || c.is_whitespace()) {
+            if !key.is_empty() && !keys.iter().any(|existing| existing == key) {
+                keys.push(key.to_string());
+            }
+        }
+        Self {
+            keys,
+            exhausted: HashMap::new(),
+        }
+    }
+
+    /// Marks `current` as exhausted until `until` and returns the next key
+    /// (in pool order, after `current`) that is not exhausted at `now`.
+    pub(crate) fn rotate(
+        &mut self,
+        current: &str,
+        until: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Option<String> {
+        if self.keys.is_empty() {
+            return None;
+        }
+        self.exhausted.insert(current.to_string(), until);
+
+        let start = self
+            .keys
+            .iter()
+            .position(|key| key == current)
+            .map_or(0, |idx| idx + 1);
+        let len = self.keys.len();
+        (0..len)
+            .map(|offset| &self.keys[(start + offset) % len])
+            .find(|key| self.is_available(key, now))
+            .cloned()
+    }
+
+    fn is_available(&self, key: &str, now: DateTime<Utc>) -> bool {
+        self.exhausted.get(key).is_none_or(|until| *until <= now)
+    }
+}
+
+/// Identifies a key by its last four characters so it can be shown to users.
+pub(crate) fn describe_api_key(key: &str) -> String {
+    let suffix: String = key
+        .chars()
+        .rev()
+        .take(4)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    format!("API key ending in {suffix}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::<Utc>::from_timestamp(1_700_000_000, 0).expect("valid timestamp")
+    }
+
+    #[test]
+    fn parse_splits_on_commas_and_whitespace() {
+        let pool = ApiKeyPool::parse("sk-a, sk-b\nsk-c,,sk-a");
+        assert_eq!(pool.keys, vec!["sk-a", "sk-b", "sk-c"]);
+    }
+
+    #[test]
+    fn rotate_skips_exhausted_keys_until_they_recover() {
+        let mut pool = ApiKeyPool::parse("sk-a,sk-b,sk-c");
+        let recovers_at = now() + chrono::Duration::minutes(5);
+        let later = now() + chrono::Duration::hours(5);
+
+        assert_eq!(
+            pool.rotate("sk-a", recovers_at, now()),
+            Some("sk-b".to_string())
+        );
+        assert_eq!(pool.rotate("sk-b", later, now()), Some("sk-c".to_string()));
+        assert_eq!(pool.rotate("sk-c", later, now()), None);
+        assert_eq!(
+            pool.rotate("sk-c", later, recovers_at),
+            Some("sk-a".to_string())
+        );
+    }
+
+    #[test]
+    fn rotate_from_key_outside_pool_starts_at_first_key() {
+        let mut pool = ApiKeyPool::parse("sk-a,sk-b");
+        assert_eq!(
+            pool.rotate("sk-login", now(), now()),
+            Some("sk-a".to_string())
+        );
+    }
+
+    #[test]
+    fn describe_api_key_shows_only_the_suffix() {
+        assert_eq!(
+            describe_api_key("sk-proj-secret1234"),
+            "API key ending in 1234"
+        );
+        assert_eq!(describe_api_key("ab"), "API key ending in ab");
+    }
+
+    #[test]
+    fn empty_pool_never_rotates() {
+        let mut pool = ApiKeyPool::default();
+        assert_eq!(pool.rotate("sk-a", now(), now()), None);
+    }
+}
@@ -0,0 +1,340 @@
+//! Optional encryption of `auth.json` at rest, for machines without an OS
+//! keyring (headless Linux CI, containers).
+//!
+//! When `CODEX_AUTH_PASSPHRASE` or `CODEX_AUTH_KEY_FILE` is set, `auth.json`
+//! is written as an AES-256-GCM envelope whose key is derived from the
+//! passphrase (or the key file's contents) with PBKDF2-HMAC-SHA256. The key
+//! is only read when an encrypted file has to be opened or written, so
+//! commands that never touch credentials do not need it. Derived keys are
+//! kept for the life of the process, so only the first read or write pays
+//! for PBKDF2. A plaintext `auth.json` is still read, and is encrypted the
+//! next time it is saved.
+
+use std::io;
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ring::aead::AES_256_GCM;
+use ring::aead::Aad;
+use ring::aead::LessSafeKey;
+use ring::aead::NONCE_LEN;
+use ring::aead::Nonce;
+use ring::aead::UnboundKey;
+use ring::digest;
+use ring::pbkdf2;
+use ring::rand::SecureRandom;
+use ring::rand::SystemRandom;
+use serde::Deserialize;
+use serde::Serialize;
+
+pub const CODEX_AUTH_PASSPHRASE_ENV_VAR: &str = "CODEX_AUTH_PASSPHRASE";
+pub const CODEX_AUTH_KEY_FILE_ENV_VAR: &str = "CODEX_AUTH_KEY_FILE";
+
+const FORMAT_VERSION: u32 = 1;
+const KDF: &str = "pbkdf2-sha256";
+const PBKDF2_ITERATIONS: u32 = 600_000;
+/// Envelopes asking for more iterations are rejected rather than tying up
+/// the process deriving a key.
+const MAX_PBKDF2_ITERATIONS: u32 = 10 * PBKDF2_ITERATIONS;
+/// Derived keys kept per process.
+const MAX_CACHED_KEYS: usize = 8;
+const SALT_LEN: usize = 16;
+/// Binds the ciphertext to its purpose so it cannot be passed off as
+/// another encrypted blob.
+const AAD: &[u8] = b"codex auth.json";
+
+/// A key derived in this process. The secret is kept as a digest only.
+struct CachedKey {
+    secret_digest: digest::Digest,
+    salt: Vec<u8>,
+    iterations: u32,
+    key: [u8; 32],
+}
+
+/// Derived keys, most recently used last.
+static DERIVED_KEYS: LazyLock<Mutex<Vec<CachedKey>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+/// An encrypted `auth.json`.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct EncryptedAuthFile {
+    encrypted_auth: u32,
+    kdf: String,
+    iterations: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// The secret the file key is derived from.
+pub(super) struct AuthFileSecret(Vec<u8>);
+
+impl AuthFileSecret {
+    /// The secret from `CODEX_AUTH_PASSPHRASE` or, failing that, the file
+    /// named by `CODEX_AUTH_KEY_FILE`. `None` when neither is set.
+    pub(super) fn from_env() -> io::Result<Option<Self>> {
+        if let Some(passphrase) = non_empty_env(CODEX_AUTH_PASSPHRASE_ENV_VAR) {
+            return Ok(Some(Self(passphrase.into_bytes())));
+        }
+        let Some(path) = non_empty_env(CODEX_AUTH_KEY_FILE_ENV_VAR).map(PathBuf::from) else {
+            return Ok(None);
+        };
+        let contents = std::fs::read(&path).map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!(
+                    "failed to read {CODEX_AUTH_KEY_FILE_ENV_VAR} {}: {err}",
+                    path.display()
+                ),
+            )
+        })?;
+        let secret = contents.trim_ascii();
+        if secret.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{CODEX_AUTH_KEY_FILE_ENV_VAR} {} is empty", path.display()),
+            ));
+        }
+        Ok(Some(Self(secret.to_vec())))
+    }
+}
+
+fn non_empty_env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Contents to write to `auth.json`: `json` encrypted under `secret`, or
+/// `json` unchanged when there is no secret.
+pub(super) fn seal(json: String, secret: Option<&AuthFileSecret>) -> io::Result<String> {
+    match secret {
+        Some(secret) => seal_with_iterations(json.as_bytes(), secret, PBKDF2_ITERATIONS),
+        None => Ok(json),
+    }
+}
+
+/// The JSON stored in `contents`, decrypting it if it is an encrypted
+/// envelope. `secret` is only called for encrypted files.
+pub(super) fn open(
+    contents: String,
+    secret: impl FnOnce() -> io::Result<Option<AuthFileSecret>>,
+) -> io::Result<String> {
+    let Ok(envelope) = serde_json::from_str::<EncryptedAuthFile>(&contents) else {
+        return Ok(contents);
+    };
+    let Some(secret) = secret()? else {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "auth.json is encrypted; set {CODEX_AUTH_PASSPHRASE_ENV_VAR} or {CODEX_AUTH_KEY_FILE_ENV_VAR} to read it"
+            ),
+        ));
+    };
+    open_envelope(&envelope, &secret)
+}
+
+fn seal_with_iterations(
+    plaintext: &[u8],
+    secret: &AuthFileSecret,
+    iterations: u32,
+) -> io::Result<String> {
+    let rng = SystemRandom::new();
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut nonce).map_err(crypto_error)?;
+    // Saves in one process share a salt, and so a key; the random nonce
+    // still differs each time.
+    let salt = match cached_salt(secret, iterations) {
+        Some(salt) => salt,
+        None => {
+            let mut salt = vec![0u8; SALT_LEN];
+            rng.fill(&mut salt).map_err(crypto_error)?;
+            salt
+        }
+    };
+
+    let key = derive_key(secret, &salt, iterations)?;
+    let mut ciphertext = plaintext.to_vec();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(AAD),
+        &mut ciphertext,
+    )
+    .map_err(crypto_error)?;
+
+    let envelope = EncryptedAuthFile {
+        encrypted_auth: FORMAT_VERSION,
+        kdf: KDF.to_string(),
+        iterations,
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    };
+    serde_json::to_string_pretty(&envelope).map_err(io::Error::other)
+}
+
+fn open_envelope(envelope: &EncryptedAuthFile, secret: &AuthFileSecret) -> io::Result<String> {
+    if envelope.encrypted_auth != FORMAT_VERSION || envelope.kdf != KDF {
+        return Err(invalid_data(format!(
+            "unsupported encrypted auth.json (version {}, kdf {})",
+            envelope.encrypted_auth, envelope.kdf
+        )));
+    }
+    let salt = decode_field("salt", &envelope.salt)?;
+    let nonce = Nonce::try_assume_unique_for_key(&decode_field("nonce", &envelope.nonce)?)
+        .map_err(|_| invalid_data("encrypted auth.json has an invalid nonce".to_string()))?;
+    let mut ciphertext = decode_field("ciphertext", &envelope.ciphertext)?;
+
+    let key = derive_key(secret, &salt, envelope.iterations)?;
+    let plaintext = key
+        .open_in_place(nonce, Aad::from(AAD), &mut ciphertext)
+        .map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "failed to decrypt auth.json: wrong passphrase or key file, or the file was modified",
+            )
+        })?;
+    String::from_utf8(plaintext.to_vec()).map_err(|err| invalid_data(err.to_string()))
+}
+
+fn derive_key(secret: &AuthFileSecret, salt: &[u8], iterations: u32) -> io::Result<LessSafeKey> {
+    if iterations > MAX_PBKDF2_ITERATIONS {
+        return Err(invalid_data(format!(
+            "encrypted auth.json asks for {iterations} iterations, more than the maximum of {MAX_PBKDF2_ITERATIONS}"
+        )));
+    }
+    let rounds = NonZeroU32::new(iterations)
+        .ok_or_else(|| invalid_data("encrypted auth.json has zero iterations".to_string()))?;
+    let secret_digest = digest::digest(&digest::SHA256, &secret.0);
+
+    let mut cache = DERIVED_KEYS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let key = match cache.iter().position(|cached| {
+        cached.secret_digest.as_ref() == secret_digest.as_ref()
+            && cached.salt == salt
+            && cached.iterations == iterations
+    }) {
+        Some(index) => {
+            let cached = cache.remove(index);
+            let key = cached.key;
+            cache.push(cached);
+            key
+        }
+        None => {
+            let mut key = [0u8; 32];
+            pbkdf2::derive(
+                pbkdf2::PBKDF2_HMAC_SHA256,
+                rounds,
+                salt,
+                &secret.0,
+                &mut key,
+            );
+            if cache.len() == MAX_CACHED_KEYS {
+                cache.remove(0);
+            }
+            cache.push(CachedKey {
+                secret_digest,
+                salt: salt.to_vec(),
+                iterations,
+                key,
+            });
+            key
+        }
+    };
+    drop(cache);
+
+    let key = UnboundKey::new(&AES_256_GCM, &key).map_err(crypto_error)?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// The salt of the most recent key derived from `secret` with `iterations`.
+fn cached_salt(secret: &AuthFileSecret, iterations: u32) -> Option<Vec<u8>> {
+    let secret_digest = digest::digest(&digest::SHA256, &secret.0);
+    DERIVED_KEYS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .iter()
+        .rev()
+        .find(|cached| {
+            cached.secret_digest.as_ref() == secret_digest.as_ref()
+                && cached.iterations == iterations
+        })
+        .map(|cached| cached.salt.clone())
+}
+
+fn decode_field(name: &str, value: &str) -> io::Result<Vec<u8>> {
+    BASE64
+        .decode(value)
+        .map_err(|err| invalid_data(format!("encrypted auth.json has an invalid {name}: {err}")))
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn crypto_error(err: ring::error::Unspecified) -> io::Error {
+    io::Error::other(format!("auth.json encryption failed: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn secret(value: &str) -> AuthFileSecret {
+        AuthFileSecret(value.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn sealed_auth_opens_only_with_the_same_secret() {
+        let json = r#"{"OPENAI_API_KEY":"sk-test"}"#;
+        let sealed =
+            seal_with_iterations(json.as_bytes(), &secret("correct horse"), 1_000).expect("seal");
+        assert!(!sealed.contains("sk-test"));
+
+        let opened = open(sealed.clone(), || Ok(Some(secret("correct horse")))).expect("open");
+        assert_eq!(opened, json);
+
+        let err = open(sealed.clone(), || Ok(Some(secret("wrong")))).expect_err("wrong secret");
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        let err = open(sealed, || Ok(None)).expect_err("no secret");
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn plaintext_auth_is_read_without_a_secret() {
+        let json = r#"{"OPENAI_API_KEY":"sk-test"}"#.to_string();
+        let opened = open(json.clone(), || panic!("secret not needed")).expect("open");
+        assert_eq!(opened, json);
+        assert_eq!(seal(json.clone(), None).expect("seal"), json);
+    }
+
+    #[test]
+    fn keys_are_derived_once_per_secret() {
+        let seal = |value: &str| {
+            let sealed = seal_with_iterations(b"{}", &secret(value), 1_000).expect("seal");
+            serde_json::from_str::<EncryptedAuthFile>(&sealed).expect("envelope")
+        };
+        let first = seal("cached secret");
+        let second = seal("cached secret");
+        assert_eq!(first.salt, second.salt);
+        assert_ne!(first.nonce, second.nonce);
+        assert_ne!(seal("another cached secret").salt, first.salt);
+    }
+
+    #[test]
+    fn excessive_iterations_are_rejected() {
+        let mut envelope: EncryptedAuthFile = serde_json::from_str(
+            &seal_with_iterations(b"{}", &secret("slow"), 1_000).expect("seal"),
+        )
+        .expect("envelope");
+        envelope.iterations = u32::MAX;
+        let err = open(serde_json::to_string(&envelope).expect("json"), || {
+            Ok(Some(secret("slow")))
+        })
+        .expect_err("too many iterations");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
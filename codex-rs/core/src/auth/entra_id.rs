@@ -0,0 +1,278 @@
+//! Entra ID (Azure AD) access tokens for Azure OpenAI providers.
+//!
+//! Two credential sources are supported, mirroring `DefaultAzureCredential`:
+//! the client credentials flow when a tenant, client id, and secret are
+//! available, and otherwise the managed identity endpoint of the host (App
+//! Service / Functions via `IDENTITY_ENDPOINT`, everything else via IMDS).
+//! Tokens are cached per credential until shortly before they expire.
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::LazyLock;
+use std::time::Duration;
+use std::time::Instant;
+
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::default_client::build_provider_reqwest_client;
+use crate::model_provider_info::AZURE_COGNITIVE_SERVICES_SCOPE;
+use crate::model_provider_info::EntraIdConfig;
+use crate::model_provider_info::non_empty_env;
+
+const AZURE_TENANT_ID_ENV_VAR: &str = "AZURE_TENANT_ID";
+const AZURE_CLIENT_ID_ENV_VAR: &str = "AZURE_CLIENT_ID";
+const AZURE_CLIENT_SECRET_ENV_VAR: &str = "AZURE_CLIENT_SECRET";
+const AZURE_AUTHORITY_HOST_ENV_VAR: &str = "AZURE_AUTHORITY_HOST";
+const DEFAULT_AUTHORITY_HOST: &str = "https://login.microsoftonline.com";
+const IMDS_TOKEN_URL: &str = "http://169.254.169.254/metadata/identity/oauth2/token";
+
+/// Refresh this long before the token's reported expiry.
+const EXPIRY_SKEW: Duration = Duration::from_secs(5 * 60);
+
+/// Used when the identity endpoint omits `expires_in`.
+const DEFAULT_TOKEN_LIFETIME: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Credential {
+    ClientSecret {
+        tenant_id: String,
+        client_id: String,
+        client_secret: String,
+    },
+    ManagedIdentity {
+        client_id: Option<String>,
+    },
+}
+
+impl Credential {
+    fn resolve(config: &EntraIdConfig) -> Self {
+        let tenant_id = config
+            .tenant_id
+            .clone()
+            .or_else(|| non_empty_env(AZURE_TENANT_ID_ENV_VAR));
+        let client_id = config
+            .client_id
+            .clone()
+            .or_else(|| non_empty_env(AZURE_CLIENT_ID_ENV_VAR));
+        let secret_env_key = config
+            .client_secret_env_key
+            .as_deref()
+            .unwrap_or(AZURE_CLIENT_SECRET_ENV_VAR);
+        match (tenant_id, client_id, non_empty_env(secret_env_key)) {
+            (Some(tenant_id), Some(client_id), Some(client_secret)) => Credential::ClientSecret {
+                tenant_id,
+                client_id,
+                client_secret,
+            },
+            (_, client_id, _) => Credential::ManagedIdentity { client_id },
+        }
+    }
+
+    fn cache_key(&self, scope: &str) -> String {
+        match self {
+            Credential::ClientSecret {
+                tenant_id,
+                client_id,
+                ..
+            } => format!("secret:{tenant_id}:{client_id}:{scope}"),
+            Credential::ManagedIdentity { client_id } => {
+                format!("msi:{}:{scope}", client_id.as_deref().unwrap_or_default())
+            }
+        }
+    }
+}
+
+struct CachedToken {
+    token: String,
+    refresh_at: Instant,
+}
+
+static TOKEN_CACHE: LazyLock<Mutex<HashMap<String, CachedToken>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Returns a bearer token for `config`, reusing a cached one while it is valid.
+pub(crate) async fn entra_id_token(config: &EntraIdConfig) -> io::Result<String> {
+    let scope = config
+        .scope
+        .as_deref()
+        .unwrap_or(AZURE_COGNITIVE_SERVICES_SCOPE);
+    let credential = Credential::resolve(config);
+    let key = credential.cache_key(scope);
+
+    // Holding the lock across the request keeps concurrent turns from
+    // fetching the same token twice.
+    let mut cache = TOKEN_CACHE.lock().await;
+    if let Some(cached) = cache.get(&key)
+        && Instant::now() < cached.refresh_at
+    {
+        return Ok(cached.token.clone());
+    }
+
+    let body = request_token(&credential, scope).await?;
+    let (token, lifetime) = parse_token_response(&body)?;
+    cache.insert(
+        key,
+        CachedToken {
+            token: token.clone(),
+            refresh_at: Instant::now() + lifetime.saturating_sub(EXPIRY_SKEW),
+        },
+    );
+    Ok(token)
+}
+
+async fn request_token(credential: &Credential, scope: &str) -> io::Result<Value> {
+    let client = build_provider_reqwest_client();
+    let request = match credential {
+        Credential::ClientSecret {
+            tenant_id,
+            client_id,
+            client_secret,
+        } => {
+            let authority = non_empty_env(AZURE_AUTHORITY_HOST_ENV_VAR)
+                .unwrap_or_else(|| DEFAULT_AUTHORITY_HOST.to_string());
+            let url = format!(
+                "{}/{tenant_id}/oauth2/v2.0/token",
+                authority.trim_end_matches('/')
+            );
+            client.post(url).form(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", client_id.as_str()),
+                ("client_secret", client_secret.as_str()),
+                ("scope", scope),
+            ])
+        }
+        Credential::ManagedIdentity { client_id } => {
+            // Managed identity takes a resource, not a `/.default` scope.
+            let resource = scope.strip_suffix("/.default").unwrap_or(scope);
+            let mut query = vec![("resource", resource.to_string())];
+            if let Some(client_id) = client_id {
+                query.push(("client_id", client_id.clone()));
+            }
+            match (
+                non_empty_env("IDENTITY_ENDPOINT"),
+                non_empty_env("IDENTITY_HEADER"),
+            ) {
+                (Some(endpoint), Some(header)) => {
+                    query.push(("api-version", "2019-08-01".to_string()));
+                    client
+                        .get(endpoint)
+                        .header("X-IDENTITY-HEADER", header)
+                        .query(&query)
+                }
+                _ => {
+                    query.push(("api-version", "2018-02-01".to_string()));
+                    client
+                        .get(IMDS_TOKEN_URL)
+                        .header("Metadata", "true")
+                        .timeout(Duration::from_secs(5))
+                        .query(&query)
+                }
+            }
+        }
+    };
+
+    let response = request.send().await.map_err(|err| {
+        io::Error::other(format!(
+            "failed to request an Entra ID token ({}): {err}",
+            credential_label(credential)
+        ))
+    })?;
+    let status = response.status();
+    let text = response.text().await.map_err(io::Error::other)?;
+    if !status.is_success() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "Entra ID token request ({}) failed with {status}: {text}",
+                credential_label(credential)
+            ),
+        ));
+    }
+    serde_json::from_str(&text).map_err(io::Error::other)
+}
+
+fn credential_label(credential: &Credential) -> &'static str {
+    match credential {
+        Credential::ClientSecret { .. } => "client credentials",
+        Credential::ManagedIdentity { .. } => {
+            "managed identity; set AZURE_TENANT_ID, AZURE_CLIENT_ID, and AZURE_CLIENT_SECRET to use a service principal instead"
+        }
+    }
+}
+
+/// Extracts `access_token` and its lifetime. The client credentials flow
+/// reports `expires_in` as a number; managed identity reports it as a string.
+fn parse_token_response(body: &Value) -> io::Result<(String, Duration)> {
+    let token = body
+        .get("access_token")
+        .and_then(Value::as_str)
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| io::Error::other("Entra ID token response has no access_token"))?;
+    let lifetime = body
+        .get("expires_in")
+        .and_then(|value| {
+            value
+                .as_u64()
+                .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+        })
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_TOKEN_LIFETIME);
+    Ok((token.to_string(), lifetime))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn parses_numeric_and_string_expiry() {
+        assert_eq!(
+            parse_token_response(&json!({"access_token": "a", "expires_in": 3599})).unwrap(),
+            ("a".to_string(), Duration::from_secs(3599))
+        );
+        assert_eq!(
+            parse_token_response(&json!({"access_token": "b", "expires_in": "86399"})).unwrap(),
+            ("b".to_string(), Duration::from_secs(86_399))
+        );
+        assert_eq!(
+            parse_token_response(&json!({"access_token": "c"})).unwrap(),
+            ("c".to_string(), DEFAULT_TOKEN_LIFETIME)
+        );
+        assert!(parse_token_response(&json!({"error": "invalid_client"})).is_err());
+    }
+
+    #[test]
+    fn configured_client_secret_uses_client_credentials() {
+        let config = EntraIdConfig {
+            tenant_id: Some("tenant".to_string()),
+            client_id: Some("client".to_string()),
+            // `PATH` is always set, which stands in for a real secret here.
+            client_secret_env_key: Some("PATH".to_string()),
+            scope: None,
+        };
+        assert!(matches!(
+            Credential::resolve(&config),
+            Credential::ClientSecret { tenant_id, client_id, .. }
+                if tenant_id == "tenant" && client_id == "client"
+        ));
+    }
+
+    #[test]
+    fn missing_secret_falls_back_to_managed_identity() {
+        let config = EntraIdConfig {
+            tenant_id: Some("tenant".to_string()),
+            client_id: Some("client".to_string()),
+            client_secret_env_key: Some("CODEX_TEST_UNSET_AZURE_SECRET".to_string()),
+            scope: None,
+        };
+        assert_eq!(
+            Credential::resolve(&config),
+            Credential::ManagedIdentity {
+                client_id: Some("client".to_string())
+            }
+        );
+    }
+}
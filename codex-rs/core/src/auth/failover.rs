@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+
+use chrono::DateTime;
+use chrono::Utc;
+use codex_app_server_protocol::AuthMode;
+
+use crate::limit_history::LimitHitEntry;
+use crate::model_provider_info::ModelProviderInfo;
+
+/// `fallback` entry for the OpenAI provider signed in with ChatGPT.
+pub const CHATGPT_FAILOVER_ENTRY: &str = "chatgpt";
+/// `fallback` entry for the OpenAI provider with an API key.
+pub const OPENAI_API_FAILOVER_ENTRY: &str = "openai_api";
+
+const OPENAI_PROVIDER_ID: &str = "openai";
+
+/// One step of the `fallback` chain: a provider and, for the OpenAI
+/// provider, which kind of credentials to use with it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailoverEntry {
+    /// The entry as written in `fallback`; also the `auth` recorded in the
+    /// limit history when this entry is limited.
+    pub name: String,
+    pub provider_id: String,
+    pub provider: ModelProviderInfo,
+    /// `None` keeps whatever credentials are signed in.
+    pub auth_mode: Option<AuthMode>,
+}
+
+impl FailoverEntry {
+    /// Resolves a `fallback` entry: `chatgpt` and `openai_api` select the
+    /// OpenAI provider with that kind of credentials, anything else is a key
+    /// into `model_providers`.
+    pub fn resolve(name: &str, providers: &HashMap<String, ModelProviderInfo>) -> Option<Self> {
+        let (provider_id, auth_mode) = match name {
+            CHATGPT_FAILOVER_ENTRY => (OPENAI_PROVIDER_ID, Some(AuthMode::ChatGPT)),
+            OPENAI_API_FAILOVER_ENTRY => (OPENAI_PROVIDER_ID, Some(AuthMode::ApiKey)),
+            other => (other, None),
+        };
+        let provider = providers.get(provider_id)?.clone();
+        Some(Self {
+            name: name.to_string(),
+            provider_id: provider_id.to_string(),
+            provider,
+            auth_mode,
+        })
+    }
+}
+
+/// Tracks which `fallback` entry requests go to. A limited entry is skipped
+/// until its reset time, after which the chain climbs back to it.
+#[derive(Debug)]
+pub(crate) struct FailoverChain {
+    entries: Vec<FailoverEntry>,
+    active: usize,
+    /// Entry index -> time it becomes usable again.
+    limited: HashMap<usize, DateTime<Utc>>,
+}
+
+impl FailoverChain {
+    /// Starts at the first entry that `history` does not show as limited at
+    /// `now`, so a new session does not retry a provider that is known to be
+    /// out of quota. Hits without a reset time count for `retry_delay`.
+    pub(crate) fn new(
+        entries: Vec<FailoverEntry>,
+        history: &[LimitHitEntry],
+        now: DateTime<Utc>,
+        retry_delay: chrono::Duration,
+    ) -> Self {
+        let mut limited = HashMap::new();
+        for hit in history {
+            let Some(until) = hit
+                .limited_until(retry_delay, history)
+                .filter(|at| *at > now)
+            else {
+                continue;
+            };
+            if let Some(idx) = entries.iter().position(|entry| entry.name == hit.auth) {
+                let limited_until = limited.entry(idx).or_insert(until);
+                *limited_until = (*limited_until).max(until);
+            }
+        }
+        let mut chain = Self {
+            entries,
+            active: 0,
+            limited,
+        };
+        chain.active = chain.first_available(now).unwrap_or(0);
+        chain
+    }
+
+    pub(crate) fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.name.as_str())
+    }
+
+    pub(crate) fn active(&self) -> &FailoverEntry {
+        &self.entries[self.active]
+    }
+
+    /// Marks the active entry as limited until `until` and moves to the
+    /// first other entry, in chain order, that is available at `now`.
+    /// Returns the new entry, or `None` when every other entry is limited.
+    pub(crate) fn advance(
+        &mut self,
+        until: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Option<&FailoverEntry> {
+        self.limited.insert(self.active, until);
+        let next = self.first_available(now)?;
+        self.active = next;
+        Some(&self.entries[next])
+    }
+
+    /// Moves back up to the first entry above the active one whose limit has
+    /// expired at `now`.
+    pub(crate) fn climb(&mut self, now: DateTime<Utc>) -> Option<&FailoverEntry> {
+        let next = self.first_available(now).filter(|idx| *idx < self.active)?;
+        self.active = next;
+        Some(&self.entries[next])
+    }
+
+    fn first_available(&self, now: DateTime<Utc>) -> Option<usize> {
+        (0..self.entries.len()).find(|idx| self.limited.get(idx).is_none_or(|until| *until <= now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_provider_info::built_in_model_providers;
+    use pretty_assertions::assert_eq;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::<Utc>::from_timestamp(1_700_000_000, 0).expect("valid timestamp")
+    }
+
+    fn chain(names: &[&str], history: &[LimitHitEntry]) -> FailoverChain {
+        let providers = built_in_model_providers();
+        let entries = names
+            .iter()
+            .map(|name| FailoverEntry::resolve(name, &providers).expect("known entry"))
+            .collect();
+        FailoverChain::new(entries, history, now(), chrono::Duration::hours(5))
+    }
+
+    #[test]
+    fn resolve_maps_openai_entries_to_auth_modes() {
+        let providers = built_in_model_providers();
+        let chatgpt = FailoverEntry::resolve("chatgpt", &providers).expect("chatgpt");
+        assert_eq!(chatgpt.provider_id, "openai");
+        assert_eq!(chatgpt.auth_mode, Some(AuthMode::ChatGPT));
+        let api = FailoverEntry::resolve("openai_api", &providers).expect("openai_api");
+        assert_eq!(api.auth_mode, Some(AuthMode::ApiKey));
+        let ollama = FailoverEntry::resolve("ollama", &providers).expect("ollama");
+        assert_eq!(ollama.auth_mode, None);
+        assert_eq!(FailoverEntry::resolve("missing", &providers), None);
+    }
+
+    #[test]
+    fn advance_skips_limited_entries_and_climbs_back_when_they_reset() {
+        let mut chain = chain(&["chatgpt", "openai_api", "ollama"], &[]);
+        let resets_at = now() + chrono::Duration::minutes(30);
+        let later = now() + chrono::Duration::hours(5);
+
+        assert_eq!(
+            chain.advance(resets_at, now()).map(|e| e.name.as_str()),
+            Some("openai_api")
+        );
+        assert_eq!(
+            chain.advance(later, now()).map(|e| e.name.as_str()),
+            Some("ollama")
+        );
+        assert_eq!(chain.climb(now()).map(|e| e.name.as_str()), None);
+        assert_eq!(
+            chain.climb(resets_at).map(|e| e.name.as_str()),
+            Some("chatgpt")
+        );
+        assert_eq!(
+            chain.advance(later, resets_at).map(|e| e.name.as_str()),
+            Some("ollama")
+        );
+        assert_eq!(
+            chain.advance(later, resets_at).map(|e| e.name.as_str()),
+            None
+        );
+    }
+
+    #[test]
+    fn new_skips_entries_the_history_shows_as_limited() {
+        let history = vec![LimitHitEntry {
+            ts: now().timestamp() - 60,
+            auth: "chatgpt".to_string(),
+            model: "gpt-5.1-codex".to_string(),
+            window_minutes: None,
+            resets_at: Some(now().timestamp() + 600),
+        }];
+        let chain = chain(&["chatgpt", "openai_api"], &history);
+        assert_eq!(chain.active().name, "openai_api");
+    }
+
+    #[test]
+    fn hits_without_a_reset_time_count_for_the_retry_delay() {
+        let hit = |minutes_ago: i64| LimitHitEntry {
+            ts: now().timestamp() - minutes_ago * 60,
+            auth: "chatgpt".to_string(),
+            model: "gpt-5.1-codex".to_string(),
+            window_minutes: None,
+            resets_at: None,
+        };
+        assert_eq!(
+            chain(&["chatgpt", "openai_api"], &[hit(60)]).active().name,
+            "openai_api"
+        );
+        assert_eq!(
+            chain(&["chatgpt", "openai_api"], &[hit(6 * 60)])
+                .active()
+                .name,
+            "chatgpt"
+        );
+    }
+}
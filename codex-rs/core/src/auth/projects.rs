@@ -0,0 +1,107 @@
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use sha2::Digest;
+use sha2::Sha256;
+
+/// Directory under `CODEX_HOME` holding one subdirectory per project that
+/// has its own API key, registered with `codex login --with-api-key
+/// --project`.
+pub(crate) const PROJECTS_SUBDIR: &str = "project_credentials";
+
+/// File inside a project's directory naming the project root, since the
+/// directory itself is named by a hash of it.
+const PROJECT_ROOT_FILENAME: &str = "project_root";
+
+/// Where the API key of the project rooted at `root` is stored. Like an
+/// account's, the key is kept in its own `auth.json` (or keyring entry)
+/// rooted at this directory.
+pub fn project_credentials_home(codex_home: &Path, root: &Path) -> PathBuf {
+    let digest = Sha256::digest(root.to_string_lossy().as_bytes());
+    let name: String = digest.iter().take(8).map(|b| format!("{b:02x}")).collect();
+    codex_home.join(PROJECTS_SUBDIR).join(name)
+}
+
+/// Records `root` as a project with its own key and returns the directory
+/// its credentials go in.
+pub fn register_project(codex_home: &Path, root: &Path) -> io::Result<PathBuf> {
+    let home = project_credentials_home(codex_home, root);
+    std::fs::create_dir_all(&home)?;
+    std::fs::write(
+        home.join(PROJECT_ROOT_FILENAME),
+        root.to_string_lossy().as_bytes(),
+    )?;
+    Ok(home)
+}
+
+/// Forgets `root` and its key. Returns whether it was registered.
+pub fn unregister_project(codex_home: &Path, root: &Path) -> io::Result<bool> {
+    match std::fs::remove_dir_all(project_credentials_home(codex_home, root)) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Roots of the projects with their own key, sorted. A missing directory
+/// means none.
+pub fn list_projects(codex_home: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(codex_home.join(PROJECTS_SUBDIR)) else {
+        return Vec::new();
+    };
+    let mut roots: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| std::fs::read_to_string(entry.path().join(PROJECT_ROOT_FILENAME)).ok())
+        .map(PathBuf::from)
+        .collect();
+    roots.sort();
+    roots
+}
+
+/// The innermost registered project containing `cwd`.
+pub fn project_for(codex_home: &Path, cwd: &Path) -> Option<PathBuf> {
+    list_projects(codex_home)
+        .into_iter()
+        .filter(|root| cwd.starts_with(root))
+        .max_by_key(|root| root.components().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn the_innermost_registered_project_is_selected() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let codex_home = tmp.path();
+        assert_eq!(list_projects(codex_home), Vec::<PathBuf>::new());
+
+        let outer = PathBuf::from("/src/app");
+        let inner = PathBuf::from("/src/app/vendor/lib");
+        let home = register_project(codex_home, &outer).expect("register");
+        register_project(codex_home, &inner).expect("register");
+        assert_eq!(home, project_credentials_home(codex_home, &outer));
+        assert_ne!(home, project_credentials_home(codex_home, &inner));
+        assert_eq!(
+            list_projects(codex_home),
+            vec![outer.clone(), inner.clone()]
+        );
+
+        assert_eq!(
+            project_for(codex_home, Path::new("/src/app/crates/core")),
+            Some(outer.clone())
+        );
+        assert_eq!(
+            project_for(codex_home, Path::new("/src/app/vendor/lib/src")),
+            Some(inner)
+        );
+        // Component-wise: `/src/application` is not inside `/src/app`.
+        assert_eq!(project_for(codex_home, Path::new("/src/application")), None);
+
+        assert!(unregister_project(codex_home, &outer).expect("unregister"));
+        assert!(!unregister_project(codex_home, &outer).expect("unregister"));
+        assert_eq!(project_for(codex_home, Path::new("/src/app/crates")), None);
+    }
+}
@@ -0,0 +1,146 @@
+//! Background refresh of ChatGPT access tokens.
+//!
+//! Without it the access token is only refreshed when `auth.json` is old or
+//! a request is rejected, so a long session can hit an expired token in the
+//! middle of a turn. The task started by
+//! [`AuthManager::start_background_refresh`] sleeps until shortly before the
+//! access token expires and refreshes it then, retrying transient failures
+//! with jittered backoff. When the refresh token is rejected, the `fallback`
+//! chain moves off `chatgpt` (if it is the active entry) so the session
+//! reports the switch and keeps working.
+
+use std::sync::Weak;
+use std::time::Duration;
+
+use chrono::DateTime;
+use chrono::Utc;
+use codex_app_server_protocol::AuthMode;
+use codex_protocol::protocol::AuthSwitchReason;
+use tracing::warn;
+
+use super::AuthManager;
+use super::RefreshTokenError;
+use crate::util::backoff;
+
+/// How long before the access token expires it is refreshed.
+const REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+/// How long to wait before looking again when there is no ChatGPT token to
+/// refresh, or after refreshing it failed.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const MAX_REFRESH_ATTEMPTS: u64 = 5;
+
+/// How long to wait at `now` before refreshing an access token that expires
+/// at `expires_at`; zero once it is inside the refresh margin.
+pub(super) fn delay_until_refresh(expires_at: DateTime<Utc>, now: DateTime<Utc>) -> Duration {
+    (expires_at - now)
+        .to_std()
+        .unwrap_or_default()
+        .saturating_sub(REFRESH_MARGIN)
+}
+
+/// Runs until the manager is dropped. Only a `Weak` reference is held while
+/// sleeping so the task does not keep the manager alive.
+pub(super) async fn run(manager: Weak<AuthManager>) {
+    // Refresh token the server last rejected; it is not retried until the
+    // user signs in again.
+    let mut rejected: Option<String> = None;
+    let mut retry_after: Option<Duration> = None;
+    loop {
+        let delay = match manager.upgrade() {
+            Some(manager) => {
+                retry_after
+                    .take()
+                    .unwrap_or_else(|| match chatgpt_token_expiry(&manager) {
+                        Some((refresh_token, expires_at))
+                            if rejected.as_ref() != Some(&refresh_token) =>
+                        {
+                            delay_until_refresh(expires_at, Utc::now())
+                        }
+                        _ => IDLE_CHECK_INTERVAL,
+                    })
+            }
+            None => return,
+        };
+        tokio::time::sleep(delay).await;
+
+        let Some(manager) = manager.upgrade() else {
+            return;
+        };
+        // Another process may have refreshed the token or signed in again.
+        manager.reload();
+        let Some((refresh_token, expires_at)) = chatgpt_token_expiry(&manager) else {
+            continue;
+        };
+        if rejected.as_ref() == Some(&refresh_token)
+            || delay_until_refresh(expires_at, Utc::now()) > Duration::ZERO
+        {
+            continue;
+        }
+        match refresh_with_retries(&manager).await {
+            Ok(()) => {}
+            Err(RefreshTokenError::Transient(err)) => {
+                warn!(
+                    "background token refresh failed; retrying in {} minutes: {err}",
+                    IDLE_CHECK_INTERVAL.as_secs() / 60
+                );
+                retry_after = Some(IDLE_CHECK_INTERVAL);
+            }
+            Err(RefreshTokenError::Permanent(err)) => {
+                warn!("ChatGPT token refresh was rejected; run `codex login` again: {err}");
+                rejected = Some(refresh_token);
+                if manager.failover_auth_mode() == Some(AuthMode::ChatGPT) {
+                    manager.fail_over(None, None, AuthSwitchReason::RefreshFailed, "");
+                }
+            }
+        }
+    }
+}
+
+async fn refresh_with_retries(manager: &AuthManager) -> Result<(), RefreshTokenError> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match manager.refresh_token().await {
+            Ok(_) => return Ok(()),
+            Err(RefreshTokenError::Transient(_)) if attempt < MAX_REFRESH_ATTEMPTS => {
+                tokio::time::sleep(backoff(attempt)).await;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// The refresh token and access-token expiry of the signed-in ChatGPT
+/// account, or `None` when not signed in with ChatGPT or the access token
+/// carries no expiry.
+fn chatgpt_token_expiry(manager: &AuthManager) -> Option<(String, DateTime<Utc>)> {
+    let auth = manager
+        .auth()
+        .filter(|auth| auth.mode == AuthMode::ChatGPT)?;
+    let expires_at = auth.access_token_expires_at()?;
+    let tokens = auth.get_current_token_data()?;
+    Some((tokens.refresh_token, expires_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn refresh_is_scheduled_ahead_of_expiry() {
+        let now = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).expect("valid timestamp");
+        assert_eq!(
+            delay_until_refresh(now + chrono::Duration::minutes(60), now),
+            Duration::from_secs(55 * 60)
+        );
+        assert_eq!(
+            delay_until_refresh(now + chrono::Duration::minutes(2), now),
+            Duration::ZERO
+        );
+        assert_eq!(
+            delay_until_refresh(now - chrono::Duration::minutes(1), now),
+            Duration::ZERO
+        );
+    }
+}
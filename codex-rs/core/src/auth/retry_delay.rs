@@ -0,0 +1,126 @@
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use serde::Deserialize;
+
+use crate::config::CONFIG_TOML_FILE;
+use crate::config::types::Limits;
+use crate::config::validate_limits;
+
+/// `limits.retry_delay_hours` and its per-plan overrides: how long limited
+/// credentials are skipped when the provider does not say when the limit
+/// resets. `config.toml` is read again whenever it changes, so an edited
+/// delay applies to running sessions.
+#[derive(Debug)]
+pub(crate) struct RetryDelay {
+    limits: Limits,
+    /// `None` for managers made with `from_auth_for_testing`, which have no
+    /// CODEX_HOME.
+    config_path: Option<PathBuf>,
+    /// Modification time of `config_path` when `limits` was last taken.
+    modified: Option<SystemTime>,
+}
+
+#[derive(Deserialize)]
+struct ConfigLimits {
+    #[serde(default)]
+    limits: Limits,
+}
+
+impl RetryDelay {
+    /// Reads `config.toml` in `codex_home` on first use.
+    pub(crate) fn new(codex_home: PathBuf) -> Self {
+        Self {
+            limits: Limits::default(),
+            config_path: (!codex_home.as_os_str().is_empty())
+                .then(|| codex_home.join(CONFIG_TOML_FILE)),
+            modified: None,
+        }
+    }
+
+    /// Uses `limits` from the session's config, with its command-line
+    /// overrides, until `config.toml` next changes.
+    pub(crate) fn configure(&mut self, limits: Limits) {
+        self.limits = limits;
+        self.modified = self.config_path.as_deref().and_then(modified);
+    }
+
+    /// The delay for credentials on `plan`.
+    pub(crate) fn for_plan(&mut self, plan: Option<&str>) -> chrono::Duration {
+        self.reload();
+        let hours = self.limits.retry_delay_hours(plan);
+        chrono::Duration::seconds((hours * 3600.0).round() as i64)
+    }
+
+    /// Takes `[limits]` from `config.toml` if the file changed. An invalid
+    /// edit is logged and the previous delay kept.
+    fn reload(&mut self) {
+        let Some(config_path) = &self.config_path else {
+            return;
+        };
+        let modified = modified(config_path);
+        if modified == self.modified {
+            return;
+        }
+        self.modified = modified;
+        match read_limits(config_path) {
+            Ok(limits) => self.limits = limits,
+            Err(err) => tracing::warn!(
+                "ignoring changed [limits] in {}: {err}",
+                config_path.display()
+            ),
+        }
+    }
+}
+
+fn read_limits(config_path: &Path) -> io::Result<Limits> {
+    let contents = match std::fs::read_to_string(config_path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Limits::default()),
+        Err(err) => return Err(err),
+    };
+    let config: ConfigLimits =
+        toml::from_str(&contents).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    validate_limits(&config.limits)?;
+    Ok(config.limits)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn edits_to_config_toml_apply_without_a_restart() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let config_path = codex_home.path().join(CONFIG_TOML_FILE);
+        let mut delay = RetryDelay::new(codex_home.path().to_path_buf());
+        assert_eq!(delay.for_plan(None), chrono::Duration::hours(5));
+
+        std::fs::write(
+            &config_path,
+            "[limits]\nretry_delay_hours = 2\n\n[limits.plans.pro]\nretry_delay_hours = 168\n",
+        )
+        .expect("write config");
+        assert_eq!(delay.for_plan(None), chrono::Duration::hours(2));
+        assert_eq!(delay.for_plan(Some("pro")), chrono::Duration::hours(168));
+
+        // An invalid edit keeps the last valid delay.
+        std::fs::write(&config_path, "[limits]\nretry_delay_hours = -1\n").expect("write config");
+        let file = std::fs::File::options()
+            .write(true)
+            .open(&config_path)
+            .expect("open config");
+        file.set_modified(SystemTime::now() + std::time::Duration::from_secs(60))
+            .expect("set mtime");
+        assert_eq!(delay.for_plan(None), chrono::Duration::hours(2));
+    }
+}
@@ -20,12 +20,16 @@ use crate::token_data::TokenData;
 use codex_keyring_store::DefaultKeyringStore;
 use codex_keyring_store::KeyringStore;
 
+use crate::auth::encryption;
+use crate::auth::encryption::AuthFileSecret;
+
 /// Determine where Codex should store CLI auth credentials.
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum AuthCredentialsStoreMode {
     #[default]
-    /// Persist credentials in CODEX_HOME/auth.json.
+    /// Persist credentials in CODEX_HOME/auth.json, encrypted when
+    /// `CODEX_AUTH_PASSPHRASE` or `CODEX_AUTH_KEY_FILE` is set.
     File,
     /// Persist credentials in the keyring. Fail if unavailable.
     Keyring,
@@ -81,6 +85,7 @@ impl FileAuthStorage {
         let mut file = File::open(auth_file)?;
         let mut contents = String::new();
         file.read_to_string(&mut contents)?;
+        let contents = encryption::open(contents, AuthFileSecret::from_env)?;
         let auth_dot_json: AuthDotJson = serde_json::from_str(&contents)?;
 
         Ok(auth_dot_json)
@@ -105,6 +110,7 @@ impl AuthStorageBackend for FileAuthStorage {
             std::fs::create_dir_all(parent)?;
         }
         let json_data = serde_json::to_string_pretty(auth_dot_json)?;
+        let json_data = encryption::seal(json_data, AuthFileSecret::from_env()?.as_ref())?;
         let mut options = OpenOptions::new();
         options.truncate(true).write(true).create(true);
         #[cfg(unix)]
@@ -277,6 +283,60 @@ fn create_auth_storage_with_keyring_store(
     }
 }
 
+/// Moves stored credentials into the `to` backend: out of `auth.json` into
+/// the keyring for `Keyring` and `Auto`, or out of the keyring into
+/// `auth.json` for `File`. The copy is read back before the original is
+/// removed. Returns `Ok(false)` when there was nothing to move.
+pub(super) fn migrate_auth_storage(
+    codex_home: PathBuf,
+    to: AuthCredentialsStoreMode,
+) -> std::io::Result<bool> {
+    let keyring_store: Arc<dyn KeyringStore> = Arc::new(DefaultKeyringStore);
+    migrate_auth_storage_with_keyring_store(codex_home, to, keyring_store)
+}
+
+fn migrate_auth_storage_with_keyring_store(
+    codex_home: PathBuf,
+    to: AuthCredentialsStoreMode,
+    keyring_store: Arc<dyn KeyringStore>,
+) -> std::io::Result<bool> {
+    let file_storage = FileAuthStorage::new(codex_home.clone());
+    let keyring_storage = KeyringAuthStorage::new(codex_home.clone(), keyring_store.clone());
+    let key = compute_store_key(&codex_home)?;
+    match to {
+        AuthCredentialsStoreMode::Keyring | AuthCredentialsStoreMode::Auto => {
+            let Some(auth) = file_storage.load()? else {
+                return Ok(false);
+            };
+            let serialized = serde_json::to_string(&auth).map_err(std::io::Error::other)?;
+            keyring_storage.save_to_keyring(&key, &serialized)?;
+            verify_migrated(&auth, keyring_storage.load_from_keyring(&key)?)?;
+            file_storage.delete()?;
+        }
+        AuthCredentialsStoreMode::File => {
+            let Some(auth) = keyring_storage.load_from_keyring(&key)? else {
+                return Ok(false);
+            };
+            file_storage.save(&auth)?;
+            verify_migrated(&auth, file_storage.load()?)?;
+            keyring_store.delete(KEYRING_SERVICE, &key).map_err(|err| {
+                std::io::Error::other(format!("failed to delete auth from keyring: {err}"))
+            })?;
+        }
+    }
+    Ok(true)
+}
+
+fn verify_migrated(expected: &AuthDotJson, stored: Option<AuthDotJson>) -> std::io::Result<()> {
+    if stored.as_ref() == Some(expected) {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(
+            "credentials read back from the new store do not match; the original was kept",
+        ))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -669,4 +729,43 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn migrate_moves_auth_between_file_and_keyring() -> anyhow::Result<()> {
+        let codex_home = tempdir()?;
+        let mock_keyring = MockKeyringStore::default();
+        let keyring_store: Arc<dyn KeyringStore> = Arc::new(mock_keyring.clone());
+        let auth = auth_with_prefix("migrate");
+        FileAuthStorage::new(codex_home.path().to_path_buf()).save(&auth)?;
+        let key = compute_store_key(codex_home.path())?;
+
+        assert!(migrate_auth_storage_with_keyring_store(
+            codex_home.path().to_path_buf(),
+            AuthCredentialsStoreMode::Keyring,
+            keyring_store.clone(),
+        )?);
+        assert!(!get_auth_file(codex_home.path()).exists());
+        assert_eq!(
+            mock_keyring.saved_value(&key),
+            Some(serde_json::to_string(&auth)?)
+        );
+
+        assert!(migrate_auth_storage_with_keyring_store(
+            codex_home.path().to_path_buf(),
+            AuthCredentialsStoreMode::File,
+            keyring_store.clone(),
+        )?);
+        assert!(!mock_keyring.contains(&key));
+        assert_eq!(
+            FileAuthStorage::new(codex_home.path().to_path_buf()).load()?,
+            Some(auth)
+        );
+
+        assert!(!migrate_auth_storage_with_keyring_store(
+            codex_home.path().to_path_buf(),
+            AuthCredentialsStoreMode::File,
+            keyring_store,
+        )?);
+        Ok(())
+    }
 }
@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use codex_app_server_protocol::AuthMode;
+use reqwest::StatusCode;
+use thiserror::Error;
+
+use crate::auth::api_key_pool::describe_api_key;
+use crate::model_provider_info::ModelProviderInfo;
+use crate::util::try_parse_error_message;
+
+/// Listing models is cheap and needs nothing but a valid key.
+const VALIDATION_PATH: &str = "models";
+const VALIDATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Error)]
+pub enum ApiKeyValidationError {
+    /// The provider refused the key, e.g. because it was revoked.
+    #[error("{credential} was rejected by {url} ({status}): {message}")]
+    Rejected {
+        credential: String,
+        url: String,
+        status: StatusCode,
+        message: String,
+    },
+    /// The key could not be checked; it may still be valid.
+    #[error("could not check {credential} against {url}: {message}")]
+    Unverified {
+        credential: String,
+        url: String,
+        message: String,
+    },
+}
+
+impl ApiKeyValidationError {
+    pub fn is_rejected(&self) -> bool {
+        matches!(self, ApiKeyValidationError::Rejected { .. })
+    }
+}
+
+/// Lists the models of `provider` with `api_key` to find out whether the key
+/// is accepted. Only 401 and 403 responses count as rejected; network errors
+/// and other statuses leave the key unverified.
+pub async fn validate_api_key(
+    provider: &ModelProviderInfo,
+    api_key: &str,
+) -> Result<(), ApiKeyValidationError> {
+    let credential = describe_api_key(api_key);
+    let api_provider = match provider.to_api_provider(Some(AuthMode::ApiKey)) {
+        Ok(api_provider) => api_provider,
+        Err(err) => {
+            return Err(ApiKeyValidationError::Unverified {
+                credential,
+                url: provider.name.clone(),
+                message: err.to_string(),
+            });
+        }
+    };
+    let url = api_provider.url_for_path(VALIDATION_PATH);
+    let unverified = |message: String| ApiKeyValidationError::Unverified {
+        credential: credential.clone(),
+        url: url.clone(),
+        message,
+    };
+
+    let client = provider
+        .build_reqwest_client()
+        .map_err(|err| unverified(err.to_string()))?;
+    let response = client
+        .get(&url)
+        .headers(api_provider.headers)
+        .bearer_auth(api_key)
+        .timeout(VALIDATION_TIMEOUT)
+        .send()
+        .await
+        .map_err(|err| unverified(err.to_string()))?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let message = try_parse_error_message(&response.text().await.unwrap_or_default());
+    if matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+        Err(ApiKeyValidationError::Rejected {
+            credential,
+            url,
+            status,
+            message,
+        })
+    } else {
+        Err(unverified(format!("{status}: {message}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+
+    fn provider(server: &MockServer) -> ModelProviderInfo {
+        let mut provider = crate::model_provider_info::built_in_model_providers()["openai"].clone();
+        provider.base_url = Some(format!("{}/v1", server.uri()));
+        provider
+    }
+
+    #[tokio::test]
+    async fn revoked_keys_are_rejected_by_name() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .and(header("authorization", "Bearer sk-good-key-1234"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"data":[]}"#))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .respond_with(
+                ResponseTemplate::new(401)
+                    .set_body_string(r#"{"error":{"message":"Incorrect API key provided"}}"#),
+            )
+            .mount(&server)
+            .await;
+        let provider = provider(&server);
+
+        assert!(
+            validate_api_key(&provider, "sk-good-key-1234")
+                .await
+                .is_ok()
+        );
+        let err = validate_api_key(&provider, "sk-revoked-5678")
+            .await
+            .expect_err("revoked key");
+        assert!(err.is_rejected());
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "API key ending in 5678 was rejected by {}/v1/models (401 Unauthorized): Incorrect API key provided",
+                server.uri()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn server_errors_leave_the_key_unverified() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let err = validate_api_key(&provider(&server), "sk-any-key-1234")
+            .await
+            .expect_err("unverified");
+        assert!(!err.is_rejected());
+    }
+}
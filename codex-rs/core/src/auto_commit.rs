@@ -0,0 +1,286 @@
+//! Opt-in commit per turn (`[features] auto_commit`). The first turn of a
+//! session switches the repository to a `codex/<task-slug>` branch named
+//! after its prompt, and every turn that changes files ends with a commit of
+//! all changes, so the run can be reviewed commit by commit and reverted
+//! with plain git. In a session with additional roots (`--root`), each
+//! root's repository gets its own branch and commits.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use codex_protocol::items::TurnItem;
+use codex_protocol::models::ResponseItem;
+use tokio::process::Command;
+use tokio::time::timeout;
+use tracing::info;
+use tracing::warn;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::event_mapping::parse_turn_item;
+use crate::git_info::current_branch_name;
+use crate::git_info::get_git_repo_root;
+use crate::git_info::local_git_branches;
+
+/// Prefix of the branches commits go to.
+const BRANCH_PREFIX: &str = "codex/";
+
+/// Staging and committing a large tree can take a while.
+const GIT_TIMEOUT: Duration = Duration::from_secs(60);
+
+const MAX_SLUG_WORDS: usize = 6;
+const MAX_SLUG_LEN: usize = 40;
+const MAX_SUBJECT_LEN: usize = 60;
+/// Lines of the agent's final message kept in the commit body.
+const MAX_BODY_LINES: usize = 20;
+/// Body of the commit of an interrupted turn, in place of the final message.
+const INTERRUPTED_BODY: &str = "Interrupted before the turn finished.";
+
+/// The git repositories the session works in: the working directory's,
+/// then those of its additional roots, each once.
+fn session_repos(ctx: &TurnContext) -> Vec<PathBuf> {
+    let config = ctx.client.config();
+    let mut repos: Vec<PathBuf> = Vec::new();
+    for dir in std::iter::once(&ctx.cwd).chain(&config.additional_roots) {
+        if let Some(repo) = get_git_repo_root(dir)
+            && !repos.contains(&repo)
+        {
+            repos.push(repo);
+        }
+    }
+    repos
+}
+
+/// Switches each repository to a task branch unless it is already on one.
+/// Called at the start of each turn; only the first switches.
+pub(crate) async fn ensure_task_branch(sess: &Session, ctx: &TurnContext, prompt: &str) {
+    let repos = session_repos(ctx);
+    let base = format!(
+        "{BRANCH_PREFIX}{}",
+        task_slug(prompt).unwrap_or_else(|| format!("session-{}", sess.conversation_id))
+    );
+    for repo in &repos {
+        if current_branch_name(repo)
+            .await
+            .is_some_and(|branch| branch.starts_with(BRANCH_PREFIX))
+        {
+            continue;
+        }
+        let existing = local_git_branches(repo).await;
+        let mut branch = base.clone();
+        let mut suffix = 1;
+        while existing.contains(&branch) {
+            suffix += 1;
+            branch = format!("{base}-{suffix}");
+        }
+        match run_git(repo, &["switch", "-c", &branch]).await {
+            Ok(_) => {
+                info!(branch, "switched to auto-commit branch");
+                let message = if repos.len() > 1 {
+                    format!("Committing each turn to {branch} in {}", repo.display())
+                } else {
+                    format!("Committing each turn to {branch}")
+                };
+                sess.notify_background_event(ctx, message).await;
+            }
+            Err(err) => warn!("failed to create auto-commit branch {branch}: {err}"),
+        }
+    }
+}
+
+/// Commits everything the turn changed, in each repository it changed.
+/// Repositories the turn did not change are skipped.
+pub(crate) async fn commit_turn(
+    sess: &Session,
+    ctx: &TurnContext,
+    prompt: &str,
+    last_agent_message: Option<&str>,
+) {
+    let repos = session_repos(ctx);
+    if repos.is_empty() {
+        return;
+    }
+    let history = sess.clone_history().await.get_history();
+    let turn = user_turns(&history);
+    let message = commit_message(
+        turn,
+        prompt,
+        last_agent_message,
+        &sess.conversation_id.to_string(),
+    );
+    let mut commits: Vec<String> = Vec::new();
+    for repo in &repos {
+        match commit_repo(repo, &message).await {
+            Ok(Some(commit)) => {
+                info!(turn, commit, repo = %repo.display(), "auto-committed turn");
+                commits.push(if repos.len() > 1 {
+                    format!("{commit} in {}", repo.display())
+                } else {
+                    commit
+                });
+            }
+            Ok(None) => {}
+            Err(err) => warn!(
+                "failed to auto-commit turn {turn} in {}: {err}",
+                repo.display()
+            ),
+        }
+    }
+    if !commits.is_empty() {
+        sess.notify_background_event(
+            ctx,
+            format!("Committed turn {turn} as {}", commits.join(", ")),
+        )
+        .await;
+    }
+}
+
+/// Commits what an interrupted turn changed so far, so the repository is
+/// left at a commit that `codex resume` can carry on from instead of with
+/// the edits of a half-finished turn in the work tree.
+pub(crate) async fn commit_interrupted_turn(sess: &Session, ctx: &TurnContext) {
+    let history = sess.clone_history().await.get_history();
+    let prompt = history
+        .iter()
+        .rev()
+        .find_map(|item| match parse_turn_item(item) {
+            Some(TurnItem::UserMessage(message))
+                if matches!(item, ResponseItem::Message { .. }) =>
+            {
+                Some(message.message())
+            }
+            _ => None,
+        })
+        .unwrap_or_default();
+    commit_turn(sess, ctx, &prompt, Some(INTERRUPTED_BODY)).await;
+}
+
+/// Commits all changes in `repo`, returning the short hash of the commit,
+/// or `None` when there was nothing to commit.
+async fn commit_repo(repo: &Path, message: &str) -> Result<Option<String>, String> {
+    run_git(repo, &["add", "--all"]).await?;
+    if run_git(repo, &["diff", "--cached", "--quiet"])
+        .await
+        .is_ok()
+    {
+        return Ok(None);
+    }
+    run_git(repo, &["commit", "--quiet", "-m", message]).await?;
+    run_git(repo, &["rev-parse", "--short", "HEAD"])
+        .await
+        .map(Some)
+}
+
+fn user_turns(history: &[ResponseItem]) -> usize {
+    history
+        .iter()
+        .filter(|item| {
+            matches!(item, ResponseItem::Message { .. })
+                && matches!(parse_turn_item(item), Some(TurnItem::UserMessage(_)))
+        })
+        .count()
+}
+
+/// Up to the first few words of `prompt`, lowercased and joined with `-`.
+fn task_slug(prompt: &str) -> Option<String> {
+    let mut slug = String::new();
+    let words = prompt
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .take(MAX_SLUG_WORDS);
+    for word in words {
+        let word = word.to_ascii_lowercase();
+        if !slug.is_empty() && slug.len() + 1 + word.len() > MAX_SLUG_LEN {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(&word);
+    }
+    slug.truncate(MAX_SLUG_LEN);
+    (!slug.is_empty()).then_some(slug)
+}
+
+fn commit_message(
+    turn: usize,
+    prompt: &str,
+    last_agent_message: Option<&str>,
+    conversation_id: &str,
+) -> String {
+    let first_line = prompt
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("");
+    let mut subject: String = first_line.trim().chars().take(MAX_SUBJECT_LEN).collect();
+    if first_line.trim().chars().count() > MAX_SUBJECT_LEN {
+        subject.push_str("...");
+    }
+    let mut message = format!("codex: turn {turn}: {subject}\n");
+    if let Some(reply) = last_agent_message
+        .map(str::trim)
+        .filter(|reply| !reply.is_empty())
+    {
+        message.push('\n');
+        for line in reply.lines().take(MAX_BODY_LINES) {
+            message.push_str(line.trim_end());
+            message.push('\n');
+        }
+    }
+    message.push_str(&format!("\nCodex-Session: {conversation_id}\n"));
+    message
+}
+
+async fn run_git(cwd: &Path, args: &[&str]) -> Result<String, String> {
+    let output = timeout(
+        GIT_TIMEOUT,
+        Command::new("git").args(args).current_dir(cwd).output(),
+    )
+    .await
+    .map_err(|_| format!("git {} timed out", args.join(" ")))?
+    .map_err(|err| format!("failed to run git: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn branch_slug_comes_from_the_prompt() {
+        assert_eq!(
+            task_slug("Fix the login redirect loop in `auth.rs`, then add tests").as_deref(),
+            Some("fix-the-login-redirect-loop-in")
+        );
+        assert_eq!(task_slug("   ").as_deref(), None);
+        assert_eq!(task_slug("日本語").as_deref(), None);
+    }
+
+    #[test]
+    fn commit_message_references_the_turn() {
+        assert_eq!(
+            commit_message(
+                3,
+                "\nFix the login redirect loop\nIt happens after logout.",
+                Some("Stopped redirecting to /login from /login.\n\nAdded a test."),
+                "7f9f9a2e-1b3c-4c7a-9b0e-123456789abc",
+            ),
+            "codex: turn 3: Fix the login redirect loop\n\
+             \n\
+             Stopped redirecting to /login from /login.\n\
+             \n\
+             Added a test.\n\
+             \n\
+             Codex-Session: 7f9f9a2e-1b3c-4c7a-9b0e-123456789abc\n"
+        );
+    }
+}
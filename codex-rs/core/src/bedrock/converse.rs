@@ -0,0 +1,368 @@
+//! Translation between Codex's conversation items and the Bedrock Converse API
+//! (`POST /model/{modelId}/converse`).
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ReasoningItemContent;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::TokenUsage;
+use serde_json::Map;
+use serde_json::Value;
+use serde_json::json;
+
+/// Builds the Converse request body. Items Bedrock has no equivalent for
+/// (reasoning, freeform tool calls, web searches) are left out.
+pub(crate) fn build_request(instructions: &str, input: &[ResponseItem], tools: &[Value]) -> Value {
+    let mut system = vec![json!({ "text": instructions })];
+    let mut messages: Vec<(&'static str, Vec<Value>)> = Vec::new();
+    let mut push = |role: &'static str, block: Value| match messages.last_mut() {
+        // Converse requires user and assistant turns to alternate.
+        Some((last_role, blocks)) if *last_role == role => blocks.push(block),
+        _ => messages.push((role, vec![block])),
+    };
+
+    for item in input {
+        match item {
+            ResponseItem::Message { role, content, .. } => {
+                let role = match role.as_str() {
+                    "assistant" => "assistant",
+                    "user" => "user",
+                    _ => {
+                        system.extend(text_blocks(content));
+                        continue;
+                    }
+                };
+                for block in content.iter().filter_map(content_block) {
+                    push(role, block);
+                }
+            }
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => push(
+                "assistant",
+                json!({
+                    "toolUse": {
+                        "toolUseId": call_id,
+                        "name": name,
+                        "input": tool_input(arguments),
+                    }
+                }),
+            ),
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                let text = if output.content.is_empty() {
+                    "(no output)"
+                } else {
+                    output.content.as_str()
+                };
+                push(
+                    "user",
+                    json!({
+                        "toolResult": {
+                            "toolUseId": call_id,
+                            "content": [{ "text": text }],
+                            "status": if output.success == Some(false) { "error" } else { "success" },
+                        }
+                    }),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let mut body = Map::new();
+    body.insert("system".to_string(), Value::Array(system));
+    body.insert(
+        "messages".to_string(),
+        messages
+            .into_iter()
+            .map(|(role, content)| json!({ "role": role, "content": content }))
+            .collect(),
+    );
+    let tool_specs: Vec<Value> = tools.iter().filter_map(tool_spec).collect();
+    if !tool_specs.is_empty() {
+        body.insert("toolConfig".to_string(), json!({ "tools": tool_specs }));
+    }
+    Value::Object(body)
+}
+
+/// Output items, token usage, and whether the model ran out of context.
+#[derive(Debug, PartialEq)]
+pub(crate) struct ConverseOutput {
+    pub items: Vec<ResponseItem>,
+    pub usage: Option<TokenUsage>,
+    pub context_window_exceeded: bool,
+}
+
+pub(crate) fn parse_response(body: &Value) -> ConverseOutput {
+    let blocks = body
+        .pointer("/output/message/content")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    let mut reasoning = Vec::new();
+    let mut text = Vec::new();
+    let mut tool_calls = Vec::new();
+    for block in blocks {
+        if let Some(segment) = block.get("text").and_then(Value::as_str) {
+            text.push(ContentItem::OutputText {
+                text: segment.to_string(),
+            });
+        } else if let Some(tool_use) = block.get("toolUse") {
+            tool_calls.push(ResponseItem::FunctionCall {
+                id: None,
+                name: string_field(tool_use, "name"),
+                arguments: tool_use
+                    .get("input")
+                    .map(Value::to_string)
+                    .unwrap_or_else(|| "{}".to_string()),
+                call_id: string_field(tool_use, "toolUseId"),
+            });
+        } else if let Some(segment) = block
+            .pointer("/reasoningContent/reasoningText/text")
+            .and_then(Value::as_str)
+        {
+            reasoning.push(ReasoningItemContent::ReasoningText {
+                text: segment.to_string(),
+            });
+        }
+    }
+
+    let mut items = Vec::new();
+    if !reasoning.is_empty() {
+        items.push(ResponseItem::Reasoning {
+            id: String::new(),
+            summary: Vec::new(),
+            content: Some(reasoning),
+            encrypted_content: None,
+        });
+    }
+    if !text.is_empty() {
+        items.push(ResponseItem::Message {
+            id: None,
+            role: "assistant".to_string(),
+            content: text,
+        });
+    }
+    items.extend(tool_calls);
+
+    let usage = body.get("usage").map(|usage| {
+        let field = |name: &str| usage.get(name).and_then(Value::as_i64).unwrap_or(0);
+        let input_tokens = field("inputTokens");
+        let output_tokens = field("outputTokens");
+        TokenUsage {
+            input_tokens,
+            cached_input_tokens: field("cacheReadInputTokens"),
+            output_tokens,
+            reasoning_output_tokens: 0,
+            total_tokens: usage
+                .get("totalTokens")
+                .and_then(Value::as_i64)
+                .unwrap_or(input_tokens + output_tokens),
+        }
+    });
+
+    ConverseOutput {
+        items,
+        usage,
+        context_window_exceeded: body.get("stopReason").and_then(Value::as_str)
+            == Some("model_context_window_exceeded"),
+    }
+}
+
+fn string_field(value: &Value, name: &str) -> String {
+    value
+        .get(name)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn text_blocks(content: &[ContentItem]) -> Vec<Value> {
+    content
+        .iter()
+        .filter_map(|item| match item {
+            ContentItem::InputText { text } | ContentItem::OutputText { text }
+                if !text.is_empty() =>
+            {
+                Some(json!({ "text": text }))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn content_block(item: &ContentItem) -> Option<Value> {
+    match item {
+        // Converse rejects empty text blocks.
+        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+            (!text.is_empty()).then(|| json!({ "text": text }))
+        }
+        ContentItem::InputImage { image_url } => Some(image_block(image_url)),
+    }
+}
+
+/// Converse only takes inline image bytes, so remote URLs become text.
+fn image_block(image_url: &str) -> Value {
+    let inline = image_url
+        .strip_prefix("data:image/")
+        .and_then(|rest| rest.split_once(";base64,"));
+    match inline {
+        Some((format, data)) => {
+            let format = if format == "jpg" { "jpeg" } else { format };
+            json!({ "image": { "format": format, "source": { "bytes": data } } })
+        }
+        None => json!({ "text": format!("[image: {image_url}]") }),
+    }
+}
+
+/// Tool arguments are a JSON string; Converse wants an object.
+fn tool_input(arguments: &str) -> Value {
+    match serde_json::from_str::<Value>(arguments) {
+        Ok(value @ Value::Object(_)) => value,
+        _ => json!({ "arguments": arguments }),
+    }
+}
+
+/// Converts a Responses API function tool into a Converse `toolSpec`.
+fn tool_spec(tool: &Value) -> Option<Value> {
+    if tool.get("type").and_then(Value::as_str) != Some("function") {
+        return None;
+    }
+    Some(json!({
+        "toolSpec": {
+            "name": tool.get("name")?,
+            "description": tool.get("description").cloned().unwrap_or_else(|| json!("")),
+            "inputSchema": {
+                "json": tool.get("parameters").cloned().unwrap_or_else(|| json!({ "type": "object" })),
+            },
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    fn message(role: &str, text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn builds_alternating_messages_with_tool_round_trip() {
+        let input = vec![
+            message("developer", "be brief"),
+            message("user", "list files"),
+            message("user", "in src"),
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: r#"{"command":["ls"]}"#.to_string(),
+                call_id: "call_1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "call_1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "main.rs".to_string(),
+                    content_items: None,
+                    success: Some(true),
+                },
+            },
+        ];
+        let tools = vec![json!({
+            "type": "function",
+            "name": "shell",
+            "description": "Runs a command",
+            "strict": false,
+            "parameters": { "type": "object", "properties": {} },
+        })];
+
+        assert_eq!(
+            build_request("You are Codex.", &input, &tools),
+            json!({
+                "system": [{ "text": "You are Codex." }, { "text": "be brief" }],
+                "messages": [
+                    { "role": "user", "content": [{ "text": "list files" }, { "text": "in src" }] },
+                    { "role": "assistant", "content": [{ "toolUse": {
+                        "toolUseId": "call_1",
+                        "name": "shell",
+                        "input": { "command": ["ls"] },
+                    } }] },
+                    { "role": "user", "content": [{ "toolResult": {
+                        "toolUseId": "call_1",
+                        "content": [{ "text": "main.rs" }],
+                        "status": "success",
+                    } }] },
+                ],
+                "toolConfig": { "tools": [{ "toolSpec": {
+                    "name": "shell",
+                    "description": "Runs a command",
+                    "inputSchema": { "json": { "type": "object", "properties": {} } },
+                } }] },
+            })
+        );
+    }
+
+    #[test]
+    fn inline_images_become_image_blocks() {
+        assert_eq!(
+            image_block("data:image/jpg;base64,AAAA"),
+            json!({ "image": { "format": "jpeg", "source": { "bytes": "AAAA" } } })
+        );
+        assert_eq!(
+            image_block("https://example.com/a.png"),
+            json!({ "text": "[image: https://example.com/a.png]" })
+        );
+    }
+
+    #[test]
+    fn parses_text_tool_use_and_usage() {
+        let output = parse_response(&json!({
+            "output": { "message": { "role": "assistant", "content": [
+                { "text": "Listing." },
+                { "toolUse": { "toolUseId": "tool_1", "name": "shell", "input": { "command": ["ls"] } } },
+            ] } },
+            "stopReason": "tool_use",
+            "usage": { "inputTokens": 100, "outputTokens": 20, "totalTokens": 120, "cacheReadInputTokens": 80 },
+        }));
+
+        assert_eq!(
+            output,
+            ConverseOutput {
+                items: vec![
+                    ResponseItem::Message {
+                        id: None,
+                        role: "assistant".to_string(),
+                        content: vec![ContentItem::OutputText {
+                            text: "Listing.".to_string()
+                        }],
+                    },
+                    ResponseItem::FunctionCall {
+                        id: None,
+                        name: "shell".to_string(),
+                        arguments: r#"{"command":["ls"]}"#.to_string(),
+                        call_id: "tool_1".to_string(),
+                    },
+                ],
+                usage: Some(TokenUsage {
+                    input_tokens: 100,
+                    cached_input_tokens: 80,
+                    output_tokens: 20,
+                    reasoning_output_tokens: 0,
+                    total_tokens: 120,
+                }),
+                context_window_exceeded: false,
+            }
+        );
+    }
+}
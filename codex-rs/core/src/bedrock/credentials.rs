@@ -0,0 +1,493 @@
+//! AWS credential and region resolution, following the order of the standard
+//! credential chain: environment variables, the shared credentials file, the
+//! profile's `credential_process`, web identity (`AWS_WEB_IDENTITY_TOKEN_FILE`,
+//! as set up by EKS IRSA), container credentials (ECS/EKS), then EC2 instance
+//! metadata (IMDSv2). SSO and profiles that assume a role are not supported;
+//! use `aws configure export-credentials` to turn those into environment
+//! variables.
+
+use std::io;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use chrono::DateTime;
+use chrono::Utc;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+use super::sigv4::uri_encode;
+use crate::default_client::build_provider_reqwest_client;
+use crate::model_provider_info::non_empty_env;
+
+const IMDS_BASE_URL: &str = "http://169.254.169.254";
+const ECS_CREDENTIALS_HOST: &str = "http://169.254.170.2";
+const METADATA_TIMEOUT: Duration = Duration::from_secs(2);
+const STS_TIMEOUT: Duration = Duration::from_secs(10);
+const STS_API_VERSION: &str = "2011-06-15";
+
+/// Refresh temporary credentials this long before they expire.
+const EXPIRY_SKEW: chrono::Duration = chrono::Duration::minutes(5);
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+/// Credentials with the time they expire, if they do.
+type ExpiringCredentials = (AwsCredentials, Option<DateTime<Utc>>);
+
+/// Credentials from `credential_process`, web identity, or a metadata
+/// endpoint, cached until shortly before expiry.
+static METADATA_CREDENTIALS: LazyLock<Mutex<Option<ExpiringCredentials>>> =
+    LazyLock::new(|| Mutex::new(None));
+
+pub(crate) async fn resolve_credentials() -> io::Result<AwsCredentials> {
+    if let Some(credentials) = from_env() {
+        return Ok(credentials);
+    }
+    let profile = profile_name();
+    if let Some(credentials) = from_shared_file(&profile)? {
+        return Ok(credentials);
+    }
+
+    let mut cached = METADATA_CREDENTIALS.lock().await;
+    if let Some((credentials, expires_at)) = cached.as_ref()
+        && expires_at.is_none_or(|at| Utc::now() + EXPIRY_SKEW < at)
+    {
+        return Ok(credentials.clone());
+    }
+    let fetched = if let Some(fetched) = from_credential_process(&profile).await? {
+        fetched
+    } else if let Some(fetched) = from_web_identity().await? {
+        fetched
+    } else if let Some(fetched) = from_container().await? {
+        fetched
+    } else {
+        from_instance_metadata().await.map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "no AWS credentials found: set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY, configure ~/.aws/credentials or a credential_process in ~/.aws/config, set AWS_WEB_IDENTITY_TOKEN_FILE and AWS_ROLE_ARN, or set AWS_BEARER_TOKEN_BEDROCK ({err})"
+                ),
+            )
+        })?
+    };
+    let credentials = fetched.0.clone();
+    *cached = Some(fetched);
+    Ok(credentials)
+}
+
+/// Region from `AWS_REGION`, `AWS_DEFAULT_REGION`, or the active profile in
+/// `~/.aws/config`.
+pub(crate) fn resolve_region() -> Option<String> {
+    non_empty_env("AWS_REGION")
+        .or_else(|| non_empty_env("AWS_DEFAULT_REGION"))
+        .or_else(|| config_value(&profile_name(), "region"))
+}
+
+/// Looks up `key` in the section for `profile` in `~/.aws/config`.
+fn config_value(profile: &str, key: &str) -> Option<String> {
+    let section = if profile == "default" {
+        profile.to_string()
+    } else {
+        format!("profile {profile}")
+    };
+    let contents = std::fs::read_to_string(config_file_path()?).ok()?;
+    ini_value(&contents, &section, key)
+}
+
+fn from_env() -> Option<AwsCredentials> {
+    Some(AwsCredentials {
+        access_key_id: non_empty_env("AWS_ACCESS_KEY_ID")?,
+        secret_access_key: non_empty_env("AWS_SECRET_ACCESS_KEY")?,
+        session_token: non_empty_env("AWS_SESSION_TOKEN"),
+    })
+}
+
+fn profile_name() -> String {
+    non_empty_env("AWS_PROFILE").unwrap_or_else(|| "default".to_string())
+}
+
+fn credentials_file_path() -> Option<PathBuf> {
+    non_empty_env("AWS_SHARED_CREDENTIALS_FILE")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".aws").join("credentials")))
+}
+
+fn config_file_path() -> Option<PathBuf> {
+    non_empty_env("AWS_CONFIG_FILE")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".aws").join("config")))
+}
+
+fn from_shared_file(profile: &str) -> io::Result<Option<AwsCredentials>> {
+    let Some(path) = credentials_file_path() else {
+        return Ok(None);
+    };
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    Ok(credentials_from_ini(&contents, profile))
+}
+
+fn credentials_from_ini(contents: &str, profile: &str) -> Option<AwsCredentials> {
+    Some(AwsCredentials {
+        access_key_id: ini_value(contents, profile, "aws_access_key_id")?,
+        secret_access_key: ini_value(contents, profile, "aws_secret_access_key")?,
+        session_token: ini_value(contents, profile, "aws_session_token"),
+    })
+}
+
+/// Looks up `key` in `[section]` of an AWS-style INI file.
+fn ini_value(contents: &str, section: &str, key: &str) -> Option<String> {
+    let mut in_section = false;
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_section = name.trim() == section;
+            continue;
+        }
+        if in_section
+            && let Some((name, value)) = line.split_once('=')
+            && name.trim() == key
+        {
+            let value = value.trim();
+            return (!value.is_empty()).then(|| value.to_string());
+        }
+    }
+    None
+}
+
+/// Shape returned by the container and the instance metadata endpoints, and
+/// printed by a `credential_process`.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct MetadataCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    #[serde(alias = "SessionToken")]
+    token: Option<String>,
+    expiration: Option<DateTime<Utc>>,
+}
+
+impl MetadataCredentials {
+    fn into_parts(self) -> ExpiringCredentials {
+        (
+            AwsCredentials {
+                access_key_id: self.access_key_id,
+                secret_access_key: self.secret_access_key,
+                session_token: self.token,
+            },
+            self.expiration,
+        )
+    }
+}
+
+/// Runs the profile's `credential_process` from `~/.aws/config`, if it has
+/// one.
+async fn from_credential_process(profile: &str) -> io::Result<Option<ExpiringCredentials>> {
+    match config_value(profile, "credential_process") {
+        Some(command) => run_credential_process(&command).await.map(Some),
+        None => Ok(None),
+    }
+}
+
+async fn run_credential_process(command: &str) -> io::Result<ExpiringCredentials> {
+    let mut process = if cfg!(windows) {
+        let mut process = tokio::process::Command::new("cmd");
+        process.args(["/C", command]);
+        process
+    } else {
+        let mut process = tokio::process::Command::new("sh");
+        process.args(["-c", command]);
+        process
+    };
+    let output = process
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "credential_process `{command}` failed with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let credentials: MetadataCredentials =
+        serde_json::from_slice(&output.stdout).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("credential_process `{command}` printed invalid credentials: {err}"),
+            )
+        })?;
+    Ok(credentials.into_parts())
+}
+
+/// Exchanges the token in `AWS_WEB_IDENTITY_TOKEN_FILE` for credentials of
+/// `AWS_ROLE_ARN` with STS `AssumeRoleWithWebIdentity`, which needs no AWS
+/// credentials of its own.
+async fn from_web_identity() -> io::Result<Option<ExpiringCredentials>> {
+    let (Some(token_file), Some(role_arn)) = (
+        non_empty_env("AWS_WEB_IDENTITY_TOKEN_FILE"),
+        non_empty_env("AWS_ROLE_ARN"),
+    ) else {
+        return Ok(None);
+    };
+    let token = std::fs::read_to_string(&token_file).map_err(|err| {
+        io::Error::new(
+            err.kind(),
+            format!("failed to read AWS_WEB_IDENTITY_TOKEN_FILE {token_file}: {err}"),
+        )
+    })?;
+    let session_name = non_empty_env("AWS_ROLE_SESSION_NAME")
+        .unwrap_or_else(|| format!("codex-{}", Utc::now().timestamp()));
+    let endpoint = match resolve_region() {
+        Some(region) => format!("https://sts.{region}.amazonaws.com/"),
+        None => "https://sts.amazonaws.com/".to_string(),
+    };
+    let body = [
+        ("Action", "AssumeRoleWithWebIdentity"),
+        ("Version", STS_API_VERSION),
+        ("RoleArn", role_arn.as_str()),
+        ("RoleSessionName", session_name.as_str()),
+        ("WebIdentityToken", token.trim()),
+    ]
+    .iter()
+    .map(|(name, value)| format!("{name}={}", uri_encode(value)))
+    .collect::<Vec<_>>()
+    .join("&");
+
+    let response = build_provider_reqwest_client()
+        .post(endpoint)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(body)
+        .timeout(STS_TIMEOUT)
+        .send()
+        .await
+        .map_err(io::Error::other)?;
+    let status = response.status();
+    let text = response.text().await.map_err(io::Error::other)?;
+    if !status.is_success() {
+        return Err(io::Error::other(format!(
+            "AssumeRoleWithWebIdentity for {role_arn} failed with {status}: {}",
+            xml_value(&text, "Message").unwrap_or(text.trim())
+        )));
+    }
+    sts_credentials(&text).map(Some).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "AssumeRoleWithWebIdentity returned no credentials",
+        )
+    })
+}
+
+/// The credentials in an STS `AssumeRole*` XML response.
+fn sts_credentials(xml: &str) -> Option<ExpiringCredentials> {
+    Some((
+        AwsCredentials {
+            access_key_id: xml_value(xml, "AccessKeyId")?.to_string(),
+            secret_access_key: xml_value(xml, "SecretAccessKey")?.to_string(),
+            session_token: xml_value(xml, "SessionToken").map(str::to_string),
+        },
+        xml_value(xml, "Expiration").and_then(|at| at.parse().ok()),
+    ))
+}
+
+/// Text of the first `<tag>` element. The STS values read here are ASCII
+/// without markup, so no entity decoding is needed.
+fn xml_value<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let start = xml.find(&format!("<{tag}>"))? + tag.len() + 2;
+    let end = start + xml[start..].find(&format!("</{tag}>"))?;
+    Some(xml[start..end].trim())
+}
+
+async fn from_container() -> io::Result<Option<ExpiringCredentials>> {
+    let url = match (
+        non_empty_env("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI"),
+        non_empty_env("AWS_CONTAINER_CREDENTIALS_FULL_URI"),
+    ) {
+        (Some(relative), _) => format!("{ECS_CREDENTIALS_HOST}{relative}"),
+        (None, Some(full)) => full,
+        (None, None) => return Ok(None),
+    };
+    let mut request = build_provider_reqwest_client()
+        .get(url)
+        .timeout(METADATA_TIMEOUT);
+    let token = match non_empty_env("AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE") {
+        Some(path) => Some(std::fs::read_to_string(path)?.trim().to_string()),
+        None => non_empty_env("AWS_CONTAINER_AUTHORIZATION_TOKEN"),
+    };
+    if let Some(token) = token {
+        request = request.header("Authorization", token);
+    }
+    let credentials: MetadataCredentials = request
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(io::Error::other)?
+        .json()
+        .await
+        .map_err(io::Error::other)?;
+    Ok(Some(credentials.into_parts()))
+}
+
+async fn from_instance_metadata() -> io::Result<ExpiringCredentials> {
+    let client = build_provider_reqwest_client();
+    let token = client
+        .put(format!("{IMDS_BASE_URL}/latest/api/token"))
+        .header("X-aws-ec2-metadata-token-ttl-seconds", "21600")
+        .timeout(METADATA_TIMEOUT)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(io::Error::other)?
+        .text()
+        .await
+        .map_err(io::Error::other)?;
+    let roles_url = format!("{IMDS_BASE_URL}/latest/meta-data/iam/security-credentials/");
+    let role = client
+        .get(&roles_url)
+        .header("X-aws-ec2-metadata-token", &token)
+        .timeout(METADATA_TIMEOUT)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(io::Error::other)?
+        .text()
+        .await
+        .map_err(io::Error::other)?;
+    let role = role
+        .lines()
+        .next()
+        .map(str::trim)
+        .filter(|role| !role.is_empty())
+        .ok_or_else(|| io::Error::other("instance has no IAM role attached"))?;
+    let credentials: MetadataCredentials = client
+        .get(format!("{roles_url}{role}"))
+        .header("X-aws-ec2-metadata-token", &token)
+        .timeout(METADATA_TIMEOUT)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(io::Error::other)?
+        .json()
+        .await
+        .map_err(io::Error::other)?;
+    Ok(credentials.into_parts())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const FILE: &str = "\
+[default]
+aws_access_key_id = AKIDDEFAULT
+aws_secret_access_key = secret-default
+
+# comment
+[work]
+aws_access_key_id=AKIDWORK
+aws_secret_access_key=secret-work
+aws_session_token=token-work
+
+[profile work]
+region = eu-west-1
+";
+
+    #[test]
+    fn reads_profiles_from_shared_credentials() {
+        assert_eq!(
+            credentials_from_ini(FILE, "default"),
+            Some(AwsCredentials {
+                access_key_id: "AKIDDEFAULT".to_string(),
+                secret_access_key: "secret-default".to_string(),
+                session_token: None,
+            })
+        );
+        assert_eq!(
+            credentials_from_ini(FILE, "work").and_then(|c| c.session_token),
+            Some("token-work".to_string())
+        );
+        assert_eq!(credentials_from_ini(FILE, "missing"), None);
+        assert_eq!(
+            ini_value(FILE, "profile work", "region"),
+            Some("eu-west-1".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_metadata_credentials() {
+        let parsed: MetadataCredentials = serde_json::from_str(
+            r#"{"Code":"Success","AccessKeyId":"ASIA","SecretAccessKey":"s","Token":"t","Expiration":"2025-01-01T00:00:00Z"}"#,
+        )
+        .expect("parse");
+        let (credentials, expires_at) = parsed.into_parts();
+        assert_eq!(credentials.session_token.as_deref(), Some("t"));
+        assert_eq!(
+            expires_at,
+            DateTime::<Utc>::from_timestamp(1_735_689_600, 0)
+        );
+    }
+
+    #[test]
+    fn reads_credentials_from_an_sts_response() {
+        let xml = r#"<AssumeRoleWithWebIdentityResponse xmlns="https://sts.amazonaws.com/doc/2011-06-15/">
+  <AssumeRoleWithWebIdentityResult>
+    <Credentials>
+      <SessionToken>FwoGZXIvYXdzEXAMPLE</SessionToken>
+      <SecretAccessKey>wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY</SecretAccessKey>
+      <Expiration>2025-01-01T00:00:00Z</Expiration>
+      <AccessKeyId>ASIAEXAMPLE</AccessKeyId>
+    </Credentials>
+  </AssumeRoleWithWebIdentityResult>
+</AssumeRoleWithWebIdentityResponse>"#;
+        assert_eq!(
+            sts_credentials(xml),
+            Some((
+                AwsCredentials {
+                    access_key_id: "ASIAEXAMPLE".to_string(),
+                    secret_access_key: "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY".to_string(),
+                    session_token: Some("FwoGZXIvYXdzEXAMPLE".to_string()),
+                },
+                DateTime::<Utc>::from_timestamp(1_735_689_600, 0),
+            ))
+        );
+        assert_eq!(sts_credentials("<ErrorResponse/>"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn runs_the_credential_process() {
+        let (credentials, expires_at) = run_credential_process(
+            r#"echo '{"Version":1,"AccessKeyId":"ASIA","SecretAccessKey":"s","SessionToken":"t"}'"#,
+        )
+        .await
+        .expect("credentials");
+        assert_eq!(
+            credentials,
+            AwsCredentials {
+                access_key_id: "ASIA".to_string(),
+                secret_access_key: "s".to_string(),
+                session_token: Some("t".to_string()),
+            }
+        );
+        assert_eq!(expires_at, None);
+
+        let err = run_credential_process("echo expired >&2; exit 1")
+            .await
+            .expect_err("failing process");
+        assert!(err.to_string().contains("expired"), "{err}");
+    }
+}
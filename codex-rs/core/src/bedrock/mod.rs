@@ -0,0 +1,252 @@
+//! Amazon Bedrock provider (`wire_api = "bedrock"`).
+//!
+//! Turns are sent to the non-streaming Converse API and replayed as a
+//! completed response, so the rest of the turn loop sees the same events it
+//! gets from the OpenAI wire APIs. Requests are signed with SigV4 using the
+//! standard AWS credential chain, or authenticated with a Bedrock API key from
+//! `AWS_BEARER_TOKEN_BEDROCK` when one is set.
+
+mod converse;
+pub(crate) mod credentials;
+pub(crate) mod sigv4;
+
+use chrono::Utc;
+use codex_api::TransportError;
+use codex_api::error::ApiError;
+use codex_protocol::models::ResponseItem;
+use http::StatusCode;
+use serde_json::Value;
+
+use crate::api_bridge::map_api_error;
+use crate::client_common::ResponseEvent;
+use crate::error::CodexErr;
+use crate::error::EnvVarError;
+use crate::error::Result;
+use crate::model_provider_info::ModelProviderInfo;
+use crate::model_provider_info::non_empty_env;
+
+/// Bedrock API key; takes precedence over SigV4 credentials.
+pub(crate) const BEDROCK_BEARER_TOKEN_ENV_VAR: &str = "AWS_BEARER_TOKEN_BEDROCK";
+
+const CONTENT_TYPE: &str = "application/json";
+
+/// Runs one turn against `POST /model/{model}/converse` and returns the
+/// events of the completed response.
+pub(crate) async fn converse(
+    provider: &ModelProviderInfo,
+    model: &str,
+    instructions: &str,
+    input: &[ResponseItem],
+    tools: &[Value],
+) -> Result<Vec<ResponseEvent>> {
+    let base_url = match &provider.base_url {
+        Some(base_url) => base_url.trim_end_matches('/').to_string(),
+        None => format!("https://bedrock-runtime.{}.amazonaws.com", region(None)?),
+    };
+    let url = reqwest::Url::parse(&format!(
+        "{base_url}/model/{}/converse",
+        sigv4::uri_encode(model)
+    ))
+    .map_err(|err| CodexErr::Stream(format!("invalid Bedrock URL {base_url}: {err}"), None))?;
+    let body = serde_json::to_vec(&converse::build_request(instructions, input, tools))?;
+
+    let mut request = provider
+        .build_reqwest_client()?
+        .post(url.clone())
+        .header("content-type", CONTENT_TYPE)
+        .timeout(provider.stream_idle_timeout());
+    for (name, value) in &provider.build_header_map()? {
+        request = request.header(name, value);
+    }
+    let bearer_token = provider
+        .api_key()?
+        .or_else(|| non_empty_env(BEDROCK_BEARER_TOKEN_ENV_VAR));
+    request = match bearer_token {
+        Some(token) => request.bearer_auth(token),
+        None => {
+            let host = match (url.host_str(), url.port()) {
+                (Some(host), Some(port)) => format!("{host}:{port}"),
+                (Some(host), None) => host.to_string(),
+                (None, _) => String::new(),
+            };
+            let credentials = credentials::resolve_credentials().await?;
+            let signed = sigv4::sign(
+                &sigv4::SigningRequest {
+                    host: &host,
+                    path: url.path(),
+                    content_type: CONTENT_TYPE,
+                    body: &body,
+                    region: &region(url.host_str())?,
+                },
+                &credentials,
+                Utc::now(),
+            );
+            request = request
+                .header("x-amz-date", signed.amz_date)
+                .header("authorization", signed.authorization);
+            match signed.security_token {
+                Some(token) => request.header("x-amz-security-token", token),
+                None => request,
+            }
+        }
+    };
+
+    let response = request.body(body).send().await.map_err(|err| {
+        map_api_error(ApiError::Transport(if err.is_timeout() {
+            TransportError::Timeout
+        } else {
+            TransportError::Network(err.to_string())
+        }))
+    })?;
+    let status = response.status();
+    let headers = response.headers().clone();
+    let text = response
+        .text()
+        .await
+        .map_err(|err| CodexErr::Stream(err.to_string(), None))?;
+    if !status.is_success() {
+        return Err(map_api_error(classify_error(status, headers, text)));
+    }
+
+    let output = converse::parse_response(&serde_json::from_str(&text)?);
+    if output.context_window_exceeded {
+        return Err(CodexErr::ContextWindowExceeded);
+    }
+    let response_id = headers
+        .get("x-amzn-requestid")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let mut events: Vec<ResponseEvent> = output
+        .items
+        .into_iter()
+        .map(ResponseEvent::OutputItemDone)
+        .collect();
+    events.push(ResponseEvent::Completed {
+        response_id,
+        token_usage: output.usage,
+    });
+    Ok(events)
+}
+
+/// Region for signing: taken from a `bedrock-runtime.<region>.` host when
+/// there is one, otherwise from the AWS environment and config.
+fn region(host: Option<&str>) -> Result<String> {
+    host.and_then(|host| host.strip_prefix("bedrock-runtime."))
+        .and_then(|rest| rest.split('.').next())
+        .filter(|region| !region.is_empty())
+        .map(str::to_string)
+        .or_else(credentials::resolve_region)
+        .ok_or_else(|| {
+            CodexErr::EnvVar(EnvVarError {
+                var: "AWS_REGION".to_string(),
+                instructions: Some(
+                    "Set AWS_REGION or a region in ~/.aws/config to use Amazon Bedrock."
+                        .to_string(),
+                ),
+            })
+        })
+}
+
+/// An input that is too long for the model is reported as a full context
+/// window. Throttling (429) and capacity (503) errors stay HTTP errors so they
+/// are recorded as limit hits and move the `fallback` chain like the OpenAI
+/// backends' do.
+fn classify_error(status: StatusCode, headers: http::HeaderMap, body: String) -> ApiError {
+    match status {
+        StatusCode::BAD_REQUEST if body.to_ascii_lowercase().contains("too long") => {
+            ApiError::ContextWindowExceeded
+        }
+        _ => ApiError::Transport(TransportError::Http {
+            status,
+            headers: Some(headers),
+            body: Some(body),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AuthManager;
+    use crate::auth::AuthCredentialsStoreMode;
+    use crate::auth::FailoverEntry;
+    use crate::client::failover_trigger;
+    use crate::limit_history::read_limit_history;
+    use crate::model_provider_info::WireApi;
+    use crate::model_provider_info::built_in_model_providers;
+    use codex_protocol::protocol::AuthSwitchReason;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn region_comes_from_runtime_host() {
+        assert_eq!(
+            region(Some("bedrock-runtime.eu-central-1.amazonaws.com")).expect("region"),
+            "eu-central-1"
+        );
+    }
+
+    #[test]
+    fn throttling_and_long_inputs_are_classified() {
+        assert!(matches!(
+            classify_error(
+                StatusCode::TOO_MANY_REQUESTS,
+                http::HeaderMap::new(),
+                r#"{"message":"Too many requests"}"#.to_string(),
+            ),
+            ApiError::Transport(TransportError::Http { .. })
+        ));
+        assert!(matches!(
+            classify_error(
+                StatusCode::BAD_REQUEST,
+                http::HeaderMap::new(),
+                r#"{"message":"Input is too long for requested model."}"#.to_string(),
+            ),
+            ApiError::ContextWindowExceeded
+        ));
+        assert!(matches!(
+            classify_error(StatusCode::FORBIDDEN, http::HeaderMap::new(), String::new()),
+            ApiError::Transport(TransportError::Http { .. })
+        ));
+    }
+
+    #[test]
+    fn throttling_advances_the_fallback_chain() {
+        let providers = built_in_model_providers();
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let manager = AuthManager::new(
+            codex_home.path().to_path_buf(),
+            false,
+            AuthCredentialsStoreMode::File,
+        );
+        manager.configure_failover(
+            ["bedrock", "anthropic"]
+                .iter()
+                .map(|name| FailoverEntry::resolve(name, &providers).expect("known entry"))
+                .collect(),
+        );
+
+        let err = map_api_error(classify_error(
+            StatusCode::TOO_MANY_REQUESTS,
+            http::HeaderMap::new(),
+            r#"{"message":"Too many requests"}"#.to_string(),
+        ));
+        let (exhausted_until, reason) = failover_trigger(&err).expect("429 triggers failover");
+        assert_eq!(reason, AuthSwitchReason::RateLimited);
+        assert!(manager.fail_over(None, exhausted_until, reason, "anthropic.claude-sonnet-4"));
+        assert_eq!(
+            manager
+                .failover_provider(None)
+                .map(|provider| provider.wire_api),
+            Some(WireApi::Anthropic)
+        );
+        let history = read_limit_history(codex_home.path(), None).expect("limit history");
+        assert_eq!(
+            history
+                .iter()
+                .map(|hit| hit.auth.as_str())
+                .collect::<Vec<_>>(),
+            vec!["bedrock"]
+        );
+    }
+}
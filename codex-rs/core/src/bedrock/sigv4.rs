@@ -0,0 +1,287 @@
+//! AWS Signature Version 4 for the `bedrock` and `s3` services.
+//!
+//! Only what Codex needs is implemented: Bedrock Runtime `POST` requests with
+//! a JSON body, and S3 object `GET`/`PUT` requests, with no query string and
+//! `content-type`, `host`, and `x-amz-date` (plus `x-amz-content-sha256` for
+//! S3 and `x-amz-security-token` for temporary credentials) as signed
+//! headers.
+
+use chrono::DateTime;
+use chrono::Utc;
+use ring::hmac;
+use sha2::Digest;
+use sha2::Sha256;
+
+use super::credentials::AwsCredentials;
+
+const SERVICE: &str = "bedrock";
+const S3_SERVICE: &str = "s3";
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// Headers to add to the request so AWS accepts it.
+#[derive(Debug, PartialEq)]
+pub(crate) struct SignedHeaders {
+    pub amz_date: String,
+    pub authorization: String,
+    pub security_token: Option<String>,
+}
+
+pub(crate) struct SigningRequest<'a> {
+    pub host: &'a str,
+    /// Path as sent on the wire, i.e. already percent-encoded once.
+    pub path: &'a str,
+    pub content_type: &'a str,
+    pub body: &'a [u8],
+    pub region: &'a str,
+}
+
+/// An S3 object request.
+pub(crate) struct S3SigningRequest<'a> {
+    pub method: &'a str,
+    pub host: &'a str,
+    /// Path as sent on the wire, i.e. already percent-encoded once.
+    pub path: &'a str,
+    pub content_type: &'a str,
+    pub body: &'a [u8],
+    pub region: &'a str,
+}
+
+pub(crate) fn sign(
+    request: &SigningRequest<'_>,
+    credentials: &AwsCredentials,
+    now: DateTime<Utc>,
+) -> SignedHeaders {
+    // Services other than S3 expect every path segment encoded a second time.
+    let canonical_uri = request
+        .path
+        .split('/')
+        .map(uri_encode)
+        .collect::<Vec<_>>()
+        .join("/");
+    sign_canonical(
+        CanonicalRequest {
+            method: "POST",
+            service: SERVICE,
+            canonical_uri: &canonical_uri,
+            headers: vec![
+                ("content-type", request.content_type.trim().to_string()),
+                ("host", request.host.to_string()),
+            ],
+            payload_hash: &payload_hash(request.body),
+            region: request.region,
+        },
+        credentials,
+        now,
+    )
+}
+
+/// Signs an S3 request. The caller must also send `x-amz-content-sha256`
+/// set to [`payload_hash`] of the body.
+pub(crate) fn sign_s3(
+    request: &S3SigningRequest<'_>,
+    credentials: &AwsCredentials,
+    now: DateTime<Utc>,
+) -> SignedHeaders {
+    let payload_hash = payload_hash(request.body);
+    sign_canonical(
+        CanonicalRequest {
+            method: request.method,
+            service: S3_SERVICE,
+            canonical_uri: request.path,
+            headers: vec![
+                ("content-type", request.content_type.trim().to_string()),
+                ("host", request.host.to_string()),
+                ("x-amz-content-sha256", payload_hash.clone()),
+            ],
+            payload_hash: &payload_hash,
+            region: request.region,
+        },
+        credentials,
+        now,
+    )
+}
+
+/// Hex SHA-256 of a request body.
+pub(crate) fn payload_hash(body: &[u8]) -> String {
+    hex(&Sha256::digest(body))
+}
+
+struct CanonicalRequest<'a> {
+    method: &'a str,
+    service: &'a str,
+    canonical_uri: &'a str,
+    /// Signed headers other than `x-amz-date` and `x-amz-security-token`,
+    /// sorted by name.
+    headers: Vec<(&'static str, String)>,
+    payload_hash: &'a str,
+    region: &'a str,
+}
+
+fn sign_canonical(
+    request: CanonicalRequest<'_>,
+    credentials: &AwsCredentials,
+    now: DateTime<Utc>,
+) -> SignedHeaders {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = &amz_date[..8];
+
+    let mut headers = request.headers;
+    headers.push(("x-amz-date", amz_date.clone()));
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{value}\n"))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let canonical_request = format!(
+        "{}\n{}\n\n{canonical_headers}\n{signed_headers}\n{}",
+        request.method, request.canonical_uri, request.payload_hash
+    );
+
+    let scope = format!("{date}/{}/{}/aws4_request", request.region, request.service);
+    let string_to_sign = format!(
+        "{ALGORITHM}\n{amz_date}\n{scope}\n{}",
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let secret = format!("AWS4{}", credentials.secret_access_key);
+    let k_date = hmac_sha256(secret.as_bytes(), date.as_bytes());
+    let k_region = hmac_sha256(k_date.as_ref(), request.region.as_bytes());
+    let k_service = hmac_sha256(k_region.as_ref(), request.service.as_bytes());
+    let k_signing = hmac_sha256(k_service.as_ref(), b"aws4_request");
+    let signature = hex(hmac_sha256(k_signing.as_ref(), string_to_sign.as_bytes()).as_ref());
+
+    SignedHeaders {
+        authorization: format!(
+            "{ALGORITHM} Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            credentials.access_key_id
+        ),
+        amz_date,
+        security_token: credentials.session_token.clone(),
+    }
+}
+
+/// RFC 3986 encoding with AWS's unreserved set (`A-Z a-z 0-9 - _ . ~`).
+pub(crate) fn uri_encode(segment: &str) -> String {
+    let mut out = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> hmac::Tag {
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// `get-vanilla` from the AWS Signature Version 4 test suite.
+    #[test]
+    fn matches_the_aws_get_vanilla_test_vector() {
+        let credentials = AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        };
+        let request = CanonicalRequest {
+            method: "GET",
+            service: "service",
+            canonical_uri: "/",
+            headers: vec![("host", "example.amazonaws.com".to_string())],
+            payload_hash: &payload_hash(b""),
+            region: "us-east-1",
+        };
+        let now = "2015-08-30T12:36:00Z"
+            .parse::<DateTime<Utc>>()
+            .expect("timestamp");
+
+        assert_eq!(
+            sign_canonical(request, &credentials, now),
+            SignedHeaders {
+                amz_date: "20150830T123600Z".to_string(),
+                authorization: "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, SignedHeaders=host;x-amz-date, Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31".to_string(),
+                security_token: None,
+            }
+        );
+    }
+
+    #[test]
+    fn signs_converse_request() {
+        let credentials = AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        };
+        let path = format!(
+            "/model/{}/converse",
+            uri_encode("anthropic.claude-sonnet-4-5-20250929-v1:0")
+        );
+        assert_eq!(
+            path,
+            "/model/anthropic.claude-sonnet-4-5-20250929-v1%3A0/converse"
+        );
+        let request = SigningRequest {
+            host: "bedrock-runtime.us-east-1.amazonaws.com",
+            path: &path,
+            content_type: "application/json",
+            body: br#"{"messages":[]}"#,
+            region: "us-east-1",
+        };
+        let now = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).expect("timestamp");
+
+        assert_eq!(
+            sign(&request, &credentials, now),
+            SignedHeaders {
+                amz_date: "20231114T221320Z".to_string(),
+                authorization: "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20231114/us-east-1/bedrock/aws4_request, SignedHeaders=content-type;host;x-amz-date, Signature=d0ab8cd0e2925f1af897953f52411e705e93353098d644ed24d8e09805d6e9fc".to_string(),
+                security_token: None,
+            }
+        );
+    }
+
+    #[test]
+    fn signs_s3_requests_with_the_payload_hash() {
+        let credentials = AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        };
+        let request = S3SigningRequest {
+            method: "GET",
+            host: "s3.us-east-1.amazonaws.com",
+            path: "/ci-cache/codex-review/abc.json",
+            content_type: "application/json",
+            body: b"",
+            region: "us-east-1",
+        };
+        let now = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).expect("timestamp");
+
+        let signed = sign_s3(&request, &credentials, now);
+        assert!(signed.authorization.starts_with(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20231114/us-east-1/s3/aws4_request, SignedHeaders=content-type;host;x-amz-content-sha256;x-amz-date, Signature="
+        ));
+        assert_eq!(
+            payload_hash(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+}
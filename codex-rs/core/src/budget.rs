@@ -0,0 +1,302 @@
+//! Spending caps on estimated cost, from `[budget]` in `config.toml`.
+//!
+//! The daily and monthly caps count every session on the machine through the
+//! usage history (see [`crate::usage_history`]). Before each model request
+//! the session's, today's, and this month's spend are compared with the caps.
+//! Past `warn_at_percent` of a cap the user is warned once per session; past
+//! the cap the task stops, or the session switches to `downgrade_model` and
+//! carries on.
+
+use std::collections::HashSet;
+use std::io::Result;
+use std::path::Path;
+
+use chrono::DateTime;
+use chrono::Datelike;
+use chrono::TimeZone;
+
+use crate::config::Config;
+use crate::config::types::Budget;
+use crate::config::types::BudgetAction;
+use crate::usage_history::UsageEntry;
+use crate::usage_history::read_usage_history;
+
+const DEFAULT_WARN_AT_PERCENT: u8 = 80;
+
+/// Spend recorded since the start of `now`'s day and since the start of its
+/// month, in `now`'s time zone, including what other machines shared through
+/// `shared_dir`.
+pub(crate) fn recorded_spend<Tz: TimeZone>(
+    codex_home: &Path,
+    shared_dir: Option<&Path>,
+    now: &DateTime<Tz>,
+) -> Result<(f64, f64)> {
+    let entries = read_usage_history(codex_home, shared_dir)?;
+    Ok(spend_by_period(&entries, now))
+}
+
+fn spend_by_period<Tz: TimeZone>(entries: &[UsageEntry], now: &DateTime<Tz>) -> (f64, f64) {
+    let tz = now.timezone();
+    let start_of = |date: chrono::NaiveDate| {
+        date.and_hms_opt(0, 0, 0)
+            .and_then(|midnight| tz.from_local_datetime(&midnight).earliest())
+            .map(|start| start.timestamp())
+            .unwrap_or(i64::MIN)
+    };
+    let today = now.date_naive();
+    let day_start = start_of(today);
+    let month_start = today.with_day(1).map(start_of).unwrap_or(i64::MIN);
+
+    let mut day_usd = 0.0;
+    let mut month_usd = 0.0;
+    for entry in entries {
+        let usd = entry.usd.unwrap_or(0.0);
+        if entry.ts >= month_start {
+            month_usd += usd;
+        }
+        if entry.ts >= day_start {
+            day_usd += usd;
+        }
+    }
+    (day_usd, month_usd)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum BudgetPeriod {
+    Session,
+    Day,
+    Month,
+}
+
+impl BudgetPeriod {
+    fn label(self) -> &'static str {
+        match self {
+            BudgetPeriod::Session => "session",
+            BudgetPeriod::Day => "daily",
+            BudgetPeriod::Month => "monthly",
+        }
+    }
+
+    fn config_key(self) -> &'static str {
+        match self {
+            BudgetPeriod::Session => "budget.session_usd",
+            BudgetPeriod::Day => "budget.daily_usd",
+            BudgetPeriod::Month => "budget.monthly_usd",
+        }
+    }
+}
+
+/// Estimated spend to compare with the caps.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct Spend {
+    pub(crate) session_usd: f64,
+    pub(crate) day_usd: f64,
+    pub(crate) month_usd: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BudgetStatus {
+    /// Spend passed `warn_at_percent` of a cap for the first time this
+    /// session.
+    Approaching {
+        period: BudgetPeriod,
+        cap_usd: f64,
+        spent_usd: f64,
+    },
+    Exceeded {
+        period: BudgetPeriod,
+        cap_usd: f64,
+        spent_usd: f64,
+    },
+}
+
+impl BudgetStatus {
+    /// Message surfaced to the user.
+    pub(crate) fn message(&self) -> String {
+        match *self {
+            BudgetStatus::Approaching {
+                period,
+                cap_usd,
+                spent_usd,
+            } => format!(
+                "Estimated spend is ${spent_usd:.2} of the ${cap_usd:.2} {} budget.",
+                period.label()
+            ),
+            BudgetStatus::Exceeded {
+                period,
+                cap_usd,
+                spent_usd,
+            } => format!(
+                "The ${cap_usd:.2} {} budget is used up (estimated spend ${spent_usd:.2}). Raise `{}` in config.toml to continue.",
+                period.label(),
+                period.config_key()
+            ),
+        }
+    }
+}
+
+/// Whether configured caps are in effect at all.
+pub(crate) fn has_caps(budget: &Budget) -> bool {
+    budget.session_usd.is_some() || has_recorded_caps(budget)
+}
+
+/// Whether a cap needs the spend history of other sessions.
+pub(crate) fn has_recorded_caps(budget: &Budget) -> bool {
+    budget.daily_usd.is_some() || budget.monthly_usd.is_some()
+}
+
+/// Why no new run should start: today's or this month's recorded spend has
+/// used up its cap and `on_exceeded` stops tasks. `codex queue run` checks
+/// this before each task.
+pub fn recorded_cap_reached(config: &Config) -> Option<String> {
+    let budget = &config.budget;
+    if !has_recorded_caps(budget) || budget.on_exceeded != BudgetAction::Stop {
+        return None;
+    }
+    let (day_usd, month_usd) = recorded_spend(
+        &crate::state_dir::state_dir(&config.codex_home),
+        config.shared_state_dir.as_deref(),
+        &chrono::Local::now(),
+    )
+    .inspect_err(|err| tracing::warn!("failed to read usage history: {err}"))
+    .ok()?;
+    let spend = Spend {
+        session_usd: 0.0,
+        day_usd,
+        month_usd,
+    };
+    match check_budget(budget, &spend, &mut HashSet::new()) {
+        Some(status @ BudgetStatus::Exceeded { .. }) => Some(status.message()),
+        _ => None,
+    }
+}
+
+/// Compares `spend` with the caps in `budget`. An exceeded cap is reported
+/// every time; a cap being approached only once per period in `warned`.
+pub(crate) fn check_budget(
+    budget: &Budget,
+    spend: &Spend,
+    warned: &mut HashSet<BudgetPeriod>,
+) -> Option<BudgetStatus> {
+    let caps = [
+        (BudgetPeriod::Session, budget.session_usd, spend.session_usd),
+        (BudgetPeriod::Day, budget.daily_usd, spend.day_usd),
+        (BudgetPeriod::Month, budget.monthly_usd, spend.month_usd),
+    ];
+    let caps = caps
+        .into_iter()
+        .filter_map(|(period, cap, spent)| cap.map(|cap| (period, cap, spent)));
+
+    let warn_at = f64::from(budget.warn_at_percent.unwrap_or(DEFAULT_WARN_AT_PERCENT)) / 100.0;
+    let mut approaching = None;
+    for (period, cap_usd, spent_usd) in caps {
+        if spent_usd >= cap_usd {
+            return Some(BudgetStatus::Exceeded {
+                period,
+                cap_usd,
+                spent_usd,
+            });
+        }
+        if approaching.is_none() && spent_usd >= cap_usd * warn_at && !warned.contains(&period) {
+            approaching = Some(BudgetStatus::Approaching {
+                period,
+                cap_usd,
+                spent_usd,
+            });
+        }
+    }
+    if let Some(BudgetStatus::Approaching { period, .. }) = approaching {
+        warned.insert(period);
+    }
+    approaching
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+    use pretty_assertions::assert_eq;
+
+    fn entry(ts: i64, usd: f64) -> UsageEntry {
+        UsageEntry {
+            ts,
+            model: "gpt-5.1-codex".to_string(),
+            usd: Some(usd),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn spend_is_split_at_local_day_and_month_boundaries() {
+        let tz = FixedOffset::east_opt(2 * 3600).expect("offset");
+        // 2024-03-15 10:00 local.
+        let now = tz
+            .with_ymd_and_hms(2024, 3, 15, 10, 0, 0)
+            .single()
+            .expect("time");
+        let day_start = tz
+            .with_ymd_and_hms(2024, 3, 15, 0, 0, 0)
+            .single()
+            .expect("time")
+            .timestamp();
+        let month_start = tz
+            .with_ymd_and_hms(2024, 3, 1, 0, 0, 0)
+            .single()
+            .expect("time")
+            .timestamp();
+        let entries = vec![
+            entry(month_start - 1, 100.0),
+            entry(month_start, 4.0),
+            entry(day_start - 1, 2.0),
+            entry(day_start, 1.0),
+            entry(now.timestamp(), 0.5),
+        ];
+        assert_eq!(spend_by_period(&entries, &now), (1.5, 7.5));
+    }
+
+    #[test]
+    fn approaching_warns_once_and_exceeding_is_always_reported() {
+        let budget = Budget {
+            session_usd: Some(10.0),
+            daily_usd: Some(20.0),
+            ..Default::default()
+        };
+        let mut warned = HashSet::new();
+        let spend = |session_usd, day_usd| Spend {
+            session_usd,
+            day_usd,
+            month_usd: 0.0,
+        };
+
+        assert_eq!(check_budget(&budget, &spend(1.0, 1.0), &mut warned), None);
+        assert_eq!(
+            check_budget(&budget, &spend(8.5, 9.0), &mut warned),
+            Some(BudgetStatus::Approaching {
+                period: BudgetPeriod::Session,
+                cap_usd: 10.0,
+                spent_usd: 8.5,
+            })
+        );
+        assert_eq!(check_budget(&budget, &spend(9.0, 9.5), &mut warned), None);
+        let exceeded = check_budget(&budget, &spend(9.0, 21.0), &mut warned);
+        assert_eq!(
+            exceeded,
+            Some(BudgetStatus::Exceeded {
+                period: BudgetPeriod::Day,
+                cap_usd: 20.0,
+                spent_usd: 21.0,
+            })
+        );
+        assert_eq!(
+            exceeded.map(|status| status.message()),
+            Some(
+                "The $20.00 daily budget is used up (estimated spend $21.00). Raise `budget.daily_usd` in config.toml to continue."
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            check_budget(&budget, &spend(9.0, 21.0), &mut warned),
+            exceeded
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Named checkpoints within a session, created with `/checkpoint` and
+//! rolled back to with `codex restore <name>`. Each is stored as
+//! `~/.codex/checkpoints/<name>.json` and records how many turns of the
+//! session it covers plus a ghost commit of the working tree, pinned under
+//! `refs/codex/checkpoints/<name>` so `git gc` does not prune it.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_git::GhostCommit;
+use codex_git::restore_ghost_commit;
+use codex_protocol::ConversationId;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Directory under `CODEX_HOME` holding one file per checkpoint.
+const CHECKPOINTS_SUBDIR: &str = "checkpoints";
+
+const MAX_NAME_LEN: usize = 64;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    pub name: String,
+    pub conversation_id: ConversationId,
+    pub rollout_path: PathBuf,
+    /// User turns of the session recorded when the checkpoint was taken.
+    pub turns: usize,
+    pub cwd: PathBuf,
+    /// Snapshot of the working tree; `None` outside a git repository.
+    #[serde(default)]
+    pub ghost_commit: Option<GhostCommit>,
+    /// When the checkpoint was taken (Unix seconds).
+    pub created_at: i64,
+}
+
+/// Names double as file names and git ref components, so they are limited
+/// to letters, digits, `-`, `_`, and `.` (not leading).
+pub fn validate_checkpoint_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("checkpoint name cannot be empty".to_string());
+    }
+    if name.len() > MAX_NAME_LEN {
+        return Err(format!(
+            "checkpoint name cannot be longer than {MAX_NAME_LEN} characters"
+        ));
+    }
+    let valid = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && !name.starts_with('.')
+        && !name.contains("..")
+        && !name.ends_with(".lock");
+    if !valid {
+        return Err(format!(
+            "invalid checkpoint name `{name}`: use letters, digits, `-`, `_`, and `.`"
+        ));
+    }
+    Ok(())
+}
+
+/// The git ref pinning the snapshot of checkpoint `name`.
+pub fn checkpoint_ref(name: &str) -> String {
+    format!("refs/codex/checkpoints/{name}")
+}
+
+fn checkpoint_path(codex_home: &Path, name: &str) -> PathBuf {
+    codex_home
+        .join(CHECKPOINTS_SUBDIR)
+        .join(format!("{name}.json"))
+}
+
+/// Stores `checkpoint`, replacing any earlier checkpoint with its name.
+pub fn save_checkpoint(codex_home: &Path, checkpoint: &Checkpoint) -> io::Result<()> {
+    let path = checkpoint_path(codex_home, &checkpoint.name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(checkpoint).map_err(io::Error::other)?;
+    std::fs::write(path, json)
+}
+
+/// The checkpoint called `name`, or `None` when there is none.
+pub fn load_checkpoint(codex_home: &Path, name: &str) -> io::Result<Option<Checkpoint>> {
+    validate_checkpoint_name(name)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    match std::fs::read_to_string(checkpoint_path(codex_home, name)) {
+        Ok(json) => serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// All checkpoints, oldest first. Unreadable files are skipped.
+pub fn list_checkpoints(codex_home: &Path) -> Vec<Checkpoint> {
+    let Ok(entries) = std::fs::read_dir(codex_home.join(CHECKPOINTS_SUBDIR)) else {
+        return Vec::new();
+    };
+    let mut checkpoints: Vec<Checkpoint> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .filter_map(|json| serde_json::from_str(&json).ok())
+        .collect();
+    checkpoints.sort_by(|a, b| (a.created_at, &a.name).cmp(&(b.created_at, &b.name)));
+    checkpoints
+}
+
+/// Rolls the working tree back to the checkpoint's snapshot. Returns
+/// `false` when the checkpoint has none.
+pub fn restore_checkpoint_files(checkpoint: &Checkpoint) -> io::Result<bool> {
+    let Some(ghost_commit) = &checkpoint.ghost_commit else {
+        return Ok(false);
+    };
+    restore_ghost_commit(&checkpoint.cwd, ghost_commit).map_err(io::Error::other)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn checkpoints_are_saved_and_listed_by_name() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let codex_home = tmp.path();
+        assert_eq!(load_checkpoint(codex_home, "missing").expect("load"), None);
+
+        let checkpoint = |name: &str, created_at| Checkpoint {
+            name: name.to_string(),
+            conversation_id: ConversationId::default(),
+            rollout_path: PathBuf::from("/sessions/rollout.jsonl"),
+            turns: 3,
+            cwd: PathBuf::from("/src/app"),
+            ghost_commit: Some(GhostCommit::new(
+                "abc123".to_string(),
+                None,
+                Vec::new(),
+                Vec::new(),
+            )),
+            created_at,
+        };
+        let later = checkpoint("after-tests", 20);
+        let earlier = checkpoint("before-refactor", 10);
+        save_checkpoint(codex_home, &later).expect("save");
+        save_checkpoint(codex_home, &earlier).expect("save");
+
+        assert_eq!(
+            load_checkpoint(codex_home, "before-refactor").expect("load"),
+            Some(earlier.clone())
+        );
+        assert_eq!(list_checkpoints(codex_home), vec![earlier, later]);
+    }
+
+    #[test]
+    fn checkpoint_names_must_be_safe_paths_and_refs() {
+        assert_eq!(validate_checkpoint_name("v1.2_before-merge"), Ok(()));
+        for name in ["", "../escape", ".hidden", "a b", "a..b", "main.lock"] {
+            assert!(validate_checkpoint_name(name).is_err(), "{name}");
+        }
+    }
+}
@@ -7,6 +7,7 @@ use codex_api::ChatClient as ApiChatClient;
 use codex_api::CompactClient as ApiCompactClient;
 use codex_api::CompactionInput as ApiCompactionInput;
 use codex_api::Prompt as ApiPrompt;
+use codex_api::Provider as ApiProvider;
 use codex_api::RequestTelemetry;
 use codex_api::ReqwestTransport;
 use codex_api::ResponseStream as ApiResponseStream;
@@ -17,13 +18,19 @@ use codex_api::TransportError;
 use codex_api::common::Reasoning;
 use codex_api::create_text_param_for_request;
 use codex_api::error::ApiError;
+use codex_api::provider::RetryConfig as ApiRetryConfig;
 use codex_app_server_protocol::AuthMode;
 use codex_otel::otel_event_manager::OtelEventManager;
 use codex_protocol::ConversationId;
 use codex_protocol::config_types::ReasoningSummary as ReasoningSummaryConfig;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::openai_models::ReasoningEffort as ReasoningEffortConfig;
+use codex_protocol::protocol::AuthSwitchReason;
+use codex_protocol::protocol::CodexErrorInfo;
+use codex_protocol::protocol::Event as ProtocolEvent;
+use codex_protocol::protocol::EventMsg;
 use codex_protocol::protocol::SessionSource;
+use codex_protocol::protocol::StreamErrorEvent;
 use eventsource_stream::Event;
 use eventsource_stream::EventStreamError;
 use futures::StreamExt;
@@ -38,12 +45,14 @@ use tracing::warn;
 
 use crate::AuthManager;
 use crate::auth::RefreshTokenError;
+use crate::auth::validate_api_key;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::client_common::ResponseStream;
 use crate::config::Config;
-use crate::default_client::build_reqwest_client;
+use crate::config::types::LimitCapAction;
 use crate::error::CodexErr;
+use crate::error::RequestTooLargeError;
 use crate::error::Result;
 use crate::flags::CODEX_RS_SSE_FIXTURE;
 use crate::model_family::ModelFamily;
@@ -52,6 +61,7 @@ use crate::model_provider_info::WireApi;
 use crate::openai_model_info::get_model_info;
 use crate::tools::spec::create_tools_json_for_chat_completions_api;
 use crate::tools::spec::create_tools_json_for_responses_api;
+use crate::util::backoff_from;
 
 #[derive(Debug, Clone)]
 pub struct ModelClient {
@@ -63,6 +73,15 @@ pub struct ModelClient {
     effort: Option<ReasoningEffortConfig>,
     summary: ReasoningSummaryConfig,
     session_source: SessionSource,
+    retry_notices: Option<RetryNotices>,
+}
+
+/// Where the client reports each retry of a failed request, so a turn that
+/// is slow to start shows why.
+#[derive(Debug, Clone)]
+struct RetryNotices {
+    tx_event: async_channel::Sender<ProtocolEvent>,
+    sub_id: String,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -86,9 +105,21 @@ impl ModelClient {
             effort,
             summary,
             session_source,
+            retry_notices: None,
         }
     }
 
+    /// Reports each retry of a failed request as a `StreamError` event of
+    /// the turn `sub_id`.
+    pub(crate) fn with_retry_notices(
+        mut self,
+        tx_event: async_channel::Sender<ProtocolEvent>,
+        sub_id: String,
+    ) -> Self {
+        self.retry_notices = Some(RetryNotices { tx_event, sub_id });
+        self
+    }
+
     pub fn get_model_context_window(&self) -> Option<i64> {
         let pct = self.config.model_family.effective_context_window_percent;
         self.config
@@ -111,12 +142,58 @@ impl ModelClient {
         &self.provider
     }
 
+    /// Number of times a turn whose response stream drops is sent again.
+    pub fn stream_max_retries(&self) -> u64 {
+        self.provider.stream_retries(&self.config.retry)
+    }
+
+    /// Delay before sending a turn again after its stream dropped for the
+    /// `attempt`th time.
+    pub(crate) fn stream_retry_delay(&self, attempt: u64) -> Duration {
+        backoff_from(self.config.retry.base_delay(), attempt)
+    }
+
     /// Streams a single model turn using either the Responses or Chat
     /// Completions wire API, depending on the configured provider.
     ///
     /// For Chat providers, the underlying stream is optionally aggregated
     /// based on the `show_raw_agent_reasoning` flag in the config.
+    ///
+    /// With a `fallback` chain configured, the turn goes to the chain's
+    /// active provider and moves down the chain when that provider is
+    /// limited or failing.
     pub async fn stream(&self, prompt: &Prompt) -> Result<ResponseStream> {
+        loop {
+            let failover_provider = self
+                .auth_manager
+                .as_ref()
+                .and_then(|manager| manager.failover_provider(Some(self.conversation_id)));
+            let result = match failover_provider {
+                Some(provider) if provider != self.provider => {
+                    let client = Self {
+                        provider,
+                        ..self.clone()
+                    };
+                    client.stream_with_provider(prompt).await
+                }
+                _ => self.stream_with_provider(prompt).await,
+            };
+            match result {
+                Err(err) if self.fail_over_on_error(&err) => {
+                    let provider = self
+                        .auth_manager
+                        .as_ref()
+                        .and_then(|manager| manager.failover_provider(Some(self.conversation_id)))
+                        .unwrap_or_else(|| self.provider.clone());
+                    self.ensure_api_key_accepted(&provider).await?;
+                    continue;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn stream_with_provider(&self, prompt: &Prompt) -> Result<ResponseStream> {
         match self.provider.wire_api {
             WireApi::Responses => self.stream_responses_api(prompt).await,
             WireApi::Chat => {
@@ -134,9 +211,101 @@ impl ModelClient {
                     ))
                 }
             }
+            WireApi::Bedrock => self.stream_bedrock(prompt).await,
+            WireApi::Gemini => self.stream_gemini(prompt).await,
+            WireApi::Anthropic => self.stream_anthropic(prompt).await,
         }
     }
 
+    /// Streams a turn via the Anthropic Messages API; `output_schema` is not
+    /// supported.
+    async fn stream_anthropic(&self, prompt: &Prompt) -> Result<ResponseStream> {
+        if prompt.output_schema.is_some() {
+            return Err(CodexErr::UnsupportedOperation(
+                "output_schema is not supported for Anthropic".to_string(),
+            ));
+        }
+
+        let instructions = prompt
+            .get_full_instructions(&self.config.model_family)
+            .into_owned();
+        let tools_json = create_tools_json_for_responses_api(&prompt.tools)?;
+        let api_prompt = build_api_prompt(prompt, instructions, tools_json);
+        self.ensure_request_fits(&api_prompt)?;
+        let model = self.provider.request_model_name(&self.config.model);
+
+        let stream = crate::anthropic::stream_messages(
+            &self.provider,
+            &model,
+            &api_prompt.instructions,
+            &api_prompt.input,
+            &api_prompt.tools,
+        )
+        .await?;
+        Ok(map_response_stream(stream, self.otel_event_manager.clone()))
+    }
+
+    /// Streams a turn via Gemini's `streamGenerateContent`, on either the
+    /// Gemini API or Vertex AI; `output_schema` is not supported.
+    async fn stream_gemini(&self, prompt: &Prompt) -> Result<ResponseStream> {
+        if prompt.output_schema.is_some() {
+            return Err(CodexErr::UnsupportedOperation(
+                "output_schema is not supported for Gemini".to_string(),
+            ));
+        }
+
+        let instructions = prompt
+            .get_full_instructions(&self.config.model_family)
+            .into_owned();
+        let tools_json = create_tools_json_for_responses_api(&prompt.tools)?;
+        let api_prompt = build_api_prompt(prompt, instructions, tools_json);
+        self.ensure_request_fits(&api_prompt)?;
+        let model = self.provider.request_model_name(&self.config.model);
+
+        let stream = crate::gemini::stream_generate_content(
+            &self.provider,
+            &model,
+            &api_prompt.instructions,
+            &api_prompt.input,
+            &api_prompt.tools,
+        )
+        .await?;
+        Ok(map_response_stream(stream, self.otel_event_manager.clone()))
+    }
+
+    /// Runs a turn via the Amazon Bedrock Converse API.
+    ///
+    /// Converse is called without streaming, so the whole response arrives
+    /// as one batch of events; `output_schema` is not supported.
+    async fn stream_bedrock(&self, prompt: &Prompt) -> Result<ResponseStream> {
+        if prompt.output_schema.is_some() {
+            return Err(CodexErr::UnsupportedOperation(
+                "output_schema is not supported for Amazon Bedrock".to_string(),
+            ));
+        }
+
+        let instructions = prompt
+            .get_full_instructions(&self.config.model_family)
+            .into_owned();
+        let tools_json = create_tools_json_for_responses_api(&prompt.tools)?;
+        let api_prompt = build_api_prompt(prompt, instructions, tools_json);
+        self.ensure_request_fits(&api_prompt)?;
+        let model = self.provider.request_model_name(&self.config.model);
+
+        let events = crate::bedrock::converse(
+            &self.provider,
+            &model,
+            &api_prompt.instructions,
+            &api_prompt.input,
+            &api_prompt.tools,
+        )
+        .await?;
+        Ok(map_response_stream(
+            futures::stream::iter(events.into_iter().map(Ok)),
+            self.otel_event_manager.clone(),
+        ))
+    }
+
     /// Streams a turn via the OpenAI Chat Completions API.
     ///
     /// This path is only used when the provider is configured with
@@ -154,24 +323,25 @@ impl ModelClient {
             .into_owned();
         let tools_json = create_tools_json_for_chat_completions_api(&prompt.tools)?;
         let api_prompt = build_api_prompt(prompt, instructions, tools_json);
+        self.ensure_request_fits(&api_prompt)?;
         let conversation_id = self.conversation_id.to_string();
         let session_source = self.session_source.clone();
+        let model = self.provider.request_model_name(&self.config.model);
 
         let mut refreshed = false;
         loop {
             let auth = auth_manager.as_ref().and_then(|m| m.auth());
-            let api_provider = self
-                .provider
-                .to_api_provider(auth.as_ref().map(|a| a.mode))?;
+            let api_provider = self.api_provider(auth.as_ref().map(|a| a.mode))?;
             let api_auth = auth_provider_from_auth(auth.clone(), &self.provider).await?;
-            let transport = ReqwestTransport::new(build_reqwest_client());
-            let (request_telemetry, sse_telemetry) = self.build_streaming_telemetry();
+            let transport = ReqwestTransport::new(self.provider.build_reqwest_client()?);
+            let (request_telemetry, sse_telemetry) =
+                self.build_streaming_telemetry(&api_provider.retry);
             let client = ApiChatClient::new(transport, api_provider, api_auth)
                 .with_telemetry(Some(request_telemetry), Some(sse_telemetry));
 
             let stream_result = client
                 .stream_prompt(
-                    &self.config.model,
+                    &model,
                     &api_prompt,
                     Some(conversation_id.clone()),
                     Some(session_source.clone()),
@@ -186,7 +356,21 @@ impl ModelClient {
                     handle_unauthorized(status, &mut refreshed, &auth_manager, &auth).await?;
                     continue;
                 }
-                Err(err) => return Err(map_api_error(err)),
+                Err(err) => {
+                    let err = map_api_error(err);
+                    if self.switches_auth_on(&err)
+                        && rotate_credentials_on_limit(
+                            &err,
+                            self.conversation_id,
+                            &auth_manager,
+                            &auth,
+                        )
+                    {
+                        self.ensure_api_key_accepted(&self.provider).await?;
+                        continue;
+                    }
+                    return Err(err);
+                }
             }
         }
     }
@@ -242,18 +426,19 @@ impl ModelClient {
 
         let text = create_text_param_for_request(verbosity, &prompt.output_schema);
         let api_prompt = build_api_prompt(prompt, instructions.clone(), tools_json);
+        self.ensure_request_fits(&api_prompt)?;
         let conversation_id = self.conversation_id.to_string();
         let session_source = self.session_source.clone();
+        let model = self.provider.request_model_name(&self.config.model);
 
         let mut refreshed = false;
         loop {
             let auth = auth_manager.as_ref().and_then(|m| m.auth());
-            let api_provider = self
-                .provider
-                .to_api_provider(auth.as_ref().map(|a| a.mode))?;
+            let api_provider = self.api_provider(auth.as_ref().map(|a| a.mode))?;
             let api_auth = auth_provider_from_auth(auth.clone(), &self.provider).await?;
-            let transport = ReqwestTransport::new(build_reqwest_client());
-            let (request_telemetry, sse_telemetry) = self.build_streaming_telemetry();
+            let transport = ReqwestTransport::new(self.provider.build_reqwest_client()?);
+            let (request_telemetry, sse_telemetry) =
+                self.build_streaming_telemetry(&api_provider.retry);
             let client = ApiResponsesClient::new(transport, api_provider, api_auth)
                 .with_telemetry(Some(request_telemetry), Some(sse_telemetry));
 
@@ -267,9 +452,7 @@ impl ModelClient {
                 session_source: Some(session_source.clone()),
             };
 
-            let stream_result = client
-                .stream_prompt(&self.config.model, &api_prompt, options)
-                .await;
+            let stream_result = client.stream_prompt(&model, &api_prompt, options).await;
 
             match stream_result {
                 Ok(stream) => {
@@ -281,11 +464,114 @@ impl ModelClient {
                     handle_unauthorized(status, &mut refreshed, &auth_manager, &auth).await?;
                     continue;
                 }
-                Err(err) => return Err(map_api_error(err)),
+                Err(err) => {
+                    let err = map_api_error(err);
+                    if self.switches_auth_on(&err)
+                        && rotate_credentials_on_limit(
+                            &err,
+                            self.conversation_id,
+                            &auth_manager,
+                            &auth,
+                        )
+                    {
+                        self.ensure_api_key_accepted(&self.provider).await?;
+                        continue;
+                    }
+                    return Err(err);
+                }
             }
         }
     }
 
+    /// Moves the `fallback` chain past the active provider when `err` means
+    /// it is limited or failing. Returns `true` when the caller should retry.
+    fn fail_over_on_error(&self, err: &CodexErr) -> bool {
+        let Some(manager) = self.auth_manager.as_ref() else {
+            return false;
+        };
+        if !self.switches_auth_on(err) {
+            return false;
+        }
+        let Some((exhausted_until, reason)) = failover_trigger(err) else {
+            return false;
+        };
+        manager.fail_over(
+            Some(self.conversation_id),
+            exhausted_until,
+            reason,
+            &self.config.model,
+        )
+    }
+
+    /// Whether `err` should move requests to other credentials. Under
+    /// `limits.on_cap = "downgrade_model"`, usage limits keep the credentials
+    /// until the session has moved to `limits.downgrade_model`.
+    fn switches_auth_on(&self, err: &CodexErr) -> bool {
+        let limits = &self.config.limits;
+        !(matches!(err, CodexErr::UsageLimitReached(_))
+            && limits.on_cap == LimitCapAction::DowngradeModel
+            && limits
+                .downgrade_model
+                .as_ref()
+                .is_some_and(|model| *model != self.config.model))
+    }
+
+    /// After a switch to an API key, checks that `provider` accepts it,
+    /// moving on to the next key or `fallback` entry when it is rejected, so
+    /// a revoked key is reported by name rather than failing the turn. Fails
+    /// when nothing to switch to is left; keys that cannot be checked are
+    /// kept.
+    async fn ensure_api_key_accepted(&self, provider: &ModelProviderInfo) -> Result<()> {
+        let Some(manager) = self.auth_manager.as_ref() else {
+            return Ok(());
+        };
+        if !provider.requires_openai_auth {
+            return Ok(());
+        }
+        loop {
+            let Some(api_key) = manager
+                .auth()
+                .filter(|auth| auth.mode == AuthMode::ApiKey)
+                .and_then(|auth| auth.api_key)
+            else {
+                return Ok(());
+            };
+            match validate_api_key(provider, &api_key).await {
+                Ok(()) => return Ok(()),
+                Err(err) if err.is_rejected() => {
+                    warn!("{err}");
+                    let reason = AuthSwitchReason::CredentialsRejected;
+                    let conversation_id = Some(self.conversation_id);
+                    if !manager.rotate_api_key(conversation_id, None, reason)
+                        && !manager.fail_over(conversation_id, None, reason, &self.config.model)
+                    {
+                        return Err(CodexErr::Fatal(err.to_string()));
+                    }
+                }
+                Err(err) => {
+                    warn!("{err}");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Rejects the prompt before it is sent when the provider caps request
+    /// bodies below its serialized size, so a gateway 413 never costs a retry.
+    fn ensure_request_fits(&self, api_prompt: &ApiPrompt) -> Result<()> {
+        let Some(limit) = self.provider.max_request_body_bytes else {
+            return Ok(());
+        };
+        let size = estimated_request_bytes(api_prompt);
+        if size > limit {
+            return Err(CodexErr::RequestTooLarge(RequestTooLargeError {
+                size: Some(size),
+                limit: Some(limit),
+            }));
+        }
+        Ok(())
+    }
+
     pub fn get_provider(&self) -> ModelProviderInfo {
         self.provider.clone()
     }
@@ -332,20 +618,19 @@ impl ModelClient {
         }
         let auth_manager = self.auth_manager.clone();
         let auth = auth_manager.as_ref().and_then(|m| m.auth());
-        let api_provider = self
-            .provider
-            .to_api_provider(auth.as_ref().map(|a| a.mode))?;
+        let api_provider = self.api_provider(auth.as_ref().map(|a| a.mode))?;
         let api_auth = auth_provider_from_auth(auth.clone(), &self.provider).await?;
-        let transport = ReqwestTransport::new(build_reqwest_client());
-        let request_telemetry = self.build_request_telemetry();
+        let transport = ReqwestTransport::new(self.provider.build_reqwest_client()?);
+        let request_telemetry = self.build_request_telemetry(&api_provider.retry);
         let client = ApiCompactClient::new(transport, api_provider, api_auth)
             .with_telemetry(Some(request_telemetry));
 
         let instructions = prompt
             .get_full_instructions(&self.config.model_family)
             .into_owned();
+        let model = self.provider.request_model_name(&self.config.model);
         let payload = ApiCompactionInput {
-            model: &self.config.model,
+            model: &model,
             input: &prompt.input,
             instructions: &instructions,
         };
@@ -373,17 +658,35 @@ impl ModelClient {
 }
 
 impl ModelClient {
+    /// The provider's API endpoint with the `[retry]` settings applied.
+    fn api_provider(&self, auth_mode: Option<AuthMode>) -> Result<ApiProvider> {
+        let mut api_provider = self.provider.to_api_provider(auth_mode)?;
+        api_provider.retry = self.provider.retry_config(&self.config.retry);
+        Ok(api_provider)
+    }
+
+    fn build_telemetry(&self, retry: &ApiRetryConfig) -> ApiTelemetry {
+        ApiTelemetry {
+            otel_event_manager: self.otel_event_manager.clone(),
+            retry: retry.clone(),
+            retry_notices: self.retry_notices.clone(),
+        }
+    }
+
     /// Builds request and SSE telemetry for streaming API calls (Chat/Responses).
-    fn build_streaming_telemetry(&self) -> (Arc<dyn RequestTelemetry>, Arc<dyn SseTelemetry>) {
-        let telemetry = Arc::new(ApiTelemetry::new(self.otel_event_manager.clone()));
+    fn build_streaming_telemetry(
+        &self,
+        retry: &ApiRetryConfig,
+    ) -> (Arc<dyn RequestTelemetry>, Arc<dyn SseTelemetry>) {
+        let telemetry = Arc::new(self.build_telemetry(retry));
         let request_telemetry: Arc<dyn RequestTelemetry> = telemetry.clone();
         let sse_telemetry: Arc<dyn SseTelemetry> = telemetry;
         (request_telemetry, sse_telemetry)
     }
 
     /// Builds request telemetry for unary API calls (e.g., Compact endpoint).
-    fn build_request_telemetry(&self) -> Arc<dyn RequestTelemetry> {
-        let telemetry = Arc::new(ApiTelemetry::new(self.otel_event_manager.clone()));
+    fn build_request_telemetry(&self, retry: &ApiRetryConfig) -> Arc<dyn RequestTelemetry> {
+        let telemetry = Arc::new(self.build_telemetry(retry));
         let request_telemetry: Arc<dyn RequestTelemetry> = telemetry;
         request_telemetry
     }
@@ -400,6 +703,18 @@ fn build_api_prompt(prompt: &Prompt, instructions: String, tools_json: Vec<Value
     }
 }
 
+/// Size of the parts of the request body that grow with the conversation.
+/// Model options and headers add a few hundred bytes on top.
+fn estimated_request_bytes(api_prompt: &ApiPrompt) -> u64 {
+    fn json_len<T: serde::Serialize + ?Sized>(value: &T) -> usize {
+        serde_json::to_vec(value).map_or(0, |bytes| bytes.len())
+    }
+    (api_prompt.instructions.len()
+        + json_len(&api_prompt.input)
+        + json_len(&api_prompt.tools)
+        + api_prompt.output_schema.as_ref().map_or(0, json_len)) as u64
+}
+
 fn map_response_stream<S>(api_stream: S, otel_event_manager: OtelEventManager) -> ResponseStream
 where
     S: futures::Stream<Item = std::result::Result<ResponseEvent, ApiError>>
@@ -492,6 +807,85 @@ async fn handle_unauthorized(
     }
 }
 
+/// Cool-down applied to a `fallback` entry after a hard provider error. An
+/// outage is not a usage limit, so `limits.retry_delay_hours` does not apply.
+const PROVIDER_ERROR_COOLDOWN: chrono::Duration = chrono::Duration::minutes(5);
+
+/// When `err` means the active credentials hit a quota or rate limit, the
+/// time they are expected to recover (`None` when the provider did not say,
+/// leaving it to `limits.retry_delay_hours`) and the reason to report.
+fn limit_exhaustion(
+    err: &CodexErr,
+) -> Option<(Option<chrono::DateTime<chrono::Utc>>, AuthSwitchReason)> {
+    match err {
+        CodexErr::QuotaExceeded => Some((None, AuthSwitchReason::QuotaExceeded)),
+        CodexErr::UsageLimitReached(limit) => {
+            Some((limit.recovers_at(), AuthSwitchReason::UsageLimitReached))
+        }
+        CodexErr::RetryLimit(limit) if limit.status == StatusCode::TOO_MANY_REQUESTS => {
+            Some((None, AuthSwitchReason::RateLimited))
+        }
+        _ => None,
+    }
+}
+
+/// Like [`limit_exhaustion`], but also treats errors that mean the provider
+/// itself is unusable as a reason to move down the `fallback` chain.
+pub(crate) fn failover_trigger(
+    err: &CodexErr,
+) -> Option<(Option<chrono::DateTime<chrono::Utc>>, AuthSwitchReason)> {
+    if let Some(limit) = limit_exhaustion(err) {
+        return Some(limit);
+    }
+    let provider_error = || {
+        Some((
+            Some(chrono::Utc::now() + PROVIDER_ERROR_COOLDOWN),
+            AuthSwitchReason::ProviderError,
+        ))
+    };
+    match err {
+        CodexErr::UsageNotIncluded => Some((None, AuthSwitchReason::ProviderError)),
+        CodexErr::InternalServerError | CodexErr::RetryLimit(_) => provider_error(),
+        CodexErr::UnexpectedStatus(err) if err.status.is_server_error() => provider_error(),
+        _ => None,
+    }
+}
+
+/// On quota or rate-limit errors, switch to the next key from the
+/// `OPENAI_API_KEYS` pool under API-key auth, or to the next signed-in
+/// ChatGPT account under ChatGPT auth. Returns `true` when the caller should
+/// retry.
+fn rotate_credentials_on_limit(
+    err: &CodexErr,
+    conversation_id: ConversationId,
+    auth_manager: &Option<Arc<AuthManager>>,
+    auth: &Option<crate::auth::CodexAuth>,
+) -> bool {
+    let (Some(manager), Some(auth)) = (auth_manager.as_ref(), auth.as_ref()) else {
+        return false;
+    };
+    let Some((exhausted_until, reason)) = limit_exhaustion(err) else {
+        return false;
+    };
+    match auth.mode {
+        AuthMode::ApiKey => {
+            let rotated = manager.rotate_api_key(Some(conversation_id), exhausted_until, reason);
+            if rotated {
+                warn!("API key hit its limit; retrying with the next key from the pool");
+            }
+            rotated
+        }
+        AuthMode::ChatGPT => {
+            let rotated =
+                manager.rotate_chatgpt_account(Some(conversation_id), exhausted_until, reason);
+            if rotated {
+                warn!("ChatGPT account hit its limit; retrying with the next signed-in account");
+            }
+            rotated
+        }
+    }
+}
+
 fn map_unauthorized_status(status: StatusCode) -> CodexErr {
     map_api_error(ApiError::Transport(TransportError::Http {
         status,
@@ -502,11 +896,42 @@ fn map_unauthorized_status(status: StatusCode) -> CodexErr {
 
 struct ApiTelemetry {
     otel_event_manager: OtelEventManager,
+    retry: ApiRetryConfig,
+    retry_notices: Option<RetryNotices>,
 }
 
 impl ApiTelemetry {
-    fn new(otel_event_manager: OtelEventManager) -> Self {
-        Self { otel_event_manager }
+    /// Announces the retry that follows a failed `attempt`, if the retry
+    /// policy makes one.
+    fn notify_retry(&self, attempt: u64, error: &TransportError) {
+        let Some(notices) = &self.retry_notices else {
+            return;
+        };
+        let policy = self.retry.to_policy();
+        if !policy
+            .retry_on
+            .should_retry(error, attempt, policy.max_attempts)
+        {
+            return;
+        }
+        let (reason, http_status_code) = match error {
+            TransportError::Http { status, .. } => (status.to_string(), Some(status.as_u16())),
+            other => (other.to_string(), None),
+        };
+        let event = ProtocolEvent {
+            id: notices.sub_id.clone(),
+            msg: EventMsg::StreamError(StreamErrorEvent {
+                message: format!(
+                    "Request failed ({reason}); retrying {}/{}...",
+                    attempt + 1,
+                    policy.max_attempts
+                ),
+                codex_error_info: Some(CodexErrorInfo::HttpConnectionFailed { http_status_code }),
+            }),
+        };
+        if let Err(err) = notices.tx_event.try_send(event) {
+            warn!("failed to report a request retry: {err}");
+        }
     }
 }
 
@@ -518,6 +943,9 @@ impl RequestTelemetry for ApiTelemetry {
         error: Option<&TransportError>,
         duration: Duration,
     ) {
+        if let Some(error) = error {
+            self.notify_retry(attempt, error);
+        }
         let error_message = error.map(std::string::ToString::to_string);
         self.otel_event_manager.record_api_request(
             attempt,
@@ -0,0 +1,134 @@
+//! Embeddings for the semantic side of the code index, from a provider's
+//! OpenAI-compatible `/embeddings` endpoint. The default provider is the
+//! built-in `ollama` one, whose endpoint is local, so the code being indexed
+//! stays on the machine.
+
+use std::io;
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::error::UnexpectedResponseError;
+use crate::model_provider_info::ModelProviderInfo;
+
+/// For providers without a `base_url`, such as the built-in `openai` one.
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+/// Inputs sent in one request.
+pub(crate) const BATCH_SIZE: usize = 32;
+/// Longer inputs are cut off, so a chunk of long lines stays within what
+/// small embedding models accept.
+const MAX_INPUT_CHARS: usize = 8_000;
+
+#[derive(Deserialize)]
+struct EmbeddingsResponse {
+    data: Vec<Embedding>,
+}
+
+#[derive(Deserialize)]
+struct Embedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+/// One embedding per input, in the order of `inputs`.
+pub(crate) async fn embed(
+    provider: &ModelProviderInfo,
+    model: &str,
+    inputs: &[String],
+) -> Result<Vec<Vec<f32>>> {
+    let base_url = provider
+        .base_url
+        .as_deref()
+        .unwrap_or(DEFAULT_BASE_URL)
+        .trim_end_matches('/');
+    let inputs: Vec<String> = inputs
+        .iter()
+        .map(|input| input.chars().take(MAX_INPUT_CHARS).collect())
+        .collect();
+    let mut request = provider
+        .build_reqwest_client()?
+        .post(format!("{base_url}/embeddings"))
+        .headers(provider.request_headers()?)
+        .json(&json!({ "model": model, "input": inputs }));
+    if let Some(query_params) = &provider.query_params {
+        request = request.query(query_params);
+    }
+    let response = request.send().await.map_err(io::Error::other)?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(CodexErr::UnexpectedStatus(UnexpectedResponseError {
+            status,
+            body: response.text().await.unwrap_or_default(),
+            request_id: None,
+        }));
+    }
+    let mut data = response
+        .json::<EmbeddingsResponse>()
+        .await
+        .map_err(io::Error::other)?
+        .data;
+    if data.len() != inputs.len() {
+        return Err(io::Error::other(format!(
+            "{} returned {} embeddings for {} inputs",
+            provider.name,
+            data.len(),
+            inputs.len()
+        ))
+        .into());
+    }
+    data.sort_by_key(|embedding| embedding.index);
+    Ok(data
+        .into_iter()
+        .map(|embedding| embedding.embedding)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_provider_info::WireApi;
+    use crate::model_provider_info::create_oss_provider_with_base_url;
+    use pretty_assertions::assert_eq;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::body_json;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+
+    #[tokio::test]
+    async fn embeddings_come_back_in_input_order() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/embeddings"))
+            .and(body_json(json!({
+                "model": "nomic-embed-text",
+                "input": ["fn reconcile_payments()", "fn refresh_token()"],
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": [
+                    { "index": 1, "embedding": [0.0, 1.0] },
+                    { "index": 0, "embedding": [1.0, 0.0] },
+                ],
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let provider =
+            create_oss_provider_with_base_url(&format!("{}/v1", server.uri()), WireApi::Chat);
+
+        let embeddings = embed(
+            &provider,
+            "nomic-embed-text",
+            &[
+                "fn reconcile_payments()".to_string(),
+                "fn refresh_token()".to_string(),
+            ],
+        )
+        .await
+        .expect("embeddings");
+        assert_eq!(embeddings, vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+    }
+}
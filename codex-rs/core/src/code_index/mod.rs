@@ -0,0 +1,581 @@
+//! The index behind the `search_code` tool: every file of a workspace split
+//! into chunks of lines, so the model can look up code by a description
+//! instead of guessing exact names for `grep`.
+//!
+//! Each chunk has a vector of the words it uses. The words of a chunk and its
+//! path are split at `camelCase` and `snake_case` boundaries, cut to a common
+//! stem, and hashed into a fixed number of buckets, so "payment
+//! reconciliation" finds `reconcile_payments`. This is computed locally and
+//! nothing leaves the machine.
+//!
+//! With `search_code.embedding_model` set, chunks also get an embedding from
+//! that model (see [`embeddings`]), and searches rank them by meaning, so code
+//! that shares no words with the query is found too. Embeddings are only
+//! computed for chunks that do not have one yet, a bounded number per search.
+//!
+//! The index lives in `index/<hash of the root>/index.json` under the state
+//! directory. Each search first re-indexes the files that changed since the
+//! last one and drops the files that are gone.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+
+pub(crate) mod embeddings;
+
+const INDEX_SUBDIR: &str = "index";
+const INDEX_FILENAME: &str = "index.json";
+/// Bumped whenever chunking, the term vectors, or the embeddings change, so
+/// old indexes are rebuilt.
+const INDEX_VERSION: u32 = 3;
+/// Hash buckets the stems of a chunk are counted in.
+const BUCKETS: usize = 256;
+const CHUNK_LINES: usize = 40;
+/// Larger files are not indexed.
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// Words are cut to this many characters, so `payment` and `payments` or
+/// `reconcile` and `reconciliation` count as the same word.
+const STEM_CHARS: usize = 6;
+/// Words too common in code to say what a chunk is about.
+const STOP_WORDS: &[&str] = &[
+    "and", "async", "await", "bool", "break", "case", "class", "const", "continue", "def", "else",
+    "end", "enum", "false", "for", "from", "func", "function", "impl", "import", "let", "mut",
+    "new", "nil", "none", "not", "null", "pub", "return", "self", "static", "str", "string",
+    "struct", "the", "this", "true", "type", "use", "var", "void", "while", "with",
+];
+
+/// The directory holding the index of the workspace rooted at `root`.
+pub(crate) fn index_dir(state_dir: &Path, root: &Path) -> PathBuf {
+    let digest = Sha256::digest(root.to_string_lossy().as_bytes());
+    let name: String = digest.iter().take(8).map(|b| format!("{b:02x}")).collect();
+    state_dir.join(INDEX_SUBDIR).join(name)
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub(crate) struct CodeIndex {
+    version: u32,
+    root: PathBuf,
+    /// The model the chunks' embeddings come from.
+    #[serde(default)]
+    embedding_model: Option<String>,
+    files: HashMap<PathBuf, IndexedFile>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct IndexedFile {
+    /// Modification time in nanoseconds since the Unix epoch.
+    modified: Option<u128>,
+    len: u64,
+    chunks: Vec<Chunk>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Chunk {
+    /// First line, counting from 1.
+    start: usize,
+    /// Last line, inclusive.
+    end: usize,
+    /// The term vector quantized to one byte per bucket, base64-encoded.
+    terms: String,
+    /// The chunk's embedding, quantized like `terms`; `None` until it has
+    /// been computed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    embedding: Option<String>,
+}
+
+/// A chunk that still needs an embedding, with the text to embed.
+#[derive(Debug, PartialEq)]
+pub(crate) struct PendingChunk {
+    pub(crate) path: PathBuf,
+    pub(crate) start: usize,
+    pub(crate) text: String,
+}
+
+/// A chunk that matched a query.
+#[derive(Debug, PartialEq)]
+pub(crate) struct CodeMatch {
+    /// Relative to the root of the index.
+    pub(crate) path: PathBuf,
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    pub(crate) score: f32,
+}
+
+impl CodeIndex {
+    /// The saved index of `root` in `dir`, or an empty one if there is none
+    /// or it was built by another version.
+    pub(crate) fn load(dir: &Path, root: &Path) -> Self {
+        std::fs::read_to_string(dir.join(INDEX_FILENAME))
+            .ok()
+            .and_then(|text| serde_json::from_str::<Self>(&text).ok())
+            .filter(|index| index.version == INDEX_VERSION && index.root == root)
+            .unwrap_or_else(|| Self {
+                version: INDEX_VERSION,
+                root: root.to_path_buf(),
+                embedding_model: None,
+                files: HashMap::new(),
+            })
+    }
+
+    /// Drops the embeddings when they come from another model than `model`,
+    /// so they are computed again with it. Returns whether any were dropped.
+    pub(crate) fn use_embedding_model(&mut self, model: Option<&str>) -> bool {
+        if self.embedding_model.as_deref() == model {
+            return false;
+        }
+        self.embedding_model = model.map(str::to_string);
+        let mut dropped = false;
+        for chunk in self.files.values_mut().flat_map(|file| &mut file.chunks) {
+            dropped |= chunk.embedding.take().is_some();
+        }
+        dropped
+    }
+
+    /// Up to `limit` chunks without an embedding, with their text as the
+    /// files are now, and how many chunks lack one in all.
+    pub(crate) fn pending_embeddings(&self, limit: usize) -> (Vec<PendingChunk>, usize) {
+        let mut paths: Vec<&PathBuf> = self
+            .files
+            .iter()
+            .filter(|(_, file)| file.chunks.iter().any(|chunk| chunk.embedding.is_none()))
+            .map(|(path, _)| path)
+            .collect();
+        paths.sort();
+        let total = paths
+            .iter()
+            .map(|path| {
+                self.files[*path]
+                    .chunks
+                    .iter()
+                    .filter(|chunk| chunk.embedding.is_none())
+                    .count()
+            })
+            .sum();
+        let mut pending = Vec::new();
+        for path in paths {
+            if pending.len() >= limit {
+                break;
+            }
+            let Ok(contents) = std::fs::read_to_string(self.root.join(path)) else {
+                continue;
+            };
+            let lines: Vec<&str> = contents.lines().collect();
+            for chunk in &self.files[path].chunks {
+                if pending.len() >= limit {
+                    break;
+                }
+                if chunk.embedding.is_some() {
+                    continue;
+                }
+                let body = lines
+                    .get(chunk.start - 1..chunk.end.min(lines.len()))
+                    .unwrap_or_default()
+                    .join("\n");
+                pending.push(PendingChunk {
+                    path: path.clone(),
+                    start: chunk.start,
+                    text: format!("{}\n{body}", path.to_string_lossy()),
+                });
+            }
+        }
+        (pending, total)
+    }
+
+    /// Stores the embedding of the chunk of `path` starting at `start`.
+    pub(crate) fn set_embedding(&mut self, path: &Path, start: usize, embedding: &[f32]) {
+        let Some(chunk) = self
+            .files
+            .get_mut(path)
+            .and_then(|file| file.chunks.iter_mut().find(|chunk| chunk.start == start))
+        else {
+            return;
+        };
+        let max = embedding
+            .iter()
+            .fold(0.0_f32, |max, value| max.max(value.abs()));
+        if max > 0.0 {
+            let scaled: Vec<f32> = embedding.iter().map(|value| value / max).collect();
+            chunk.embedding = Some(quantize(&scaled));
+        }
+    }
+
+    pub(crate) fn save(&self, dir: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        let json = serde_json::to_string(self).map_err(io::Error::other)?;
+        // Write and rename so an interrupted save leaves the old index.
+        let tmp = dir.join(format!("{INDEX_FILENAME}.tmp"));
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(tmp, dir.join(INDEX_FILENAME))
+    }
+
+    /// Re-indexes the files under the root that are new or changed and drops
+    /// the ones that are gone. Returns how many files changed.
+    pub(crate) fn update(&mut self, files: &[PathBuf]) -> usize {
+        let mut changed = 0;
+        let mut indexed = HashMap::with_capacity(files.len());
+        for path in files {
+            let Ok(relative) = path.strip_prefix(&self.root) else {
+                continue;
+            };
+            let metadata = std::fs::metadata(path).ok();
+            let modified = metadata
+                .as_ref()
+                .and_then(|metadata| metadata.modified().ok())
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|modified| modified.as_nanos());
+            let len = metadata.as_ref().map_or(0, std::fs::Metadata::len);
+            let file = match self.files.remove(relative) {
+                Some(file)
+                    if file.modified.is_some() && file.modified == modified && file.len == len =>
+                {
+                    file
+                }
+                _ => {
+                    changed += 1;
+                    IndexedFile {
+                        modified,
+                        len,
+                        chunks: index_file(path, relative, len),
+                    }
+                }
+            };
+            indexed.insert(relative.to_path_buf(), file);
+        }
+        changed += self.files.len();
+        self.files = indexed;
+        changed
+    }
+
+    /// The `limit` chunks under `scope` (relative to the root; empty for all)
+    /// that share the most words with `query`, best first.
+    pub(crate) fn search(&self, query: &str, scope: &Path, limit: usize) -> Vec<CodeMatch> {
+        let query = term_vector(query);
+        self.rank(scope, limit, |chunk| Some(similarity(&query, &chunk.terms)))
+    }
+
+    /// Like [`Self::search`], ranking the chunks that have an embedding by
+    /// their cosine similarity to the query's `embedding`.
+    pub(crate) fn search_embeddings(
+        &self,
+        embedding: &[f32],
+        scope: &Path,
+        limit: usize,
+    ) -> Vec<CodeMatch> {
+        let norm = embedding
+            .iter()
+            .map(|value| value * value)
+            .sum::<f32>()
+            .sqrt();
+        if norm == 0.0 {
+            return Vec::new();
+        }
+        let query: Vec<f32> = embedding.iter().map(|value| value / norm).collect();
+        self.rank(scope, limit, |chunk| {
+            chunk
+                .embedding
+                .as_deref()
+                .map(|embedding| cosine_similarity(&query, embedding))
+        })
+    }
+
+    fn rank(
+        &self,
+        scope: &Path,
+        limit: usize,
+        score: impl Fn(&Chunk) -> Option<f32>,
+    ) -> Vec<CodeMatch> {
+        let mut matches: Vec<CodeMatch> = self
+            .files
+            .iter()
+            .filter(|(path, _)| path.starts_with(scope))
+            .flat_map(|(path, file)| {
+                file.chunks.iter().filter_map(|chunk| {
+                    Some(CodeMatch {
+                        path: path.clone(),
+                        start: chunk.start,
+                        end: chunk.end,
+                        score: score(chunk)?,
+                    })
+                })
+            })
+            .filter(|found| found.score > 0.0)
+            .collect();
+        matches.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.path.cmp(&b.path))
+                .then_with(|| a.start.cmp(&b.start))
+        });
+        matches.truncate(limit);
+        matches
+    }
+}
+
+fn index_file(path: &Path, relative: &Path, len: u64) -> Vec<Chunk> {
+    if len > MAX_FILE_BYTES {
+        return Vec::new();
+    }
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let lines: Vec<&str> = contents.lines().collect();
+    let path_text = relative.to_string_lossy();
+    lines
+        .chunks(CHUNK_LINES)
+        .enumerate()
+        .filter(|(_, chunk)| chunk.iter().any(|line| !line.trim().is_empty()))
+        .map(|(index, chunk)| {
+            let start = index * CHUNK_LINES + 1;
+            let text = format!("{path_text}\n{}", chunk.join("\n"));
+            Chunk {
+                start,
+                end: start + chunk.len() - 1,
+                terms: quantize(&term_vector(&text)),
+                embedding: None,
+            }
+        })
+        .collect()
+}
+
+/// A unit vector with a signed count of each word's stem in the bucket its
+/// hash picks.
+fn term_vector(text: &str) -> Vec<f32> {
+    let mut vector = vec![0.0_f32; BUCKETS];
+    for word in words(text) {
+        let hash = fnv1a(word.as_bytes());
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        vector[(hash % BUCKETS as u64) as usize] += sign;
+    }
+    // Damp repeated words so one name used throughout a chunk does not
+    // drown out the rest.
+    for value in &mut vector {
+        *value = value.signum() * value.abs().ln_1p();
+    }
+    let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for value in &mut vector {
+            *value /= norm;
+        }
+    }
+    vector
+}
+
+/// The stems of the words in `text`, lowercased and split at `camelCase`,
+/// `snake_case`, and digit boundaries.
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .flat_map(split_identifier)
+        .map(str::to_lowercase)
+        .filter(|word| word.chars().count() >= 3 && !STOP_WORDS.contains(&word.as_str()))
+        .map(|word| word.chars().take(STEM_CHARS).collect())
+}
+
+fn split_identifier(identifier: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut previous: Option<char> = None;
+    for (index, c) in identifier.char_indices() {
+        if let Some(previous) = previous {
+            let boundary = (previous.is_lowercase() && c.is_uppercase())
+                || (previous.is_alphabetic() != c.is_alphabetic());
+            if boundary {
+                parts.push(&identifier[start..index]);
+                start = index;
+            }
+        }
+        previous = Some(c);
+    }
+    parts.push(&identifier[start..]);
+    parts
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn quantize(vector: &[f32]) -> String {
+    let bytes: Vec<u8> = vector
+        .iter()
+        .map(|value| (value * 127.0).round().clamp(-127.0, 127.0) as i8 as u8)
+        .collect();
+    BASE64.encode(bytes)
+}
+
+fn similarity(query: &[f32], terms: &str) -> f32 {
+    let Ok(bytes) = BASE64.decode(terms) else {
+        return 0.0;
+    };
+    query
+        .iter()
+        .zip(bytes)
+        .map(|(value, byte)| value * f32::from(byte as i8) / 127.0)
+        .sum()
+}
+
+/// Cosine similarity of the unit vector `query` and a quantized embedding.
+fn cosine_similarity(query: &[f32], embedding: &str) -> f32 {
+    let Ok(bytes) = BASE64.decode(embedding) else {
+        return 0.0;
+    };
+    let (dot, norm) = query
+        .iter()
+        .zip(bytes)
+        .map(|(value, byte)| (*value, f32::from(byte as i8)))
+        .fold((0.0, 0.0), |(dot, norm), (value, other)| {
+            (dot + value * other, norm + other * other)
+        });
+    if norm > 0.0 { dot / norm.sqrt() } else { 0.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn search_finds_code_by_related_words() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let root = dir.path().join("repo");
+        let write = |path: &str, contents: &str| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().expect("parent")).expect("mkdir");
+            std::fs::write(path, contents).expect("write");
+        };
+        write(
+            "src/billing/ledger.rs",
+            "pub fn reconcile_payments(ledger: &Ledger, statement: &BankStatement) -> Report {\n    // Match each settled payment against the bank statement.\n}\n",
+        );
+        write(
+            "src/auth/session.rs",
+            "pub fn refresh_login_token(session: &mut Session) {\n    session.token = issue_token();\n}\n",
+        );
+        write("README.md", "# Demo\n");
+        let files = vec![
+            root.join("README.md"),
+            root.join("src/auth/session.rs"),
+            root.join("src/billing/ledger.rs"),
+        ];
+
+        let mut index = CodeIndex::load(&dir.path().join("index"), &root);
+        assert_eq!(index.update(&files), 3);
+        let matches = index.search("payment reconciliation", Path::new(""), 5);
+        assert_eq!(
+            matches
+                .iter()
+                .map(|found| (found.path.clone(), found.start, found.end))
+                .collect::<Vec<_>>(),
+            vec![(PathBuf::from("src/billing/ledger.rs"), 1, 3)]
+        );
+        assert_eq!(
+            index.search("tokenRefresh", Path::new("src/auth"), 5)[0].path,
+            PathBuf::from("src/auth/session.rs")
+        );
+        assert!(
+            index
+                .search("tokenRefresh", Path::new("src/billing"), 5)
+                .is_empty()
+        );
+
+        // Saved indexes are reloaded, and only changed files are indexed
+        // again.
+        let index_dir = index_dir(dir.path(), &root);
+        index.save(&index_dir).expect("save");
+        let mut reloaded = CodeIndex::load(&index_dir, &root);
+        assert_eq!(reloaded, index);
+        assert_eq!(reloaded.update(&files), 0);
+        write("README.md", "# Demo\n\nSee src/billing for payments.\n");
+        std::fs::remove_file(root.join("src/auth/session.rs")).expect("remove");
+        assert_eq!(reloaded.update(&[files[0].clone(), files[2].clone()]), 2);
+        assert!(
+            !reloaded
+                .files
+                .contains_key(Path::new("src/auth/session.rs"))
+        );
+        assert_eq!(
+            reloaded.search("payment", Path::new(""), 5)[0].path,
+            PathBuf::from("README.md")
+        );
+        // An index of another root is not reused.
+        assert!(CodeIndex::load(&index_dir, dir.path()).files.is_empty());
+    }
+
+    #[test]
+    fn embeddings_rank_code_by_meaning() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let root = dir.path().join("repo");
+        std::fs::create_dir_all(root.join("src")).expect("mkdir");
+        std::fs::write(
+            root.join("src/ledger.rs"),
+            "fn settle(statement: &Statement) {}\n",
+        )
+        .expect("write");
+        std::fs::write(
+            root.join("src/session.rs"),
+            "fn renew(session: &mut Session) {}\n",
+        )
+        .expect("write");
+        let files = vec![root.join("src/ledger.rs"), root.join("src/session.rs")];
+
+        let mut index = CodeIndex::load(&dir.path().join("index"), &root);
+        index.update(&files);
+        assert!(!index.use_embedding_model(Some("nomic-embed-text")));
+        let (pending, total) = index.pending_embeddings(1);
+        assert_eq!(total, 2);
+        assert_eq!(
+            pending,
+            vec![PendingChunk {
+                path: PathBuf::from("src/ledger.rs"),
+                start: 1,
+                text: "src/ledger.rs\nfn settle(statement: &Statement) {}".to_string(),
+            }]
+        );
+        index.set_embedding(Path::new("src/ledger.rs"), 1, &[0.9, 0.1, 0.0]);
+        index.set_embedding(Path::new("src/session.rs"), 1, &[0.0, 0.2, 0.8]);
+        assert_eq!(index.pending_embeddings(10), (Vec::new(), 0));
+
+        // "payment reconciliation" shares no words with either file.
+        assert!(
+            index
+                .search("payment reconciliation", Path::new(""), 5)
+                .is_empty()
+        );
+        let matches = index.search_embeddings(&[0.8, 0.2, 0.1], Path::new(""), 5);
+        assert_eq!(
+            matches
+                .iter()
+                .map(|found| found.path.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                PathBuf::from("src/ledger.rs"),
+                PathBuf::from("src/session.rs")
+            ]
+        );
+        assert!(matches[0].score > 0.95, "{matches:?}");
+
+        // A changed file and a new model both need new embeddings.
+        std::fs::write(root.join("src/session.rs"), "fn expire() {}\n").expect("write");
+        index.update(&files);
+        assert_eq!(index.pending_embeddings(10).1, 1);
+        assert!(index.use_embedding_model(Some("mxbai-embed-large")));
+        assert_eq!(index.pending_embeddings(10).1, 2);
+    }
+
+    #[test]
+    fn identifiers_split_into_word_stems() {
+        assert_eq!(
+            words("reconcilePayments HTTPServer parse_v2_header").collect::<Vec<_>>(),
+            vec!["reconc", "paymen", "httpse", "parse", "header"]
+        );
+    }
+}
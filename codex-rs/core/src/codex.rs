@@ -1,5 +1,7 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::AtomicU64;
@@ -14,11 +16,16 @@ use crate::compact_remote::run_inline_remote_auto_compact_task;
 use crate::features::Feature;
 use crate::features::Features;
 use crate::function_tool::FunctionCallError;
+use crate::hooks::HookEvent;
+use crate::hooks::HookOutcome;
+use crate::hooks::HookRunner;
 use crate::parse_command::parse_command;
 use crate::parse_turn_item;
 use crate::response_processing::process_items;
 use crate::terminal;
 use crate::truncate::TruncationPolicy;
+use crate::usage_history::UsageEntry;
+use crate::usage_history::append_usage;
 use crate::user_notification::UserNotifier;
 use crate::util::error_or_panic;
 use async_channel::Receiver;
@@ -51,32 +58,51 @@ use mcp_types::ReadResourceResult;
 use mcp_types::RequestId;
 use serde_json;
 use serde_json::Value;
+use serde_json::json;
 use tokio::sync::Mutex;
 use tokio::sync::RwLock;
 use tokio::sync::oneshot;
 use tokio_util::sync::CancellationToken;
+use tokio_util::task::AbortOnDropHandle;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
 use tracing::warn;
 
 use crate::ModelProviderInfo;
+use crate::audit_log::AuditEntry;
+use crate::audit_log::AuditEvent;
+use crate::budget;
+use crate::budget::BudgetStatus;
 use crate::client::ModelClient;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::compact::collect_user_messages;
 use crate::config::Config;
+use crate::config::types::BudgetAction;
+use crate::config::types::Hooks;
+use crate::config::types::LimitCapAction;
+use crate::config::types::RunLimits;
 use crate::config::types::ShellEnvironmentPolicy;
 use crate::context_manager::ContextManager;
+use crate::conversation_manager::fork_origin;
 use crate::environment_context::EnvironmentContext;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
+use crate::error::UsageLimitReachedError;
 #[cfg(test)]
 use crate::exec::StreamOutput;
+use crate::external_edits::ExternalEdits;
+use crate::limit_history::LimitHitEntry;
+use crate::limit_history::append_limit_hit;
+use crate::limit_wait;
 use crate::mcp::auth::compute_auth_statuses;
 use crate::mcp_connection_manager::McpConnectionManager;
+use crate::mcp_connection_manager::ToolInfo;
 use crate::model_family::find_family_for_model;
 use crate::openai_model_info::get_model_info;
+use crate::output_schema;
+use crate::pricing;
 use crate::project_doc::get_user_instructions;
 use crate::protocol::AgentMessageContentDeltaEvent;
 use crate::protocol::AgentReasoningSectionBreakEvent;
@@ -87,7 +113,9 @@ use crate::protocol::DeprecationNoticeEvent;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecApprovalRequestEvent;
+use crate::protocol::ExecCommandSource;
 use crate::protocol::Op;
+use crate::protocol::PatchHunkDecision;
 use crate::protocol::RateLimitSnapshot;
 use crate::protocol::ReasoningContentDeltaEvent;
 use crate::protocol::ReasoningRawContentDeltaEvent;
@@ -102,13 +130,18 @@ use crate::protocol::TokenUsage;
 use crate::protocol::TokenUsageInfo;
 use crate::protocol::TurnDiffEvent;
 use crate::protocol::WarningEvent;
+use crate::redact::Redactor;
+use crate::repo_map::RepoMap;
 use crate::rollout::RolloutRecorder;
 use crate::rollout::RolloutRecorderParams;
 use crate::rollout::map_session_init_error;
+use crate::run_limits::RunLimitHit;
+use crate::run_limits::RunLimitTracker;
 use crate::shell;
 use crate::state::ActiveTurn;
 use crate::state::SessionServices;
 use crate::state::SessionState;
+use crate::sub_agents::SubAgents;
 use crate::tasks::GhostSnapshotTask;
 use crate::tasks::ReviewTask;
 use crate::tasks::SessionTask;
@@ -124,7 +157,6 @@ use crate::unified_exec::UnifiedExecSessionManager;
 use crate::user_instructions::DeveloperInstructions;
 use crate::user_instructions::UserInstructions;
 use crate::user_notification::UserNotification;
-use crate::util::backoff;
 use codex_async_utils::OrCancelExt;
 use codex_execpolicy::Policy as ExecPolicy;
 use codex_otel::otel_event_manager::OtelEventManager;
@@ -134,7 +166,9 @@ use codex_protocol::models::FunctionCallOutputPayload;
 use codex_protocol::models::ResponseInputItem;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::openai_models::ReasoningEffort as ReasoningEffortConfig;
+use codex_protocol::plan_tool::UpdatePlanArgs;
 use codex_protocol::protocol::CodexErrorInfo;
+use codex_protocol::protocol::ErrorEvent;
 use codex_protocol::protocol::InitialHistory;
 use codex_protocol::user_input::UserInput;
 use codex_utils_readiness::Readiness;
@@ -261,7 +295,7 @@ impl Codex {
 ///
 /// A session has at most 1 running task at a time, and can be interrupted by user input.
 pub(crate) struct Session {
-    conversation_id: ConversationId,
+    pub(crate) conversation_id: ConversationId,
     tx_event: Sender<Event>,
     state: Mutex<SessionState>,
     /// The set of enabled features should be invariant for the lifetime of the
@@ -434,7 +468,13 @@ impl Session {
         let tools_config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             features,
-        });
+        })
+        .with_custom_tools(per_turn_config.custom_tools.clone())
+        .with_network_access(
+            session_configuration
+                .sandbox_policy
+                .has_full_network_access(),
+        );
 
         TurnContext {
             sub_id,
@@ -478,12 +518,17 @@ impl Session {
         let (conversation_id, rollout_params) = match &initial_history {
             InitialHistory::New | InitialHistory::Forked(_) => {
                 let conversation_id = ConversationId::default();
+                let forked_from = match &initial_history {
+                    InitialHistory::Forked(items) => fork_origin(items),
+                    _ => None,
+                };
                 (
                     conversation_id,
                     RolloutRecorderParams::new(
                         conversation_id,
                         session_configuration.user_instructions.clone(),
                         session_source,
+                        forked_from,
                     ),
                 )
             }
@@ -493,6 +538,44 @@ impl Session {
             ),
         };
 
+        let source = match &initial_history {
+            InitialHistory::New => "new",
+            InitialHistory::Resumed(_) => "resumed",
+            InitialHistory::Forked(_) => "forked",
+        };
+        let hooks = if config.dry_run {
+            Hooks::default()
+        } else {
+            config.hooks.clone()
+        };
+        let start_hook = HookRunner::new(hooks.clone())
+            .run(
+                HookEvent::SessionStart,
+                None,
+                &session_configuration.cwd,
+                json!({
+                    "session_id": conversation_id.to_string(),
+                    "cwd": session_configuration.cwd,
+                    "model": session_configuration.model,
+                    "source": source,
+                }),
+            )
+            .await;
+        if let HookOutcome::Block(reason) = start_hook {
+            return Err(anyhow::anyhow!(
+                "the session_start hook blocked the session: {reason}"
+            ));
+        }
+
+        auth_manager.apply_policy(&config.policy);
+        auth_manager.set_shared_state_dir(config.shared_state_dir.clone());
+        auth_manager.configure_limits(config.limits.clone());
+        auth_manager.use_project_credentials(&config.cwd);
+        if !config.fallback.is_empty() {
+            auth_manager.configure_failover(config.fallback.clone());
+        }
+        auth_manager.start_background_refresh();
+
         // Kick off independent async setup tasks in parallel to reduce startup latency.
         //
         // - initialize RolloutRecorder with new or resumed session info
@@ -507,9 +590,18 @@ impl Session {
             config.mcp_oauth_credentials_store_mode,
         );
 
+        let plugins_dir = crate::plugins::plugins_dir(&config.codex_home);
+        let plugin_tools_fut =
+            tokio::task::spawn_blocking(move || crate::plugins::load_plugins(&plugins_dir));
+
         // Join all independent futures.
-        let (rollout_recorder, (history_log_id, history_entry_count), auth_statuses) =
-            tokio::join!(rollout_fut, history_meta_fut, auth_statuses_fut);
+        let (rollout_recorder, (history_log_id, history_entry_count), auth_statuses, plugin_tools) = tokio::join!(
+            rollout_fut,
+            history_meta_fut,
+            auth_statuses_fut,
+            plugin_tools_fut
+        );
+        let plugin_tools = plugin_tools.unwrap_or_default();
 
         let rollout_recorder = rollout_recorder.map_err(|e| {
             error!("failed to initialize rollout recorder: {e:#}");
@@ -519,6 +611,15 @@ impl Session {
 
         let mut post_session_configured_events = Vec::<Event>::new();
 
+        if let Some(fallback) = crate::state_dir::read_only_fallback(&config.codex_home) {
+            post_session_configured_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::Warning(WarningEvent {
+                    message: crate::state_dir::read_only_warning(&config.codex_home, &fallback),
+                }),
+            });
+        }
+
         for (alias, feature) in config.features.legacy_feature_usages() {
             let canonical = feature.key();
             let summary = format!("`{alias}` is deprecated. Use `{canonical}` instead.");
@@ -544,7 +645,8 @@ impl Session {
             auth_manager.auth().map(|a| a.mode),
             config.otel.log_user_prompt,
             terminal::user_agent(),
-        );
+        )
+        .with_tags(&config.tags);
 
         otel_event_manager.conversation_starts(
             config.model_provider.name.as_str(),
@@ -559,13 +661,22 @@ impl Session {
         );
 
         // Create the mutable state for the Session.
-        let state = SessionState::new(session_configuration.clone());
+        let mut state = SessionState::new(session_configuration.clone());
+        for pin in &config.pinned_files {
+            state.pin(pin.clone());
+        }
 
         let services = SessionServices {
             mcp_connection_manager: Arc::new(RwLock::new(McpConnectionManager::default())),
             mcp_startup_cancellation_token: CancellationToken::new(),
             unified_exec_manager: UnifiedExecSessionManager::default(),
-            notifier: UserNotifier::new(config.notify.clone()),
+            notifier: UserNotifier::new(config.notify.clone(), config.notify_hooks.clone()),
+            hooks: HookRunner::new(hooks),
+            plugin_tools,
+            repo_map: Mutex::new(RepoMap::default()),
+            external_edits: ExternalEdits::default(),
+            sub_agents: SubAgents::default(),
+            redactor: Redactor::new(&config.redaction),
             rollout: Mutex::new(Some(rollout_recorder)),
             user_shell: default_shell,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
@@ -608,18 +719,20 @@ impl Session {
         for event in events {
             sess.send_event_raw(event).await;
         }
-        sess.services
-            .mcp_connection_manager
-            .write()
-            .await
-            .initialize(
-                config.mcp_servers.clone(),
-                config.mcp_oauth_credentials_store_mode,
-                auth_statuses.clone(),
-                tx_event.clone(),
-                sess.services.mcp_startup_cancellation_token.clone(),
-            )
-            .await;
+        if !config.dry_run {
+            sess.services
+                .mcp_connection_manager
+                .write()
+                .await
+                .initialize(
+                    config.mcp_servers.clone(),
+                    config.mcp_oauth_credentials_store_mode,
+                    auth_statuses.clone(),
+                    tx_event.clone(),
+                    sess.services.mcp_startup_cancellation_token.clone(),
+                )
+                .await;
+        }
 
         let sandbox_state = SandboxState {
             sandbox_policy: session_configuration.sandbox_policy.clone(),
@@ -668,6 +781,11 @@ impl Session {
         }
     }
 
+    pub(crate) async fn rollout_path(&self) -> Option<PathBuf> {
+        let guard = self.services.rollout.lock().await;
+        guard.as_ref().map(|rec| rec.rollout_path.clone())
+    }
+
     fn next_internal_sub_id(&self) -> String {
         let id = self
             .next_internal_sub_id
@@ -722,6 +840,25 @@ impl Session {
                     }
                 }
 
+                // Carry the recorded cost over so session totals span resumes.
+                if let InitialHistory::Resumed(_) = conversation_history
+                    && let Some(cost) = rollout_items.iter().rev().find_map(|it| match it {
+                        RolloutItem::EventMsg(EventMsg::TokenCount(ev)) => ev.cost.as_ref(),
+                        _ => None,
+                    })
+                {
+                    let mut state = self.state.lock().await;
+                    state.cost_mut().resume_from(cost);
+                }
+
+                // Pick the plan up where the model left it.
+                if let Some(plan) = rollout_items.iter().rev().find_map(|it| match it {
+                    RolloutItem::EventMsg(EventMsg::PlanUpdate(plan)) => Some(plan.clone()),
+                    _ => None,
+                }) {
+                    self.state.lock().await.set_plan(plan);
+                }
+
                 // Always add response items to conversation history
                 let reconstructed_history =
                     self.reconstruct_history_from_rollout(&turn_context, &rollout_items);
@@ -771,6 +908,10 @@ impl Session {
             self.conversation_id,
             sub_id,
         );
+        turn_context.tools_config.plugin_tools = self.services.plugin_tools.clone();
+        turn_context.client = turn_context
+            .client
+            .with_retry_notices(self.tx_event.clone(), turn_context.sub_id.clone());
         if let Some(final_schema) = updates.final_output_json_schema {
             turn_context.final_output_json_schema = final_schema;
         }
@@ -814,6 +955,179 @@ impl Session {
         }
     }
 
+    /// Reports credential switches the model client made while opening the
+    /// last request, so a silent key rotation never goes unnoticed.
+    pub(crate) async fn send_auth_switch_events(&self, turn_context: &TurnContext) {
+        let Some(auth_manager) = turn_context.client.get_auth_manager() else {
+            return;
+        };
+        for switch in auth_manager.take_auth_switches(self.conversation_id) {
+            self.audit(
+                turn_context,
+                AuditEntry::now(AuditEvent::AuthSwitch, switch.to.clone())
+                    .reason(switch.reason.description())
+                    .detail(format!("from {}", switch.from)),
+            )
+            .await;
+            self.notifier().notify(&UserNotification::AuthSwitch {
+                thread_id: self.conversation_id.to_string(),
+                text: format!(
+                    "Codex switched from {} to {} ({}).",
+                    switch.from,
+                    switch.to,
+                    switch.reason.description()
+                ),
+                from: switch.from.clone(),
+                to: switch.to.clone(),
+                reason: switch.reason,
+            });
+            self.send_event(turn_context, EventMsg::AuthSwitched(switch))
+                .await;
+        }
+    }
+
+    /// Records a command the agent or the user is about to run in the audit
+    /// log, along with the credentials the turn's requests use.
+    pub(crate) async fn audit_command(
+        &self,
+        turn_context: &TurnContext,
+        command: &[String],
+        cwd: &Path,
+        source: ExecCommandSource,
+    ) {
+        let auth = self.audit_auth(turn_context);
+        let reason = match source {
+            ExecCommandSource::UserShell => "user shell",
+            ExecCommandSource::Agent
+            | ExecCommandSource::UnifiedExecStartup
+            | ExecCommandSource::UnifiedExecInteraction => "agent",
+        };
+        let entry = AuditEntry {
+            cwd: Some(cwd.to_path_buf()),
+            ..AuditEntry::now(AuditEvent::Command, auth)
+        }
+        .reason(reason)
+        .detail(crate::parse_command::shlex_join(command));
+        self.audit(turn_context, entry).await;
+    }
+
+    /// `text` with the secrets `[redaction]` covers replaced, or `None` when
+    /// it has none. Each secret is logged, and recorded in the audit log, the
+    /// first time it is found in `source`, e.g. the tool that returned it.
+    pub(crate) async fn redact(
+        &self,
+        turn_context: &TurnContext,
+        source: &str,
+        text: &str,
+    ) -> Option<String> {
+        let (text, found) = self.services.redactor.redact(text);
+        let Cow::Owned(text) = text else {
+            return None;
+        };
+        let found = self.services.redactor.unreported(source, found);
+        if !found.is_empty() {
+            let auth = self.audit_auth(turn_context);
+            for secret in found {
+                info!(
+                    "redacted {} from {source} as {}",
+                    secret.kind, secret.placeholder
+                );
+                let entry = AuditEntry::now(AuditEvent::Redaction, auth.clone())
+                    .reason(source)
+                    .detail(format!("{} as {}", secret.kind, secret.placeholder));
+                self.audit(turn_context, entry).await;
+            }
+        }
+        Some(text)
+    }
+
+    /// `item` with [`Session::redact`] applied to the text of a message.
+    pub(crate) async fn redact_item(
+        &self,
+        turn_context: &TurnContext,
+        source: &str,
+        item: ResponseItem,
+    ) -> ResponseItem {
+        let ResponseItem::Message { id, role, content } = item else {
+            return item;
+        };
+        let mut redacted = Vec::with_capacity(content.len());
+        for content in content {
+            redacted.push(match content {
+                ContentItem::InputText { text } => ContentItem::InputText {
+                    text: self
+                        .redact(turn_context, source, &text)
+                        .await
+                        .unwrap_or(text),
+                },
+                content => content,
+            });
+        }
+        ResponseItem::Message {
+            id,
+            role,
+            content: redacted,
+        }
+    }
+
+    /// The credentials the turn's requests use, as recorded in the audit log.
+    fn audit_auth(&self, turn_context: &TurnContext) -> String {
+        turn_context
+            .client
+            .get_auth_manager()
+            .and_then(|manager| manager.active_failover_label())
+            .unwrap_or_else(|| {
+                let provider = turn_context.client.get_provider();
+                if provider.requires_openai_auth {
+                    self.services
+                        .auth_manager
+                        .auth()
+                        .map(|auth| auth.identity_label())
+                        .unwrap_or_default()
+                } else {
+                    provider.name
+                }
+            })
+    }
+
+    /// Appends `entry`, tagged with this conversation, to the audit log.
+    async fn audit(&self, turn_context: &TurnContext, entry: AuditEntry) {
+        let entry = AuditEntry {
+            thread_id: Some(self.conversation_id.to_string()),
+            ..entry
+        };
+        let codex_home = turn_context.client.config().codex_home.clone();
+        let _ = tokio::task::spawn_blocking(move || {
+            crate::audit_log::record(&codex_home, &entry);
+        })
+        .await;
+    }
+
+    /// Announces the start of a task along with the credentials its requests
+    /// start out with. Between turns this is where the `fallback` chain climbs
+    /// back to an entry whose limit has reset, e.g. from an API key to
+    /// ChatGPT, so the switch is reported before the first request.
+    pub(crate) async fn send_task_started(&self, turn_context: &TurnContext) {
+        let auth = turn_context.client.get_auth_manager().and_then(|manager| {
+            manager
+                .failover_credentials_label(Some(self.conversation_id))
+                .or_else(|| {
+                    turn_context
+                        .client
+                        .get_provider()
+                        .requires_openai_auth
+                        .then(|| manager.auth().map(|auth| auth.identity_label()))
+                        .flatten()
+                })
+        });
+        let event = EventMsg::TaskStarted(TaskStartedEvent {
+            model_context_window: turn_context.client.get_model_context_window(),
+            auth,
+        });
+        self.send_event(turn_context, event).await;
+        self.send_auth_switch_events(turn_context).await;
+    }
+
     pub(crate) async fn send_event_raw(&self, event: Event) {
         // Persist the event into rollout (recorder filters as needed)
         let rollout_items = vec![RolloutItem::EventMsg(event.msg.clone())];
@@ -978,6 +1292,27 @@ impl Session {
         }
     }
 
+    /// Approves the pending patch for `sub_id`, applying only the hunks
+    /// `hunks` accepts.
+    pub async fn notify_hunk_review(&self, sub_id: &str, hunks: Vec<PatchHunkDecision>) {
+        {
+            let mut active = self.active_turn.lock().await;
+            if let Some(at) = active.as_mut() {
+                at.turn_state
+                    .lock()
+                    .await
+                    .insert_hunk_review(sub_id.to_string(), hunks);
+            }
+        }
+        self.notify_approval(sub_id, ReviewDecision::Approved).await;
+    }
+
+    pub(crate) async fn take_hunk_review(&self, sub_id: &str) -> Option<Vec<PatchHunkDecision>> {
+        let mut active = self.active_turn.lock().await;
+        let at = active.as_mut()?;
+        at.turn_state.lock().await.take_hunk_review(sub_id)
+    }
+
     pub async fn resolve_elicitation(
         &self,
         server_name: String,
@@ -1116,6 +1451,61 @@ impl Session {
         items
     }
 
+    /// The message carrying the pinned files, read from disk for this turn.
+    async fn pinned_files_item(&self, turn_context: &TurnContext) -> Option<ResponseItem> {
+        let pins = self.state.lock().await.pins();
+        if pins.is_empty() {
+            return None;
+        }
+        let (item, report) = crate::pinned_files::render_pins(&turn_context.cwd, &pins);
+        for pin in report {
+            if let Some(error) = pin.error {
+                warn!("not sending pinned {}: {error}", pin.path.display());
+            }
+        }
+        Some(self.redact_item(turn_context, "pinned files", item?).await)
+    }
+
+    /// The map of the workspace for the `repo_map` feature, reparsing only
+    /// the files that changed since the last turn.
+    async fn repo_map_item(&self, turn_context: &TurnContext) -> Option<ResponseItem> {
+        if !self.enabled(Feature::RepoMap) {
+            return None;
+        }
+        let mut repo_map = self.services.repo_map.lock().await;
+        let mut map = std::mem::take(&mut *repo_map);
+        let cwd = turn_context.cwd.clone();
+        match tokio::task::spawn_blocking(move || {
+            let item = map.render(&cwd);
+            (map, item)
+        })
+        .await
+        {
+            Ok((map, item)) => {
+                *repo_map = map;
+                item
+            }
+            Err(err) => {
+                warn!("failed to map the workspace: {err}");
+                None
+            }
+        }
+    }
+
+    /// Records the model's latest plan from `update_plan`.
+    pub(crate) async fn set_plan(&self, plan: UpdatePlanArgs) {
+        self.state.lock().await.set_plan(plan);
+    }
+
+    /// The message carrying the model's plan, while it has steps left.
+    async fn plan_item(&self) -> Option<ResponseItem> {
+        self.state
+            .lock()
+            .await
+            .plan()
+            .and_then(crate::tools::handlers::render_plan)
+    }
+
     pub(crate) async fn persist_rollout_items(&self, items: &[RolloutItem]) {
         let recorder = {
             let guard = self.services.rollout.lock().await;
@@ -1133,23 +1523,201 @@ impl Session {
         state.clone_history()
     }
 
+    pub(crate) async fn record_file_base(&self, path: PathBuf, contents: String) {
+        let mut state = self.state.lock().await;
+        state.record_file_base(path, contents);
+    }
+
+    pub(crate) async fn file_base(&self, path: &Path) -> Option<String> {
+        let mut state = self.state.lock().await;
+        state.file_base(path)
+    }
+
+    pub(crate) async fn forget_file_base(&self, path: &Path) {
+        let mut state = self.state.lock().await;
+        state.forget_file_base(path);
+    }
+
     pub(crate) async fn update_token_usage_info(
         &self,
         turn_context: &TurnContext,
         token_usage: Option<&TokenUsage>,
     ) {
-        {
+        let model = turn_context.client.get_model();
+        let request_usd = {
             let mut state = self.state.lock().await;
-            if let Some(token_usage) = token_usage {
+            token_usage.map(|token_usage| {
                 state.update_token_info_from_usage(
                     token_usage,
                     turn_context.client.get_model_context_window(),
                 );
-            }
+                let price =
+                    pricing::model_price(&model, &turn_context.client.config().model_prices);
+                state
+                    .cost_mut()
+                    .record(&turn_context.sub_id, token_usage, price)
+            })
+        };
+        if let (Some(token_usage), Some(usd)) = (token_usage, request_usd) {
+            self.record_usage(turn_context, model, token_usage, usd)
+                .await;
         }
         self.send_token_count_event(turn_context).await;
     }
 
+    /// Appends a request's usage to the usage history, which the daily and
+    /// monthly budgets and `codex usage report` read.
+    async fn record_usage(
+        &self,
+        turn_context: &TurnContext,
+        model: String,
+        token_usage: &TokenUsage,
+        usd: Option<f64>,
+    ) {
+        let provider = turn_context.client.get_provider();
+        let auth = if provider.requires_openai_auth {
+            self.services
+                .auth_manager
+                .auth()
+                .map(|auth| auth.identity_label())
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
+        let project = crate::git_info::get_git_repo_root(&turn_context.cwd)
+            .unwrap_or_else(|| turn_context.cwd.clone());
+        let entry = UsageEntry {
+            ts: chrono::Utc::now().timestamp(),
+            model,
+            provider: provider.name,
+            auth,
+            project: Some(project.to_string_lossy().into_owned()),
+            input_tokens: token_usage.input_tokens,
+            cached_input_tokens: token_usage.cached_input_tokens,
+            output_tokens: token_usage.output_tokens,
+            reasoning_output_tokens: token_usage.reasoning_output_tokens,
+            usd,
+        };
+        let config = turn_context.client.config();
+        let state_dir = crate::state_dir::state_dir(&config.codex_home);
+        let shared_dir = config.shared_state_dir.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            append_usage(&state_dir, shared_dir.as_deref(), &entry)
+        })
+        .await;
+        if let Ok(Err(err)) = result {
+            warn!("failed to record usage: {err}");
+        }
+    }
+
+    /// Checks the estimated spend against `[budget]` before a model request.
+    /// Returns the turn context to make the request with, which uses
+    /// `budget.downgrade_model` once a cap is exceeded under
+    /// `on_exceeded = "downgrade"`, or `None` when the task has to stop.
+    async fn enforce_budget(&self, turn_context: Arc<TurnContext>) -> Option<Arc<TurnContext>> {
+        let config = turn_context.client.config();
+        let budget = &config.budget;
+        if !budget::has_caps(budget) {
+            return Some(turn_context);
+        }
+        let (day_usd, month_usd) = if budget::has_recorded_caps(budget) {
+            let state_dir = crate::state_dir::state_dir(&config.codex_home);
+            let shared_dir = config.shared_state_dir.clone();
+            tokio::task::spawn_blocking(move || {
+                budget::recorded_spend(&state_dir, shared_dir.as_deref(), &chrono::Local::now())
+            })
+            .await
+            .ok()
+            .and_then(|result| {
+                result
+                    .inspect_err(|err| warn!("failed to read usage history: {err}"))
+                    .ok()
+            })
+            .unwrap_or_default()
+        } else {
+            (0.0, 0.0)
+        };
+        let status = {
+            let mut state = self.state.lock().await;
+            let (session_usd, warned) = state.budget_state_mut();
+            let spend = budget::Spend {
+                session_usd,
+                day_usd,
+                month_usd,
+            };
+            budget::check_budget(budget, &spend, warned)
+        };
+        let Some(status) = status else {
+            return Some(turn_context);
+        };
+        let message = status.message();
+        if let BudgetStatus::Approaching { .. } = status {
+            self.send_event(&turn_context, EventMsg::Warning(WarningEvent { message }))
+                .await;
+            return Some(turn_context);
+        }
+        match (budget.on_exceeded, budget.downgrade_model.as_ref()) {
+            (BudgetAction::Downgrade, Some(model)) => {
+                if turn_context.client.get_model() == *model {
+                    return Some(turn_context);
+                }
+                let message =
+                    format!("{message} Switching to `{model}` for the rest of this session.");
+                warn!("{message}");
+                self.send_event(&turn_context, EventMsg::Warning(WarningEvent { message }))
+                    .await;
+                let update = SessionSettingsUpdate {
+                    model: Some(model.clone()),
+                    ..Default::default()
+                };
+                Some(
+                    self.new_turn_with_sub_id(turn_context.sub_id.clone(), update)
+                        .await,
+                )
+            }
+            _ => {
+                self.send_event(
+                    &turn_context,
+                    EventMsg::Error(ErrorEvent {
+                        message,
+                        codex_error_info: Some(CodexErrorInfo::Other),
+                    }),
+                )
+                .await;
+                None
+            }
+        }
+    }
+
+    /// Under `limits.on_cap = "downgrade_model"`, switches the session to
+    /// `limits.downgrade_model` after `err` and returns the turn context to
+    /// retry with; `None` when there is nothing to downgrade to.
+    async fn downgrade_for_limit(
+        &self,
+        turn_context: &TurnContext,
+        err: &UsageLimitReachedError,
+    ) -> Option<Arc<TurnContext>> {
+        let config = turn_context.client.config();
+        let model = config.limits.downgrade_model.as_ref()?;
+        if config.limits.on_cap != LimitCapAction::DowngradeModel
+            || turn_context.client.get_model() == *model
+        {
+            return None;
+        }
+        let message = format!("{err} Switching to `{model}` for the rest of this session.");
+        warn!("{message}");
+        self.send_event(turn_context, EventMsg::Warning(WarningEvent { message }))
+            .await;
+        let update = SessionSettingsUpdate {
+            model: Some(model.clone()),
+            ..Default::default()
+        };
+        Some(
+            self.new_turn_with_sub_id(turn_context.sub_id.clone(), update)
+                .await,
+        )
+    }
+
     pub(crate) async fn recompute_token_usage(&self, turn_context: &TurnContext) {
         let Some(estimated_total_tokens) = self
             .clone_history()
@@ -1183,6 +1751,32 @@ impl Session {
         self.send_token_count_event(turn_context).await;
     }
 
+    /// Sleeps until `recovers_at` under `limits.on_cap = "wait"`, announcing the
+    /// time left. The retried request then picks up whichever credentials
+    /// the reset made available again.
+    async fn wait_for_limit_reset(
+        &self,
+        turn_context: &TurnContext,
+        recovers_at: chrono::DateTime<chrono::Utc>,
+    ) {
+        let auth = self
+            .services
+            .auth_manager
+            .auth()
+            .map(|auth| auth.identity_label())
+            .unwrap_or_default();
+        let mut now = chrono::Utc::now();
+        while let Some(delay) = limit_wait::next_sleep(recovers_at, now) {
+            let message = limit_wait::countdown_message(&auth, recovers_at, now);
+            info!("{message}");
+            self.notify_background_event(turn_context, message).await;
+            tokio::time::sleep(delay).await;
+            now = chrono::Utc::now();
+        }
+        self.notify_background_event(turn_context, "Usage limit reset; resuming.")
+            .await;
+    }
+
     pub(crate) async fn update_rate_limits(
         &self,
         turn_context: &TurnContext,
@@ -1195,12 +1789,59 @@ impl Session {
         self.send_token_count_event(turn_context).await;
     }
 
+    /// Records the limit hit in the limit history for `codex limits history`
+    /// and runs the `notify.on_limit_hit` hook.
+    async fn record_limit_hit(&self, turn_context: &TurnContext, err: &UsageLimitReachedError) {
+        let window = err.binding_window();
+        let entry = LimitHitEntry {
+            ts: chrono::Utc::now().timestamp(),
+            auth: self
+                .services
+                .auth_manager
+                .auth()
+                .map(|auth| auth.identity_label())
+                .unwrap_or_default(),
+            model: turn_context.client.get_model(),
+            window_minutes: window.and_then(|window| window.window_minutes),
+            resets_at: err.recovers_at().map(|at| at.timestamp()),
+        };
+        self.audit(
+            turn_context,
+            AuditEntry::now(AuditEvent::LimitHit, entry.auth.clone())
+                .reason(err.to_string())
+                .detail(entry.model.clone()),
+        )
+        .await;
+        self.notifier().notify(&UserNotification::LimitHit {
+            thread_id: self.conversation_id.to_string(),
+            text: format!("Codex ({}, {}): {err}", entry.auth, entry.model),
+            auth: entry.auth.clone(),
+            model: entry.model.clone(),
+            window_minutes: entry.window_minutes,
+            resets_at: entry.resets_at,
+        });
+        let config = turn_context.client.config();
+        let state_dir = crate::state_dir::state_dir(&config.codex_home);
+        let shared_dir = config.shared_state_dir.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            append_limit_hit(&state_dir, shared_dir.as_deref(), &entry)
+        })
+        .await;
+        if let Ok(Err(err)) = result {
+            warn!("failed to record limit hit: {err}");
+        }
+    }
+
     async fn send_token_count_event(&self, turn_context: &TurnContext) {
-        let (info, rate_limits) = {
+        let (info, rate_limits, cost) = {
             let state = self.state.lock().await;
-            state.token_info_and_rate_limits()
+            state.token_info_rate_limits_and_cost()
         };
-        let event = EventMsg::TokenCount(TokenCountEvent { info, rate_limits });
+        let event = EventMsg::TokenCount(TokenCountEvent {
+            info,
+            rate_limits,
+            cost: Some(cost),
+        });
         self.send_event(turn_context, event).await;
     }
 
@@ -1390,6 +2031,42 @@ impl Session {
         &self.services.user_shell
     }
 
+    /// Runs the `[hooks]` entry for `event` in the turn's working directory.
+    /// The payload gets the session id and working directory.
+    pub(crate) async fn run_hook(
+        &self,
+        turn_context: &TurnContext,
+        event: HookEvent,
+        tool: Option<&str>,
+        mut payload: Value,
+    ) -> HookOutcome {
+        if let Value::Object(fields) = &mut payload {
+            fields.insert("session_id".to_string(), json!(self.conversation_id));
+            fields.insert("cwd".to_string(), json!(turn_context.cwd));
+        }
+        self.services
+            .hooks
+            .run(event, tool, &turn_context.cwd, payload)
+            .await
+    }
+
+    /// Runs the `session_end` hook; it cannot stop the shutdown.
+    async fn run_session_end_hook(&self) {
+        let cwd = self.state.lock().await.session_configuration.cwd.clone();
+        let payload = json!({
+            "session_id": self.conversation_id,
+            "cwd": cwd,
+        });
+        if let HookOutcome::Block(reason) = self
+            .services
+            .hooks
+            .run(HookEvent::SessionEnd, None, &cwd, payload)
+            .await
+        {
+            warn!("session_end hook failed: {reason}");
+        }
+    }
+
     fn show_raw_agent_reasoning(&self) -> bool {
         self.services.show_raw_agent_reasoning
     }
@@ -1443,6 +2120,9 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
             Op::PatchApproval { id, decision } => {
                 handlers::patch_approval(&sess, id, decision).await;
             }
+            Op::PatchHunkReview { id, hunks } => {
+                handlers::patch_hunk_review(&sess, id, hunks).await;
+            }
             Op::AddToHistory { text } => {
                 handlers::add_to_history(&sess, &config, text).await;
             }
@@ -1459,6 +2139,24 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
             Op::Undo => {
                 handlers::undo(&sess, sub.id.clone()).await;
             }
+            Op::Checkpoint { name } => {
+                handlers::checkpoint(&sess, &config, sub.id.clone(), name).await;
+            }
+            Op::Pin { path } => {
+                handlers::pin(&sess, sub.id.clone(), path).await;
+            }
+            Op::Unpin { path } => {
+                handlers::unpin(&sess, sub.id.clone(), path).await;
+            }
+            Op::ListPins => {
+                handlers::list_pins(&sess, sub.id.clone()).await;
+            }
+            Op::DryRun {
+                items,
+                final_output_json_schema,
+            } => {
+                handlers::dry_run(&sess, sub.id.clone(), items, final_output_json_schema).await;
+            }
             Op::Compact => {
                 handlers::compact(&sess, sub.id.clone()).await;
             }
@@ -1501,18 +2199,26 @@ mod handlers {
     use crate::codex::SessionSettingsUpdate;
     use crate::codex::TurnContext;
 
+    use crate::codex::build_prompt;
+    use crate::codex::images_as_text;
     use crate::codex::spawn_review_thread;
     use crate::config::Config;
+    use crate::dry_run;
     use crate::mcp::auth::compute_auth_statuses;
     use crate::mcp::collect_mcp_snapshot_from_manager;
     use crate::openai_models::model_presets::builtin_model_presets;
+    use crate::pinned_files::check_pin;
+    use crate::pinned_files::render_pins;
     use crate::review_prompts::resolve_review_request;
+    use crate::tasks::CheckpointTask;
     use crate::tasks::CompactTask;
     use crate::tasks::RegularTask;
     use crate::tasks::UndoTask;
     use crate::tasks::UserShellCommandTask;
     use codex_app_server_protocol::AuthMode;
     use codex_protocol::custom_prompts::CustomPrompt;
+    use codex_protocol::models::ResponseInputItem;
+    use codex_protocol::models::ResponseItem;
     use codex_protocol::openai_models::AvailableModelsEvent;
     use codex_protocol::protocol::CodexErrorInfo;
     use codex_protocol::protocol::ErrorEvent;
@@ -1520,6 +2226,8 @@ mod handlers {
     use codex_protocol::protocol::EventMsg;
     use codex_protocol::protocol::ListCustomPromptsResponseEvent;
     use codex_protocol::protocol::Op;
+    use codex_protocol::protocol::PatchHunkDecision;
+    use codex_protocol::protocol::PinnedFilesEvent;
     use codex_protocol::protocol::ReviewDecision;
     use codex_protocol::protocol::ReviewRequest;
     use codex_protocol::protocol::TurnAbortReason;
@@ -1528,11 +2236,14 @@ mod handlers {
     use codex_rmcp_client::ElicitationAction;
     use codex_rmcp_client::ElicitationResponse;
     use mcp_types::RequestId;
+    use serde_json::Value;
+    use std::path::PathBuf;
     use std::sync::Arc;
     use tracing::info;
     use tracing::warn;
 
     pub async fn interrupt(sess: &Arc<Session>) {
+        sess.services.sub_agents.cancel_all();
         sess.interrupt_task().await;
     }
 
@@ -1577,6 +2288,11 @@ mod handlers {
             .client
             .get_otel_event_manager()
             .user_prompt(&items);
+        let items = if current_context.client.get_provider().supports_images() {
+            items
+        } else {
+            images_as_text(items)
+        };
 
         // Attempt to inject input into current task
         if let Err(items) = sess.inject_input(items).await {
@@ -1655,6 +2371,10 @@ mod handlers {
         }
     }
 
+    pub async fn patch_hunk_review(sess: &Arc<Session>, id: String, hunks: Vec<PatchHunkDecision>) {
+        sess.notify_hunk_review(&id, hunks).await;
+    }
+
     pub async fn add_to_history(sess: &Arc<Session>, config: &Arc<Config>, text: String) {
         let id = sess.conversation_id;
         let config = Arc::clone(config);
@@ -1745,6 +2465,123 @@ mod handlers {
             .await;
     }
 
+    pub async fn checkpoint(
+        sess: &Arc<Session>,
+        config: &Arc<Config>,
+        sub_id: String,
+        name: String,
+    ) {
+        if let Err(message) = crate::checkpoints::validate_checkpoint_name(&name) {
+            let event = Event {
+                id: sub_id,
+                msg: EventMsg::Error(ErrorEvent {
+                    message,
+                    codex_error_info: Some(CodexErrorInfo::Other),
+                }),
+            };
+            sess.send_event_raw(event).await;
+            return;
+        }
+        let turn_context = sess
+            .new_turn_with_sub_id(sub_id, SessionSettingsUpdate::default())
+            .await;
+        sess.spawn_task(
+            turn_context,
+            Vec::new(),
+            CheckpointTask::new(name, config.codex_home.clone()),
+        )
+        .await;
+    }
+
+    pub async fn pin(sess: &Session, sub_id: String, path: PathBuf) {
+        let cwd = sess.state.lock().await.session_configuration.cwd.clone();
+        if let Err(message) = check_pin(&cwd, &path) {
+            send_pin_error(sess, sub_id, message).await;
+            return;
+        }
+        sess.state.lock().await.pin(path);
+        list_pins(sess, sub_id).await;
+    }
+
+    pub async fn unpin(sess: &Session, sub_id: String, path: PathBuf) {
+        if !sess.state.lock().await.unpin(&path) {
+            let message = format!("{} is not pinned", path.display());
+            send_pin_error(sess, sub_id, message).await;
+            return;
+        }
+        list_pins(sess, sub_id).await;
+    }
+
+    pub async fn list_pins(sess: &Session, sub_id: String) {
+        let (cwd, pins) = {
+            let state = sess.state.lock().await;
+            (state.session_configuration.cwd.clone(), state.pins())
+        };
+        let (_, pins) = render_pins(&cwd, &pins);
+        let event = Event {
+            id: sub_id,
+            msg: EventMsg::PinnedFiles(PinnedFilesEvent { pins }),
+        };
+        sess.send_event_raw(event).await;
+    }
+
+    pub async fn dry_run(
+        sess: &Session,
+        sub_id: String,
+        items: Vec<UserInput>,
+        final_output_json_schema: Option<Value>,
+    ) {
+        let turn_context = sess
+            .new_turn_with_sub_id(
+                sub_id.clone(),
+                SessionSettingsUpdate {
+                    final_output_json_schema: Some(final_output_json_schema),
+                    ..Default::default()
+                },
+            )
+            .await;
+        let user_input = if turn_context.client.get_provider().supports_images() {
+            items.clone()
+        } else {
+            images_as_text(items.clone())
+        };
+        // The history and the new input as the first request of the turn
+        // would see them, without recording the input.
+        let mut input = sess.clone_history().await.get_history_for_prompt();
+        input.push(ResponseItem::from(ResponseInputItem::from(user_input)));
+        let mcp_tools = sess
+            .services
+            .mcp_connection_manager
+            .read()
+            .await
+            .list_all_tools()
+            .await;
+        let (_, prompt) = build_prompt(sess, &turn_context, mcp_tools, input).await;
+        let pins = sess.state.lock().await.pins();
+        let (_, pinned_files) = render_pins(&turn_context.cwd, &pins);
+        let event = Event {
+            id: sub_id,
+            msg: EventMsg::DryRunReport(dry_run::report(
+                &turn_context,
+                &prompt,
+                &items,
+                pinned_files,
+            )),
+        };
+        sess.send_event_raw(event).await;
+    }
+
+    async fn send_pin_error(sess: &Session, sub_id: String, message: String) {
+        let event = Event {
+            id: sub_id,
+            msg: EventMsg::Error(ErrorEvent {
+                message,
+                codex_error_info: Some(CodexErrorInfo::Other),
+            }),
+        };
+        sess.send_event_raw(event).await;
+    }
+
     pub async fn compact(sess: &Arc<Session>, sub_id: String) {
         let turn_context = sess
             .new_turn_with_sub_id(sub_id, SessionSettingsUpdate::default())
@@ -1761,11 +2598,16 @@ mod handlers {
     }
 
     pub async fn shutdown(sess: &Arc<Session>, sub_id: String) -> bool {
+        sess.services.sub_agents.cancel_all();
         sess.abort_all_tasks(TurnAbortReason::Interrupted).await;
+        sess.run_session_end_hook().await;
         sess.services
             .unified_exec_manager
             .terminate_all_sessions()
             .await;
+        sess.services
+            .auth_manager
+            .forget_auth_switches(sess.conversation_id);
         info!("Shutting down Codex instance");
 
         // Gracefully flush and shutdown rollout recorder on session end so tests
@@ -1850,11 +2692,14 @@ async fn spawn_review_thread(
     let model = config.review_model.clone();
     let review_model_family = find_family_for_model(&model)
         .unwrap_or_else(|| parent_turn_context.client.get_model_family());
-    // For reviews, disable web_search and view_image regardless of global settings.
+    // For reviews, disable web_search, view_image, fetch_url, and sub-agents regardless of
+    // global settings.
     let mut review_features = sess.features.clone();
     review_features
         .disable(crate::features::Feature::WebSearchRequest)
-        .disable(crate::features::Feature::ViewImageTool);
+        .disable(crate::features::Feature::ViewImageTool)
+        .disable(crate::features::Feature::FetchUrlTool)
+        .disable(crate::features::Feature::SubAgents);
     let tools_config = ToolsConfig::new(&ToolsConfigParams {
         model_family: &review_model_family,
         features: &review_features,
@@ -1895,7 +2740,8 @@ async fn spawn_review_thread(
         per_turn_config.model_reasoning_summary,
         sess.conversation_id,
         parent_turn_context.client.get_session_source(),
-    );
+    )
+    .with_retry_notices(sess.tx_event.clone(), sub_id.clone());
 
     let review_turn_context = TurnContext {
         sub_id: sub_id.to_string(),
@@ -1921,7 +2767,8 @@ async fn spawn_review_thread(
         text: review_prompt,
     }];
     let tc = Arc::new(review_turn_context);
-    sess.spawn_task(tc.clone(), input, ReviewTask::new()).await;
+    sess.spawn_task(tc.clone(), input, ReviewTask::new(resolved.target.clone()))
+        .await;
 
     // Announce entering review mode so UIs can switch modes.
     let review_request = ReviewRequest {
@@ -1932,6 +2779,31 @@ async fn spawn_review_thread(
         .await;
 }
 
+/// Replaces images in `input` with a mention of where they came from, for
+/// providers whose models cannot view them.
+fn images_as_text(input: Vec<UserInput>) -> Vec<UserInput> {
+    input
+        .into_iter()
+        .map(|item| match item {
+            UserInput::LocalImage { path } => UserInput::Text {
+                text: format!(
+                    "The user attached the image `{}`, but this model cannot view images.",
+                    path.display()
+                ),
+            },
+            UserInput::Image { image_url } if image_url.starts_with("data:") => UserInput::Text {
+                text: "The user attached an image, but this model cannot view images.".to_string(),
+            },
+            UserInput::Image { image_url } => UserInput::Text {
+                text: format!(
+                    "The user attached the image {image_url}, but this model cannot view images."
+                ),
+            },
+            item => item,
+        })
+        .collect()
+}
+
 /// Takes a user message as input and runs a loop where, at each turn, the model
 /// replies with either:
 ///
@@ -1948,17 +2820,14 @@ async fn spawn_review_thread(
 ///
 pub(crate) async fn run_task(
     sess: Arc<Session>,
-    turn_context: Arc<TurnContext>,
+    mut turn_context: Arc<TurnContext>,
     input: Vec<UserInput>,
     cancellation_token: CancellationToken,
 ) -> Option<String> {
     if input.is_empty() {
         return None;
     }
-    let event = EventMsg::TaskStarted(TaskStartedEvent {
-        model_context_window: turn_context.client.get_model_context_window(),
-    });
-    sess.send_event(&turn_context, event).await;
+    sess.send_task_started(&turn_context).await;
 
     let initial_input_for_turn: ResponseInputItem = ResponseInputItem::from(input);
     let response_item: ResponseItem = initial_input_for_turn.clone().into();
@@ -1971,8 +2840,29 @@ pub(crate) async fn run_task(
     // Although from the perspective of codex.rs, TurnDiffTracker has the lifecycle of a Task which contains
     // many turns, from the perspective of the user, it is a single turn.
     let turn_diff_tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
+    // Set after compacting because the request was too large, so a request
+    // that still does not fit is reported instead of compacting forever.
+    let mut compacted_for_request_size = false;
+    // Final messages sent back for not matching the output schema.
+    let mut schema_retries = 0;
+    let run_limits = turn_context.client.config().run_limits;
+    let mut run_limits = if run_limits == RunLimits::default() {
+        None
+    } else {
+        let history_len = sess.clone_history().await.get_history().len();
+        let output_tokens = sess.state.lock().await.output_tokens_used();
+        Some(RunLimitTracker::new(run_limits, output_tokens, history_len))
+    };
 
     loop {
+        if let Some(tracker) = run_limits.as_mut() {
+            let output_tokens = sess.state.lock().await.output_tokens_used();
+            if let Some(hit) = tracker.start_turn(output_tokens) {
+                stop_at_run_limit(&sess, &turn_context, tracker, hit, &turn_diff_tracker).await;
+                break;
+            }
+        }
+
         // Note that pending_input would be something like a message the user
         // submitted through the UI while the model was running. Though the UI
         // may support this, the model might not.
@@ -1998,16 +2888,35 @@ pub(crate) async fn run_task(
             })
             .map(|user_message| user_message.message())
             .collect::<Vec<String>>();
+        let Some(budgeted_turn_context) = sess.enforce_budget(turn_context).await else {
+            break;
+        };
+        turn_context = budgeted_turn_context;
+        let tokens_before_turn = sess.state.lock().await.session_tokens_used();
+        // Past `max_duration` the turn is cancelled like an interrupt, so what
+        // it did so far is recorded before the task stops.
+        let turn_cancellation = cancellation_token.child_token();
+        let _deadline = run_limits
+            .as_ref()
+            .and_then(RunLimitTracker::deadline)
+            .map(|deadline| {
+                let turn_cancellation = turn_cancellation.clone();
+                AbortOnDropHandle::new(tokio::spawn(async move {
+                    tokio::time::sleep_until(deadline.into()).await;
+                    turn_cancellation.cancel();
+                }))
+            });
         match run_turn(
             Arc::clone(&sess),
             Arc::clone(&turn_context),
             Arc::clone(&turn_diff_tracker),
             turn_input,
-            cancellation_token.child_token(),
+            turn_cancellation,
         )
         .await
         {
             Ok(turn_output) => {
+                compacted_for_request_size = false;
                 let processed_items = turn_output;
                 let limit = turn_context
                     .client
@@ -2015,9 +2924,35 @@ pub(crate) async fn run_task(
                     .unwrap_or(i64::MAX);
                 let total_usage_tokens = sess.get_total_token_usage().await;
                 let token_limit_reached = total_usage_tokens >= limit;
+                let guardrail_trip = if sess.enabled(Feature::SessionGuardrail) {
+                    let mut state = sess.state.lock().await;
+                    let request_tokens = state.session_tokens_used() - tokens_before_turn;
+                    state.observe_guardrail(request_tokens, &processed_items)
+                } else {
+                    None
+                };
                 let (responses, items_to_record_in_conversation_history) =
                     process_items(processed_items, &sess, &turn_context).await;
 
+                if let Some(trip) = guardrail_trip
+                    && !responses.is_empty()
+                {
+                    let message = trip.message();
+                    warn!("session guardrail tripped: {message}");
+                    sess.send_event(&turn_context, EventMsg::Warning(WarningEvent { message }))
+                        .await;
+                    let Some(prompt) = trip.intervention_prompt() else {
+                        break;
+                    };
+                    let intervention = ResponseItem::Message {
+                        id: None,
+                        role: "user".to_string(),
+                        content: vec![ContentItem::InputText { text: prompt }],
+                    };
+                    sess.record_conversation_items(&turn_context, &[intervention])
+                        .await;
+                }
+
                 // as long as compaction works well in getting us way below the token limit, we shouldn't worry about being in an infinite loop.
                 if token_limit_reached {
                     if should_use_remote_compact_task(&sess) {
@@ -2033,6 +2968,39 @@ pub(crate) async fn run_task(
                     last_agent_message = get_last_assistant_message_from_turn(
                         &items_to_record_in_conversation_history,
                     );
+                    if let Some(schema) = turn_context.final_output_json_schema.as_ref()
+                        && !output_schema::enforced_by(&turn_context.client.get_provider())
+                    {
+                        match output_schema::check_final_message(
+                            schema,
+                            last_agent_message.as_deref(),
+                        ) {
+                            Ok(value) => last_agent_message = Some(value.to_string()),
+                            Err(err) if schema_retries < output_schema::MAX_RETRIES => {
+                                schema_retries += 1;
+                                warn!("final message does not match the output schema: {err}");
+                                sess.record_conversation_items(
+                                    &turn_context,
+                                    &[output_schema::retry_item(&err)],
+                                )
+                                .await;
+                                continue;
+                            }
+                            Err(err) => {
+                                let message = format!(
+                                    "The final message does not match the output schema: {err}"
+                                );
+                                sess.send_event(
+                                    &turn_context,
+                                    EventMsg::Error(ErrorEvent {
+                                        message,
+                                        codex_error_info: None,
+                                    }),
+                                )
+                                .await;
+                            }
+                        }
+                    }
                     sess.notifier()
                         .notify(&UserNotification::AgentTurnComplete {
                             thread_id: sess.conversation_id.to_string(),
@@ -2049,6 +3017,12 @@ pub(crate) async fn run_task(
                 dangling_artifacts: processed_items,
             }) => {
                 let _ = process_items(processed_items, &sess, &turn_context).await;
+                if !cancellation_token.is_cancelled()
+                    && let Some(tracker) = run_limits.as_ref()
+                    && let Some(hit) = tracker.elapsed_hit()
+                {
+                    stop_at_run_limit(&sess, &turn_context, tracker, hit, &turn_diff_tracker).await;
+                }
                 // Aborted turn is reported via a different event.
                 break;
             }
@@ -2059,6 +3033,27 @@ pub(crate) async fn run_task(
                 );
                 state.history.replace_last_turn_images("Invalid image");
             }
+            Err(CodexErr::RequestTooLarge(e)) if !compacted_for_request_size => {
+                // Stateless APIs resend the whole history every turn, so the
+                // only way to shrink the request is to shrink the history.
+                warn!("{e}; compacting the conversation and retrying");
+                compacted_for_request_size = true;
+                if should_use_remote_compact_task(&sess) {
+                    run_inline_remote_auto_compact_task(sess.clone(), turn_context.clone()).await;
+                } else {
+                    run_inline_auto_compact_task(sess.clone(), turn_context.clone()).await;
+                }
+            }
+            Err(CodexErr::UsageLimitReached(e)) => {
+                if let Some(downgraded) = sess.downgrade_for_limit(&turn_context, &e).await {
+                    turn_context = downgraded;
+                    continue;
+                }
+                info!("Turn error: {e:#}");
+                let event = EventMsg::Error(CodexErr::UsageLimitReached(e).to_error_event(None));
+                sess.send_event(&turn_context, event).await;
+                break;
+            }
             Err(e) => {
                 info!("Turn error: {e:#}");
                 let event = EventMsg::Error(e.to_error_event(None));
@@ -2072,21 +3067,41 @@ pub(crate) async fn run_task(
     last_agent_message
 }
 
-async fn run_turn(
-    sess: Arc<Session>,
-    turn_context: Arc<TurnContext>,
-    turn_diff_tracker: SharedTurnDiffTracker,
-    input: Vec<ResponseItem>,
-    cancellation_token: CancellationToken,
-) -> CodexResult<Vec<ProcessedResponseItem>> {
-    let mcp_tools = sess
-        .services
-        .mcp_connection_manager
-        .read()
+/// Ends the task at `hit` with a summary of what it attempted.
+async fn stop_at_run_limit(
+    sess: &Session,
+    turn_context: &TurnContext,
+    tracker: &RunLimitTracker,
+    hit: RunLimitHit,
+    turn_diff_tracker: &SharedTurnDiffTracker,
+) {
+    let history = sess.clone_history().await.get_history();
+    let unified_diff = turn_diff_tracker
+        .lock()
         .await
-        .list_all_tools()
-        .or_cancel(&cancellation_token)
-        .await?;
+        .get_unified_diff()
+        .ok()
+        .flatten();
+    let message = tracker.summary(hit, &history, unified_diff.as_deref());
+    warn!("{message}");
+    sess.send_event(
+        turn_context,
+        EventMsg::Error(ErrorEvent {
+            message,
+            codex_error_info: Some(CodexErrorInfo::RunLimitReached),
+        }),
+    )
+    .await;
+}
+
+/// The request a turn sends for `input`: the tools, the instructions, and
+/// the context that is sent with every request but never recorded.
+async fn build_prompt(
+    sess: &Session,
+    turn_context: &TurnContext,
+    mcp_tools: HashMap<String, ToolInfo>,
+    input: Vec<ResponseItem>,
+) -> (Arc<ToolRouter>, Prompt) {
     let router = Arc::new(ToolRouter::from_config(
         &turn_context.tools_config,
         Some(
@@ -2115,13 +3130,55 @@ async fn run_turn(
             base_instructions = Some(new_instructions);
         }
     }
+    // The plan, pinned files, and the repo map are sent again for every turn
+    // and never recorded in the history, so compaction and truncation
+    // cannot drop them.
+    let mut input = input;
+    if let Some(plan) = sess.plan_item().await {
+        input.insert(0, plan);
+    }
+    if let Some(pinned) = sess.pinned_files_item(turn_context).await {
+        input.insert(0, pinned);
+    }
+    if let Some(repo_map) = sess.repo_map_item(turn_context).await {
+        input.insert(0, repo_map);
+    }
+    let mut output_schema = turn_context.final_output_json_schema.clone();
+    if let Some(schema) = output_schema.as_ref()
+        && !output_schema::enforced_by(&turn_context.client.get_provider())
+    {
+        input.insert(0, output_schema::instructions_item(schema));
+        output_schema = None;
+    }
     let prompt = Prompt {
         input,
         tools: router.specs(),
         parallel_tool_calls,
         base_instructions_override: base_instructions,
-        output_schema: turn_context.final_output_json_schema.clone(),
+        output_schema,
     };
+    (router, prompt)
+}
+
+async fn run_turn(
+    sess: Arc<Session>,
+    turn_context: Arc<TurnContext>,
+    turn_diff_tracker: SharedTurnDiffTracker,
+    input: Vec<ResponseItem>,
+    cancellation_token: CancellationToken,
+) -> CodexResult<Vec<ProcessedResponseItem>> {
+    let mcp_tools = sess
+        .services
+        .mcp_connection_manager
+        .read()
+        .await
+        .list_all_tools()
+        .or_cancel(&cancellation_token)
+        .await?;
+    if sess.enabled(Feature::DetectExternalEdits) {
+        sess.services.external_edits.watch(&turn_context.cwd);
+    }
+    let (router, prompt) = build_prompt(&sess, &turn_context, mcp_tools, input).await;
 
     let mut retries = 0;
     loop {
@@ -2151,25 +3208,36 @@ async fn run_turn(
                 return Err(e);
             }
             Err(CodexErr::UsageLimitReached(e)) => {
+                sess.record_limit_hit(&turn_context, &e).await;
                 let rate_limits = e.rate_limits.clone();
                 if let Some(rate_limits) = rate_limits {
                     sess.update_rate_limits(&turn_context, rate_limits).await;
                 }
+                if turn_context.client.config().limits.on_cap == LimitCapAction::Wait {
+                    let recovers_at = e
+                        .recovers_at()
+                        .unwrap_or_else(|| sess.services.auth_manager.estimated_limit_reset());
+                    sess.wait_for_limit_reset(&turn_context, recovers_at)
+                        .or_cancel(&cancellation_token)
+                        .await?;
+                    continue;
+                }
                 return Err(CodexErr::UsageLimitReached(e));
             }
             Err(CodexErr::UsageNotIncluded) => return Err(CodexErr::UsageNotIncluded),
             Err(e @ CodexErr::QuotaExceeded) => return Err(e),
             Err(e @ CodexErr::InvalidImageRequest()) => return Err(e),
             Err(e @ CodexErr::InvalidRequest(_)) => return Err(e),
+            Err(e @ CodexErr::RequestTooLarge(_)) => return Err(e),
             Err(e @ CodexErr::RefreshTokenFailed(_)) => return Err(e),
             Err(e) => {
-                // Use the configured provider-specific stream retry budget.
-                let max_retries = turn_context.client.get_provider().stream_max_retries();
+                // Use the configured stream retry budget.
+                let max_retries = turn_context.client.stream_max_retries();
                 if retries < max_retries {
                     retries += 1;
                     let delay = match e {
                         CodexErr::Stream(_, Some(delay)) => delay,
-                        _ => backoff(retries),
+                        _ => turn_context.client.stream_retry_delay(retries),
                     };
                     warn!(
                         "stream disconnected - retrying turn ({retries}/{max_retries} in {delay:?})...",
@@ -2223,12 +3291,14 @@ async fn try_run_turn(
     });
 
     sess.persist_rollout_items(&[rollout_item]).await;
-    let mut stream = turn_context
+    let stream = turn_context
         .client
         .clone()
         .stream(prompt)
         .or_cancel(&cancellation_token)
-        .await??;
+        .await;
+    sess.send_auth_switch_events(&turn_context).await;
+    let mut stream = stream??;
 
     let tool_runtime = ToolCallRuntime::new(
         Arc::clone(&router),
@@ -2490,6 +3560,7 @@ mod tests {
     use super::*;
     use crate::config::ConfigOverrides;
     use crate::config::ConfigToml;
+    use crate::config::types::NotifyHooks;
     use crate::exec::ExecToolCallOutput;
     use crate::shell::default_user_shell;
     use crate::tools::format_exec_output_str;
@@ -2500,6 +3571,7 @@ mod tests {
     use crate::protocol::RateLimitSnapshot;
     use crate::protocol::RateLimitWindow;
     use crate::protocol::ResumedHistory;
+    use crate::protocol::ReviewTarget;
     use crate::state::TaskKind;
     use crate::tasks::SessionTask;
     use crate::tasks::SessionTaskContext;
@@ -2608,6 +3680,7 @@ mod tests {
                 unlimited: false,
                 balance: Some("10.00".to_string()),
             }),
+            request_limits: None,
         };
         state.set_rate_limits(initial.clone());
 
@@ -2623,6 +3696,7 @@ mod tests {
                 resets_at: Some(1_900),
             }),
             credits: None,
+            request_limits: None,
         };
         state.set_rate_limits(update.clone());
 
@@ -2632,6 +3706,7 @@ mod tests {
                 primary: update.primary.clone(),
                 secondary: update.secondary,
                 credits: initial.credits,
+                request_limits: None,
             })
         );
     }
@@ -2737,6 +3812,36 @@ mod tests {
         assert_eq!(expected, got);
     }
 
+    #[test]
+    fn images_become_text_for_providers_without_image_input() {
+        let input = vec![
+            UserInput::Text {
+                text: "fix this layout".to_string(),
+            },
+            UserInput::LocalImage {
+                path: PathBuf::from("/tmp/screenshot.png"),
+            },
+            UserInput::Image {
+                image_url: "data:image/png;base64,AAAA".to_string(),
+            },
+        ];
+        assert_eq!(
+            images_as_text(input),
+            vec![
+                UserInput::Text {
+                    text: "fix this layout".to_string(),
+                },
+                UserInput::Text {
+                    text: "The user attached the image `/tmp/screenshot.png`, but this model cannot view images.".to_string(),
+                },
+                UserInput::Text {
+                    text: "The user attached an image, but this model cannot view images."
+                        .to_string(),
+                },
+            ]
+        );
+    }
+
     fn text_block(s: &str) -> ContentBlock {
         ContentBlock::TextContent(TextContent {
             annotations: None,
@@ -2799,7 +3904,13 @@ mod tests {
             mcp_connection_manager: Arc::new(RwLock::new(McpConnectionManager::default())),
             mcp_startup_cancellation_token: CancellationToken::new(),
             unified_exec_manager: UnifiedExecSessionManager::default(),
-            notifier: UserNotifier::new(None),
+            notifier: UserNotifier::new(None, NotifyHooks::default()),
+            hooks: HookRunner::default(),
+            plugin_tools: Vec::new(),
+            repo_map: Mutex::new(RepoMap::default()),
+            external_edits: ExternalEdits::default(),
+            sub_agents: SubAgents::default(),
+            redactor: Redactor::new(&config.redaction),
             rollout: Mutex::new(None),
             user_shell: default_user_shell(),
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
@@ -2877,7 +3988,13 @@ mod tests {
             mcp_connection_manager: Arc::new(RwLock::new(McpConnectionManager::default())),
             mcp_startup_cancellation_token: CancellationToken::new(),
             unified_exec_manager: UnifiedExecSessionManager::default(),
-            notifier: UserNotifier::new(None),
+            notifier: UserNotifier::new(None, NotifyHooks::default()),
+            hooks: HookRunner::default(),
+            plugin_tools: Vec::new(),
+            repo_map: Mutex::new(RepoMap::default()),
+            external_edits: ExternalEdits::default(),
+            sub_agents: SubAgents::default(),
+            redactor: Redactor::new(&config.redaction),
             rollout: Mutex::new(None),
             user_shell: default_user_shell(),
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
@@ -3028,8 +4145,12 @@ mod tests {
         let input = vec![UserInput::Text {
             text: "start review".to_string(),
         }];
-        sess.spawn_task(Arc::clone(&tc), input, ReviewTask::new())
-            .await;
+        sess.spawn_task(
+            Arc::clone(&tc),
+            input,
+            ReviewTask::new(ReviewTarget::UncommittedChanges),
+        )
+        .await;
 
         sess.abort_all_tasks(TurnAbortReason::Interrupted).await;
 
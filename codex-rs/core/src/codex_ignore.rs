@@ -0,0 +1,103 @@
+//! `.codexignore` files, in gitignore syntax, keep paths out of the model's
+//! view: the repo map, code search, pinned files, and the file tools skip
+//! what they match. Like `.gitignore`, a `.codexignore` applies to the
+//! directory it is in and everything below it, and a deeper file takes
+//! precedence over a shallower one; files above the repository root are not
+//! read.
+//!
+//! Shell commands are not filtered: a command the model runs can still read
+//! an ignored file.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use ignore::Match;
+use ignore::gitignore::Gitignore;
+use tracing::warn;
+
+pub(crate) const CODEX_IGNORE_FILENAME: &str = ".codexignore";
+
+/// Answers whether paths are ignored, reading each directory's
+/// `.codexignore` once.
+#[derive(Default)]
+pub(crate) struct CodexIgnore {
+    matchers: HashMap<PathBuf, Option<Gitignore>>,
+}
+
+impl CodexIgnore {
+    /// Whether a `.codexignore` in `path`'s ancestors excludes it. `path`
+    /// must be absolute.
+    pub(crate) fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+        for dir in path.ancestors().skip(1) {
+            if let Some(matcher) = self.matcher(dir) {
+                match matcher.matched_path_or_any_parents(path, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+            // Like git, stop at the repository root.
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+        false
+    }
+
+    fn matcher(&mut self, dir: &Path) -> Option<&Gitignore> {
+        self.matchers
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                let file = dir.join(CODEX_IGNORE_FILENAME);
+                if !file.is_file() {
+                    return None;
+                }
+                let (matcher, err) = Gitignore::new(&file);
+                if let Some(err) = err {
+                    warn!("ignoring part of {}: {err}", file.display());
+                }
+                Some(matcher)
+            })
+            .as_ref()
+    }
+}
+
+/// Whether `path` is excluded by a `.codexignore`; see
+/// [`CodexIgnore::is_ignored`].
+pub(crate) fn is_ignored(path: &Path) -> bool {
+    CodexIgnore::default().is_ignored(path, path.is_dir())
+}
+
+/// The error a file tool returns for an ignored path.
+pub(crate) fn ignored_error(path: &Path) -> String {
+    format!(
+        "`{}` is excluded by {CODEX_IGNORE_FILENAME} and cannot be read",
+        path.display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deeper_codexignore_files_take_precedence() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let root = dir.path();
+        std::fs::create_dir_all(root.join(".git")).expect("mkdir");
+        std::fs::create_dir_all(root.join("secrets")).expect("mkdir");
+        std::fs::create_dir_all(root.join("fixtures")).expect("mkdir");
+        std::fs::write(root.join(".codexignore"), "secrets/\n*.json\n*.bundle.js\n")
+            .expect("write");
+        std::fs::write(root.join("fixtures/.codexignore"), "!small.json\n").expect("write");
+
+        let mut ignore = CodexIgnore::default();
+        assert!(ignore.is_ignored(&root.join("secrets"), true));
+        assert!(ignore.is_ignored(&root.join("secrets/prod.env"), false));
+        assert!(ignore.is_ignored(&root.join("web/app.bundle.js"), false));
+        assert!(ignore.is_ignored(&root.join("fixtures/huge.json"), false));
+        assert!(!ignore.is_ignored(&root.join("fixtures/small.json"), false));
+        assert!(!ignore.is_ignored(&root.join("src/main.rs"), false));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::auth::AuthCredentialsStoreMode;
 use crate::config::CONFIG_TOML_FILE;
 use crate::config::types::McpServerConfig;
 use crate::config::types::Notice;
@@ -121,8 +122,17 @@ mod document_helpers {
                 bearer_token_env_var,
                 http_headers,
                 env_http_headers,
+            }
+            | McpServerTransportConfig::Sse {
+                url,
+                bearer_token_env_var,
+                http_headers,
+                env_http_headers,
             } => {
                 entry["url"] = value(url.clone());
+                if matches!(config.transport, McpServerTransportConfig::Sse { .. }) {
+                    entry["transport"] = value("sse");
+                }
                 if let Some(env_var) = bearer_token_env_var {
                     entry["bearer_token_env_var"] = value(env_var.clone());
                 }
@@ -410,6 +420,36 @@ pub fn apply_blocking(
         Err(err) => return Err(err.into()),
     };
 
+    let Some(updated) = apply_to_str(&serialized, profile, edits)? else {
+        return Ok(());
+    };
+
+    std::fs::create_dir_all(codex_home).with_context(|| {
+        format!(
+            "failed to create Codex home directory at {}",
+            codex_home.display()
+        )
+    })?;
+
+    let tmp = NamedTempFile::new_in(codex_home)?;
+    std::fs::write(tmp.path(), updated).with_context(|| {
+        format!(
+            "failed to write temporary config file at {}",
+            tmp.path().display()
+        )
+    })?;
+    tmp.persist(config_path)?;
+
+    Ok(())
+}
+
+/// Apply edits to serialized config contents, keeping comments and formatting.
+/// Returns `None` when no edit changed the document.
+pub fn apply_to_str(
+    serialized: &str,
+    profile: Option<&str>,
+    edits: &[ConfigEdit],
+) -> anyhow::Result<Option<String>> {
     let doc = if serialized.is_empty() {
         DocumentMut::new()
     } else {
@@ -429,27 +469,7 @@ pub fn apply_blocking(
         mutated |= document.apply(edit)?;
     }
 
-    if !mutated {
-        return Ok(());
-    }
-
-    std::fs::create_dir_all(codex_home).with_context(|| {
-        format!(
-            "failed to create Codex home directory at {}",
-            codex_home.display()
-        )
-    })?;
-
-    let tmp = NamedTempFile::new_in(codex_home)?;
-    std::fs::write(tmp.path(), document.doc.to_string()).with_context(|| {
-        format!(
-            "failed to write temporary config file at {}",
-            tmp.path().display()
-        )
-    })?;
-    tmp.persist(config_path)?;
-
-    Ok(())
+    Ok(mutated.then(|| document.doc.to_string()))
 }
 
 /// Persist edits asynchronously by offloading the blocking writer.
@@ -555,6 +575,20 @@ impl ConfigEditsBuilder {
         self
     }
 
+    /// Set `cli_auth_credentials_store`, where CLI credentials are kept.
+    pub fn set_cli_auth_credentials_store(mut self, mode: AuthCredentialsStoreMode) -> Self {
+        let mode = match mode {
+            AuthCredentialsStoreMode::File => "file",
+            AuthCredentialsStoreMode::Keyring => "keyring",
+            AuthCredentialsStoreMode::Auto => "auto",
+        };
+        self.edits.push(ConfigEdit::SetPath {
+            segments: vec!["cli_auth_credentials_store".to_string()],
+            value: value(mode),
+        });
+        self
+    }
+
     /// Apply edits on a blocking thread.
     pub fn apply_blocking(self) -> anyhow::Result<()> {
         apply_blocking(&self.codex_home, self.profile.as_deref(), &self.edits)
@@ -923,6 +957,23 @@ existing = "value"
             },
         );
 
+        servers.insert(
+            "sse".to_string(),
+            McpServerConfig {
+                transport: McpServerTransportConfig::Sse {
+                    url: "https://example.com/sse".to_string(),
+                    bearer_token_env_var: None,
+                    http_headers: None,
+                    env_http_headers: None,
+                },
+                enabled: true,
+                startup_timeout_sec: None,
+                tool_timeout_sec: None,
+                enabled_tools: None,
+                disabled_tools: None,
+            },
+        );
+
         apply_blocking(
             codex_home,
             None,
@@ -942,6 +993,10 @@ disabled_tools = [\"forbidden\"]
 [mcp_servers.http.http_headers]
 Z-Header = \"z\"
 
+[mcp_servers.sse]
+url = \"https://example.com/sse\"
+transport = \"sse\"
+
 [mcp_servers.stdio]
 command = \"cmd\"
 args = [\"--flag\"]
@@ -975,6 +1030,35 @@ B = \"2\"
         );
     }
 
+    #[test]
+    fn apply_to_str_keeps_comments() {
+        let original = "# my model\nmodel = \"gpt-5.1\" # pinned\n";
+
+        let updated = apply_to_str(
+            original,
+            None,
+            &[ConfigEdit::SetPath {
+                segments: vec!["approval_policy".to_string()],
+                value: value("never"),
+            }],
+        )
+        .expect("apply");
+
+        assert_eq!(
+            updated.as_deref(),
+            Some("# my model\nmodel = \"gpt-5.1\" # pinned\napproval_policy = \"never\"\n")
+        );
+        let unchanged = apply_to_str(
+            original,
+            None,
+            &[ConfigEdit::ClearPath {
+                segments: vec!["missing".to_string()],
+            }],
+        )
+        .expect("apply");
+        assert_eq!(unchanged, None);
+    }
+
     #[test]
     fn blocking_set_path_updates_notifications() {
         let tmp = tempdir().expect("tmpdir");
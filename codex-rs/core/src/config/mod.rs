@@ -1,16 +1,42 @@
 use crate::auth::AuthCredentialsStoreMode;
+use crate::auth::FailoverEntry;
+use crate::config::types::Budget;
+use crate::config::types::BudgetAction;
+use crate::config::types::CustomToolConfig;
 use crate::config::types::DEFAULT_OTEL_ENVIRONMENT;
+use crate::config::types::FetchUrl;
 use crate::config::types::History;
+use crate::config::types::Hooks;
+use crate::config::types::LimitCapAction;
+use crate::config::types::Limits;
 use crate::config::types::McpServerConfig;
+use crate::config::types::ModelPrice;
 use crate::config::types::Notice;
 use crate::config::types::Notifications;
+use crate::config::types::NotifyHooks;
+use crate::config::types::NotifyTable;
+use crate::config::types::NotifyTarget;
+use crate::config::types::NotifyToml;
 use crate::config::types::OtelConfig;
 use crate::config::types::OtelConfigToml;
 use crate::config::types::OtelExporterKind;
+use crate::config::types::Policy;
+use crate::config::types::Preflight;
+use crate::config::types::Privacy;
 use crate::config::types::ReasoningSummaryFormat;
+use crate::config::types::Redaction;
+use crate::config::types::Retry;
+use crate::config::types::ReviewCacheStorage;
+use crate::config::types::ReviewCacheToml;
+use crate::config::types::RunLimits;
 use crate::config::types::SandboxWorkspaceWrite;
+use crate::config::types::SearchCode;
 use crate::config::types::ShellEnvironmentPolicy;
 use crate::config::types::ShellEnvironmentPolicyToml;
+use crate::config::types::SubAgents;
+use crate::config::types::SystemPrompt;
+use crate::config::types::SystemPromptMode;
+use crate::config::types::TimestampFormat;
 use crate::config::types::Tui;
 use crate::config::types::UriBasedFileOpener;
 use crate::config_loader::LoadedConfigLayers;
@@ -25,15 +51,21 @@ use crate::git_info::resolve_root_git_project_for_trust;
 use crate::model_family::ModelFamily;
 use crate::model_family::derive_default_model_family;
 use crate::model_family::find_family_for_model;
+use crate::model_provider_info::ANTHROPIC_PROVIDER_ID;
+use crate::model_provider_info::AZURE_PROVIDER_ID;
+use crate::model_provider_info::BEDROCK_PROVIDER_ID;
+use crate::model_provider_info::GEMINI_PROVIDER_ID;
 use crate::model_provider_info::LMSTUDIO_OSS_PROVIDER_ID;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::OLLAMA_OSS_PROVIDER_ID;
+use crate::model_provider_info::VERTEX_PROVIDER_ID;
 use crate::model_provider_info::built_in_model_providers;
 use crate::openai_model_info::get_model_info;
 use crate::project_doc::DEFAULT_PROJECT_DOC_FILENAME;
 use crate::project_doc::LOCAL_PROJECT_DOC_FILENAME;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
+use crate::tools::spec::custom_tool_to_openai_tool;
 use crate::util::resolve_path;
 use codex_app_server_protocol::Tools;
 use codex_app_server_protocol::UserSavedConfig;
@@ -59,7 +91,9 @@ use toml::Value as TomlValue;
 use toml_edit::DocumentMut;
 
 pub mod edit;
+pub mod policy;
 pub mod profile;
+pub mod project;
 pub mod types;
 
 pub const OPENAI_DEFAULT_MODEL: &str = "gpt-5.1-codex";
@@ -71,6 +105,9 @@ pub const GPT_5_CODEX_MEDIUM_MODEL: &str = "gpt-5.1-codex";
 /// the context window.
 pub(crate) const PROJECT_DOC_MAX_BYTES: usize = 32 * 1024; // 32 KiB
 
+/// Read-only tool calls from one model response that may run at once.
+pub(crate) const DEFAULT_MAX_PARALLEL_TOOL_CALLS: usize = 8;
+
 pub const CONFIG_TOML_FILE: &str = "config.toml";
 
 /// Application configuration loaded from disk and merged with overrides.
@@ -82,6 +119,10 @@ pub struct Config {
     /// Model used specifically for review sessions. Defaults to "gpt-5.1-codex-max".
     pub review_model: String,
 
+    /// Where review findings are cached, keyed by the reviewed changes,
+    /// config, and model. `None` disables the cache.
+    pub review_cache: Option<ReviewCacheStorage>,
+
     pub model_family: ModelFamily,
 
     /// Size of the context window for the model, in tokens.
@@ -154,6 +195,10 @@ pub struct Config {
     /// If unset the feature is disabled.
     pub notify: Option<Vec<String>>,
 
+    /// Hooks run when a usage limit is hit or credentials are switched, from
+    /// the table form of `notify`.
+    pub notify_hooks: NotifyHooks,
+
     /// TUI notifications preference. When set, the TUI will send OSC 9 notifications on approvals
     /// and turn completions when not focused.
     pub tui_notifications: Notifications,
@@ -164,6 +209,9 @@ pub struct Config {
     /// Show startup tooltips in the TUI welcome screen.
     pub show_tooltips: bool,
 
+    /// How the TUI renders timestamps (reset times, session ages).
+    pub tui_timestamp_format: TimestampFormat,
+
     /// The directory that should be treated as the current working directory
     /// for the session. All relative paths inside the business-logic layer are
     /// resolved against this path.
@@ -178,6 +226,10 @@ pub struct Config {
     /// Definition for MCP servers that Codex can reach out to for tool calls.
     pub mcp_servers: HashMap<String, McpServerConfig>,
 
+    /// Tools from `[custom_tools]`, offered to the model next to the
+    /// built-in ones.
+    pub custom_tools: HashMap<String, CustomToolConfig>,
+
     /// Preferred store for MCP OAuth credentials.
     /// keyring: Use an OS-specific keyring service.
     ///          Credentials stored in the keyring will only be readable by Codex unless the user explicitly grants access via OS-level keyring access.
@@ -199,6 +251,10 @@ pub struct Config {
     /// Token budget applied when storing tool/function outputs in the context manager.
     pub tool_output_token_limit: Option<usize>,
 
+    /// Maximum number of read-only tool calls from one model response that
+    /// run concurrently.
+    pub max_parallel_tool_calls: usize,
+
     /// Directory containing all Codex state (defaults to `~/.codex` but can be
     /// overridden by the `CODEX_HOME` environment variable).
     pub codex_home: PathBuf,
@@ -261,6 +317,14 @@ pub struct Config {
     /// The active profile name used to derive this `Config` (if any).
     pub active_profile: Option<String>,
 
+    /// Key/value labels, such as `team = "payments"`, attached to this
+    /// session's telemetry events and rollout for spend attribution.
+    pub tags: BTreeMap<String, String>,
+
+    /// Ordered `fallback` chain that requests move down when the active
+    /// provider is limited or failing. Empty when not configured.
+    pub fallback: Vec<FailoverEntry>,
+
     /// The currently active project config, resolved by checking if cwd:
     /// is (1) part of a git repo, (2) a git worktree, or (3) just using the cwd
     pub active_project: ProjectConfig,
@@ -283,6 +347,69 @@ pub struct Config {
 
     /// OTEL configuration (exporter type, endpoint, headers, etc.).
     pub otel: crate::config::types::OtelConfig,
+
+    /// Privacy settings; see [`Privacy`].
+    pub privacy: Privacy,
+
+    /// The project's `.codex/config.toml`, when it was applied on top of
+    /// `~/.codex/config.toml`; see [`project`].
+    pub project_config: Option<PathBuf>,
+
+    /// The administrator's policy; see [`policy`].
+    pub policy: Policy,
+
+    /// Per-model prices from `[model_prices]`, used over the built-in price
+    /// table when estimating costs.
+    pub model_prices: HashMap<String, ModelPrice>,
+
+    /// Spending caps; see [`crate::budget`].
+    pub budget: Budget,
+
+    /// Commands run around sessions, tool calls, and patches; see
+    /// [`crate::hooks`].
+    pub hooks: Hooks,
+
+    /// Quota check before a `codex exec` run.
+    pub preflight: Preflight,
+
+    /// Secrets kept out of what the model sees; see [`crate::redact`].
+    pub redaction: Redaction,
+
+    /// What to do when a usage limit is hit.
+    pub limits: Limits,
+
+    /// Limits on each task of the session; only set by `codex exec`.
+    pub run_limits: RunLimits,
+
+    /// Set by `codex exec --dry-run`: the session only answers `Op::DryRun`,
+    /// so it runs no hooks and starts no MCP servers.
+    pub dry_run: bool,
+
+    /// How failed model requests are retried.
+    pub retry: Retry,
+
+    /// Which pages the `fetch_url` tool may download.
+    pub fetch_url: FetchUrl,
+
+    /// Embedding model and provider for the `search_code` tool.
+    pub search_code: SearchCode,
+
+    /// How many sub-agents may run and how many tokens each may use.
+    pub sub_agents: SubAgents,
+
+    /// Files and directories kept in context for every turn; see
+    /// [`crate::pinned_files`]. Relative paths are resolved against the
+    /// session's working directory.
+    pub pinned_files: Vec<PathBuf>,
+
+    /// Directories besides `cwd` the session spans, from `--root`. They are
+    /// writable under `workspace-write`, and each contributes its own
+    /// `AGENTS.md` and git repository.
+    pub additional_roots: Vec<PathBuf>,
+
+    /// Directory, kept in sync between machines, through which the limit
+    /// and usage history are shared; see [`crate::shared_state`].
+    pub shared_state_dir: Option<PathBuf>,
 }
 
 impl Config {
@@ -292,22 +419,58 @@ impl Config {
     ) -> std::io::Result<Self> {
         let codex_home = find_codex_home()?;
 
-        let root_value = load_resolved_config(
+        let layers = load_config_layers_with_overrides(
             &codex_home,
-            cli_overrides,
             crate::config_loader::LoaderOverrides::default(),
         )
         .await?;
+        let cwd = match overrides.cwd.as_deref() {
+            Some(cwd) if cwd.is_absolute() => cwd.to_path_buf(),
+            Some(cwd) => std::env::current_dir()?.join(cwd),
+            None => std::env::current_dir()?,
+        };
+        let (mut root_value, project_config) =
+            apply_overlays_with_project_config(layers, cli_overrides, &cwd, &codex_home)?;
+        policy::apply_policy_file(&mut root_value, &policy::policy_file_path())?;
 
         let cfg: ConfigToml = root_value.try_into().map_err(|e| {
             tracing::error!("Failed to deserialize overridden config: {e}");
             std::io::Error::new(std::io::ErrorKind::InvalidData, e)
         })?;
 
-        Self::load_from_base_config_with_overrides(cfg, overrides, codex_home)
+        let mut config = Self::load_from_base_config_with_overrides(cfg, overrides, codex_home)?;
+        config.project_config = project_config;
+        Ok(config)
     }
 }
 
+/// Like [`apply_overlays`], with the project's `.codex/config.toml` merged
+/// over `~/.codex/config.toml` when the project is trusted. Returns the path
+/// of the project config that was applied, if any.
+fn apply_overlays_with_project_config(
+    mut layers: LoadedConfigLayers,
+    cli_overrides: Vec<(String, TomlValue)>,
+    cwd: &Path,
+    codex_home: &Path,
+) -> std::io::Result<(TomlValue, Option<PathBuf>)> {
+    let root_value = apply_overlays(layers.clone(), cli_overrides.clone());
+    let Some(path) = project::find_project_config(cwd, codex_home) else {
+        return Ok((root_value, None));
+    };
+    // Trust is read from the user's config, never from the project file.
+    let cfg: Option<ConfigToml> = root_value.clone().try_into().ok();
+    let trusted = cfg
+        .and_then(|cfg| cfg.get_active_project(cwd))
+        .is_some_and(|project| project.is_trusted());
+    if !trusted {
+        tracing::info!("ignoring {}: the project is not trusted", path.display());
+        return Ok((root_value, None));
+    }
+    let project_value = project::load_project_config(&path)?;
+    merge_toml_values(&mut layers.base, &project_value);
+    Ok((apply_overlays(layers, cli_overrides), Some(path)))
+}
+
 pub async fn load_config_as_toml_with_cli_overrides(
     codex_home: &Path,
     cli_overrides: Vec<(String, TomlValue)>,
@@ -333,7 +496,9 @@ async fn load_resolved_config(
     overrides: crate::config_loader::LoaderOverrides,
 ) -> std::io::Result<TomlValue> {
     let layers = load_config_layers_with_overrides(codex_home, overrides).await?;
-    Ok(apply_overlays(layers, cli_overrides))
+    let mut root_value = apply_overlays(layers, cli_overrides);
+    policy::apply_policy_file(&mut root_value, &policy::policy_file_path())?;
+    Ok(root_value)
 }
 
 fn apply_overlays(
@@ -394,6 +559,49 @@ fn ensure_no_inline_bearer_tokens(value: &TomlValue) -> std::io::Result<()> {
     Ok(())
 }
 
+/// Longest `retry_delay_hours` accepted under `[limits]`: a month.
+const MAX_RETRY_DELAY_HOURS: f64 = 24.0 * 31.0;
+
+/// Checks the `[limits]` values serde cannot, so a bad retry delay is
+/// reported at load rather than when a limit is hit.
+pub(crate) fn validate_limits(limits: &Limits) -> std::io::Result<()> {
+    let invalid = |message: String| std::io::Error::new(ErrorKind::InvalidInput, message);
+    if limits.on_cap == LimitCapAction::DowngradeModel && limits.downgrade_model.is_none() {
+        return Err(invalid(
+            "`limits.on_cap = \"downgrade_model\"` requires `limits.downgrade_model`".to_string(),
+        ));
+    }
+    let delays = std::iter::once((
+        "limits.retry_delay_hours".to_string(),
+        limits.retry_delay_hours,
+    ))
+    .chain(limits.plans.iter().map(|(plan, plan_limits)| {
+        (
+            format!("limits.plans.{plan}.retry_delay_hours"),
+            plan_limits.retry_delay_hours,
+        )
+    }));
+    for (key, hours) in delays {
+        if let Some(hours) = hours
+            && !(hours > 0.0 && hours <= MAX_RETRY_DELAY_HOURS)
+        {
+            return Err(invalid(format!(
+                "`{key}` must be more than 0 and at most {MAX_RETRY_DELAY_HOURS} hours, got {hours}"
+            )));
+        }
+    }
+    if let Some(plan) = limits
+        .plans
+        .keys()
+        .find(|plan| *plan != &plan.to_ascii_lowercase())
+    {
+        return Err(invalid(format!(
+            "`limits.plans.{plan}`: plan names are lowercase, such as `pro`"
+        )));
+    }
+    Ok(())
+}
+
 pub(crate) fn set_project_trust_level_inner(
     doc: &mut DocumentMut,
     project_path: &Path,
@@ -570,6 +778,9 @@ pub struct ConfigToml {
     /// Review model override used by the `/review` feature.
     pub review_model: Option<String>,
 
+    /// Cache for review findings; see `[review_cache]`.
+    pub review_cache: Option<ReviewCacheToml>,
+
     /// Provider to use from the model_providers map.
     pub model_provider: Option<String>,
 
@@ -591,9 +802,10 @@ pub struct ConfigToml {
     /// Sandbox configuration to apply if `sandbox` is `WorkspaceWrite`.
     pub sandbox_workspace_write: Option<SandboxWorkspaceWrite>,
 
-    /// Optional external command to spawn for end-user notifications.
+    /// Optional external command to spawn for end-user notifications, or a
+    /// table of hooks for turn completion, limit hits, and auth switches.
     #[serde(default)]
-    pub notify: Option<Vec<String>>,
+    pub notify: Option<NotifyToml>,
 
     /// System instructions.
     pub instructions: Option<String>,
@@ -602,6 +814,9 @@ pub struct ConfigToml {
     #[serde(default)]
     pub developer_instructions: Option<String>,
 
+    /// Replaces or extends the built-in system prompt.
+    pub system_prompt: Option<SystemPrompt>,
+
     /// Compact prompt used for history compaction.
     pub compact_prompt: Option<String>,
 
@@ -624,6 +839,10 @@ pub struct ConfigToml {
     #[serde(default)]
     pub mcp_servers: HashMap<String, McpServerConfig>,
 
+    /// Tools that run a command, keyed by the name the model calls them by.
+    #[serde(default)]
+    pub custom_tools: HashMap<String, CustomToolConfig>,
+
     /// Preferred backend for storing MCP OAuth credentials.
     /// keyring: Use an OS-specific keyring service.
     ///          https://github.com/openai/codex/blob/main/codex-rs/rmcp-client/src/oauth.rs#L2
@@ -645,6 +864,10 @@ pub struct ConfigToml {
     /// Token budget applied when storing tool/function outputs in the context manager.
     pub tool_output_token_limit: Option<usize>,
 
+    /// Maximum number of read-only tool calls from one model response that
+    /// run concurrently. Defaults to 8; 1 runs them one at a time.
+    pub max_parallel_tool_calls: Option<usize>,
+
     /// Profile to use from the `profiles` map.
     pub profile: Option<String>,
 
@@ -707,6 +930,64 @@ pub struct ConfigToml {
     /// OTEL configuration.
     pub otel: Option<crate::config::types::OtelConfigToml>,
 
+    /// Key/value labels attached to every session, e.g. `team = "payments"`.
+    /// `--tag key=value` adds to or overrides these.
+    pub tags: Option<BTreeMap<String, String>>,
+
+    /// Ordered failover chain, e.g. `["chatgpt", "openai_api", "ollama"]`.
+    /// `chatgpt` and `openai_api` select the OpenAI provider with that kind
+    /// of credentials; other entries are `model_providers` keys.
+    pub fallback: Option<Vec<String>>,
+
+    /// Privacy settings, including the non-provider network kill switch.
+    pub privacy: Option<Privacy>,
+
+    /// Administrator policy on authentication and providers; merged from
+    /// `/etc/codex/policy.toml` when present.
+    pub policy: Option<Policy>,
+
+    /// Prices, in US dollars per million tokens, keyed by model slug.
+    #[serde(default)]
+    pub model_prices: HashMap<String, ModelPrice>,
+
+    /// Spending caps on estimated cost, per session, day, and month.
+    pub budget: Option<Budget>,
+
+    /// Commands run at session start and end, around tool calls, and before
+    /// patches are applied.
+    pub hooks: Option<Hooks>,
+
+    /// Quota check before a `codex exec` run.
+    pub preflight: Option<Preflight>,
+
+    /// Secret patterns and environment variables redacted from tool output
+    /// and pinned files.
+    pub redaction: Option<Redaction>,
+
+    /// What to do when a usage limit is hit.
+    pub limits: Option<Limits>,
+
+    /// How failed model requests and dropped response streams are retried.
+    pub retry: Option<Retry>,
+
+    /// Domain lists and size limit for the `fetch_url` tool.
+    pub fetch_url: Option<FetchUrl>,
+
+    /// Embedding model for the `search_code` tool's semantic index.
+    pub search_code: Option<SearchCode>,
+
+    /// Limits for sub-agents started with `spawn_agent`.
+    pub sub_agents: Option<SubAgents>,
+
+    /// Files and directories to keep in context for every turn, e.g.
+    /// `["docs/architecture.md", "schema.sql"]`.
+    pub pinned_files: Option<Vec<PathBuf>>,
+
+    /// Directory, e.g. inside a synced folder, through which machines share
+    /// the usage limits they hit and their usage. Relative paths are
+    /// resolved against `CODEX_HOME`.
+    pub shared_state_dir: Option<PathBuf>,
+
     /// Tracks whether the Windows onboarding screen has been acknowledged.
     pub windows_wsl_setup_acknowledged: Option<bool>,
 
@@ -867,23 +1148,22 @@ impl ConfigToml {
         None
     }
 
+    /// The profile selected by `override_profile`, `CODEX_PROFILE`, or
+    /// `profile`, from `[profiles]` or else `CODEX_HOME/profiles/<name>.toml`.
     pub fn get_config_profile(
         &self,
+        codex_home: &Path,
         override_profile: Option<String>,
     ) -> Result<ConfigProfile, std::io::Error> {
-        let profile = override_profile.or_else(|| self.profile.clone());
+        let profile = override_profile
+            .or_else(profile_from_env)
+            .or_else(|| self.profile.clone());
 
         match profile {
-            Some(key) => {
-                if let Some(profile) = self.profiles.get(key.as_str()) {
-                    return Ok(profile.clone());
-                }
-
-                Err(std::io::Error::new(
-                    std::io::ErrorKind::NotFound,
-                    format!("config profile `{key}` not found"),
-                ))
-            }
+            Some(key) => match self.profiles.get(key.as_str()) {
+                Some(profile) => Ok(profile.clone()),
+                None => load_profile_file(codex_home, &key),
+            },
             None => Ok(ConfigProfile::default()),
         }
     }
@@ -901,6 +1181,9 @@ pub struct ConfigOverrides {
     pub config_profile: Option<String>,
     pub codex_linux_sandbox_exe: Option<PathBuf>,
     pub base_instructions: Option<String>,
+    /// From `--system-prompt-file`; takes precedence over `system_prompt` in
+    /// config.toml and the profile.
+    pub system_prompt: Option<SystemPrompt>,
     pub developer_instructions: Option<String>,
     pub compact_prompt: Option<String>,
     pub include_apply_patch_tool: Option<bool>,
@@ -909,11 +1192,15 @@ pub struct ConfigOverrides {
     pub experimental_sandbox_command_assessment: Option<bool>,
     /// Additional directories that should be treated as writable roots for this session.
     pub additional_writable_roots: Vec<PathBuf>,
+    /// From `--root`: the first is the working directory unless `cwd` is
+    /// set, and the rest become [`Config::additional_roots`].
+    pub roots: Vec<PathBuf>,
 }
 
 /// Resolves the OSS provider from CLI override, profile config, or global config.
 /// Returns `None` if no provider is configured at any level.
 pub fn resolve_oss_provider(
+    codex_home: &Path,
     explicit_provider: Option<&str>,
     config_toml: &ConfigToml,
     config_profile: Option<String>,
@@ -923,7 +1210,9 @@ pub fn resolve_oss_provider(
         Some(provider.to_string())
     } else {
         // Check profile config first, then global config
-        let profile = config_toml.get_config_profile(config_profile).ok();
+        let profile = config_toml
+            .get_config_profile(codex_home, config_profile)
+            .ok();
         if let Some(profile) = &profile {
             // Check if profile has an oss provider
             if let Some(profile_oss_provider) = &profile.oss_provider {
@@ -960,6 +1249,7 @@ impl Config {
             config_profile: config_profile_key,
             codex_linux_sandbox_exe,
             base_instructions,
+            system_prompt: system_prompt_override,
             developer_instructions,
             compact_prompt,
             include_apply_patch_tool: include_apply_patch_tool_override,
@@ -967,25 +1257,13 @@ impl Config {
             tools_web_search_request: override_tools_web_search_request,
             experimental_sandbox_command_assessment: sandbox_command_assessment_override,
             additional_writable_roots,
+            roots,
         } = overrides;
 
         let active_profile_name = config_profile_key
-            .as_ref()
-            .or(cfg.profile.as_ref())
-            .cloned();
-        let config_profile = match active_profile_name.as_ref() {
-            Some(key) => cfg
-                .profiles
-                .get(key)
-                .ok_or_else(|| {
-                    std::io::Error::new(
-                        std::io::ErrorKind::NotFound,
-                        format!("config profile `{key}` not found"),
-                    )
-                })?
-                .clone(),
-            None => ConfigProfile::default(),
-        };
+            .or_else(profile_from_env)
+            .or_else(|| cfg.profile.clone());
+        let config_profile = cfg.get_config_profile(&codex_home, active_profile_name.clone())?;
 
         let feature_overrides = FeatureOverrides {
             include_apply_patch_tool: include_apply_patch_tool_override,
@@ -993,12 +1271,21 @@ impl Config {
             experimental_sandbox_command_assessment: sandbox_command_assessment_override,
         };
 
-        let features = Features::from_config(&cfg, &config_profile, feature_overrides);
+        let privacy = cfg.privacy.unwrap_or_default();
+        let mut features = Features::from_config(&cfg, &config_profile, feature_overrides);
+        if privacy.disable_all_network_except_providers {
+            features.disable(Feature::WebSearchRequest);
+        }
         #[cfg(target_os = "windows")]
         {
             crate::safety::set_windows_sandbox_enabled(features.enabled(Feature::WindowsSandbox));
         }
 
+        let mut roots = roots.into_iter();
+        let cwd = match cwd {
+            Some(cwd) => Some(cwd),
+            None => roots.next().map(|root| canonicalize(&root).unwrap_or(root)),
+        };
         let resolved_cwd = {
             use std::env;
 
@@ -1017,13 +1304,20 @@ impl Config {
                 }
             }
         };
-        let additional_writable_roots: Vec<PathBuf> = additional_writable_roots
-            .into_iter()
-            .map(|path| {
-                let absolute = resolve_path(&resolved_cwd, &path);
-                canonicalize(&absolute).unwrap_or(absolute)
-            })
-            .collect();
+        let mut additional_roots: Vec<PathBuf> = Vec::new();
+        for root in roots {
+            // Like the first root, the others are relative to where Codex
+            // was started, not to the working directory.
+            let absolute = if root.is_absolute() {
+                root
+            } else {
+                std::env::current_dir()?.join(root)
+            };
+            let root = canonicalize(&absolute).unwrap_or(absolute);
+            if root != resolved_cwd && !additional_roots.contains(&root) {
+                additional_roots.push(root);
+            }
+        }
         let active_project = cfg
             .get_active_project(&resolved_cwd)
             .unwrap_or(ProjectConfig { trust_level: None });
@@ -1033,7 +1327,14 @@ impl Config {
             forced_auto_mode_downgraded_on_windows,
         } = cfg.derive_sandbox_policy(sandbox_mode, config_profile.sandbox_mode, &resolved_cwd);
         if let SandboxPolicy::WorkspaceWrite { writable_roots, .. } = &mut sandbox_policy {
-            for path in additional_writable_roots {
+            for path in additional_writable_roots
+                .into_iter()
+                .map(|path| {
+                    let absolute = resolve_path(&resolved_cwd, &path);
+                    canonicalize(&absolute).unwrap_or(absolute)
+                })
+                .chain(additional_roots.iter().cloned())
+            {
                 if !writable_roots.iter().any(|existing| existing == &path) {
                     writable_roots.push(path);
                 }
@@ -1064,7 +1365,34 @@ impl Config {
         let mut model_providers = built_in_model_providers();
         // Merge user-defined providers into the built-in list.
         for (key, provider) in cfg.model_providers.into_iter() {
-            model_providers.entry(key).or_insert(provider);
+            if matches!(
+                key.as_str(),
+                AZURE_PROVIDER_ID
+                    | BEDROCK_PROVIDER_ID
+                    | GEMINI_PROVIDER_ID
+                    | VERTEX_PROVIDER_ID
+                    | ANTHROPIC_PROVIDER_ID
+            ) {
+                // These ids were commonly user-defined before they were built
+                // in; keep honoring existing tables for them.
+                model_providers.insert(key, provider);
+            } else if matches!(
+                key.as_str(),
+                OLLAMA_OSS_PROVIDER_ID | LMSTUDIO_OSS_PROVIDER_ID
+            ) {
+                // A table for a local provider points it elsewhere, e.g. at a
+                // remote server behind an authenticating proxy; without a
+                // `base_url` it keeps the local default.
+                let mut provider = provider;
+                if provider.base_url.is_none()
+                    && let Some(built_in) = model_providers.get(&key)
+                {
+                    provider.base_url = built_in.base_url.clone();
+                }
+                model_providers.insert(key, provider);
+            } else {
+                model_providers.entry(key).or_insert(provider);
+            }
         }
 
         let model_provider_id = model_provider
@@ -1081,6 +1409,114 @@ impl Config {
             })?
             .clone();
 
+        let fallback = cfg
+            .fallback
+            .unwrap_or_default()
+            .iter()
+            .map(|name| {
+                FailoverEntry::resolve(name, &model_providers).ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!("Model provider `{name}` in `fallback` not found"),
+                    )
+                })
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        let budget = cfg.budget.unwrap_or_default();
+        if budget.on_exceeded == BudgetAction::Downgrade && budget.downgrade_model.is_none() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "`budget.on_exceeded = \"downgrade\"` requires `budget.downgrade_model`",
+            ));
+        }
+
+        let limits = cfg.limits.unwrap_or_default();
+        validate_limits(&limits)?;
+
+        let custom_tools = cfg.custom_tools;
+        for (name, tool) in &custom_tools {
+            let invalid = |reason: String| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("custom tool `{name}`: {reason}"),
+                )
+            };
+            if name.is_empty()
+                || name.len() > 64
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                return Err(invalid(
+                    "names may only use letters, digits, `_`, and `-`".to_string(),
+                ));
+            }
+            if tool.command.is_empty() {
+                return Err(invalid("`command` must name a program".to_string()));
+            }
+            custom_tool_to_openai_tool(name.clone(), tool)
+                .map_err(|err| invalid(format!("unsupported `parameters` schema: {err}")))?;
+        }
+
+        let hooks = cfg.hooks.unwrap_or_default();
+        for (key, hook) in [
+            ("hooks.session_start", &hooks.session_start),
+            ("hooks.session_end", &hooks.session_end),
+            ("hooks.pre_tool", &hooks.pre_tool),
+            ("hooks.post_tool", &hooks.post_tool),
+            ("hooks.pre_patch", &hooks.pre_patch),
+        ] {
+            if hook.as_ref().is_some_and(|hook| hook.command.is_empty()) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("`{key}` must name a command"),
+                ));
+            }
+        }
+
+        let (notify, notify_hooks) = match cfg.notify {
+            None => (None, NotifyHooks::default()),
+            Some(NotifyToml::Command(command)) => (Some(command), NotifyHooks::default()),
+            Some(NotifyToml::Hooks(NotifyTable {
+                on_turn_complete,
+                hooks,
+            })) => (on_turn_complete, hooks),
+        };
+        for (key, target) in [
+            ("notify.on_limit_hit", &notify_hooks.on_limit_hit),
+            ("notify.on_auth_switch", &notify_hooks.on_auth_switch),
+        ] {
+            if let Some(NotifyTarget::Webhook(url)) = target
+                && !(url.starts_with("http://") || url.starts_with("https://"))
+            {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "`{key}` must be an http:// or https:// URL, or a command as an array of strings"
+                    ),
+                ));
+            }
+        }
+
+        let review_cache = match cfg.review_cache.unwrap_or_default() {
+            ReviewCacheToml {
+                dir: Some(_),
+                s3: Some(_),
+            } => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "set either `review_cache.dir` or `review_cache.s3`, not both",
+                ));
+            }
+            ReviewCacheToml { dir: Some(dir), .. } => Some(ReviewCacheStorage::Dir(dir)),
+            ReviewCacheToml { s3: Some(s3), .. } => Some(ReviewCacheStorage::S3(s3)),
+            ReviewCacheToml {
+                dir: None,
+                s3: None,
+            } => None,
+        };
+
         let shell_environment_policy = cfg.shell_environment_policy.into();
 
         let history = cfg.history.unwrap_or_default();
@@ -1102,7 +1538,27 @@ impl Config {
                 }
             });
 
-        let forced_login_method = cfg.forced_login_method;
+        let shared_state_dir = cfg
+            .shared_state_dir
+            .as_ref()
+            .map(|dir| resolve_path(&codex_home, dir));
+
+        let policy = cfg.policy.unwrap_or_default();
+        let forced_login_method = config_profile
+            .forced_login_method
+            .or(cfg.forced_login_method);
+        policy::check_policy(
+            &policy,
+            &model_provider_id,
+            &model_provider,
+            &fallback,
+            forced_login_method,
+        )?;
+        let forced_login_method = if policy.disallow_api_key_auth {
+            Some(ForcedLoginMethod::Chatgpt)
+        } else {
+            forced_login_method
+        };
 
         let model = model
             .or(config_profile.model)
@@ -1150,7 +1606,24 @@ impl Config {
             &resolved_cwd,
             "experimental instructions file",
         )?;
-        let base_instructions = base_instructions.or(file_base_instructions);
+        let system_prompt = system_prompt_override
+            .or(config_profile.system_prompt.clone())
+            .or(cfg.system_prompt.clone());
+        let base_instructions = match system_prompt {
+            Some(system_prompt) => {
+                let text = Self::load_system_prompt(&system_prompt, &resolved_cwd)?;
+                match system_prompt.mode {
+                    SystemPromptMode::Replace => base_instructions.or(Some(text)),
+                    SystemPromptMode::Append => {
+                        let base = base_instructions
+                            .or(file_base_instructions)
+                            .unwrap_or_else(|| model_family.base_instructions.clone());
+                        Some(format!("{}\n\n{text}", base.trim_end()))
+                    }
+                }
+            }
+            None => base_instructions.or(file_base_instructions),
+        };
         let developer_instructions = developer_instructions.or(cfg.developer_instructions);
 
         let experimental_compact_prompt_path = config_profile
@@ -1169,11 +1642,13 @@ impl Config {
             .or(cfg.review_model)
             .unwrap_or_else(default_review_model);
 
-        let check_for_update_on_startup = cfg.check_for_update_on_startup.unwrap_or(true);
+        let check_for_update_on_startup = cfg.check_for_update_on_startup.unwrap_or(true)
+            && !privacy.disable_all_network_except_providers;
 
         let config = Self {
             model,
             review_model,
+            review_cache,
             model_family,
             model_context_window,
             model_auto_compact_token_limit,
@@ -1185,7 +1660,8 @@ impl Config {
             did_user_set_custom_approval_policy_or_sandbox_mode,
             forced_auto_mode_downgraded_on_windows,
             shell_environment_policy,
-            notify: cfg.notify,
+            notify,
+            notify_hooks,
             user_instructions,
             base_instructions,
             developer_instructions,
@@ -1194,6 +1670,7 @@ impl Config {
             // is important in code to differentiate the mode from the store implementation.
             cli_auth_credentials_store_mode: cfg.cli_auth_credentials_store.unwrap_or_default(),
             mcp_servers: cfg.mcp_servers,
+            custom_tools,
             // The config.toml omits "_mode" because it's a config file. However, "_mode"
             // is important in code to differentiate the mode from the store implementation.
             mcp_oauth_credentials_store_mode: cfg.mcp_oauth_credentials_store.unwrap_or_default(),
@@ -1213,6 +1690,10 @@ impl Config {
                 })
                 .collect(),
             tool_output_token_limit: cfg.tool_output_token_limit,
+            max_parallel_tool_calls: cfg
+                .max_parallel_tool_calls
+                .unwrap_or(DEFAULT_MAX_PARALLEL_TOOL_CALLS)
+                .max(1),
             codex_home,
             history,
             file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
@@ -1244,6 +1725,8 @@ impl Config {
             use_experimental_use_rmcp_client,
             features,
             active_profile: active_profile_name,
+            tags: cfg.tags.unwrap_or_default(),
+            fallback,
             active_project,
             windows_wsl_setup_acknowledged: cfg.windows_wsl_setup_acknowledged.unwrap_or(false),
             notices: cfg.notice.unwrap_or_default(),
@@ -1256,19 +1739,42 @@ impl Config {
                 .unwrap_or_default(),
             animations: cfg.tui.as_ref().map(|t| t.animations).unwrap_or(true),
             show_tooltips: cfg.tui.as_ref().map(|t| t.show_tooltips).unwrap_or(true),
+            tui_timestamp_format: cfg.tui.as_ref().map(|t| t.timestamps).unwrap_or_default(),
             otel: {
                 let t: OtelConfigToml = cfg.otel.unwrap_or_default();
                 let log_user_prompt = t.log_user_prompt.unwrap_or(false);
                 let environment = t
                     .environment
                     .unwrap_or(DEFAULT_OTEL_ENVIRONMENT.to_string());
-                let exporter = t.exporter.unwrap_or(OtelExporterKind::None);
+                let exporter = if privacy.disable_all_network_except_providers {
+                    OtelExporterKind::None
+                } else {
+                    t.exporter.unwrap_or(OtelExporterKind::None)
+                };
                 OtelConfig {
                     log_user_prompt,
                     environment,
                     exporter,
                 }
             },
+            privacy,
+            project_config: None,
+            policy,
+            model_prices: cfg.model_prices,
+            budget,
+            hooks,
+            preflight: cfg.preflight.unwrap_or_default(),
+            redaction: cfg.redaction.unwrap_or_default(),
+            limits,
+            run_limits: RunLimits::default(),
+            dry_run: false,
+            retry: cfg.retry.unwrap_or_default(),
+            fetch_url: cfg.fetch_url.unwrap_or_default(),
+            search_code: cfg.search_code.unwrap_or_default(),
+            sub_agents: cfg.sub_agents.unwrap_or_default(),
+            pinned_files: cfg.pinned_files.unwrap_or_default(),
+            additional_roots,
+            shared_state_dir,
         };
         Ok(config)
     }
@@ -1288,6 +1794,33 @@ impl Config {
         None
     }
 
+    fn load_system_prompt(system_prompt: &SystemPrompt, cwd: &Path) -> std::io::Result<String> {
+        match (&system_prompt.text, &system_prompt.file) {
+            (Some(text), None) if !text.trim().is_empty() => Ok(text.trim().to_string()),
+            (Some(_), None) => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "system_prompt.text is empty",
+            )),
+            (None, Some(file)) => {
+                Self::load_override_from_file(Some(file), cwd, "system prompt file")
+                    .map(Option::unwrap_or_default)
+            }
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "system_prompt needs exactly one of `text` and `file`",
+            )),
+        }
+    }
+
+    /// The system prompt sessions start with: the built-in instructions of
+    /// the model, unless `system_prompt` or an override replaced or extended
+    /// them.
+    pub fn effective_base_instructions(&self) -> &str {
+        self.base_instructions
+            .as_deref()
+            .unwrap_or(&self.model_family.base_instructions)
+    }
+
     fn load_override_from_file(
         path: Option<&PathBuf>,
         cwd: &Path,
@@ -1336,6 +1869,43 @@ fn default_review_model() -> String {
     OPENAI_DEFAULT_REVIEW_MODEL.to_string()
 }
 
+/// Environment variable selecting the active profile when `--profile` is not given.
+pub const CODEX_PROFILE_ENV_VAR: &str = "CODEX_PROFILE";
+
+fn profile_from_env() -> Option<String> {
+    std::env::var(CODEX_PROFILE_ENV_VAR)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Loads a profile that is not defined inline in `config.toml` from
+/// `CODEX_HOME/profiles/<name>.toml`.
+fn load_profile_file(codex_home: &Path, name: &str) -> std::io::Result<ConfigProfile> {
+    let not_found = || {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("config profile `{name}` not found"),
+        )
+    };
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(not_found());
+    }
+
+    let path = codex_home.join("profiles").join(format!("{name}.toml"));
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Err(not_found()),
+        Err(err) => return Err(err),
+    };
+    toml::from_str(&contents).map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("failed to parse profile {}: {e}", path.display()),
+        )
+    })
+}
+
 /// Returns the path to the Codex configuration directory, which can be
 /// specified by the `CODEX_HOME` environment variable. If not set, defaults to
 /// `~/.codex`.
@@ -1363,10 +1933,11 @@ pub fn find_codex_home() -> std::io::Result<PathBuf> {
     Ok(p)
 }
 
-/// Returns the path to the folder where Codex logs are stored. Does not verify
-/// that the directory exists.
+/// Returns the path to the folder where Codex logs are stored, which moves to
+/// a temporary directory when `codex_home` is read-only. Does not verify that
+/// the directory exists.
 pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
-    let mut p = cfg.codex_home.clone();
+    let mut p = crate::state_dir::state_dir(&cfg.codex_home);
     p.push("log");
     Ok(p)
 }
@@ -1377,6 +1948,7 @@ mod tests {
     use crate::config::edit::ConfigEditsBuilder;
     use crate::config::edit::apply_blocking;
     use crate::config::types::HistoryPersistence;
+    use crate::config::types::Hook;
     use crate::config::types::McpServerTransportConfig;
     use crate::config::types::Notifications;
     use crate::features::Feature;
@@ -1431,6 +2003,21 @@ persistence = "none"
 
         assert_eq!(tui.notifications, Notifications::Enabled(true));
         assert!(tui.show_tooltips);
+        assert_eq!(tui.timestamps, TimestampFormat::Auto);
+    }
+
+    #[test]
+    fn tui_config_parses_timestamp_format() {
+        let cfg = r#"
+[tui]
+timestamps = "iso8601"
+"#;
+
+        let parsed =
+            toml::from_str::<ConfigToml>(cfg).expect("TUI config with timestamps should succeed");
+        let tui = parsed.tui.expect("config should include tui section");
+
+        assert_eq!(tui.timestamps, TimestampFormat::Iso8601);
     }
 
     #[test]
@@ -1619,6 +2206,122 @@ trust_level = "trusted"
         Ok(())
     }
 
+    #[test]
+    fn roots_set_the_cwd_and_extend_workspace_writable_roots() -> std::io::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let frontend = temp_dir.path().join("frontend");
+        let shared = temp_dir.path().join("shared-lib");
+        std::fs::create_dir_all(&frontend)?;
+        std::fs::create_dir_all(&shared)?;
+
+        let overrides = ConfigOverrides {
+            sandbox_mode: Some(SandboxMode::WorkspaceWrite),
+            roots: vec![frontend.clone(), shared.clone(), frontend.clone()],
+            ..Default::default()
+        };
+        let config = Config::load_from_base_config_with_overrides(
+            ConfigToml::default(),
+            overrides,
+            temp_dir.path().to_path_buf(),
+        )?;
+
+        let expected_shared = canonicalize(&shared)?;
+        assert_eq!(config.cwd, canonicalize(&frontend)?);
+        assert_eq!(config.additional_roots, vec![expected_shared.clone()]);
+        if let SandboxPolicy::WorkspaceWrite { writable_roots, .. } = config.sandbox_policy {
+            assert!(writable_roots.contains(&expected_shared));
+        }
+
+        // With an explicit working directory, every root is an extra one.
+        let overrides = ConfigOverrides {
+            cwd: Some(temp_dir.path().to_path_buf()),
+            roots: vec![frontend.clone(), shared],
+            ..Default::default()
+        };
+        let config = Config::load_from_base_config_with_overrides(
+            ConfigToml::default(),
+            overrides,
+            temp_dir.path().to_path_buf(),
+        )?;
+        assert_eq!(
+            config.additional_roots,
+            vec![canonicalize(&frontend)?, expected_shared]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn limits_retry_delay_is_validated_and_overridden_per_plan() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let load = |toml: &str| {
+            let cfg = toml::from_str::<ConfigToml>(toml).expect("TOML deserialization");
+            Config::load_from_base_config_with_overrides(
+                cfg,
+                ConfigOverrides::default(),
+                codex_home.path().to_path_buf(),
+            )
+        };
+
+        let config = load(
+            r#"
+[limits]
+retry_delay_hours = 3
+
+[limits.plans.pro]
+retry_delay_hours = 168
+"#,
+        )?;
+        assert_eq!(config.limits.retry_delay_hours(None), 3.0);
+        assert_eq!(config.limits.retry_delay_hours(Some("Pro")), 168.0);
+        assert_eq!(config.limits.retry_delay_hours(Some("plus")), 3.0);
+        assert_eq!(Limits::default().retry_delay_hours(None), 5.0);
+
+        for invalid in [
+            "[limits]\nretry_delay_hours = 0",
+            "[limits]\nretry_delay_hours = -1",
+            "[limits]\nretry_delay_hours = nan",
+            "[limits.plans.pro]\nretry_delay_hours = 10000",
+            "[limits.plans.Pro]\nretry_delay_hours = 168",
+        ] {
+            let err = load(invalid).expect_err(invalid);
+            assert_eq!(err.kind(), ErrorKind::InvalidInput, "{invalid}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn limits_downgrade_requires_a_model() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let load = |toml: &str| {
+            let cfg = toml::from_str::<ConfigToml>(toml).expect("TOML deserialization");
+            Config::load_from_base_config_with_overrides(
+                cfg,
+                ConfigOverrides::default(),
+                codex_home.path().to_path_buf(),
+            )
+        };
+
+        let err = load("[limits]\non_cap = \"downgrade_model\"").expect_err("missing model");
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        let config = load(
+            r#"
+[limits]
+on_cap = "downgrade_model"
+downgrade_model = "gpt-5.1-codex-mini"
+"#,
+        )?;
+        assert_eq!(config.limits.on_cap, LimitCapAction::DowngradeModel);
+        assert_eq!(
+            config.limits.downgrade_model.as_deref(),
+            Some("gpt-5.1-codex-mini")
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn config_defaults_to_file_cli_auth_store_mode() -> std::io::Result<()> {
         let codex_home = TempDir::new()?;
@@ -1660,6 +2363,175 @@ trust_level = "trusted"
         Ok(())
     }
 
+    #[test]
+    fn notify_accepts_a_command_or_a_table_of_hooks() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let load = |toml: &str| {
+            let cfg: ConfigToml =
+                toml::from_str(toml).expect("TOML deserialization should succeed");
+            Config::load_from_base_config_with_overrides(
+                cfg,
+                ConfigOverrides::default(),
+                codex_home.path().to_path_buf(),
+            )
+        };
+
+        let config = load(r#"notify = ["notify-send", "Codex"]"#)?;
+        assert_eq!(
+            config.notify,
+            Some(vec!["notify-send".to_string(), "Codex".to_string()])
+        );
+        assert_eq!(config.notify_hooks, NotifyHooks::default());
+
+        let config = load(
+            r#"
+[notify]
+on_turn_complete = ["notify-send", "Codex"]
+on_limit_hit = "https://hooks.slack.com/services/T000/B000/XXXX"
+on_auth_switch = ["/usr/local/bin/codex-alert"]
+"#,
+        )?;
+        assert_eq!(
+            config.notify,
+            Some(vec!["notify-send".to_string(), "Codex".to_string()])
+        );
+        assert_eq!(
+            config.notify_hooks,
+            NotifyHooks {
+                on_limit_hit: Some(NotifyTarget::Webhook(
+                    "https://hooks.slack.com/services/T000/B000/XXXX".to_string()
+                )),
+                on_auth_switch: Some(NotifyTarget::Command(vec![
+                    "/usr/local/bin/codex-alert".to_string()
+                ])),
+            }
+        );
+
+        let err = load("notify = { on_limit_hit = \"notify-send Codex\" }")
+            .expect_err("a string that is not a URL is rejected");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        Ok(())
+    }
+
+    #[test]
+    fn hooks_accept_a_command_or_a_table() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+[hooks]
+session_start = ["/usr/local/bin/check-repo"]
+post_tool = { command = ["cargo", "fmt"], tools = ["apply_patch"], timeout_ms = 30000 }
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+        assert_eq!(
+            config.hooks,
+            Hooks {
+                session_start: Some(Hook {
+                    command: vec!["/usr/local/bin/check-repo".to_string()],
+                    tools: Vec::new(),
+                    timeout_ms: None,
+                }),
+                post_tool: Some(Hook {
+                    command: vec!["cargo".to_string(), "fmt".to_string()],
+                    tools: vec!["apply_patch".to_string()],
+                    timeout_ms: Some(30_000),
+                }),
+                ..Hooks::default()
+            }
+        );
+
+        let cfg: ConfigToml = toml::from_str("hooks = { pre_tool = [] }")
+            .expect("TOML deserialization should succeed");
+        let err = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )
+        .expect_err("an empty command is rejected");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        Ok(())
+    }
+
+    #[test]
+    fn custom_tools_are_validated_when_loaded() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let load = |toml: &str| {
+            let cfg: ConfigToml =
+                toml::from_str(toml).expect("TOML deserialization should succeed");
+            Config::load_from_base_config_with_overrides(
+                cfg,
+                ConfigOverrides::default(),
+                codex_home.path().to_path_buf(),
+            )
+        };
+
+        let config = load(
+            r#"
+[custom_tools.verify]
+description = "Run the verification suite."
+command = ["make", "verify", "TARGET={target}"]
+timeout_ms = 600000
+
+[custom_tools.verify.parameters]
+type = "object"
+properties = { target = { type = "string" } }
+"#,
+        )?;
+        assert_eq!(
+            config.custom_tools.get("verify"),
+            Some(&CustomToolConfig {
+                description: "Run the verification suite.".to_string(),
+                parameters: Some(serde_json::json!({
+                    "type": "object",
+                    "properties": { "target": { "type": "string" } },
+                })),
+                command: vec![
+                    "make".to_string(),
+                    "verify".to_string(),
+                    "TARGET={target}".to_string(),
+                ],
+                cwd: None,
+                timeout_ms: Some(600_000),
+            })
+        );
+
+        let err = load(
+            r#"
+[custom_tools."run tests"]
+description = "d"
+command = ["make"]
+"#,
+        )
+        .expect_err("names with spaces are rejected");
+        assert_eq!(
+            err.to_string(),
+            "custom tool `run tests`: names may only use letters, digits, `_`, and `-`"
+        );
+
+        let err = load(
+            r#"
+[custom_tools.verify]
+description = "d"
+command = []
+"#,
+        )
+        .expect_err("an empty command is rejected");
+        assert_eq!(
+            err.to_string(),
+            "custom tool `verify`: `command` must name a program"
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn config_defaults_to_auto_oauth_store_mode() -> std::io::Result<()> {
         let codex_home = TempDir::new()?;
@@ -1679,6 +2551,51 @@ trust_level = "trusted"
         Ok(())
     }
 
+    #[test]
+    fn profile_loaded_from_profiles_dir() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let profiles_dir = codex_home.path().join("profiles");
+        std::fs::create_dir_all(&profiles_dir)?;
+        std::fs::write(
+            profiles_dir.join("client-x.toml"),
+            r#"
+model = "gpt-5.1-client"
+forced_login_method = "api"
+"#,
+        )?;
+
+        let config = Config::load_from_base_config_with_overrides(
+            ConfigToml::default(),
+            ConfigOverrides {
+                config_profile: Some("client-x".to_string()),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )?;
+
+        assert_eq!(config.model, "gpt-5.1-client");
+        assert_eq!(config.forced_login_method, Some(ForcedLoginMethod::Api));
+        assert_eq!(config.active_profile.as_deref(), Some("client-x"));
+        Ok(())
+    }
+
+    #[test]
+    fn missing_profile_file_is_not_found() {
+        let codex_home = TempDir::new().expect("tempdir");
+        for name in ["absent", "../config"] {
+            let err = Config::load_from_base_config_with_overrides(
+                ConfigToml::default(),
+                ConfigOverrides {
+                    config_profile: Some(name.to_string()),
+                    ..Default::default()
+                },
+                codex_home.path().to_path_buf(),
+            )
+            .expect_err("profile should not resolve");
+            assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        }
+    }
+
     #[test]
     fn profile_legacy_toggles_override_base() -> std::io::Result<()> {
         let codex_home = TempDir::new()?;
@@ -1782,6 +2699,72 @@ trust_level = "trusted"
         Ok(())
     }
 
+    #[test]
+    fn system_prompt_replaces_or_extends_base_instructions() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cwd = TempDir::new()?;
+        std::fs::write(cwd.path().join("tdd.md"), "Write the failing test first.\n")?;
+        let toml = r#"
+system_prompt = { text = "Be terse." }
+
+[profiles.tdd]
+system_prompt = { file = "tdd.md", mode = "append" }
+"#;
+        let cfg: ConfigToml = toml::from_str(toml).expect("TOML deserialization should succeed");
+        let load = |config_profile: Option<&str>, system_prompt: Option<SystemPrompt>| {
+            Config::load_from_base_config_with_overrides(
+                cfg.clone(),
+                ConfigOverrides {
+                    cwd: Some(cwd.path().to_path_buf()),
+                    config_profile: config_profile.map(str::to_string),
+                    system_prompt,
+                    ..Default::default()
+                },
+                codex_home.path().to_path_buf(),
+            )
+        };
+
+        let config = load(None, None)?;
+        assert_eq!(config.effective_base_instructions(), "Be terse.");
+
+        let config = load(Some("tdd"), None)?;
+        assert_eq!(
+            config.effective_base_instructions(),
+            format!(
+                "{}\n\nWrite the failing test first.",
+                config.model_family.base_instructions.trim_end()
+            )
+        );
+
+        // `--system-prompt-file` wins over the profile.
+        let config = load(
+            Some("tdd"),
+            Some(SystemPrompt {
+                file: Some(PathBuf::from("tdd.md")),
+                ..Default::default()
+            }),
+        )?;
+        assert_eq!(
+            config.effective_base_instructions(),
+            "Write the failing test first."
+        );
+
+        let err = load(
+            None,
+            Some(SystemPrompt {
+                mode: SystemPromptMode::Append,
+                ..Default::default()
+            }),
+        )
+        .expect_err("neither text nor file");
+        assert_eq!(
+            err.to_string(),
+            "system_prompt needs exactly one of `text` and `file`"
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn feature_table_overrides_legacy_flags() -> std::io::Result<()> {
         let codex_home = TempDir::new()?;
@@ -2899,6 +3882,11 @@ model_verbosity = "high"
             request_max_retries: Some(4),
             stream_max_retries: Some(10),
             stream_idle_timeout_ms: Some(300_000),
+            max_request_body_bytes: None,
+            supports_images: None,
+            azure: None,
+            gateway: None,
+            tls: None,
             requires_openai_auth: false,
         };
         let model_provider_map = {
@@ -2955,6 +3943,7 @@ model_verbosity = "high"
             Config {
                 model: "o3".to_string(),
                 review_model: OPENAI_DEFAULT_REVIEW_MODEL.to_string(),
+                review_cache: None,
                 model_family: find_family_for_model("o3").expect("known model slug"),
                 model_context_window: Some(200_000),
                 model_auto_compact_token_limit: Some(180_000),
@@ -2967,14 +3956,17 @@ model_verbosity = "high"
                 shell_environment_policy: ShellEnvironmentPolicy::default(),
                 user_instructions: None,
                 notify: None,
+                notify_hooks: NotifyHooks::default(),
                 cwd: fixture.cwd(),
                 cli_auth_credentials_store_mode: Default::default(),
                 mcp_servers: HashMap::new(),
+                custom_tools: HashMap::new(),
                 mcp_oauth_credentials_store_mode: Default::default(),
                 model_providers: fixture.model_provider_map.clone(),
                 project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
                 project_doc_fallback_filenames: Vec::new(),
                 tool_output_token_limit: None,
+                max_parallel_tool_calls: DEFAULT_MAX_PARALLEL_TOOL_CALLS,
                 codex_home: fixture.codex_home(),
                 history: History::default(),
                 file_opener: UriBasedFileOpener::VsCode,
@@ -2997,6 +3989,8 @@ model_verbosity = "high"
                 use_experimental_use_rmcp_client: false,
                 features: Features::with_defaults(),
                 active_profile: Some("o3".to_string()),
+                tags: BTreeMap::new(),
+                fallback: Vec::new(),
                 active_project: ProjectConfig { trust_level: None },
                 windows_wsl_setup_acknowledged: false,
                 notices: Default::default(),
@@ -3005,7 +3999,26 @@ model_verbosity = "high"
                 tui_notifications: Default::default(),
                 animations: true,
                 show_tooltips: true,
+                tui_timestamp_format: TimestampFormat::Auto,
                 otel: OtelConfig::default(),
+                privacy: Privacy::default(),
+                project_config: None,
+                policy: Policy::default(),
+                model_prices: HashMap::new(),
+                budget: Budget::default(),
+                hooks: Hooks::default(),
+                preflight: Preflight::default(),
+                redaction: Redaction::default(),
+                limits: Limits::default(),
+                run_limits: RunLimits::default(),
+                dry_run: false,
+                retry: Retry::default(),
+                fetch_url: FetchUrl::default(),
+                search_code: SearchCode::default(),
+                sub_agents: SubAgents::default(),
+                pinned_files: Vec::new(),
+                additional_roots: Vec::new(),
+                shared_state_dir: None,
             },
             o3_profile_config
         );
@@ -3029,6 +4042,7 @@ model_verbosity = "high"
         let expected_gpt3_profile_config = Config {
             model: "gpt-3.5-turbo".to_string(),
             review_model: OPENAI_DEFAULT_REVIEW_MODEL.to_string(),
+            review_cache: None,
             model_family: find_family_for_model("gpt-3.5-turbo").expect("known model slug"),
             model_context_window: Some(16_385),
             model_auto_compact_token_limit: Some(14_746),
@@ -3041,14 +4055,17 @@ model_verbosity = "high"
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
+            notify_hooks: NotifyHooks::default(),
             cwd: fixture.cwd(),
             cli_auth_credentials_store_mode: Default::default(),
             mcp_servers: HashMap::new(),
+            custom_tools: HashMap::new(),
             mcp_oauth_credentials_store_mode: Default::default(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             project_doc_fallback_filenames: Vec::new(),
             tool_output_token_limit: None,
+            max_parallel_tool_calls: DEFAULT_MAX_PARALLEL_TOOL_CALLS,
             codex_home: fixture.codex_home(),
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
@@ -3071,6 +4088,8 @@ model_verbosity = "high"
             use_experimental_use_rmcp_client: false,
             features: Features::with_defaults(),
             active_profile: Some("gpt3".to_string()),
+            tags: BTreeMap::new(),
+            fallback: Vec::new(),
             active_project: ProjectConfig { trust_level: None },
             windows_wsl_setup_acknowledged: false,
             notices: Default::default(),
@@ -3079,7 +4098,26 @@ model_verbosity = "high"
             tui_notifications: Default::default(),
             animations: true,
             show_tooltips: true,
+            tui_timestamp_format: TimestampFormat::Auto,
             otel: OtelConfig::default(),
+            privacy: Privacy::default(),
+            project_config: None,
+            policy: Policy::default(),
+            model_prices: HashMap::new(),
+            budget: Budget::default(),
+            hooks: Hooks::default(),
+            preflight: Preflight::default(),
+            redaction: Redaction::default(),
+            limits: Limits::default(),
+            run_limits: RunLimits::default(),
+            dry_run: false,
+            retry: Retry::default(),
+            fetch_url: FetchUrl::default(),
+            search_code: SearchCode::default(),
+            sub_agents: SubAgents::default(),
+            pinned_files: Vec::new(),
+            additional_roots: Vec::new(),
+            shared_state_dir: None,
         };
 
         assert_eq!(expected_gpt3_profile_config, gpt3_profile_config);
@@ -3118,6 +4156,7 @@ model_verbosity = "high"
         let expected_zdr_profile_config = Config {
             model: "o3".to_string(),
             review_model: OPENAI_DEFAULT_REVIEW_MODEL.to_string(),
+            review_cache: None,
             model_family: find_family_for_model("o3").expect("known model slug"),
             model_context_window: Some(200_000),
             model_auto_compact_token_limit: Some(180_000),
@@ -3130,14 +4169,17 @@ model_verbosity = "high"
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
+            notify_hooks: NotifyHooks::default(),
             cwd: fixture.cwd(),
             cli_auth_credentials_store_mode: Default::default(),
             mcp_servers: HashMap::new(),
+            custom_tools: HashMap::new(),
             mcp_oauth_credentials_store_mode: Default::default(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             project_doc_fallback_filenames: Vec::new(),
             tool_output_token_limit: None,
+            max_parallel_tool_calls: DEFAULT_MAX_PARALLEL_TOOL_CALLS,
             codex_home: fixture.codex_home(),
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
@@ -3160,6 +4202,8 @@ model_verbosity = "high"
             use_experimental_use_rmcp_client: false,
             features: Features::with_defaults(),
             active_profile: Some("zdr".to_string()),
+            tags: BTreeMap::new(),
+            fallback: Vec::new(),
             active_project: ProjectConfig { trust_level: None },
             windows_wsl_setup_acknowledged: false,
             notices: Default::default(),
@@ -3168,7 +4212,26 @@ model_verbosity = "high"
             tui_notifications: Default::default(),
             animations: true,
             show_tooltips: true,
+            tui_timestamp_format: TimestampFormat::Auto,
             otel: OtelConfig::default(),
+            privacy: Privacy::default(),
+            project_config: None,
+            policy: Policy::default(),
+            model_prices: HashMap::new(),
+            budget: Budget::default(),
+            hooks: Hooks::default(),
+            preflight: Preflight::default(),
+            redaction: Redaction::default(),
+            limits: Limits::default(),
+            run_limits: RunLimits::default(),
+            dry_run: false,
+            retry: Retry::default(),
+            fetch_url: FetchUrl::default(),
+            search_code: SearchCode::default(),
+            sub_agents: SubAgents::default(),
+            pinned_files: Vec::new(),
+            additional_roots: Vec::new(),
+            shared_state_dir: None,
         };
 
         assert_eq!(expected_zdr_profile_config, zdr_profile_config);
@@ -3193,6 +4256,7 @@ model_verbosity = "high"
         let expected_gpt5_profile_config = Config {
             model: "gpt-5.1".to_string(),
             review_model: OPENAI_DEFAULT_REVIEW_MODEL.to_string(),
+            review_cache: None,
             model_family: find_family_for_model("gpt-5.1").expect("known model slug"),
             model_context_window: Some(272_000),
             model_auto_compact_token_limit: Some(244_800),
@@ -3205,14 +4269,17 @@ model_verbosity = "high"
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
+            notify_hooks: NotifyHooks::default(),
             cwd: fixture.cwd(),
             cli_auth_credentials_store_mode: Default::default(),
             mcp_servers: HashMap::new(),
+            custom_tools: HashMap::new(),
             mcp_oauth_credentials_store_mode: Default::default(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             project_doc_fallback_filenames: Vec::new(),
             tool_output_token_limit: None,
+            max_parallel_tool_calls: DEFAULT_MAX_PARALLEL_TOOL_CALLS,
             codex_home: fixture.codex_home(),
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
@@ -3235,6 +4302,8 @@ model_verbosity = "high"
             use_experimental_use_rmcp_client: false,
             features: Features::with_defaults(),
             active_profile: Some("gpt5".to_string()),
+            tags: BTreeMap::new(),
+            fallback: Vec::new(),
             active_project: ProjectConfig { trust_level: None },
             windows_wsl_setup_acknowledged: false,
             notices: Default::default(),
@@ -3243,7 +4312,26 @@ model_verbosity = "high"
             tui_notifications: Default::default(),
             animations: true,
             show_tooltips: true,
+            tui_timestamp_format: TimestampFormat::Auto,
             otel: OtelConfig::default(),
+            privacy: Privacy::default(),
+            project_config: None,
+            policy: Policy::default(),
+            model_prices: HashMap::new(),
+            budget: Budget::default(),
+            hooks: Hooks::default(),
+            preflight: Preflight::default(),
+            redaction: Redaction::default(),
+            limits: Limits::default(),
+            run_limits: RunLimits::default(),
+            dry_run: false,
+            retry: Retry::default(),
+            fetch_url: FetchUrl::default(),
+            search_code: SearchCode::default(),
+            sub_agents: SubAgents::default(),
+            pinned_files: Vec::new(),
+            additional_roots: Vec::new(),
+            shared_state_dir: None,
         };
 
         assert_eq!(expected_gpt5_profile_config, gpt5_profile_config);
@@ -3432,13 +4520,20 @@ trust_level = "untrusted"
 
     #[test]
     fn test_resolve_oss_provider_explicit_override() {
+        let codex_home = TempDir::new().expect("tempdir");
         let config_toml = ConfigToml::default();
-        let result = resolve_oss_provider(Some("custom-provider"), &config_toml, None);
+        let result = resolve_oss_provider(
+            codex_home.path(),
+            Some("custom-provider"),
+            &config_toml,
+            None,
+        );
         assert_eq!(result, Some("custom-provider".to_string()));
     }
 
     #[test]
     fn test_resolve_oss_provider_from_profile() {
+        let codex_home = TempDir::new().expect("tempdir");
         let mut profiles = std::collections::HashMap::new();
         let profile = ConfigProfile {
             oss_provider: Some("profile-provider".to_string()),
@@ -3450,23 +4545,30 @@ trust_level = "untrusted"
             ..Default::default()
         };
 
-        let result = resolve_oss_provider(None, &config_toml, Some("test-profile".to_string()));
+        let result = resolve_oss_provider(
+            codex_home.path(),
+            None,
+            &config_toml,
+            Some("test-profile".to_string()),
+        );
         assert_eq!(result, Some("profile-provider".to_string()));
     }
 
     #[test]
     fn test_resolve_oss_provider_from_global_config() {
+        let codex_home = TempDir::new().expect("tempdir");
         let config_toml = ConfigToml {
             oss_provider: Some("global-provider".to_string()),
             ..Default::default()
         };
 
-        let result = resolve_oss_provider(None, &config_toml, None);
+        let result = resolve_oss_provider(codex_home.path(), None, &config_toml, None);
         assert_eq!(result, Some("global-provider".to_string()));
     }
 
     #[test]
     fn test_resolve_oss_provider_profile_fallback_to_global() {
+        let codex_home = TempDir::new().expect("tempdir");
         let mut profiles = std::collections::HashMap::new();
         let profile = ConfigProfile::default(); // No oss_provider set
         profiles.insert("test-profile".to_string(), profile);
@@ -3476,19 +4578,49 @@ trust_level = "untrusted"
             ..Default::default()
         };
 
-        let result = resolve_oss_provider(None, &config_toml, Some("test-profile".to_string()));
+        let result = resolve_oss_provider(
+            codex_home.path(),
+            None,
+            &config_toml,
+            Some("test-profile".to_string()),
+        );
         assert_eq!(result, Some("global-provider".to_string()));
     }
 
+    #[test]
+    fn test_resolve_oss_provider_from_profile_file() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        std::fs::create_dir(codex_home.path().join("profiles"))?;
+        std::fs::write(
+            codex_home.path().join("profiles").join("local.toml"),
+            "oss_provider = \"ollama\"\n",
+        )?;
+        let config_toml = ConfigToml {
+            oss_provider: Some("global-provider".to_string()),
+            ..Default::default()
+        };
+
+        let result = resolve_oss_provider(
+            codex_home.path(),
+            None,
+            &config_toml,
+            Some("local".to_string()),
+        );
+        assert_eq!(result, Some("ollama".to_string()));
+        Ok(())
+    }
+
     #[test]
     fn test_resolve_oss_provider_none_when_not_configured() {
+        let codex_home = TempDir::new().expect("tempdir");
         let config_toml = ConfigToml::default();
-        let result = resolve_oss_provider(None, &config_toml, None);
+        let result = resolve_oss_provider(codex_home.path(), None, &config_toml, None);
         assert_eq!(result, None);
     }
 
     #[test]
     fn test_resolve_oss_provider_explicit_overrides_all() {
+        let codex_home = TempDir::new().expect("tempdir");
         let mut profiles = std::collections::HashMap::new();
         let profile = ConfigProfile {
             oss_provider: Some("profile-provider".to_string()),
@@ -3502,6 +4634,7 @@ trust_level = "untrusted"
         };
 
         let result = resolve_oss_provider(
+            codex_home.path(),
             Some("explicit-provider"),
             &config_toml,
             Some("test-profile".to_string()),
@@ -3559,6 +4692,71 @@ trust_level = "untrusted"
 
         Ok(())
     }
+
+    #[test]
+    fn trusted_project_config_sits_between_user_config_and_cli_overrides() {
+        let tmp = TempDir::new().expect("tempdir");
+        let codex_home = tmp.path().join("home");
+        let repo = tmp.path().join("repo");
+        std::fs::create_dir_all(repo.join(".codex")).expect("create dir");
+        let project_config = repo.join(".codex").join(CONFIG_TOML_FILE);
+        std::fs::write(
+            &project_config,
+            "model = \"gpt-5.1\"\nmodel_provider = \"azure\"\napproval_policy = \"never\"\n",
+        )
+        .expect("write project config");
+
+        let layers = |trust_level: &str| {
+            let mut base: TomlValue =
+                toml::from_str("model = \"gpt-5.1-codex\"\napproval_policy = \"on-request\"\n")
+                    .expect("parse");
+            let mut project = toml::Table::new();
+            project.insert(
+                "trust_level".to_string(),
+                TomlValue::String(trust_level.to_string()),
+            );
+            let mut projects = toml::Table::new();
+            projects.insert(
+                repo.to_string_lossy().to_string(),
+                TomlValue::Table(project),
+            );
+            base.as_table_mut()
+                .expect("table")
+                .insert("projects".to_string(), TomlValue::Table(projects));
+            LoadedConfigLayers {
+                base,
+                managed_config: None,
+                managed_preferences: None,
+            }
+        };
+        let cli_overrides = vec![(
+            "model_provider".to_string(),
+            TomlValue::String("openai".to_string()),
+        )];
+
+        let (value, applied) = apply_overlays_with_project_config(
+            layers("trusted"),
+            cli_overrides.clone(),
+            &repo,
+            &codex_home,
+        )
+        .expect("resolve");
+        assert_eq!(applied, Some(project_config));
+        assert_eq!(value["model"].as_str(), Some("gpt-5.1"));
+        assert_eq!(value["model_provider"].as_str(), Some("openai"));
+        // Keys outside the model and credentials are not taken from the project.
+        assert_eq!(value["approval_policy"].as_str(), Some("on-request"));
+
+        let (value, applied) = apply_overlays_with_project_config(
+            layers("untrusted"),
+            cli_overrides,
+            &repo,
+            &codex_home,
+        )
+        .expect("resolve");
+        assert_eq!(applied, None);
+        assert_eq!(value["model"].as_str(), Some("gpt-5.1-codex"));
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,249 @@
+//! An administrator's policy constraining how Codex authenticates and which
+//! model providers it talks to.
+//!
+//! The policy is the `[policy]` table of the effective configuration. An
+//! administrator sets it in `/etc/codex/policy.toml`
+//! (`%ProgramData%\codex\policy.toml` on Windows), which holds the table's
+//! keys at the top level and is merged over every other layer, or under
+//! `[policy]` in managed configuration, which is also how MDM profiles deliver
+//! it. Users can add restrictions of their own but cannot lift the
+//! administrator's.
+//!
+//! A configuration that breaks the policy fails to load with every violation
+//! listed, rather than quietly falling back to something else; the
+//! [`AuthManager`](crate::AuthManager) additionally refuses API-key
+//! credentials and automatic switching when the policy forbids them.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_app_server_protocol::AuthMode;
+use codex_protocol::config_types::ForcedLoginMethod;
+use toml::Value as TomlValue;
+
+use crate::auth::FailoverEntry;
+use crate::config::types::Policy;
+use crate::config_loader::merge_toml_values;
+use crate::model_provider_info::ModelProviderInfo;
+
+#[cfg(not(windows))]
+const CODEX_POLICY_SYSTEM_PATH: &str = "/etc/codex/policy.toml";
+
+/// Where the administrator's policy file is read from. It lives outside
+/// `CODEX_HOME` so that a user who can write there cannot replace it.
+pub fn policy_file_path() -> PathBuf {
+    #[cfg(windows)]
+    {
+        let program_data = std::env::var_os("ProgramData")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(r"C:\ProgramData"));
+        program_data.join("codex").join("policy.toml")
+    }
+
+    #[cfg(not(windows))]
+    {
+        PathBuf::from(CODEX_POLICY_SYSTEM_PATH)
+    }
+}
+
+/// Merges the policy file at `path`, if it exists, into `root`'s `[policy]`
+/// table, over whatever the other layers set there.
+pub(crate) fn apply_policy_file(root: &mut TomlValue, path: &Path) -> io::Result<()> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => {
+            return Err(io::Error::new(
+                err.kind(),
+                format!("failed to read policy file {}: {err}", path.display()),
+            ));
+        }
+    };
+    let policy = toml::from_str::<TomlValue>(&contents).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("failed to parse policy file {}: {err}", path.display()),
+        )
+    })?;
+    let mut overlay = toml::Table::new();
+    overlay.insert("policy".to_string(), policy);
+    merge_toml_values(root, &TomlValue::Table(overlay));
+    Ok(())
+}
+
+/// Fails with every way the resolved provider, `fallback` chain, and
+/// `forced_login_method` break `policy`.
+pub(crate) fn check_policy(
+    policy: &Policy,
+    provider_id: &str,
+    provider: &ModelProviderInfo,
+    fallback: &[FailoverEntry],
+    forced_login_method: Option<ForcedLoginMethod>,
+) -> io::Result<()> {
+    let mut violations = Vec::new();
+    check_provider(
+        policy,
+        "`model_provider`",
+        provider_id,
+        provider,
+        &mut violations,
+    );
+    if policy.disallow_api_key_auth && forced_login_method == Some(ForcedLoginMethod::Api) {
+        violations.push("`forced_login_method = \"api\"` but API keys are not allowed".to_string());
+    }
+    if policy.disable_auto_switching && !fallback.is_empty() {
+        violations.push("`fallback` is set but automatic switching is disabled".to_string());
+    }
+    for entry in fallback {
+        let what = format!("`fallback` entry `{}`", entry.name);
+        check_provider(
+            policy,
+            &what,
+            &entry.provider_id,
+            &entry.provider,
+            &mut violations,
+        );
+        if policy.disallow_api_key_auth && entry.auth_mode == Some(AuthMode::ApiKey) {
+            violations.push(format!(
+                "{what} uses an API key but API keys are not allowed"
+            ));
+        }
+    }
+
+    if violations.is_empty() {
+        return Ok(());
+    }
+    Err(io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        format!(
+            "the configuration is not allowed by the Codex policy set by your administrator:\n{}",
+            violations
+                .iter()
+                .map(|violation| format!("  - {violation}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ),
+    ))
+}
+
+fn check_provider(
+    policy: &Policy,
+    what: &str,
+    provider_id: &str,
+    provider: &ModelProviderInfo,
+    violations: &mut Vec<String>,
+) {
+    if let Some(allowed) = &policy.allowed_providers
+        && !allowed.iter().any(|id| id == provider_id)
+    {
+        violations.push(format!(
+            "{what} uses provider `{provider_id}`, which is not one of the allowed providers ({})",
+            allowed.join(", ")
+        ));
+    }
+    // Built-in providers without an explicit `base_url` use their default
+    // endpoint; `allowed_providers` is what restricts those.
+    if let (Some(allowed), Some(base_url)) = (&policy.allowed_base_urls, &provider.base_url)
+        && !allowed.iter().any(|prefix| base_url.starts_with(prefix))
+    {
+        violations.push(format!(
+            "{what} uses base URL `{base_url}`, which does not start with an allowed prefix ({})",
+            allowed.join(", ")
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_provider_info::built_in_model_providers;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn policy_file_overrides_policy_from_other_layers() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let path = tmp.path().join("policy.toml");
+        let mut root: TomlValue = toml::from_str(
+            r#"
+model = "gpt-5.1"
+
+[policy]
+disallow_api_key_auth = false
+allowed_providers = ["openai", "ollama"]
+"#,
+        )
+        .expect("parse");
+
+        apply_policy_file(&mut root, &path).expect("missing policy file is fine");
+        assert_eq!(
+            root["policy"]["disallow_api_key_auth"].as_bool(),
+            Some(false)
+        );
+
+        std::fs::write(&path, "disallow_api_key_auth = true\n").expect("write");
+        apply_policy_file(&mut root, &path).expect("apply");
+        let policy: Policy = root["policy"].clone().try_into().expect("policy");
+        assert_eq!(
+            policy,
+            Policy {
+                disallow_api_key_auth: true,
+                allowed_providers: Some(vec!["openai".to_string(), "ollama".to_string()]),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn every_violation_is_reported() {
+        let mut providers = built_in_model_providers();
+        let mut proxy = providers["openai"].clone();
+        proxy.base_url = Some("https://proxy.example.com/v1".to_string());
+        providers.insert("proxy".to_string(), proxy);
+        let fallback: Vec<FailoverEntry> = ["openai_api", "proxy"]
+            .iter()
+            .map(|name| FailoverEntry::resolve(name, &providers).expect("resolve"))
+            .collect();
+        let policy = Policy {
+            disallow_api_key_auth: true,
+            allowed_providers: Some(vec!["openai".to_string()]),
+            allowed_base_urls: Some(vec!["https://gateway.corp.example/".to_string()]),
+            disable_auto_switching: true,
+        };
+
+        let err = check_policy(
+            &policy,
+            "proxy",
+            &providers["proxy"],
+            &fallback,
+            Some(ForcedLoginMethod::Api),
+        )
+        .expect_err("violations");
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        let message = err.to_string();
+        let lines: Vec<&str> = message.lines().skip(1).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "  - `model_provider` uses provider `proxy`, which is not one of the allowed providers (openai)",
+                "  - `model_provider` uses base URL `https://proxy.example.com/v1`, which does not start with an allowed prefix (https://gateway.corp.example/)",
+                "  - `forced_login_method = \"api\"` but API keys are not allowed",
+                "  - `fallback` is set but automatic switching is disabled",
+                "  - `fallback` entry `openai_api` uses an API key but API keys are not allowed",
+                "  - `fallback` entry `proxy` uses provider `proxy`, which is not one of the allowed providers (openai)",
+                "  - `fallback` entry `proxy` uses base URL `https://proxy.example.com/v1`, which does not start with an allowed prefix (https://gateway.corp.example/)",
+            ]
+        );
+
+        let mut gateway = providers["openai"].clone();
+        gateway.base_url = Some("https://gateway.corp.example/openai/v1".to_string());
+        check_policy(
+            &policy,
+            "openai",
+            &gateway,
+            &[],
+            Some(ForcedLoginMethod::Chatgpt),
+        )
+        .expect("allowed");
+    }
+}
@@ -1,14 +1,16 @@
 use serde::Deserialize;
 use std::path::PathBuf;
 
+use crate::config::types::SystemPrompt;
 use crate::protocol::AskForApproval;
+use codex_protocol::config_types::ForcedLoginMethod;
 use codex_protocol::config_types::ReasoningSummary;
 use codex_protocol::config_types::SandboxMode;
 use codex_protocol::config_types::Verbosity;
 use codex_protocol::openai_models::ReasoningEffort;
 
 /// Collection of common configuration options that a user can define as a unit
-/// in `config.toml`.
+/// in `config.toml`, or in its own file at `CODEX_HOME/profiles/<name>.toml`.
 #[derive(Debug, Clone, Default, PartialEq, Deserialize)]
 pub struct ConfigProfile {
     pub model: Option<String>,
@@ -21,7 +23,12 @@ pub struct ConfigProfile {
     pub model_reasoning_summary: Option<ReasoningSummary>,
     pub model_verbosity: Option<Verbosity>,
     pub chatgpt_base_url: Option<String>,
+    /// Login method required while this profile is active.
+    pub forced_login_method: Option<ForcedLoginMethod>,
     pub experimental_instructions_file: Option<PathBuf>,
+    /// Replaces or extends the built-in system prompt while this profile is
+    /// active.
+    pub system_prompt: Option<SystemPrompt>,
     pub experimental_compact_prompt_file: Option<PathBuf>,
     pub include_apply_patch_tool: Option<bool>,
     pub experimental_use_unified_exec_tool: Option<bool>,
@@ -0,0 +1,141 @@
+//! Per-project overrides from a `.codex/config.toml` checked into a
+//! repository, e.g. so client work always uses the client's Azure tenant.
+//!
+//! The nearest `.codex/config.toml` between the working directory and the
+//! root of its git repository is used. Because the file comes with the
+//! repository, it is only read for projects marked as trusted in
+//! `~/.codex/config.toml`, and only the keys that choose the model and the
+//! credentials are honored; anything else is ignored with a warning.
+//!
+//! Precedence, highest first: managed config, CLI flags and `-c` overrides,
+//! the active profile, the project file, `~/.codex/config.toml`.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use toml::Value as TomlValue;
+
+use crate::config::CONFIG_TOML_FILE;
+use crate::git_info::get_git_repo_root;
+
+/// Directory inside a project that holds its `config.toml`.
+pub const PROJECT_CONFIG_DIR: &str = ".codex";
+
+/// Top-level keys a project config may set.
+const PROJECT_CONFIG_KEYS: &[&str] = &[
+    "model",
+    "model_provider",
+    "model_providers",
+    "profile",
+    "forced_login_method",
+    "forced_chatgpt_workspace_id",
+];
+
+/// The nearest `.codex/config.toml` from `cwd` up to the repository root
+/// (just `cwd` outside a repository). `codex_home`'s own `config.toml` is
+/// never treated as a project config.
+pub(crate) fn find_project_config(cwd: &Path, codex_home: &Path) -> Option<PathBuf> {
+    let root = get_git_repo_root(cwd).unwrap_or_else(|| cwd.to_path_buf());
+    let user_config = codex_home.join(CONFIG_TOML_FILE);
+    cwd.ancestors()
+        .take_while(|dir| dir.starts_with(&root))
+        .map(|dir| dir.join(PROJECT_CONFIG_DIR).join(CONFIG_TOML_FILE))
+        .find(|path| path.is_file() && !same_file(path, &user_config))
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Reads the project config at `path`, keeping only the keys a project may
+/// set.
+pub(crate) fn load_project_config(path: &Path) -> std::io::Result<TomlValue> {
+    let contents = std::fs::read_to_string(path)?;
+    let value = toml::from_str::<TomlValue>(&contents).map_err(|err| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("failed to parse {}: {err}", path.display()),
+        )
+    })?;
+    Ok(restrict_to_project_keys(value, path))
+}
+
+fn restrict_to_project_keys(value: TomlValue, path: &Path) -> TomlValue {
+    let TomlValue::Table(table) = value else {
+        return TomlValue::Table(Default::default());
+    };
+    let mut allowed = toml::Table::new();
+    let mut ignored = Vec::new();
+    for (key, value) in table {
+        if PROJECT_CONFIG_KEYS.contains(&key.as_str()) {
+            allowed.insert(key, value);
+        } else {
+            ignored.push(key);
+        }
+    }
+    if !ignored.is_empty() {
+        tracing::warn!(
+            "ignoring {} in {}: project configs may only set {}",
+            ignored.join(", "),
+            path.display(),
+            PROJECT_CONFIG_KEYS.join(", ")
+        );
+    }
+    TomlValue::Table(allowed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn project_config_is_found_up_to_the_repo_root() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let codex_home = tmp.path().join("home");
+        let repo = tmp.path().join("repo");
+        let nested = repo.join("crates").join("app");
+        std::fs::create_dir_all(&nested).expect("create dirs");
+        std::fs::create_dir_all(repo.join(".git")).expect("create .git");
+        assert_eq!(find_project_config(&nested, &codex_home), None);
+
+        // A config above the repository root does not apply.
+        std::fs::create_dir_all(tmp.path().join(PROJECT_CONFIG_DIR)).expect("create dir");
+        std::fs::write(
+            tmp.path().join(PROJECT_CONFIG_DIR).join(CONFIG_TOML_FILE),
+            "",
+        )
+        .expect("write");
+        assert_eq!(find_project_config(&nested, &codex_home), None);
+
+        let project_config = repo.join(PROJECT_CONFIG_DIR).join(CONFIG_TOML_FILE);
+        std::fs::create_dir_all(repo.join(PROJECT_CONFIG_DIR)).expect("create dir");
+        std::fs::write(&project_config, "model = \"gpt-5.1\"\n").expect("write");
+        assert_eq!(
+            find_project_config(&nested, &codex_home),
+            Some(project_config)
+        );
+    }
+
+    #[test]
+    fn only_model_and_credential_keys_are_kept() {
+        let value: TomlValue = toml::from_str(
+            r#"
+model = "gpt-5.1"
+model_provider = "azure"
+approval_policy = "never"
+
+[model_providers.azure]
+name = "Azure"
+base_url = "https://client.openai.azure.com/openai"
+"#,
+        )
+        .expect("parse");
+        let restricted = restrict_to_project_keys(value, Path::new(".codex/config.toml"));
+        let keys: Vec<&String> = restricted.as_table().expect("table").keys().collect();
+        assert_eq!(keys, vec!["model", "model_provider", "model_providers"]);
+    }
+}
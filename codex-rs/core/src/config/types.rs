@@ -50,8 +50,19 @@ impl<'de> Deserialize<'de> for McpServerConfig {
     where
         D: Deserializer<'de>,
     {
+        #[derive(Deserialize, Clone, Copy, PartialEq)]
+        #[serde(rename_all = "snake_case")]
+        enum RawTransport {
+            Stdio,
+            StreamableHttp,
+            Sse,
+        }
+
         #[derive(Deserialize, Clone)]
         struct RawMcpServerConfig {
+            #[serde(default)]
+            transport: Option<RawTransport>,
+
             // stdio
             command: Option<String>,
             #[serde(default)]
@@ -114,6 +125,14 @@ impl<'de> Deserialize<'de> for McpServerConfig {
         }
 
         let transport = if let Some(command) = raw.command.clone() {
+            if raw
+                .transport
+                .is_some_and(|transport| transport != RawTransport::Stdio)
+            {
+                return Err(SerdeError::custom(
+                    "`command` is only supported for the stdio transport",
+                ));
+            }
             throw_if_set("stdio", "url", raw.url.as_ref())?;
             throw_if_set(
                 "stdio",
@@ -136,11 +155,27 @@ impl<'de> Deserialize<'de> for McpServerConfig {
             throw_if_set("streamable_http", "env_vars", raw.env_vars.as_ref())?;
             throw_if_set("streamable_http", "cwd", raw.cwd.as_ref())?;
             throw_if_set("streamable_http", "bearer_token", raw.bearer_token.as_ref())?;
-            McpServerTransportConfig::StreamableHttp {
-                url,
-                bearer_token_env_var: raw.bearer_token_env_var.clone(),
-                http_headers: raw.http_headers.clone(),
-                env_http_headers: raw.env_http_headers.take(),
+            let bearer_token_env_var = raw.bearer_token_env_var.clone();
+            let http_headers = raw.http_headers.clone();
+            let env_http_headers = raw.env_http_headers.take();
+            match raw.transport {
+                None | Some(RawTransport::StreamableHttp) => {
+                    McpServerTransportConfig::StreamableHttp {
+                        url,
+                        bearer_token_env_var,
+                        http_headers,
+                        env_http_headers,
+                    }
+                }
+                Some(RawTransport::Sse) => McpServerTransportConfig::Sse {
+                    url,
+                    bearer_token_env_var,
+                    http_headers,
+                    env_http_headers,
+                },
+                Some(RawTransport::Stdio) => {
+                    return Err(SerdeError::custom("the stdio transport requires `command`"));
+                }
             }
         } else {
             return Err(SerdeError::custom("invalid transport"));
@@ -191,6 +226,23 @@ pub enum McpServerTransportConfig {
         #[serde(default, skip_serializing_if = "Option::is_none")]
         env_http_headers: Option<HashMap<String, String>>,
     },
+    /// The HTTP+SSE transport of earlier protocol versions, selected with
+    /// `transport = "sse"`: server messages arrive on an event stream
+    /// opened at `url`, and client messages are POSTed to the endpoint that
+    /// stream announces.
+    /// https://modelcontextprotocol.io/specification/2024-11-05/basic/transports#http-with-sse
+    Sse {
+        url: String,
+        /// Name of the environment variable to read for an HTTP bearer token.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bearer_token_env_var: Option<String>,
+        /// Additional HTTP headers to include in requests to this server.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        http_headers: Option<HashMap<String, String>>,
+        /// HTTP headers where the value is sourced from an environment variable.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        env_http_headers: Option<HashMap<String, String>>,
+    },
 }
 
 mod option_duration_secs {
@@ -271,6 +323,451 @@ pub enum HistoryPersistence {
     None,
 }
 
+/// Settings under `[privacy]`.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct Privacy {
+    /// When true, Codex makes no network requests other than to the model
+    /// provider (and MCP servers you configured): update checks, telemetry
+    /// export, feedback upload, and the web search tool are all disabled.
+    #[serde(default)]
+    pub disable_all_network_except_providers: bool,
+}
+
+/// A model's price under `[model_prices."<model>"]`, in US dollars per
+/// million tokens.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ModelPrice {
+    pub input: f64,
+    /// Price of input tokens served from the prompt cache; defaults to
+    /// `input`.
+    pub cached_input: Option<f64>,
+    /// Price of output tokens, including reasoning tokens.
+    pub output: f64,
+}
+
+/// `notify` in `config.toml`: either the command run when a turn completes,
+/// or a table of hooks for that and other events; see
+/// [`crate::user_notification`].
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum NotifyToml {
+    Command(Vec<String>),
+    Hooks(NotifyTable),
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct NotifyTable {
+    /// Same as the array form of `notify`.
+    pub on_turn_complete: Option<Vec<String>>,
+    #[serde(flatten)]
+    pub hooks: NotifyHooks,
+}
+
+/// Hooks for events other than a completed turn.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct NotifyHooks {
+    /// Run when a session hits a usage limit.
+    pub on_limit_hit: Option<NotifyTarget>,
+    /// Run when Codex switches credentials or providers on its own.
+    pub on_auth_switch: Option<NotifyTarget>,
+}
+
+/// Where a notification hook delivers its JSON payload.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum NotifyTarget {
+    /// `http://` or `https://` URL the payload is POSTed to.
+    Webhook(String),
+    /// Program and arguments; the payload is passed as one more argument.
+    Command(Vec<String>),
+}
+
+/// A tool declared under `[custom_tools.<name>]` that runs a command with
+/// the call's arguments; see [`crate::tools::handlers::ConfigToolHandler`].
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct CustomToolConfig {
+    /// Shown to the model; say what the tool does and when to use it.
+    pub description: String,
+    /// JSON Schema of the arguments object; the tool takes no arguments
+    /// when omitted.
+    pub parameters: Option<serde_json::Value>,
+    /// Program and arguments. `{name}` in an argument is replaced with the
+    /// call's `name` argument; an argument that is exactly `{name}` is left
+    /// out when `name` was not given and becomes one argument per item when
+    /// it is an array.
+    pub command: Vec<String>,
+    /// Directory the command runs in, relative to the session's working
+    /// directory.
+    pub cwd: Option<PathBuf>,
+    pub timeout_ms: Option<u64>,
+}
+
+/// Commands run at points of a session's lifecycle, under `[hooks]`; see
+/// [`crate::hooks`].
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Hooks {
+    /// Before a session starts; a failing hook prevents the session.
+    pub session_start: Option<Hook>,
+    /// When a session shuts down.
+    pub session_end: Option<Hook>,
+    /// Before each tool call; may block it or rewrite its arguments.
+    pub pre_tool: Option<Hook>,
+    /// After each tool call; may replace the output the model sees.
+    pub post_tool: Option<Hook>,
+    /// Before a patch is applied; may block it or rewrite it.
+    pub pre_patch: Option<Hook>,
+}
+
+/// A hook: a program and its arguments, or a table that also limits which
+/// tools it runs for.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "HookToml")]
+pub struct Hook {
+    pub command: Vec<String>,
+    /// Tools `pre_tool` and `post_tool` run for; all tools when empty.
+    pub tools: Vec<String>,
+    /// How long the command may run before the action is blocked.
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HookToml {
+    Command(Vec<String>),
+    Table {
+        command: Vec<String>,
+        #[serde(default)]
+        tools: Vec<String>,
+        timeout_ms: Option<u64>,
+    },
+}
+
+impl From<HookToml> for Hook {
+    fn from(hook: HookToml) -> Self {
+        match hook {
+            HookToml::Command(command) => Hook {
+                command,
+                tools: Vec::new(),
+                timeout_ms: None,
+            },
+            HookToml::Table {
+                command,
+                tools,
+                timeout_ms,
+            } => Hook {
+                command,
+                tools,
+                timeout_ms,
+            },
+        }
+    }
+}
+
+/// Spending caps under `[budget]`, in US dollars of estimated cost; see
+/// [`crate::budget`].
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Budget {
+    pub session_usd: Option<f64>,
+    /// Resets at local midnight.
+    pub daily_usd: Option<f64>,
+    /// Resets at local midnight on the first of the month.
+    pub monthly_usd: Option<f64>,
+    /// Percentage of a cap at which Codex warns; defaults to 80.
+    pub warn_at_percent: Option<u8>,
+    #[serde(default)]
+    pub on_exceeded: BudgetAction,
+    /// Model to continue with when `on_exceeded = "downgrade"`.
+    pub downgrade_model: Option<String>,
+}
+
+/// What happens once a `[budget]` cap is exceeded.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetAction {
+    /// Stop the running task with an error.
+    #[default]
+    Stop,
+    /// Switch the session to `downgrade_model` and keep going.
+    Downgrade,
+}
+
+/// Quota check `codex exec` makes before starting, under `[preflight]`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Preflight {
+    /// Percentage of the usage limit that must remain; unset disables the
+    /// check.
+    pub min_remaining_percent: Option<f64>,
+    #[serde(default)]
+    pub on_low: PreflightAction,
+}
+
+/// What `codex exec` does when less quota remains than
+/// `preflight.min_remaining_percent`.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PreflightAction {
+    /// Print a warning and start the run anyway.
+    #[default]
+    Warn,
+    /// Exit with an error without starting the run.
+    Abort,
+}
+
+/// How failed model requests are retried, under `[retry]`. A provider's own
+/// `request_max_retries` and `stream_max_retries` take precedence over the
+/// counts here.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Retry {
+    /// Retries of a request that fails before its response starts; defaults
+    /// to 4.
+    pub request_max_retries: Option<u64>,
+    /// Retries of a turn whose response stream drops; defaults to 5.
+    pub stream_max_retries: Option<u64>,
+    /// Delay before the first retry, doubled for each further one; defaults
+    /// to 200.
+    pub base_delay_ms: Option<u64>,
+    /// Failures that are retried; defaults to `["5xx", "transport"]`.
+    pub retry_on: Option<Vec<RetryableError>>,
+    /// Whether a turn whose response stream drops is sent again; defaults to
+    /// `true`. `false` fails the turn instead.
+    pub resume_stream: Option<bool>,
+}
+
+impl Retry {
+    pub fn base_delay(&self) -> Duration {
+        Duration::from_millis(self.base_delay_ms.unwrap_or(200))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryableError {
+    /// `429 Too Many Requests`.
+    #[serde(rename = "429")]
+    TooManyRequests,
+    /// Any `5xx` status.
+    #[serde(rename = "5xx")]
+    ServerError,
+    /// Timeouts and network errors.
+    #[serde(rename = "transport")]
+    Transport,
+}
+
+/// Secrets replaced with placeholders in tool output and pinned files before
+/// the model sees them, under `[redaction]`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Redaction {
+    /// Whether well-known secret formats, such as AWS access keys and GitHub
+    /// tokens, are redacted; defaults to `true`.
+    pub patterns: Option<bool>,
+    /// Environment variables whose values are redacted wherever they appear.
+    #[serde(default)]
+    pub env_vars: Vec<String>,
+}
+
+/// Instructions that replace or extend the model's built-in system prompt,
+/// set with `system_prompt` at the top level or in a profile, or with
+/// `--system-prompt-file`. Exactly one of `text` and `file` is set.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SystemPrompt {
+    pub text: Option<String>,
+    /// Markdown file to read the instructions from. Relative paths are
+    /// resolved against the working directory of the session.
+    pub file: Option<PathBuf>,
+    #[serde(default)]
+    pub mode: SystemPromptMode,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SystemPromptMode {
+    /// Use the instructions instead of the built-in system prompt.
+    #[default]
+    Replace,
+    /// Add the instructions after the built-in system prompt.
+    Append,
+}
+
+/// What happens when a usage limit is hit, under `[limits]`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Limits {
+    #[serde(default)]
+    pub on_cap: LimitCapAction,
+    /// Model to switch to when `on_cap = "downgrade_model"`.
+    pub downgrade_model: Option<String>,
+    /// Hours until limited credentials are tried again when the provider
+    /// does not say when the limit resets; defaults to 5.
+    pub retry_delay_hours: Option<f64>,
+    /// Overrides per ChatGPT plan, such as `pro` or `plus`, under
+    /// `[limits.plans.<plan>]`.
+    #[serde(default)]
+    pub plans: HashMap<String, PlanLimits>,
+}
+
+impl Limits {
+    /// The retry delay for credentials on `plan`, in hours.
+    pub fn retry_delay_hours(&self, plan: Option<&str>) -> f64 {
+        plan.and_then(|plan| self.plans.get(&plan.to_ascii_lowercase()))
+            .and_then(|limits| limits.retry_delay_hours)
+            .or(self.retry_delay_hours)
+            .unwrap_or(5.0)
+    }
+}
+
+/// Settings for one ChatGPT plan under `[limits.plans.<plan>]`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct PlanLimits {
+    pub retry_delay_hours: Option<f64>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitCapAction {
+    /// Move on to other credentials: the next key of the API key pool, the
+    /// next ChatGPT account, or the next `fallback` entry.
+    #[default]
+    SwitchAuth,
+    /// Keep the credentials and switch the session to
+    /// `limits.downgrade_model`, which has limits of its own.
+    DowngradeModel,
+    /// Like `switch_auth`, but once no other credentials are left, wait for
+    /// the limit to reset and resume the turn.
+    Wait,
+}
+
+/// Limits on a single task, from `codex exec --max-turns`,
+/// `--max-output-tokens`, and `--max-duration`; see [`crate::run_limits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RunLimits {
+    /// Model requests the task may make.
+    pub max_turns: Option<u32>,
+    /// Output tokens the task may generate, reasoning included.
+    pub max_output_tokens: Option<i64>,
+    /// Wall-clock time the task may run.
+    pub max_duration: Option<Duration>,
+}
+
+/// Which pages the `fetch_url` tool may download, under `[fetch_url]`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct FetchUrl {
+    /// Hosts that may be fetched, each also covering its subdomains. Empty
+    /// allows every host not denied.
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    /// Hosts that may never be fetched, with their subdomains. Takes
+    /// precedence over `allowed_domains`.
+    #[serde(default)]
+    pub denied_domains: Vec<String>,
+    /// Largest response body downloaded; defaults to 2 MiB.
+    pub max_bytes: Option<usize>,
+}
+
+impl FetchUrl {
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes.unwrap_or(2 * 1024 * 1024)
+    }
+}
+
+/// The semantic index of the `search_code` tool, under `[search_code]`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SearchCode {
+    /// Embedding model that makes searches rank code by meaning, such as
+    /// `nomic-embed-text`. Without one, code is matched by shared words.
+    pub embedding_model: Option<String>,
+    /// `model_providers` id whose `/embeddings` endpoint computes the
+    /// embeddings; defaults to the local Ollama server.
+    pub embedding_provider: Option<String>,
+}
+
+impl SearchCode {
+    pub fn embedding_provider(&self) -> &str {
+        self.embedding_provider
+            .as_deref()
+            .unwrap_or(crate::model_provider_info::OLLAMA_OSS_PROVIDER_ID)
+    }
+}
+
+/// Limits for the sub-agents the model starts with `spawn_agent`, under
+/// `[sub_agents]`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SubAgents {
+    /// Most sub-agents running at once; defaults to 4.
+    pub max_running: Option<usize>,
+    /// Most tokens one sub-agent may use, and its budget when the model
+    /// does not give one; defaults to 200,000.
+    pub max_tokens: Option<i64>,
+}
+
+impl SubAgents {
+    pub fn max_running(&self) -> usize {
+        self.max_running.unwrap_or(4)
+    }
+
+    pub fn max_tokens(&self) -> i64 {
+        self.max_tokens.unwrap_or(200_000)
+    }
+}
+
+/// Settings under `[policy]`, normally set by an administrator in
+/// `/etc/codex/policy.toml` or managed configuration; see
+/// [`crate::config::policy`].
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Policy {
+    /// When true, only ChatGPT sign-in may be used: API keys from
+    /// `auth.json` or the environment are ignored.
+    #[serde(default)]
+    pub disallow_api_key_auth: bool,
+
+    /// Model provider ids that may be used. `None` allows any provider.
+    pub allowed_providers: Option<Vec<String>>,
+
+    /// Prefixes a provider's `base_url` must start with. `None` allows any
+    /// URL.
+    pub allowed_base_urls: Option<Vec<String>>,
+
+    /// When true, Codex never switches credentials or providers on its own:
+    /// `fallback` may not be set and `OPENAI_API_KEYS` is not rotated.
+    #[serde(default)]
+    pub disable_auto_switching: bool,
+}
+
+/// Settings under `[review_cache]`: where review findings are cached so a
+/// review of unchanged code is not run again. Set `dir` or `s3`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ReviewCacheToml {
+    /// Local directory to store cached reviews in.
+    pub dir: Option<PathBuf>,
+    /// S3-compatible bucket to store cached reviews in, for runners that do
+    /// not share a disk.
+    pub s3: Option<S3ReviewCache>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct S3ReviewCache {
+    pub bucket: String,
+    /// Prefix for object keys, e.g. `codex-review/`.
+    #[serde(default)]
+    pub prefix: String,
+    /// Endpoint of an S3-compatible service such as MinIO or Cloudflare R2.
+    /// Defaults to AWS S3 in `region`.
+    pub endpoint: Option<String>,
+    /// Defaults to `AWS_REGION` or `~/.aws/config`, then `us-east-1`.
+    pub region: Option<String>,
+}
+
+/// Where cached reviews are stored.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReviewCacheStorage {
+    Dir(PathBuf),
+    S3(S3ReviewCache),
+}
+
 // ===== OTEL configuration =====
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
@@ -373,6 +870,25 @@ pub struct Tui {
     /// Defaults to `true`.
     #[serde(default = "default_true")]
     pub show_tooltips: bool,
+
+    /// How timestamps such as rate-limit resets and session ages are rendered.
+    /// Defaults to `auto`.
+    #[serde(default)]
+    pub timestamps: TimestampFormat,
+}
+
+/// Rendering style for timestamps shown in the TUI. All styles use the local timezone.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampFormat {
+    /// Clock times for upcoming events (e.g. "14:05 on 3 Mar") and relative ages for past ones
+    /// (e.g. "2 days ago").
+    #[default]
+    Auto,
+    /// Relative times in both directions (e.g. "in 3h 12m", "2 days ago").
+    Relative,
+    /// ISO-8601 timestamps with the local UTC offset (e.g. "2025-03-03T14:05:00+01:00").
+    Iso8601,
 }
 
 const fn default_true() -> bool {
@@ -730,6 +1246,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn deserialize_sse_server_config() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            url = "https://example.com/sse"
+            transport = "sse"
+            bearer_token_env_var = "DOCS_TOKEN"
+            http_headers = { "X-Foo" = "bar" }
+        "#,
+        )
+        .expect("should deserialize sse config");
+
+        assert_eq!(
+            cfg.transport,
+            McpServerTransportConfig::Sse {
+                url: "https://example.com/sse".to_string(),
+                bearer_token_env_var: Some("DOCS_TOKEN".to_string()),
+                http_headers: Some(HashMap::from([("X-Foo".to_string(), "bar".to_string())])),
+                env_http_headers: None,
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_rejects_transport_mismatch() {
+        toml::from_str::<McpServerConfig>(
+            r#"
+            command = "echo"
+            transport = "sse"
+        "#,
+        )
+        .expect_err("should reject sse with a command");
+
+        toml::from_str::<McpServerConfig>(
+            r#"
+            url = "https://example.com"
+            transport = "stdio"
+        "#,
+        )
+        .expect_err("should reject stdio with a url");
+    }
+
     #[test]
     fn deserialize_server_config_with_tool_filters() {
         let cfg: McpServerConfig = toml::from_str(
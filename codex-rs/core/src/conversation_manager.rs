@@ -14,6 +14,7 @@ use crate::rollout::RolloutRecorder;
 use codex_protocol::ConversationId;
 use codex_protocol::items::TurnItem;
 use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::ForkedFrom;
 use codex_protocol::protocol::InitialHistory;
 use codex_protocol::protocol::RolloutItem;
 use codex_protocol::protocol::SessionSource;
@@ -193,6 +194,70 @@ impl ConversationManager {
 
         self.finalize_spawn(codex, conversation_id).await
     }
+
+    /// Fork the session recorded at `path` after its first `turns` turns,
+    /// leaving the original untouched. Unlike [`Self::fork_conversation`],
+    /// every turn may be kept.
+    pub async fn fork_conversation_after_turn(
+        &self,
+        turns: usize,
+        config: Config,
+        path: PathBuf,
+    ) -> CodexResult<NewConversation> {
+        let items = RolloutRecorder::get_rollout_history(&path)
+            .await?
+            .get_rollout_items();
+        let recorded = user_message_positions(&items).len();
+        if turns == 0 || turns > recorded {
+            return Err(CodexErr::InvalidRequest(format!(
+                "cannot fork at turn {turns}: the session has {recorded} turns"
+            )));
+        }
+        let history = if turns == recorded {
+            InitialHistory::Forked(items)
+        } else {
+            truncate_before_nth_user_message(InitialHistory::Forked(items), turns)
+        };
+
+        let auth_manager = self.auth_manager.clone();
+        let CodexSpawnOk {
+            codex,
+            conversation_id,
+        } = Codex::spawn(config, auth_manager, history, self.session_source.clone()).await?;
+
+        self.finalize_spawn(codex, conversation_id).await
+    }
+}
+
+/// Lineage of a session forked with `items`, the prefix of its parent's
+/// rollout: the parent is the first session recorded in it.
+pub(crate) fn fork_origin(items: &[RolloutItem]) -> Option<ForkedFrom> {
+    items.iter().find_map(|item| match item {
+        RolloutItem::SessionMeta(meta) => Some(ForkedFrom {
+            id: meta.meta.id,
+            turn: user_message_positions(items).len(),
+        }),
+        _ => None,
+    })
+}
+
+/// Indices of the user messages in `items`, one per turn.
+pub(crate) fn user_message_positions(items: &[RolloutItem]) -> Vec<usize> {
+    items
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, item)| match item {
+            RolloutItem::ResponseItem(item @ ResponseItem::Message { .. })
+                if matches!(
+                    crate::event_mapping::parse_turn_item(item),
+                    Some(TurnItem::UserMessage(_))
+                ) =>
+            {
+                Some(idx)
+            }
+            _ => None,
+        })
+        .collect()
 }
 
 /// Return a prefix of `items` obtained by cutting strictly before the nth user message
@@ -202,17 +267,7 @@ fn truncate_before_nth_user_message(history: InitialHistory, n: usize) -> Initia
     let items: Vec<RolloutItem> = history.get_rollout_items();
 
     // Find indices of user message inputs in rollout order.
-    let mut user_positions: Vec<usize> = Vec::new();
-    for (idx, item) in items.iter().enumerate() {
-        if let RolloutItem::ResponseItem(item @ ResponseItem::Message { .. }) = item
-            && matches!(
-                crate::event_mapping::parse_turn_item(item),
-                Some(TurnItem::UserMessage(_))
-            )
-        {
-            user_positions.push(idx);
-        }
-    }
+    let user_positions = user_message_positions(&items);
 
     // If fewer than or equal to n user messages exist, treat as empty (out of range).
     if user_positions.len() <= n {
@@ -340,4 +395,38 @@ mod tests {
             serde_json::to_value(&expected).unwrap()
         );
     }
+
+    #[test]
+    fn fork_origin_names_the_parent_and_the_turns_kept() {
+        use codex_protocol::protocol::SessionMeta;
+        use codex_protocol::protocol::SessionMetaLine;
+
+        let meta = |id: ConversationId| {
+            RolloutItem::SessionMeta(SessionMetaLine {
+                meta: SessionMeta {
+                    id,
+                    ..SessionMeta::default()
+                },
+                git: None,
+            })
+        };
+        let parent = ConversationId::new();
+        let grandparent = ConversationId::new();
+        let items = vec![
+            meta(parent),
+            meta(grandparent),
+            RolloutItem::ResponseItem(user_msg("u1")),
+            RolloutItem::ResponseItem(assistant_msg("a1")),
+            RolloutItem::ResponseItem(user_msg("u2")),
+        ];
+
+        assert_eq!(
+            fork_origin(&items),
+            Some(ForkedFrom {
+                id: parent,
+                turn: 2
+            })
+        );
+        assert_eq!(fork_origin(&items[2..]), None);
+    }
 }
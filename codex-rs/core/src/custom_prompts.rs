@@ -1,5 +1,7 @@
 use codex_protocol::custom_prompts::CustomPrompt;
+use std::collections::HashMap;
 use std::collections::HashSet;
+use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
 use tokio::fs;
@@ -75,6 +77,89 @@ pub async fn discover_prompts_in_excluding(
     out
 }
 
+/// Returns the names of the variables a prompt template uses, de-duplicated
+/// and in order of first use. A variable is written either `{{name}}`
+/// (spaces inside the braces are allowed) or `$NAME` in uppercase. `$$NAME`
+/// is a literal, and `$ARGUMENTS` is reserved for positional arguments.
+pub fn prompt_variable_names(content: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut names = Vec::new();
+    for (_, name) in placeholders(content) {
+        if name == "ARGUMENTS" {
+            continue;
+        }
+        if seen.insert(name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Replaces the variables of a prompt template with their values in `vars`.
+/// Variables without a value are left as written.
+pub fn expand_prompt_variables(content: &str, vars: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    for (range, name) in placeholders(content) {
+        if let Some(value) = vars.get(name) {
+            out.push_str(&content[last..range.start]);
+            out.push_str(value);
+            last = range.end;
+        }
+    }
+    out.push_str(&content[last..]);
+    out
+}
+
+/// Finds the `{{name}}` and `$NAME` placeholders in `content`, returning the
+/// span of each and the name it refers to.
+fn placeholders(content: &str) -> Vec<(Range<usize>, &str)> {
+    let bytes = content.as_bytes();
+    let mut found = Vec::new();
+    let mut i = 0;
+    while let Some(off) = content[i..].find(['{', '$']) {
+        let start = i + off;
+        // Both markers are ASCII, so the next byte starts a character.
+        i = start + 1;
+        if bytes[start] == b'{' {
+            let Some(rest) = content[start..].strip_prefix("{{") else {
+                continue;
+            };
+            let Some(close) = rest.find("}}") else {
+                continue;
+            };
+            let name = rest[..close].trim();
+            let mut chars = name.chars();
+            let valid = chars
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if valid {
+                let end = start + 2 + close + 2;
+                found.push((start..end, name));
+                i = end;
+            }
+        } else {
+            if start > 0 && bytes[start - 1] == b'$' {
+                continue;
+            }
+            let name_len = content[start + 1..]
+                .bytes()
+                .enumerate()
+                .take_while(|&(idx, b)| {
+                    b.is_ascii_uppercase() || (idx > 0 && (b.is_ascii_digit() || b == b'_'))
+                })
+                .count();
+            if name_len > 0 {
+                let end = start + 1 + name_len;
+                found.push((start..end, &content[start + 1..end]));
+                i = end;
+            }
+        }
+    }
+    found
+}
+
 /// Parse optional YAML-like frontmatter at the beginning of `content`.
 /// Supported keys:
 /// - `description`: short description shown in the slash popup
@@ -241,4 +326,23 @@ mod tests {
         assert_eq!(hint.as_deref(), Some("[arg]"));
         assert_eq!(body, "First line\r\nSecond line\r\n");
     }
+
+    #[test]
+    fn finds_braced_and_dollar_variables() {
+        let content = "Add {{ table }} to $DB, then {{table}} again. Cost: $$USD, {{ not valid }}, {x}, $ARGUMENTS, $1";
+        assert_eq!(prompt_variable_names(content), vec!["table", "DB"]);
+
+        let vars = HashMap::from([
+            ("table".to_string(), "users".to_string()),
+            ("DB".to_string(), "postgres".to_string()),
+        ]);
+        assert_eq!(
+            expand_prompt_variables(content, &vars),
+            "Add users to postgres, then users again. Cost: $$USD, {{ not valid }}, {x}, $ARGUMENTS, $1"
+        );
+        assert_eq!(
+            expand_prompt_variables("{{missing}} and {{table}}", &vars),
+            "{{missing}} and users"
+        );
+    }
 }
@@ -1,3 +1,5 @@
+use crate::config::types::Privacy;
+use crate::model_provider_info::ProviderTlsConfig;
 use crate::spawn::CODEX_SANDBOX_ENV_VAR;
 use http::Error as HttpError;
 use reqwest::IntoUrl;
@@ -11,6 +13,8 @@ use std::fmt::Display;
 use std::sync::LazyLock;
 use std::sync::Mutex;
 use std::sync::OnceLock;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 
 /// Set this to add a suffix to the User-Agent string.
 ///
@@ -28,6 +32,10 @@ use std::sync::OnceLock;
 /// The full user agent string is returned from the mcp initialize response.
 /// Parenthesis will be added by Codex. This should only specify what goes inside of the parenthesis.
 pub static USER_AGENT_SUFFIX: LazyLock<Mutex<Option<String>>> = LazyLock::new(|| Mutex::new(None));
+/// Set when `privacy.disable_all_network_except_providers` is on. It is a
+/// one-way latch: once a config enables it, nothing in the process clears it.
+static NON_PROVIDER_NETWORK_DISABLED: AtomicBool = AtomicBool::new(false);
+
 pub const DEFAULT_ORIGINATOR: &str = "codex_cli_rs";
 pub const CODEX_INTERNAL_ORIGINATOR_OVERRIDE_ENV_VAR: &str = "CODEX_INTERNAL_ORIGINATOR_OVERRIDE";
 
@@ -257,12 +265,99 @@ fn sanitize_user_agent(candidate: String, fallback: &str) -> String {
 }
 
 /// Create an HTTP client with default `originator` and `User-Agent` headers set.
-pub fn create_client() -> CodexHttpClient {
-    let inner = build_reqwest_client();
-    CodexHttpClient::new(inner)
+/// Fails while the privacy kill switch is on; model provider and sign-in
+/// traffic uses [`create_provider_client`] instead.
+pub fn create_client() -> std::io::Result<CodexHttpClient> {
+    build_reqwest_client().map(CodexHttpClient::new)
+}
+
+/// [`create_client`] for requests to a model provider or its sign-in service,
+/// the only traffic the privacy kill switch allows.
+pub fn create_provider_client() -> CodexHttpClient {
+    CodexHttpClient::new(build_provider_reqwest_client())
 }
 
-pub fn build_reqwest_client() -> reqwest::Client {
+/// Blocks all non-provider traffic (update checks, feedback upload, ...) for
+/// the rest of the process.
+pub fn disable_non_provider_network() {
+    NON_PROVIDER_NETWORK_DISABLED.store(true, Ordering::SeqCst);
+}
+
+/// Applies the privacy kill switch of the configuration a binary runs with.
+/// Called once at each entrypoint rather than when a config is loaded, so
+/// loading another config, e.g. to diagnose it, does not affect the process.
+pub fn apply_privacy(privacy: &Privacy) {
+    if privacy.disable_all_network_except_providers {
+        disable_non_provider_network();
+    }
+}
+
+pub fn non_provider_network_allowed() -> bool {
+    !NON_PROVIDER_NETWORK_DISABLED.load(Ordering::SeqCst)
+}
+
+/// Fails with `PermissionDenied` when the privacy kill switch is on.
+/// `purpose` names the blocked feature in the error message.
+pub fn check_non_provider_network(purpose: &str) -> std::io::Result<()> {
+    if non_provider_network_allowed() {
+        Ok(())
+    } else {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!("{purpose} is disabled by privacy.disable_all_network_except_providers"),
+        ))
+    }
+}
+
+/// [`create_client`] with `purpose` naming the blocked feature in the error.
+pub fn create_non_provider_client(purpose: &str) -> std::io::Result<CodexHttpClient> {
+    check_non_provider_network(purpose)?;
+    create_client()
+}
+
+/// [`create_non_provider_client`] with a model provider's `tls` settings, for
+/// requests that pass through the same TLS-intercepting proxy as model
+/// traffic, such as the update check.
+pub fn create_non_provider_client_with_tls(
+    purpose: &str,
+    tls: Option<&ProviderTlsConfig>,
+) -> std::io::Result<CodexHttpClient> {
+    check_non_provider_network(purpose)?;
+    let Some(tls) = tls else {
+        return create_client();
+    };
+    let builder = tls
+        .apply(reqwest_client_builder()?)
+        .map_err(|err| std::io::Error::other(err.to_string()))?;
+    let inner = builder.build().map_err(std::io::Error::other)?;
+    Ok(CodexHttpClient::new(inner))
+}
+
+/// The `reqwest` client behind [`create_client`]. Fails while the privacy kill
+/// switch is on.
+pub fn build_reqwest_client() -> std::io::Result<reqwest::Client> {
+    reqwest_client_builder()?
+        .build()
+        .map_err(std::io::Error::other)
+}
+
+/// The `reqwest` client behind [`create_provider_client`].
+pub fn build_provider_reqwest_client() -> reqwest::Client {
+    provider_reqwest_client_builder()
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+}
+
+/// The builder behind [`build_reqwest_client`], for callers that need to add
+/// their own settings (timeouts, TLS) on top of the Codex defaults. Fails
+/// while the privacy kill switch is on.
+pub fn reqwest_client_builder() -> std::io::Result<reqwest::ClientBuilder> {
+    check_non_provider_network("Network access outside model providers")?;
+    Ok(provider_reqwest_client_builder())
+}
+
+/// [`reqwest_client_builder`] for model provider and sign-in traffic.
+pub fn provider_reqwest_client_builder() -> reqwest::ClientBuilder {
     use reqwest::header::HeaderMap;
 
     let mut headers = HeaderMap::new();
@@ -276,8 +371,7 @@ pub fn build_reqwest_client() -> reqwest::Client {
     if is_sandboxed() {
         builder = builder.no_proxy();
     }
-
-    builder.build().unwrap_or_else(|_| reqwest::Client::new())
+    builder
 }
 
 fn is_sandboxed() -> bool {
@@ -305,7 +399,7 @@ mod tests {
         use wiremock::matchers::method;
         use wiremock::matchers::path;
 
-        let client = create_client();
+        let client = create_client().expect("create client");
 
         // Spin up a local mock server and capture a request.
         let server = MockServer::start().await;
@@ -0,0 +1,103 @@
+//! `Op::DryRun` (`codex exec --dry-run`): what the first request of a turn
+//! would send, assembled by the same code as a real turn but never sent.
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::DryRunReportEvent;
+use codex_protocol::protocol::PinnedFile;
+use codex_protocol::user_input::UserInput;
+
+use crate::client_common::Prompt;
+use crate::codex::TurnContext;
+use crate::project_doc::instruction_file_paths;
+use crate::truncate::approx_token_count;
+
+/// Tokens counted for an attached image, instead of the size of its data
+/// URL, which says little about what the model is billed for it.
+const IMAGE_TOKENS: u64 = 765;
+
+/// Describes `prompt`, built for `turn_context` from `items`.
+pub(crate) fn report(
+    turn_context: &TurnContext,
+    prompt: &Prompt,
+    items: &[UserInput],
+    pinned_files: Vec<PinnedFile>,
+) -> DryRunReportEvent {
+    let model_family = turn_context.client.get_model_family();
+    let instructions_tokens = tokens(&prompt.get_full_instructions(&model_family));
+    let tools_tokens = tokens(&serde_json::to_string(&prompt.tools).unwrap_or_default());
+    let input_tokens = prompt.get_formatted_input().iter().map(item_tokens).sum();
+    let images = items
+        .iter()
+        .filter_map(|item| match item {
+            UserInput::LocalImage { path } => Some(path.clone()),
+            _ => None,
+        })
+        .collect();
+    let writable_roots = turn_context
+        .sandbox_policy
+        .get_writable_roots_with_cwd(&turn_context.cwd)
+        .into_iter()
+        .map(|root| root.root)
+        .collect();
+
+    DryRunReportEvent {
+        model: turn_context.client.get_model(),
+        model_context_window: turn_context.client.get_model_context_window(),
+        estimated_prompt_tokens: instructions_tokens + tools_tokens + input_tokens,
+        instructions_tokens,
+        tools_tokens,
+        input_tokens,
+        instruction_files: instruction_file_paths(&turn_context.client.config()),
+        pinned_files,
+        images,
+        tools: prompt
+            .tools
+            .iter()
+            .map(|tool| tool.name().to_string())
+            .collect(),
+        approval_policy: turn_context.approval_policy,
+        sandbox_policy: turn_context.sandbox_policy.clone(),
+        writable_roots,
+    }
+}
+
+fn tokens(text: &str) -> u64 {
+    approx_token_count(text) as u64
+}
+
+fn item_tokens(item: &ResponseItem) -> u64 {
+    match item {
+        ResponseItem::Message { content, .. } => content
+            .iter()
+            .map(|content| match content {
+                ContentItem::InputText { text } | ContentItem::OutputText { text } => tokens(text),
+                ContentItem::InputImage { .. } => IMAGE_TOKENS,
+            })
+            .sum(),
+        item => tokens(&serde_json::to_string(item).unwrap_or_default()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn images_count_a_flat_estimate_instead_of_their_data() {
+        let message = |content: Vec<ContentItem>| ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content,
+        };
+        let text = message(vec![ContentItem::InputText {
+            text: "x".repeat(400),
+        }]);
+        let image = message(vec![ContentItem::InputImage {
+            image_url: format!("data:image/png;base64,{}", "A".repeat(100_000)),
+        }]);
+        assert_eq!(item_tokens(&text), 100);
+        assert_eq!(item_tokens(&image), IMAGE_TOKENS);
+    }
+}
@@ -1,5 +1,7 @@
 use crate::codex::ProcessedResponseItem;
 use crate::exec::ExecToolCallOutput;
+use crate::rate_limits::binding_window;
+use crate::rate_limits::window_label;
 use crate::token_data::KnownPlan;
 use crate::token_data::PlanType;
 use crate::truncate::TruncationPolicy;
@@ -13,6 +15,7 @@ use codex_protocol::ConversationId;
 use codex_protocol::protocol::CodexErrorInfo;
 use codex_protocol::protocol::ErrorEvent;
 use codex_protocol::protocol::RateLimitSnapshot;
+use codex_protocol::protocol::RateLimitWindow;
 use reqwest::StatusCode;
 use serde_json;
 use std::io;
@@ -79,6 +82,12 @@ pub enum CodexErr {
     )]
     ContextWindowExceeded,
 
+    /// The request body is larger than the provider accepts, either because
+    /// `max_request_body_bytes` is configured or the server answered 413.
+    /// The Session loop compacts the conversation and retries once.
+    #[error("{0}")]
+    RequestTooLarge(RequestTooLargeError),
+
     #[error("no conversation with id: {0}")]
     ConversationNotFound(ConversationId),
 
@@ -311,6 +320,33 @@ impl std::fmt::Display for RetryLimitReachedError {
     }
 }
 
+#[derive(Debug)]
+pub struct RequestTooLargeError {
+    /// Serialized size of the rejected request, when known.
+    pub size: Option<u64>,
+    /// The configured `max_request_body_bytes`, when the request was rejected locally.
+    pub limit: Option<u64>,
+}
+
+impl std::fmt::Display for RequestTooLargeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.size, self.limit) {
+            (Some(size), Some(limit)) => write!(
+                f,
+                "request of {size} bytes exceeds the provider's max_request_body_bytes ({limit})"
+            )?,
+            _ => write!(
+                f,
+                "the provider rejected the request as too large (HTTP 413)"
+            )?,
+        }
+        write!(
+            f,
+            ". Compact the conversation or raise max_request_body_bytes for this provider."
+        )
+    }
+}
+
 #[derive(Debug)]
 pub struct UsageLimitReachedError {
     pub(crate) plan_type: Option<PlanType>,
@@ -318,36 +354,57 @@ pub struct UsageLimitReachedError {
     pub(crate) rate_limits: Option<RateLimitSnapshot>,
 }
 
+impl UsageLimitReachedError {
+    /// The exhausted rate-limit window that resets last. When both the 5-hour and the weekly
+    /// window are used up, the weekly one decides when usage becomes available again.
+    pub(crate) fn binding_window(&self) -> Option<&RateLimitWindow> {
+        binding_window(self.rate_limits.as_ref()?)
+    }
+
+    /// When usage becomes available again: the reported reset time, else the
+    /// reset of the binding window.
+    pub(crate) fn recovers_at(&self) -> Option<DateTime<Utc>> {
+        self.resets_at.or_else(|| {
+            self.binding_window()
+                .and_then(|window| window.resets_at)
+                .and_then(|ts| DateTime::<Utc>::from_timestamp(ts, 0))
+        })
+    }
+}
+
 impl std::fmt::Display for UsageLimitReachedError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hit = match self.binding_window().and_then(window_label) {
+            Some(label) => format!("You've hit your {label} usage limit"),
+            None => "You've hit your usage limit".to_string(),
+        };
         let message = match self.plan_type.as_ref() {
             Some(PlanType::Known(KnownPlan::Plus)) => format!(
-                "You've hit your usage limit. Upgrade to Pro (https://openai.com/chatgpt/pricing), visit https://chatgpt.com/codex/settings/usage to purchase more credits{}",
+                "{hit}. Upgrade to Pro (https://openai.com/chatgpt/pricing), visit https://chatgpt.com/codex/settings/usage to purchase more credits{}",
                 retry_suffix_after_or(self.resets_at.as_ref())
             ),
             Some(PlanType::Known(KnownPlan::Team)) | Some(PlanType::Known(KnownPlan::Business)) => {
                 format!(
-                    "You've hit your usage limit. To get more access now, send a request to your admin{}",
+                    "{hit}. To get more access now, send a request to your admin{}",
                     retry_suffix_after_or(self.resets_at.as_ref())
                 )
             }
             Some(PlanType::Known(KnownPlan::Free)) => {
-                "You've hit your usage limit. Upgrade to Plus to continue using Codex (https://openai.com/chatgpt/pricing)."
-                    .to_string()
+                format!(
+                    "{hit}. Upgrade to Plus to continue using Codex (https://openai.com/chatgpt/pricing)."
+                )
             }
             Some(PlanType::Known(KnownPlan::Pro)) => format!(
-                "You've hit your usage limit. Visit https://chatgpt.com/codex/settings/usage to purchase more credits{}",
+                "{hit}. Visit https://chatgpt.com/codex/settings/usage to purchase more credits{}",
                 retry_suffix_after_or(self.resets_at.as_ref())
             ),
             Some(PlanType::Known(KnownPlan::Enterprise))
-            | Some(PlanType::Known(KnownPlan::Edu)) => format!(
-                "You've hit your usage limit.{}",
-                retry_suffix(self.resets_at.as_ref())
-            ),
-            Some(PlanType::Unknown(_)) | None => format!(
-                "You've hit your usage limit.{}",
-                retry_suffix(self.resets_at.as_ref())
-            ),
+            | Some(PlanType::Known(KnownPlan::Edu)) => {
+                format!("{hit}.{}", retry_suffix(self.resets_at.as_ref()))
+            }
+            Some(PlanType::Unknown(_)) | None => {
+                format!("{hit}.{}", retry_suffix(self.resets_at.as_ref()))
+            }
         };
 
         write!(f, "{message}")
@@ -372,7 +429,7 @@ fn retry_suffix_after_or(resets_at: Option<&DateTime<Utc>>) -> String {
     }
 }
 
-fn format_retry_timestamp(resets_at: &DateTime<Utc>) -> String {
+pub(crate) fn format_retry_timestamp(resets_at: &DateTime<Utc>) -> String {
     let local_reset = resets_at.with_timezone(&Local);
     let local_now = now_for_retry().with_timezone(&Local);
     if local_reset.date_naive() == local_now.date_naive() {
@@ -461,9 +518,9 @@ impl CodexErr {
             CodexErr::SessionConfiguredNotFirstEvent
             | CodexErr::InternalServerError
             | CodexErr::InternalAgentDied => CodexErrorInfo::InternalServerError,
-            CodexErr::UnsupportedOperation(_) | CodexErr::ConversationNotFound(_) => {
-                CodexErrorInfo::BadRequest
-            }
+            CodexErr::UnsupportedOperation(_)
+            | CodexErr::ConversationNotFound(_)
+            | CodexErr::RequestTooLarge(_) => CodexErrorInfo::BadRequest,
             CodexErr::Sandbox(_) => CodexErrorInfo::SandboxError,
             _ => CodexErrorInfo::Other,
         }
@@ -565,6 +622,7 @@ mod tests {
                 resets_at: Some(secondary_reset_at),
             }),
             credits: None,
+            request_limits: None,
         }
     }
 
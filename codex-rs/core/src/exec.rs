@@ -25,6 +25,7 @@ use crate::protocol::EventMsg;
 use crate::protocol::ExecCommandOutputDeltaEvent;
 use crate::protocol::ExecOutputStream;
 use crate::protocol::SandboxPolicy;
+use crate::redact::redact_secrets;
 use crate::sandboxing::CommandSpec;
 use crate::sandboxing::ExecEnv;
 use crate::sandboxing::SandboxManager;
@@ -462,9 +463,11 @@ impl StreamOutput<String> {
 }
 
 impl StreamOutput<Vec<u8>> {
+    /// Decodes the output, redacting secrets such as project API keys that
+    /// the command printed.
     pub fn from_utf8_lossy(&self) -> StreamOutput<String> {
         StreamOutput {
-            text: bytes_to_string_smart(&self.text),
+            text: redact_secrets(&bytes_to_string_smart(&self.text)).into_owned(),
             truncated_after_lines: self.truncated_after_lines,
         }
     }
@@ -0,0 +1,227 @@
+//! Notices files changed outside the session while it runs, such as edits
+//! made in the user's editor, so a patch the model wrote against an earlier
+//! version of a file is not applied on top of those edits.
+//!
+//! The working directory is watched for changes. Changes made while one of
+//! the agent's own mutating tool calls runs, or shortly after it, are the
+//! agent's; every other change is external. A patch that targets an
+//! externally changed file is refused once, and the model is asked to read
+//! the file again before it retries.
+
+use std::collections::HashSet;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+use std::time::Instant;
+
+use notify::Event;
+use notify::EventKind;
+use notify::RecommendedWatcher;
+use notify::RecursiveMode;
+use notify::Watcher;
+use notify::event::ModifyKind;
+use tracing::warn;
+
+/// How long after one of the agent's tool calls its writes may still be
+/// reported, since file system events arrive with some delay.
+const AGENT_WRITE_GRACE: Duration = Duration::from_secs(1);
+/// Changes in these directories never matter to a patch.
+const IGNORED_DIRS: &[&str] = &[".git", "node_modules", "target"];
+
+#[derive(Default)]
+pub(crate) struct ExternalEdits {
+    watching: Mutex<Option<Watching>>,
+    state: Arc<Mutex<State>>,
+}
+
+struct Watching {
+    root: PathBuf,
+    _watcher: RecommendedWatcher,
+}
+
+#[derive(Default)]
+struct State {
+    /// The agent's mutating tool calls in progress.
+    agent_calls: usize,
+    agent_quiet_at: Option<Instant>,
+    changed: HashSet<PathBuf>,
+}
+
+impl State {
+    fn agent_writing(&self, now: Instant) -> bool {
+        self.agent_calls > 0 || self.agent_quiet_at.is_some_and(|quiet_at| now < quiet_at)
+    }
+
+    fn record(&mut self, event: Event, now: Instant) {
+        let relevant = match event.kind {
+            EventKind::Create(_) | EventKind::Remove(_) => true,
+            EventKind::Modify(kind) => !matches!(kind, ModifyKind::Metadata(_)),
+            _ => false,
+        };
+        if !relevant || self.agent_writing(now) {
+            return;
+        }
+        for path in event.paths {
+            let ignored = path.components().any(|component| match component {
+                Component::Normal(name) => IGNORED_DIRS.iter().any(|dir| name == *dir),
+                _ => false,
+            });
+            if !ignored {
+                self.changed.insert(path);
+            }
+        }
+    }
+}
+
+/// Held while one of the agent's tool calls may write files.
+pub(crate) struct AgentWriteGuard {
+    state: Arc<Mutex<State>>,
+}
+
+impl Drop for AgentWriteGuard {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.agent_calls = state.agent_calls.saturating_sub(1);
+        state.agent_quiet_at = Some(Instant::now() + AGENT_WRITE_GRACE);
+    }
+}
+
+impl ExternalEdits {
+    /// Starts watching `root`, unless it is already watched. Changes seen
+    /// under a previous root are kept.
+    pub(crate) fn watch(&self, root: &Path) {
+        let mut watching = self.watching.lock().unwrap_or_else(PoisonError::into_inner);
+        if watching
+            .as_ref()
+            .is_some_and(|watching| watching.root == root)
+        {
+            return;
+        }
+        let state = Arc::clone(&self.state);
+        let watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            if let Ok(event) = event {
+                state
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .record(event, Instant::now());
+            }
+        })
+        .and_then(|mut watcher| {
+            watcher.watch(root, RecursiveMode::Recursive)?;
+            Ok(watcher)
+        });
+        match watcher {
+            Ok(watcher) => {
+                *watching = Some(Watching {
+                    root: root.to_path_buf(),
+                    _watcher: watcher,
+                });
+            }
+            Err(err) => {
+                warn!(
+                    "not watching {} for external changes: {err}",
+                    root.display()
+                );
+            }
+        }
+    }
+
+    /// Marks a tool call of the agent's that may write files; its changes
+    /// are not reported until the guard is dropped.
+    pub(crate) fn agent_writing(&self) -> AgentWriteGuard {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .agent_calls += 1;
+        AgentWriteGuard {
+            state: Arc::clone(&self.state),
+        }
+    }
+
+    /// Which of `paths` changed externally since they were last taken,
+    /// sorted. They are not reported again until they change again.
+    pub(crate) fn take_changed<'a>(
+        &self,
+        paths: impl IntoIterator<Item = &'a Path>,
+    ) -> Vec<PathBuf> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut changed: Vec<PathBuf> = paths
+            .into_iter()
+            .filter(|path| state.changed.remove(*path))
+            .map(Path::to_path_buf)
+            .collect();
+        changed.sort();
+        changed.dedup();
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::CreateKind;
+    use notify::event::DataChange;
+    use notify::event::MetadataKind;
+    use pretty_assertions::assert_eq;
+
+    fn event(kind: EventKind, path: &str) -> Event {
+        Event::new(kind).add_path(PathBuf::from(path))
+    }
+
+    #[test]
+    fn only_changes_outside_agent_calls_are_external() {
+        let edits = ExternalEdits::default();
+        let record = |kind: EventKind, path: &str, now: Instant| {
+            edits
+                .state
+                .lock()
+                .expect("state")
+                .record(event(kind, path), now);
+        };
+        let modified = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+
+        record(modified, "/repo/src/lib.rs", Instant::now());
+        record(
+            EventKind::Modify(ModifyKind::Metadata(MetadataKind::Permissions)),
+            "/repo/src/main.rs",
+            Instant::now(),
+        );
+        record(modified, "/repo/.git/index", Instant::now());
+        {
+            let _guard = edits.agent_writing();
+            record(
+                EventKind::Create(CreateKind::File),
+                "/repo/src/new.rs",
+                Instant::now(),
+            );
+        }
+        // Late events from the agent's call are still the agent's.
+        record(modified, "/repo/src/late.rs", Instant::now());
+        record(
+            modified,
+            "/repo/src/later.rs",
+            Instant::now() + AGENT_WRITE_GRACE * 2,
+        );
+
+        let paths = [
+            "/repo/src/later.rs",
+            "/repo/src/lib.rs",
+            "/repo/src/main.rs",
+            "/repo/src/new.rs",
+            "/repo/src/late.rs",
+            "/repo/.git/index",
+        ];
+        assert_eq!(
+            edits.take_changed(paths.iter().map(Path::new)),
+            vec![
+                PathBuf::from("/repo/src/later.rs"),
+                PathBuf::from("/repo/src/lib.rs"),
+            ]
+        );
+        assert!(edits.take_changed(paths.iter().map(Path::new)).is_empty());
+    }
+}
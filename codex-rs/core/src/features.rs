@@ -55,6 +55,24 @@ pub enum Feature {
     Skills,
     /// Send warnings to the model to correct it on the tool usage.
     ModelWarnings,
+    /// Pause the session when a turn looks pathological (token spikes, loops).
+    SessionGuardrail,
+    /// Apply project file templates to files created by apply_patch.
+    FileTemplates,
+    /// Include the workspace_stats tool.
+    WorkspaceStatsTool,
+    /// Commit each turn to a `codex/` task branch.
+    AutoCommit,
+    /// Send a map of the workspace's files and symbols with every turn.
+    RepoMap,
+    /// Include the search_code tool backed by a local code index.
+    SearchCodeTool,
+    /// Refuse patches to files changed outside the session since the model saw them.
+    DetectExternalEdits,
+    /// Include the fetch_url tool.
+    FetchUrlTool,
+    /// Let the model delegate subtasks to sub-agents with spawn_agent and wait_agents.
+    SubAgents,
 }
 
 impl Feature {
@@ -342,4 +360,58 @@ pub const FEATURES: &[FeatureSpec] = &[
         stage: Stage::Experimental,
         default_enabled: false,
     },
+    FeatureSpec {
+        id: Feature::SessionGuardrail,
+        key: "session_guardrail",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::FileTemplates,
+        key: "file_templates",
+        stage: Stage::Beta,
+        default_enabled: true,
+    },
+    FeatureSpec {
+        id: Feature::WorkspaceStatsTool,
+        key: "workspace_stats_tool",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::AutoCommit,
+        key: "auto_commit",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::RepoMap,
+        key: "repo_map",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::SearchCodeTool,
+        key: "search_code_tool",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::DetectExternalEdits,
+        key: "detect_external_edits",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::FetchUrlTool,
+        key: "fetch_url_tool",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::SubAgents,
+        key: "sub_agents",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
 ];
@@ -0,0 +1,281 @@
+//! Project templates for files created through `apply_patch`.
+//!
+//! Templates live in `.codex/templates/`, looked up from the new file's
+//! directory upward to the repository root. A template's file name, minus an
+//! optional `.tmpl` suffix, is a glob matched against the new file's name
+//! (`*.rs`, `test_*.py`, `Dockerfile`); the longest matching pattern wins.
+//! `{{content}}` in the template is replaced with what the model wrote, and a
+//! template without it is used as a header. `{{file_name}}`, `{{file_stem}}`,
+//! and `{{year}}` are substituted as well.
+//!
+//! Without a matching template, a leading comment block that every sibling
+//! with the same extension shares (usually a license header) is prepended.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::Datelike;
+use codex_apply_patch::ApplyPatchAction;
+use codex_apply_patch::ApplyPatchFileChange;
+use wildmatch::WildMatch;
+
+const TEMPLATES_DIR: &str = ".codex/templates";
+const TEMPLATE_SUFFIX: &str = ".tmpl";
+const CONTENT_PLACEHOLDER: &str = "{{content}}";
+
+/// Siblings sampled when inferring a shared header.
+const MAX_SIBLINGS: usize = 8;
+const MIN_SIBLINGS: usize = 2;
+const MAX_SIBLING_BYTES: u64 = 256 * 1024;
+
+/// Rewrites every file added by `action` to follow its template.
+pub(crate) fn apply_file_templates(action: &mut ApplyPatchAction) {
+    let added: Vec<(PathBuf, String)> = action
+        .changes()
+        .iter()
+        .filter_map(|(path, change)| match change {
+            ApplyPatchFileChange::Add { content } => Some((path.clone(), content.clone())),
+            _ => None,
+        })
+        .collect();
+    for (path, content) in added {
+        if let Some(templated) = templated_content(&path, &content)
+            && templated != content
+        {
+            action.replace_added_file(&path, &templated);
+        }
+    }
+}
+
+fn templated_content(path: &Path, content: &str) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    if path.ancestors().any(|dir| dir.ends_with(TEMPLATES_DIR)) {
+        return None;
+    }
+    if let Some(template) = find_template(path, file_name) {
+        return Some(render(&template, path, content));
+    }
+    sibling_header(path).map(|header| with_header(&header, content))
+}
+
+fn find_template(path: &Path, file_name: &str) -> Option<String> {
+    let templates_dir = path.parent()?.ancestors().find_map(|dir| {
+        let candidate = dir.join(TEMPLATES_DIR);
+        if candidate.is_dir() {
+            Some(Some(candidate))
+        } else if dir.join(".git").exists() {
+            // Stop at the repository root.
+            Some(None)
+        } else {
+            None
+        }
+    })??;
+
+    let mut best: Option<(String, PathBuf)> = None;
+    for entry in std::fs::read_dir(templates_dir).ok()?.flatten() {
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        let pattern = name
+            .strip_suffix(TEMPLATE_SUFFIX)
+            .unwrap_or(&name)
+            .to_string();
+        if entry.path().is_file()
+            && WildMatch::new(&pattern).matches(file_name)
+            && best
+                .as_ref()
+                .is_none_or(|(current, _)| (pattern.len(), &pattern) > (current.len(), current))
+        {
+            best = Some((pattern, entry.path()));
+        }
+    }
+    std::fs::read_to_string(best?.1).ok()
+}
+
+fn render(template: &str, path: &Path, content: &str) -> String {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let file_stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let fill = |text: &str| {
+        text.replace("{{file_name}}", &file_name)
+            .replace("{{file_stem}}", &file_stem)
+            .replace("{{year}}", &chrono::Local::now().year().to_string())
+    };
+    match template.split_once(CONTENT_PLACEHOLDER) {
+        Some((before, after)) => {
+            let before = fill(before);
+            // The model already followed the template; don't apply it twice.
+            if content.starts_with(before.trim_end()) {
+                return content.to_string();
+            }
+            format!("{before}{content}{}", fill(after))
+        }
+        None => with_header(&fill(template), content),
+    }
+}
+
+/// Puts `header` at the top of `content`, after any shebang line, unless it
+/// is already there.
+fn with_header(header: &str, content: &str) -> String {
+    let (shebang, body) = split_shebang(content);
+    if body.starts_with(header.trim_end()) {
+        return content.to_string();
+    }
+    let mut header = header.to_string();
+    if !header.ends_with('\n') {
+        header.push('\n');
+    }
+    format!("{shebang}{header}{body}")
+}
+
+fn split_shebang(content: &str) -> (&str, &str) {
+    if content.starts_with("#!") {
+        let end = content.find('\n').map_or(content.len(), |i| i + 1);
+        content.split_at(end)
+    } else {
+        ("", content)
+    }
+}
+
+/// The leading comment block shared by every sampled sibling with the same
+/// extension, including one blank line after it when they all have one.
+fn sibling_header(path: &Path) -> Option<String> {
+    let extension = path.extension()?.to_str()?;
+    let prefixes = comment_prefixes(extension)?;
+    let mut siblings: Vec<PathBuf> = std::fs::read_dir(path.parent()?)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|sibling| {
+            sibling != path
+                && sibling.extension().and_then(|ext| ext.to_str()) == Some(extension)
+                && sibling
+                    .metadata()
+                    .is_ok_and(|meta| meta.is_file() && meta.len() <= MAX_SIBLING_BYTES)
+        })
+        .collect();
+    if siblings.len() < MIN_SIBLINGS {
+        return None;
+    }
+    siblings.sort();
+    siblings.truncate(MAX_SIBLINGS);
+
+    let mut shared: Option<String> = None;
+    for sibling in siblings {
+        let text = std::fs::read_to_string(sibling).ok()?;
+        let header = leading_comment_block(split_shebang(&text).1, prefixes)?;
+        match &shared {
+            Some(shared) if *shared != header => return None,
+            Some(_) => {}
+            None => shared = Some(header),
+        }
+    }
+    shared
+}
+
+fn leading_comment_block(text: &str, prefixes: &[&str]) -> Option<String> {
+    let mut block = String::new();
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        // Doc comments describe the file itself, so they are never shared.
+        let is_doc = trimmed.starts_with("///") || trimmed.starts_with("//!");
+        if !is_doc && prefixes.iter().any(|prefix| trimmed.starts_with(prefix)) {
+            block.push_str(line);
+        } else {
+            if !block.is_empty() && line.trim().is_empty() {
+                block.push_str(line);
+            }
+            break;
+        }
+    }
+    (!block.is_empty()).then_some(block)
+}
+
+fn comment_prefixes(extension: &str) -> Option<&'static [&'static str]> {
+    match extension {
+        "rs" | "go" | "c" | "h" | "cc" | "cpp" | "hpp" | "java" | "kt" | "kts" | "swift"
+        | "scala" | "cs" | "js" | "jsx" | "mjs" | "ts" | "tsx" | "dart" | "php" | "proto" => {
+            Some(&["//", "/*", "*"])
+        }
+        "py" | "sh" | "bash" | "zsh" | "rb" | "pl" | "toml" | "yaml" | "yml" | "r" | "ex"
+        | "exs" | "nix" | "cmake" | "bzl" => Some(&["#"]),
+        "sql" | "lua" | "hs" => Some(&["--"]),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+
+    const LICENSE: &str = "// Copyright Example Corp.\n// SPDX-License-Identifier: MIT\n\n";
+
+    #[test]
+    fn explicit_template_wraps_content() {
+        let root = tempfile::tempdir().expect("tempdir");
+        fs::create_dir(root.path().join(".git")).expect("git dir");
+        let templates = root.path().join(TEMPLATES_DIR);
+        fs::create_dir_all(&templates).expect("templates dir");
+        fs::write(templates.join("*.py.tmpl"), "# {{file_stem}}\n{{content}}").expect("write");
+        fs::write(templates.join("test_*.py"), "import pytest\n\n{{content}}").expect("write");
+
+        let path = root.path().join("pkg").join("test_parse.py");
+        assert_eq!(
+            templated_content(&path, "def test_ok():\n    pass\n").as_deref(),
+            Some("import pytest\n\ndef test_ok():\n    pass\n")
+        );
+        assert_eq!(
+            templated_content(&root.path().join("util.py"), "x = 1\n").as_deref(),
+            Some("# util\nx = 1\n")
+        );
+        assert_eq!(
+            templated_content(&path, "import pytest\n\nx = 1\n").as_deref(),
+            Some("import pytest\n\nx = 1\n")
+        );
+    }
+
+    #[test]
+    fn infers_header_shared_by_siblings() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        fs::write(dir.path().join("a.rs"), format!("{LICENSE}fn a() {{}}\n")).expect("write");
+        fs::write(
+            dir.path().join("b.rs"),
+            format!("{LICENSE}//! Module b.\nfn b() {{}}\n"),
+        )
+        .expect("write");
+
+        let path = dir.path().join("c.rs");
+        assert_eq!(
+            templated_content(&path, "fn c() {}\n"),
+            Some(format!("{LICENSE}fn c() {{}}\n"))
+        );
+        let already = format!("{LICENSE}fn c() {{}}\n");
+        assert_eq!(templated_content(&path, &already), Some(already.clone()));
+    }
+
+    #[test]
+    fn no_header_when_siblings_disagree() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        fs::write(dir.path().join("a.sh"), "#!/bin/sh\n# one\necho a\n").expect("write");
+        fs::write(dir.path().join("b.sh"), "# two\necho b\n").expect("write");
+        assert_eq!(
+            templated_content(&dir.path().join("c.sh"), "echo c\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn header_goes_after_shebang() {
+        assert_eq!(
+            with_header("# Licensed\n", "#!/usr/bin/env python3\nprint(1)\n"),
+            "#!/usr/bin/env python3\n# Licensed\nprint(1)\n"
+        );
+    }
+}
@@ -0,0 +1,426 @@
+//! Translation between Codex's conversation items and Gemini's
+//! `generateContent` request and streamed response chunks.
+
+use std::collections::HashMap;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::TokenUsage;
+use serde_json::Map;
+use serde_json::Value;
+use serde_json::json;
+
+use crate::client_common::ResponseEvent;
+
+/// Schema keywords Gemini function declarations accept; anything else (such
+/// as `additionalProperties`) is rejected by the API and gets dropped.
+const SCHEMA_KEYS: &[&str] = &[
+    "type",
+    "format",
+    "description",
+    "nullable",
+    "enum",
+    "items",
+    "properties",
+    "required",
+    "anyOf",
+    "minItems",
+    "maxItems",
+    "minimum",
+    "maximum",
+];
+
+pub(crate) fn build_request(instructions: &str, input: &[ResponseItem], tools: &[Value]) -> Value {
+    let mut system = vec![json!({ "text": instructions })];
+    let mut contents: Vec<(&'static str, Vec<Value>)> = Vec::new();
+    let mut push = |role: &'static str, part: Value| match contents.last_mut() {
+        Some((last_role, parts)) if *last_role == role => parts.push(part),
+        _ => contents.push((role, vec![part])),
+    };
+    // `functionResponse` is matched to its call by name, not by id.
+    let mut call_names: HashMap<&str, &str> = HashMap::new();
+
+    for item in input {
+        match item {
+            ResponseItem::Message { role, content, .. } => {
+                let role = match role.as_str() {
+                    "assistant" => "model",
+                    "user" => "user",
+                    _ => {
+                        system.extend(content.iter().filter_map(text_part));
+                        continue;
+                    }
+                };
+                for part in content.iter().filter_map(content_part) {
+                    push(role, part);
+                }
+            }
+            ResponseItem::FunctionCall {
+                id,
+                name,
+                arguments,
+                call_id,
+            } => {
+                call_names.insert(call_id.as_str(), name.as_str());
+                let mut part = json!({
+                    "functionCall": { "name": name, "args": function_args(arguments) }
+                });
+                if let Some(signature) = id {
+                    part["thoughtSignature"] = json!(signature);
+                }
+                push("model", part);
+            }
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                let name = call_names
+                    .get(call_id.as_str())
+                    .copied()
+                    .unwrap_or_default();
+                push(
+                    "user",
+                    json!({
+                        "functionResponse": {
+                            "name": name,
+                            "response": { "content": output.content },
+                        }
+                    }),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let mut body = Map::new();
+    body.insert("systemInstruction".to_string(), json!({ "parts": system }));
+    body.insert(
+        "contents".to_string(),
+        contents
+            .into_iter()
+            .map(|(role, parts)| json!({ "role": role, "parts": parts }))
+            .collect(),
+    );
+    let declarations: Vec<Value> = tools.iter().filter_map(function_declaration).collect();
+    if !declarations.is_empty() {
+        body.insert(
+            "tools".to_string(),
+            json!([{ "functionDeclarations": declarations }]),
+        );
+    }
+    Value::Object(body)
+}
+
+fn text_part(item: &ContentItem) -> Option<Value> {
+    match item {
+        ContentItem::InputText { text } | ContentItem::OutputText { text } if !text.is_empty() => {
+            Some(json!({ "text": text }))
+        }
+        _ => None,
+    }
+}
+
+fn content_part(item: &ContentItem) -> Option<Value> {
+    match item {
+        ContentItem::InputImage { image_url } => {
+            let inline = image_url
+                .strip_prefix("data:")
+                .and_then(|rest| rest.split_once(";base64,"));
+            Some(match inline {
+                Some((mime_type, data)) => {
+                    json!({ "inlineData": { "mimeType": mime_type, "data": data } })
+                }
+                None => json!({ "text": format!("[image: {image_url}]") }),
+            })
+        }
+        _ => text_part(item),
+    }
+}
+
+/// Tool arguments are a JSON string; Gemini wants an object.
+fn function_args(arguments: &str) -> Value {
+    match serde_json::from_str::<Value>(arguments) {
+        Ok(value @ Value::Object(_)) => value,
+        _ => json!({ "arguments": arguments }),
+    }
+}
+
+/// Converts a Responses API function tool into a Gemini function declaration.
+fn function_declaration(tool: &Value) -> Option<Value> {
+    if tool.get("type").and_then(Value::as_str) != Some("function") {
+        return None;
+    }
+    let mut declaration = json!({
+        "name": tool.get("name")?,
+        "description": tool.get("description").cloned().unwrap_or_else(|| json!("")),
+    });
+    // Gemini rejects an object schema without properties.
+    if let Some(parameters) = tool.get("parameters").map(sanitize_schema)
+        && parameters
+            .get("properties")
+            .and_then(Value::as_object)
+            .is_some_and(|properties| !properties.is_empty())
+    {
+        declaration["parameters"] = parameters;
+    }
+    Some(declaration)
+}
+
+fn sanitize_schema(schema: &Value) -> Value {
+    let Some(object) = schema.as_object() else {
+        return schema.clone();
+    };
+    let mut sanitized = Map::new();
+    for (key, value) in object {
+        if !SCHEMA_KEYS.contains(&key.as_str()) {
+            continue;
+        }
+        let value = match key.as_str() {
+            "properties" => Value::Object(
+                value
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(|(name, property)| (name.clone(), sanitize_schema(property)))
+                    .collect(),
+            ),
+            "items" => sanitize_schema(value),
+            "anyOf" => value
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(sanitize_schema)
+                .collect(),
+            _ => value.clone(),
+        };
+        sanitized.insert(key.clone(), value);
+    }
+    Value::Object(sanitized)
+}
+
+/// Accumulates streamed `GenerateContentResponse` chunks into response
+/// events. Text is streamed as deltas; function calls arrive whole.
+#[derive(Default)]
+pub(crate) struct StreamState {
+    message: Option<Vec<ContentItem>>,
+    function_calls: Vec<ResponseItem>,
+    usage: Option<TokenUsage>,
+    response_id: String,
+}
+
+impl StreamState {
+    pub(crate) fn handle_chunk(&mut self, chunk: &Value) -> Vec<ResponseEvent> {
+        let mut events = Vec::new();
+        if let Some(id) = chunk.get("responseId").and_then(Value::as_str) {
+            self.response_id = id.to_string();
+        }
+        if let Some(usage) = chunk.get("usageMetadata") {
+            self.usage = Some(token_usage(usage));
+        }
+
+        let parts = chunk
+            .pointer("/candidates/0/content/parts")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for part in parts {
+            let is_thought = part.get("thought").and_then(Value::as_bool) == Some(true);
+            if let Some(call) = part.get("functionCall") {
+                self.function_calls.push(ResponseItem::FunctionCall {
+                    // Gemini 3 requires the signature on the call it came with
+                    // when the call is sent back, so it rides along in `id`.
+                    id: part
+                        .get("thoughtSignature")
+                        .and_then(Value::as_str)
+                        .map(str::to_string),
+                    name: call
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                    arguments: call
+                        .get("args")
+                        .map(Value::to_string)
+                        .unwrap_or_else(|| "{}".to_string()),
+                    call_id: call
+                        .get("id")
+                        .and_then(Value::as_str)
+                        .map(str::to_string)
+                        .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple())),
+                });
+            } else if let Some(text) = part.get("text").and_then(Value::as_str)
+                && !is_thought
+                && !text.is_empty()
+            {
+                let message = self.message.get_or_insert_with(|| {
+                    events.push(ResponseEvent::OutputItemAdded(ResponseItem::Message {
+                        id: None,
+                        role: "assistant".to_string(),
+                        content: Vec::new(),
+                    }));
+                    Vec::new()
+                });
+                message.push(ContentItem::OutputText {
+                    text: text.to_string(),
+                });
+                events.push(ResponseEvent::OutputTextDelta(text.to_string()));
+            }
+        }
+        events
+    }
+
+    pub(crate) fn finish(self) -> Vec<ResponseEvent> {
+        let mut events = Vec::new();
+        if let Some(content) = self.message {
+            events.push(ResponseEvent::OutputItemDone(ResponseItem::Message {
+                id: None,
+                role: "assistant".to_string(),
+                content,
+            }));
+        }
+        events.extend(
+            self.function_calls
+                .into_iter()
+                .map(ResponseEvent::OutputItemDone),
+        );
+        events.push(ResponseEvent::Completed {
+            response_id: self.response_id,
+            token_usage: self.usage,
+        });
+        events
+    }
+}
+
+fn token_usage(usage: &Value) -> TokenUsage {
+    let field = |name: &str| usage.get(name).and_then(Value::as_i64).unwrap_or(0);
+    let input_tokens = field("promptTokenCount");
+    let output_tokens = field("candidatesTokenCount");
+    let reasoning_output_tokens = field("thoughtsTokenCount");
+    TokenUsage {
+        input_tokens,
+        cached_input_tokens: field("cachedContentTokenCount"),
+        output_tokens,
+        reasoning_output_tokens,
+        total_tokens: usage
+            .get("totalTokenCount")
+            .and_then(Value::as_i64)
+            .unwrap_or(input_tokens + output_tokens + reasoning_output_tokens),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn builds_contents_with_function_round_trip() {
+        let input = vec![
+            ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "list files".to_string(),
+                }],
+            },
+            ResponseItem::FunctionCall {
+                id: Some("sig".to_string()),
+                name: "shell".to_string(),
+                arguments: r#"{"command":["ls"]}"#.to_string(),
+                call_id: "call_1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "call_1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "main.rs".to_string(),
+                    content_items: None,
+                    success: Some(true),
+                },
+            },
+        ];
+        let tools = vec![
+            json!({
+                "type": "function",
+                "name": "shell",
+                "description": "Runs a command",
+                "strict": false,
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "command": { "type": "array", "items": { "type": "string" } },
+                    },
+                    "required": ["command"],
+                    "additionalProperties": false,
+                },
+            }),
+            json!({ "type": "custom", "name": "apply_patch" }),
+        ];
+
+        assert_eq!(
+            build_request("You are Codex.", &input, &tools),
+            json!({
+                "systemInstruction": { "parts": [{ "text": "You are Codex." }] },
+                "contents": [
+                    { "role": "user", "parts": [{ "text": "list files" }] },
+                    { "role": "model", "parts": [{
+                        "functionCall": { "name": "shell", "args": { "command": ["ls"] } },
+                        "thoughtSignature": "sig",
+                    }] },
+                    { "role": "user", "parts": [{
+                        "functionResponse": { "name": "shell", "response": { "content": "main.rs" } },
+                    }] },
+                ],
+                "tools": [{ "functionDeclarations": [{
+                    "name": "shell",
+                    "description": "Runs a command",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {
+                            "command": { "type": "array", "items": { "type": "string" } },
+                        },
+                        "required": ["command"],
+                    },
+                }] }],
+            })
+        );
+    }
+
+    #[test]
+    fn streams_text_and_collects_function_calls() {
+        let mut state = StreamState::default();
+        let first = state.handle_chunk(&json!({
+            "candidates": [{ "content": { "role": "model", "parts": [
+                { "text": "thinking", "thought": true },
+                { "text": "Let me " },
+            ] } }],
+        }));
+        assert!(matches!(
+            first.as_slice(),
+            [
+                ResponseEvent::OutputItemAdded(ResponseItem::Message { .. }),
+                ResponseEvent::OutputTextDelta(delta),
+            ] if delta == "Let me "
+        ));
+
+        state.handle_chunk(&json!({
+            "candidates": [{ "content": { "role": "model", "parts": [
+                { "text": "look." },
+                { "functionCall": { "id": "fc_1", "name": "shell", "args": { "command": ["ls"] } } },
+            ] }, "finishReason": "STOP" }],
+            "usageMetadata": { "promptTokenCount": 10, "candidatesTokenCount": 5, "totalTokenCount": 15 },
+            "responseId": "resp_1",
+        }));
+
+        let done = state.finish();
+        assert!(matches!(
+            done.as_slice(),
+            [
+                ResponseEvent::OutputItemDone(ResponseItem::Message { content, .. }),
+                ResponseEvent::OutputItemDone(ResponseItem::FunctionCall { call_id, arguments, .. }),
+                ResponseEvent::Completed { response_id, token_usage: Some(usage) },
+            ] if content.len() == 2
+                && call_id == "fc_1"
+                && arguments == r#"{"command":["ls"]}"#
+                && response_id == "resp_1"
+                && usage.total_tokens == 15
+        ));
+    }
+}
@@ -0,0 +1,218 @@
+//! Google Gemini provider (`wire_api = "gemini"`), for both the Gemini API
+//! and Vertex AI.
+//!
+//! Turns are streamed from `models/{model}:streamGenerateContent`. A provider
+//! with an `env_key` authenticates with that API key; one without it uses
+//! Google Application Default Credentials, which is how Vertex AI is reached.
+
+mod content;
+mod credentials;
+
+use codex_api::ResponseStream as ApiResponseStream;
+use codex_api::TransportError;
+use codex_api::error::ApiError;
+use codex_protocol::models::ResponseItem;
+use eventsource_stream::Eventsource;
+use futures::StreamExt;
+use http::StatusCode;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tracing::debug;
+
+use crate::api_bridge::map_api_error;
+use crate::client_common::ResponseEvent;
+use crate::error::CodexErr;
+use crate::error::EnvVarError;
+use crate::error::Result;
+use crate::model_provider_info::ModelProviderInfo;
+use crate::model_provider_info::non_empty_env;
+
+pub(crate) const GEMINI_API_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+const DEFAULT_VERTEX_LOCATION: &str = "global";
+
+/// Opens a `streamGenerateContent` stream for one turn.
+pub(crate) async fn stream_generate_content(
+    provider: &ModelProviderInfo,
+    model: &str,
+    instructions: &str,
+    input: &[ResponseItem],
+    tools: &[Value],
+) -> Result<ApiResponseStream> {
+    let api_key = provider.api_key()?;
+    let base_url = match (&provider.base_url, &api_key) {
+        (Some(base_url), _) => base_url.trim_end_matches('/').to_string(),
+        (None, Some(_)) => GEMINI_API_BASE_URL.to_string(),
+        (None, None) => vertex_base_url()?,
+    };
+    let model = model.strip_prefix("models/").unwrap_or(model);
+    let body = content::build_request(instructions, input, tools);
+
+    let mut request = provider
+        .build_reqwest_client()?
+        .post(format!("{base_url}/models/{model}:streamGenerateContent"))
+        .query(&[("alt", "sse")])
+        .json(&body);
+    if let Some(query_params) = &provider.query_params {
+        request = request.query(query_params);
+    }
+    for (name, value) in &provider.build_header_map()? {
+        request = request.header(name, value);
+    }
+    request = match api_key {
+        Some(key) => request.header("x-goog-api-key", key),
+        None => request.bearer_auth(credentials::access_token().await?),
+    };
+
+    let response = request.send().await.map_err(|err| {
+        map_api_error(ApiError::Transport(if err.is_timeout() {
+            TransportError::Timeout
+        } else {
+            TransportError::Network(err.to_string())
+        }))
+    })?;
+    let status = response.status();
+    if !status.is_success() {
+        let headers = response.headers().clone();
+        let text = response.text().await.unwrap_or_default();
+        return Err(map_api_error(classify_error(status, headers, text)));
+    }
+
+    let idle_timeout = provider.stream_idle_timeout();
+    let (tx_event, rx_event) = mpsc::channel::<std::result::Result<ResponseEvent, ApiError>>(1600);
+    tokio::spawn(async move {
+        let mut stream = response.bytes_stream().eventsource();
+        let mut state = content::StreamState::default();
+        loop {
+            let event = match timeout(idle_timeout, stream.next()).await {
+                Ok(Some(Ok(event))) => event,
+                Ok(Some(Err(err))) => {
+                    let _ = tx_event.send(Err(ApiError::Stream(err.to_string()))).await;
+                    return;
+                }
+                Ok(None) => break,
+                Err(_) => {
+                    let _ = tx_event
+                        .send(Err(ApiError::Stream("idle timeout waiting for SSE".into())))
+                        .await;
+                    return;
+                }
+            };
+            let chunk: Value = match serde_json::from_str(&event.data) {
+                Ok(chunk) => chunk,
+                Err(err) => {
+                    debug!(
+                        "Failed to parse Gemini SSE event: {err}, data: {}",
+                        event.data
+                    );
+                    continue;
+                }
+            };
+            if let Some(error) = chunk.get("error") {
+                let _ = tx_event
+                    .send(Err(ApiError::Stream(format!("Gemini error: {error}"))))
+                    .await;
+                return;
+            }
+            for event in state.handle_chunk(&chunk) {
+                if tx_event.send(Ok(event)).await.is_err() {
+                    return;
+                }
+            }
+        }
+        for event in state.finish() {
+            if tx_event.send(Ok(event)).await.is_err() {
+                return;
+            }
+        }
+    });
+    Ok(ApiResponseStream { rx_event })
+}
+
+/// `https://{location}-aiplatform.googleapis.com/v1/projects/{project}/locations/{location}/publishers/google`,
+/// with the project and location taken from the gcloud environment.
+fn vertex_base_url() -> Result<String> {
+    let project = credentials::project_id().ok_or_else(|| {
+        CodexErr::EnvVar(EnvVarError {
+            var: "GOOGLE_CLOUD_PROJECT".to_string(),
+            instructions: Some(
+                "Set it to the Google Cloud project to use Vertex AI with, or set `base_url` for this provider.".to_string(),
+            ),
+        })
+    })?;
+    let location = non_empty_env("GOOGLE_CLOUD_LOCATION")
+        .unwrap_or_else(|| DEFAULT_VERTEX_LOCATION.to_string());
+    let host = if location == "global" {
+        "aiplatform.googleapis.com".to_string()
+    } else {
+        format!("{location}-aiplatform.googleapis.com")
+    };
+    Ok(format!(
+        "https://{host}/v1/projects/{project}/locations/{location}/publishers/google"
+    ))
+}
+
+/// A prompt over the model's token limit is reported as a full context
+/// window. Quota (429) and capacity (503) errors stay HTTP errors so they are
+/// recorded as limit hits and move the `fallback` chain.
+fn classify_error(status: StatusCode, headers: http::HeaderMap, body: String) -> ApiError {
+    match status {
+        StatusCode::BAD_REQUEST if body.contains("exceeds the maximum number of tokens") => {
+            ApiError::ContextWindowExceeded
+        }
+        _ => ApiError::Transport(TransportError::Http {
+            status,
+            headers: Some(headers),
+            body: Some(body),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::failover_trigger;
+    use codex_protocol::protocol::AuthSwitchReason;
+
+    #[test]
+    fn token_limit_errors_are_context_window_exceeded() {
+        assert!(matches!(
+            classify_error(
+                StatusCode::BAD_REQUEST,
+                http::HeaderMap::new(),
+                r#"{"error":{"code":400,"message":"The input token count (1200000) exceeds the maximum number of tokens allowed (1048576).","status":"INVALID_ARGUMENT"}}"#.to_string(),
+            ),
+            ApiError::ContextWindowExceeded
+        ));
+        assert!(matches!(
+            classify_error(
+                StatusCode::TOO_MANY_REQUESTS,
+                http::HeaderMap::new(),
+                r#"{"error":{"status":"RESOURCE_EXHAUSTED"}}"#.to_string(),
+            ),
+            ApiError::Transport(TransportError::Http { .. })
+        ));
+    }
+
+    #[test]
+    fn quota_and_capacity_errors_trigger_failover() {
+        let quota = map_api_error(classify_error(
+            StatusCode::TOO_MANY_REQUESTS,
+            http::HeaderMap::new(),
+            r#"{"error":{"status":"RESOURCE_EXHAUSTED"}}"#.to_string(),
+        ));
+        assert!(matches!(
+            failover_trigger(&quota),
+            Some((None, AuthSwitchReason::RateLimited))
+        ));
+        let capacity = map_api_error(classify_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            http::HeaderMap::new(),
+            r#"{"error":{"status":"UNAVAILABLE"}}"#.to_string(),
+        ));
+        assert!(matches!(
+            failover_trigger(&capacity),
+            Some((Some(_), AuthSwitchReason::ProviderError))
+        ));
+    }
+}
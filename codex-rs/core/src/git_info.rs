@@ -473,7 +473,21 @@ async fn find_closest_sha(cwd: &Path, branches: &[String], remotes: &[String]) -
     closest_sha.map(|(sha, _)| sha)
 }
 
-async fn diff_against_sha(cwd: &Path, sha: &GitSha) -> Option<String> {
+/// Resolves `rev` to the full SHA of the commit it names.
+pub(crate) async fn resolve_commit(cwd: &Path, rev: &str) -> Option<GitSha> {
+    let spec = format!("{rev}^{{commit}}");
+    let output =
+        run_git_command_with_timeout(&["rev-parse", "--verify", "--quiet", &spec], cwd).await?;
+    if !output.status.success() {
+        return None;
+    }
+    let sha = String::from_utf8(output.stdout).ok()?;
+    let sha = sha.trim();
+    (!sha.is_empty()).then(|| GitSha::new(sha))
+}
+
+/// Diff of the working tree, including untracked files, against `sha`.
+pub(crate) async fn diff_against_sha(cwd: &Path, sha: &GitSha) -> Option<String> {
     let output =
         run_git_command_with_timeout(&["diff", "--no-textconv", "--no-ext-diff", &sha.0], cwd)
             .await?;
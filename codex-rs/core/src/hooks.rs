@@ -0,0 +1,240 @@
+//! User-supplied commands run at points of a session's lifecycle (`[hooks]`
+//! in `config.toml`). Each hook gets a JSON payload describing the event on
+//! stdin. A hook that exits non-zero blocks the action, with its stderr (or
+//! stdout) as the reason. A hook that exits zero and prints a JSON object
+//! may replace part of the action: `{"arguments": ...}` for `pre_tool`,
+//! `{"output": ...}` for `post_tool`, and `{"patch": ...}` for `pre_patch`.
+//! Hooks fail closed: one that cannot be started or times out blocks the
+//! action as well.
+
+use std::path::Path;
+use std::process::Output;
+use std::process::Stdio;
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::config::types::Hook;
+use crate::config::types::Hooks;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HookEvent {
+    SessionStart,
+    SessionEnd,
+    PreTool,
+    PostTool,
+    PrePatch,
+}
+
+impl HookEvent {
+    pub(crate) fn key(self) -> &'static str {
+        match self {
+            HookEvent::SessionStart => "session_start",
+            HookEvent::SessionEnd => "session_end",
+            HookEvent::PreTool => "pre_tool",
+            HookEvent::PostTool => "post_tool",
+            HookEvent::PrePatch => "pre_patch",
+        }
+    }
+
+    /// The field of a hook's JSON output that replaces part of the action.
+    fn replacement_field(self) -> Option<&'static str> {
+        match self {
+            HookEvent::PreTool => Some("arguments"),
+            HookEvent::PostTool => Some("output"),
+            HookEvent::PrePatch => Some("patch"),
+            HookEvent::SessionStart | HookEvent::SessionEnd => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum HookOutcome {
+    /// Go ahead, with the hook's replacement when it printed one.
+    Proceed(Option<String>),
+    /// Do not go ahead, for the given reason.
+    Block(String),
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct HookRunner {
+    hooks: Hooks,
+}
+
+impl HookRunner {
+    pub(crate) fn new(hooks: Hooks) -> Self {
+        Self { hooks }
+    }
+
+    fn hook(&self, event: HookEvent) -> Option<&Hook> {
+        match event {
+            HookEvent::SessionStart => self.hooks.session_start.as_ref(),
+            HookEvent::SessionEnd => self.hooks.session_end.as_ref(),
+            HookEvent::PreTool => self.hooks.pre_tool.as_ref(),
+            HookEvent::PostTool => self.hooks.post_tool.as_ref(),
+            HookEvent::PrePatch => self.hooks.pre_patch.as_ref(),
+        }
+    }
+
+    /// Runs the hook configured for `event`, if any. `tool` is the tool the
+    /// event concerns, matched against the hook's `tools`; the payload gets
+    /// an `event` field naming the event.
+    pub(crate) async fn run(
+        &self,
+        event: HookEvent,
+        tool: Option<&str>,
+        cwd: &Path,
+        mut payload: Value,
+    ) -> HookOutcome {
+        let Some(hook) = self.hook(event) else {
+            return HookOutcome::Proceed(None);
+        };
+        if let Some(tool) = tool
+            && !hook.tools.is_empty()
+            && !hook.tools.iter().any(|name| name == tool)
+        {
+            return HookOutcome::Proceed(None);
+        }
+        if let Value::Object(fields) = &mut payload {
+            fields.insert("event".to_string(), Value::from(event.key()));
+        }
+        match run_command(hook, cwd, &payload).await {
+            Ok(output) => outcome(event, &output),
+            Err(reason) => HookOutcome::Block(reason),
+        }
+    }
+}
+
+async fn run_command(hook: &Hook, cwd: &Path, payload: &Value) -> Result<Output, String> {
+    let Some((program, args)) = hook.command.split_first() else {
+        return Err("the hook has no command".to_string());
+    };
+    let mut child = Command::new(program)
+        .args(args)
+        .current_dir(cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|err| format!("failed to start `{program}`: {err}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let payload = payload.to_string();
+        // A hook that ignores its payload may exit before reading it all.
+        tokio::spawn(async move {
+            let _ = stdin.write_all(payload.as_bytes()).await;
+        });
+    }
+    let timeout = hook
+        .timeout_ms
+        .map_or(DEFAULT_TIMEOUT, Duration::from_millis);
+    tokio::time::timeout(timeout, child.wait_with_output())
+        .await
+        .map_err(|_| format!("`{program}` timed out after {}ms", timeout.as_millis()))?
+        .map_err(|err| format!("`{program}` failed: {err}"))
+}
+
+fn outcome(event: HookEvent, output: &Output) -> HookOutcome {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = [stderr.trim(), stdout.trim()]
+            .into_iter()
+            .find(|text| !text.is_empty())
+            .map_or_else(|| format!("exited with {}", output.status), str::to_string);
+        return HookOutcome::Block(reason);
+    }
+    // Anything other than a JSON object, such as a formatter's progress
+    // output, leaves the action as it is.
+    let replacement = event.replacement_field().and_then(|field| {
+        match serde_json::from_str::<Value>(stdout.trim()) {
+            Ok(Value::Object(mut fields)) => fields.remove(field),
+            _ => None,
+        }
+    });
+    HookOutcome::Proceed(replacement.map(|value| match value {
+        Value::String(text) => text,
+        other => other.to_string(),
+    }))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn runner(event: HookEvent, script: &str, tools: &[&str]) -> HookRunner {
+        let hook = Some(Hook {
+            command: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+            tools: tools.iter().map(ToString::to_string).collect(),
+            timeout_ms: Some(5_000),
+        });
+        let mut hooks = Hooks::default();
+        match event {
+            HookEvent::SessionStart => hooks.session_start = hook,
+            HookEvent::SessionEnd => hooks.session_end = hook,
+            HookEvent::PreTool => hooks.pre_tool = hook,
+            HookEvent::PostTool => hooks.post_tool = hook,
+            HookEvent::PrePatch => hooks.pre_patch = hook,
+        }
+        HookRunner::new(hooks)
+    }
+
+    #[tokio::test]
+    async fn exit_status_blocks_and_json_output_replaces() {
+        let cwd = std::env::temp_dir();
+        let payload = || json!({"tool": "shell", "arguments": "{}"});
+
+        let blocking = runner(HookEvent::PreTool, "echo 'no shell here' >&2; exit 1", &[]);
+        assert_eq!(
+            blocking
+                .run(HookEvent::PreTool, Some("shell"), &cwd, payload())
+                .await,
+            HookOutcome::Block("no shell here".to_string())
+        );
+
+        // The hook sees the payload, including the event, on stdin.
+        let rewriting = runner(
+            HookEvent::PreTool,
+            r#"grep -q '"event":"pre_tool"' && echo '{"arguments": {"command": ["ls"]}}'"#,
+            &[],
+        );
+        assert_eq!(
+            rewriting
+                .run(HookEvent::PreTool, Some("shell"), &cwd, payload())
+                .await,
+            HookOutcome::Proceed(Some(r#"{"command":["ls"]}"#.to_string()))
+        );
+
+        let chatty = runner(HookEvent::PostTool, "echo formatted 3 files", &[]);
+        assert_eq!(
+            chatty
+                .run(HookEvent::PostTool, Some("shell"), &cwd, payload())
+                .await,
+            HookOutcome::Proceed(None)
+        );
+    }
+
+    #[tokio::test]
+    async fn tool_hooks_only_run_for_their_tools() {
+        let cwd = std::env::temp_dir();
+        let hooks = runner(HookEvent::PostTool, "exit 1", &["apply_patch"]);
+        assert_eq!(
+            hooks
+                .run(HookEvent::PostTool, Some("shell"), &cwd, json!({}))
+                .await,
+            HookOutcome::Proceed(None)
+        );
+        assert_eq!(
+            hooks
+                .run(HookEvent::PostTool, Some("apply_patch"), &cwd, json!({}))
+                .await,
+            HookOutcome::Block("exited with exit status: 1".to_string())
+        );
+    }
+}
@@ -5,10 +5,16 @@
 // the TUI or the tracing stack).
 #![deny(clippy::print_stdout, clippy::print_stderr)]
 
+mod anthropic;
 pub mod api_bridge;
 mod apply_patch;
+pub mod audit_log;
 pub mod auth;
+mod auto_commit;
 pub mod bash;
+mod bedrock;
+mod budget;
+pub mod checkpoints;
 mod client;
 mod client_common;
 pub mod codex;
@@ -16,20 +22,27 @@ mod codex_conversation;
 mod compact_remote;
 pub use codex_conversation::CodexConversation;
 mod codex_delegate;
+mod codex_ignore;
 mod command_safety;
 pub mod config;
 pub mod config_loader;
 mod context_manager;
 pub mod custom_prompts;
+mod dry_run;
 mod environment_context;
 pub mod error;
 pub mod exec;
 pub mod exec_env;
 mod exec_policy;
 pub mod features;
+mod file_templates;
 mod flags;
+mod gemini;
 pub mod git_info;
+mod hooks;
 pub mod landlock;
+pub mod limit_history;
+mod limit_wait;
 pub mod mcp;
 mod mcp_connection_manager;
 pub mod openai_models;
@@ -40,7 +53,13 @@ mod mcp_tool_call;
 mod message_history;
 mod model_provider_info;
 pub mod parse_command;
+mod patch_merge;
+pub mod patch_review;
+mod pinned_files;
+mod plugins;
 pub mod powershell;
+pub mod rate_limits;
+mod repo_map;
 mod response_processing;
 pub mod sandboxing;
 mod text_encoding;
@@ -48,18 +67,32 @@ pub mod token_data;
 mod truncate;
 mod unified_exec;
 mod user_instructions;
+pub use model_provider_info::ANTHROPIC_PROVIDER_ID;
+pub use model_provider_info::AZURE_PROVIDER_ID;
+pub use model_provider_info::AzureProviderConfig;
+pub use model_provider_info::BEDROCK_PROVIDER_ID;
 pub use model_provider_info::DEFAULT_LMSTUDIO_PORT;
 pub use model_provider_info::DEFAULT_OLLAMA_PORT;
+pub use model_provider_info::EntraIdConfig;
+pub use model_provider_info::GEMINI_PROVIDER_ID;
+pub use model_provider_info::GatewayConfig;
 pub use model_provider_info::LMSTUDIO_OSS_PROVIDER_ID;
 pub use model_provider_info::ModelProviderInfo;
+pub use model_provider_info::ModelRewrite;
 pub use model_provider_info::OLLAMA_OSS_PROVIDER_ID;
+pub use model_provider_info::ProviderTlsConfig;
+pub use model_provider_info::VERTEX_PROVIDER_ID;
 pub use model_provider_info::WireApi;
 pub use model_provider_info::built_in_model_providers;
 pub use model_provider_info::create_oss_provider_with_base_url;
+mod code_index;
 mod conversation_manager;
 mod event_mapping;
+mod external_edits;
+mod review_cache;
 pub mod review_format;
 pub mod review_prompts;
+pub use budget::recorded_cap_reached;
 pub use codex_protocol::protocol::InitialHistory;
 pub use conversation_manager::ConversationManager;
 pub use conversation_manager::NewConversation;
@@ -69,16 +102,25 @@ pub use auth::CodexAuth;
 pub mod default_client;
 pub mod model_family;
 mod openai_model_info;
+mod output_schema;
+mod pricing;
 pub mod project_doc;
+pub mod redact;
 mod rollout;
+mod run_limits;
 pub(crate) mod safety;
 pub mod seatbelt;
+mod session_guardrail;
+mod shared_state;
 pub mod shell;
 pub mod skills;
 pub mod spawn;
+pub mod state_dir;
+mod sub_agents;
 pub mod terminal;
 mod tools;
 pub mod turn_diff_tracker;
+pub mod usage_history;
 pub use rollout::ARCHIVED_SESSIONS_SUBDIR;
 pub use rollout::INTERACTIVE_SESSION_SOURCES;
 pub use rollout::RolloutRecorder;
@@ -90,6 +132,13 @@ pub use rollout::list::ConversationsPage;
 pub use rollout::list::Cursor;
 pub use rollout::list::parse_cursor;
 pub use rollout::list::read_head_for_summary;
+pub use rollout::resume::ResumedSessionSettings;
+pub use rollout::resume::read_resumed_session_settings;
+pub use rollout::transcript::Transcript;
+pub use rollout::transcript::TranscriptEntry;
+pub use rollout::transcript::read_transcript;
+pub use rollout::undo::UndoneTurn;
+pub use rollout::undo::undo_last_recorded_turn;
 mod function_tool;
 mod state;
 mod tasks;
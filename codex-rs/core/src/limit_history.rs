@@ -0,0 +1,358 @@
+//! Record of usage-limit hits, kept in the `limit_hits` table of the SQLite
+//! store at `~/.codex/state.db`: when the limit was hit, who was signed in,
+//! the model, and when the limit resets.
+//!
+//! SQLite serializes concurrent sessions' writes, so no record is lost or
+//! interleaved. With `shared_state_dir` set, hits are also shared with other
+//! machines as JSON lines (see [`crate::shared_state`]); the database itself
+//! is never put in the shared directory.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io;
+use std::io::Result;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::DateTime;
+use chrono::TimeZone;
+use chrono::Timelike;
+use chrono::Utc;
+use rusqlite::Connection;
+use rusqlite::params;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::shared_state;
+
+/// SQLite store for Codex state inside `~/.codex`.
+const STATE_DB_FILENAME: &str = "state.db";
+
+/// Name the limit history is shared under in `shared_state_dir`.
+const SHARED_HISTORY_FILENAME: &str = "limit_history.jsonl";
+
+/// How long a write waits for another session holding the database lock.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS limit_hits (
+    id INTEGER PRIMARY KEY,
+    ts INTEGER NOT NULL,
+    auth TEXT NOT NULL,
+    model TEXT NOT NULL,
+    window_minutes INTEGER,
+    resets_at INTEGER
+)";
+
+/// Reset hours are only reported as "typical" once they repeat.
+const MIN_HITS_FOR_TYPICAL_RESET: usize = 2;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LimitHitEntry {
+    /// When the limit was hit (Unix seconds).
+    pub ts: i64,
+    /// Who was signed in, e.g. the account email or "API key ending in 1234".
+    pub auth: String,
+    pub model: String,
+    /// Length of the exhausted window, when the server reported it.
+    #[serde(default)]
+    pub window_minutes: Option<i64>,
+    /// When the exhausted window resets (Unix seconds), when known.
+    #[serde(default)]
+    pub resets_at: Option<i64>,
+}
+
+pub fn state_db_path(codex_home: &Path) -> PathBuf {
+    codex_home.join(STATE_DB_FILENAME)
+}
+
+/// Records `entry` in `codex_home` and, when `shared_dir` is set, shares it
+/// with the other machines using that directory.
+pub fn append_limit_hit(
+    codex_home: &Path,
+    shared_dir: Option<&Path>,
+    entry: &LimitHitEntry,
+) -> Result<()> {
+    std::fs::create_dir_all(codex_home)?;
+    let conn = open(&state_db_path(codex_home))?;
+    conn.execute(
+        "INSERT INTO limit_hits (ts, auth, model, window_minutes, resets_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            entry.ts,
+            entry.auth,
+            entry.model,
+            entry.window_minutes,
+            entry.resets_at
+        ],
+    )
+    .map_err(io::Error::other)?;
+    if let Some(shared_dir) = shared_dir {
+        let line = serde_json::to_string(entry).map_err(io::Error::other)?;
+        shared_state::publish_line(codex_home, shared_dir, SHARED_HISTORY_FILENAME, &line);
+    }
+    Ok(())
+}
+
+/// Reads every recorded hit: this machine's oldest first, then those other
+/// machines shared through `shared_dir`, each hit once. A missing database
+/// is an empty history; shared lines that fail to parse are skipped.
+pub fn read_limit_history(
+    codex_home: &Path,
+    shared_dir: Option<&Path>,
+) -> Result<Vec<LimitHitEntry>> {
+    let path = state_db_path(codex_home);
+    let mut entries = if path.exists() {
+        read_local(&open(&path)?).map_err(io::Error::other)?
+    } else {
+        Vec::new()
+    };
+    if let Some(shared_dir) = shared_dir {
+        let mut seen: HashSet<String> = entries
+            .iter()
+            .filter_map(|entry| serde_json::to_string(entry).ok())
+            .collect();
+        for line in shared_state::read_shared_lines(shared_dir, SHARED_HISTORY_FILENAME) {
+            if let Ok(entry) = serde_json::from_str::<LimitHitEntry>(&line)
+                && seen.insert(line)
+            {
+                entries.push(entry);
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Opens the database at `path`, creating it readable only by the user and
+/// adding the table on first use.
+fn open(path: &Path) -> Result<Connection> {
+    create_private(path)?;
+    let conn = Connection::open(path).map_err(io::Error::other)?;
+    conn.busy_timeout(BUSY_TIMEOUT).map_err(io::Error::other)?;
+    conn.execute_batch(SCHEMA).map_err(io::Error::other)?;
+    Ok(conn)
+}
+
+#[cfg(unix)]
+fn create_private(path: &Path) -> Result<()> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    match std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+    {
+        Ok(_) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(not(unix))]
+fn create_private(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+fn read_local(conn: &Connection) -> rusqlite::Result<Vec<LimitHitEntry>> {
+    let mut statement = conn
+        .prepare("SELECT ts, auth, model, window_minutes, resets_at FROM limit_hits ORDER BY id")?;
+    statement
+        .query_map([], |row| {
+            Ok(LimitHitEntry {
+                ts: row.get(0)?,
+                auth: row.get(1)?,
+                model: row.get(2)?,
+                window_minutes: row.get(3)?,
+                resets_at: row.get(4)?,
+            })
+        })?
+        .collect()
+}
+
+/// The hour of day (in `tz`) at which recorded limits most often reset, once
+/// at least two hits agree on it. Ties go to the most recent hit.
+pub fn typical_reset_hour<Tz: TimeZone>(entries: &[LimitHitEntry], tz: &Tz) -> Option<u32> {
+    let mut counts: HashMap<u32, (usize, i64)> = HashMap::new();
+    for entry in entries {
+        let Some(hour) = entry
+            .resets_at
+            .and_then(|at| DateTime::<Utc>::from_timestamp(at, 0))
+            .map(|at| at.with_timezone(tz).hour())
+        else {
+            continue;
+        };
+        let slot = counts.entry(hour).or_default();
+        slot.0 += 1;
+        slot.1 = slot.1.max(entry.ts);
+    }
+    counts
+        .into_iter()
+        .filter(|(_, (count, _))| *count >= MIN_HITS_FOR_TYPICAL_RESET)
+        .max_by_key(|(_, (count, latest))| (*count, *latest))
+        .map(|(hour, _)| hour)
+}
+
+/// When credentials limited at `hit_at` are expected to recover if the
+/// provider gave no reset time: after `delay`, or sooner at the hour `auth`'s
+/// earlier limits typically reset.
+pub fn estimated_reset(
+    auth: &str,
+    hit_at: DateTime<Utc>,
+    delay: chrono::Duration,
+    history: &[LimitHitEntry],
+) -> DateTime<Utc> {
+    let latest = hit_at + delay;
+    let own: Vec<LimitHitEntry> = history
+        .iter()
+        .filter(|entry| entry.auth == auth)
+        .cloned()
+        .collect();
+    let Some(next) = typical_reset_hour(&own, &Utc)
+        .and_then(|hour| hit_at.date_naive().and_hms_opt(hour, 0, 0))
+        .map(|at| at.and_utc())
+        .map(|at| {
+            if at <= hit_at {
+                at + chrono::Duration::days(1)
+            } else {
+                at
+            }
+        })
+    else {
+        return latest;
+    };
+    next.min(latest)
+}
+
+impl LimitHitEntry {
+    /// Until when the hit keeps its credentials limited: the recorded reset
+    /// time, else [`estimated_reset`] from when it was hit.
+    pub fn limited_until(
+        &self,
+        delay: chrono::Duration,
+        history: &[LimitHitEntry],
+    ) -> Option<DateTime<Utc>> {
+        match self.resets_at {
+            Some(resets_at) => DateTime::<Utc>::from_timestamp(resets_at, 0),
+            None => DateTime::<Utc>::from_timestamp(self.ts, 0)
+                .map(|hit_at| estimated_reset(&self.auth, hit_at, delay, history)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn hit(ts: i64, resets_at: Option<i64>) -> LimitHitEntry {
+        LimitHitEntry {
+            ts,
+            auth: "me@example.com".to_string(),
+            model: "gpt-5.1-codex".to_string(),
+            window_minutes: Some(300),
+            resets_at,
+        }
+    }
+
+    #[test]
+    fn appended_hits_read_back_in_order() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let first = hit(1_700_000_000, Some(1_700_018_000));
+        let second = hit(1_700_100_000, None);
+        append_limit_hit(codex_home.path(), None, &first).expect("append");
+        append_limit_hit(codex_home.path(), None, &second).expect("append");
+
+        assert_eq!(
+            read_limit_history(codex_home.path(), None).expect("read"),
+            vec![first, second]
+        );
+    }
+
+    #[test]
+    fn missing_history_is_empty() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        assert_eq!(
+            read_limit_history(codex_home.path(), None).expect("read"),
+            Vec::new()
+        );
+        assert!(!state_db_path(codex_home.path()).exists());
+    }
+
+    #[test]
+    fn hits_shared_by_other_machines_are_read_once() {
+        let laptop = tempfile::tempdir().expect("tempdir");
+        let desktop = tempfile::tempdir().expect("tempdir");
+        let shared_dir = tempfile::tempdir().expect("tempdir");
+        let shared = Some(shared_dir.path());
+        let on_laptop = hit(1_700_000_000, Some(1_700_018_000));
+        let on_desktop = hit(1_700_000_600, None);
+        append_limit_hit(laptop.path(), shared, &on_laptop).expect("append");
+        append_limit_hit(desktop.path(), shared, &on_desktop).expect("append");
+
+        assert_eq!(
+            read_limit_history(laptop.path(), shared).expect("read"),
+            vec![on_laptop.clone(), on_desktop]
+        );
+        assert_eq!(
+            read_limit_history(laptop.path(), None).expect("read"),
+            vec![on_laptop]
+        );
+    }
+
+    #[test]
+    fn typical_reset_hour_needs_a_repeat() {
+        // 2023-11-14T22:13:20Z and the same hour a day later.
+        let reset = 1_700_000_000;
+        let entries = vec![
+            hit(1, Some(reset)),
+            hit(2, Some(reset + 3 * 3600)),
+            hit(3, Some(reset + 86_400)),
+        ];
+        assert_eq!(typical_reset_hour(&entries, &Utc), Some(22));
+        assert_eq!(typical_reset_hour(&entries[..2], &Utc), None);
+    }
+
+    #[test]
+    fn reset_without_a_reported_time_falls_back_to_the_retry_delay() {
+        // 2023-11-14T22:13:20Z.
+        let hit_at = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).expect("timestamp");
+        let delay = chrono::Duration::hours(5);
+        assert_eq!(
+            estimated_reset("me@example.com", hit_at, delay, &[]),
+            hit_at + delay
+        );
+
+        // Two earlier resets at 01:00 UTC make 01:00 the next day the
+        // estimate, as it comes before the delay runs out.
+        let one_am = 1_699_923_600;
+        let history = vec![hit(1, Some(one_am)), hit(2, Some(one_am - 86_400))];
+        let next_one_am = DateTime::<Utc>::from_timestamp(one_am + 86_400, 0).expect("timestamp");
+        assert_eq!(
+            estimated_reset("me@example.com", hit_at, delay, &history),
+            next_one_am
+        );
+        assert_eq!(
+            estimated_reset("other@example.com", hit_at, delay, &history),
+            hit_at + delay
+        );
+        assert_eq!(
+            estimated_reset(
+                "me@example.com",
+                hit_at,
+                chrono::Duration::hours(1),
+                &history
+            ),
+            hit_at + chrono::Duration::hours(1)
+        );
+
+        assert_eq!(
+            hit(hit_at.timestamp(), None).limited_until(delay, &[]),
+            Some(hit_at + delay)
+        );
+        assert_eq!(
+            hit(hit_at.timestamp(), Some(one_am)).limited_until(delay, &[]),
+            DateTime::<Utc>::from_timestamp(one_am, 0)
+        );
+    }
+}
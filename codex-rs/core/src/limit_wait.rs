@@ -0,0 +1,79 @@
+//! Waiting out a usage limit under `limits.on_cap = "wait"`: instead of
+//! failing the turn, the session sleeps until the limit resets, announcing
+//! the time left every [`UPDATE_INTERVAL`], and then retries the request.
+
+use std::time::Duration;
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use crate::error::format_retry_timestamp;
+use crate::rate_limits::format_countdown;
+
+/// How often the remaining wait is announced.
+pub(crate) const UPDATE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Extra time after the reported reset before retrying, so a slightly
+/// earlier local clock does not hit the limit again.
+const RESUME_DELAY: Duration = Duration::from_secs(5);
+
+/// How long to sleep before the next announcement, or `None` once the wait
+/// is over.
+pub(crate) fn next_sleep(recovers_at: DateTime<Utc>, now: DateTime<Utc>) -> Option<Duration> {
+    let remaining = (recovers_at - now).to_std().ok()? + RESUME_DELAY;
+    Some(remaining.min(UPDATE_INTERVAL))
+}
+
+/// E.g. "Usage limit reached for me@example.com. Waiting until 3:15 PM
+/// (1h 5m left) to resume."
+pub(crate) fn countdown_message(
+    auth: &str,
+    recovers_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> String {
+    let subject = if auth.is_empty() {
+        String::new()
+    } else {
+        format!(" for {auth}")
+    };
+    let left = format_countdown((recovers_at - now).num_seconds());
+    format!(
+        "Usage limit reached{subject}. Waiting until {} ({left} left) to resume.",
+        format_retry_timestamp(&recovers_at)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn waits_are_split_into_updates_until_the_reset() {
+        let now = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).expect("timestamp");
+        let recovers_at = now + chrono::Duration::minutes(65);
+
+        assert_eq!(next_sleep(recovers_at, now), Some(UPDATE_INTERVAL));
+        assert_eq!(
+            next_sleep(recovers_at, recovers_at - chrono::Duration::minutes(3)),
+            Some(Duration::from_secs(3 * 60) + RESUME_DELAY)
+        );
+        assert_eq!(next_sleep(recovers_at, recovers_at), Some(RESUME_DELAY));
+        assert_eq!(
+            next_sleep(recovers_at, recovers_at + chrono::Duration::seconds(1)),
+            None
+        );
+    }
+
+    #[test]
+    fn countdown_message_uses_the_shared_countdown_format() {
+        let now = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).expect("timestamp");
+
+        let message = countdown_message("me@example.com", now + chrono::Duration::minutes(65), now);
+        assert!(message.starts_with("Usage limit reached for me@example.com. Waiting until "));
+        assert!(message.ends_with(" (1h 5m left) to resume."));
+
+        let message = countdown_message("", now + chrono::Duration::seconds(30), now);
+        assert!(message.ends_with(" (<1m left) to resume."));
+    }
+}
@@ -68,5 +68,14 @@ async fn compute_auth_status(
             )
             .await
         }
+        // OAuth discovery is specific to streamable HTTP.
+        McpServerTransportConfig::Sse {
+            bearer_token_env_var,
+            ..
+        } => Ok(if bearer_token_env_var.is_some() {
+            McpAuthStatus::BearerToken
+        } else {
+            McpAuthStatus::Unsupported
+        }),
     }
 }
@@ -833,6 +833,18 @@ async fn make_rmcp_client(
             .await
             .map_err(StartupOutcomeError::from)
         }
+        McpServerTransportConfig::Sse {
+            url,
+            http_headers,
+            env_http_headers,
+            bearer_token_env_var,
+        } => {
+            let resolved_bearer_token =
+                resolve_bearer_token(server_name, bearer_token_env_var.as_deref())?;
+            RmcpClient::new_sse_client(&url, resolved_bearer_token, http_headers, env_http_headers)
+                .await
+                .map_err(StartupOutcomeError::from)
+        }
     }
 }
 
@@ -59,7 +59,7 @@ pub struct HistoryEntry {
 }
 
 fn history_filepath(config: &Config) -> PathBuf {
-    let mut path = config.codex_home.clone();
+    let mut path = crate::state_dir::state_dir(&config.codex_home);
     path.push(HISTORY_FILENAME);
     path
 }
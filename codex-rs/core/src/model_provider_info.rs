@@ -16,12 +16,17 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::env::VarError;
+use std::path::PathBuf;
 use std::time::Duration;
 
+use crate::config::types::Retry;
+use crate::config::types::RetryableError;
 use crate::error::EnvVarError;
 const DEFAULT_STREAM_IDLE_TIMEOUT_MS: u64 = 300_000;
 const DEFAULT_STREAM_MAX_RETRIES: u64 = 5;
 const DEFAULT_REQUEST_MAX_RETRIES: u64 = 4;
+const DEFAULT_RETRY_ON: &[RetryableError] =
+    &[RetryableError::ServerError, RetryableError::Transport];
 /// Hard cap for user-configured `stream_max_retries`.
 const MAX_STREAM_MAX_RETRIES: u64 = 100;
 /// Hard cap for user-configured `request_max_retries`.
@@ -42,6 +47,16 @@ pub enum WireApi {
     /// Regular Chat Completions compatible with `/v1/chat/completions`.
     #[default]
     Chat,
+
+    /// The Amazon Bedrock Converse API at `/model/{model}/converse`.
+    Bedrock,
+
+    /// Google's `models/{model}:streamGenerateContent`, on the Gemini API or
+    /// Vertex AI.
+    Gemini,
+
+    /// The Anthropic Messages API at `/v1/messages`.
+    Anthropic,
 }
 
 /// Serializable representation of a provider definition.
@@ -90,6 +105,28 @@ pub struct ModelProviderInfo {
     /// the connection as lost.
     pub stream_idle_timeout_ms: Option<u64>,
 
+    /// Largest request body, in bytes, this provider (or a gateway in front
+    /// of it) accepts. Larger requests trigger a compaction of the
+    /// conversation instead of being sent.
+    pub max_request_body_bytes: Option<u64>,
+
+    /// Whether the provider's models accept image inputs. Defaults to true;
+    /// when false, attached images are described by their path instead.
+    pub supports_images: Option<bool>,
+
+    /// Azure OpenAI settings. When present, the base URL and `api-version`
+    /// are derived from the resource endpoint, and requests authenticate with
+    /// `AZURE_OPENAI_API_KEY` or, failing that, an Entra ID token.
+    pub azure: Option<AzureProviderConfig>,
+
+    /// Settings for an organization-hosted OpenAI-compatible gateway: how the
+    /// credential is sent and how model names are rewritten.
+    pub gateway: Option<GatewayConfig>,
+
+    /// TLS settings for a provider behind a proxy with a private CA or one
+    /// that requires a client certificate.
+    pub tls: Option<ProviderTlsConfig>,
+
     /// Does this provider require an OpenAI API Key or ChatGPT login token? If true,
     /// user is presented with login screen on first run, and login preference and token/key
     /// are stored in auth.json. If false (which is the default), login screen is skipped,
@@ -98,9 +135,234 @@ pub struct ModelProviderInfo {
     pub requires_openai_auth: bool,
 }
 
+/// Azure OpenAI resource settings. Every field falls back to the matching
+/// `AZURE_OPENAI_*` environment variable.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct AzureProviderConfig {
+    /// Resource endpoint, e.g. `https://my-resource.openai.azure.com`.
+    /// Defaults to `AZURE_OPENAI_ENDPOINT`.
+    pub endpoint: Option<String>,
+
+    /// Deployment to send Chat Completions requests to
+    /// (`/openai/deployments/<deployment>`). Defaults to
+    /// `AZURE_OPENAI_DEPLOYMENT`. The Responses API selects the deployment
+    /// through `model` instead.
+    pub deployment: Option<String>,
+
+    /// Value of the `api-version` query parameter. Defaults to
+    /// `AZURE_OPENAI_API_VERSION`, then [`AZURE_DEFAULT_API_VERSION`].
+    pub api_version: Option<String>,
+
+    /// Entra ID credentials used when no API key is available.
+    pub entra_id: Option<EntraIdConfig>,
+}
+
+/// Entra ID (Azure AD) token settings. With a tenant, client id, and client
+/// secret Codex uses the client credentials flow; otherwise it asks the
+/// managed identity endpoint of the host it runs on.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct EntraIdConfig {
+    /// Directory (tenant) id. Defaults to `AZURE_TENANT_ID`.
+    pub tenant_id: Option<String>,
+
+    /// Application (client) id, or the client id of a user-assigned managed
+    /// identity. Defaults to `AZURE_CLIENT_ID`.
+    pub client_id: Option<String>,
+
+    /// Environment variable holding the client secret. Defaults to
+    /// `AZURE_CLIENT_SECRET`.
+    pub client_secret_env_key: Option<String>,
+
+    /// Token scope. Defaults to [`AZURE_COGNITIVE_SERVICES_SCOPE`].
+    pub scope: Option<String>,
+}
+
+/// Settings for an internal proxy that speaks the OpenAI wire format but not
+/// byte for byte.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct GatewayConfig {
+    /// Header that carries the credential. Defaults to `Authorization`.
+    pub auth_header: Option<String>,
+
+    /// Value of `auth_header`; `{token}` is replaced with the provider's API
+    /// key or token. Defaults to `Bearer {token}`.
+    pub auth_value: Option<String>,
+
+    /// Rewrites applied to the model name before each request. The first
+    /// matching rule wins.
+    #[serde(default)]
+    pub model_rewrites: Vec<ModelRewrite>,
+}
+
+/// TLS settings for requests to a provider. Paths are read when the client
+/// is built, so a missing or malformed file fails the request that needs it.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct ProviderTlsConfig {
+    /// PEM root certificate to trust, e.g. the CA that signed a reverse
+    /// proxy's certificate. The file may hold several certificates.
+    #[serde(alias = "ca_bundle")]
+    pub ca_certificate: Option<PathBuf>,
+
+    /// PEM client certificate for mutual TLS. Requires `client_private_key`.
+    #[serde(alias = "client_cert")]
+    pub client_certificate: Option<PathBuf>,
+
+    /// PEM private key for `client_certificate`.
+    #[serde(alias = "client_key")]
+    pub client_private_key: Option<PathBuf>,
+}
+
+impl ProviderTlsConfig {
+    /// Adds the trusted certificates and the client identity to `builder`.
+    pub fn apply(
+        &self,
+        mut builder: reqwest::ClientBuilder,
+    ) -> crate::error::Result<reqwest::ClientBuilder> {
+        if let Some(path) = &self.ca_certificate {
+            let pem = read_tls_file(path)?;
+            let certificates =
+                reqwest::Certificate::from_pem_bundle(&pem).map_err(|err| tls_error(path, err))?;
+            if certificates.is_empty() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("invalid TLS file {}: no certificates found", path.display()),
+                )
+                .into());
+            }
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        match (&self.client_certificate, &self.client_private_key) {
+            (Some(cert_path), Some(key_path)) => {
+                let mut pem = read_tls_file(cert_path)?;
+                pem.push(b'\n');
+                pem.extend(read_tls_file(key_path)?);
+                let identity =
+                    reqwest::Identity::from_pem(&pem).map_err(|err| tls_error(cert_path, err))?;
+                builder = builder.identity(identity);
+            }
+            (None, None) => {}
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "client_certificate and client_private_key must both be provided for mTLS",
+                )
+                .into());
+            }
+        }
+
+        Ok(builder)
+    }
+}
+
+/// Maps a Codex model name to the name the gateway expects.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ModelRewrite {
+    /// Model name to match. A trailing `*` matches any name with that prefix.
+    pub from: String,
+
+    /// Replacement name; `{model}` is replaced with the original name.
+    pub to: String,
+}
+
+impl ModelRewrite {
+    fn apply(&self, model: &str) -> Option<String> {
+        let matches = match self.from.strip_suffix('*') {
+            Some(prefix) => model.starts_with(prefix),
+            None => model == self.from,
+        };
+        matches.then(|| self.to.replace("{model}", model))
+    }
+}
+
+fn read_tls_file(path: &std::path::Path) -> std::io::Result<Vec<u8>> {
+    std::fs::read(path).map_err(|err| {
+        std::io::Error::new(
+            err.kind(),
+            format!("failed to read TLS file {}: {err}", path.display()),
+        )
+    })
+}
+
+fn tls_error(path: &std::path::Path, err: reqwest::Error) -> crate::error::CodexErr {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("invalid TLS file {}: {err}", path.display()),
+    )
+    .into()
+}
+
+pub const AZURE_PROVIDER_ID: &str = "azure";
+pub const BEDROCK_PROVIDER_ID: &str = "bedrock";
+pub const GEMINI_PROVIDER_ID: &str = "gemini";
+pub const VERTEX_PROVIDER_ID: &str = "vertex";
+pub const GEMINI_API_KEY_ENV_VAR: &str = "GEMINI_API_KEY";
+pub const ANTHROPIC_PROVIDER_ID: &str = "anthropic";
+pub const ANTHROPIC_API_KEY_ENV_VAR: &str = "ANTHROPIC_API_KEY";
+pub const AZURE_DEFAULT_API_VERSION: &str = "2025-04-01-preview";
+pub const AZURE_COGNITIVE_SERVICES_SCOPE: &str = "https://cognitiveservices.azure.com/.default";
+pub const AZURE_OPENAI_API_KEY_ENV_VAR: &str = "AZURE_OPENAI_API_KEY";
+const AZURE_OPENAI_ENDPOINT_ENV_VAR: &str = "AZURE_OPENAI_ENDPOINT";
+const AZURE_OPENAI_DEPLOYMENT_ENV_VAR: &str = "AZURE_OPENAI_DEPLOYMENT";
+const AZURE_OPENAI_API_VERSION_ENV_VAR: &str = "AZURE_OPENAI_API_VERSION";
+
+/// Non-empty value of an environment variable.
+pub(crate) fn non_empty_env(var: &str) -> Option<String> {
+    std::env::var(var).ok().filter(|v| !v.trim().is_empty())
+}
+
+impl AzureProviderConfig {
+    /// `<endpoint>/openai` for the Responses API, or
+    /// `<endpoint>/openai/deployments/<deployment>` for Chat Completions.
+    fn base_url(&self, wire_api: WireApi) -> crate::error::Result<String> {
+        let endpoint = self
+            .endpoint
+            .clone()
+            .or_else(|| non_empty_env(AZURE_OPENAI_ENDPOINT_ENV_VAR))
+            .ok_or_else(|| {
+                crate::error::CodexErr::EnvVar(EnvVarError {
+                    var: AZURE_OPENAI_ENDPOINT_ENV_VAR.to_string(),
+                    instructions: Some(
+                        "Set it to your Azure OpenAI resource endpoint, e.g. https://my-resource.openai.azure.com, or set `azure.endpoint` for this provider.".to_string(),
+                    ),
+                })
+            })?;
+        let endpoint = endpoint.trim_end_matches('/');
+        let endpoint = endpoint.strip_suffix("/openai").unwrap_or(endpoint);
+        match wire_api {
+            WireApi::Responses | WireApi::Bedrock | WireApi::Gemini | WireApi::Anthropic => {
+                Ok(format!("{endpoint}/openai"))
+            }
+            WireApi::Chat => {
+                let deployment = self
+                    .deployment
+                    .clone()
+                    .or_else(|| non_empty_env(AZURE_OPENAI_DEPLOYMENT_ENV_VAR))
+                    .ok_or_else(|| {
+                        crate::error::CodexErr::EnvVar(EnvVarError {
+                            var: AZURE_OPENAI_DEPLOYMENT_ENV_VAR.to_string(),
+                            instructions: Some(
+                                "Chat Completions on Azure needs a deployment name; set it here or as `azure.deployment` for this provider.".to_string(),
+                            ),
+                        })
+                    })?;
+                Ok(format!("{endpoint}/openai/deployments/{deployment}"))
+            }
+        }
+    }
+
+    fn api_version(&self) -> String {
+        self.api_version
+            .clone()
+            .or_else(|| non_empty_env(AZURE_OPENAI_API_VERSION_ENV_VAR))
+            .unwrap_or_else(|| AZURE_DEFAULT_API_VERSION.to_string())
+    }
+}
+
 impl ModelProviderInfo {
-    #[allow(dead_code)]
-    fn build_header_map(&self) -> crate::error::Result<HeaderMap> {
+    pub(crate) fn build_header_map(&self) -> crate::error::Result<HeaderMap> {
         let mut headers = HeaderMap::new();
         if let Some(extra) = &self.http_headers {
             for (k, v) in extra {
@@ -125,6 +387,39 @@ impl ModelProviderInfo {
         Ok(headers)
     }
 
+    /// [`Self::build_header_map`] plus `Authorization: Bearer` with the
+    /// provider's API key or `experimental_bearer_token`, for callers that
+    /// talk to the provider outside the model API client (model listing and
+    /// pulls on Ollama and LM Studio).
+    pub fn request_headers(&self) -> crate::error::Result<HeaderMap> {
+        let mut headers = self.build_header_map()?;
+        if let Some(token) = self
+            .api_key()?
+            .or_else(|| self.experimental_bearer_token.clone())
+            && let Ok(value) = HeaderValue::try_from(format!("Bearer {token}"))
+        {
+            headers.insert(http::header::AUTHORIZATION, value);
+        }
+        Ok(headers)
+    }
+
+    /// Client builder with the Codex defaults and this provider's `tls`
+    /// settings applied.
+    pub fn reqwest_client_builder(&self) -> crate::error::Result<reqwest::ClientBuilder> {
+        let builder = crate::default_client::provider_reqwest_client_builder();
+        match &self.tls {
+            Some(tls) => tls.apply(builder),
+            None => Ok(builder),
+        }
+    }
+
+    /// HTTP client for requests to this provider.
+    pub fn build_reqwest_client(&self) -> crate::error::Result<reqwest::Client> {
+        self.reqwest_client_builder()?
+            .build()
+            .map_err(|err| std::io::Error::other(err).into())
+    }
+
     pub(crate) fn to_api_provider(
         &self,
         auth_mode: Option<AuthMode>,
@@ -134,34 +429,57 @@ impl ModelProviderInfo {
         } else {
             "https://api.openai.com/v1"
         };
-        let base_url = self
-            .base_url
-            .clone()
-            .unwrap_or_else(|| default_base_url.to_string());
+        let mut query_params = self.query_params.clone();
+        let base_url = match (&self.base_url, &self.azure) {
+            (Some(base_url), _) => base_url.clone(),
+            (None, Some(azure)) => azure.base_url(self.wire_api)?,
+            (None, None) => default_base_url.to_string(),
+        };
+        if let Some(azure) = &self.azure {
+            query_params
+                .get_or_insert_with(HashMap::new)
+                .entry("api-version".to_string())
+                .or_insert_with(|| azure.api_version());
+        }
 
         let headers = self.build_header_map()?;
-        let retry = ApiRetryConfig {
-            max_attempts: self.request_max_retries(),
-            base_delay: Duration::from_millis(200),
-            retry_429: false,
-            retry_5xx: true,
-            retry_transport: true,
+        let retry = self.retry_config(&Retry::default());
+
+        let wire = match self.wire_api {
+            WireApi::Responses => ApiWireApi::Responses,
+            WireApi::Chat => ApiWireApi::Chat,
+            WireApi::Bedrock | WireApi::Gemini | WireApi::Anthropic => {
+                return Err(crate::error::CodexErr::UnsupportedOperation(format!(
+                    "provider `{}` does not expose an OpenAI-compatible endpoint",
+                    self.name
+                )));
+            }
         };
 
         Ok(ApiProvider {
             name: self.name.clone(),
             base_url,
-            query_params: self.query_params.clone(),
-            wire: match self.wire_api {
-                WireApi::Responses => ApiWireApi::Responses,
-                WireApi::Chat => ApiWireApi::Chat,
-            },
+            query_params,
+            wire,
             headers,
             retry,
             stream_idle_timeout: self.stream_idle_timeout(),
         })
     }
 
+    /// Model name to send on the wire, after any gateway `model_rewrites`.
+    pub(crate) fn request_model_name(&self, model: &str) -> String {
+        self.gateway
+            .as_ref()
+            .and_then(|gateway| {
+                gateway
+                    .model_rewrites
+                    .iter()
+                    .find_map(|rewrite| rewrite.apply(model))
+            })
+            .unwrap_or_else(|| model.to_string())
+    }
+
     /// If `env_key` is Some, returns the API key for this provider if present
     /// (and non-empty) in the environment. If `env_key` is required but
     /// cannot be found, returns an error.
@@ -202,12 +520,46 @@ impl ModelProviderInfo {
             .min(MAX_STREAM_MAX_RETRIES)
     }
 
+    /// Request retry policy under the `[retry]` settings, with this
+    /// provider's own `request_max_retries` taking precedence.
+    pub(crate) fn retry_config(&self, retry: &Retry) -> ApiRetryConfig {
+        let retry_on = retry.retry_on.as_deref().unwrap_or(DEFAULT_RETRY_ON);
+        ApiRetryConfig {
+            max_attempts: self
+                .request_max_retries
+                .or(retry.request_max_retries)
+                .unwrap_or(DEFAULT_REQUEST_MAX_RETRIES)
+                .min(MAX_REQUEST_MAX_RETRIES),
+            base_delay: retry.base_delay(),
+            retry_429: retry_on.contains(&RetryableError::TooManyRequests),
+            retry_5xx: retry_on.contains(&RetryableError::ServerError),
+            retry_transport: retry_on.contains(&RetryableError::Transport),
+        }
+    }
+
+    /// Number of times a turn whose response stream drops is sent again
+    /// under the `[retry]` settings; 0 with `resume_stream = false`.
+    pub fn stream_retries(&self, retry: &Retry) -> u64 {
+        if retry.resume_stream == Some(false) {
+            return 0;
+        }
+        self.stream_max_retries
+            .or(retry.stream_max_retries)
+            .unwrap_or(DEFAULT_STREAM_MAX_RETRIES)
+            .min(MAX_STREAM_MAX_RETRIES)
+    }
+
     /// Effective idle timeout for streaming responses.
     pub fn stream_idle_timeout(&self) -> Duration {
         self.stream_idle_timeout_ms
             .map(Duration::from_millis)
             .unwrap_or(Duration::from_millis(DEFAULT_STREAM_IDLE_TIMEOUT_MS))
     }
+
+    /// Whether images can be sent to this provider as image content.
+    pub fn supports_images(&self) -> bool {
+        self.supports_images.unwrap_or(true)
+    }
 }
 
 pub const DEFAULT_LMSTUDIO_PORT: u16 = 1234;
@@ -221,9 +573,10 @@ pub fn built_in_model_providers() -> HashMap<String, ModelProviderInfo> {
     use ModelProviderInfo as P;
 
     // We do not want to be in the business of adjucating which third-party
-    // providers are bundled with Codex CLI, so we only include the OpenAI and
-    // open source ("oss") providers by default. Users are encouraged to add to
-    // `model_providers` in config.toml to add their own providers.
+    // providers are bundled with Codex CLI, so we only include the OpenAI
+    // (including Azure OpenAI) and open source ("oss") providers by default.
+    // Users are encouraged to add to `model_providers` in config.toml to add
+    // their own providers.
     [
         (
             "openai",
@@ -262,9 +615,19 @@ pub fn built_in_model_providers() -> HashMap<String, ModelProviderInfo> {
                 request_max_retries: None,
                 stream_max_retries: None,
                 stream_idle_timeout_ms: None,
+                max_request_body_bytes: None,
+                supports_images: None,
+                azure: None,
+                gateway: None,
+                tls: None,
                 requires_openai_auth: true,
             },
         ),
+        (AZURE_PROVIDER_ID, create_azure_provider()),
+        (BEDROCK_PROVIDER_ID, create_bedrock_provider()),
+        (GEMINI_PROVIDER_ID, create_gemini_provider()),
+        (VERTEX_PROVIDER_ID, create_vertex_provider()),
+        (ANTHROPIC_PROVIDER_ID, create_anthropic_provider()),
         (
             OLLAMA_OSS_PROVIDER_ID,
             create_oss_provider(DEFAULT_OLLAMA_PORT, WireApi::Chat),
@@ -279,6 +642,122 @@ pub fn built_in_model_providers() -> HashMap<String, ModelProviderInfo> {
     .collect()
 }
 
+/// Amazon Bedrock via the Converse API. The region and credentials come from
+/// the standard AWS environment variables and `~/.aws` files.
+pub fn create_bedrock_provider() -> ModelProviderInfo {
+    ModelProviderInfo {
+        name: "Amazon Bedrock".into(),
+        base_url: None,
+        env_key: None,
+        env_key_instructions: None,
+        experimental_bearer_token: None,
+        wire_api: WireApi::Bedrock,
+        query_params: None,
+        http_headers: None,
+        env_http_headers: None,
+        request_max_retries: None,
+        stream_max_retries: None,
+        stream_idle_timeout_ms: None,
+        max_request_body_bytes: None,
+        supports_images: None,
+        azure: None,
+        gateway: None,
+        tls: None,
+        requires_openai_auth: false,
+    }
+}
+
+/// The Gemini API, authenticated with `GEMINI_API_KEY`.
+pub fn create_gemini_provider() -> ModelProviderInfo {
+    ModelProviderInfo {
+        name: "Gemini".into(),
+        base_url: None,
+        env_key: Some(GEMINI_API_KEY_ENV_VAR.into()),
+        env_key_instructions: Some(
+            "Create an API key at https://aistudio.google.com/apikey and export it as GEMINI_API_KEY."
+                .into(),
+        ),
+        experimental_bearer_token: None,
+        wire_api: WireApi::Gemini,
+        query_params: None,
+        http_headers: None,
+        env_http_headers: None,
+        request_max_retries: None,
+        stream_max_retries: None,
+        stream_idle_timeout_ms: None,
+        max_request_body_bytes: None,
+        supports_images: None,
+        azure: None,
+        gateway: None,
+        tls: None,
+        requires_openai_auth: false,
+    }
+}
+
+/// Gemini on Vertex AI, authenticated with Application Default Credentials.
+/// The project and location come from `GOOGLE_CLOUD_PROJECT` and
+/// `GOOGLE_CLOUD_LOCATION`.
+pub fn create_vertex_provider() -> ModelProviderInfo {
+    ModelProviderInfo {
+        name: "Vertex AI".into(),
+        env_key: None,
+        env_key_instructions: None,
+        ..create_gemini_provider()
+    }
+}
+
+/// The Anthropic Messages API, authenticated with `ANTHROPIC_API_KEY`.
+pub fn create_anthropic_provider() -> ModelProviderInfo {
+    ModelProviderInfo {
+        name: "Anthropic".into(),
+        base_url: None,
+        env_key: Some(ANTHROPIC_API_KEY_ENV_VAR.into()),
+        env_key_instructions: Some(
+            "Create an API key at https://console.anthropic.com/settings/keys and export it as ANTHROPIC_API_KEY."
+                .into(),
+        ),
+        experimental_bearer_token: None,
+        wire_api: WireApi::Anthropic,
+        query_params: None,
+        http_headers: None,
+        env_http_headers: None,
+        request_max_retries: None,
+        stream_max_retries: None,
+        stream_idle_timeout_ms: None,
+        max_request_body_bytes: None,
+        supports_images: None,
+        azure: None,
+        gateway: None,
+        tls: None,
+        requires_openai_auth: false,
+    }
+}
+
+/// Azure OpenAI over the Responses API, configured through the
+/// `AZURE_OPENAI_*` and `AZURE_*` identity environment variables.
+pub fn create_azure_provider() -> ModelProviderInfo {
+    ModelProviderInfo {
+        name: "Azure".into(),
+        base_url: None,
+        env_key: None,
+        env_key_instructions: None,
+        experimental_bearer_token: None,
+        wire_api: WireApi::Responses,
+        query_params: None,
+        http_headers: None,
+        env_http_headers: None,
+        request_max_retries: None,
+        stream_max_retries: None,
+        stream_idle_timeout_ms: None,
+        max_request_body_bytes: None,
+        supports_images: None,
+        azure: Some(AzureProviderConfig::default()),
+        gateway: None,
+        tls: None,
+        requires_openai_auth: false,
+    }
+}
+
 pub fn create_oss_provider(default_provider_port: u16, wire_api: WireApi) -> ModelProviderInfo {
     // These CODEX_OSS_ environment variables are experimental: we may
     // switch to reading values from config.toml instead.
@@ -313,6 +792,11 @@ pub fn create_oss_provider_with_base_url(base_url: &str, wire_api: WireApi) -> M
         request_max_retries: None,
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
+        max_request_body_bytes: None,
+        supports_images: None,
+        azure: None,
+        gateway: None,
+        tls: None,
         requires_openai_auth: false,
     }
 }
@@ -341,6 +825,11 @@ base_url = "http://localhost:11434/v1"
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            max_request_body_bytes: None,
+            supports_images: None,
+            azure: None,
+            gateway: None,
+            tls: None,
             requires_openai_auth: false,
         };
 
@@ -371,6 +860,11 @@ query_params = { api-version = "2025-04-01-preview" }
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            max_request_body_bytes: None,
+            supports_images: None,
+            azure: None,
+            gateway: None,
+            tls: None,
             requires_openai_auth: false,
         };
 
@@ -404,6 +898,11 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            max_request_body_bytes: None,
+            supports_images: None,
+            azure: None,
+            gateway: None,
+            tls: None,
             requires_openai_auth: false,
         };
 
@@ -411,6 +910,210 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
         assert_eq!(expected_provider, provider);
     }
 
+    #[test]
+    fn gateway_rewrites_model_names_in_order() {
+        let provider: ModelProviderInfo = toml::from_str(
+            r#"
+name = "Corp gateway"
+base_url = "https://llm.corp.example/v1"
+
+[gateway]
+auth_header = "X-Api-Key"
+auth_value = "{token}"
+
+[[gateway.model_rewrites]]
+from = "gpt-5.1-codex"
+to = "codex-prod"
+
+[[gateway.model_rewrites]]
+from = "gpt-*"
+to = "openai/{model}"
+"#,
+        )
+        .unwrap();
+        assert_eq!(provider.request_model_name("gpt-5.1-codex"), "codex-prod");
+        assert_eq!(provider.request_model_name("gpt-5.1"), "openai/gpt-5.1");
+        assert_eq!(provider.request_model_name("o3"), "o3");
+        assert_eq!(
+            provider
+                .gateway
+                .as_ref()
+                .and_then(|gateway| gateway.auth_header.as_deref()),
+            Some("X-Api-Key")
+        );
+    }
+
+    #[test]
+    fn tls_accepts_short_names_and_requires_a_key_with_the_certificate() {
+        let provider: ModelProviderInfo = toml::from_str(
+            r#"
+name = "Corp gateway"
+base_url = "https://llm.corp.example/v1"
+
+[tls]
+ca_bundle = "/etc/ssl/corp-ca.pem"
+client_cert = "/etc/ssl/codex.crt"
+client_key = "/etc/ssl/codex.key"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            provider.tls,
+            Some(ProviderTlsConfig {
+                ca_certificate: Some("/etc/ssl/corp-ca.pem".into()),
+                client_certificate: Some("/etc/ssl/codex.crt".into()),
+                client_private_key: Some("/etc/ssl/codex.key".into()),
+            })
+        );
+
+        let cert_only = ProviderTlsConfig {
+            client_certificate: Some("/etc/ssl/codex.crt".into()),
+            ..Default::default()
+        };
+        let err = cert_only
+            .apply(reqwest::Client::builder())
+            .expect_err("client certificate without key");
+        assert!(err.to_string().contains("must both be provided"));
+
+        let missing_ca = ProviderTlsConfig {
+            ca_certificate: Some("/nonexistent/ca.pem".into()),
+            ..Default::default()
+        };
+        let err = missing_ca
+            .apply(reqwest::Client::builder())
+            .expect_err("missing CA bundle");
+        assert!(
+            err.to_string()
+                .contains("failed to read TLS file /nonexistent/ca.pem")
+        );
+    }
+
+    #[test]
+    fn azure_config_derives_deployment_url_and_api_version() {
+        let provider: ModelProviderInfo = toml::from_str(
+            r#"
+name = "Azure"
+wire_api = "chat"
+azure = { endpoint = "https://res.openai.azure.com/", deployment = "gpt-5", api_version = "2024-10-21", entra_id = { tenant_id = "tenant" } }
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            provider
+                .azure
+                .as_ref()
+                .and_then(|azure| azure.entra_id.clone()),
+            Some(EntraIdConfig {
+                tenant_id: Some("tenant".into()),
+                ..Default::default()
+            })
+        );
+
+        let api = provider.to_api_provider(None).unwrap();
+        assert_eq!(
+            api.base_url,
+            "https://res.openai.azure.com/openai/deployments/gpt-5"
+        );
+        assert_eq!(
+            api.query_params,
+            Some(maplit::hashmap! {
+                "api-version".to_string() => "2024-10-21".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn azure_responses_url_keeps_explicit_api_version() {
+        let provider = ModelProviderInfo {
+            query_params: Some(maplit::hashmap! {
+                "api-version".to_string() => "preview".to_string(),
+            }),
+            azure: Some(AzureProviderConfig {
+                endpoint: Some("https://res.openai.azure.com/openai".into()),
+                ..Default::default()
+            }),
+            ..create_azure_provider()
+        };
+        let api = provider.to_api_provider(None).unwrap();
+        assert_eq!(api.base_url, "https://res.openai.azure.com/openai");
+        assert_eq!(
+            api.query_params,
+            Some(maplit::hashmap! {
+                "api-version".to_string() => "preview".to_string(),
+            })
+        );
+        assert!(api.is_azure_responses_endpoint());
+    }
+
+    #[test]
+    fn retry_settings_apply_unless_the_provider_sets_its_own() {
+        let retry: Retry = toml::from_str(
+            r#"
+request_max_retries = 7
+stream_max_retries = 9
+base_delay_ms = 1000
+retry_on = ["429", "transport"]
+"#,
+        )
+        .unwrap();
+        let provider: ModelProviderInfo = toml::from_str(r#"name = "Proxy""#).unwrap();
+        let config = provider.retry_config(&retry);
+        assert_eq!(
+            (
+                config.max_attempts,
+                config.base_delay,
+                config.retry_429,
+                config.retry_5xx,
+                config.retry_transport
+            ),
+            (7, Duration::from_secs(1), true, false, true)
+        );
+        assert_eq!(provider.stream_retries(&retry), 9);
+
+        let provider = ModelProviderInfo {
+            request_max_retries: Some(1),
+            stream_max_retries: Some(2),
+            ..provider
+        };
+        assert_eq!(provider.retry_config(&retry).max_attempts, 1);
+        assert_eq!(provider.stream_retries(&retry), 2);
+        let no_resume = Retry {
+            resume_stream: Some(false),
+            ..retry
+        };
+        assert_eq!(provider.stream_retries(&no_resume), 0);
+
+        let defaults = provider.retry_config(&Retry::default());
+        assert_eq!(
+            (
+                defaults.retry_429,
+                defaults.retry_5xx,
+                defaults.retry_transport
+            ),
+            (false, true, true)
+        );
+        assert!(toml::from_str::<Retry>(r#"retry_on = ["404"]"#).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_bedrock_model_provider_toml() {
+        let bedrock_provider_toml = r#"
+name = "Bedrock (us-west-2)"
+base_url = "https://bedrock-runtime.us-west-2.amazonaws.com"
+wire_api = "bedrock"
+        "#;
+        let provider: ModelProviderInfo = toml::from_str(bedrock_provider_toml).unwrap();
+        assert_eq!(
+            provider,
+            ModelProviderInfo {
+                name: "Bedrock (us-west-2)".into(),
+                base_url: Some("https://bedrock-runtime.us-west-2.amazonaws.com".into()),
+                ..create_bedrock_provider()
+            }
+        );
+        assert!(provider.to_api_provider(None).is_err());
+    }
+
     #[test]
     fn detects_azure_responses_base_urls() {
         let positive_cases = [
@@ -435,6 +1138,11 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
                 request_max_retries: None,
                 stream_max_retries: None,
                 stream_idle_timeout_ms: None,
+                max_request_body_bytes: None,
+                supports_images: None,
+                azure: None,
+                gateway: None,
+                tls: None,
                 requires_openai_auth: false,
             };
             let api = provider.to_api_provider(None).expect("api provider");
@@ -457,6 +1165,11 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            max_request_body_bytes: None,
+            supports_images: None,
+            azure: None,
+            gateway: None,
+            tls: None,
             requires_openai_auth: false,
         };
         let named_api = named_provider.to_api_provider(None).expect("api provider");
@@ -481,6 +1194,11 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
                 request_max_retries: None,
                 stream_max_retries: None,
                 stream_idle_timeout_ms: None,
+                max_request_body_bytes: None,
+                supports_images: None,
+                azure: None,
+                gateway: None,
+                tls: None,
                 requires_openai_auth: false,
             };
             let api = provider.to_api_provider(None).expect("api provider");
@@ -0,0 +1,342 @@
+//! The final-output JSON schema (`codex exec --output-schema`) for providers
+//! without structured outputs.
+//!
+//! The Responses API constrains the final message to the schema itself. For
+//! the other wire APIs the schema is described to the model instead, and the
+//! final message is checked when the turn ends; a message that does not
+//! match is sent back with the mismatch, up to [`MAX_RETRIES`] times.
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::model_provider_info::ModelProviderInfo;
+use crate::model_provider_info::WireApi;
+
+/// Times a final message that does not match is sent back before the
+/// mismatch is reported as an error.
+pub(crate) const MAX_RETRIES: usize = 2;
+
+/// Deepest `$ref` chain followed, so a schema that refers to itself cannot
+/// loop forever.
+const MAX_DEPTH: usize = 64;
+
+const OPEN_TAG: &str = "<output_schema>";
+const CLOSE_TAG: &str = "</output_schema>";
+
+/// Whether `provider` constrains the final message to the schema itself.
+pub(crate) fn enforced_by(provider: &ModelProviderInfo) -> bool {
+    provider.wire_api == WireApi::Responses
+}
+
+/// Tells the model what its final message must look like. It is sent with
+/// every request of the turn, like the plan, instead of being recorded.
+pub(crate) fn instructions_item(schema: &Value) -> ResponseItem {
+    let schema = serde_json::to_string_pretty(schema).unwrap_or_else(|_| schema.to_string());
+    user_message(format!(
+        "{OPEN_TAG}\nYour final message must be a single JSON value that matches this JSON Schema, with no code fences or other text. Messages before the final one are not affected.\n{schema}\n{CLOSE_TAG}"
+    ))
+}
+
+/// Asks for the final message again after `error`.
+pub(crate) fn retry_item(error: &str) -> ResponseItem {
+    user_message(format!(
+        "Your final message does not match the required output schema: {error}. Reply again with only a JSON value that matches the schema."
+    ))
+}
+
+/// The final message parsed as JSON, if it matches `schema`. Code fences
+/// around the JSON are accepted and dropped.
+pub(crate) fn check_final_message(schema: &Value, message: Option<&str>) -> Result<Value, String> {
+    let message = message
+        .map(str::trim)
+        .filter(|message| !message.is_empty())
+        .ok_or_else(|| "there is no final message".to_string())?;
+    let json = message
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map(|fenced| fenced.trim_start_matches("json").trim())
+        .unwrap_or(message);
+    let value: Value = serde_json::from_str(json)
+        .map_err(|err| format!("the final message is not valid JSON ({err})"))?;
+    Validator { root: schema }.check(schema, &value, "$", 0)?;
+    Ok(value)
+}
+
+fn user_message(text: String) -> ResponseItem {
+    ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText { text }],
+    }
+}
+
+/// Checks values against the JSON Schema keywords structured outputs
+/// accept; other keywords are ignored.
+struct Validator<'a> {
+    root: &'a Value,
+}
+
+impl Validator<'_> {
+    fn check(&self, schema: &Value, value: &Value, path: &str, depth: usize) -> Result<(), String> {
+        let schema = match schema {
+            Value::Object(schema) => schema,
+            Value::Bool(false) => return Err(format!("{path} is not allowed")),
+            _ => return Ok(()),
+        };
+        if depth > MAX_DEPTH {
+            return Err(format!("{path}: the schema nests too deeply"));
+        }
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let target = reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.root.pointer(pointer))
+                .ok_or_else(|| format!("{path}: cannot resolve `$ref` {reference}"))?;
+            self.check(target, value, path, depth + 1)?;
+        }
+
+        let types: Vec<&str> = match schema.get("type") {
+            Some(Value::String(ty)) => vec![ty.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|ty| has_type(value, ty)) {
+            return Err(format!(
+                "{path} should be of type {}, not {}",
+                types.join(" or "),
+                type_name(value)
+            ));
+        }
+        if let Some(Value::Array(options)) = schema.get("enum")
+            && !options.contains(value)
+        {
+            let options: Vec<String> = options.iter().map(Value::to_string).collect();
+            return Err(format!("{path} should be one of {}", options.join(", ")));
+        }
+        if let Some(expected) = schema.get("const")
+            && expected != value
+        {
+            return Err(format!("{path} should be {expected}"));
+        }
+        for keyword in ["anyOf", "oneOf"] {
+            if let Some(Value::Array(options)) = schema.get(keyword)
+                && !options
+                    .iter()
+                    .any(|option| self.check(option, value, path, depth + 1).is_ok())
+            {
+                return Err(format!("{path} does not match any of the allowed shapes"));
+            }
+        }
+        if let Some(Value::Array(all)) = schema.get("allOf") {
+            for part in all {
+                self.check(part, value, path, depth + 1)?;
+            }
+        }
+
+        match value {
+            Value::Object(object) => self.check_object(schema, object, path, depth),
+            Value::Array(items) => {
+                if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+                    && (items.len() as u64) < min
+                {
+                    return Err(format!("{path} should have at least {min} items"));
+                }
+                if let Some(max) = schema.get("maxItems").and_then(Value::as_u64)
+                    && items.len() as u64 > max
+                {
+                    return Err(format!("{path} should have at most {max} items"));
+                }
+                if let Some(item_schema) = schema.get("items") {
+                    for (index, item) in items.iter().enumerate() {
+                        self.check(item_schema, item, &format!("{path}[{index}]"), depth + 1)?;
+                    }
+                }
+                Ok(())
+            }
+            Value::String(text) => {
+                let len = text.chars().count() as u64;
+                if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+                    && len < min
+                {
+                    return Err(format!("{path} should be at least {min} characters"));
+                }
+                if let Some(max) = schema.get("maxLength").and_then(Value::as_u64)
+                    && len > max
+                {
+                    return Err(format!("{path} should be at most {max} characters"));
+                }
+                if let Some(pattern) = schema.get("pattern").and_then(Value::as_str)
+                    && let Ok(regex) = regex_lite::Regex::new(pattern)
+                    && !regex.is_match(text)
+                {
+                    return Err(format!("{path} should match the pattern {pattern}"));
+                }
+                Ok(())
+            }
+            Value::Number(number) => {
+                let Some(number) = number.as_f64() else {
+                    return Ok(());
+                };
+                let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+                if let Some(min) = bound("minimum")
+                    && number < min
+                {
+                    return Err(format!("{path} should be at least {min}"));
+                }
+                if let Some(max) = bound("maximum")
+                    && number > max
+                {
+                    return Err(format!("{path} should be at most {max}"));
+                }
+                if let Some(min) = bound("exclusiveMinimum")
+                    && number <= min
+                {
+                    return Err(format!("{path} should be greater than {min}"));
+                }
+                if let Some(max) = bound("exclusiveMaximum")
+                    && number >= max
+                {
+                    return Err(format!("{path} should be less than {max}"));
+                }
+                Ok(())
+            }
+            Value::Bool(_) | Value::Null => Ok(()),
+        }
+    }
+
+    fn check_object(
+        &self,
+        schema: &Map<String, Value>,
+        object: &Map<String, Value>,
+        path: &str,
+        depth: usize,
+    ) -> Result<(), String> {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    return Err(format!("{path} is missing the property `{name}`"));
+                }
+            }
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (key, item) in object {
+            let item_path = format!("{path}.{key}");
+            match properties.and_then(|properties| properties.get(key)) {
+                Some(property) => self.check(property, item, &item_path, depth + 1)?,
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        return Err(format!("{path} has the unexpected property `{key}`"));
+                    }
+                    Some(extra) => self.check(extra, item, &item_path, depth + 1)?,
+                    None => {}
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+fn has_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "status": { "type": "string", "enum": ["ok", "failed"] },
+                "files": { "type": "array", "items": { "$ref": "#/$defs/file" } },
+                "note": { "type": ["string", "null"] }
+            },
+            "required": ["status", "files"],
+            "additionalProperties": false,
+            "$defs": {
+                "file": {
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string", "minLength": 1 },
+                        "lines": { "type": "integer", "minimum": 0 }
+                    },
+                    "required": ["path", "lines"],
+                    "additionalProperties": false
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn accepts_matching_messages_with_or_without_fences() {
+        let message = r#"{"status":"ok","files":[{"path":"src/lib.rs","lines":12}],"note":null}"#;
+        let expected: Value = serde_json::from_str(message).expect("json");
+        assert_eq!(
+            check_final_message(&schema(), Some(message)),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            check_final_message(&schema(), Some(&format!("```json\n{message}\n```"))),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn reports_the_first_mismatch_with_its_path() {
+        let check = |message: &str| check_final_message(&schema(), Some(message)).unwrap_err();
+        assert_eq!(
+            check(r#"{"status":"done","files":[]}"#),
+            r#"$.status should be one of "ok", "failed""#
+        );
+        assert_eq!(
+            check(r#"{"status":"ok","files":[{"path":"a","lines":-1}]}"#),
+            "$.files[0].lines should be at least 0"
+        );
+        assert_eq!(
+            check(r#"{"status":"ok","files":[{"path":"a"}]}"#),
+            "$.files[0] is missing the property `lines`"
+        );
+        assert_eq!(
+            check(r#"{"status":"ok","files":[],"extra":1}"#),
+            "$ has the unexpected property `extra`"
+        );
+        assert_eq!(
+            check(r#"{"status":"ok","files":{}}"#),
+            "$.files should be of type array, not an object"
+        );
+        assert!(
+            check("Done! Here is the summary.").starts_with("the final message is not valid JSON")
+        );
+        assert_eq!(
+            check_final_message(&schema(), None).unwrap_err(),
+            "there is no final message"
+        );
+    }
+}
@@ -0,0 +1,369 @@
+//! Three-way merge fallback for `apply_patch`. The session records each
+//! file's contents when the model reads it, until a patch to the file is
+//! applied. When a patch no longer applies because the file changed on disk
+//! since then, the patch is applied to that recorded base instead and merged
+//! with the file as it is now. Overlapping changes are reported back to the
+//! model as conflicts rather than written.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_apply_patch::ApplyPatchAction;
+use codex_apply_patch::ApplyPatchArgs;
+use codex_apply_patch::ApplyPatchFileChange;
+use codex_apply_patch::Hunk;
+use codex_apply_patch::MaybeApplyPatch;
+use codex_apply_patch::MaybeApplyPatchVerified;
+use codex_apply_patch::apply_update_chunks;
+use codex_protocol::parse_command::ParsedCommand;
+use indexmap::IndexMap;
+use similar::ChangeTag;
+use similar::TextDiff;
+
+use crate::codex::Session;
+use crate::parse_command::parse_command;
+
+/// Larger files are not recorded.
+const MAX_BASE_BYTES: usize = 1024 * 1024;
+/// Total size of the recorded files; the least recently used are dropped
+/// beyond it.
+const MAX_TOTAL_BASE_BYTES: usize = 16 * MAX_BASE_BYTES;
+/// Files recorded at most.
+const MAX_BASES: usize = 256;
+
+/// Context lines around each change of a rewritten patch.
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum MergeFallback {
+    /// The patch, rewritten against the files as they are on disk.
+    Merged {
+        action: ApplyPatchAction,
+        /// Tells the model its patch was merged.
+        note: String,
+    },
+    /// Nothing was applied; the report shows the conflicting regions.
+    Conflicts(String),
+}
+
+/// Contents of each file as the model last read it, least recently used
+/// first.
+#[derive(Default)]
+pub(crate) struct FileBases {
+    bases: IndexMap<PathBuf, String>,
+    total_bytes: usize,
+}
+
+impl FileBases {
+    pub(crate) fn record(&mut self, path: PathBuf, contents: String) {
+        self.forget(&path);
+        self.total_bytes += contents.len();
+        self.bases.insert(path, contents);
+        while self.bases.len() > MAX_BASES || self.total_bytes > MAX_TOTAL_BASE_BYTES {
+            let Some((_, evicted)) = self.bases.shift_remove_index(0) else {
+                break;
+            };
+            self.total_bytes -= evicted.len();
+        }
+    }
+
+    pub(crate) fn get(&mut self, path: &Path) -> Option<String> {
+        let index = self.bases.get_index_of(path)?;
+        self.bases.move_index(index, self.bases.len() - 1);
+        self.bases.get(path).cloned()
+    }
+
+    pub(crate) fn forget(&mut self, path: &Path) {
+        if let Some(contents) = self.bases.shift_remove(path) {
+            self.total_bytes -= contents.len();
+        }
+    }
+}
+
+/// Records the contents of `path` as the model has just seen them.
+pub(crate) async fn record_read(sess: &Session, path: &Path) {
+    if let Ok(contents) = tokio::fs::read_to_string(path).await
+        && contents.len() <= MAX_BASE_BYTES
+    {
+        sess.record_file_base(path.to_path_buf(), contents).await;
+    }
+}
+
+/// Records the files a shell command such as `cat` or `sed -n` read.
+pub(crate) async fn record_reads(sess: &Session, command: &[String], cwd: &Path) {
+    for parsed in parse_command(command) {
+        if let ParsedCommand::Read { path, .. } = parsed {
+            record_read(sess, &cwd.join(path)).await;
+        }
+    }
+}
+
+/// Drops the recorded contents of the files an applied patch touched, which
+/// no longer match what the model last read.
+pub(crate) async fn forget_applied(sess: &Session, action: &ApplyPatchAction) {
+    for (path, change) in action.changes() {
+        sess.forget_file_base(path).await;
+        if let ApplyPatchFileChange::Update {
+            move_path: Some(move_path),
+            ..
+        } = change
+        {
+            sess.forget_file_base(move_path).await;
+        }
+    }
+}
+
+/// Retries a patch that failed verification by merging it into files that
+/// changed since the model last saw them. Returns `None` when the failure
+/// has another cause.
+pub(crate) async fn merge_with_recorded_bases(
+    sess: &Session,
+    argv: &[String],
+    cwd: &Path,
+) -> Option<MergeFallback> {
+    let MaybeApplyPatch::Body(ApplyPatchArgs { hunks, workdir, .. }) =
+        codex_apply_patch::maybe_parse_apply_patch(argv)
+    else {
+        return None;
+    };
+    let cwd = match workdir {
+        Some(dir) => cwd.join(dir),
+        None => cwd.to_path_buf(),
+    };
+
+    let mut patch = String::new();
+    let mut merged: Vec<String> = Vec::new();
+    let mut conflicts: Vec<String> = Vec::new();
+    for hunk in hunks {
+        let path = hunk.resolve_path(&cwd);
+        let display = path
+            .strip_prefix(&cwd)
+            .unwrap_or(&path)
+            .display()
+            .to_string();
+        match hunk {
+            Hunk::AddFile { contents, .. } => {
+                patch.push_str(&format!("*** Add File: {}\n", path.display()));
+                for line in contents.lines() {
+                    patch.push_str(&format!("+{line}\n"));
+                }
+            }
+            Hunk::DeleteFile { .. } => {
+                patch.push_str(&format!("*** Delete File: {}\n", path.display()));
+            }
+            Hunk::UpdateFile {
+                move_path, chunks, ..
+            } => {
+                let current = tokio::fs::read_to_string(&path).await.ok()?;
+                let target = match apply_update_chunks(&path, &current, &chunks) {
+                    Ok(target) => target,
+                    Err(_) => {
+                        let base = sess.file_base(&path).await?;
+                        let patched_base = apply_update_chunks(&path, &base, &chunks).ok()?;
+                        match diffy::merge(&base, &current, &patched_base) {
+                            Ok(target) => {
+                                merged.push(display);
+                                target
+                            }
+                            Err(with_markers) => {
+                                conflicts.push(conflict_report(&display, &with_markers));
+                                continue;
+                            }
+                        }
+                    }
+                };
+                let changes = update_lines(&current, &target);
+                if changes.is_empty() && move_path.is_none() {
+                    continue;
+                }
+                patch.push_str(&format!("*** Update File: {}\n", path.display()));
+                if let Some(move_path) = move_path {
+                    patch.push_str(&format!("*** Move to: {}\n", cwd.join(move_path).display()));
+                }
+                patch.push_str(&changes);
+            }
+        }
+    }
+
+    if !conflicts.is_empty() {
+        return Some(MergeFallback::Conflicts(format!(
+            "apply_patch was not applied: files changed on disk since you last read them, and \
+             the changes conflict with your patch. In each conflict, `ours` is the file on disk \
+             and `theirs` is your patch.\n\n{}\n\nRead the files again and send a patch against \
+             their current contents.",
+            conflicts.join("\n\n")
+        )));
+    }
+    if merged.is_empty() || patch.is_empty() {
+        return None;
+    }
+    let patch = format!("*** Begin Patch\n{patch}*** End Patch\n");
+    match codex_apply_patch::maybe_parse_apply_patch_verified(
+        &["apply_patch".to_string(), patch],
+        &cwd,
+    ) {
+        MaybeApplyPatchVerified::Body(action) => Some(MergeFallback::Merged {
+            action,
+            note: format!(
+                "Note: {} changed on disk since you last read {}; your patch was merged with \
+                 those changes.",
+                merged.join(", "),
+                if merged.len() == 1 { "it" } else { "them" }
+            ),
+        }),
+        _ => None,
+    }
+}
+
+/// `apply_patch` chunks turning `current` into `target`.
+fn update_lines(current: &str, target: &str) -> String {
+    let diff = TextDiff::from_lines(current, target);
+    let mut lines = String::new();
+    for group in diff.grouped_ops(CONTEXT_LINES) {
+        lines.push_str("@@\n");
+        for op in &group {
+            for change in diff.iter_changes(op) {
+                lines.push(match change.tag() {
+                    ChangeTag::Equal => ' ',
+                    ChangeTag::Delete => '-',
+                    ChangeTag::Insert => '+',
+                });
+                let value = change.value();
+                lines.push_str(value.strip_suffix('\n').unwrap_or(value));
+                lines.push('\n');
+            }
+        }
+    }
+    lines
+}
+
+/// The conflict blocks of a merge, without the lines merged cleanly.
+fn conflict_report(path: &str, with_markers: &str) -> String {
+    let mut blocks: Vec<String> = Vec::new();
+    let mut block: Option<String> = None;
+    for line in with_markers.lines() {
+        if line.starts_with("<<<<<<<") {
+            block = Some(String::new());
+        }
+        if let Some(block) = block.as_mut() {
+            block.push_str(line);
+            block.push('\n');
+        }
+        if line.starts_with(">>>>>>>")
+            && let Some(block) = block.take()
+        {
+            blocks.push(block);
+        }
+    }
+    format!("{path}:\n```\n{}```", blocks.join("...\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codex::make_session_and_context;
+    use pretty_assertions::assert_eq;
+
+    fn patch_argv(patch: &str) -> Vec<String> {
+        vec!["apply_patch".to_string(), patch.to_string()]
+    }
+
+    const PATCH: &str = "*** Begin Patch\n\
+                         *** Update File: a.txt\n\
+                         @@\n\
+                         -two\n\
+                         +TWO\n\
+                         *** End Patch";
+
+    #[tokio::test]
+    async fn stale_patch_is_merged_with_changes_on_disk() {
+        let (session, _turn) = make_session_and_context();
+        let tmp = tempfile::tempdir().expect("tmp");
+        let path = tmp.path().join("a.txt");
+        let patch = "*** Begin Patch\n\
+                     *** Update File: a.txt\n\
+                     @@\n \
+                     one\n \
+                     two\n\
+                     -three\n\
+                     +THREE\n\
+                     *** End Patch";
+        std::fs::write(&path, "ONE\ntwo\nthree\nfour\n").expect("write");
+        assert_eq!(
+            merge_with_recorded_bases(&session, &patch_argv(patch), tmp.path()).await,
+            None,
+            "nothing to merge with before the model has read the file"
+        );
+
+        std::fs::write(&path, "one\ntwo\nthree\nfour\n").expect("write");
+        record_read(&session, &path).await;
+        // The user edits the file after the model read it, so the patch's
+        // context no longer matches.
+        std::fs::write(&path, "ONE\ntwo\nthree\nfour\n").expect("write");
+        let Some(MergeFallback::Merged { action, note }) =
+            merge_with_recorded_bases(&session, &patch_argv(patch), tmp.path()).await
+        else {
+            panic!("expected a merge");
+        };
+        assert_eq!(
+            note,
+            "Note: a.txt changed on disk since you last read it; your patch was merged with \
+             those changes."
+        );
+        let Some(ApplyPatchFileChange::Update { new_content, .. }) = action.changes().get(&path)
+        else {
+            panic!("expected an update of a.txt");
+        };
+        assert_eq!(new_content, "ONE\ntwo\nTHREE\nfour\n");
+    }
+
+    #[test]
+    fn least_recently_used_bases_are_dropped_first() {
+        let mut bases = FileBases::default();
+        let big = "x".repeat(MAX_BASE_BYTES);
+        for n in 0..16 {
+            bases.record(PathBuf::from(format!("{n}.txt")), big.clone());
+        }
+        // Reading 0.txt makes 1.txt the least recently used.
+        assert_eq!(bases.get(Path::new("0.txt")), Some(big.clone()));
+        bases.record(PathBuf::from("16.txt"), "new".to_string());
+        assert_eq!(bases.get(Path::new("1.txt")), None);
+        assert_eq!(bases.get(Path::new("0.txt")), Some(big.clone()));
+        assert_eq!(bases.total_bytes, 15 * MAX_BASE_BYTES + 3);
+
+        bases.forget(Path::new("16.txt"));
+        assert_eq!(bases.get(Path::new("16.txt")), None);
+        assert_eq!(bases.total_bytes, 15 * MAX_BASE_BYTES);
+
+        for n in 0..MAX_BASES + 10 {
+            bases.record(PathBuf::from(format!("small-{n}.txt")), "small".to_string());
+        }
+        assert_eq!(bases.bases.len(), MAX_BASES);
+        assert_eq!(bases.get(Path::new("small-0.txt")), None);
+    }
+
+    #[tokio::test]
+    async fn overlapping_changes_are_reported_as_conflicts() {
+        let (session, _turn) = make_session_and_context();
+        let tmp = tempfile::tempdir().expect("tmp");
+        let path = tmp.path().join("a.txt");
+        std::fs::write(&path, "one\ntwo\nthree\n").expect("write");
+        record_read(&session, &path).await;
+        std::fs::write(&path, "one\n2\nthree\n").expect("write");
+
+        let Some(MergeFallback::Conflicts(report)) =
+            merge_with_recorded_bases(&session, &patch_argv(PATCH), tmp.path()).await
+        else {
+            panic!("expected conflicts");
+        };
+        assert!(
+            report.contains("a.txt:\n```\n<<<<<<< ours\n2\n"),
+            "{report}"
+        );
+        assert!(report.contains("TWO\n>>>>>>> theirs\n```"), "{report}");
+        assert_eq!(
+            std::fs::read_to_string(&path).expect("read"),
+            "one\n2\nthree\n"
+        );
+    }
+}
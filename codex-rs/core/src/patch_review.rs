@@ -0,0 +1,312 @@
+//! Hunk-by-hunk review of a proposed patch. The client splits each file's
+//! change with [`file_change_hunks`] and decides on every hunk
+//! ([`Op::PatchHunkReview`](crate::protocol::Op::PatchHunkReview)); the
+//! session then applies a patch rebuilt from the accepted and edited hunks
+//! and tells the model which hunks were rejected and why.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_apply_patch::ApplyPatchAction;
+
+use crate::apply_patch::convert_apply_patch_to_protocol;
+use crate::protocol::FileChange;
+use crate::protocol::HunkDecision;
+use crate::protocol::PatchHunkDecision;
+
+/// The hunks of `change` as shown for review. An update is split at the
+/// `@@` headers of its unified diff, each hunk keeping its header; an added
+/// or deleted file is a single hunk of `+` or `-` lines.
+pub fn file_change_hunks(change: &FileChange) -> Vec<String> {
+    match change {
+        FileChange::Add { content } => vec![prefix_lines(content, '+')],
+        FileChange::Delete { content } => vec![prefix_lines(content, '-')],
+        FileChange::Update { unified_diff, .. } => {
+            let mut hunks: Vec<String> = Vec::new();
+            for line in unified_diff.split_inclusive('\n') {
+                if line.starts_with("@@") {
+                    hunks.push(line.to_string());
+                } else if let Some(hunk) = hunks.last_mut() {
+                    hunk.push_str(line);
+                }
+            }
+            hunks
+        }
+    }
+}
+
+fn prefix_lines(content: &str, marker: char) -> String {
+    content
+        .lines()
+        .map(|line| format!("{marker}{line}\n"))
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct ReviewedPatch {
+    /// The accepted and edited hunks as an `apply_patch` patch; `None` when
+    /// every hunk was rejected.
+    pub(crate) patch: Option<String>,
+    /// What the review changed, for the model. Empty when every hunk was
+    /// accepted as proposed.
+    pub(crate) feedback: String,
+}
+
+/// Rebuilds `action` from the user's per-hunk `decisions`. Hunks without a
+/// decision are rejected.
+pub(crate) fn review_patch(
+    action: &ApplyPatchAction,
+    decisions: &[PatchHunkDecision],
+) -> Result<ReviewedPatch, String> {
+    let changes = convert_apply_patch_to_protocol(action);
+    let mut paths: Vec<&PathBuf> = changes.keys().collect();
+    paths.sort();
+
+    let mut patch = String::new();
+    let mut edited: Vec<String> = Vec::new();
+    let mut rejected: Vec<String> = Vec::new();
+    for path in paths {
+        let change = &changes[path];
+        let display = path.strip_prefix(&action.cwd).unwrap_or(path);
+        let hunks = file_change_hunks(change);
+        let mut body = String::new();
+        let mut kept = false;
+        for (index, hunk) in hunks.iter().enumerate() {
+            let label = format!("{} hunk {}/{}", display.display(), index + 1, hunks.len());
+            let decision = decisions
+                .iter()
+                .find(|decision| decision.path == *path && decision.hunk == index)
+                .map(|decision| &decision.decision);
+            match decision {
+                Some(HunkDecision::Accept) => {
+                    kept = true;
+                    body.push_str(&proposed_lines(change, hunk));
+                }
+                Some(HunkDecision::Edit { diff }) => {
+                    kept = true;
+                    body.push_str(&edited_lines(change, display, diff)?);
+                    edited.push(label);
+                }
+                Some(HunkDecision::Reject { comment }) => {
+                    rejected.push(rejection(&label, hunk, comment.as_deref()));
+                }
+                None => rejected.push(rejection(&label, hunk, None)),
+            }
+        }
+        if !kept {
+            continue;
+        }
+        match change {
+            FileChange::Add { .. } => {
+                patch.push_str(&format!("*** Add File: {}\n{body}", display.display()));
+            }
+            FileChange::Delete { .. } => {
+                patch.push_str(&format!("*** Delete File: {}\n", display.display()));
+            }
+            FileChange::Update { move_path, .. } => {
+                patch.push_str(&format!("*** Update File: {}\n", display.display()));
+                if let Some(move_path) = move_path {
+                    let move_path = move_path.strip_prefix(&action.cwd).unwrap_or(move_path);
+                    patch.push_str(&format!("*** Move to: {}\n", move_path.display()));
+                }
+                patch.push_str(&body);
+            }
+        }
+    }
+
+    let mut feedback = String::new();
+    if !edited.is_empty() || !rejected.is_empty() {
+        feedback.push_str("The user reviewed this patch hunk by hunk.");
+    }
+    if !edited.is_empty() {
+        feedback.push_str(&format!(
+            " These hunks were applied as edited by the user: {}.",
+            edited.join(", ")
+        ));
+    }
+    if !rejected.is_empty() {
+        feedback.push_str(" These hunks were rejected and not applied:\n");
+        feedback.push_str(&rejected.join("\n"));
+    }
+    Ok(ReviewedPatch {
+        patch: (!patch.is_empty()).then(|| format!("*** Begin Patch\n{patch}*** End Patch\n")),
+        feedback,
+    })
+}
+
+/// A proposed hunk as `apply_patch` lines.
+fn proposed_lines(change: &FileChange, hunk: &str) -> String {
+    match change {
+        FileChange::Add { .. } | FileChange::Delete { .. } => hunk.to_string(),
+        FileChange::Update { .. } => {
+            let mut lines = "@@\n".to_string();
+            for line in hunk.lines().skip(1) {
+                // `\ No newline at end of file` has no `apply_patch` equivalent.
+                if !line.starts_with('\\') {
+                    lines.push_str(line);
+                    lines.push('\n');
+                }
+            }
+            lines
+        }
+    }
+}
+
+/// A hunk as rewritten by the user, checked to be valid `apply_patch` lines.
+fn edited_lines(change: &FileChange, path: &Path, diff: &str) -> Result<String, String> {
+    let (mut lines, markers, blank) = match change {
+        FileChange::Add { .. } => (String::new(), &['+'][..], "+"),
+        FileChange::Update { .. } => ("@@\n".to_string(), &[' ', '-', '+'][..], " "),
+        FileChange::Delete { .. } => {
+            return Err(format!(
+                "the deletion of {} cannot be edited",
+                path.display()
+            ));
+        }
+    };
+    for line in diff.lines() {
+        // Editors tend to strip the marker of a blank line.
+        let line = if line.is_empty() { blank } else { line };
+        if !line.starts_with(markers) {
+            return Err(format!(
+                "edited hunk of {} has a line not starting with {}: {line}",
+                path.display(),
+                markers
+                    .iter()
+                    .map(|marker| format!("`{marker}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        lines.push_str(line);
+        lines.push('\n');
+    }
+    Ok(lines)
+}
+
+fn rejection(label: &str, hunk: &str, comment: Option<&str>) -> String {
+    let comment = comment
+        .map(str::trim)
+        .filter(|comment| !comment.is_empty())
+        .map(|comment| format!(" (user comment: {comment})"))
+        .unwrap_or_default();
+    format!("{label}{comment}\n```diff\n{hunk}```")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn update_action(dir: &Path) -> ApplyPatchAction {
+        let original: String = (1..=12).map(|n| format!("line {n}\n")).collect();
+        std::fs::write(dir.join("a.txt"), original).expect("write");
+        let patch = "*** Begin Patch\n\
+                     *** Update File: a.txt\n\
+                     @@\n\
+                     -line 2\n\
+                     +line two\n\
+                     @@\n\
+                     -line 11\n\
+                     +line eleven\n\
+                     *** Add File: b.txt\n\
+                     +new\n\
+                     *** End Patch";
+        match codex_apply_patch::maybe_parse_apply_patch_verified(
+            &["apply_patch".to_string(), patch.to_string()],
+            dir,
+        ) {
+            codex_apply_patch::MaybeApplyPatchVerified::Body(action) => action,
+            other => panic!("unexpected parse result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn updates_are_split_at_hunk_headers() {
+        let tmp = tempfile::tempdir().expect("tmp");
+        let action = update_action(tmp.path());
+        let changes = convert_apply_patch_to_protocol(&action);
+        assert_eq!(
+            file_change_hunks(&changes[&tmp.path().join("a.txt")]),
+            vec![
+                "@@ -1,3 +1,3 @@\n line 1\n-line 2\n+line two\n line 3\n".to_string(),
+                "@@ -10,3 +10,3 @@\n line 10\n-line 11\n+line eleven\n line 12\n".to_string(),
+            ]
+        );
+        assert_eq!(
+            file_change_hunks(&changes[&tmp.path().join("b.txt")]),
+            vec!["+new\n".to_string()]
+        );
+    }
+
+    #[test]
+    fn only_accepted_and_edited_hunks_are_kept() {
+        let tmp = tempfile::tempdir().expect("tmp");
+        let action = update_action(tmp.path());
+        let decide = |path: &str, hunk, decision| PatchHunkDecision {
+            path: tmp.path().join(path),
+            hunk,
+            decision,
+        };
+        let reviewed = review_patch(
+            &action,
+            &[
+                decide(
+                    "a.txt",
+                    0,
+                    HunkDecision::Edit {
+                        diff: " line 1\n-line 2\n+line 2 (edited)\n line 3\n".to_string(),
+                    },
+                ),
+                decide(
+                    "a.txt",
+                    1,
+                    HunkDecision::Reject {
+                        comment: Some("keep 11 as is".to_string()),
+                    },
+                ),
+            ],
+        )
+        .expect("review");
+        assert_eq!(
+            reviewed,
+            ReviewedPatch {
+                patch: Some(
+                    "*** Begin Patch\n\
+                     *** Update File: a.txt\n\
+                     @@\n line 1\n-line 2\n+line 2 (edited)\n line 3\n\
+                     *** End Patch\n"
+                        .to_string()
+                ),
+                feedback: "The user reviewed this patch hunk by hunk. These hunks were applied \
+                           as edited by the user: a.txt hunk 1/2. These hunks were rejected and \
+                           not applied:\n\
+                           a.txt hunk 2/2 (user comment: keep 11 as is)\n\
+                           ```diff\n@@ -10,3 +10,3 @@\n line 10\n-line 11\n+line eleven\n line 12\n```\n\
+                           b.txt hunk 1/1\n```diff\n+new\n```"
+                    .to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn edited_hunks_must_keep_their_markers() {
+        let tmp = tempfile::tempdir().expect("tmp");
+        let action = update_action(tmp.path());
+        let err = review_patch(
+            &action,
+            &[PatchHunkDecision {
+                path: tmp.path().join("b.txt"),
+                hunk: 0,
+                decision: HunkDecision::Edit {
+                    diff: "+new\nforgot the marker\n".to_string(),
+                },
+            }],
+        )
+        .expect_err("invalid edit");
+        assert_eq!(
+            err,
+            "edited hunk of b.txt has a line not starting with `+`: forgot the marker"
+        );
+    }
+}
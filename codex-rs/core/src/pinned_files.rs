@@ -0,0 +1,220 @@
+//! Files and directories pinned with `pinned_files` in config.toml or
+//! `/pin` in the TUI. They are read from disk for every turn and sent ahead
+//! of the conversation history instead of being recorded in it, so the
+//! model always sees their current contents and neither compaction nor
+//! history truncation can drop them.
+
+use std::fmt::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use walkdir::WalkDir;
+
+use crate::codex_ignore;
+use crate::codex_ignore::CodexIgnore;
+use crate::protocol::PinnedFile;
+use crate::truncate::approx_token_count;
+
+pub(crate) const PINNED_FILES_OPEN_TAG: &str = "<pinned_files>";
+const PINNED_FILES_CLOSE_TAG: &str = "</pinned_files>";
+/// Pins larger than this are not sent; pin a narrower path instead.
+const MAX_PIN_TOKENS: usize = 32_000;
+/// A pinned directory lists at most this many files.
+const MAX_DIR_FILES: usize = 200;
+
+/// A pin as it is sent for the current turn.
+struct ReadPin {
+    report: PinnedFile,
+    files: Vec<(PathBuf, String)>,
+}
+
+/// Reads every pin and returns the message that carries them, if any could
+/// be read, along with how many tokens each pin takes.
+pub(crate) fn render_pins(cwd: &Path, pins: &[PathBuf]) -> (Option<ResponseItem>, Vec<PinnedFile>) {
+    let read: Vec<ReadPin> = pins.iter().map(|pin| read_pin(cwd, pin)).collect();
+    let mut text = String::new();
+    for pin in read.iter().filter(|pin| pin.report.error.is_none()) {
+        for (path, contents) in &pin.files {
+            text.push_str(&render_file(path, contents));
+        }
+    }
+    let item = (!text.is_empty()).then(|| ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: format!(
+                "{PINNED_FILES_OPEN_TAG}\nThe user pinned these files to keep them in context. They are read again before every turn, so they are current.\n{text}{PINNED_FILES_CLOSE_TAG}"
+            ),
+        }],
+    });
+    (item, read.into_iter().map(|pin| pin.report).collect())
+}
+
+/// Reads `pin` when it is pinned, so a path that cannot be sent is
+/// rejected up front.
+pub(crate) fn check_pin(cwd: &Path, pin: &Path) -> Result<PinnedFile, String> {
+    let pin = read_pin(cwd, pin);
+    match pin.report.error {
+        Some(error) => Err(error),
+        None => Ok(pin.report),
+    }
+}
+
+fn read_pin(cwd: &Path, pin: &Path) -> ReadPin {
+    let files = read_pin_files(&cwd.join(pin)).and_then(|files| {
+        // Show paths as pinned, not as resolved against the cwd.
+        let files: Vec<(PathBuf, String)> = files
+            .into_iter()
+            .map(|(path, contents)| match path.strip_prefix(cwd) {
+                Ok(shown) => (shown.to_path_buf(), contents),
+                Err(_) => (path, contents),
+            })
+            .collect();
+        let tokens: usize = files
+            .iter()
+            .map(|(path, contents)| approx_token_count(&render_file(path, contents)))
+            .sum();
+        if tokens > MAX_PIN_TOKENS {
+            Err(format!(
+                "{} is about {tokens} tokens, more than the {MAX_PIN_TOKENS} a pin may use",
+                pin.display()
+            ))
+        } else {
+            Ok((files, tokens))
+        }
+    });
+    match files {
+        Ok((files, tokens)) => ReadPin {
+            report: PinnedFile {
+                path: pin.to_path_buf(),
+                files: files.len(),
+                tokens: tokens as u64,
+                error: None,
+            },
+            files,
+        },
+        Err(error) => ReadPin {
+            report: PinnedFile {
+                path: pin.to_path_buf(),
+                files: 0,
+                tokens: 0,
+                error: Some(error),
+            },
+            files: Vec::new(),
+        },
+    }
+}
+
+/// The text files `path` covers: the file itself, or the files under a
+/// directory, skipping hidden entries, files that are not UTF-8, and files
+/// excluded by a `.codexignore`.
+fn read_pin_files(path: &Path) -> Result<Vec<(PathBuf, String)>, String> {
+    if codex_ignore::is_ignored(path) {
+        return Err(codex_ignore::ignored_error(path));
+    }
+    let metadata =
+        std::fs::metadata(path).map_err(|err| format!("cannot read {}: {err}", path.display()))?;
+    if !metadata.is_dir() {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|err| format!("cannot read {}: {err}", path.display()))?;
+        return Ok(vec![(path.to_path_buf(), contents)]);
+    }
+    let mut ignore = CodexIgnore::default();
+    let mut files = Vec::new();
+    let entries = WalkDir::new(path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.')
+        })
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file());
+    for entry in entries {
+        if ignore.is_ignored(entry.path(), false) {
+            continue;
+        }
+        if files.len() == MAX_DIR_FILES {
+            return Err(format!(
+                "{} has more than {MAX_DIR_FILES} files; pin a narrower path",
+                path.display()
+            ));
+        }
+        if let Ok(contents) = std::fs::read_to_string(entry.path()) {
+            files.push((entry.into_path(), contents));
+        }
+    }
+    if files.is_empty() {
+        return Err(format!("{} has no text files", path.display()));
+    }
+    Ok(files)
+}
+
+fn render_file(path: &Path, contents: &str) -> String {
+    let mut rendered = String::new();
+    let _ = writeln!(rendered, "<file path=\"{}\">", path.display());
+    rendered.push_str(contents);
+    if !contents.ends_with('\n') {
+        rendered.push('\n');
+    }
+    rendered.push_str("</file>\n");
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn pins_are_rendered_with_their_token_counts() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let cwd = dir.path();
+        std::fs::write(cwd.join("schema.sql"), "create table users (id int);").expect("write");
+        std::fs::create_dir_all(cwd.join("docs/.drafts")).expect("mkdir");
+        std::fs::write(cwd.join("docs/b.md"), "# B\n").expect("write");
+        std::fs::write(cwd.join("docs/a.md"), "# A\n").expect("write");
+        std::fs::write(cwd.join("docs/.drafts/c.md"), "# C\n").expect("write");
+        std::fs::write(cwd.join("docs/logo.png"), [0xff, 0xfe, 0x00]).expect("write");
+
+        let pins = [
+            PathBuf::from("schema.sql"),
+            PathBuf::from("docs"),
+            PathBuf::from("missing.md"),
+        ];
+        let (item, report) = render_pins(cwd, &pins);
+        let Some(ResponseItem::Message { content, .. }) = item else {
+            panic!("expected a message");
+        };
+        let [ContentItem::InputText { text }] = content.as_slice() else {
+            panic!("expected text");
+        };
+        assert!(text.starts_with(PINNED_FILES_OPEN_TAG));
+        assert!(text.ends_with(&format!(
+            "<file path=\"schema.sql\">\ncreate table users (id int);\n</file>\n<file path=\"docs/a.md\">\n# A\n</file>\n<file path=\"docs/b.md\">\n# B\n</file>\n{PINNED_FILES_CLOSE_TAG}"
+        )));
+
+        assert_eq!(
+            report
+                .iter()
+                .map(|pin| (pin.path.to_string_lossy().into_owned(), pin.files))
+                .collect::<Vec<_>>(),
+            vec![
+                ("schema.sql".to_string(), 1),
+                ("docs".to_string(), 2),
+                ("missing.md".to_string(), 0),
+            ]
+        );
+        assert!(report[0].tokens > 0);
+        assert!(report[2].error.is_some());
+        assert!(check_pin(cwd, Path::new("missing.md")).is_err());
+        assert_eq!(check_pin(cwd, Path::new("docs")), Ok(report[1].clone()));
+
+        std::fs::write(cwd.join("huge.txt"), "x".repeat(MAX_PIN_TOKENS * 8)).expect("write");
+        assert!(
+            check_pin(cwd, Path::new("huge.txt"))
+                .is_err_and(|err| err.contains("more than the 32000 a pin may use"))
+        );
+    }
+}
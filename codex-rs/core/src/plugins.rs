@@ -0,0 +1,347 @@
+//! Tools shipped as WebAssembly modules in `$CODEX_HOME/plugins/`, so they
+//! can be added without rebuilding Codex. Each `*.wasm` file is a core
+//! module that imports nothing, so it cannot reach the file system, the
+//! network, or the clock. It exports:
+//!
+//! - `memory`: its linear memory.
+//! - `codex_plugin_abi() -> i32`: the version of this interface, `1`.
+//! - `codex_alloc(len: i32) -> i32`: the address of `len` bytes Codex may
+//!   write its input to.
+//! - `codex_tools() -> i64`: a JSON array of the tools the plugin
+//!   provides, each with a `name`, a `description`, and a JSON Schema for
+//!   its `parameters`.
+//! - `codex_call(name: i32, name_len: i32, args: i32, args_len: i32) -> i64`:
+//!   runs the named tool on its JSON arguments and returns
+//!   `{"output": "...", "success": true}`; `success` defaults to `true`.
+//!
+//! Both `i64` results point at UTF-8 bytes in `memory`: the address in the
+//! high 32 bits and the length in the low 32. Every call runs in a fresh
+//! instance with bounded memory and fuel, so calls share no state and a
+//! plugin that loops forever is stopped.
+
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Context;
+use anyhow::bail;
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::warn;
+use wasmtime::Engine;
+use wasmtime::Instance;
+use wasmtime::Memory;
+use wasmtime::Module;
+use wasmtime::Store;
+use wasmtime::StoreLimits;
+use wasmtime::StoreLimitsBuilder;
+use wasmtime::TypedFunc;
+use wasmtime::WasmParams;
+use wasmtime::WasmResults;
+
+const PLUGINS_SUBDIR: &str = "plugins";
+const ABI_VERSION: i32 = 1;
+const MAX_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+/// Roughly a few seconds of work.
+const FUEL_PER_CALL: u64 = 2_000_000_000;
+const MAX_RESULT_BYTES: usize = 4 * 1024 * 1024;
+const MAX_TOOL_NAME_LEN: usize = 64;
+
+/// A tool as a plugin declares it in `codex_tools`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct PluginToolSpec {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) description: String,
+    #[serde(default)]
+    pub(crate) parameters: Option<Value>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub(crate) struct PluginOutput {
+    pub(crate) output: String,
+    #[serde(default = "default_success")]
+    pub(crate) success: bool,
+}
+
+fn default_success() -> bool {
+    true
+}
+
+/// One tool of a loaded plugin.
+#[derive(Clone)]
+pub(crate) struct PluginTool {
+    pub(crate) spec: PluginToolSpec,
+    plugin: Arc<Plugin>,
+}
+
+impl fmt::Debug for PluginTool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PluginTool")
+            .field("spec", &self.spec)
+            .field("plugin", &self.plugin.path)
+            .finish()
+    }
+}
+
+impl PluginTool {
+    /// Runs the tool on `arguments`. Blocks until the plugin returns.
+    pub(crate) fn call(&self, arguments: &str) -> Result<PluginOutput, String> {
+        self.plugin
+            .call(&self.spec.name, arguments)
+            .map_err(|err| format!("plugin {} failed: {err:#}", self.plugin.path.display()))
+    }
+}
+
+struct Plugin {
+    path: PathBuf,
+    engine: Engine,
+    module: Module,
+}
+
+struct State {
+    limits: StoreLimits,
+}
+
+/// A fresh instance of a plugin.
+struct Running {
+    store: Store<State>,
+    instance: Instance,
+    memory: Memory,
+}
+
+pub(crate) fn plugins_dir(codex_home: &Path) -> PathBuf {
+    codex_home.join(PLUGINS_SUBDIR)
+}
+
+/// Loads every plugin in `dir`, sorted by file name. Plugins that fail to
+/// load are skipped with a warning, as are tools whose name an earlier
+/// plugin already took.
+pub(crate) fn load_plugins(dir: &Path) -> Vec<PluginTool> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+        .collect();
+    if paths.is_empty() {
+        return Vec::new();
+    }
+    paths.sort();
+
+    let mut config = wasmtime::Config::new();
+    config.consume_fuel(true);
+    let engine = match Engine::new(&config) {
+        Ok(engine) => engine,
+        Err(err) => {
+            warn!("failed to start the plugin runtime: {err:#}");
+            return Vec::new();
+        }
+    };
+    let mut tools: Vec<PluginTool> = Vec::new();
+    for path in paths {
+        let loaded = Module::from_file(&engine, &path).and_then(|module| {
+            let plugin = Plugin {
+                path: path.clone(),
+                engine: engine.clone(),
+                module,
+            };
+            let specs = plugin.tools()?;
+            Ok((Arc::new(plugin), specs))
+        });
+        let (plugin, specs) = match loaded {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                warn!("skipping plugin {}: {err:#}", path.display());
+                continue;
+            }
+        };
+        for spec in specs {
+            if !is_valid_tool_name(&spec.name) {
+                warn!(
+                    "skipping tool {:?} of plugin {}: names are at most 64 letters, digits, `_`, and `-`",
+                    spec.name,
+                    path.display()
+                );
+            } else if tools.iter().any(|tool| tool.spec.name == spec.name) {
+                warn!(
+                    "skipping tool {:?} of plugin {}: another plugin provides it",
+                    spec.name,
+                    path.display()
+                );
+            } else {
+                tools.push(PluginTool {
+                    spec,
+                    plugin: Arc::clone(&plugin),
+                });
+            }
+        }
+    }
+    tools
+}
+
+fn is_valid_tool_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_TOOL_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+impl Plugin {
+    fn tools(&self) -> anyhow::Result<Vec<PluginToolSpec>> {
+        let mut running = self.instantiate()?;
+        let version = running
+            .func::<(), i32>("codex_plugin_abi")?
+            .call(&mut running.store, ())?;
+        if version != ABI_VERSION {
+            bail!("the plugin implements version {version} of the interface, not {ABI_VERSION}");
+        }
+        let tools = running
+            .func::<(), i64>("codex_tools")?
+            .call(&mut running.store, ())?;
+        let tools = running.read(tools)?;
+        serde_json::from_str(&tools).context("codex_tools did not return a list of tools")
+    }
+
+    fn call(&self, name: &str, arguments: &str) -> anyhow::Result<PluginOutput> {
+        let mut running = self.instantiate()?;
+        let name_ptr = running.write(name.as_bytes())?;
+        let args_ptr = running.write(arguments.as_bytes())?;
+        let output = running
+            .func::<(i32, i32, i32, i32), i64>("codex_call")?
+            .call(
+                &mut running.store,
+                (
+                    name_ptr,
+                    i32::try_from(name.len())?,
+                    args_ptr,
+                    i32::try_from(arguments.len())?,
+                ),
+            )?;
+        let output = running.read(output)?;
+        serde_json::from_str(&output).context("codex_call did not return an output object")
+    }
+
+    fn instantiate(&self) -> anyhow::Result<Running> {
+        let mut store = Store::new(
+            &self.engine,
+            State {
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(MAX_MEMORY_BYTES)
+                    .build(),
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(FUEL_PER_CALL)?;
+        // No imports: a plugin that needs any is rejected here.
+        let instance = Instance::new(&mut store, &self.module, &[])?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .context("the plugin does not export its memory")?;
+        Ok(Running {
+            store,
+            instance,
+            memory,
+        })
+    }
+}
+
+impl Running {
+    fn func<Params, Results>(&mut self, name: &str) -> anyhow::Result<TypedFunc<Params, Results>>
+    where
+        Params: WasmParams,
+        Results: WasmResults,
+    {
+        self.instance
+            .get_typed_func::<Params, Results>(&mut self.store, name)
+            .with_context(|| format!("the plugin does not export `{name}`"))
+    }
+
+    /// Copies `bytes` into memory the plugin allocated for them.
+    fn write(&mut self, bytes: &[u8]) -> anyhow::Result<i32> {
+        let ptr = self
+            .func::<i32, i32>("codex_alloc")?
+            .call(&mut self.store, i32::try_from(bytes.len())?)?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, bytes)
+            .context("codex_alloc returned memory out of bounds")?;
+        Ok(ptr)
+    }
+
+    fn read(&self, packed: i64) -> anyhow::Result<String> {
+        let packed = packed as u64;
+        let ptr = (packed >> 32) as usize;
+        let len = (packed & 0xffff_ffff) as usize;
+        if len > MAX_RESULT_BYTES {
+            bail!("the plugin returned {len} bytes, more than the {MAX_RESULT_BYTES} allowed");
+        }
+        let mut bytes = vec![0; len];
+        self.memory
+            .read(&self.store, ptr, &mut bytes)
+            .context("the plugin returned memory out of bounds")?;
+        String::from_utf8(bytes).context("the plugin returned invalid UTF-8")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Declares an `echo` tool whose output is its arguments.
+    const ECHO_PLUGIN: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (data (i32.const 0) "[{\"name\":\"echo\",\"description\":\"Echoes its arguments.\"}]")
+          (func (export "codex_plugin_abi") (result i32) (i32.const 1))
+          (func (export "codex_alloc") (param i32) (result i32) (i32.const 1024))
+          (func (export "codex_tools") (result i64) (i64.const 55))
+          (func (export "codex_call") (param i32 i32 i32 i32) (result i64)
+            (i64.or
+              (i64.shl (i64.extend_i32_u (local.get 2)) (i64.const 32))
+              (i64.extend_i32_u (local.get 3)))))
+    "#;
+
+    /// Asks for access to the host, which plugins do not get.
+    const IMPORTING_PLUGIN: &str = r#"
+        (module
+          (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32)))
+          (memory (export "memory") 1))
+    "#;
+
+    #[test]
+    fn plugins_declare_tools_and_run_in_isolation() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        // Modules in the text format load like binary ones.
+        std::fs::write(dir.path().join("echo.wasm"), ECHO_PLUGIN).expect("write echo plugin");
+        std::fs::write(dir.path().join("host.wasm"), IMPORTING_PLUGIN).expect("write host plugin");
+        std::fs::write(dir.path().join("README.md"), "not a plugin").expect("write readme");
+
+        let tools = load_plugins(dir.path());
+        let specs: Vec<PluginToolSpec> = tools.iter().map(|tool| tool.spec.clone()).collect();
+        assert_eq!(
+            specs,
+            vec![PluginToolSpec {
+                name: "echo".to_string(),
+                description: "Echoes its arguments.".to_string(),
+                parameters: None,
+            }]
+        );
+        assert_eq!(
+            tools[0].call(r#"{"output":"pong","success":false}"#),
+            Ok(PluginOutput {
+                output: "pong".to_string(),
+                success: false,
+            })
+        );
+        assert!(
+            tools[0]
+                .call("not json")
+                .is_err_and(|err| err.contains("codex_call did not return an output object")),
+        );
+    }
+}
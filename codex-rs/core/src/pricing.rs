@@ -0,0 +1,179 @@
+//! Estimated cost of model usage.
+//!
+//! Prices come from `model_prices` in `config.toml` when the model is listed
+//! there, else from the built-in table below, which follows OpenAI's
+//! published API prices and goes out of date as they change. Usage on a
+//! ChatGPT plan is priced the same way, as the API-equivalent cost. Models
+//! with no known price are counted separately instead of being guessed.
+
+use std::collections::HashMap;
+
+use crate::config::types::ModelPrice;
+use crate::protocol::TokenCost;
+use crate::protocol::TokenUsage;
+
+const fn price(input: f64, cached_input: f64, output: f64) -> ModelPrice {
+    ModelPrice {
+        input,
+        cached_input: Some(cached_input),
+        output,
+    }
+}
+
+/// Built-in price of `model`, matched on the model slug.
+fn built_in_price(model: &str) -> Option<ModelPrice> {
+    let found = match model {
+        _ if model.starts_with("gpt-5.1-codex-mini") || model.starts_with("gpt-5-codex-mini") => {
+            price(0.25, 0.025, 2.0)
+        }
+        _ if model.starts_with("gpt-5-mini") => price(0.25, 0.025, 2.0),
+        _ if model.starts_with("gpt-5-nano") => price(0.05, 0.005, 0.4),
+        _ if model.starts_with("gpt-5") => price(1.25, 0.125, 10.0),
+        _ if model.starts_with("codex-mini") => price(1.5, 0.375, 6.0),
+        _ if model.starts_with("gpt-4.1-mini") => price(0.4, 0.1, 1.6),
+        _ if model.starts_with("gpt-4.1-nano") => price(0.1, 0.025, 0.4),
+        _ if model.starts_with("gpt-4.1") => price(2.0, 0.5, 8.0),
+        _ if model.starts_with("gpt-4o-mini") => price(0.15, 0.075, 0.6),
+        _ if model.starts_with("gpt-4o") => price(2.5, 1.25, 10.0),
+        _ if model.starts_with("o4-mini") => price(1.1, 0.275, 4.4),
+        _ if model.starts_with("o3-mini") => price(1.1, 0.55, 4.4),
+        _ if model.starts_with("o3") => price(2.0, 0.5, 8.0),
+        _ => return None,
+    };
+    Some(found)
+}
+
+/// Price of `model`: its entry in `overrides` (`model_prices` from
+/// `config.toml`), else the built-in price.
+pub(crate) fn model_price(
+    model: &str,
+    overrides: &HashMap<String, ModelPrice>,
+) -> Option<ModelPrice> {
+    overrides
+        .get(model)
+        .copied()
+        .or_else(|| built_in_price(model))
+}
+
+/// Cost of `usage` at `price`, in US dollars.
+pub(crate) fn usage_cost_usd(usage: &TokenUsage, price: &ModelPrice) -> f64 {
+    let cached_input = price.cached_input.unwrap_or(price.input);
+    (usage.non_cached_input() as f64 * price.input
+        + usage.cached_input() as f64 * cached_input
+        + usage.output_tokens.max(0) as f64 * price.output)
+        / 1_000_000.0
+}
+
+/// Running cost of a session and of its current turn.
+#[derive(Debug, Default)]
+pub(crate) struct CostTracker {
+    /// Submission id of the turn `cost.turn_usd` belongs to.
+    turn_id: String,
+    cost: TokenCost,
+}
+
+impl CostTracker {
+    /// Continues from the totals recorded in a resumed session's rollout.
+    pub(crate) fn resume_from(&mut self, recorded: &TokenCost) {
+        self.cost.session_usd = recorded.session_usd;
+        self.cost.unpriced_tokens = recorded.unpriced_tokens;
+    }
+
+    /// Adds the usage of one model request made during turn `turn_id` and
+    /// returns its cost, or `None` when the model has no price.
+    pub(crate) fn record(
+        &mut self,
+        turn_id: &str,
+        usage: &TokenUsage,
+        price: Option<ModelPrice>,
+    ) -> Option<f64> {
+        if self.turn_id != turn_id {
+            self.turn_id = turn_id.to_string();
+            self.cost.turn_usd = 0.0;
+        }
+        let Some(price) = price else {
+            self.cost.unpriced_tokens += usage.total_tokens.max(0);
+            return None;
+        };
+        let cost = usage_cost_usd(usage, &price);
+        self.cost.turn_usd += cost;
+        self.cost.session_usd += cost;
+        Some(cost)
+    }
+
+    pub(crate) fn session_usd(&self) -> f64 {
+        self.cost.session_usd
+    }
+
+    pub(crate) fn cost(&self) -> TokenCost {
+        self.cost.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn usage(input: i64, cached: i64, output: i64) -> TokenUsage {
+        TokenUsage {
+            input_tokens: input,
+            cached_input_tokens: cached,
+            output_tokens: output,
+            reasoning_output_tokens: 0,
+            total_tokens: input + output,
+        }
+    }
+
+    #[test]
+    fn overrides_take_precedence_over_built_in_prices() {
+        let mut overrides = HashMap::new();
+        assert_eq!(
+            model_price("gpt-5.1-codex", &overrides),
+            Some(price(1.25, 0.125, 10.0))
+        );
+        assert_eq!(
+            model_price("gpt-5.1-codex-mini", &overrides),
+            Some(price(0.25, 0.025, 2.0))
+        );
+        assert_eq!(model_price("llama3.2", &overrides), None);
+
+        let custom = ModelPrice {
+            input: 0.5,
+            cached_input: None,
+            output: 1.0,
+        };
+        overrides.insert("llama3.2".to_string(), custom);
+        assert_eq!(model_price("llama3.2", &overrides), Some(custom));
+    }
+
+    #[test]
+    fn cached_input_is_priced_separately() {
+        // 600k uncached input, 400k cached input, 100k output.
+        let cost = usage_cost_usd(&usage(1_000_000, 400_000, 100_000), &price(1.0, 0.1, 10.0));
+        assert!((cost - (0.6 + 0.04 + 1.0)).abs() < 1e-9, "cost was {cost}");
+    }
+
+    #[test]
+    fn turn_cost_restarts_with_each_turn() {
+        let price = Some(price(1.0, 1.0, 1.0));
+        let mut tracker = CostTracker::default();
+        tracker.resume_from(&TokenCost {
+            turn_usd: 9.0,
+            session_usd: 2.0,
+            unpriced_tokens: 5,
+        });
+        tracker.record("1", &usage(1_000_000, 0, 0), price);
+        tracker.record("1", &usage(1_000_000, 0, 0), price);
+        tracker.record("2", &usage(500_000, 0, 0), price);
+        tracker.record("2", &usage(10, 0, 0), None);
+        assert_eq!(
+            tracker.cost(),
+            TokenCost {
+                turn_usd: 0.5,
+                session_usd: 4.5,
+                unpriced_tokens: 15,
+            }
+        );
+    }
+}
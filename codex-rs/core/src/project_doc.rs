@@ -18,6 +18,7 @@ use crate::features::Feature;
 use crate::skills::load_skills;
 use crate::skills::render_skills_section;
 use dunce::canonicalize as normalize_path;
+use std::path::Path;
 use std::path::PathBuf;
 use tokio::io::AsyncReadExt;
 use tracing::error;
@@ -84,6 +85,9 @@ pub(crate) async fn get_user_instructions(config: &Config) -> Option<String> {
 /// concatenation of all discovered docs. If no documentation file is found the
 /// function returns `Ok(None)`. Unexpected I/O failures bubble up as `Err` so
 /// callers can decide how to handle them.
+///
+/// When the session has additional roots, they are listed after the docs,
+/// followed by each root's own docs.
 pub async fn read_project_docs(config: &Config) -> std::io::Result<Option<String>> {
     let max_total = config.project_doc_max_bytes;
 
@@ -91,16 +95,45 @@ pub async fn read_project_docs(config: &Config) -> std::io::Result<Option<String
         return Ok(None);
     }
 
-    let paths = discover_project_doc_paths(config)?;
-    if paths.is_empty() {
-        return Ok(None);
+    let mut remaining: u64 = max_total as u64;
+    let mut parts = read_docs(discover_project_doc_paths(config)?, &mut remaining).await?;
+
+    if !config.additional_roots.is_empty() {
+        let roots: Vec<String> = config
+            .additional_roots
+            .iter()
+            .map(|root| format!("- {}", root.display()))
+            .collect();
+        parts.push(format!(
+            "This session spans more than one directory. Besides the working directory, you can read and change files under:\n{}\nEach root has its own git repository, so run git commands from the root a file belongs to.",
+            roots.join("\n")
+        ));
+    }
+    for root in &config.additional_roots {
+        let docs = read_docs(discover_project_doc_paths_in(config, root)?, &mut remaining).await?;
+        if !docs.is_empty() {
+            parts.push(format!(
+                "Instructions for files under {}:\n\n{}",
+                root.display(),
+                docs.join("\n\n")
+            ));
+        }
     }
 
-    let mut remaining: u64 = max_total as u64;
+    if parts.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(parts.join("\n\n")))
+    }
+}
+
+/// Reads the non-empty docs at `paths`, truncating them to fit the
+/// `remaining` byte budget and deducting what they use.
+async fn read_docs(paths: Vec<PathBuf>, remaining: &mut u64) -> std::io::Result<Vec<String>> {
     let mut parts: Vec<String> = Vec::new();
 
     for p in paths {
-        if remaining == 0 {
+        if *remaining == 0 {
             break;
         }
 
@@ -111,11 +144,11 @@ pub async fn read_project_docs(config: &Config) -> std::io::Result<Option<String
         };
 
         let size = file.metadata().await?.len();
-        let mut reader = tokio::io::BufReader::new(file).take(remaining);
+        let mut reader = tokio::io::BufReader::new(file).take(*remaining);
         let mut data: Vec<u8> = Vec::new();
         reader.read_to_end(&mut data).await?;
 
-        if size > remaining {
+        if size > *remaining {
             tracing::warn!(
                 "Project doc `{}` exceeds remaining budget ({} bytes) - truncating.",
                 p.display(),
@@ -126,15 +159,11 @@ pub async fn read_project_docs(config: &Config) -> std::io::Result<Option<String
         let text = String::from_utf8_lossy(&data).to_string();
         if !text.trim().is_empty() {
             parts.push(text);
-            remaining = remaining.saturating_sub(data.len() as u64);
+            *remaining = remaining.saturating_sub(data.len() as u64);
         }
     }
 
-    if parts.is_empty() {
-        Ok(None)
-    } else {
-        Ok(Some(parts.join("\n\n")))
-    }
+    Ok(parts)
 }
 
 /// Discover the list of AGENTS.md files using the same search rules as
@@ -143,7 +172,39 @@ pub async fn read_project_docs(config: &Config) -> std::io::Result<Option<String
 /// directory (inclusive). Symlinks are allowed. When `project_doc_max_bytes`
 /// is zero, returns an empty list.
 pub fn discover_project_doc_paths(config: &Config) -> std::io::Result<Vec<PathBuf>> {
-    let mut dir = config.cwd.clone();
+    discover_project_doc_paths_in(config, &config.cwd)
+}
+
+/// Every file the user instructions are read from: the global doc in
+/// `codex_home`, then the project docs of the working directory and of each
+/// additional root.
+pub(crate) fn instruction_file_paths(config: &Config) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    if config.user_instructions.is_some()
+        && let Some(global) = [LOCAL_PROJECT_DOC_FILENAME, DEFAULT_PROJECT_DOC_FILENAME]
+            .iter()
+            .map(|name| config.codex_home.join(name))
+            .find(|path| {
+                std::fs::read_to_string(path).is_ok_and(|contents| !contents.trim().is_empty())
+            })
+    {
+        paths.push(global);
+    }
+    if config.project_doc_max_bytes > 0 {
+        for start in std::iter::once(&config.cwd).chain(&config.additional_roots) {
+            match discover_project_doc_paths_in(config, start) {
+                Ok(found) => paths.extend(found),
+                Err(e) => error!("error trying to find project doc: {e:#}"),
+            }
+        }
+    }
+    paths
+}
+
+/// Like [`discover_project_doc_paths`], but from `start` instead of the
+/// working directory.
+fn discover_project_doc_paths_in(config: &Config, start: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut dir = start.to_path_buf();
     if let Ok(canon) = normalize_path(&dir) {
         dir = canon;
     }
@@ -184,7 +245,7 @@ pub fn discover_project_doc_paths(config: &Config) -> std::io::Result<Vec<PathBu
         }
         dirs
     } else {
-        vec![config.cwd.clone()]
+        vec![start.to_path_buf()]
     };
 
     let mut found: Vec<PathBuf> = Vec::new();
@@ -313,6 +374,57 @@ mod tests {
         );
     }
 
+    /// The global doc comes first, then the docs of each root; project docs
+    /// are left out when they are disabled.
+    #[test]
+    fn instruction_file_paths_list_the_global_doc_and_each_root() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let home = tempfile::tempdir().expect("tempdir");
+        let extra = tempfile::tempdir().expect("tempdir");
+        fs::write(home.path().join("AGENTS.md"), "global").unwrap();
+        fs::write(tmp.path().join("AGENTS.md"), "project").unwrap();
+        fs::write(extra.path().join("AGENTS.md"), "extra").unwrap();
+
+        let mut config = make_config(&tmp, 4096, Some("global"));
+        config.codex_home = home.path().to_path_buf();
+        config.additional_roots = vec![extra.path().to_path_buf()];
+        let doc = |dir: &TempDir| normalize_path(dir.path()).unwrap().join("AGENTS.md");
+        assert_eq!(
+            instruction_file_paths(&config),
+            vec![home.path().join("AGENTS.md"), doc(&tmp), doc(&extra)]
+        );
+
+        config.project_doc_max_bytes = 0;
+        assert_eq!(
+            instruction_file_paths(&config),
+            vec![home.path().join("AGENTS.md")]
+        );
+    }
+
+    /// Each additional root is listed, followed by its own AGENTS.md.
+    #[tokio::test]
+    async fn additional_roots_add_their_docs() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let shared = tempfile::tempdir().expect("tempdir");
+        let docs = tempfile::tempdir().expect("tempdir");
+        fs::write(tmp.path().join("AGENTS.md"), "frontend rules").unwrap();
+        fs::write(shared.path().join("AGENTS.md"), "shared rules").unwrap();
+
+        let mut config = make_config(&tmp, 4096, None);
+        config.additional_roots = vec![shared.path().to_path_buf(), docs.path().to_path_buf()];
+        let res = get_user_instructions(&config).await.expect("doc expected");
+
+        assert_eq!(
+            res,
+            format!(
+                "frontend rules\n\nThis session spans more than one directory. Besides the working directory, you can read and change files under:\n- {}\n- {}\nEach root has its own git repository, so run git commands from the root a file belongs to.\n\nInstructions for files under {}:\n\nshared rules",
+                shared.path().display(),
+                docs.path().display(),
+                shared.path().display()
+            )
+        );
+    }
+
     /// Oversize file is truncated to `project_doc_max_bytes`.
     #[tokio::test]
     async fn doc_larger_than_limit_is_truncated() {
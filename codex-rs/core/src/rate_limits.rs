@@ -0,0 +1,96 @@
+//! Helpers for describing ChatGPT usage windows, shared by the usage-limit
+//! error, `codex limits`, and the TUI.
+
+use chrono::Utc;
+use codex_protocol::protocol::RateLimitSnapshot;
+use codex_protocol::protocol::RateLimitWindow;
+
+/// The exhausted window that resets last. When both the 5-hour and the weekly
+/// window are used up, the weekly one decides when usage becomes available
+/// again. A window without a reset time never wins over one with a reset time.
+pub fn binding_window(snapshot: &RateLimitSnapshot) -> Option<&RateLimitWindow> {
+    [snapshot.primary.as_ref(), snapshot.secondary.as_ref()]
+        .into_iter()
+        .flatten()
+        .filter(|window| window.used_percent >= 100.0)
+        .max_by_key(|window| window.resets_at.unwrap_or(i64::MIN))
+}
+
+/// "weekly", "daily", or "5-hour"; `None` when the length is unknown or does
+/// not fall on a whole hour.
+pub fn window_label(window: &RateLimitWindow) -> Option<String> {
+    const MINUTES_PER_DAY: i64 = 24 * 60;
+    match window.window_minutes? {
+        minutes if minutes == 7 * MINUTES_PER_DAY => Some("weekly".to_string()),
+        MINUTES_PER_DAY => Some("daily".to_string()),
+        minutes if minutes > 0 && minutes % 60 == 0 => Some(format!("{}-hour", minutes / 60)),
+        _ => None,
+    }
+}
+
+/// "2d 4h", "3h 12m", "45m", or "<1m" for anything under a minute (including the past).
+pub fn format_countdown(seconds: i64) -> String {
+    let minutes = seconds.max(0) / 60;
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m")
+    } else {
+        "<1m".to_string()
+    }
+}
+
+/// Current time in Unix seconds, the unit `resets_at` is reported in.
+pub fn unix_now() -> i64 {
+    Utc::now().timestamp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn window(used_percent: f64, window_minutes: i64, resets_at: Option<i64>) -> RateLimitWindow {
+        RateLimitWindow {
+            used_percent,
+            window_minutes: Some(window_minutes),
+            resets_at,
+        }
+    }
+
+    #[test]
+    fn binding_window_prefers_the_later_reset() {
+        let snapshot = RateLimitSnapshot {
+            primary: Some(window(100.0, 300, Some(1_000))),
+            secondary: Some(window(100.0, 7 * 24 * 60, Some(5_000))),
+            credits: None,
+            request_limits: None,
+        };
+        assert_eq!(
+            binding_window(&snapshot).and_then(window_label).as_deref(),
+            Some("weekly")
+        );
+
+        let snapshot = RateLimitSnapshot {
+            primary: Some(window(100.0, 300, Some(1_000))),
+            secondary: Some(window(100.0, 7 * 24 * 60, None)),
+            credits: None,
+            request_limits: None,
+        };
+        assert_eq!(
+            binding_window(&snapshot).and_then(window_label).as_deref(),
+            Some("5-hour")
+        );
+    }
+
+    #[test]
+    fn countdown_uses_two_largest_units() {
+        assert_eq!(format_countdown(2 * 86_400 + 4 * 3600 + 59), "2d 4h");
+        assert_eq!(format_countdown(3 * 3600 + 12 * 60), "3h 12m");
+        assert_eq!(format_countdown(45 * 60), "45m");
+        assert_eq!(format_countdown(-5), "<1m");
+    }
+}
@@ -0,0 +1,562 @@
+//! A map of the workspace sent ahead of the conversation history when the
+//! `repo_map` feature is enabled: the files that are not ignored, laid out as
+//! a tree, each with an outline of the functions, types, and exports it
+//! defines, so the model can find code instead of guessing paths. Outlines
+//! are parsed with tree-sitter and cached by modification time, so each turn
+//! only parses the files that changed since the last one.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use ignore::WalkBuilder;
+use tree_sitter::Language;
+use tree_sitter::Node;
+use tree_sitter::Parser;
+
+use crate::codex_ignore::CODEX_IGNORE_FILENAME;
+use crate::truncate::approx_token_count;
+
+pub(crate) const REPO_MAP_OPEN_TAG: &str = "<repo_map>";
+const REPO_MAP_CLOSE_TAG: &str = "</repo_map>";
+/// The map is cut down to fit: outlines are dropped first, then files.
+const MAX_REPO_MAP_TOKENS: usize = 8_000;
+/// Stop walking after this many files so huge trees still map quickly.
+const MAX_FILES: usize = 5_000;
+/// Larger files are listed without an outline.
+const MAX_OUTLINE_BYTES: u64 = 512 * 1024;
+/// Directories skipped even when no ignore file mentions them.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "dist", "build", "__pycache__"];
+
+/// The outlines of the files mapped so far, kept for the whole session.
+#[derive(Default)]
+pub(crate) struct RepoMap {
+    files: HashMap<PathBuf, Outline>,
+}
+
+struct Outline {
+    modified: Option<SystemTime>,
+    len: u64,
+    symbols: Vec<String>,
+}
+
+/// One line of the rendered tree.
+struct Line {
+    text: String,
+    outline: String,
+    is_file: bool,
+}
+
+impl RepoMap {
+    /// Maps `cwd`, parsing only the files that are new or changed since the
+    /// last call. Returns `None` when there is nothing to map.
+    pub(crate) fn render(&mut self, cwd: &Path) -> Option<ResponseItem> {
+        let (paths, complete) = list_files(cwd);
+        if paths.is_empty() {
+            return None;
+        }
+        let mut files = HashMap::with_capacity(paths.len());
+        let mut lines = Vec::new();
+        let mut shown_dirs: Vec<&std::ffi::OsStr> = Vec::new();
+        for path in &paths {
+            let Ok(relative) = path.strip_prefix(cwd) else {
+                continue;
+            };
+            let outline = self.outline(path);
+            let dirs: Vec<_> = relative.parent().into_iter().flat_map(Path::iter).collect();
+            let common = shown_dirs
+                .iter()
+                .zip(&dirs)
+                .take_while(|(shown, dir)| shown == dir)
+                .count();
+            for (depth, dir) in dirs.iter().enumerate().skip(common) {
+                lines.push(Line {
+                    text: format!("{}{}/", "  ".repeat(depth), dir.to_string_lossy()),
+                    outline: String::new(),
+                    is_file: false,
+                });
+            }
+            lines.push(Line {
+                text: format!(
+                    "{}{}",
+                    "  ".repeat(dirs.len()),
+                    relative.file_name().unwrap_or_default().to_string_lossy()
+                ),
+                outline: if outline.symbols.is_empty() {
+                    String::new()
+                } else {
+                    format!(": {}", outline.symbols.join(", "))
+                },
+                is_file: true,
+            });
+            shown_dirs = dirs;
+            files.insert(path.clone(), outline);
+        }
+        // Files that were deleted or are now ignored fall out of the cache.
+        self.files = files;
+
+        let text = fit(&lines, complete);
+        Some(ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: format!(
+                    "{REPO_MAP_OPEN_TAG}\nThe files in {}, with the symbols each defines. The map is rebuilt before every turn, so it is current.\n{text}{REPO_MAP_CLOSE_TAG}",
+                    cwd.display()
+                ),
+            }],
+        })
+    }
+
+    /// The cached outline of `path` if the file has not changed, else a
+    /// freshly parsed one.
+    fn outline(&mut self, path: &Path) -> Outline {
+        let metadata = std::fs::metadata(path).ok();
+        let modified = metadata
+            .as_ref()
+            .and_then(|metadata| metadata.modified().ok());
+        let len = metadata.as_ref().map_or(0, std::fs::Metadata::len);
+        if let Some(cached) = self.files.remove(path)
+            && cached.modified.is_some()
+            && cached.modified == modified
+            && cached.len == len
+        {
+            return cached;
+        }
+        let symbols = match SourceLanguage::for_path(path) {
+            Some(language) if len <= MAX_OUTLINE_BYTES => std::fs::read_to_string(path)
+                .map(|source| outline_source(language, &source))
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        Outline {
+            modified,
+            len,
+            symbols,
+        }
+    }
+}
+
+/// The files under `cwd` that are not hidden, ignored, or excluded by a
+/// `.codexignore`, in tree order, and
+/// whether that is all of them.
+pub(crate) fn list_files(cwd: &Path) -> (Vec<PathBuf>, bool) {
+    let walk = WalkBuilder::new(cwd)
+        // Apply .gitignore even outside a git repository.
+        .require_git(false)
+        .add_custom_ignore_filename(CODEX_IGNORE_FILENAME)
+        .sort_by_file_name(std::cmp::Ord::cmp)
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || !entry.file_type().is_some_and(|kind| kind.is_dir())
+                || !SKIPPED_DIRS.iter().any(|dir| entry.file_name() == *dir)
+        })
+        .build();
+    let mut files = Vec::new();
+    for entry in walk.filter_map(Result::ok) {
+        if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+            continue;
+        }
+        if files.len() == MAX_FILES {
+            return (files, false);
+        }
+        files.push(entry.into_path());
+    }
+    (files, true)
+}
+
+/// Renders `lines` within the token budget: with outlines if they fit, else
+/// as a bare tree, cut short if even that is too long.
+fn fit(lines: &[Line], complete: bool) -> String {
+    let with_outlines: String = lines
+        .iter()
+        .map(|line| format!("{}{}\n", line.text, line.outline))
+        .collect();
+    if approx_token_count(&with_outlines) <= MAX_REPO_MAP_TOKENS {
+        return with_more_files_note(with_outlines, complete);
+    }
+    let mut text = String::new();
+    for (index, line) in lines.iter().enumerate() {
+        if approx_token_count(&text) + approx_token_count(&line.text) >= MAX_REPO_MAP_TOKENS {
+            let omitted = lines[index..].iter().filter(|line| line.is_file).count();
+            text.push_str(&format!("({omitted} more files not shown)\n"));
+            return text;
+        }
+        text.push_str(&line.text);
+        text.push('\n');
+    }
+    with_more_files_note(text, complete)
+}
+
+fn with_more_files_note(mut text: String, complete: bool) -> String {
+    if !complete {
+        text.push_str(&format!("(only the first {MAX_FILES} files are shown)\n"));
+    }
+    text
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SourceLanguage {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Tsx,
+    Go,
+}
+
+impl SourceLanguage {
+    fn for_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "rs" => Some(Self::Rust),
+            "py" | "pyi" => Some(Self::Python),
+            "js" | "jsx" | "mjs" | "cjs" => Some(Self::JavaScript),
+            "ts" | "mts" | "cts" => Some(Self::TypeScript),
+            "tsx" => Some(Self::Tsx),
+            "go" => Some(Self::Go),
+            _ => None,
+        }
+    }
+
+    fn grammar(self) -> Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+            Self::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Self::Go => tree_sitter_go::LANGUAGE.into(),
+        }
+    }
+}
+
+/// The functions, types, and exports `source` defines at its top level,
+/// along with the methods of its types, such as `pub fn Config::load`.
+fn outline_source(language: SourceLanguage, source: &str) -> Vec<String> {
+    let mut parser = Parser::new();
+    if parser.set_language(&language.grammar()).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(source, None) else {
+        return Vec::new();
+    };
+    let mut symbols = Vec::new();
+    let outliner = Outliner { language, source };
+    outliner.collect(tree.root_node(), None, &mut symbols);
+    symbols
+}
+
+struct Outliner<'a> {
+    language: SourceLanguage,
+    source: &'a str,
+}
+
+impl Outliner<'_> {
+    fn text(&self, node: Node) -> &str {
+        node.utf8_text(self.source.as_bytes()).unwrap_or_default()
+    }
+
+    fn field_text(&self, node: Node, field: &str) -> Option<&str> {
+        node.child_by_field_name(field)
+            .map(|child| self.text(child))
+    }
+
+    /// `kind name`, or `kind Scope::name` for a member of `scope`.
+    fn symbol(&self, kind: &str, name: &str, scope: Option<&str>) -> String {
+        let separator = if self.language == SourceLanguage::Rust {
+            "::"
+        } else {
+            "."
+        };
+        match scope {
+            Some(scope) => format!("{kind} {scope}{separator}{name}"),
+            None => format!("{kind} {name}"),
+        }
+    }
+
+    fn collect(&self, node: Node, scope: Option<&str>, out: &mut Vec<String>) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match self.language {
+                SourceLanguage::Rust => self.rust(child, scope, out),
+                SourceLanguage::Python => self.python(child, scope, out),
+                SourceLanguage::JavaScript | SourceLanguage::TypeScript | SourceLanguage::Tsx => {
+                    self.javascript(child, scope, "", out)
+                }
+                SourceLanguage::Go => self.go(child, out),
+            }
+        }
+    }
+
+    fn rust(&self, node: Node, scope: Option<&str>, out: &mut Vec<String>) {
+        let kind = match node.kind() {
+            "function_item" | "function_signature_item" => "fn",
+            "struct_item" => "struct",
+            "enum_item" => "enum",
+            "union_item" => "union",
+            "trait_item" => "trait",
+            "type_item" => "type",
+            "mod_item" => "mod",
+            "macro_definition" => "macro_rules!",
+            "impl_item" => {
+                // Only inherent methods: a trait's methods are outlined
+                // where the trait is.
+                if node.child_by_field_name("trait").is_none()
+                    && let (Some(ty), Some(body)) = (
+                        self.field_text(node, "type"),
+                        node.child_by_field_name("body"),
+                    )
+                {
+                    self.collect(body, Some(ty), out);
+                }
+                return;
+            }
+            _ => return,
+        };
+        let Some(name) = self.field_text(node, "name") else {
+            return;
+        };
+        let mut cursor = node.walk();
+        let is_pub = node
+            .named_children(&mut cursor)
+            .any(|child| child.kind() == "visibility_modifier");
+        let kind = if is_pub {
+            format!("pub {kind}")
+        } else {
+            kind.to_string()
+        };
+        out.push(self.symbol(&kind, name, scope));
+        if node.kind() == "trait_item"
+            && let Some(body) = node.child_by_field_name("body")
+        {
+            self.collect(body, Some(name), out);
+        }
+    }
+
+    fn python(&self, node: Node, scope: Option<&str>, out: &mut Vec<String>) {
+        match node.kind() {
+            "decorated_definition" => {
+                if let Some(definition) = node.child_by_field_name("definition") {
+                    self.python(definition, scope, out);
+                }
+            }
+            "function_definition" => {
+                if let Some(name) = self.field_text(node, "name") {
+                    out.push(self.symbol("def", name, scope));
+                }
+            }
+            "class_definition" => {
+                let Some(name) = self.field_text(node, "name") else {
+                    return;
+                };
+                out.push(self.symbol("class", name, scope));
+                // Nested classes are not outlined.
+                if scope.is_none()
+                    && let Some(body) = node.child_by_field_name("body")
+                {
+                    self.collect(body, Some(name), out);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// JavaScript and TypeScript; `prefix` is `export ` inside an export.
+    fn javascript(&self, node: Node, scope: Option<&str>, prefix: &str, out: &mut Vec<String>) {
+        let kind = match node.kind() {
+            "export_statement" => {
+                if let Some(declaration) = node.child_by_field_name("declaration") {
+                    self.javascript(declaration, scope, "export ", out);
+                    return;
+                }
+                // `export { a, b }`.
+                let mut cursor = node.walk();
+                if let Some(clause) = node
+                    .named_children(&mut cursor)
+                    .find(|child| child.kind() == "export_clause")
+                {
+                    out.push(format!("export {}", self.text(clause)));
+                }
+                return;
+            }
+            "function_declaration" | "generator_function_declaration" => "function",
+            "class_declaration" | "abstract_class_declaration" => "class",
+            "interface_declaration" => "interface",
+            "type_alias_declaration" => "type",
+            "enum_declaration" => "enum",
+            "method_definition" | "method_signature" | "abstract_method_signature" => "method",
+            "lexical_declaration" | "variable_declaration" => {
+                let mut cursor = node.walk();
+                for declarator in node.named_children(&mut cursor) {
+                    let Some(name) = self.field_text(declarator, "name") else {
+                        continue;
+                    };
+                    let is_function =
+                        declarator
+                            .child_by_field_name("value")
+                            .is_some_and(|value| {
+                                matches!(
+                                    value.kind(),
+                                    "arrow_function" | "function_expression" | "function"
+                                )
+                            });
+                    // Unexported constants are only outlined if they hold a
+                    // function.
+                    if !prefix.is_empty() || is_function {
+                        out.push(self.symbol(&format!("{prefix}const"), name, scope));
+                    }
+                }
+                return;
+            }
+            _ => return,
+        };
+        let Some(name) = self.field_text(node, "name") else {
+            return;
+        };
+        if kind == "method" && name == "constructor" {
+            return;
+        }
+        out.push(self.symbol(&format!("{prefix}{kind}"), name, scope));
+        if kind == "class"
+            && scope.is_none()
+            && let Some(body) = node.child_by_field_name("body")
+        {
+            self.collect(body, Some(name), out);
+        }
+    }
+
+    fn go(&self, node: Node, out: &mut Vec<String>) {
+        match node.kind() {
+            "function_declaration" => {
+                if let Some(name) = self.field_text(node, "name") {
+                    out.push(self.symbol("func", name, None));
+                }
+            }
+            "method_declaration" => {
+                let receiver = node
+                    .child_by_field_name("receiver")
+                    .and_then(|receiver| receiver.named_child(0))
+                    .and_then(|parameter| self.field_text(parameter, "type"))
+                    .map(|ty| ty.trim_start_matches('*'))
+                    .map(|ty| ty.split('[').next().unwrap_or(ty));
+                if let Some(name) = self.field_text(node, "name") {
+                    out.push(self.symbol("func", name, receiver));
+                }
+            }
+            "type_declaration" => {
+                let mut cursor = node.walk();
+                for spec in node.named_children(&mut cursor) {
+                    if let Some(name) = self.field_text(spec, "name") {
+                        out.push(self.symbol("type", name, None));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn map_text(map: &mut RepoMap, cwd: &Path) -> String {
+        let Some(ResponseItem::Message { content, .. }) = map.render(cwd) else {
+            panic!("expected a message");
+        };
+        let [ContentItem::InputText { text }] = content.as_slice() else {
+            panic!("expected text");
+        };
+        let body = text
+            .strip_prefix(REPO_MAP_OPEN_TAG)
+            .and_then(|text| text.strip_suffix(REPO_MAP_CLOSE_TAG))
+            .expect("tagged map");
+        body.split_once("current.\n")
+            .expect("preamble")
+            .1
+            .to_string()
+    }
+
+    #[test]
+    fn map_lists_files_with_their_symbols() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let cwd = dir.path();
+        let write = |path: &str, contents: &str| {
+            let path = cwd.join(path);
+            std::fs::create_dir_all(path.parent().expect("parent")).expect("mkdir");
+            std::fs::write(path, contents).expect("write");
+        };
+        write(".gitignore", "generated/\n");
+        write(".codexignore", "secrets/\n");
+        write("secrets/prod.env", "TOKEN=1\n");
+        write("generated/out.js", "function generated() {}\n");
+        write("node_modules/left-pad/index.js", "function pad() {}\n");
+        write(".hidden/notes.py", "def secret(): pass\n");
+        write("README.md", "# Demo\n");
+        write(
+            "src/lib.rs",
+            "pub mod config;\npub struct Config;\nimpl Config {\n    pub fn load() -> Self { Config }\n    fn check(&self) {}\n}\nimpl Default for Config {\n    fn default() -> Self { Config }\n}\npub trait Tool {\n    fn run(&self);\n}\nfn helper() {}\n",
+        );
+        write(
+            "src/cli/app.py",
+            "import os\n\nclass App:\n    @property\n    def name(self):\n        return 'app'\n\ndef main():\n    App()\n",
+        );
+        write(
+            "web/api.ts",
+            "export interface User { id: string }\nexport type Id = string;\nexport class Client {\n  constructor() {}\n  fetchUser(id: Id) {}\n}\nconst helper = () => 1;\nconst LIMIT = 10;\nexport const VERSION = '1';\n",
+        );
+        write(
+            "main.go",
+            "package main\n\ntype Server struct{}\n\nfunc (s *Server) Start() {}\n\nfunc main() {}\n",
+        );
+
+        let mut map = RepoMap::default();
+        assert_eq!(
+            map_text(&mut map, cwd),
+            "\
+README.md
+main.go: type Server, func Server.Start, func main
+src/
+  cli/
+    app.py: class App, def App.name, def main
+  lib.rs: pub mod config, pub struct Config, pub fn Config::load, fn Config::check, pub trait Tool, fn Tool::run, fn helper
+web/
+  api.ts: export interface User, export type Id, export class Client, method Client.fetchUser, const helper, export const VERSION
+"
+        );
+
+        // Only the changed file is parsed again; deleted files drop out.
+        write(
+            "main.go",
+            "package main\n\nfunc main() {}\n\nfunc serve() {}\n",
+        );
+        std::fs::remove_file(cwd.join("web/api.ts")).expect("remove");
+        let text = map_text(&mut map, cwd);
+        assert!(text.contains("main.go: func main, func serve\n"));
+        assert!(!text.contains("api.ts"));
+        assert!(!map.files.contains_key(&cwd.join("web/api.ts")));
+    }
+
+    #[test]
+    fn large_maps_drop_outlines_then_files() {
+        let file = |name: &str, outline: &str| Line {
+            text: name.to_string(),
+            outline: outline.to_string(),
+            is_file: true,
+        };
+        let long_outline = format!(": {}", "fn x, ".repeat(MAX_REPO_MAP_TOKENS));
+        let lines = vec![file("a.rs", &long_outline), file("b.rs", "")];
+        assert_eq!(fit(&lines, true), "a.rs\nb.rs\n");
+
+        let lines: Vec<Line> = (0..MAX_REPO_MAP_TOKENS)
+            .map(|index| file(&format!("file{index}.rs"), ""))
+            .collect();
+        let text = fit(&lines, true);
+        assert!(approx_token_count(&text) <= MAX_REPO_MAP_TOKENS + 10);
+        assert!(text.ends_with(" more files not shown)\n"));
+    }
+}
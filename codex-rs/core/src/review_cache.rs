@@ -0,0 +1,329 @@
+//! Cache of review findings, so re-running a review in CI on an unchanged
+//! PR head returns the stored findings without calling the model.
+//!
+//! Entries are keyed by a hash of the reviewed repository state (the target,
+//! `HEAD`, and the working tree diff including untracked files), a hash of
+//! the settings that shape the review (Codex version, review rubric,
+//! provider, enabled features), and the model. They are stored as one JSON
+//! object per key in a local directory or an S3-compatible bucket.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use codex_protocol::protocol::ReviewOutputEvent;
+use codex_protocol::protocol::ReviewTarget;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use tracing::warn;
+
+use crate::bedrock::credentials::resolve_credentials;
+use crate::bedrock::credentials::resolve_region;
+use crate::bedrock::sigv4;
+use crate::config::Config;
+use crate::config::types::ReviewCacheStorage;
+use crate::config::types::S3ReviewCache;
+use crate::default_client::build_reqwest_client;
+use crate::default_client::check_non_provider_network;
+use crate::features::FEATURES;
+use crate::git_info::diff_against_sha;
+use crate::git_info::resolve_commit;
+
+const CONTENT_TYPE: &str = "application/json";
+const DEFAULT_S3_REGION: &str = "us-east-1";
+
+pub(crate) struct ReviewCache {
+    store: Box<dyn ReviewCacheStore>,
+    key: ReviewCacheKey,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct ReviewCacheKey {
+    diff_hash: String,
+    config_hash: String,
+    model: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct CachedReview {
+    #[serde(flatten)]
+    key: ReviewCacheKey,
+    /// When the review ran (Unix seconds).
+    created_at: i64,
+    review_output: ReviewOutputEvent,
+}
+
+impl ReviewCache {
+    /// The cache entry for reviewing `target` from `cwd`, or `None` when no
+    /// cache is configured or the repository state cannot be read.
+    pub(crate) async fn for_review(
+        config: &Config,
+        cwd: &Path,
+        target: &ReviewTarget,
+        prompt: &str,
+    ) -> Option<Self> {
+        let store: Box<dyn ReviewCacheStore> = match config.review_cache.as_ref()? {
+            ReviewCacheStorage::Dir(dir) => Box::new(DirStore { dir: dir.clone() }),
+            ReviewCacheStorage::S3(s3) => Box::new(S3Store { config: s3.clone() }),
+        };
+        let Some(diff_hash) = repo_state_hash(cwd, target, prompt).await else {
+            warn!("not caching review: could not read the repository state");
+            return None;
+        };
+        Some(Self {
+            store,
+            key: ReviewCacheKey {
+                diff_hash,
+                config_hash: config_hash(config),
+                model: config.model.clone(),
+            },
+        })
+    }
+
+    /// Findings stored for this key, if any.
+    pub(crate) async fn load(&self) -> Option<ReviewOutputEvent> {
+        let bytes = match self.store.get(&self.object_name()).await {
+            Ok(bytes) => bytes?,
+            Err(err) => {
+                warn!("failed to read cached review: {err}");
+                return None;
+            }
+        };
+        serde_json::from_slice::<CachedReview>(&bytes)
+            .ok()
+            .filter(|cached| cached.key == self.key)
+            .map(|cached| cached.review_output)
+    }
+
+    pub(crate) async fn store(&self, review_output: &ReviewOutputEvent) {
+        let cached = CachedReview {
+            key: self.key.clone(),
+            created_at: Utc::now().timestamp(),
+            review_output: review_output.clone(),
+        };
+        let result = match serde_json::to_vec_pretty(&cached) {
+            Ok(bytes) => self.store.put(&self.object_name(), bytes).await,
+            Err(err) => Err(io::Error::other(err)),
+        };
+        if let Err(err) = result {
+            warn!("failed to cache review: {err}");
+        }
+    }
+
+    fn object_name(&self) -> String {
+        let mut hasher = Sha256::new();
+        for part in [&self.key.diff_hash, &self.key.config_hash, &self.key.model] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        format!("{}.json", hex(&hasher.finalize()))
+    }
+}
+
+async fn repo_state_hash(cwd: &Path, target: &ReviewTarget, prompt: &str) -> Option<String> {
+    let head = resolve_commit(cwd, "HEAD").await?;
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(target).ok()?);
+    hasher.update(prompt.as_bytes());
+    hasher.update(head.0.as_bytes());
+    if let ReviewTarget::Commit { sha, .. } = target {
+        hasher.update(resolve_commit(cwd, sha).await?.0.as_bytes());
+    }
+    hasher.update(diff_against_sha(cwd, &head).await?.as_bytes());
+    Some(hex(&hasher.finalize()))
+}
+
+fn config_hash(config: &Config) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.update(crate::REVIEW_PROMPT.as_bytes());
+    hasher.update(config.model_provider_id.as_bytes());
+    if let Some(base_url) = &config.model_provider.base_url {
+        hasher.update(base_url.as_bytes());
+    }
+    for spec in FEATURES {
+        if config.features.enabled(spec.id) {
+            hasher.update(spec.key.as_bytes());
+        }
+    }
+    hex(&hasher.finalize())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[async_trait]
+trait ReviewCacheStore: Send + Sync {
+    /// `Ok(None)` when there is no entry for `name`.
+    async fn get(&self, name: &str) -> io::Result<Option<Vec<u8>>>;
+    async fn put(&self, name: &str, bytes: Vec<u8>) -> io::Result<()>;
+}
+
+struct DirStore {
+    dir: PathBuf,
+}
+
+#[async_trait]
+impl ReviewCacheStore for DirStore {
+    async fn get(&self, name: &str) -> io::Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.dir.join(name)).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn put(&self, name: &str, bytes: Vec<u8>) -> io::Result<()> {
+        let dir = self.dir.clone();
+        let name = name.to_string();
+        tokio::task::spawn_blocking(move || {
+            std::fs::create_dir_all(&dir)?;
+            // Write to a temporary file first so concurrent runs never read a
+            // partial entry.
+            let mut file = tempfile::NamedTempFile::new_in(&dir)?;
+            std::io::Write::write_all(&mut file, &bytes)?;
+            file.persist(dir.join(name)).map_err(|err| err.error)?;
+            Ok(())
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+}
+
+/// Objects are addressed path-style (`{endpoint}/{bucket}/{prefix}{name}`),
+/// which AWS and the common S3-compatible services all accept, and signed
+/// with the standard AWS credential chain.
+struct S3Store {
+    config: S3ReviewCache,
+}
+
+impl S3Store {
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        name: &str,
+        body: Vec<u8>,
+    ) -> io::Result<reqwest::Response> {
+        // Checked before the credentials are resolved, which can reach the
+        // instance metadata service.
+        check_non_provider_network("Review cache")?;
+        let region = self
+            .config
+            .region
+            .clone()
+            .or_else(resolve_region)
+            .unwrap_or_else(|| DEFAULT_S3_REGION.to_string());
+        let endpoint = match &self.config.endpoint {
+            Some(endpoint) => endpoint.trim_end_matches('/').to_string(),
+            None => format!("https://s3.{region}.amazonaws.com"),
+        };
+        let path = format!("{}/{}{name}", self.config.bucket, self.config.prefix)
+            .split('/')
+            .map(sigv4::uri_encode)
+            .collect::<Vec<_>>()
+            .join("/");
+        let url = reqwest::Url::parse(&format!("{endpoint}/{path}")).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid review cache endpoint {endpoint}: {err}"),
+            )
+        })?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => String::new(),
+        };
+
+        let credentials = resolve_credentials().await?;
+        let signed = sigv4::sign_s3(
+            &sigv4::S3SigningRequest {
+                method: method.as_str(),
+                host: &host,
+                path: url.path(),
+                content_type: CONTENT_TYPE,
+                body: &body,
+                region: &region,
+            },
+            &credentials,
+            Utc::now(),
+        );
+        let mut request = build_reqwest_client()?
+            .request(method, url)
+            .header("content-type", CONTENT_TYPE)
+            .header("x-amz-content-sha256", sigv4::payload_hash(&body))
+            .header("x-amz-date", signed.amz_date)
+            .header("authorization", signed.authorization);
+        if let Some(token) = signed.security_token {
+            request = request.header("x-amz-security-token", token);
+        }
+        request.body(body).send().await.map_err(io::Error::other)
+    }
+}
+
+#[async_trait]
+impl ReviewCacheStore for S3Store {
+    async fn get(&self, name: &str) -> io::Result<Option<Vec<u8>>> {
+        let response = self.send(reqwest::Method::GET, name, Vec::new()).await?;
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(io::Error::other(format!("S3 GET returned {status}")));
+        }
+        let bytes = response.bytes().await.map_err(io::Error::other)?;
+        Ok(Some(bytes.to_vec()))
+    }
+
+    async fn put(&self, name: &str, bytes: Vec<u8>) -> io::Result<()> {
+        let response = self.send(reqwest::Method::PUT, name, bytes).await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(io::Error::other(format!("S3 PUT returned {status}")));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn dir_cache(dir: &Path, model: &str) -> ReviewCache {
+        ReviewCache {
+            store: Box::new(DirStore {
+                dir: dir.to_path_buf(),
+            }),
+            key: ReviewCacheKey {
+                diff_hash: "diff".to_string(),
+                config_hash: "config".to_string(),
+                model: model.to_string(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn dir_store_returns_findings_for_the_same_key_only() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let output = ReviewOutputEvent {
+            overall_correctness: "patch is correct".to_string(),
+            overall_explanation: "No issues found.".to_string(),
+            ..Default::default()
+        };
+
+        let cache = dir_cache(dir.path(), "gpt-5.1-codex");
+        assert_eq!(cache.load().await, None);
+        cache.store(&output).await;
+        assert_eq!(cache.load().await, Some(output));
+        assert_eq!(
+            dir_cache(dir.path(), "gpt-5.1-codex-max").load().await,
+            None
+        );
+    }
+}
@@ -26,6 +26,8 @@ use super::policy::is_persisted_response_item;
 use crate::config::Config;
 use crate::default_client::originator;
 use crate::git_info::collect_git_info;
+use crate::redact::redact_secrets;
+use codex_protocol::protocol::ForkedFrom;
 use codex_protocol::protocol::InitialHistory;
 use codex_protocol::protocol::ResumedHistory;
 use codex_protocol::protocol::RolloutItem;
@@ -55,6 +57,7 @@ pub enum RolloutRecorderParams {
         conversation_id: ConversationId,
         instructions: Option<String>,
         source: SessionSource,
+        forked_from: Option<ForkedFrom>,
     },
     Resume {
         path: PathBuf,
@@ -77,11 +80,13 @@ impl RolloutRecorderParams {
         conversation_id: ConversationId,
         instructions: Option<String>,
         source: SessionSource,
+        forked_from: Option<ForkedFrom>,
     ) -> Self {
         Self::Create {
             conversation_id,
             instructions,
             source,
+            forked_from,
         }
     }
 
@@ -120,6 +125,7 @@ impl RolloutRecorder {
                 conversation_id,
                 instructions,
                 source,
+                forked_from,
             } => {
                 let LogFileInfo {
                     file,
@@ -148,6 +154,8 @@ impl RolloutRecorder {
                         instructions,
                         source,
                         model_provider: Some(config.model_provider_id.clone()),
+                        tags: config.tags.clone(),
+                        forked_from,
                     }),
                 )
             }
@@ -312,10 +320,11 @@ fn create_log_file(
     config: &Config,
     conversation_id: ConversationId,
 ) -> std::io::Result<LogFileInfo> {
-    // Resolve ~/.codex/sessions/YYYY/MM/DD and create it if missing.
+    // Resolve ~/.codex/sessions/YYYY/MM/DD (or its read-only fallback) and
+    // create it if missing.
     let timestamp = OffsetDateTime::now_local()
         .map_err(|e| IoError::other(format!("failed to get local time: {e}")))?;
-    let mut dir = config.codex_home.clone();
+    let mut dir = crate::state_dir::state_dir(&config.codex_home);
     dir.push(SESSIONS_SUBDIR);
     dir.push(timestamp.year().to_string());
     dir.push(format!("{:02}", u8::from(timestamp.month())));
@@ -415,7 +424,8 @@ impl JsonlWriter {
         self.write_line(&line).await
     }
     async fn write_line(&mut self, item: &impl serde::Serialize) -> std::io::Result<()> {
-        let mut json = serde_json::to_string(item)?;
+        // Transcripts are exported and shared, so secrets never reach them.
+        let mut json = redact_secrets(&serde_json::to_string(item)?).into_owned();
         json.push('\n');
         self.file.write_all(json.as_bytes()).await?;
         self.file.flush().await?;
@@ -0,0 +1,117 @@
+//! Settings a session was running with, read back from its rollout so
+//! `codex resume` continues in the same directory, with the same model and
+//! sandbox, instead of whatever the resuming shell would start with.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::protocol::AskForApproval;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::SandboxPolicy;
+
+use super::RolloutRecorder;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResumedSessionSettings {
+    pub cwd: PathBuf,
+    /// `None` when the session ended before its first turn; only the working
+    /// directory is recorded before then.
+    pub model: Option<String>,
+    pub approval_policy: Option<AskForApproval>,
+    pub sandbox_policy: Option<SandboxPolicy>,
+}
+
+/// The settings of the last turn recorded in the rollout at `path`, or of
+/// the session start when no turn was recorded.
+pub async fn read_resumed_session_settings(
+    path: &Path,
+) -> io::Result<Option<ResumedSessionSettings>> {
+    let history = RolloutRecorder::get_rollout_history(path).await?;
+    Ok(settings_from_items(&history.get_rollout_items()))
+}
+
+pub(super) fn settings_from_items(items: &[RolloutItem]) -> Option<ResumedSessionSettings> {
+    let last_turn = items.iter().rev().find_map(|item| match item {
+        RolloutItem::TurnContext(ctx) => Some(ctx),
+        _ => None,
+    });
+    if let Some(ctx) = last_turn {
+        return Some(ResumedSessionSettings {
+            cwd: ctx.cwd.clone(),
+            model: Some(ctx.model.clone()),
+            approval_policy: Some(ctx.approval_policy),
+            sandbox_policy: Some(ctx.sandbox_policy.clone()),
+        });
+    }
+    items.iter().find_map(|item| match item {
+        RolloutItem::SessionMeta(meta) => Some(ResumedSessionSettings {
+            cwd: meta.meta.cwd.clone(),
+            model: None,
+            approval_policy: None,
+            sandbox_policy: None,
+        }),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::config_types::ReasoningSummary;
+    use codex_protocol::protocol::SessionMeta;
+    use codex_protocol::protocol::SessionMetaLine;
+    use codex_protocol::protocol::TurnContextItem;
+    use pretty_assertions::assert_eq;
+
+    fn turn(cwd: &str, model: &str, sandbox_policy: SandboxPolicy) -> RolloutItem {
+        RolloutItem::TurnContext(TurnContextItem {
+            cwd: PathBuf::from(cwd),
+            approval_policy: AskForApproval::OnRequest,
+            sandbox_policy,
+            model: model.to_string(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+        })
+    }
+
+    #[test]
+    fn the_last_turn_wins_over_the_session_start() {
+        let start = RolloutItem::SessionMeta(SessionMetaLine {
+            meta: SessionMeta {
+                cwd: PathBuf::from("/src/app"),
+                ..SessionMeta::default()
+            },
+            git: None,
+        });
+        assert_eq!(
+            settings_from_items(std::slice::from_ref(&start)),
+            Some(ResumedSessionSettings {
+                cwd: PathBuf::from("/src/app"),
+                model: None,
+                approval_policy: None,
+                sandbox_policy: None,
+            })
+        );
+
+        let items = vec![
+            start,
+            turn("/src/app", "gpt-5", SandboxPolicy::ReadOnly),
+            turn(
+                "/src/app/web",
+                "gpt-5-codex",
+                SandboxPolicy::DangerFullAccess,
+            ),
+        ];
+        assert_eq!(
+            settings_from_items(&items),
+            Some(ResumedSessionSettings {
+                cwd: PathBuf::from("/src/app/web"),
+                model: Some("gpt-5-codex".to_string()),
+                approval_policy: Some(AskForApproval::OnRequest),
+                sandbox_policy: Some(SandboxPolicy::DangerFullAccess),
+            })
+        );
+        assert_eq!(settings_from_items(&[]), None);
+    }
+}
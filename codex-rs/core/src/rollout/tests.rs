@@ -592,6 +592,8 @@ async fn test_updated_at_uses_file_mtime() -> Result<()> {
                 cli_version: "test_version".into(),
                 source: SessionSource::VSCode,
                 model_provider: Some("test-provider".into()),
+                tags: Default::default(),
+                forked_from: None,
             },
             git: None,
         }),
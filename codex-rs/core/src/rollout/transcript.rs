@@ -0,0 +1,210 @@
+//! The readable transcript of a recorded session: the prompts, reasoning
+//! summaries, replies, commands and tool calls, patches, and their output,
+//! in the order they happened. Shared by `codex sessions export`,
+//! `codex replay`, and the MCP server's `codex-transcript` tool.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::models::LocalShellAction;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::ForkedFrom;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use serde_json::Value;
+
+use super::list::find_conversation_path_by_id_str;
+
+/// One step of a session, in the order it was recorded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TranscriptEntry {
+    Prompt { turn: usize, text: String },
+    Reasoning(String),
+    Reply(String),
+    Command(String),
+    Patch(String),
+    ToolCall { name: String, input: String },
+    Output(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transcript {
+    pub id: String,
+    pub started_at: Option<String>,
+    pub cwd: Option<PathBuf>,
+    /// Model of the last turn.
+    pub model: Option<String>,
+    pub forked_from: Option<ForkedFrom>,
+    pub entries: Vec<TranscriptEntry>,
+}
+
+/// The transcript of the session recorded with `conversation_id`, and the
+/// rollout file it was read from; `None` when no such session is saved.
+pub async fn read_transcript(
+    codex_home: &Path,
+    conversation_id: &str,
+) -> io::Result<Option<(PathBuf, Transcript)>> {
+    let Some(path) = find_conversation_path_by_id_str(codex_home, conversation_id).await? else {
+        return Ok(None);
+    };
+    let contents = tokio::fs::read_to_string(&path).await.map_err(|err| {
+        io::Error::new(
+            err.kind(),
+            format!("failed to read {}: {err}", path.display()),
+        )
+    })?;
+    let transcript = Transcript::parse(&contents);
+    Ok(Some((path, transcript)))
+}
+
+impl Transcript {
+    /// Parses the lines of a rollout file, skipping any it cannot read.
+    pub fn parse(contents: &str) -> Self {
+        let mut transcript = Transcript {
+            id: String::new(),
+            started_at: None,
+            cwd: None,
+            model: None,
+            forked_from: None,
+            entries: Vec::new(),
+        };
+        let mut seen_meta = false;
+        let mut turn = 0;
+        for line in contents.lines() {
+            let Ok(line) = serde_json::from_str::<RolloutLine>(line) else {
+                continue;
+            };
+            let entry = match line.item {
+                // Forks repeat their parent's meta after their own.
+                RolloutItem::SessionMeta(meta) if !seen_meta => {
+                    seen_meta = true;
+                    transcript.id = meta.meta.id.to_string();
+                    transcript.started_at = Some(meta.meta.timestamp);
+                    transcript.cwd = Some(meta.meta.cwd);
+                    transcript.forked_from = meta.meta.forked_from;
+                    None
+                }
+                RolloutItem::TurnContext(ctx) => {
+                    transcript.model = Some(ctx.model);
+                    None
+                }
+                RolloutItem::EventMsg(EventMsg::UserMessage(event)) => {
+                    turn += 1;
+                    Some(TranscriptEntry::Prompt {
+                        turn,
+                        text: event.message,
+                    })
+                }
+                RolloutItem::EventMsg(EventMsg::AgentReasoning(event)) => {
+                    Some(TranscriptEntry::Reasoning(event.text))
+                }
+                RolloutItem::EventMsg(EventMsg::AgentMessage(event)) => {
+                    Some(TranscriptEntry::Reply(event.message))
+                }
+                RolloutItem::ResponseItem(item) => tool_entry(item),
+                _ => None,
+            };
+            transcript.entries.extend(entry);
+        }
+        transcript
+    }
+
+    /// Replaces the text of every entry with `redact` applied to it.
+    pub fn redact(&mut self, redact: impl Fn(&str) -> String) {
+        for entry in &mut self.entries {
+            match entry {
+                TranscriptEntry::Prompt { text, .. }
+                | TranscriptEntry::Reasoning(text)
+                | TranscriptEntry::Reply(text)
+                | TranscriptEntry::Command(text)
+                | TranscriptEntry::Patch(text)
+                | TranscriptEntry::Output(text)
+                | TranscriptEntry::ToolCall { input: text, .. } => *text = redact(text),
+            }
+        }
+    }
+
+    /// Labelled details of the session for a document header.
+    pub fn details(&self) -> Vec<(&'static str, String)> {
+        let mut details = Vec::new();
+        if let Some(started_at) = &self.started_at {
+            details.push(("Started", started_at.clone()));
+        }
+        if let Some(cwd) = &self.cwd {
+            details.push(("Directory", cwd.display().to_string()));
+        }
+        if let Some(model) = &self.model {
+            details.push(("Model", model.clone()));
+        }
+        if let Some(from) = &self.forked_from {
+            details.push(("Forked from", format!("{} at turn {}", from.id, from.turn)));
+        }
+        details
+    }
+}
+
+/// Commands, patches, other tool calls, and their output. Messages and
+/// reasoning come from the matching events, which carry readable text.
+fn tool_entry(item: ResponseItem) -> Option<TranscriptEntry> {
+    match item {
+        ResponseItem::FunctionCall {
+            name, arguments, ..
+        } => {
+            let args: Value = serde_json::from_str(&arguments).unwrap_or(Value::Null);
+            if let Some(command) = shell_command(&args) {
+                return Some(TranscriptEntry::Command(command));
+            }
+            if name == "apply_patch"
+                && let Some(patch) = args.get("input").and_then(Value::as_str)
+            {
+                return Some(TranscriptEntry::Patch(patch.to_string()));
+            }
+            let input = if args.is_null() {
+                arguments
+            } else {
+                serde_json::to_string_pretty(&args).unwrap_or(arguments)
+            };
+            Some(TranscriptEntry::ToolCall { name, input })
+        }
+        ResponseItem::CustomToolCall { name, input, .. } if name == "apply_patch" => {
+            Some(TranscriptEntry::Patch(input))
+        }
+        ResponseItem::CustomToolCall { name, input, .. } => {
+            Some(TranscriptEntry::ToolCall { name, input })
+        }
+        ResponseItem::LocalShellCall {
+            action: LocalShellAction::Exec(exec),
+            ..
+        } => Some(TranscriptEntry::Command(script(&exec.command))),
+        ResponseItem::FunctionCallOutput { output, .. } => {
+            Some(TranscriptEntry::Output(output.content))
+        }
+        ResponseItem::CustomToolCallOutput { output, .. } => Some(TranscriptEntry::Output(output)),
+        _ => None,
+    }
+}
+
+/// The command of a `shell` (`command` array) or `exec_command` (`cmd`
+/// string) call.
+fn shell_command(args: &Value) -> Option<String> {
+    if let Some(cmd) = args.get("cmd").and_then(Value::as_str) {
+        return Some(cmd.to_string());
+    }
+    let command: Vec<String> = args
+        .get("command")?
+        .as_array()?
+        .iter()
+        .map(|part| part.as_str().map(str::to_string))
+        .collect::<Option<_>>()?;
+    Some(script(&command))
+}
+
+/// `bash -lc "<script>"` is shown as the script.
+fn script(command: &[String]) -> String {
+    match command {
+        [_, flag, script] if flag == "-lc" || flag == "-c" => script.clone(),
+        _ => command.join(" "),
+    }
+}
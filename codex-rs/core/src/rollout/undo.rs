@@ -0,0 +1,161 @@
+//! Undoes the last turn of a recorded session that is not open, the way
+//! `/undo` does inside one (`codex undo`): the working tree goes back to the
+//! snapshot taken when the turn started, and the turn is dropped from the
+//! conversation a resumed session continues.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_git::ghost_commit_changed_paths;
+use codex_git::restore_ghost_commit;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::CompactedItem;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::UndoCompletedEvent;
+
+use super::RolloutRecorder;
+use super::RolloutRecorderParams;
+use super::resume::settings_from_items;
+use crate::config::Config;
+use crate::tasks::plan_undo;
+use crate::tasks::undo_message;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UndoneTurn {
+    /// Directory whose files were restored.
+    pub cwd: PathBuf,
+    pub message: String,
+}
+
+/// Undoes the last turn recorded in the rollout at `path` and records the
+/// trimmed conversation in it.
+pub async fn undo_last_recorded_turn(config: &Config, path: &Path) -> io::Result<UndoneTurn> {
+    let items = RolloutRecorder::get_rollout_history(path)
+        .await?
+        .get_rollout_items();
+    let history = current_history(&items)?;
+    let (ghost_commit, remaining) = plan_undo(&history)
+        .ok_or_else(|| io::Error::other("No ghost snapshot available to undo."))?;
+    let cwd = settings_from_items(&items)
+        .map(|settings| settings.cwd)
+        .ok_or_else(|| io::Error::other("the session did not record a working directory"))?;
+
+    let (ghost_commit, reverted) = tokio::task::spawn_blocking({
+        let cwd = cwd.clone();
+        move || {
+            let reverted = ghost_commit_changed_paths(&cwd, &ghost_commit).unwrap_or_default();
+            restore_ghost_commit(&cwd, &ghost_commit).map(|()| (ghost_commit, reverted))
+        }
+    })
+    .await
+    .map_err(io::Error::other)?
+    .map_err(io::Error::other)?;
+    let short_id: String = ghost_commit.id().chars().take(7).collect();
+    let message = undo_message(&short_id, &reverted);
+
+    let recorder =
+        RolloutRecorder::new(config, RolloutRecorderParams::resume(path.to_path_buf())).await?;
+    recorder
+        .record_items(&[
+            RolloutItem::Compacted(CompactedItem {
+                message: String::new(),
+                replacement_history: Some(remaining),
+            }),
+            RolloutItem::EventMsg(EventMsg::UndoCompleted(UndoCompletedEvent {
+                success: true,
+                message: Some(message.clone()),
+            })),
+        ])
+        .await?;
+    recorder.shutdown().await?;
+    Ok(UndoneTurn { cwd, message })
+}
+
+/// The conversation a resumed session would continue. A compaction that
+/// recorded only its summary cannot be rebuilt without the session's
+/// context, so that case is left to `/undo` inside the resumed session.
+fn current_history(items: &[RolloutItem]) -> io::Result<Vec<ResponseItem>> {
+    let mut history = Vec::new();
+    for item in items {
+        match item {
+            RolloutItem::ResponseItem(item) => history.push(item.clone()),
+            RolloutItem::Compacted(CompactedItem {
+                replacement_history: Some(replacement),
+                ..
+            }) => history = replacement.clone(),
+            RolloutItem::Compacted(_) => {
+                return Err(io::Error::other(
+                    "the session was compacted; resume it and use /undo instead",
+                ));
+            }
+            RolloutItem::SessionMeta(_)
+            | RolloutItem::TurnContext(_)
+            | RolloutItem::EventMsg(_) => {}
+        }
+    }
+    Ok(history)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_git::GhostCommit;
+    use codex_protocol::models::ContentItem;
+    use pretty_assertions::assert_eq;
+
+    fn message(role: &str, text: &str) -> ResponseItem {
+        let content = if role == "user" {
+            ContentItem::InputText {
+                text: text.to_string(),
+            }
+        } else {
+            ContentItem::OutputText {
+                text: text.to_string(),
+            }
+        };
+        ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![content],
+        }
+    }
+
+    fn snapshot(id: &str) -> ResponseItem {
+        ResponseItem::GhostSnapshot {
+            ghost_commit: GhostCommit::new(id.to_string(), None, Vec::new(), Vec::new()),
+        }
+    }
+
+    #[test]
+    fn undo_drops_the_turn_its_snapshot_started() {
+        let items: Vec<RolloutItem> = [
+            message("user", "first"),
+            snapshot("aaa"),
+            message("assistant", "done first"),
+            message("user", "second"),
+            snapshot("bbb"),
+            message("assistant", "done second"),
+        ]
+        .into_iter()
+        .map(RolloutItem::ResponseItem)
+        .collect();
+        let history = current_history(&items).expect("history");
+
+        let (ghost_commit, remaining) = plan_undo(&history).expect("plan");
+        assert_eq!(ghost_commit.id(), "bbb");
+        assert_eq!(remaining, history[..3].to_vec());
+
+        // An earlier undo recorded the trimmed history; the next undoes turn 1.
+        let mut items = items;
+        items.push(RolloutItem::Compacted(CompactedItem {
+            message: String::new(),
+            replacement_history: Some(remaining),
+        }));
+        let (ghost_commit, remaining) =
+            plan_undo(&current_history(&items).expect("history")).expect("plan");
+        assert_eq!(ghost_commit.id(), "aaa");
+        assert_eq!(remaining, Vec::new());
+    }
+}
@@ -0,0 +1,297 @@
+//! Limits on a single task (`codex exec --max-turns`, `--max-output-tokens`,
+//! and `--max-duration`), so an agent stuck in a loop stops on its own
+//! instead of running until a provider limit trips.
+//!
+//! Turns and output tokens are checked before each model request. The
+//! duration is also enforced during a request or a tool call, which is
+//! cancelled like an interrupt when the time is up so that what the turn did
+//! so far is still recorded. Either way the task ends with an error that
+//! summarizes what the agent attempted.
+
+use std::collections::BTreeSet;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_protocol::models::LocalShellAction;
+use codex_protocol::models::ResponseItem;
+use serde_json::Value;
+
+use crate::config::types::RunLimits;
+use crate::rate_limits::format_countdown;
+
+/// Most recent commands listed in the summary.
+const MAX_LISTED_COMMANDS: usize = 5;
+/// Changed files listed in the summary before the rest are counted.
+const MAX_LISTED_FILES: usize = 10;
+
+/// Which limit stopped the task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RunLimitHit {
+    Turns(u32),
+    OutputTokens(i64),
+    Duration(Duration),
+}
+
+impl RunLimitHit {
+    fn describe(self) -> String {
+        match self {
+            RunLimitHit::Turns(1) => "the limit of 1 turn".to_string(),
+            RunLimitHit::Turns(turns) => format!("the limit of {turns} turns"),
+            RunLimitHit::OutputTokens(tokens) => format!("the limit of {tokens} output tokens"),
+            RunLimitHit::Duration(duration) => {
+                format!(
+                    "the time limit of {}",
+                    format_countdown(duration.as_secs() as i64)
+                )
+            }
+        }
+    }
+}
+
+/// Progress of one task against its [`RunLimits`].
+pub(crate) struct RunLimitTracker {
+    limits: RunLimits,
+    started: Instant,
+    turns: u32,
+    /// The session's output tokens when the task started.
+    output_tokens_at_start: i64,
+    /// Length of the history when the task started, so the summary only
+    /// covers this task.
+    history_at_start: usize,
+}
+
+impl RunLimitTracker {
+    pub(crate) fn new(
+        limits: RunLimits,
+        output_tokens_at_start: i64,
+        history_at_start: usize,
+    ) -> Self {
+        Self {
+            limits,
+            started: Instant::now(),
+            turns: 0,
+            output_tokens_at_start,
+            history_at_start,
+        }
+    }
+
+    /// Counts the model request about to be made, unless a limit is already
+    /// reached; `output_tokens` is the session's running total.
+    pub(crate) fn start_turn(&mut self, output_tokens: i64) -> Option<RunLimitHit> {
+        if let Some(hit) = self.elapsed_hit() {
+            return Some(hit);
+        }
+        if let Some(max) = self.limits.max_turns
+            && self.turns >= max
+        {
+            return Some(RunLimitHit::Turns(max));
+        }
+        if let Some(max) = self.limits.max_output_tokens
+            && output_tokens - self.output_tokens_at_start >= max
+        {
+            return Some(RunLimitHit::OutputTokens(max));
+        }
+        self.turns += 1;
+        None
+    }
+
+    /// When the task has to stop for `max_duration`.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.limits
+            .max_duration
+            .map(|max_duration| self.started + max_duration)
+    }
+
+    /// The duration limit, once it has passed.
+    pub(crate) fn elapsed_hit(&self) -> Option<RunLimitHit> {
+        self.limits
+            .max_duration
+            .filter(|max_duration| self.started.elapsed() >= *max_duration)
+            .map(RunLimitHit::Duration)
+    }
+
+    /// The message the task ends with: the limit, then the tool calls and
+    /// commands the agent ran and the files it changed.
+    pub(crate) fn summary(
+        &self,
+        hit: RunLimitHit,
+        history: &[ResponseItem],
+        unified_diff: Option<&str>,
+    ) -> String {
+        let items = history.get(self.history_at_start..).unwrap_or_default();
+        let turns = match self.turns {
+            1 => "1 turn".to_string(),
+            turns => format!("{turns} turns"),
+        };
+        let mut summary = format!(
+            "Stopped after {turns} and {}: reached {}.",
+            format_countdown(self.started.elapsed().as_secs() as i64),
+            hit.describe()
+        );
+
+        let mut tool_calls = 0;
+        let mut commands = Vec::new();
+        for item in items {
+            match item {
+                ResponseItem::FunctionCall {
+                    name, arguments, ..
+                } => {
+                    tool_calls += 1;
+                    if let Some(command) = shell_command(name, arguments) {
+                        commands.push(command);
+                    }
+                }
+                ResponseItem::LocalShellCall {
+                    action: LocalShellAction::Exec(exec),
+                    ..
+                } => {
+                    tool_calls += 1;
+                    commands.push(exec.command.join(" "));
+                }
+                ResponseItem::CustomToolCall { .. } => tool_calls += 1,
+                _ => {}
+            }
+        }
+        if tool_calls == 0 {
+            summary.push_str("\nThe agent made no tool calls.");
+        } else {
+            summary.push_str(&format!(
+                "\nThe agent made {tool_calls} tool call{}.",
+                if tool_calls == 1 { "" } else { "s" }
+            ));
+        }
+        if !commands.is_empty() {
+            let skipped = commands.len().saturating_sub(MAX_LISTED_COMMANDS);
+            summary.push_str(&format!(
+                "\nLast commands: {}",
+                commands[skipped..]
+                    .iter()
+                    .map(|command| format!("`{command}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
+        let files = changed_files(unified_diff.unwrap_or_default());
+        if files.is_empty() {
+            summary.push_str("\nNo files were changed.");
+        } else {
+            let listed: Vec<&str> = files
+                .iter()
+                .take(MAX_LISTED_FILES)
+                .map(String::as_str)
+                .collect();
+            summary.push_str(&format!("\nChanged files: {}", listed.join(", ")));
+            if files.len() > MAX_LISTED_FILES {
+                summary.push_str(&format!(" and {} more", files.len() - MAX_LISTED_FILES));
+            }
+        }
+        summary.push_str("\nResume the session to continue.");
+        summary
+    }
+}
+
+/// The command of a shell tool call, from its JSON arguments.
+fn shell_command(name: &str, arguments: &str) -> Option<String> {
+    if !matches!(
+        name,
+        "shell" | "shell_command" | "exec_command" | "container.exec"
+    ) {
+        return None;
+    }
+    let arguments: Value = serde_json::from_str(arguments).ok()?;
+    match arguments.get("command").or_else(|| arguments.get("cmd"))? {
+        Value::String(command) => Some(command.clone()),
+        Value::Array(words) => Some(
+            words
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        _ => None,
+    }
+}
+
+fn changed_files(unified_diff: &str) -> BTreeSet<String> {
+    unified_diff
+        .lines()
+        .filter_map(|line| line.strip_prefix("diff --git a/"))
+        .filter_map(|paths| paths.split_once(" b/").map(|(_, path)| path.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn limits(max_turns: Option<u32>, max_output_tokens: Option<i64>) -> RunLimits {
+        RunLimits {
+            max_turns,
+            max_output_tokens,
+            max_duration: None,
+        }
+    }
+
+    #[test]
+    fn stops_before_the_request_past_a_limit() {
+        let mut tracker = RunLimitTracker::new(limits(Some(2), None), 0, 0);
+        assert_eq!(tracker.start_turn(0), None);
+        assert_eq!(tracker.start_turn(0), None);
+        assert_eq!(tracker.start_turn(0), Some(RunLimitHit::Turns(2)));
+
+        // Output tokens count from the start of the task.
+        let mut tracker = RunLimitTracker::new(limits(None, Some(1000)), 5000, 0);
+        assert_eq!(tracker.start_turn(5000), None);
+        assert_eq!(tracker.start_turn(5999), None);
+        assert_eq!(
+            tracker.start_turn(6000),
+            Some(RunLimitHit::OutputTokens(1000))
+        );
+
+        let tracker = RunLimitTracker::new(
+            RunLimits {
+                max_duration: Some(Duration::ZERO),
+                ..RunLimits::default()
+            },
+            0,
+            0,
+        );
+        assert_eq!(
+            tracker.elapsed_hit(),
+            Some(RunLimitHit::Duration(Duration::ZERO))
+        );
+    }
+
+    #[test]
+    fn summary_lists_what_the_task_attempted() {
+        let call = |name: &str, arguments: &str| ResponseItem::FunctionCall {
+            id: None,
+            name: name.to_string(),
+            arguments: arguments.to_string(),
+            call_id: "call".to_string(),
+        };
+        let history = vec![
+            call("shell_command", r#"{"command":"echo earlier task"}"#),
+            call("shell_command", r#"{"command":"cargo test"}"#),
+            call("apply_patch", r#"{"input":"*** Begin Patch"}"#),
+            call("shell", r#"{"command":["bash","-lc","cargo test -p api"]}"#),
+        ];
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n+x\ndiff --git a/README.md b/README.md\n";
+        let mut tracker = RunLimitTracker::new(limits(Some(3), None), 0, 1);
+        for _ in 0..3 {
+            tracker.start_turn(0);
+        }
+        let hit = tracker.start_turn(0).expect("limit");
+        assert_eq!(
+            tracker.summary(hit, &history, Some(diff)),
+            "\
+Stopped after 3 turns and <1m: reached the limit of 3 turns.
+The agent made 3 tool calls.
+Last commands: `cargo test`, `bash -lc cargo test -p api`
+Changed files: README.md, src/lib.rs
+Resume the session to continue."
+        );
+    }
+}
@@ -0,0 +1,534 @@
+//! Per-session guardrail that reacts when a turn looks pathological.
+//!
+//! Three signals are tracked across the lifetime of a session:
+//!
+//! - a model request whose token usage is far above the session's running
+//!   average,
+//! - the model issuing the exact same tool call over and over again, and
+//! - the model resubmitting a patch that already failed to apply.
+//!
+//! Short loops trigger an intervention: the model is asked to summarize what
+//! it has tried and change approach. Long loops and token spikes stop the
+//! running task with a warning so that an unattended run cannot burn through a
+//! day's budget. The user can continue by sending another message.
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+
+use codex_protocol::models::LocalShellAction;
+use codex_protocol::models::ResponseInputItem;
+use codex_protocol::models::ResponseItem;
+use serde::Deserialize;
+
+use crate::codex::ProcessedResponseItem;
+
+/// A request is anomalous when it uses this many times the session average.
+const TOKEN_SPIKE_FACTOR: i64 = 10;
+
+/// Number of completed requests required before the average is meaningful.
+const MIN_REQUESTS_FOR_AVERAGE: i64 = 3;
+
+/// Number of consecutive identical tool calls after which the model is asked
+/// to change approach.
+const INTERVENE_AFTER_IDENTICAL_TOOL_CALLS: i64 = 3;
+
+/// Number of consecutive identical tool calls treated as a runaway loop.
+const MAX_IDENTICAL_TOOL_CALLS: i64 = 8;
+
+/// Number of times the same patch may fail before the model is asked to
+/// change approach.
+const INTERVENE_AFTER_PATCH_FAILURES: i64 = 3;
+
+const APPLY_PATCH_TOOL_NAME: &str = "apply_patch";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum GuardrailTrip {
+    TokenSpike {
+        request_tokens: i64,
+        average_tokens: i64,
+    },
+    RepeatedToolCall {
+        tool_name: String,
+        repetitions: i64,
+    },
+    LoopSuspected {
+        tool_name: String,
+        repetitions: i64,
+    },
+    RepeatedPatchFailure {
+        failures: i64,
+    },
+}
+
+impl GuardrailTrip {
+    /// Whether the running task should stop and wait for the user.
+    pub(crate) fn pauses(&self) -> bool {
+        matches!(
+            self,
+            GuardrailTrip::TokenSpike { .. } | GuardrailTrip::RepeatedToolCall { .. }
+        )
+    }
+
+    /// Message surfaced to the user.
+    pub(crate) fn message(&self) -> String {
+        match self {
+            GuardrailTrip::TokenSpike {
+                request_tokens,
+                average_tokens,
+            } => format!(
+                "Paused: the last model request used {request_tokens} tokens, more than {TOKEN_SPIKE_FACTOR}x this session's average of {average_tokens}. Send a message to continue."
+            ),
+            GuardrailTrip::RepeatedToolCall {
+                tool_name,
+                repetitions,
+            } => format!(
+                "Paused: the agent called `{tool_name}` with identical arguments {repetitions} times in a row. Send a message to continue."
+            ),
+            GuardrailTrip::LoopSuspected {
+                tool_name,
+                repetitions,
+            } => format!(
+                "Loop detected: the agent called `{tool_name}` with identical arguments {repetitions} times in a row. Asking it to change approach."
+            ),
+            GuardrailTrip::RepeatedPatchFailure { failures } => format!(
+                "Loop detected: the agent submitted the same failing patch {failures} times. Asking it to change approach."
+            ),
+        }
+    }
+
+    /// Instruction injected into the conversation so the model breaks out of
+    /// the loop. `None` for trips that pause instead.
+    pub(crate) fn intervention_prompt(&self) -> Option<String> {
+        let observation = match self {
+            GuardrailTrip::TokenSpike { .. } | GuardrailTrip::RepeatedToolCall { .. } => {
+                return None;
+            }
+            GuardrailTrip::LoopSuspected {
+                tool_name,
+                repetitions,
+            } => format!(
+                "You have called `{tool_name}` with identical arguments {repetitions} times in a row without making progress."
+            ),
+            GuardrailTrip::RepeatedPatchFailure { failures } => format!(
+                "You have submitted the same patch {failures} times and it has failed to apply each time."
+            ),
+        };
+        Some(format!(
+            "<loop_intervention>\n{observation}\nDo not repeat that call. Before continuing:\n1. Summarize what you have tried so far and what each attempt returned.\n2. Explain why those attempts did not work.\n3. Choose a different approach, for example re-reading the relevant files before editing them.\n</loop_intervention>"
+        ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ToolCallSignature {
+    tool_name: String,
+    arguments: String,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct SessionGuardrail {
+    completed_requests: i64,
+    total_request_tokens: i64,
+    last_tool_call: Option<ToolCallSignature>,
+    repetitions: i64,
+    /// Failure counts keyed by a hash of the patch body.
+    patch_failures: HashMap<u64, i64>,
+}
+
+impl SessionGuardrail {
+    /// Record a completed model request and the items it produced.
+    ///
+    /// `request_tokens` is the token usage reported for the request, or zero
+    /// when the provider did not report usage. Trips that pause take
+    /// precedence over interventions.
+    pub(crate) fn observe_request(
+        &mut self,
+        request_tokens: i64,
+        items: &[ProcessedResponseItem],
+    ) -> Option<GuardrailTrip> {
+        let repeated = self.observe_tool_calls(items);
+        let patch = self.observe_patch_failures(items);
+        let spike = self.observe_tokens(request_tokens);
+
+        [repeated, spike, patch]
+            .into_iter()
+            .flatten()
+            .min_by_key(|trip| !trip.pauses())
+    }
+
+    fn observe_tool_calls(&mut self, items: &[ProcessedResponseItem]) -> Option<GuardrailTrip> {
+        let mut trip = None;
+        for signature in items
+            .iter()
+            .filter_map(|processed| tool_call_signature(&processed.item))
+        {
+            if self.last_tool_call.as_ref() == Some(&signature) {
+                self.repetitions += 1;
+            } else {
+                self.repetitions = 1;
+            }
+
+            if self.repetitions >= MAX_IDENTICAL_TOOL_CALLS {
+                trip = Some(GuardrailTrip::RepeatedToolCall {
+                    tool_name: signature.tool_name.clone(),
+                    repetitions: self.repetitions,
+                });
+                // Start counting again so that continuing does not immediately
+                // pause on the next identical call.
+                self.repetitions = 0;
+            } else if self.repetitions == INTERVENE_AFTER_IDENTICAL_TOOL_CALLS && trip.is_none() {
+                trip = Some(GuardrailTrip::LoopSuspected {
+                    tool_name: signature.tool_name.clone(),
+                    repetitions: self.repetitions,
+                });
+            }
+            self.last_tool_call = Some(signature);
+        }
+        trip
+    }
+
+    fn observe_patch_failures(&mut self, items: &[ProcessedResponseItem]) -> Option<GuardrailTrip> {
+        let mut trip = None;
+        for processed in items {
+            let Some(signature) = tool_call_signature(&processed.item) else {
+                continue;
+            };
+            if signature.tool_name != APPLY_PATCH_TOOL_NAME {
+                continue;
+            }
+
+            let key = hash_patch(&signature.arguments);
+            match response_success(processed.response.as_ref()) {
+                Some(true) => {
+                    self.patch_failures.remove(&key);
+                    continue;
+                }
+                Some(false) => {}
+                None => continue,
+            }
+
+            let failures = self.patch_failures.entry(key).or_default();
+            *failures += 1;
+            if *failures == INTERVENE_AFTER_PATCH_FAILURES {
+                trip = Some(GuardrailTrip::RepeatedPatchFailure {
+                    failures: *failures,
+                });
+            }
+        }
+        trip
+    }
+
+    fn observe_tokens(&mut self, request_tokens: i64) -> Option<GuardrailTrip> {
+        if request_tokens <= 0 {
+            return None;
+        }
+
+        let trip = if self.completed_requests >= MIN_REQUESTS_FOR_AVERAGE {
+            let average_tokens = self.total_request_tokens / self.completed_requests;
+            (average_tokens > 0
+                && request_tokens > average_tokens.saturating_mul(TOKEN_SPIKE_FACTOR))
+            .then_some(GuardrailTrip::TokenSpike {
+                request_tokens,
+                average_tokens,
+            })
+        } else {
+            None
+        };
+
+        self.completed_requests += 1;
+        self.total_request_tokens = self.total_request_tokens.saturating_add(request_tokens);
+        trip
+    }
+}
+
+fn tool_call_signature(item: &ResponseItem) -> Option<ToolCallSignature> {
+    match item {
+        ResponseItem::FunctionCall {
+            name, arguments, ..
+        } => Some(ToolCallSignature {
+            tool_name: name.clone(),
+            arguments: arguments.clone(),
+        }),
+        ResponseItem::CustomToolCall { name, input, .. } => Some(ToolCallSignature {
+            tool_name: name.clone(),
+            arguments: input.clone(),
+        }),
+        ResponseItem::LocalShellCall {
+            action: LocalShellAction::Exec(action),
+            ..
+        } => Some(ToolCallSignature {
+            tool_name: "local_shell".to_string(),
+            arguments: action.command.join(" "),
+        }),
+        _ => None,
+    }
+}
+
+/// Whether an `apply_patch` call succeeded, or `None` when its output does
+/// not say.
+fn response_success(response: Option<&ResponseInputItem>) -> Option<bool> {
+    match response? {
+        ResponseInputItem::FunctionCallOutput { output, .. } => output.success,
+        ResponseInputItem::CustomToolCallOutput { output, .. } => freeform_patch_success(output),
+        ResponseInputItem::McpToolCallOutput { .. } | ResponseInputItem::Message { .. } => None,
+    }
+}
+
+/// The freeform `apply_patch` only returns text: the patch summary when it
+/// was applied in process, the structured exec output when it ran as a
+/// command, or the reason it was refused.
+fn freeform_patch_success(output: &str) -> Option<bool> {
+    #[derive(Deserialize)]
+    struct ExecOutput {
+        metadata: ExecMetadata,
+    }
+
+    #[derive(Deserialize)]
+    struct ExecMetadata {
+        exit_code: i32,
+    }
+
+    if let Ok(exec) = serde_json::from_str::<ExecOutput>(output) {
+        return Some(exec.metadata.exit_code == 0);
+    }
+    if output.starts_with("Success.") {
+        Some(true)
+    } else if output.starts_with("apply_patch verification failed") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+fn hash_patch(patch: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    patch.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    fn shell_call(command: &str) -> ProcessedResponseItem {
+        ProcessedResponseItem {
+            item: ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: format!(r#"{{"command":["bash","-lc","{command}"]}}"#),
+                call_id: "call".to_string(),
+            },
+            response: None,
+        }
+    }
+
+    fn patch_call(patch: &str, success: bool) -> ProcessedResponseItem {
+        ProcessedResponseItem {
+            item: ResponseItem::FunctionCall {
+                id: None,
+                name: APPLY_PATCH_TOOL_NAME.to_string(),
+                arguments: patch.to_string(),
+                call_id: "call".to_string(),
+            },
+            response: Some(ResponseInputItem::FunctionCallOutput {
+                call_id: "call".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: String::new(),
+                    success: Some(success),
+                    ..Default::default()
+                },
+            }),
+        }
+    }
+
+    #[test]
+    fn token_spike_requires_history() {
+        let mut guardrail = SessionGuardrail::default();
+        assert_eq!(guardrail.observe_request(100, &[]), None);
+        assert_eq!(guardrail.observe_request(5_000, &[]), None);
+    }
+
+    #[test]
+    fn token_spike_trips_above_factor() {
+        let mut guardrail = SessionGuardrail::default();
+        for _ in 0..3 {
+            assert_eq!(guardrail.observe_request(1_000, &[]), None);
+        }
+        assert_eq!(guardrail.observe_request(10_000, &[]), None);
+
+        let average_tokens = (3_000 + 10_000) / 4;
+        assert_eq!(
+            guardrail.observe_request(average_tokens * 10 + 1, &[]),
+            Some(GuardrailTrip::TokenSpike {
+                request_tokens: average_tokens * 10 + 1,
+                average_tokens,
+            })
+        );
+    }
+
+    #[test]
+    fn unreported_usage_is_ignored() {
+        let mut guardrail = SessionGuardrail::default();
+        for _ in 0..3 {
+            guardrail.observe_request(1_000, &[]);
+        }
+        assert_eq!(guardrail.observe_request(0, &[]), None);
+        assert_eq!(guardrail.completed_requests, 3);
+    }
+
+    #[test]
+    fn identical_tool_calls_intervene_then_pause_then_reset() {
+        let mut guardrail = SessionGuardrail::default();
+        let mut trips = Vec::new();
+        for _ in 0..MAX_IDENTICAL_TOOL_CALLS + 1 {
+            trips.push(guardrail.observe_request(0, &[shell_call("ls")]));
+        }
+
+        let mut expected = vec![None; (MAX_IDENTICAL_TOOL_CALLS + 1) as usize];
+        expected[(INTERVENE_AFTER_IDENTICAL_TOOL_CALLS - 1) as usize] =
+            Some(GuardrailTrip::LoopSuspected {
+                tool_name: "shell".to_string(),
+                repetitions: INTERVENE_AFTER_IDENTICAL_TOOL_CALLS,
+            });
+        expected[(MAX_IDENTICAL_TOOL_CALLS - 1) as usize] = Some(GuardrailTrip::RepeatedToolCall {
+            tool_name: "shell".to_string(),
+            repetitions: MAX_IDENTICAL_TOOL_CALLS,
+        });
+        assert_eq!(trips, expected);
+    }
+
+    #[test]
+    fn distinct_tool_calls_do_not_trip() {
+        let mut guardrail = SessionGuardrail::default();
+        for i in 0..MAX_IDENTICAL_TOOL_CALLS * 2 {
+            let command = if i % 2 == 0 { "ls" } else { "pwd" };
+            assert_eq!(guardrail.observe_request(0, &[shell_call(command)]), None);
+        }
+    }
+
+    #[test]
+    fn repeated_failing_patch_intervenes() {
+        let mut guardrail = SessionGuardrail::default();
+        assert_eq!(
+            guardrail.observe_request(0, &[patch_call("a", false)]),
+            None
+        );
+        assert_eq!(guardrail.observe_request(0, &[shell_call("cat a")]), None);
+        assert_eq!(
+            guardrail.observe_request(0, &[patch_call("a", false)]),
+            None
+        );
+        assert_eq!(
+            guardrail.observe_request(0, &[patch_call("b", false)]),
+            None
+        );
+        assert_eq!(
+            guardrail.observe_request(0, &[patch_call("a", false)]),
+            Some(GuardrailTrip::RepeatedPatchFailure { failures: 3 })
+        );
+    }
+
+    #[test]
+    fn freeform_patch_outputs_are_not_failures() {
+        let freeform_patch = ProcessedResponseItem {
+            item: ResponseItem::CustomToolCall {
+                id: None,
+                status: None,
+                call_id: "call".to_string(),
+                name: APPLY_PATCH_TOOL_NAME.to_string(),
+                input: "a".to_string(),
+            },
+            response: Some(ResponseInputItem::CustomToolCallOutput {
+                call_id: "call".to_string(),
+                output: "Success. Updated the following files:\nM a".to_string(),
+            }),
+        };
+        let mut guardrail = SessionGuardrail::default();
+        for _ in 0..INTERVENE_AFTER_PATCH_FAILURES {
+            assert_eq!(
+                guardrail.observe_request(0, std::slice::from_ref(&freeform_patch)),
+                None
+            );
+            assert_eq!(guardrail.observe_request(0, &[shell_call("cat a")]), None);
+        }
+        assert!(guardrail.patch_failures.is_empty());
+    }
+
+    #[test]
+    fn repeated_freeform_patch_failures_intervene() {
+        let freeform_patch = |output: &str| ProcessedResponseItem {
+            item: ResponseItem::CustomToolCall {
+                id: None,
+                status: None,
+                call_id: "call".to_string(),
+                name: APPLY_PATCH_TOOL_NAME.to_string(),
+                input: "a".to_string(),
+            },
+            response: Some(ResponseInputItem::CustomToolCallOutput {
+                call_id: "call".to_string(),
+                output: output.to_string(),
+            }),
+        };
+        let verification_failed = freeform_patch(
+            "apply_patch verification failed: Failed to find expected lines in a",
+        );
+        let exec_failed = freeform_patch(
+            r#"{"output":"error: hunk failed","metadata":{"exit_code":1,"duration_seconds":0.1}}"#,
+        );
+        let mut guardrail = SessionGuardrail::default();
+        for patch in [&verification_failed, &exec_failed] {
+            assert_eq!(
+                guardrail.observe_request(0, std::slice::from_ref(patch)),
+                None
+            );
+            assert_eq!(guardrail.observe_request(0, &[shell_call("cat a")]), None);
+        }
+        assert_eq!(
+            guardrail.observe_request(0, std::slice::from_ref(&verification_failed)),
+            Some(GuardrailTrip::RepeatedPatchFailure { failures: 3 })
+        );
+    }
+
+    #[test]
+    fn successful_patch_clears_failures() {
+        let mut guardrail = SessionGuardrail::default();
+        guardrail.observe_request(0, &[patch_call("a", false)]);
+        guardrail.observe_request(0, &[shell_call("cat a")]);
+        guardrail.observe_request(0, &[patch_call("a", true)]);
+        guardrail.observe_request(0, &[shell_call("cat a")]);
+        assert_eq!(
+            guardrail.observe_request(0, &[patch_call("a", false)]),
+            None
+        );
+        assert_eq!(
+            guardrail
+                .patch_failures
+                .values()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn only_loop_trips_carry_an_intervention() {
+        let spike = GuardrailTrip::TokenSpike {
+            request_tokens: 10,
+            average_tokens: 1,
+        };
+        assert_eq!(spike.intervention_prompt(), None);
+        assert!(spike.pauses());
+
+        let repeated = GuardrailTrip::RepeatedPatchFailure { failures: 3 };
+        assert!(!repeated.pauses());
+        assert!(
+            repeated
+                .intervention_prompt()
+                .is_some_and(|prompt| prompt.starts_with("<loop_intervention>"))
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Limit and usage history shared between machines through
+//! `shared_state_dir`, a directory kept in sync by Dropbox, Syncthing, a
+//! network mount, or similar.
+//!
+//! Each history is still kept in the state directory first. With a
+//! shared directory configured, every line is also appended to a file only
+//! this machine writes, `<shared_state_dir>/<history>/<machine id>.jsonl`,
+//! so machines never edit the same file and a sync tool has nothing to
+//! merge. Readers combine the local file with every file in the shared
+//! directory, including the "conflicted copy" files some sync tools create,
+//! and drop lines seen more than once.
+
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use tracing::warn;
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+/// File in the state directory naming this machine in the shared directory.
+const MACHINE_ID_FILENAME: &str = "machine_id";
+
+/// Appends `line` to `file_name` in `state_dir` and, when `shared_dir` is
+/// set, to this machine's copy under it. Only the local write can fail; a
+/// shared directory that is missing or unmounted is logged and skipped.
+pub(crate) fn append_line(
+    state_dir: &Path,
+    shared_dir: Option<&Path>,
+    file_name: &str,
+    line: &str,
+) -> io::Result<()> {
+    std::fs::create_dir_all(state_dir)?;
+    append_to(&state_dir.join(file_name), line)?;
+    if let Some(shared_dir) = shared_dir {
+        publish_line(state_dir, shared_dir, file_name, line);
+    }
+    Ok(())
+}
+
+/// Appends `line` to this machine's copy of `file_name` under `shared_dir`,
+/// for histories whose local copy is kept elsewhere. Failures are logged and
+/// skipped.
+pub(crate) fn publish_line(state_dir: &Path, shared_dir: &Path, file_name: &str, line: &str) {
+    let published = machine_id(state_dir).and_then(|machine| {
+        let dir = shared_history_dir(shared_dir, file_name);
+        std::fs::create_dir_all(&dir)?;
+        append_to(&dir.join(format!("{machine}.jsonl")), line)
+    });
+    if let Err(err) = published {
+        warn!(
+            "failed to write {file_name} to {}: {err}",
+            shared_dir.display()
+        );
+    }
+}
+
+/// Lines of `file_name` in `state_dir` followed by the ones other machines
+/// published under `shared_dir`, each distinct line once. A missing file is
+/// empty; an unreadable shared directory is logged and skipped.
+pub(crate) fn read_lines(
+    state_dir: &Path,
+    shared_dir: Option<&Path>,
+    file_name: &str,
+) -> io::Result<Vec<String>> {
+    let mut seen = HashSet::new();
+    let mut lines = Vec::new();
+    let mut keep = |line: String| {
+        if !line.is_empty() && seen.insert(line.clone()) {
+            lines.push(line);
+        }
+    };
+    for line in read_file(&state_dir.join(file_name))? {
+        keep(line);
+    }
+    if let Some(shared_dir) = shared_dir {
+        read_shared_lines(shared_dir, file_name)
+            .into_iter()
+            .for_each(keep);
+    }
+    Ok(lines)
+}
+
+/// Every line published under `shared_dir` for `file_name`, by this machine
+/// and the others, in file name order. Unreadable files are logged and
+/// skipped.
+pub(crate) fn read_shared_lines(shared_dir: &Path, file_name: &str) -> Vec<String> {
+    let files = match shared_files(&shared_history_dir(shared_dir, file_name)) {
+        Ok(files) => files,
+        Err(err) => {
+            warn!(
+                "failed to read {file_name} from {}: {err}",
+                shared_dir.display()
+            );
+            return Vec::new();
+        }
+    };
+    let mut lines = Vec::new();
+    for file in files {
+        match read_file(&file) {
+            Ok(file_lines) => lines.extend(file_lines),
+            Err(err) => warn!("failed to read {}: {err}", file.display()),
+        }
+    }
+    lines
+}
+
+/// `limit_history.jsonl` is shared under `<shared_dir>/limit_history/`.
+fn shared_history_dir(shared_dir: &Path, file_name: &str) -> PathBuf {
+    let stem = file_name.strip_suffix(".jsonl").unwrap_or(file_name);
+    shared_dir.join(stem)
+}
+
+/// Every `.jsonl` file in `dir`, sorted by name. A missing directory has
+/// none.
+fn shared_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn read_file(path: &Path) -> io::Result<Vec<String>> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    BufReader::new(file).lines().collect()
+}
+
+fn append_to(path: &Path, line: &str) -> io::Result<()> {
+    let mut line = line.to_string();
+    line.push('\n');
+
+    let mut options = OpenOptions::new();
+    options.append(true).create(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(path)?;
+    file.write_all(line.as_bytes())
+}
+
+/// Random id for this machine, created in `state_dir` the first time it is
+/// needed.
+fn machine_id(state_dir: &Path) -> io::Result<String> {
+    let path = state_dir.join(MACHINE_ID_FILENAME);
+    match std::fs::read_to_string(&path) {
+        Ok(id) if !id.trim().is_empty() => return Ok(id.trim().to_string()),
+        Ok(_) => {}
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    std::fs::write(&path, &id)?;
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn lines_from_every_machine_are_merged_once() {
+        let laptop = tempfile::tempdir().expect("tempdir");
+        let desktop = tempfile::tempdir().expect("tempdir");
+        let shared_dir = tempfile::tempdir().expect("tempdir");
+        let shared = Some(shared_dir.path());
+
+        append_line(laptop.path(), shared, "history.jsonl", "laptop 1").expect("append");
+        append_line(desktop.path(), shared, "history.jsonl", "desktop 1").expect("append");
+        append_line(laptop.path(), shared, "history.jsonl", "laptop 2").expect("append");
+        append_line(desktop.path(), None, "history.jsonl", "desktop only").expect("append");
+
+        assert_eq!(
+            read_lines(laptop.path(), shared, "history.jsonl").expect("read"),
+            vec!["laptop 1", "laptop 2", "desktop 1"]
+        );
+        assert_eq!(
+            read_lines(desktop.path(), shared, "history.jsonl").expect("read"),
+            vec!["desktop 1", "desktop only", "laptop 1", "laptop 2"]
+        );
+        assert_eq!(
+            read_lines(laptop.path(), None, "history.jsonl").expect("read"),
+            vec!["laptop 1", "laptop 2"]
+        );
+
+        // A conflicted copy left behind by a sync tool adds nothing new.
+        let dir = shared_history_dir(shared_dir.path(), "history.jsonl");
+        let machine = machine_id(laptop.path()).expect("machine id");
+        std::fs::copy(
+            dir.join(format!("{machine}.jsonl")),
+            dir.join(format!("{machine} (conflicted copy).jsonl")),
+        )
+        .expect("copy");
+        assert_eq!(
+            read_lines(laptop.path(), shared, "history.jsonl").expect("read"),
+            vec!["laptop 1", "laptop 2", "desktop 1"]
+        );
+    }
+}
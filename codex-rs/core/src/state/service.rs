@@ -2,7 +2,13 @@ use std::sync::Arc;
 
 use crate::AuthManager;
 use crate::RolloutRecorder;
+use crate::external_edits::ExternalEdits;
+use crate::hooks::HookRunner;
 use crate::mcp_connection_manager::McpConnectionManager;
+use crate::plugins::PluginTool;
+use crate::redact::Redactor;
+use crate::repo_map::RepoMap;
+use crate::sub_agents::SubAgents;
 use crate::tools::sandboxing::ApprovalStore;
 use crate::unified_exec::UnifiedExecSessionManager;
 use crate::user_notification::UserNotifier;
@@ -16,6 +22,17 @@ pub(crate) struct SessionServices {
     pub(crate) mcp_startup_cancellation_token: CancellationToken,
     pub(crate) unified_exec_manager: UnifiedExecSessionManager,
     pub(crate) notifier: UserNotifier,
+    pub(crate) hooks: HookRunner,
+    /// Tools from the plugins in `$CODEX_HOME/plugins/`, loaded once per session.
+    pub(crate) plugin_tools: Vec<PluginTool>,
+    /// Outlines for the `repo_map` feature, refreshed before each turn.
+    pub(crate) repo_map: Mutex<RepoMap>,
+    /// Files changed outside the session, for the `detect_external_edits` feature.
+    pub(crate) external_edits: ExternalEdits,
+    /// Sub-agents started with `spawn_agent`, for the `sub_agents` feature.
+    pub(crate) sub_agents: SubAgents,
+    /// Secrets replaced in tool output and pinned files, from `[redaction]`.
+    pub(crate) redactor: Redactor,
     pub(crate) rollout: Mutex<Option<RolloutRecorder>>,
     pub(crate) user_shell: crate::shell::Shell,
     pub(crate) show_raw_agent_reasoning: bool,
@@ -1,12 +1,24 @@
 //! Session-wide mutable state.
 
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
 use codex_protocol::models::ResponseItem;
+use codex_protocol::plan_tool::UpdatePlanArgs;
 
+use crate::budget::BudgetPeriod;
+use crate::codex::ProcessedResponseItem;
 use crate::codex::SessionConfiguration;
 use crate::context_manager::ContextManager;
+use crate::patch_merge::FileBases;
+use crate::pricing::CostTracker;
 use crate::protocol::RateLimitSnapshot;
+use crate::protocol::TokenCost;
 use crate::protocol::TokenUsage;
 use crate::protocol::TokenUsageInfo;
+use crate::session_guardrail::GuardrailTrip;
+use crate::session_guardrail::SessionGuardrail;
 use crate::truncate::TruncationPolicy;
 
 /// Persistent, session-scoped state previously stored directly on `Session`.
@@ -14,6 +26,17 @@ pub(crate) struct SessionState {
     pub(crate) session_configuration: SessionConfiguration,
     pub(crate) history: ContextManager,
     pub(crate) latest_rate_limits: Option<RateLimitSnapshot>,
+    guardrail: SessionGuardrail,
+    cost: CostTracker,
+    /// Budget caps already warned about this session.
+    budget_warnings: HashSet<BudgetPeriod>,
+    /// Contents of each file as the model last read it, the base of the
+    /// three-way merge that rescues patches against stale files.
+    file_bases: FileBases,
+    /// Paths kept in context for every turn, in the order they were pinned.
+    pins: Vec<PathBuf>,
+    /// The plan from the model's last `update_plan` call, restored on resume.
+    plan: Option<UpdatePlanArgs>,
 }
 
 impl SessionState {
@@ -24,6 +47,12 @@ impl SessionState {
             session_configuration,
             history,
             latest_rate_limits: None,
+            guardrail: SessionGuardrail::default(),
+            cost: CostTracker::default(),
+            budget_warnings: HashSet::new(),
+            file_bases: FileBases::default(),
+            pins: Vec::new(),
+            plan: None,
         }
     }
 
@@ -61,6 +90,16 @@ impl SessionState {
         self.history.token_info()
     }
 
+    pub(crate) fn cost_mut(&mut self) -> &mut CostTracker {
+        &mut self.cost
+    }
+
+    /// The session's estimated spend and the budget caps already warned
+    /// about.
+    pub(crate) fn budget_state_mut(&mut self) -> (f64, &mut HashSet<BudgetPeriod>) {
+        (self.cost.session_usd(), &mut self.budget_warnings)
+    }
+
     pub(crate) fn set_rate_limits(&mut self, snapshot: RateLimitSnapshot) {
         self.latest_rate_limits = Some(merge_rate_limit_credits(
             self.latest_rate_limits.as_ref(),
@@ -68,10 +107,14 @@ impl SessionState {
         ));
     }
 
-    pub(crate) fn token_info_and_rate_limits(
+    pub(crate) fn token_info_rate_limits_and_cost(
         &self,
-    ) -> (Option<TokenUsageInfo>, Option<RateLimitSnapshot>) {
-        (self.token_info(), self.latest_rate_limits.clone())
+    ) -> (Option<TokenUsageInfo>, Option<RateLimitSnapshot>, TokenCost) {
+        (
+            self.token_info(),
+            self.latest_rate_limits.clone(),
+            self.cost.cost(),
+        )
     }
 
     pub(crate) fn set_token_usage_full(&mut self, context_window: i64) {
@@ -81,6 +124,71 @@ impl SessionState {
     pub(crate) fn get_total_token_usage(&self) -> i64 {
         self.history.get_total_token_usage()
     }
+
+    /// Cumulative output tokens reported by the provider over the whole
+    /// session.
+    pub(crate) fn output_tokens_used(&self) -> i64 {
+        self.token_info()
+            .map(|info| info.total_token_usage.output_tokens)
+            .unwrap_or(0)
+    }
+
+    /// Cumulative tokens reported by the provider over the whole session.
+    pub(crate) fn session_tokens_used(&self) -> i64 {
+        self.token_info()
+            .map(|info| info.total_token_usage.total_tokens)
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn record_file_base(&mut self, path: PathBuf, contents: String) {
+        self.file_bases.record(path, contents);
+    }
+
+    pub(crate) fn file_base(&mut self, path: &Path) -> Option<String> {
+        self.file_bases.get(path)
+    }
+
+    pub(crate) fn forget_file_base(&mut self, path: &Path) {
+        self.file_bases.forget(path);
+    }
+
+    // Pin helpers
+    pub(crate) fn pins(&self) -> Vec<PathBuf> {
+        self.pins.clone()
+    }
+
+    /// Returns `false` when `path` was already pinned.
+    pub(crate) fn pin(&mut self, path: PathBuf) -> bool {
+        if self.pins.contains(&path) {
+            return false;
+        }
+        self.pins.push(path);
+        true
+    }
+
+    /// Returns `false` when `path` was not pinned.
+    pub(crate) fn unpin(&mut self, path: &Path) -> bool {
+        let before = self.pins.len();
+        self.pins.retain(|pin| pin != path);
+        self.pins.len() != before
+    }
+
+    pub(crate) fn plan(&self) -> Option<&UpdatePlanArgs> {
+        self.plan.as_ref()
+    }
+
+    pub(crate) fn set_plan(&mut self, plan: UpdatePlanArgs) {
+        self.plan = Some(plan);
+    }
+
+    // Guardrail helpers
+    pub(crate) fn observe_guardrail(
+        &mut self,
+        request_tokens: i64,
+        items: &[ProcessedResponseItem],
+    ) -> Option<GuardrailTrip> {
+        self.guardrail.observe_request(request_tokens, items)
+    }
 }
 
 // Sometimes new snapshots don't include credits
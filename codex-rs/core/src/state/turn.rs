@@ -12,6 +12,7 @@ use codex_protocol::models::ResponseInputItem;
 use tokio::sync::oneshot;
 
 use crate::codex::TurnContext;
+use crate::protocol::PatchHunkDecision;
 use crate::protocol::ReviewDecision;
 use crate::tasks::SessionTask;
 
@@ -67,6 +68,8 @@ impl ActiveTurn {
 #[derive(Default)]
 pub(crate) struct TurnState {
     pending_approvals: HashMap<String, oneshot::Sender<ReviewDecision>>,
+    /// Per-hunk decisions sent with the approval of the same key.
+    pending_hunk_reviews: HashMap<String, Vec<PatchHunkDecision>>,
     pending_input: Vec<ResponseInputItem>,
 }
 
@@ -86,8 +89,17 @@ impl TurnState {
         self.pending_approvals.remove(key)
     }
 
+    pub(crate) fn insert_hunk_review(&mut self, key: String, hunks: Vec<PatchHunkDecision>) {
+        self.pending_hunk_reviews.insert(key, hunks);
+    }
+
+    pub(crate) fn take_hunk_review(&mut self, key: &str) -> Option<Vec<PatchHunkDecision>> {
+        self.pending_hunk_reviews.remove(key)
+    }
+
     pub(crate) fn clear_pending(&mut self) {
         self.pending_approvals.clear();
+        self.pending_hunk_reviews.clear();
         self.pending_input.clear();
     }
 
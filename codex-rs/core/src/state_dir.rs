@@ -0,0 +1,138 @@
+//! Where Codex writes the state it keeps under `CODEX_HOME`: session
+//! rollouts, message history, logs, and the limit history.
+//!
+//! `CODEX_HOME` is normally writable, but containers and locked-down CI
+//! images often mount it read-only. Rather than failing on the first write,
+//! the state moves to a private temporary directory for the rest of the
+//! process; it is lost when that directory is cleaned up. Configuration,
+//! credentials, prompts, and skills are still read from `CODEX_HOME`.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+
+use tracing::warn;
+
+static STATE_DIRS: LazyLock<Mutex<HashMap<PathBuf, StateDir>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, PartialEq)]
+enum StateDir {
+    Home,
+    Fallback(PathBuf),
+}
+
+/// Directory to write state into: `codex_home` itself when it can be
+/// written, otherwise the temporary fallback directory.
+pub fn state_dir(codex_home: &Path) -> PathBuf {
+    match resolve(codex_home) {
+        StateDir::Home => codex_home.to_path_buf(),
+        StateDir::Fallback(dir) => dir,
+    }
+}
+
+/// The temporary directory state is written to because `codex_home` is
+/// read-only, or `None` when `codex_home` is writable.
+pub fn read_only_fallback(codex_home: &Path) -> Option<PathBuf> {
+    match resolve(codex_home) {
+        StateDir::Home => None,
+        StateDir::Fallback(dir) => Some(dir),
+    }
+}
+
+/// The message shown to users when state is not being persisted.
+pub fn read_only_warning(codex_home: &Path, fallback: &Path) -> String {
+    format!(
+        "{} is read-only, so sessions, history, and logs are written to {} and will not be kept. Set CODEX_HOME to a writable directory to keep them.",
+        codex_home.display(),
+        fallback.display()
+    )
+}
+
+fn resolve(codex_home: &Path) -> StateDir {
+    let mut dirs = STATE_DIRS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if let Some(dir) = dirs.get(codex_home) {
+        return dir.clone();
+    }
+
+    let dir = match probe_writable(codex_home) {
+        Ok(()) => StateDir::Home,
+        Err(err) if is_read_only_error(&err) => match create_fallback_dir() {
+            Ok(fallback) => {
+                warn!("{}", read_only_warning(codex_home, &fallback));
+                StateDir::Fallback(fallback)
+            }
+            Err(fallback_err) => {
+                warn!(
+                    "{} is read-only and no temporary directory could be created: {fallback_err}",
+                    codex_home.display()
+                );
+                StateDir::Home
+            }
+        },
+        // Other failures (a full disk, say) are left for the actual write
+        // to report.
+        Err(_) => StateDir::Home,
+    };
+    dirs.insert(codex_home.to_path_buf(), dir.clone());
+    dir
+}
+
+fn probe_writable(codex_home: &Path) -> io::Result<()> {
+    std::fs::create_dir_all(codex_home)?;
+    tempfile::Builder::new()
+        .prefix(".write-test-")
+        .tempfile_in(codex_home)
+        .map(drop)
+}
+
+fn is_read_only_error(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::PermissionDenied | io::ErrorKind::ReadOnlyFilesystem
+    )
+}
+
+/// Creates `codex-home-XXXXXX` in the system temp directory, readable only
+/// by the current user.
+fn create_fallback_dir() -> io::Result<PathBuf> {
+    Ok(tempfile::Builder::new()
+        .prefix("codex-home-")
+        .tempdir()?
+        .keep())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn read_only_home_falls_back_to_temp_dir() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let writable = temp.path().join("writable");
+        assert_eq!(state_dir(&writable), writable);
+        assert_eq!(read_only_fallback(&writable), None);
+
+        let read_only = temp.path().join("read-only");
+        std::fs::create_dir(&read_only).expect("create dir");
+        std::fs::set_permissions(&read_only, std::fs::Permissions::from_mode(0o555))
+            .expect("chmod");
+        // Root ignores directory permissions.
+        if probe_writable(&read_only).is_ok() {
+            return;
+        }
+
+        let fallback = read_only_fallback(&read_only).expect("fallback");
+        assert_ne!(fallback, read_only);
+        assert!(fallback.is_dir());
+        assert_eq!(state_dir(&read_only), fallback);
+        std::fs::remove_dir_all(&fallback).expect("remove fallback");
+    }
+}
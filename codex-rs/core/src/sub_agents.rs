@@ -0,0 +1,465 @@
+//! Sub-agents the model starts with `spawn_agent` to work on a subtask, such
+//! as writing the tests for one module, while it goes on with another. Each
+//! sub-agent is a conversation of its own with a token budget; it can only
+//! read unless the model gives it write access, and its approval requests
+//! go to the user like the session's own. The model collects the reports
+//! with `wait_agents`, and the transcript notes when each sub-agent starts
+//! and what it reported.
+//!
+//! Sub-agents cannot start sub-agents of their own. Approval requests are
+//! answered through the turn that started the sub-agent, so a request made
+//! after that turn ends is declined. Interrupting the turn or ending the
+//! session cancels the sub-agents still running.
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+use std::time::Duration;
+
+use async_channel::Receiver;
+use codex_protocol::protocol::AskForApproval;
+use codex_protocol::protocol::BackgroundEventEvent;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::SandboxPolicy;
+use codex_protocol::protocol::TokenCountEvent;
+use codex_protocol::user_input::UserInput;
+use serde::Deserialize;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::codex_delegate::run_codex_conversation_one_shot;
+use crate::config::Config;
+use crate::features::Feature;
+
+/// Longest task description shown in the transcript.
+const MAX_TITLE_CHARS: usize = 60;
+
+/// What a sub-agent may do.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Access {
+    /// Read files and run commands in a read-only sandbox.
+    #[default]
+    Read,
+    /// Edit the workspace under the session's sandbox and approval policy.
+    Write,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    Finished,
+    OverBudget,
+    Failed(String),
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Report {
+    outcome: Outcome,
+    /// The sub-agent's last message.
+    message: Option<String>,
+    /// Tokens used: uncached input plus output.
+    tokens: i64,
+}
+
+struct SubAgent {
+    id: usize,
+    title: String,
+    cancel: CancellationToken,
+    report: watch::Receiver<Option<Report>>,
+    /// Whether `wait_agents` has returned the report to the model.
+    collected: bool,
+}
+
+/// The session's sub-agents, numbered from 1 in the order they started.
+#[derive(Default)]
+pub(crate) struct SubAgents {
+    agents: Mutex<Vec<SubAgent>>,
+}
+
+impl SubAgents {
+    /// Starts a sub-agent on `task` for the model's turn `turn` and returns
+    /// its number. `max_tokens` is capped by `[sub_agents].max_tokens`.
+    pub(crate) async fn spawn(
+        &self,
+        session: Arc<Session>,
+        turn: Arc<TurnContext>,
+        task: &str,
+        access: Access,
+        max_tokens: Option<i64>,
+    ) -> Result<usize, String> {
+        let limits = turn.client.config().sub_agents.clone();
+        let budget = max_tokens
+            .unwrap_or(limits.max_tokens())
+            .clamp(1, limits.max_tokens());
+
+        // Reserve the slot before starting, so concurrent spawns cannot all
+        // pass the `max_running` check while the conversations start.
+        let title = title(task);
+        let cancel = CancellationToken::new();
+        let (report_tx, report) = watch::channel(None);
+        let id = {
+            let mut agents = self.lock();
+            let running = agents
+                .iter()
+                .filter(|agent| agent.report.borrow().is_none())
+                .count();
+            if running >= limits.max_running() {
+                return Err(format!(
+                    "{running} sub-agents are already running, the most allowed; wait for one with wait_agents first"
+                ));
+            }
+            let id = agents.last().map_or(1, |agent| agent.id + 1);
+            agents.push(SubAgent {
+                id,
+                title: title.clone(),
+                cancel: cancel.clone(),
+                report,
+                collected: false,
+            });
+            id
+        };
+
+        let started = run_codex_conversation_one_shot(
+            sub_agent_config(&turn, access),
+            Arc::clone(&session.services.auth_manager),
+            vec![UserInput::Text {
+                text: prompt(task, access),
+            }],
+            Arc::clone(&session),
+            Arc::clone(&turn),
+            cancel.clone(),
+            None,
+        )
+        .await;
+        let io = match started {
+            Ok(io) => io,
+            Err(err) => {
+                self.lock().retain(|agent| agent.id != id);
+                return Err(format!("failed to start a sub-agent: {err}"));
+            }
+        };
+        session
+            .send_event(
+                &turn,
+                EventMsg::BackgroundEvent(BackgroundEventEvent {
+                    message: format!("Started agent {id}: {title}"),
+                }),
+            )
+            .await;
+
+        tokio::spawn(async move {
+            let report = drive(io.rx_event, &cancel, budget).await;
+            session
+                .send_event(
+                    &turn,
+                    EventMsg::BackgroundEvent(BackgroundEventEvent {
+                        message: render_report(id, &title, &report),
+                    }),
+                )
+                .await;
+            let _ = report_tx.send(Some(report));
+        });
+        Ok(id)
+    }
+
+    /// Waits up to `timeout` for the sub-agents `ids`, or for every one
+    /// whose report the model has not seen, and describes how each did.
+    pub(crate) async fn wait(
+        &self,
+        ids: Option<Vec<usize>>,
+        timeout: Duration,
+    ) -> Result<String, String> {
+        let waiting: Vec<(usize, watch::Receiver<Option<Report>>)> = {
+            let agents = self.lock();
+            match ids {
+                Some(ids) => ids
+                    .into_iter()
+                    .map(|id| {
+                        agents
+                            .iter()
+                            .find(|agent| agent.id == id)
+                            .map(|agent| (id, agent.report.clone()))
+                            .ok_or_else(|| format!("there is no agent {id}"))
+                    })
+                    .collect::<Result<_, _>>()?,
+                None => agents
+                    .iter()
+                    .filter(|agent| !agent.collected)
+                    .map(|agent| (agent.id, agent.report.clone()))
+                    .collect(),
+            }
+        };
+        if waiting.is_empty() {
+            return Ok("No sub-agents are running or waiting to report.".to_string());
+        }
+
+        let _ = tokio::time::timeout(
+            timeout,
+            futures::future::join_all(waiting.iter().map(|(_, report)| {
+                let mut report = report.clone();
+                async move {
+                    let _ = report.wait_for(Option::is_some).await;
+                }
+            })),
+        )
+        .await;
+
+        let mut agents = self.lock();
+        let mut output = Vec::new();
+        for (id, _) in waiting {
+            let Some(agent) = agents.iter_mut().find(|agent| agent.id == id) else {
+                continue;
+            };
+            let report = agent.report.borrow().clone();
+            match report {
+                Some(report) => {
+                    agent.collected = true;
+                    output.push(render_report(id, &agent.title, &report));
+                }
+                None => output.push(format!("Agent {id} ({}) is still running.", agent.title)),
+            }
+        }
+        Ok(output.join("\n\n"))
+    }
+
+    /// Cancels every sub-agent still running.
+    pub(crate) fn cancel_all(&self) {
+        for agent in self.lock().iter() {
+            if agent.report.borrow().is_none() {
+                agent.cancel.cancel();
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<SubAgent>> {
+        self.agents.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// The session's configuration for the turn, scoped down for a sub-agent.
+fn sub_agent_config(turn: &TurnContext, access: Access) -> Config {
+    let mut config = turn.client.config().as_ref().clone();
+    config.cwd = turn.cwd.clone();
+    match access {
+        Access::Read => {
+            config.sandbox_policy = SandboxPolicy::ReadOnly;
+            config.approval_policy = AskForApproval::Never;
+        }
+        Access::Write => {
+            config.sandbox_policy = turn.sandbox_policy.clone();
+            config.approval_policy = turn.approval_policy;
+        }
+    }
+    // The session commits and notifies for its own turns, which include
+    // whatever the sub-agent did.
+    config
+        .features
+        .disable(Feature::SubAgents)
+        .disable(Feature::AutoCommit);
+    config.notify = None;
+    config
+}
+
+fn prompt(task: &str, access: Access) -> String {
+    let access = match access {
+        Access::Read => "You can read files and run commands, but not change anything.",
+        Access::Write => {
+            "You can change files in the workspace. Other agents may be working in it at the same time, so change only what your task needs."
+        }
+    };
+    format!(
+        "You are a sub-agent: another agent handed you one part of its work and is carrying on with the rest. Your task:\n\n{task}\n\n{access} Work only on this task. When you are done, reply with a short report of what you did, the files you changed, and anything left undone; the other agent sees only that report."
+    )
+}
+
+fn title(task: &str) -> String {
+    let line = task.trim().lines().next().unwrap_or_default().trim();
+    if line.chars().count() > MAX_TITLE_CHARS {
+        let mut title: String = line.chars().take(MAX_TITLE_CHARS - 1).collect();
+        title.push('…');
+        title
+    } else {
+        line.to_string()
+    }
+}
+
+/// Follows a sub-agent's events until it finishes, is cancelled, or uses
+/// up its budget.
+async fn drive(events: Receiver<Event>, cancel: &CancellationToken, budget: i64) -> Report {
+    let mut progress = Progress::default();
+    loop {
+        let event = tokio::select! {
+            _ = cancel.cancelled() => return progress.report(Outcome::Cancelled),
+            event = events.recv() => event,
+        };
+        let Ok(event) = event else {
+            return progress.report(Outcome::Failed("it stopped without finishing".to_string()));
+        };
+        if let Some(outcome) = progress.observe(event.msg, budget) {
+            if outcome == Outcome::OverBudget {
+                cancel.cancel();
+            }
+            return progress.report(outcome);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Progress {
+    tokens: i64,
+    last_message: Option<String>,
+    error: Option<String>,
+}
+
+impl Progress {
+    /// Records `msg`, returning how the sub-agent ended if it did.
+    fn observe(&mut self, msg: EventMsg, budget: i64) -> Option<Outcome> {
+        match msg {
+            EventMsg::TokenCount(TokenCountEvent {
+                info: Some(info), ..
+            }) => {
+                self.tokens = info.total_token_usage.blended_total();
+                (self.tokens >= budget).then_some(Outcome::OverBudget)
+            }
+            EventMsg::AgentMessage(event) => {
+                self.last_message = Some(event.message);
+                None
+            }
+            EventMsg::Error(event) => {
+                self.error = Some(event.message);
+                None
+            }
+            EventMsg::TaskComplete(event) => {
+                if event.last_agent_message.is_some() {
+                    self.last_message = event.last_agent_message;
+                }
+                Some(match (&self.last_message, self.error.take()) {
+                    (None, Some(error)) => Outcome::Failed(error),
+                    _ => Outcome::Finished,
+                })
+            }
+            EventMsg::TurnAborted(_) => Some(Outcome::Cancelled),
+            _ => None,
+        }
+    }
+
+    fn report(self, outcome: Outcome) -> Report {
+        Report {
+            outcome,
+            message: self.last_message,
+            tokens: self.tokens,
+        }
+    }
+}
+
+fn render_report(id: usize, title: &str, report: &Report) -> String {
+    let tokens = report.tokens;
+    let summary = match &report.outcome {
+        Outcome::Finished => format!("Agent {id} ({title}) finished, using {tokens} tokens."),
+        Outcome::OverBudget => {
+            format!("Agent {id} ({title}) was stopped after using {tokens} tokens, its budget.")
+        }
+        Outcome::Failed(err) => format!("Agent {id} ({title}) failed: {err}"),
+        Outcome::Cancelled => format!("Agent {id} ({title}) was cancelled."),
+    };
+    match (&report.outcome, &report.message) {
+        (Outcome::Finished, Some(message)) => format!("{summary} Its report:\n{message}"),
+        (Outcome::Finished, None) => format!("{summary} It did not report anything."),
+        (_, Some(message)) => format!("{summary} Its last message:\n{message}"),
+        (_, None) => summary,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::AgentMessageEvent;
+    use codex_protocol::protocol::TaskCompleteEvent;
+    use codex_protocol::protocol::TokenUsage;
+    use codex_protocol::protocol::TokenUsageInfo;
+    use pretty_assertions::assert_eq;
+
+    fn token_count(input: i64, cached: i64, output: i64) -> EventMsg {
+        let usage = TokenUsage {
+            input_tokens: input,
+            cached_input_tokens: cached,
+            output_tokens: output,
+            reasoning_output_tokens: 0,
+            total_tokens: input + output,
+        };
+        EventMsg::TokenCount(TokenCountEvent {
+            info: Some(TokenUsageInfo {
+                total_token_usage: usage.clone(),
+                last_token_usage: usage,
+                model_context_window: None,
+            }),
+            rate_limits: None,
+            cost: None,
+        })
+    }
+
+    #[test]
+    fn sub_agents_report_or_stop_at_their_budget() {
+        let mut progress = Progress::default();
+        assert_eq!(
+            progress.observe(token_count(6_000, 2_000, 1_000), 10_000),
+            None
+        );
+        assert_eq!(
+            progress.observe(
+                EventMsg::AgentMessage(AgentMessageEvent {
+                    message: "Reading src/a.rs".to_string(),
+                }),
+                10_000,
+            ),
+            None
+        );
+        assert_eq!(
+            progress.observe(
+                EventMsg::TaskComplete(TaskCompleteEvent {
+                    last_agent_message: Some("Added 3 tests to src/a.rs.".to_string()),
+                }),
+                10_000,
+            ),
+            Some(Outcome::Finished)
+        );
+        assert_eq!(
+            render_report(
+                1,
+                "write tests for module A",
+                &progress.report(Outcome::Finished)
+            ),
+            "Agent 1 (write tests for module A) finished, using 5000 tokens. Its report:\nAdded 3 tests to src/a.rs."
+        );
+
+        let mut progress = Progress::default();
+        assert_eq!(
+            progress.observe(token_count(12_000, 0, 500), 10_000),
+            Some(Outcome::OverBudget)
+        );
+        assert_eq!(
+            render_report(
+                2,
+                "refactor module B",
+                &progress.report(Outcome::OverBudget)
+            ),
+            "Agent 2 (refactor module B) was stopped after using 12500 tokens, its budget."
+        );
+    }
+
+    #[test]
+    fn titles_are_the_first_line_shortened() {
+        assert_eq!(
+            title("  Write tests for module A\nCover the error paths too."),
+            "Write tests for module A"
+        );
+        let long = title(&"word ".repeat(30));
+        assert_eq!(long.chars().count(), MAX_TITLE_CHARS);
+        assert!(long.ends_with('…'));
+    }
+}
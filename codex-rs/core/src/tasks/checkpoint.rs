@@ -0,0 +1,124 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::checkpoints::Checkpoint;
+use crate::checkpoints::checkpoint_ref;
+use crate::checkpoints::save_checkpoint;
+use crate::codex::TurnContext;
+use crate::conversation_manager::user_message_positions;
+use crate::protocol::CheckpointCreatedEvent;
+use crate::protocol::CodexErrorInfo;
+use crate::protocol::ErrorEvent;
+use crate::protocol::EventMsg;
+use crate::rollout::RolloutRecorder;
+use crate::state::TaskKind;
+use crate::tasks::SessionTask;
+use crate::tasks::SessionTaskContext;
+use async_trait::async_trait;
+use codex_git::CreateGhostCommitOptions;
+use codex_git::GhostCommit;
+use codex_git::GitToolingError;
+use codex_git::create_ghost_commit;
+use codex_git::pin_ghost_commit;
+use codex_protocol::user_input::UserInput;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+use tracing::warn;
+
+pub(crate) struct CheckpointTask {
+    name: String,
+    codex_home: PathBuf,
+}
+
+impl CheckpointTask {
+    pub(crate) fn new(name: String, codex_home: PathBuf) -> Self {
+        Self { name, codex_home }
+    }
+
+    async fn create(
+        &self,
+        session: &SessionTaskContext,
+        ctx: &TurnContext,
+    ) -> Result<CheckpointCreatedEvent, String> {
+        let sess = session.clone_session();
+        // The turn count comes from the rollout, as `codex restore` forks it.
+        sess.flush_rollout().await;
+        let Some(rollout_path) = sess.rollout_path().await else {
+            return Err("this session is not being recorded".to_string());
+        };
+        let items = RolloutRecorder::get_rollout_history(&rollout_path)
+            .await
+            .map_err(|err| format!("failed to read the session: {err}"))?
+            .get_rollout_items();
+        let turns = user_message_positions(&items).len();
+
+        let repo_path = ctx.cwd.clone();
+        let ref_name = checkpoint_ref(&self.name);
+        let ghost_commit = tokio::task::spawn_blocking(move || snapshot(&repo_path, &ref_name))
+            .await
+            .map_err(|err| format!("failed to snapshot the working tree: {err}"))?
+            .map_err(|err| format!("failed to snapshot the working tree: {err}"))?;
+
+        let checkpoint = Checkpoint {
+            name: self.name.clone(),
+            conversation_id: sess.conversation_id,
+            rollout_path,
+            turns,
+            cwd: ctx.cwd.clone(),
+            ghost_commit,
+            created_at: chrono::Utc::now().timestamp(),
+        };
+        save_checkpoint(&self.codex_home, &checkpoint)
+            .map_err(|err| format!("failed to save checkpoint: {err}"))?;
+        info!(name = self.name, turns, "checkpoint saved");
+        Ok(CheckpointCreatedEvent {
+            name: checkpoint.name,
+            turns,
+            snapshot: checkpoint.ghost_commit.is_some(),
+        })
+    }
+}
+
+/// Captures and pins a ghost commit of `repo_path`; `None` outside a git
+/// repository, where the checkpoint covers the conversation only.
+fn snapshot(repo_path: &Path, ref_name: &str) -> Result<Option<GhostCommit>, GitToolingError> {
+    let ghost_commit = match create_ghost_commit(&CreateGhostCommitOptions::new(repo_path)) {
+        Ok(ghost_commit) => ghost_commit,
+        Err(GitToolingError::NotAGitRepository { .. }) => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    pin_ghost_commit(repo_path, ref_name, &ghost_commit)?;
+    Ok(Some(ghost_commit))
+}
+
+#[async_trait]
+impl SessionTask for CheckpointTask {
+    fn kind(&self) -> TaskKind {
+        TaskKind::Regular
+    }
+
+    async fn run(
+        self: Arc<Self>,
+        session: Arc<SessionTaskContext>,
+        ctx: Arc<TurnContext>,
+        _input: Vec<UserInput>,
+        cancellation_token: CancellationToken,
+    ) -> Option<String> {
+        if cancellation_token.is_cancelled() {
+            return None;
+        }
+        let msg = match self.create(&session, &ctx).await {
+            Ok(created) => EventMsg::CheckpointCreated(created),
+            Err(message) => {
+                warn!("failed to create checkpoint {}: {message}", self.name);
+                EventMsg::Error(ErrorEvent {
+                    message: format!("Failed to create checkpoint {}: {message}", self.name),
+                    codex_error_info: Some(CodexErrorInfo::Other),
+                })
+            }
+        };
+        session.clone_session().send_event(ctx.as_ref(), msg).await;
+        None
+    }
+}
@@ -1,3 +1,4 @@
+mod checkpoint;
 mod compact;
 mod ghost_snapshot;
 mod regular;
@@ -28,11 +29,14 @@ use crate::state::RunningTask;
 use crate::state::TaskKind;
 use codex_protocol::user_input::UserInput;
 
+pub(crate) use checkpoint::CheckpointTask;
 pub(crate) use compact::CompactTask;
 pub(crate) use ghost_snapshot::GhostSnapshotTask;
 pub(crate) use regular::RegularTask;
 pub(crate) use review::ReviewTask;
 pub(crate) use undo::UndoTask;
+pub(crate) use undo::plan_undo;
+pub(crate) use undo::undo_message;
 pub(crate) use user_shell::UserShellCommandTask;
 
 const GRACEFULL_INTERRUPTION_TIMEOUT_MS: u64 = 100;
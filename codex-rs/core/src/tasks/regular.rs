@@ -3,13 +3,16 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use tokio_util::sync::CancellationToken;
 
+use crate::auto_commit;
 use crate::codex::TurnContext;
 use crate::codex::run_task;
+use crate::features::Feature;
 use crate::state::TaskKind;
 use codex_protocol::user_input::UserInput;
 
 use super::SessionTask;
 use super::SessionTaskContext;
+use super::undo;
 
 #[derive(Clone, Copy, Default)]
 pub(crate) struct RegularTask;
@@ -28,6 +31,53 @@ impl SessionTask for RegularTask {
         cancellation_token: CancellationToken,
     ) -> Option<String> {
         let sess = session.clone_session();
-        run_task(sess, ctx, input, cancellation_token).await
+        if !sess.enabled(Feature::AutoCommit) {
+            return run_task(sess, ctx, input, cancellation_token).await;
+        }
+
+        let prompt = input
+            .iter()
+            .filter_map(|item| match item {
+                UserInput::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        auto_commit::ensure_task_branch(&sess, &ctx, &prompt).await;
+        let last_agent_message = run_task(
+            Arc::clone(&sess),
+            Arc::clone(&ctx),
+            input,
+            cancellation_token.clone(),
+        )
+        .await;
+        if !cancellation_token.is_cancelled() {
+            auto_commit::commit_turn(&sess, &ctx, &prompt, last_agent_message.as_deref()).await;
+        }
+        last_agent_message
+    }
+
+    async fn abort(&self, session: Arc<SessionTaskContext>, ctx: Arc<TurnContext>) {
+        let sess = session.clone_session();
+        if sess.enabled(Feature::AutoCommit) {
+            auto_commit::commit_interrupted_turn(&sess, &ctx).await;
+            return;
+        }
+
+        // Without auto-commit, put the work tree back the way the turn found
+        // it rather than leave a half-finished edit behind.
+        let history = sess.clone_history().await.get_history();
+        let Some((ghost_commit, remaining)) = undo::plan_revert_current_turn(&history) else {
+            return;
+        };
+        let short_id: String = ghost_commit.id().chars().take(7).collect();
+        let message = match undo::restore_turn(&sess, &ctx, ghost_commit, remaining).await {
+            Ok(reverted) => undo::with_reverted_files(
+                format!("Interrupted turn reverted to snapshot {short_id}."),
+                &reverted,
+            ),
+            Err(err) => format!("Could not revert the interrupted turn: {err}"),
+        };
+        sess.notify_background_event(&ctx, message).await;
     }
 }
@@ -6,16 +6,19 @@ use codex_protocol::models::ContentItem;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::protocol::AgentMessageContentDeltaEvent;
 use codex_protocol::protocol::AgentMessageDeltaEvent;
+use codex_protocol::protocol::BackgroundEventEvent;
 use codex_protocol::protocol::Event;
 use codex_protocol::protocol::EventMsg;
 use codex_protocol::protocol::ExitedReviewModeEvent;
 use codex_protocol::protocol::ItemCompletedEvent;
 use codex_protocol::protocol::ReviewOutputEvent;
+use codex_protocol::protocol::ReviewTarget;
 use tokio_util::sync::CancellationToken;
 
 use crate::codex::Session;
 use crate::codex::TurnContext;
 use crate::codex_delegate::run_codex_conversation_one_shot;
+use crate::review_cache::ReviewCache;
 use crate::review_format::format_review_findings_block;
 use crate::review_format::render_review_output_text;
 use crate::state::TaskKind;
@@ -24,12 +27,14 @@ use codex_protocol::user_input::UserInput;
 use super::SessionTask;
 use super::SessionTaskContext;
 
-#[derive(Clone, Copy)]
-pub(crate) struct ReviewTask;
+#[derive(Clone)]
+pub(crate) struct ReviewTask {
+    target: ReviewTarget,
+}
 
 impl ReviewTask {
-    pub(crate) fn new() -> Self {
-        Self
+    pub(crate) fn new(target: ReviewTarget) -> Self {
+        Self { target }
     }
 }
 
@@ -46,6 +51,34 @@ impl SessionTask for ReviewTask {
         input: Vec<UserInput>,
         cancellation_token: CancellationToken,
     ) -> Option<String> {
+        let prompt = input
+            .iter()
+            .filter_map(|item| match item {
+                UserInput::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let cache =
+            ReviewCache::for_review(&ctx.client.config(), &ctx.cwd, &self.target, &prompt).await;
+        let cached = match &cache {
+            Some(cache) => cache.load().await,
+            None => None,
+        };
+        if let Some(cached) = cached {
+            session
+                .clone_session()
+                .send_event(
+                    ctx.as_ref(),
+                    EventMsg::BackgroundEvent(BackgroundEventEvent {
+                        message: "Reusing the cached review of these changes.".to_string(),
+                    }),
+                )
+                .await;
+            exit_review_mode(session.clone_session(), Some(cached), ctx.clone()).await;
+            return None;
+        }
+
         // Start sub-codex conversation and get the receiver for events.
         let output = match start_review_conversation(
             session.clone(),
@@ -59,6 +92,9 @@ impl SessionTask for ReviewTask {
             None => None,
         };
         if !cancellation_token.is_cancelled() {
+            if let (Some(cache), Some(output)) = (&cache, &output) {
+                cache.store(output).await;
+            }
             exit_review_mode(session.clone_session(), output.clone(), ctx.clone()).await;
         }
         None
@@ -82,11 +118,12 @@ async fn start_review_conversation(
     // Avoid loading project docs; reviewer only needs findings
     sub_agent_config.project_doc_max_bytes = 0;
     // Carry over review-only feature restrictions so the delegate cannot
-    // re-enable blocked tools (web search, view image).
+    // re-enable blocked tools (web search, view image, sub-agents).
     sub_agent_config
         .features
         .disable(crate::features::Feature::WebSearchRequest)
-        .disable(crate::features::Feature::ViewImageTool);
+        .disable(crate::features::Feature::ViewImageTool)
+        .disable(crate::features::Feature::SubAgents);
 
     // Set explicit review rubric for the sub-agent
     sub_agent_config.base_instructions = Some(crate::REVIEW_PROMPT.to_string());
@@ -1,6 +1,9 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
+use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::event_mapping::parse_turn_item;
 use crate::protocol::EventMsg;
 use crate::protocol::UndoCompletedEvent;
 use crate::protocol::UndoStartedEvent;
@@ -8,8 +11,13 @@ use crate::state::TaskKind;
 use crate::tasks::SessionTask;
 use crate::tasks::SessionTaskContext;
 use async_trait::async_trait;
+use codex_git::GhostCommit;
+use codex_git::ghost_commit_changed_paths;
 use codex_git::restore_ghost_commit;
+use codex_protocol::items::TurnItem;
 use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::CompactedItem;
+use codex_protocol::protocol::RolloutItem;
 use codex_protocol::user_input::UserInput;
 use tokio_util::sync::CancellationToken;
 use tracing::error;
@@ -18,6 +26,125 @@ use tracing::warn;
 
 pub(crate) struct UndoTask;
 
+/// Most file names listed in an undo message.
+const MAX_LISTED_PATHS: usize = 5;
+
+/// The latest turn of `history` that can be undone: the snapshot taken when
+/// it started, and the history without that turn. The turn begins at the
+/// user message recorded just before the snapshot.
+pub(crate) fn plan_undo(history: &[ResponseItem]) -> Option<(GhostCommit, Vec<ResponseItem>)> {
+    let (snapshot_idx, ghost_commit) =
+        history
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(idx, item)| match item {
+                ResponseItem::GhostSnapshot { ghost_commit } => Some((idx, ghost_commit.clone())),
+                _ => None,
+            })?;
+    let turn_start = history[..snapshot_idx]
+        .iter()
+        .rposition(is_user_message)
+        .unwrap_or(snapshot_idx);
+    Some((ghost_commit, history[..turn_start].to_vec()))
+}
+
+/// The snapshot taken when the latest turn started, if that turn is still
+/// the last one in `history`, and the history without it. Unlike
+/// [`plan_undo`], a snapshot from an earlier turn is never returned, so a
+/// turn that took none leaves the earlier turns alone.
+pub(crate) fn plan_revert_current_turn(
+    history: &[ResponseItem],
+) -> Option<(GhostCommit, Vec<ResponseItem>)> {
+    let snapshot_idx = history
+        .iter()
+        .rposition(|item| matches!(item, ResponseItem::GhostSnapshot { .. }))?;
+    if history[snapshot_idx + 1..].iter().any(is_user_message) {
+        return None;
+    }
+    plan_undo(history)
+}
+
+fn is_user_message(item: &ResponseItem) -> bool {
+    matches!(item, ResponseItem::Message { .. })
+        && matches!(parse_turn_item(item), Some(TurnItem::UserMessage(_)))
+}
+
+/// Restores `ghost_commit` in the turn's directory and replaces the session
+/// history with `remaining`, returning the files that were reverted.
+pub(crate) async fn restore_turn(
+    sess: &Session,
+    ctx: &TurnContext,
+    ghost_commit: GhostCommit,
+    remaining: Vec<ResponseItem>,
+) -> Result<Vec<PathBuf>, String> {
+    let commit_id = ghost_commit.id().to_string();
+    let repo_path = ctx.cwd.clone();
+    let restore_result = tokio::task::spawn_blocking(move || {
+        // Listing the changes is best effort; the restore is what matters.
+        let reverted =
+            ghost_commit_changed_paths(&repo_path, &ghost_commit).unwrap_or_else(|err| {
+                warn!("failed to list files changed since snapshot: {err}");
+                Vec::new()
+            });
+        restore_ghost_commit(&repo_path, &ghost_commit).map(|()| reverted)
+    })
+    .await;
+
+    match restore_result {
+        Ok(Ok(reverted)) => {
+            sess.replace_history(remaining.clone()).await;
+            // Record the trimmed history so a resumed session matches.
+            sess.persist_rollout_items(&[RolloutItem::Compacted(CompactedItem {
+                message: String::new(),
+                replacement_history: Some(remaining),
+            })])
+            .await;
+            Ok(reverted)
+        }
+        Ok(Err(err)) => {
+            let message = format!("Failed to restore snapshot {commit_id}: {err}");
+            warn!("{message}");
+            Err(message)
+        }
+        Err(err) => {
+            let message = format!("Failed to restore snapshot {commit_id}: {err}");
+            error!("{message}");
+            Err(message)
+        }
+    }
+}
+
+/// Outcome of a successful undo for `short_id`, naming the reverted files.
+pub(crate) fn undo_message(short_id: &str, reverted: &[PathBuf]) -> String {
+    with_reverted_files(
+        format!("Undo restored snapshot {short_id} and removed the last turn."),
+        reverted,
+    )
+}
+
+/// `message` followed by the names of the reverted files, if any.
+pub(crate) fn with_reverted_files(mut message: String, reverted: &[PathBuf]) -> String {
+    if reverted.is_empty() {
+        return message;
+    }
+    let mut names: Vec<String> = reverted
+        .iter()
+        .take(MAX_LISTED_PATHS)
+        .map(|path| path.display().to_string())
+        .collect();
+    if reverted.len() > MAX_LISTED_PATHS {
+        names.push(format!("{} more", reverted.len() - MAX_LISTED_PATHS));
+    }
+    let plural = if reverted.len() == 1 { "" } else { "s" };
+    message.push_str(&format!(
+        " Reverted {} file{plural}: {}.",
+        reverted.len(),
+        names.join(", ")
+    ));
+    message
+}
+
 impl UndoTask {
     pub(crate) fn new() -> Self {
         Self
@@ -59,24 +186,13 @@ impl SessionTask for UndoTask {
         }
 
         let mut history = sess.clone_history().await;
-        let mut items = history.get_history();
+        let items = history.get_history();
         let mut completed = UndoCompletedEvent {
             success: false,
             message: None,
         };
 
-        let Some((idx, ghost_commit)) =
-            items
-                .iter()
-                .enumerate()
-                .rev()
-                .find_map(|(idx, item)| match item {
-                    ResponseItem::GhostSnapshot { ghost_commit } => {
-                        Some((idx, ghost_commit.clone()))
-                    }
-                    _ => None,
-                })
-        else {
+        let Some((ghost_commit, remaining)) = plan_undo(&items) else {
             completed.message = Some("No ghost snapshot available to undo.".to_string());
             sess.send_event(ctx.as_ref(), EventMsg::UndoCompleted(completed))
                 .await;
@@ -84,30 +200,14 @@ impl SessionTask for UndoTask {
         };
 
         let commit_id = ghost_commit.id().to_string();
-        let repo_path = ctx.cwd.clone();
-        let restore_result =
-            tokio::task::spawn_blocking(move || restore_ghost_commit(&repo_path, &ghost_commit))
-                .await;
-
-        match restore_result {
-            Ok(Ok(())) => {
-                items.remove(idx);
-                sess.replace_history(items).await;
+        match restore_turn(&sess, ctx.as_ref(), ghost_commit, remaining).await {
+            Ok(reverted) => {
                 let short_id: String = commit_id.chars().take(7).collect();
                 info!(commit_id = commit_id, "Undo restored ghost snapshot");
                 completed.success = true;
-                completed.message = Some(format!("Undo restored snapshot {short_id}."));
-            }
-            Ok(Err(err)) => {
-                let message = format!("Failed to restore snapshot {commit_id}: {err}");
-                warn!("{message}");
-                completed.message = Some(message);
-            }
-            Err(err) => {
-                let message = format!("Failed to restore snapshot {commit_id}: {err}");
-                error!("{message}");
-                completed.message = Some(message);
+                completed.message = Some(undo_message(&short_id, &reverted));
             }
+            Err(message) => completed.message = Some(message),
         }
 
         sess.send_event(ctx.as_ref(), EventMsg::UndoCompleted(completed))
@@ -115,3 +215,55 @@ impl SessionTask for UndoTask {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::ContentItem;
+    use pretty_assertions::assert_eq;
+
+    fn user_message(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    fn snapshot(id: &str) -> ResponseItem {
+        ResponseItem::GhostSnapshot {
+            ghost_commit: GhostCommit::new(id.to_string(), None, Vec::new(), Vec::new()),
+        }
+    }
+
+    #[test]
+    fn only_the_current_turn_snapshot_is_reverted() {
+        let history = vec![user_message("first"), snapshot("aaa")];
+        let (ghost_commit, remaining) = plan_revert_current_turn(&history).expect("plan");
+        assert_eq!(ghost_commit.id(), "aaa");
+        assert_eq!(remaining, Vec::new());
+
+        // The second turn took no snapshot, so there is nothing to revert.
+        let history = vec![
+            user_message("first"),
+            snapshot("aaa"),
+            user_message("second"),
+        ];
+        assert_eq!(plan_revert_current_turn(&history), None);
+    }
+
+    #[test]
+    fn undo_message_names_reverted_files() {
+        assert_eq!(
+            undo_message("abc1234", &[]),
+            "Undo restored snapshot abc1234 and removed the last turn."
+        );
+        let reverted: Vec<PathBuf> = (1..=7).map(|n| PathBuf::from(format!("f{n}.rs"))).collect();
+        assert_eq!(
+            undo_message("abc1234", &reverted),
+            "Undo restored snapshot abc1234 and removed the last turn. Reverted 7 files: f1.rs, f2.rs, f3.rs, f4.rs, f5.rs, 2 more."
+        );
+    }
+}
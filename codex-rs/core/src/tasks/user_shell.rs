@@ -59,6 +59,7 @@ impl SessionTask for UserShellCommandTask {
     ) -> Option<String> {
         let event = EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: turn_context.client.get_model_context_window(),
+            auth: None,
         });
         let session = session.clone_session();
         session.send_event(turn_context.as_ref(), event).await;
@@ -76,6 +77,14 @@ impl SessionTask for UserShellCommandTask {
         let cwd = turn_context.cwd.clone();
 
         let parsed_cmd = parse_command(&command);
+        session
+            .audit_command(
+                turn_context.as_ref(),
+                &command,
+                &cwd,
+                ExecCommandSource::UserShell,
+            )
+            .await;
         session
             .send_event(
                 turn_context.as_ref(),
@@ -153,6 +162,7 @@ impl SessionTask for UserShellCommandTask {
                             exit_code: -1,
                             duration: Duration::ZERO,
                             formatted_output: aborted_message,
+                            sandbox_denied: false,
                         }),
                     )
                     .await;
@@ -179,15 +189,15 @@ impl SessionTask for UserShellCommandTask {
                                 &output,
                                 turn_context.truncation_policy,
                             ),
+                            sandbox_denied: false,
                         }),
                     )
                     .await;
 
-                let output_items = [user_shell_command_record_item(
-                    &raw_command,
-                    &output,
-                    &turn_context,
-                )];
+                let item = user_shell_command_record_item(&raw_command, &output, &turn_context);
+                let output_items = [session
+                    .redact_item(turn_context.as_ref(), "user shell", item)
+                    .await];
                 session
                     .record_conversation_items(turn_context.as_ref(), &output_items)
                     .await;
@@ -224,6 +234,7 @@ impl SessionTask for UserShellCommandTask {
                                 &exec_output,
                                 turn_context.truncation_policy,
                             ),
+                            sandbox_denied: false,
                         }),
                     )
                     .await;
@@ -114,6 +114,23 @@ pub fn parse_id_token(id_token: &str) -> Result<IdTokenInfo, IdTokenInfoError> {
     }
 }
 
+/// The `exp` claim (Unix seconds) of a JWT such as the access token. The
+/// signature is not checked; this is only used to schedule refreshes.
+pub(crate) fn jwt_expiry(jwt: &str) -> Option<i64> {
+    #[derive(Deserialize)]
+    struct ExpiryClaims {
+        exp: Option<i64>,
+    }
+
+    let payload_b64 = jwt.split('.').nth(1).filter(|p| !p.is_empty())?;
+    let payload_bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload_b64)
+        .ok()?;
+    serde_json::from_slice::<ExpiryClaims>(&payload_bytes)
+        .ok()?
+        .exp
+}
+
 fn deserialize_id_token<'de, D>(deserializer: D) -> Result<IdTokenInfo, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -192,4 +209,26 @@ mod tests {
         assert!(info.email.is_none());
         assert!(info.get_chatgpt_plan_type().is_none());
     }
+
+    #[test]
+    fn jwt_expiry_reads_the_exp_claim() {
+        fn b64url_no_pad(bytes: &[u8]) -> String {
+            base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+        }
+
+        let header_b64 = b64url_no_pad(br#"{"alg":"none","typ":"JWT"}"#);
+        let with_exp = b64url_no_pad(br#"{"sub":"123","exp":1700000000}"#);
+        let without_exp = b64url_no_pad(br#"{"sub":"123"}"#);
+        let signature_b64 = b64url_no_pad(b"sig");
+
+        assert_eq!(
+            jwt_expiry(&format!("{header_b64}.{with_exp}.{signature_b64}")),
+            Some(1_700_000_000)
+        );
+        assert_eq!(
+            jwt_expiry(&format!("{header_b64}.{without_exp}.{signature_b64}")),
+            None
+        );
+        assert_eq!(jwt_expiry("Access Token"), None);
+    }
 }
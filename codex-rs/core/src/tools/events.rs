@@ -55,6 +55,8 @@ pub(crate) enum ToolEventStage {
 
 pub(crate) enum ToolEventFailure {
     Output(ExecToolCallOutput),
+    /// The sandbox blocked the command.
+    SandboxDenied(ExecToolCallOutput),
     Message(String),
 }
 
@@ -67,6 +69,11 @@ pub(crate) async fn emit_exec_command_begin(
     interaction_input: Option<String>,
     process_id: Option<&str>,
 ) {
+    if source != ExecCommandSource::UnifiedExecInteraction {
+        ctx.session
+            .audit_command(ctx.turn, command, cwd, source)
+            .await;
+    }
     ctx.session
         .send_event(
             ctx.turn,
@@ -203,7 +210,9 @@ impl ToolEmitter {
             }
             (
                 Self::ApplyPatch { changes, .. },
-                ToolEventStage::Failure(ToolEventFailure::Output(output)),
+                ToolEventStage::Failure(
+                    ToolEventFailure::Output(output) | ToolEventFailure::SandboxDenied(output),
+                ),
             ) => {
                 emit_patch_end(
                     ctx,
@@ -289,13 +298,18 @@ impl ToolEmitter {
                 };
                 (event, result)
             }
-            Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Timeout { output })))
-            | Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Denied { output }))) => {
+            Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Timeout { output }))) => {
                 let response = self.format_exec_output_for_model(&output, ctx);
                 let event = ToolEventStage::Failure(ToolEventFailure::Output(*output));
                 let result = Err(FunctionCallError::RespondToModel(response));
                 (event, result)
             }
+            Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Denied { output }))) => {
+                let response = self.format_exec_output_for_model(&output, ctx);
+                let event = ToolEventStage::Failure(ToolEventFailure::SandboxDenied(*output));
+                let result = Err(FunctionCallError::RespondToModel(response));
+                (event, result)
+            }
             Err(ToolError::Codex(err)) => {
                 let message = format!("execution error: {err:?}");
                 let event = ToolEventStage::Failure(ToolEventFailure::Message(message.clone()));
@@ -356,6 +370,7 @@ struct ExecCommandResult {
     exit_code: i32,
     duration: Duration,
     formatted_output: String,
+    sandbox_denied: bool,
 }
 
 async fn emit_exec_stage(
@@ -363,6 +378,10 @@ async fn emit_exec_stage(
     exec_input: ExecCommandInput<'_>,
     stage: ToolEventStage,
 ) {
+    let sandbox_denied = matches!(
+        stage,
+        ToolEventStage::Failure(ToolEventFailure::SandboxDenied(_))
+    );
     match stage {
         ToolEventStage::Begin => {
             emit_exec_command_begin(
@@ -377,7 +396,9 @@ async fn emit_exec_stage(
             .await;
         }
         ToolEventStage::Success(output)
-        | ToolEventStage::Failure(ToolEventFailure::Output(output)) => {
+        | ToolEventStage::Failure(
+            ToolEventFailure::Output(output) | ToolEventFailure::SandboxDenied(output),
+        ) => {
             let exec_result = ExecCommandResult {
                 stdout: output.stdout.text.clone(),
                 stderr: output.stderr.text.clone(),
@@ -385,6 +406,7 @@ async fn emit_exec_stage(
                 exit_code: output.exit_code,
                 duration: output.duration,
                 formatted_output: format_exec_output_str(&output, ctx.turn.truncation_policy),
+                sandbox_denied,
             };
             emit_exec_end(ctx, exec_input, exec_result).await;
         }
@@ -397,6 +419,7 @@ async fn emit_exec_stage(
                 exit_code: -1,
                 duration: Duration::ZERO,
                 formatted_output: text,
+                sandbox_denied: false,
             };
             emit_exec_end(ctx, exec_input, exec_result).await;
         }
@@ -426,6 +449,7 @@ async fn emit_exec_end(
                 exit_code: exec_result.exit_code,
                 duration: exec_result.duration,
                 formatted_output: exec_result.formatted_output,
+                sandbox_denied: exec_result.sandbox_denied,
             }),
         )
         .await;
@@ -1,5 +1,6 @@
 use std::collections::BTreeMap;
 use std::path::Path;
+use std::path::PathBuf;
 
 use crate::apply_patch;
 use crate::apply_patch::InternalApplyPatchInvocation;
@@ -11,6 +12,10 @@ use crate::client_common::tools::ToolSpec;
 use crate::codex::Session;
 use crate::codex::TurnContext;
 use crate::function_tool::FunctionCallError;
+use crate::hooks::HookEvent;
+use crate::hooks::HookOutcome;
+use crate::patch_merge;
+use crate::patch_merge::MergeFallback;
 use crate::tools::context::SharedTurnDiffTracker;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
@@ -26,8 +31,11 @@ use crate::tools::sandboxing::ToolCtx;
 use crate::tools::spec::ApplyPatchToolArgs;
 use crate::tools::spec::JsonSchema;
 use async_trait::async_trait;
+use codex_apply_patch::ApplyPatchAction;
+use codex_apply_patch::MaybeApplyPatchVerified;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::json;
 
 pub struct ApplyPatchHandler;
 
@@ -81,8 +89,10 @@ impl ToolHandler for ApplyPatchHandler {
         // Avoid building temporary ExecParams/command vectors; derive directly from inputs.
         let cwd = turn.cwd.clone();
         let command = vec!["apply_patch".to_string(), patch_input.clone()];
-        match codex_apply_patch::maybe_parse_apply_patch_verified(&command, &cwd) {
-            codex_apply_patch::MaybeApplyPatchVerified::Body(changes) => {
+        let (verified, merge_note) =
+            verify_patch(session.as_ref(), turn.as_ref(), &command, &cwd).await?;
+        match verified {
+            MaybeApplyPatchVerified::Body(changes) => {
                 match apply_patch::apply_patch(session.as_ref(), turn.as_ref(), &call_id, changes)
                     .await
                 {
@@ -133,6 +143,8 @@ impl ToolHandler for ApplyPatchHandler {
                             Some(&tracker),
                         );
                         let content = emitter.finish(event_ctx, out).await?;
+                        patch_merge::forget_applied(session.as_ref(), &apply.action).await;
+                        let content = with_note(apply.with_review_feedback(content), merge_note);
                         Ok(ToolOutput::Function {
                             content,
                             content_items: None,
@@ -141,22 +153,20 @@ impl ToolHandler for ApplyPatchHandler {
                     }
                 }
             }
-            codex_apply_patch::MaybeApplyPatchVerified::CorrectnessError(parse_error) => {
+            MaybeApplyPatchVerified::CorrectnessError(parse_error) => {
                 Err(FunctionCallError::RespondToModel(format!(
                     "apply_patch verification failed: {parse_error}"
                 )))
             }
-            codex_apply_patch::MaybeApplyPatchVerified::ShellParseError(error) => {
+            MaybeApplyPatchVerified::ShellParseError(error) => {
                 tracing::trace!("Failed to parse apply_patch input, {error:?}");
                 Err(FunctionCallError::RespondToModel(
                     "apply_patch handler received invalid patch input".to_string(),
                 ))
             }
-            codex_apply_patch::MaybeApplyPatchVerified::NotApplyPatch => {
-                Err(FunctionCallError::RespondToModel(
-                    "apply_patch handler received non-apply_patch input".to_string(),
-                ))
-            }
+            MaybeApplyPatchVerified::NotApplyPatch => Err(FunctionCallError::RespondToModel(
+                "apply_patch handler received non-apply_patch input".to_string(),
+            )),
         }
     }
 }
@@ -179,8 +189,9 @@ pub(crate) async fn intercept_apply_patch(
     call_id: &str,
     tool_name: &str,
 ) -> Result<Option<ToolOutput>, FunctionCallError> {
-    match codex_apply_patch::maybe_parse_apply_patch_verified(command, cwd) {
-        codex_apply_patch::MaybeApplyPatchVerified::Body(changes) => {
+    let (verified, merge_note) = verify_patch(session, turn, command, cwd).await?;
+    match verified {
+        MaybeApplyPatchVerified::Body(changes) => {
             session
                 .record_model_warning(
                     format!("apply_patch was requested via {tool_name}. Use the apply_patch tool instead of exec_command."),
@@ -227,6 +238,8 @@ pub(crate) async fn intercept_apply_patch(
                     let event_ctx =
                         ToolEventCtx::new(session, turn, call_id, tracker.as_ref().copied());
                     let content = emitter.finish(event_ctx, out).await?;
+                    patch_merge::forget_applied(session, &apply.action).await;
+                    let content = with_note(apply.with_review_feedback(content), merge_note);
                     Ok(Some(ToolOutput::Function {
                         content,
                         content_items: None,
@@ -235,16 +248,101 @@ pub(crate) async fn intercept_apply_patch(
                 }
             }
         }
-        codex_apply_patch::MaybeApplyPatchVerified::CorrectnessError(parse_error) => {
+        MaybeApplyPatchVerified::CorrectnessError(parse_error) => {
             Err(FunctionCallError::RespondToModel(format!(
                 "apply_patch verification failed: {parse_error}"
             )))
         }
-        codex_apply_patch::MaybeApplyPatchVerified::ShellParseError(error) => {
+        MaybeApplyPatchVerified::ShellParseError(error) => {
             tracing::trace!("Failed to parse apply_patch input, {error:?}");
             Ok(None)
         }
-        codex_apply_patch::MaybeApplyPatchVerified::NotApplyPatch => Ok(None),
+        MaybeApplyPatchVerified::NotApplyPatch => Ok(None),
+    }
+}
+
+/// Verifies the patch against the files on disk, merging it into files that
+/// changed since the model read them when it no longer applies, and runs
+/// the `pre_patch` hook on it. The note tells the model about a merge.
+async fn verify_patch(
+    session: &Session,
+    turn: &TurnContext,
+    command: &[String],
+    cwd: &Path,
+) -> Result<(MaybeApplyPatchVerified, Option<String>), FunctionCallError> {
+    let (verified, note) = verify_or_merge_patch(session, command, cwd).await?;
+    match verified {
+        MaybeApplyPatchVerified::Body(action) => Ok((
+            MaybeApplyPatchVerified::Body(run_pre_patch_hook(session, turn, action).await?),
+            note,
+        )),
+        verified => Ok((verified, note)),
+    }
+}
+
+async fn verify_or_merge_patch(
+    session: &Session,
+    command: &[String],
+    cwd: &Path,
+) -> Result<(MaybeApplyPatchVerified, Option<String>), FunctionCallError> {
+    match codex_apply_patch::maybe_parse_apply_patch_verified(command, cwd) {
+        MaybeApplyPatchVerified::CorrectnessError(err) => {
+            match patch_merge::merge_with_recorded_bases(session, command, cwd).await {
+                Some(MergeFallback::Merged { action, note }) => {
+                    Ok((MaybeApplyPatchVerified::Body(action), Some(note)))
+                }
+                Some(MergeFallback::Conflicts(report)) => {
+                    Err(FunctionCallError::RespondToModel(report))
+                }
+                None => Ok((MaybeApplyPatchVerified::CorrectnessError(err), None)),
+            }
+        }
+        verified => Ok((verified, None)),
+    }
+}
+
+/// Lets the `pre_patch` hook block the patch or replace it.
+async fn run_pre_patch_hook(
+    session: &Session,
+    turn: &TurnContext,
+    action: ApplyPatchAction,
+) -> Result<ApplyPatchAction, FunctionCallError> {
+    let mut files: Vec<&PathBuf> = action.changes().keys().collect();
+    files.sort();
+    let payload = json!({
+        "patch": action.patch,
+        "files": files,
+    });
+    match session
+        .run_hook(turn, HookEvent::PrePatch, None, payload)
+        .await
+    {
+        HookOutcome::Proceed(None) => Ok(action),
+        HookOutcome::Proceed(Some(patch)) => {
+            let command = vec!["apply_patch".to_string(), patch];
+            match codex_apply_patch::maybe_parse_apply_patch_verified(&command, &action.cwd) {
+                MaybeApplyPatchVerified::Body(action) => Ok(action),
+                MaybeApplyPatchVerified::CorrectnessError(err) => {
+                    Err(FunctionCallError::RespondToModel(format!(
+                        "the pre_patch hook returned a patch that does not apply: {err}"
+                    )))
+                }
+                MaybeApplyPatchVerified::ShellParseError(_)
+                | MaybeApplyPatchVerified::NotApplyPatch => Err(FunctionCallError::RespondToModel(
+                    "the pre_patch hook returned an invalid patch".to_string(),
+                )),
+            }
+        }
+        HookOutcome::Block(reason) => Err(FunctionCallError::RespondToModel(format!(
+            "patch blocked by the pre_patch hook: {reason}"
+        ))),
+    }
+}
+
+fn with_note(content: String, note: Option<String>) -> String {
+    match note {
+        Some(note) => format!("{content}\n\n{note}"),
+        None => content,
     }
 }
 
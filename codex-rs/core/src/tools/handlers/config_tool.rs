@@ -0,0 +1,203 @@
+use async_trait::async_trait;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::config::types::CustomToolConfig;
+use crate::exec::ExecParams;
+use crate::exec_env::create_env;
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::handlers::ShellHandler;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+/// Runs a tool from `[custom_tools]`: the configured command, with the
+/// call's arguments filled into its placeholders, goes through the same
+/// sandbox and approvals as a shell command.
+pub struct ConfigToolHandler {
+    tool: CustomToolConfig,
+}
+
+impl ConfigToolHandler {
+    pub fn new(tool: CustomToolConfig) -> Self {
+        Self { tool }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for ConfigToolHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    fn is_mutating(&self, _invocation: &ToolInvocation) -> bool {
+        true
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            tracker,
+            call_id,
+            tool_name,
+            payload,
+        } = invocation;
+
+        let ToolPayload::Function { arguments } = payload else {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "unsupported payload for custom tool: {tool_name}"
+            )));
+        };
+        let arguments = if arguments.trim().is_empty() {
+            Map::new()
+        } else {
+            match serde_json::from_str::<Value>(&arguments) {
+                Ok(Value::Object(arguments)) => arguments,
+                Ok(_) => {
+                    return Err(FunctionCallError::RespondToModel(
+                        "arguments must be a JSON object".to_string(),
+                    ));
+                }
+                Err(err) => {
+                    return Err(FunctionCallError::RespondToModel(format!(
+                        "failed to parse function arguments: {err:?}"
+                    )));
+                }
+            }
+        };
+
+        let cwd = match &self.tool.cwd {
+            Some(dir) => turn.cwd.join(dir),
+            None => turn.cwd.clone(),
+        };
+        let exec_params = ExecParams {
+            command: expand_command(&self.tool.command, &arguments),
+            cwd,
+            expiration: self.tool.timeout_ms.into(),
+            env: create_env(&turn.shell_environment_policy),
+            with_escalated_permissions: None,
+            justification: None,
+            arg0: None,
+        };
+        ShellHandler::run_exec_like(
+            tool_name.as_str(),
+            exec_params,
+            session,
+            turn,
+            tracker,
+            call_id,
+            false,
+        )
+        .await
+    }
+}
+
+/// Fills the `{name}` placeholders of `template` from `arguments`. Each
+/// argument stays one argv entry however it is quoted, so no shell parses
+/// it.
+fn expand_command(template: &[String], arguments: &Map<String, Value>) -> Vec<String> {
+    let mut command = Vec::with_capacity(template.len());
+    for part in template {
+        let whole = part
+            .strip_prefix('{')
+            .and_then(|rest| rest.strip_suffix('}'))
+            .filter(|name| is_placeholder_name(name));
+        match whole.map(|name| arguments.get(name)) {
+            Some(None | Some(Value::Null)) => {}
+            Some(Some(Value::Array(items))) => command.extend(items.iter().map(render)),
+            Some(Some(value)) => command.push(render(value)),
+            None => command.push(expand_inline(part, arguments)),
+        }
+    }
+    command
+}
+
+fn expand_inline(part: &str, arguments: &Map<String, Value>) -> String {
+    let mut expanded = String::new();
+    let mut rest = part;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('}') {
+            Some(end) if is_placeholder_name(&after[..end]) => {
+                match arguments.get(&after[..end]) {
+                    Some(Value::Array(items)) => {
+                        expanded.push_str(&items.iter().map(render).collect::<Vec<_>>().join(" "))
+                    }
+                    Some(Value::Null) | None => {}
+                    Some(value) => expanded.push_str(&render(value)),
+                }
+                rest = &after[end + 1..];
+            }
+            _ => {
+                expanded.push('{');
+                rest = after;
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+fn is_placeholder_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn render(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn strings(parts: &[&str]) -> Vec<String> {
+        parts.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn placeholders_are_filled_from_the_arguments() {
+        let arguments = json!({
+            "target": "crates/core; rm -rf /",
+            "features": ["a", "b"],
+            "jobs": 4,
+        });
+        let Value::Object(arguments) = arguments else {
+            unreachable!()
+        };
+        assert_eq!(
+            expand_command(
+                &strings(&[
+                    "make",
+                    "verify",
+                    "TARGET={target}",
+                    "-j{jobs}",
+                    "{features}",
+                    "{missing}",
+                    "{not a placeholder}",
+                ]),
+                &arguments,
+            ),
+            strings(&[
+                "make",
+                "verify",
+                "TARGET=crates/core; rm -rf /",
+                "-j4",
+                "a",
+                "b",
+                "{not a placeholder}",
+            ])
+        );
+    }
+}
@@ -0,0 +1,274 @@
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use htmd::HtmlToMarkdown;
+use htmd::options::HeadingStyle;
+use htmd::options::Options;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::redirect;
+use serde::Deserialize;
+use url::Url;
+
+use crate::config::types::FetchUrl;
+use crate::default_client::check_non_provider_network;
+use crate::default_client::reqwest_client_builder;
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct FetchUrlHandler;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_REDIRECTS: usize = 5;
+
+#[derive(Deserialize)]
+struct FetchUrlArgs {
+    url: String,
+}
+
+#[async_trait]
+impl ToolHandler for FetchUrlHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation { payload, turn, .. } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "fetch_url handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+
+        let args: FetchUrlArgs = serde_json::from_str(&arguments).map_err(|err| {
+            FunctionCallError::RespondToModel(format!(
+                "failed to parse function arguments: {err:?}"
+            ))
+        })?;
+
+        // The tool is not offered without network access, but the sandbox
+        // policy can change while a call is pending.
+        if !turn.sandbox_policy.has_full_network_access() {
+            return Err(FunctionCallError::RespondToModel(
+                "fetch_url is disabled because the sandbox does not allow network access"
+                    .to_string(),
+            ));
+        }
+        let policy = turn.client.config().fetch_url.clone();
+        let url = Url::parse(args.url.trim()).map_err(|err| {
+            FunctionCallError::RespondToModel(format!("invalid URL `{}`: {err}", args.url))
+        })?;
+        check_url(&policy, &url).map_err(FunctionCallError::RespondToModel)?;
+
+        let page = fetch(&policy, url)
+            .await
+            .map_err(FunctionCallError::RespondToModel)?;
+        Ok(ToolOutput::Function {
+            content: page,
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
+
+/// Whether `url` may be fetched under `policy`: only http and https, and
+/// only hosts that are allowed and not denied.
+fn check_url(policy: &FetchUrl, url: &Url) -> Result<(), String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "cannot fetch `{url}`: only http and https URLs are supported"
+        ));
+    }
+    let Some(host) = url.host_str() else {
+        return Err(format!("cannot fetch `{url}`: the URL has no host"));
+    };
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    if policy
+        .denied_domains
+        .iter()
+        .any(|domain| host_matches(&host, domain))
+    {
+        return Err(format!(
+            "cannot fetch `{url}`: {host} is in fetch_url.denied_domains"
+        ));
+    }
+    if !policy.allowed_domains.is_empty()
+        && !policy
+            .allowed_domains
+            .iter()
+            .any(|domain| host_matches(&host, domain))
+    {
+        return Err(format!(
+            "cannot fetch `{url}`: {host} is not in fetch_url.allowed_domains"
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `host` is `domain` or one of its subdomains.
+fn host_matches(host: &str, domain: &str) -> bool {
+    let domain = domain.trim().trim_start_matches("*.").trim_end_matches('.');
+    if domain.is_empty() {
+        return false;
+    }
+    host.eq_ignore_ascii_case(domain)
+        || host
+            .strip_suffix(&domain.to_ascii_lowercase())
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+/// Downloads `url`, following redirects the policy allows, and renders the
+/// page as markdown under a heading with the address it ended up at.
+async fn fetch(policy: &FetchUrl, url: Url) -> Result<String, String> {
+    check_non_provider_network("fetch_url").map_err(|err| err.to_string())?;
+    let redirect_policy = policy.clone();
+    let client = reqwest_client_builder()
+        .map_err(|err| err.to_string())?
+        .timeout(FETCH_TIMEOUT)
+        .redirect(redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.error(format!("stopped after {MAX_REDIRECTS} redirects"))
+            } else if let Err(err) = check_url(&redirect_policy, attempt.url()) {
+                attempt.error(err)
+            } else {
+                attempt.follow()
+            }
+        }))
+        .build()
+        .map_err(|err| format!("failed to build the HTTP client: {err}"))?;
+
+    let mut response = client
+        .get(url.clone())
+        .send()
+        .await
+        .map_err(|err| format!("failed to fetch `{url}`: {err}"))?;
+    let final_url = response.url().clone();
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("`{final_url}` answered {status}"));
+    }
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("text/html")
+        .to_ascii_lowercase();
+
+    let max_bytes = policy.max_bytes();
+    let mut body = Vec::new();
+    let mut truncated = false;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|err| format!("failed to read `{final_url}`: {err}"))?
+    {
+        let room = max_bytes - body.len();
+        if chunk.len() > room {
+            body.extend_from_slice(&chunk[..room]);
+            truncated = true;
+            break;
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    let mut page = format!(
+        "# {final_url}\n\n{}",
+        render(&content_type, &String::from_utf8_lossy(&body))?
+    );
+    if truncated {
+        let _ = write!(
+            page,
+            "\n\n[Truncated: the page is larger than the {max_bytes} bytes fetch_url downloads.]"
+        );
+    }
+    Ok(page)
+}
+
+/// The page as the model sees it: HTML converted to markdown, other text
+/// as it is.
+fn render(content_type: &str, body: &str) -> Result<String, String> {
+    let mime = content_type.split(';').next().unwrap_or_default().trim();
+    if matches!(mime, "text/html" | "application/xhtml+xml") {
+        let converter = HtmlToMarkdown::builder()
+            .options(Options {
+                heading_style: HeadingStyle::Atx,
+                ..Options::default()
+            })
+            .skip_tags(vec!["script", "style", "noscript", "svg", "nav", "footer"])
+            .build();
+        return converter
+            .convert(body)
+            .map(|markdown| markdown.trim().to_string())
+            .map_err(|err| format!("failed to convert the page to markdown: {err}"));
+    }
+    let is_text = mime.starts_with("text/")
+        || mime.ends_with("json")
+        || mime.ends_with("xml")
+        || mime == "application/javascript";
+    if is_text {
+        Ok(body.trim().to_string())
+    } else {
+        Err(format!(
+            "cannot show `{mime}` content; fetch_url only returns web pages and text"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).expect("url")
+    }
+
+    #[test]
+    fn domain_lists_cover_subdomains_and_deny_wins() {
+        let policy = FetchUrl {
+            allowed_domains: vec!["rust-lang.org".to_string(), "*.docs.rs".to_string()],
+            denied_domains: vec!["blog.rust-lang.org".to_string()],
+            max_bytes: None,
+        };
+        assert_eq!(
+            check_url(&policy, &url("https://doc.rust-lang.org/std/")),
+            Ok(())
+        );
+        assert_eq!(check_url(&policy, &url("https://RUST-LANG.org")), Ok(()));
+        assert_eq!(check_url(&policy, &url("https://serde.docs.rs/")), Ok(()));
+        assert_eq!(
+            check_url(&policy, &url("https://blog.rust-lang.org/")),
+            Err("cannot fetch `https://blog.rust-lang.org/`: blog.rust-lang.org is in fetch_url.denied_domains".to_string())
+        );
+        assert_eq!(
+            check_url(&policy, &url("https://evil-rust-lang.org/")),
+            Err("cannot fetch `https://evil-rust-lang.org/`: evil-rust-lang.org is not in fetch_url.allowed_domains".to_string())
+        );
+        assert!(check_url(&policy, &url("file:///etc/passwd")).is_err());
+
+        let open = FetchUrl::default();
+        assert_eq!(check_url(&open, &url("http://example.com/a")), Ok(()));
+    }
+
+    #[test]
+    fn html_is_rendered_as_markdown() {
+        let html = "<html><head><style>p { color: red }</style><script>track()</script></head><body><h1>Install</h1><p>Run <code>cargo add serde</code>.</p></body></html>";
+        assert_eq!(
+            render("text/html; charset=utf-8", html),
+            Ok("# Install\n\nRun `cargo add serde`.".to_string())
+        );
+        assert_eq!(
+            render("application/json", " {\"ok\":true}\n"),
+            Ok("{\"ok\":true}".to_string())
+        );
+        assert!(render("image/png", "").is_err());
+    }
+}
@@ -6,6 +6,7 @@ use serde::Deserialize;
 use tokio::process::Command;
 use tokio::time::timeout;
 
+use crate::codex_ignore::CodexIgnore;
 use crate::function_tool::FunctionCallError;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
@@ -144,7 +145,16 @@ async fn run_rg_search(
         })?;
 
     match output.status.code() {
-        Some(0) => Ok(parse_results(&output.stdout, limit)),
+        Some(0) => {
+            // rg cannot read .codexignore files itself, so its matches are
+            // filtered before the limit applies.
+            let mut ignore = CodexIgnore::default();
+            Ok(parse_results(&output.stdout, usize::MAX)
+                .into_iter()
+                .filter(|path| !ignore.is_ignored(&cwd.join(path), false))
+                .take(limit)
+                .collect())
+        }
         Some(1) => Ok(Vec::new()),
         _ => {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -250,6 +260,24 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn run_search_skips_codexignored_files() -> anyhow::Result<()> {
+        if !rg_available() {
+            return Ok(());
+        }
+        let temp = tempdir().expect("create temp dir");
+        let dir = temp.path();
+        std::fs::create_dir_all(dir.join("secrets")).unwrap();
+        std::fs::write(dir.join("secrets/prod.env"), "alpha=1").unwrap();
+        std::fs::write(dir.join("config.txt"), "alpha").unwrap();
+        std::fs::write(dir.join(".codexignore"), "secrets/\n").unwrap();
+
+        let results = run_rg_search("alpha", None, dir, 10, dir).await?;
+        assert_eq!(results.len(), 1);
+        assert!(results[0].ends_with("config.txt"));
+        Ok(())
+    }
+
     #[tokio::test]
     async fn run_search_handles_no_matches() -> anyhow::Result<()> {
         if !rg_available() {
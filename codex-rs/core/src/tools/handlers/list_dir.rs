@@ -9,6 +9,8 @@ use codex_utils_string::take_bytes_at_char_boundary;
 use serde::Deserialize;
 use tokio::fs;
 
+use crate::codex_ignore;
+use crate::codex_ignore::CodexIgnore;
 use crate::function_tool::FunctionCallError;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
@@ -99,6 +101,11 @@ impl ToolHandler for ListDirHandler {
                 "dir_path must be an absolute path".to_string(),
             ));
         }
+        if codex_ignore::is_ignored(&path) {
+            return Err(FunctionCallError::RespondToModel(
+                codex_ignore::ignored_error(&path),
+            ));
+        }
 
         let entries = list_dir_slice(&path, offset, limit, depth).await?;
         let mut output = Vec::with_capacity(entries.len() + 1);
@@ -156,6 +163,7 @@ async fn collect_entries(
     depth: usize,
     entries: &mut Vec<DirEntry>,
 ) -> Result<(), FunctionCallError> {
+    let mut ignore = CodexIgnore::default();
     let mut queue = VecDeque::new();
     queue.push_back((dir_path.to_path_buf(), relative_prefix.to_path_buf(), depth));
 
@@ -173,6 +181,11 @@ async fn collect_entries(
                 FunctionCallError::RespondToModel(format!("failed to inspect entry: {err}"))
             })?;
 
+            let kind = DirEntryKind::from(&file_type);
+            if ignore.is_ignored(&entry.path(), kind == DirEntryKind::Directory) {
+                continue;
+            }
+
             let file_name = entry.file_name();
             let relative_path = if prefix.as_os_str().is_empty() {
                 PathBuf::from(&file_name)
@@ -183,7 +196,6 @@ async fn collect_entries(
             let display_name = format_entry_component(&file_name);
             let display_depth = prefix.components().count();
             let sort_key = format_entry_name(&relative_path);
-            let kind = DirEntryKind::from(&file_type);
             dir_entries.push((
                 entry.path(),
                 relative_path,
@@ -350,6 +362,42 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn skips_entries_excluded_by_codexignore() {
+        let temp = tempdir().expect("create tempdir");
+        let dir_path = temp.path();
+        tokio::fs::create_dir(dir_path.join(".git"))
+            .await
+            .expect("create git dir");
+        tokio::fs::create_dir(dir_path.join("secrets"))
+            .await
+            .expect("create secrets");
+        tokio::fs::write(dir_path.join("secrets/prod.env"), b"TOKEN=1")
+            .await
+            .expect("write secret");
+        tokio::fs::write(dir_path.join("app.bundle.js"), b"bundle")
+            .await
+            .expect("write bundle");
+        tokio::fs::write(dir_path.join("main.rs"), b"fn main() {}")
+            .await
+            .expect("write main");
+        tokio::fs::write(dir_path.join(".codexignore"), b"secrets/\n*.bundle.js\n")
+            .await
+            .expect("write codexignore");
+
+        let entries = list_dir_slice(dir_path, 1, 20, 2)
+            .await
+            .expect("list directory");
+        assert_eq!(
+            entries,
+            vec![
+                ".codexignore".to_string(),
+                ".git/".to_string(),
+                "main.rs".to_string(),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn respects_depth_parameter() {
         let temp = tempdir().expect("create tempdir");
@@ -1,26 +1,39 @@
 pub mod apply_patch;
+mod config_tool;
+mod fetch_url;
 mod grep_files;
 mod list_dir;
 mod mcp;
 mod mcp_resource;
 mod plan;
+mod plugin;
 mod read_file;
+mod search_code;
 mod shell;
+mod sub_agents;
 mod test_sync;
 mod unified_exec;
 mod view_image;
+mod workspace_stats;
 
 pub use plan::PLAN_TOOL;
+pub(crate) use plan::render_plan;
 
 pub use apply_patch::ApplyPatchHandler;
+pub use config_tool::ConfigToolHandler;
+pub use fetch_url::FetchUrlHandler;
 pub use grep_files::GrepFilesHandler;
 pub use list_dir::ListDirHandler;
 pub use mcp::McpHandler;
 pub use mcp_resource::McpResourceHandler;
 pub use plan::PlanHandler;
+pub(crate) use plugin::PluginToolHandler;
 pub use read_file::ReadFileHandler;
+pub use search_code::SearchCodeHandler;
 pub use shell::ShellCommandHandler;
 pub use shell::ShellHandler;
+pub use sub_agents::SubAgentsHandler;
 pub use test_sync::TestSyncHandler;
 pub use unified_exec::UnifiedExecHandler;
 pub use view_image::ViewImageHandler;
+pub use workspace_stats::WorkspaceStatsHandler;
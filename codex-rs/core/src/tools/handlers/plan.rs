@@ -10,13 +10,21 @@ use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
 use crate::tools::spec::JsonSchema;
 use async_trait::async_trait;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::plan_tool::PlanItemArg;
+use codex_protocol::plan_tool::StepStatus;
 use codex_protocol::plan_tool::UpdatePlanArgs;
 use codex_protocol::protocol::EventMsg;
 use std::collections::BTreeMap;
+use std::fmt::Write as _;
 use std::sync::LazyLock;
 
 pub struct PlanHandler;
 
+const PLAN_OPEN_TAG: &str = "<plan>";
+const PLAN_CLOSE_TAG: &str = "</plan>";
+
 pub static PLAN_TOOL: LazyLock<ToolSpec> = LazyLock::new(|| {
     let mut plan_item_props = BTreeMap::new();
     plan_item_props.insert("step".to_string(), JsonSchema::String { description: None });
@@ -104,14 +112,84 @@ pub(crate) async fn handle_update_plan(
     _call_id: String,
 ) -> Result<String, FunctionCallError> {
     let args = parse_update_plan_arguments(&arguments)?;
+    session.set_plan(args.clone()).await;
     session
         .send_event(turn_context, EventMsg::PlanUpdate(args))
         .await;
     Ok("Plan updated".to_string())
 }
 
+/// The message that reminds the model of its plan on every turn, so the
+/// plan survives compaction and resumed sessions. `None` once every step
+/// is completed.
+pub(crate) fn render_plan(plan: &UpdatePlanArgs) -> Option<ResponseItem> {
+    if plan
+        .plan
+        .iter()
+        .all(|item| matches!(item.status, StepStatus::Completed))
+    {
+        return None;
+    }
+    let mut text = format!(
+        "{PLAN_OPEN_TAG}\nYour current plan, from your last update_plan call. Keep it up to date with update_plan as you work.\n"
+    );
+    for PlanItemArg { step, status } in &plan.plan {
+        let mark = match status {
+            StepStatus::Completed => "[x]",
+            StepStatus::InProgress => "[~]",
+            StepStatus::Pending => "[ ]",
+        };
+        let _ = writeln!(text, "- {mark} {step}");
+    }
+    text.push_str(PLAN_CLOSE_TAG);
+    Some(ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText { text }],
+    })
+}
+
 fn parse_update_plan_arguments(arguments: &str) -> Result<UpdatePlanArgs, FunctionCallError> {
     serde_json::from_str::<UpdatePlanArgs>(arguments).map_err(|e| {
         FunctionCallError::RespondToModel(format!("failed to parse function arguments: {e}"))
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(step: &str, status: StepStatus) -> PlanItemArg {
+        PlanItemArg {
+            step: step.to_string(),
+            status,
+        }
+    }
+
+    #[test]
+    fn plan_is_sent_until_every_step_is_completed() {
+        let mut plan = UpdatePlanArgs {
+            explanation: None,
+            plan: vec![
+                step("Add the migration", StepStatus::Completed),
+                step("Backfill old rows", StepStatus::InProgress),
+                step("Drop the legacy column", StepStatus::Pending),
+            ],
+        };
+        let Some(ResponseItem::Message { content, .. }) = render_plan(&plan) else {
+            panic!("expected a message");
+        };
+        let [ContentItem::InputText { text }] = content.as_slice() else {
+            panic!("expected text");
+        };
+        assert!(text.starts_with(PLAN_OPEN_TAG));
+        assert!(text.ends_with(&format!(
+            "- [x] Add the migration\n- [~] Backfill old rows\n- [ ] Drop the legacy column\n{PLAN_CLOSE_TAG}"
+        )));
+
+        for item in &mut plan.plan {
+            item.status = StepStatus::Completed;
+        }
+        assert!(render_plan(&plan).is_none());
+    }
+}
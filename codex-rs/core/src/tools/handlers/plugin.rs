@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+
+use crate::function_tool::FunctionCallError;
+use crate::plugins::PluginOutput;
+use crate::plugins::PluginTool;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+/// Runs a tool from a WebAssembly plugin in `$CODEX_HOME/plugins/`. Plugins
+/// cannot touch the machine, so their calls need no sandbox or approval.
+pub(crate) struct PluginToolHandler {
+    tool: PluginTool,
+}
+
+impl PluginToolHandler {
+    pub(crate) fn new(tool: PluginTool) -> Self {
+        Self { tool }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for PluginToolHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            tool_name, payload, ..
+        } = invocation;
+
+        let ToolPayload::Function { arguments } = payload else {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "unsupported payload for plugin tool: {tool_name}"
+            )));
+        };
+        let tool = self.tool.clone();
+        let result = tokio::task::spawn_blocking(move || tool.call(&arguments))
+            .await
+            .map_err(|err| {
+                FunctionCallError::RespondToModel(format!("plugin tool {tool_name} failed: {err}"))
+            })?;
+        match result {
+            Ok(PluginOutput { output, success }) => Ok(ToolOutput::Function {
+                content: output,
+                content_items: None,
+                success: Some(success),
+            }),
+            Err(err) => Err(FunctionCallError::RespondToModel(err)),
+        }
+    }
+}
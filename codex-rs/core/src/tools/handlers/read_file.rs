@@ -5,7 +5,9 @@ use async_trait::async_trait;
 use codex_utils_string::take_bytes_at_char_boundary;
 use serde::Deserialize;
 
+use crate::codex_ignore;
 use crate::function_tool::FunctionCallError;
+use crate::patch_merge;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
 use crate::tools::context::ToolPayload;
@@ -96,7 +98,9 @@ impl ToolHandler for ReadFileHandler {
     }
 
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
-        let ToolInvocation { payload, .. } = invocation;
+        let ToolInvocation {
+            session, payload, ..
+        } = invocation;
 
         let arguments = match payload {
             ToolPayload::Function { arguments } => arguments,
@@ -139,6 +143,11 @@ impl ToolHandler for ReadFileHandler {
                 "file_path must be an absolute path".to_string(),
             ));
         }
+        if codex_ignore::is_ignored(&path) {
+            return Err(FunctionCallError::RespondToModel(
+                codex_ignore::ignored_error(&path),
+            ));
+        }
 
         let collected = match mode {
             ReadMode::Slice => slice::read(&path, offset, limit).await?,
@@ -147,6 +156,7 @@ impl ToolHandler for ReadFileHandler {
                 indentation::read_block(&path, offset, limit, indentation).await?
             }
         };
+        patch_merge::record_read(session.as_ref(), &path).await;
         Ok(ToolOutput::Function {
             content: collected.join("\n"),
             content_items: None,
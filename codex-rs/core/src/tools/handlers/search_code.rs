@@ -0,0 +1,256 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::code_index::CodeIndex;
+use crate::code_index::CodeMatch;
+use crate::code_index::PendingChunk;
+use crate::code_index::embeddings;
+use crate::code_index::index_dir;
+use crate::error::CodexErr;
+use crate::function_tool::FunctionCallError;
+use crate::git_info::get_git_repo_root;
+use crate::model_provider_info::ModelProviderInfo;
+use crate::repo_map::list_files;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct SearchCodeHandler;
+
+const DEFAULT_LIMIT: usize = 8;
+const MAX_LIMIT: usize = 30;
+/// Lines of each match shown in the output.
+const PREVIEW_LINES: usize = 6;
+/// Chunks embedded by one search; the rest are embedded by later ones, so
+/// the first search of a large repository does not stall.
+const MAX_EMBEDDED_PER_SEARCH: usize = 512;
+
+fn default_limit() -> usize {
+    DEFAULT_LIMIT
+}
+
+#[derive(Deserialize)]
+struct SearchCodeArgs {
+    query: String,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+#[async_trait]
+impl ToolHandler for SearchCodeHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation { payload, turn, .. } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "search_code handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+
+        let args: SearchCodeArgs = serde_json::from_str(&arguments).map_err(|err| {
+            FunctionCallError::RespondToModel(format!(
+                "failed to parse function arguments: {err:?}"
+            ))
+        })?;
+        if args.query.trim().is_empty() {
+            return Err(FunctionCallError::RespondToModel(
+                "query must not be empty".to_string(),
+            ));
+        }
+        if args.limit == 0 {
+            return Err(FunctionCallError::RespondToModel(
+                "limit must be greater than zero".to_string(),
+            ));
+        }
+        let limit = args.limit.min(MAX_LIMIT);
+
+        // The whole repository is indexed, whatever part of it is searched,
+        // so every search shares one index.
+        let root = get_git_repo_root(&turn.cwd).unwrap_or_else(|| turn.cwd.clone());
+        let scope = turn.resolve_path(args.path);
+        let Ok(scope) = scope.strip_prefix(&root).map(Path::to_path_buf) else {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "`{}` is outside the indexed workspace `{}`",
+                scope.display(),
+                root.display()
+            )));
+        };
+        let config = turn.client.config();
+        let dir = index_dir(&crate::state_dir::state_dir(&config.codex_home), &root);
+        let mut notes = Vec::new();
+        let semantic = match &config.search_code.embedding_model {
+            Some(model) => {
+                let provider_id = config.search_code.embedding_provider();
+                match config.model_providers.get(provider_id) {
+                    Some(provider) => Some((provider.clone(), model.clone())),
+                    None => {
+                        notes.push(format!(
+                            "The embedding provider `{provider_id}` is not configured, so matches are by shared words only."
+                        ));
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let model = semantic.as_ref().map(|(_, model)| model.clone());
+        let index_root = root.clone();
+        let load_dir = dir.clone();
+        let (mut index, files, complete, changed, pending) =
+            tokio::task::spawn_blocking(move || {
+                let mut index = CodeIndex::load(&load_dir, &index_root);
+                let dropped = index.use_embedding_model(model.as_deref());
+                let (files, complete) = list_files(&index_root);
+                let changed = index.update(&files);
+                if changed > 0 || dropped {
+                    save(&index, &load_dir);
+                }
+                let pending = model
+                    .is_some()
+                    .then(|| index.pending_embeddings(MAX_EMBEDDED_PER_SEARCH));
+                (index, files.len(), complete, changed, pending)
+            })
+            .await
+            .map_err(search_failed)?;
+
+        let query = args.query;
+        let matches = match (semantic, pending) {
+            (Some((provider, model)), Some((pending, unembedded))) => {
+                let embedded = pending.len();
+                let result = embed_chunks(&mut index, &provider, &model, pending, &query).await;
+                if embedded > 0 {
+                    index = tokio::task::spawn_blocking(move || {
+                        save(&index, &dir);
+                        index
+                    })
+                    .await
+                    .map_err(search_failed)?;
+                }
+                match result {
+                    Ok(embedding) => {
+                        if unembedded > embedded {
+                            notes.push(format!(
+                                "{} chunks are not embedded yet and were left out; later searches embed them.",
+                                unembedded - embedded
+                            ));
+                        }
+                        index.search_embeddings(&embedding, &scope, limit)
+                    }
+                    Err(err) => {
+                        notes.push(format!(
+                            "The embedding model `{model}` could not be used ({err}), so matches are by shared words only."
+                        ));
+                        index.search(&query, &scope, limit)
+                    }
+                }
+            }
+            _ => index.search(&query, &scope, limit),
+        };
+        let output = render(&root, &matches, files, changed, complete, &notes);
+
+        Ok(ToolOutput::Function {
+            content: output,
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
+
+fn save(index: &CodeIndex, dir: &Path) {
+    if let Err(err) = index.save(dir) {
+        tracing::warn!("failed to save the code index in {}: {err}", dir.display());
+    }
+}
+
+fn search_failed(err: impl std::fmt::Display) -> FunctionCallError {
+    FunctionCallError::RespondToModel(format!("failed to search the code index: {err}"))
+}
+
+/// Embeds the `pending` chunks and returns the embedding of `query`. Chunks
+/// embedded before a failure keep their embeddings.
+async fn embed_chunks(
+    index: &mut CodeIndex,
+    provider: &ModelProviderInfo,
+    model: &str,
+    pending: Vec<PendingChunk>,
+    query: &str,
+) -> Result<Vec<f32>, CodexErr> {
+    for batch in pending.chunks(embeddings::BATCH_SIZE) {
+        let inputs: Vec<String> = batch.iter().map(|chunk| chunk.text.clone()).collect();
+        let vectors = embeddings::embed(provider, model, &inputs).await?;
+        for (chunk, vector) in batch.iter().zip(vectors) {
+            index.set_embedding(&chunk.path, chunk.start, &vector);
+        }
+    }
+    embeddings::embed(provider, model, &[query.to_string()])
+        .await?
+        .pop()
+        .ok_or_else(|| std::io::Error::other("no embedding for the query").into())
+}
+
+fn render(
+    root: &Path,
+    matches: &[CodeMatch],
+    files: usize,
+    changed: usize,
+    complete: bool,
+    notes: &[String],
+) -> String {
+    let mut output = String::new();
+    let _ = write!(output, "Searched {files} files");
+    if !complete {
+        output.push_str(", where the index stops,");
+    }
+    let _ = writeln!(output, " ({changed} changed since the last search).");
+    for note in notes {
+        let _ = writeln!(output, "{note}");
+    }
+    if matches.is_empty() {
+        output.push_str("No matching code. Try other words, or grep for exact names.\n");
+        return output;
+    }
+    for found in matches {
+        let _ = writeln!(
+            output,
+            "\n{}:{}-{} (score {:.2})",
+            found.path.display(),
+            found.start,
+            found.end,
+            found.score
+        );
+        for line in preview(&root.join(&found.path), found.start, found.end) {
+            let _ = writeln!(output, "  {line}");
+        }
+    }
+    output
+}
+
+/// The first non-blank lines of a match, read from the file as it is now.
+fn preview(path: &Path, start: usize, end: usize) -> Vec<String> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    contents
+        .lines()
+        .skip(start.saturating_sub(1))
+        .take(end + 1 - start)
+        .filter(|line| !line.trim().is_empty())
+        .take(PREVIEW_LINES)
+        .map(str::to_string)
+        .collect()
+}
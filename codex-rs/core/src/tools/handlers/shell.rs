@@ -9,6 +9,7 @@ use crate::exec_env::create_env;
 use crate::exec_policy::create_approval_requirement_for_command;
 use crate::function_tool::FunctionCallError;
 use crate::is_safe_command::is_known_safe_command;
+use crate::patch_merge;
 use crate::protocol::ExecCommandSource;
 use crate::sandboxing::SandboxPermissions;
 use crate::tools::context::ToolInvocation;
@@ -183,7 +184,7 @@ impl ToolHandler for ShellCommandHandler {
 }
 
 impl ShellHandler {
-    async fn run_exec_like(
+    pub(crate) async fn run_exec_like(
         tool_name: &str,
         exec_params: ExecParams,
         session: Arc<crate::codex::Session>,
@@ -262,6 +263,7 @@ impl ShellHandler {
             .await;
         let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
         let content = emitter.finish(event_ctx, out).await?;
+        patch_merge::record_reads(session.as_ref(), &exec_params.command, &exec_params.cwd).await;
         Ok(ToolOutput::Function {
             content,
             content_items: None,
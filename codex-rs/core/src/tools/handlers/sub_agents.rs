@@ -0,0 +1,117 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::function_tool::FunctionCallError;
+use crate::sub_agents::Access;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct SubAgentsHandler;
+
+const DEFAULT_WAIT_SECONDS: u64 = 600;
+const MAX_WAIT_SECONDS: u64 = 1800;
+
+fn default_wait_seconds() -> u64 {
+    DEFAULT_WAIT_SECONDS
+}
+
+#[derive(Deserialize)]
+struct SpawnAgentArgs {
+    task: String,
+    #[serde(default)]
+    access: Access,
+    #[serde(default)]
+    max_tokens: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct WaitAgentsArgs {
+    #[serde(default)]
+    ids: Option<Vec<usize>>,
+    #[serde(default = "default_wait_seconds")]
+    timeout_seconds: u64,
+}
+
+#[async_trait]
+impl ToolHandler for SubAgentsHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            tool_name,
+            payload,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "sub-agent handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+        let parse_error = |err: serde_json::Error| {
+            FunctionCallError::RespondToModel(format!(
+                "failed to parse function arguments: {err:?}"
+            ))
+        };
+
+        let content = match tool_name.as_str() {
+            "spawn_agent" => {
+                let args: SpawnAgentArgs = serde_json::from_str(&arguments).map_err(parse_error)?;
+                if args.task.trim().is_empty() {
+                    return Err(FunctionCallError::RespondToModel(
+                        "task must not be empty".to_string(),
+                    ));
+                }
+                let id = session
+                    .services
+                    .sub_agents
+                    .spawn(
+                        Arc::clone(&session),
+                        turn,
+                        &args.task,
+                        args.access,
+                        args.max_tokens,
+                    )
+                    .await
+                    .map_err(FunctionCallError::RespondToModel)?;
+                format!(
+                    "Started agent {id}. Go on with other work, and call wait_agents to collect its report."
+                )
+            }
+            "wait_agents" => {
+                let args: WaitAgentsArgs = serde_json::from_str(&arguments).map_err(parse_error)?;
+                let timeout = Duration::from_secs(args.timeout_seconds.min(MAX_WAIT_SECONDS));
+                session
+                    .services
+                    .sub_agents
+                    .wait(args.ids, timeout)
+                    .await
+                    .map_err(FunctionCallError::RespondToModel)?
+            }
+            other => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "unsupported sub-agent tool: {other}"
+                )));
+            }
+        };
+
+        Ok(ToolOutput::Function {
+            content,
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use serde::Deserialize;
 use tokio::fs;
 
+use crate::codex_ignore;
 use crate::function_tool::FunctionCallError;
 use crate::protocol::EventMsg;
 use crate::protocol::ViewImageToolCallEvent;
@@ -47,6 +48,12 @@ impl ToolHandler for ViewImageHandler {
             FunctionCallError::RespondToModel(format!("failed to parse function arguments: {e:?}"))
         })?;
 
+        if !turn.client.get_provider().supports_images() {
+            return Err(FunctionCallError::RespondToModel(
+                "this model cannot view images; the provider does not accept them".to_string(),
+            ));
+        }
+
         let abs_path = turn.resolve_path(Some(args.path));
 
         let metadata = fs::metadata(&abs_path).await.map_err(|error| {
@@ -62,6 +69,11 @@ impl ToolHandler for ViewImageHandler {
                 abs_path.display()
             )));
         }
+        if codex_ignore::is_ignored(&abs_path) {
+            return Err(FunctionCallError::RespondToModel(
+                codex_ignore::ignored_error(&abs_path),
+            ));
+        }
         let event_path = abs_path.clone();
 
         session
@@ -0,0 +1,606 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use tokio::process::Command;
+use tokio::time::timeout;
+use walkdir::WalkDir;
+
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct WorkspaceStatsHandler;
+
+const DEFAULT_LIMIT: usize = 10;
+const MAX_LIMIT: usize = 50;
+/// Stop counting after this many files so huge trees still answer quickly.
+const MAX_FILES: usize = 50_000;
+/// Number of recent commits inspected for churn.
+const CHURN_COMMITS: usize = 500;
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+/// Directories skipped when the workspace is not a git repository.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "dist", "build", "__pycache__"];
+
+fn default_limit() -> usize {
+    DEFAULT_LIMIT
+}
+
+#[derive(Deserialize)]
+struct WorkspaceStatsArgs {
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+#[async_trait]
+impl ToolHandler for WorkspaceStatsHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation { payload, turn, .. } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "workspace_stats handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+
+        let args: WorkspaceStatsArgs = serde_json::from_str(&arguments).map_err(|err| {
+            FunctionCallError::RespondToModel(format!(
+                "failed to parse function arguments: {err:?}"
+            ))
+        })?;
+
+        if args.limit == 0 {
+            return Err(FunctionCallError::RespondToModel(
+                "limit must be greater than zero".to_string(),
+            ));
+        }
+        let limit = args.limit.min(MAX_LIMIT);
+
+        let root = turn.resolve_path(args.path);
+        let metadata = tokio::fs::metadata(&root).await.map_err(|err| {
+            FunctionCallError::RespondToModel(format!(
+                "unable to access `{}`: {err}",
+                root.display()
+            ))
+        })?;
+        if !metadata.is_dir() {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "`{}` is not a directory",
+                root.display()
+            )));
+        }
+
+        let git_files = git_lines(
+            &root,
+            &["ls-files", "--cached", "--others", "--exclude-standard"],
+        )
+        .await;
+        let churn = match git_files {
+            Some(_) => git_lines(
+                &root,
+                &[
+                    "log",
+                    "--no-merges",
+                    "--relative",
+                    "--name-only",
+                    "--format=",
+                    &format!("-n{CHURN_COMMITS}"),
+                ],
+            )
+            .await
+            .map(|lines| count_churn(&lines))
+            .unwrap_or_default(),
+            None => Vec::new(),
+        };
+
+        let stats_root = root.clone();
+        let stats = tokio::task::spawn_blocking(move || {
+            let files = match git_files {
+                Some(files) => files.into_iter().map(PathBuf::from).collect(),
+                None => walk_files(&stats_root),
+            };
+            WorkspaceStats::collect(&stats_root, files)
+        })
+        .await
+        .map_err(|err| {
+            FunctionCallError::RespondToModel(format!("failed to collect workspace stats: {err}"))
+        })?;
+
+        Ok(ToolOutput::Function {
+            content: stats.render(&root, &churn, limit),
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct LanguageStats {
+    files: usize,
+    bytes: u64,
+}
+
+#[derive(Debug, PartialEq)]
+struct DependencyCount {
+    manifest: PathBuf,
+    runtime: usize,
+    dev: usize,
+    build: usize,
+}
+
+#[derive(Debug, Default)]
+struct WorkspaceStats {
+    total_files: usize,
+    total_bytes: u64,
+    truncated: bool,
+    languages: HashMap<&'static str, LanguageStats>,
+    /// `(bytes, path)` for every counted file.
+    sizes: Vec<(u64, PathBuf)>,
+    dependencies: Vec<DependencyCount>,
+}
+
+impl WorkspaceStats {
+    /// Stats `files` (relative to `root`) and parses any dependency manifests among them.
+    fn collect(root: &Path, files: Vec<PathBuf>) -> Self {
+        let mut stats = WorkspaceStats::default();
+        for relative in files {
+            if stats.total_files == MAX_FILES {
+                stats.truncated = true;
+                break;
+            }
+            let path = root.join(&relative);
+            let Ok(metadata) = std::fs::symlink_metadata(&path) else {
+                // Deleted but still in the index.
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            let bytes = metadata.len();
+            stats.total_files += 1;
+            stats.total_bytes += bytes;
+            let language = stats
+                .languages
+                .entry(language_for(&relative).unwrap_or("Other"))
+                .or_default();
+            language.files += 1;
+            language.bytes += bytes;
+
+            if let Some(file_name) = relative.file_name().and_then(OsStr::to_str)
+                && is_manifest(file_name)
+                && let Ok(contents) = std::fs::read_to_string(&path)
+                && let Some((runtime, dev, build)) = count_dependencies(file_name, &contents)
+                && runtime + dev + build > 0
+            {
+                stats.dependencies.push(DependencyCount {
+                    manifest: relative.clone(),
+                    runtime,
+                    dev,
+                    build,
+                });
+            }
+            stats.sizes.push((bytes, relative));
+        }
+        stats
+    }
+
+    fn render(&self, root: &Path, churn: &[(usize, String)], limit: usize) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Workspace: {}", root.display());
+        let _ = write!(
+            out,
+            "Files: {} ({})",
+            self.total_files,
+            format_bytes(self.total_bytes)
+        );
+        if self.truncated {
+            let _ = write!(out, " [stopped counting at {MAX_FILES} files]");
+        }
+        out.push('\n');
+
+        let mut languages: Vec<_> = self.languages.iter().collect();
+        languages.sort_by_key(|(name, stats)| (Reverse(stats.bytes), **name));
+        out.push_str("\nLanguages (by size):\n");
+        for (name, stats) in languages.into_iter().take(limit) {
+            let share = if self.total_bytes == 0 {
+                0.0
+            } else {
+                stats.bytes as f64 * 100.0 / self.total_bytes as f64
+            };
+            let _ = writeln!(
+                out,
+                "  {name}: {} files, {} ({share:.1}%)",
+                stats.files,
+                format_bytes(stats.bytes)
+            );
+        }
+
+        let mut sizes: Vec<_> = self.sizes.iter().collect();
+        sizes.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        out.push_str("\nLargest files:\n");
+        for (bytes, path) in sizes.into_iter().take(limit) {
+            let _ = writeln!(out, "  {} {}", format_bytes(*bytes), path.display());
+        }
+
+        let _ = writeln!(out, "\nMost changed files (last {CHURN_COMMITS} commits):");
+        if churn.is_empty() {
+            out.push_str("  (no git history)\n");
+        }
+        for (commits, path) in churn.iter().take(limit) {
+            let _ = writeln!(out, "  {commits} {path}");
+        }
+
+        let mut dependencies: Vec<_> = self.dependencies.iter().collect();
+        dependencies.sort_by(|a, b| {
+            (b.runtime + b.dev + b.build)
+                .cmp(&(a.runtime + a.dev + a.build))
+                .then_with(|| a.manifest.cmp(&b.manifest))
+        });
+        out.push_str("\nDependencies:\n");
+        if dependencies.is_empty() {
+            out.push_str("  (no manifests found)\n");
+        }
+        for dependency in dependencies.iter().take(limit) {
+            let _ = write!(
+                out,
+                "  {}: {}",
+                dependency.manifest.display(),
+                dependency.runtime
+            );
+            if dependency.dev > 0 || dependency.build > 0 {
+                let _ = write!(
+                    out,
+                    " (+{} dev, +{} build)",
+                    dependency.dev, dependency.build
+                );
+            }
+            out.push('\n');
+        }
+        if dependencies.len() > limit {
+            let _ = writeln!(out, "  ... {} more manifests", dependencies.len() - limit);
+        }
+        out
+    }
+}
+
+/// Runs git in `cwd` and returns its output lines, or `None` when git fails
+/// (not a repository, git missing, or too slow).
+async fn git_lines(cwd: &Path, args: &[&str]) -> Option<Vec<String>> {
+    let output = timeout(
+        COMMAND_TIMEOUT,
+        Command::new("git")
+            .current_dir(cwd)
+            .args(["-c", "core.quotepath=off"])
+            .args(args)
+            .output(),
+    )
+    .await
+    .ok()?
+    .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// Commit counts per path, most changed first.
+fn count_churn(lines: &[String]) -> Vec<(usize, String)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for line in lines {
+        *counts.entry(line.as_str()).or_default() += 1;
+    }
+    let mut churn: Vec<_> = counts
+        .into_iter()
+        .map(|(path, commits)| (commits, path.to_string()))
+        .collect();
+    churn.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    churn
+}
+
+/// Lists files under `root` outside of a git repository, skipping hidden and
+/// build output directories.
+fn walk_files(root: &Path) -> Vec<PathBuf> {
+    WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || !entry.file_type().is_dir()
+                || entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| !name.starts_with('.') && !SKIPPED_DIRS.contains(&name))
+        })
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.path().strip_prefix(root).ok().map(Path::to_path_buf))
+        .take(MAX_FILES + 1)
+        .collect()
+}
+
+fn is_manifest(file_name: &str) -> bool {
+    matches!(
+        file_name,
+        "Cargo.toml" | "package.json" | "go.mod" | "requirements.txt" | "pyproject.toml"
+    )
+}
+
+/// Returns `(runtime, dev, build)` dependency counts for a known manifest.
+fn count_dependencies(file_name: &str, contents: &str) -> Option<(usize, usize, usize)> {
+    match file_name {
+        "Cargo.toml" => {
+            let manifest: toml::Table = toml::from_str(contents).ok()?;
+            let table_len = |value: Option<&toml::Value>| {
+                value
+                    .and_then(toml::Value::as_table)
+                    .map_or(0, toml::Table::len)
+            };
+            let workspace = manifest
+                .get("workspace")
+                .and_then(|workspace| workspace.get("dependencies"));
+            Some((
+                table_len(manifest.get("dependencies")) + table_len(workspace),
+                table_len(manifest.get("dev-dependencies")),
+                table_len(manifest.get("build-dependencies")),
+            ))
+        }
+        "package.json" => {
+            let manifest: JsonValue = serde_json::from_str(contents).ok()?;
+            let count = |key: &str| {
+                manifest
+                    .get(key)
+                    .and_then(JsonValue::as_object)
+                    .map_or(0, serde_json::Map::len)
+            };
+            Some((
+                count("dependencies") + count("peerDependencies"),
+                count("devDependencies"),
+                0,
+            ))
+        }
+        "go.mod" => {
+            let mut in_block = false;
+            let mut runtime = 0;
+            for line in contents.lines().map(str::trim) {
+                if in_block {
+                    if line == ")" {
+                        in_block = false;
+                    } else if !line.is_empty() && !line.starts_with("//") {
+                        runtime += 1;
+                    }
+                } else if line == "require (" {
+                    in_block = true;
+                } else if line.starts_with("require ") {
+                    runtime += 1;
+                }
+            }
+            Some((runtime, 0, 0))
+        }
+        "requirements.txt" => Some((
+            contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('-'))
+                .count(),
+            0,
+            0,
+        )),
+        "pyproject.toml" => {
+            let manifest: toml::Table = toml::from_str(contents).ok()?;
+            let project = manifest
+                .get("project")
+                .and_then(|project| project.get("dependencies"))
+                .and_then(toml::Value::as_array)
+                .map_or(0, Vec::len);
+            let poetry = manifest
+                .get("tool")
+                .and_then(|tool| tool.get("poetry"))
+                .and_then(|poetry| poetry.get("dependencies"))
+                .and_then(toml::Value::as_table)
+                .map_or(0, |deps| {
+                    deps.keys().filter(|name| *name != "python").count()
+                });
+            Some((project + poetry, 0, 0))
+        }
+        _ => None,
+    }
+}
+
+fn language_for(path: &Path) -> Option<&'static str> {
+    let file_name = path.file_name()?.to_str()?;
+    match file_name {
+        "Dockerfile" => return Some("Dockerfile"),
+        "Makefile" | "makefile" | "GNUmakefile" => return Some("Makefile"),
+        _ => {}
+    }
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    let language = match extension.as_str() {
+        "rs" => "Rust",
+        "ts" | "tsx" | "mts" | "cts" => "TypeScript",
+        "js" | "jsx" | "mjs" | "cjs" => "JavaScript",
+        "py" | "pyi" => "Python",
+        "go" => "Go",
+        "java" => "Java",
+        "kt" | "kts" => "Kotlin",
+        "swift" => "Swift",
+        "c" | "h" => "C",
+        "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" => "C++",
+        "cs" => "C#",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "scala" => "Scala",
+        "ex" | "exs" => "Elixir",
+        "hs" => "Haskell",
+        "lua" => "Lua",
+        "dart" => "Dart",
+        "zig" => "Zig",
+        "sh" | "bash" | "zsh" | "fish" => "Shell",
+        "ps1" => "PowerShell",
+        "sql" => "SQL",
+        "html" | "htm" => "HTML",
+        "css" | "scss" | "sass" | "less" => "CSS",
+        "vue" => "Vue",
+        "svelte" => "Svelte",
+        "md" | "mdx" | "rst" | "txt" => "Text",
+        "json" | "jsonc" => "JSON",
+        "toml" => "TOML",
+        "yaml" | "yml" => "YAML",
+        "xml" => "XML",
+        "proto" => "Protocol Buffers",
+        "png" | "jpg" | "jpeg" | "gif" | "svg" | "ico" | "webp" => "Image",
+        _ => return None,
+    };
+    Some(language)
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    format!("{value:.1} {unit}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    #[test]
+    fn collects_languages_sizes_and_dependencies() {
+        let temp = tempdir().expect("create tempdir");
+        let root = temp.path();
+        std::fs::create_dir(root.join("src")).expect("create src");
+        std::fs::write(root.join("src/main.rs"), "fn main() {}\n".repeat(10)).expect("write");
+        std::fs::write(root.join("src/lib.rs"), "pub fn f() {}\n").expect("write");
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"demo\"\n\n[dependencies]\nserde = \"1\"\ntokio = \"1\"\n\n[dev-dependencies]\ntempfile = \"3\"\n",
+        )
+        .expect("write");
+
+        let stats = WorkspaceStats::collect(
+            root,
+            vec![
+                PathBuf::from("src/main.rs"),
+                PathBuf::from("src/lib.rs"),
+                PathBuf::from("Cargo.toml"),
+                PathBuf::from("deleted.rs"),
+            ],
+        );
+
+        assert_eq!(stats.total_files, 3);
+        assert_eq!(
+            stats.languages.get("Rust"),
+            Some(&LanguageStats {
+                files: 2,
+                bytes: 144,
+            })
+        );
+        assert_eq!(
+            stats.dependencies,
+            vec![DependencyCount {
+                manifest: PathBuf::from("Cargo.toml"),
+                runtime: 2,
+                dev: 1,
+                build: 0,
+            }]
+        );
+
+        let churn = count_churn(&[
+            "src/lib.rs".to_string(),
+            "src/main.rs".to_string(),
+            "src/lib.rs".to_string(),
+        ]);
+        let rendered = stats.render(root, &churn, 1);
+        assert!(
+            rendered.contains("\nLanguages (by size):\n  Rust: 2 files, 144 B ("),
+            "{rendered}"
+        );
+        assert!(rendered.contains("\nLargest files:\n  130 B src/main.rs\n"));
+        assert!(rendered.contains("commits):\n  2 src/lib.rs\n\n"));
+        assert!(rendered.contains("\nDependencies:\n  Cargo.toml: 2 (+1 dev, +0 build)\n"));
+    }
+
+    #[test]
+    fn counts_dependencies_for_other_ecosystems() {
+        assert_eq!(
+            count_dependencies(
+                "package.json",
+                r#"{"dependencies":{"react":"18"},"devDependencies":{"jest":"29","ts-node":"10"}}"#,
+            ),
+            Some((1, 2, 0))
+        );
+        assert_eq!(
+            count_dependencies(
+                "go.mod",
+                "module x\n\nrequire (\n\tgithub.com/a/b v1.0.0\n\tgithub.com/c/d v1.2.0 // indirect\n)\n\nrequire github.com/e/f v0.1.0\n",
+            ),
+            Some((3, 0, 0))
+        );
+        assert_eq!(
+            count_dependencies(
+                "requirements.txt",
+                "# pinned\nrequests==2.31\n-r base.txt\nflask\n"
+            ),
+            Some((2, 0, 0))
+        );
+        assert_eq!(
+            count_dependencies(
+                "Cargo.toml",
+                "[workspace]\nmembers = [\"a\"]\n\n[workspace.dependencies]\nanyhow = \"1\"\n",
+            ),
+            Some((1, 0, 0))
+        );
+    }
+
+    #[test]
+    fn walks_files_outside_git_skipping_build_output() {
+        let temp = tempdir().expect("create tempdir");
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("target/debug")).expect("create target");
+        std::fs::create_dir(root.join(".cache")).expect("create hidden");
+        std::fs::write(root.join("target/debug/app"), "bin").expect("write");
+        std::fs::write(root.join(".cache/blob"), "blob").expect("write");
+        std::fs::write(root.join(".env"), "KEY=1").expect("write");
+        std::fs::write(root.join("main.py"), "print(1)").expect("write");
+
+        let mut files = walk_files(root);
+        files.sort();
+        assert_eq!(files, vec![PathBuf::from(".env"), PathBuf::from("main.py")]);
+    }
+}
@@ -2,6 +2,7 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use tokio::sync::RwLock;
+use tokio::sync::Semaphore;
 use tokio_util::either::Either;
 use tokio_util::sync::CancellationToken;
 use tokio_util::task::AbortOnDropHandle;
@@ -23,6 +24,8 @@ pub(crate) struct ToolCallRuntime {
     turn_context: Arc<TurnContext>,
     tracker: SharedTurnDiffTracker,
     parallel_execution: Arc<RwLock<()>>,
+    /// Caps how many parallel calls run at once (`max_parallel_tool_calls`).
+    parallel_slots: Arc<Semaphore>,
 }
 
 impl ToolCallRuntime {
@@ -32,12 +35,14 @@ impl ToolCallRuntime {
         turn_context: Arc<TurnContext>,
         tracker: SharedTurnDiffTracker,
     ) -> Self {
+        let max_parallel = turn_context.client.config().max_parallel_tool_calls;
         Self {
             router,
             session,
             turn_context,
             tracker,
             parallel_execution: Arc::new(RwLock::new(())),
+            parallel_slots: Arc::new(Semaphore::new(max_parallel.max(1))),
         }
     }
 
@@ -46,13 +51,16 @@ impl ToolCallRuntime {
         call: ToolCall,
         cancellation_token: CancellationToken,
     ) -> impl std::future::Future<Output = Result<ResponseInputItem, CodexErr>> {
-        let supports_parallel = self.router.tool_supports_parallel(&call.tool_name);
+        let supports_parallel =
+            self.router
+                .call_supports_parallel(&self.session, &self.turn_context, &call);
 
         let router = Arc::clone(&self.router);
         let session = Arc::clone(&self.session);
         let turn = Arc::clone(&self.turn_context);
         let tracker = Arc::clone(&self.tracker);
         let lock = Arc::clone(&self.parallel_execution);
+        let slots = Arc::clone(&self.parallel_slots);
         let started = Instant::now();
 
         let handle: AbortOnDropHandle<Result<ResponseInputItem, FunctionCallError>> =
@@ -68,6 +76,11 @@ impl ToolCallRuntime {
                         } else {
                             Either::Right(lock.write().await)
                         };
+                        let _slot = if supports_parallel {
+                            slots.acquire_owned().await.ok()
+                        } else {
+                            None
+                        };
 
                         router
                             .dispatch_tool_call(session, turn, tracker, call.clone())
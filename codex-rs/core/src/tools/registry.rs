@@ -110,11 +110,14 @@ impl ToolRegistry {
                     let output_cell = &output_cell;
                     let invocation = invocation;
                     async move {
-                        if handler.is_mutating(&invocation) {
+                        let _agent_writing = if handler.is_mutating(&invocation) {
                             tracing::trace!("waiting for tool gate");
                             invocation.turn.tool_call_gate.wait_ready().await;
                             tracing::trace!("tool gate released");
-                        }
+                            Some(invocation.session.services.external_edits.agent_writing())
+                        } else {
+                            None
+                        };
                         match handler.handle(invocation).await {
                             Ok(output) => {
                                 let preview = output.log_preview();
@@ -184,6 +187,12 @@ impl ToolRegistryBuilder {
             .push(ConfiguredToolSpec::new(spec, supports_parallel_tool_calls));
     }
 
+    /// Whether a tool named `name` was already added.
+    pub fn has_tool(&self, name: &str) -> bool {
+        self.handlers.contains_key(name)
+            || self.specs.iter().any(|config| config.spec.name() == name)
+    }
+
     pub fn register_handler(&mut self, name: impl Into<String>, handler: Arc<dyn ToolHandler>) {
         let name = name.into();
         if self
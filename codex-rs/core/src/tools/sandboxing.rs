@@ -202,6 +202,7 @@ pub(crate) trait ProvidesSandboxRetryData {
     fn sandbox_retry_data(&self) -> Option<SandboxRetryData>;
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub(crate) enum ToolError {
     Rejected(String),
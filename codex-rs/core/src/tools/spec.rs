@@ -1,8 +1,11 @@
 use crate::client_common::tools::ResponsesApiTool;
 use crate::client_common::tools::ToolSpec;
+use crate::config::types::CustomToolConfig;
 use crate::features::Feature;
 use crate::features::Features;
 use crate::model_family::ModelFamily;
+use crate::plugins::PluginTool;
+use crate::plugins::PluginToolSpec;
 use crate::tools::handlers::PLAN_TOOL;
 use crate::tools::handlers::apply_patch::ApplyPatchToolType;
 use crate::tools::handlers::apply_patch::create_apply_patch_freeform_tool;
@@ -35,7 +38,13 @@ pub(crate) struct ToolsConfig {
     pub apply_patch_tool_type: Option<ApplyPatchToolType>,
     pub web_search_request: bool,
     pub include_view_image_tool: bool,
+    pub include_workspace_stats_tool: bool,
+    pub include_search_code_tool: bool,
+    pub include_fetch_url_tool: bool,
+    pub include_sub_agent_tools: bool,
     pub experimental_supported_tools: Vec<String>,
+    pub custom_tools: HashMap<String, CustomToolConfig>,
+    pub plugin_tools: Vec<PluginTool>,
 }
 
 pub(crate) struct ToolsConfigParams<'a> {
@@ -52,6 +61,10 @@ impl ToolsConfig {
         let include_apply_patch_tool = features.enabled(Feature::ApplyPatchFreeform);
         let include_web_search_request = features.enabled(Feature::WebSearchRequest);
         let include_view_image_tool = features.enabled(Feature::ViewImageTool);
+        let include_workspace_stats_tool = features.enabled(Feature::WorkspaceStatsTool);
+        let include_search_code_tool = features.enabled(Feature::SearchCodeTool);
+        let include_fetch_url_tool = features.enabled(Feature::FetchUrlTool);
+        let include_sub_agent_tools = features.enabled(Feature::SubAgents);
 
         let shell_type = if !features.enabled(Feature::ShellTool) {
             ConfigShellToolType::Disabled
@@ -78,9 +91,28 @@ impl ToolsConfig {
             apply_patch_tool_type,
             web_search_request: include_web_search_request,
             include_view_image_tool,
+            include_workspace_stats_tool,
+            include_search_code_tool,
+            include_fetch_url_tool,
+            include_sub_agent_tools,
             experimental_supported_tools: model_family.experimental_supported_tools.clone(),
+            custom_tools: HashMap::new(),
+            plugin_tools: Vec::new(),
         }
     }
+
+    /// Also offers the tools from `[custom_tools]`.
+    pub fn with_custom_tools(mut self, custom_tools: HashMap<String, CustomToolConfig>) -> Self {
+        self.custom_tools = custom_tools;
+        self
+    }
+
+    /// Leaves out the tools that need network access when the sandbox
+    /// policy does not allow it.
+    pub fn with_network_access(mut self, network_access: bool) -> Self {
+        self.include_fetch_url_tool &= network_access;
+        self
+    }
 }
 
 /// Generic JSON‑Schema subset needed for our tool definitions
@@ -670,6 +702,180 @@ fn create_list_dir_tool() -> ToolSpec {
     })
 }
 
+fn create_workspace_stats_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "path".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Directory to summarize. Defaults to the session's working directory.".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "limit".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "Maximum number of rows per section (defaults to 10, max 50).".to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "workspace_stats".to_string(),
+        description: "Summarizes a workspace in one call: file count and size per language, the largest files, the most changed files in recent git history, and dependency counts per manifest. Use it to orient yourself in an unfamiliar repository before exploring it."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_search_code_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "query".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Words the code uses or is about, such as \"payment reconciliation\".".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "path".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Only return code under this directory. Defaults to the whole repository."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "limit".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "Maximum number of matches to return (defaults to 8, max 30).".to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "search_code".to_string(),
+        description: "Finds the code most related to a description, from an index of the repository that is brought up to date before each search. When an embedding model is configured, code is ranked by meaning, so it is found even when it uses none of the query's words; otherwise code is ranked by the words it shares with the query, matched by stem, so `reconcile_payments` matches \"payment reconciliation\". The output says which was used. Returns file paths and line ranges, best match first, with a preview of each. Use it to find where something is implemented when you do not know the exact names to grep for."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["query".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_fetch_url_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "url".to_string(),
+        JsonSchema::String {
+            description: Some("The http or https URL of the page to fetch.".to_string()),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "fetch_url".to_string(),
+        description: "Downloads a web page, such as documentation or an issue, and returns it as markdown. Only some domains may be allowed; the error says so when a URL is not."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["url".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_spawn_agent_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "task".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "What the sub-agent should do, with the context it needs: it does not see this conversation."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "access".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "\"read\" (default) to only read files and run commands, or \"write\" to also change files."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "max_tokens".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "Token budget; the sub-agent is stopped when it is used up. Defaults to the most allowed."
+                    .to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "spawn_agent".to_string(),
+        description: "Starts a sub-agent on a self-contained subtask, such as writing the tests for one module, and returns its number at once so you can keep working on something else. Give sub-agents parts of the work that do not touch the same files. Collect what they did with wait_agents."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["task".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_wait_agents_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "ids".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::Number { description: None }),
+            description: Some(
+                "The sub-agents to wait for. Defaults to every one whose report you have not seen."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "timeout_seconds".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "How long to wait before returning with the sub-agents still running (default 600, 0 to check without waiting)."
+                    .to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "wait_agents".to_string(),
+        description:
+            "Waits for sub-agents started with spawn_agent to finish and returns their reports."
+                .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
 fn create_list_mcp_resources_tool() -> ToolSpec {
     let mut properties = BTreeMap::new();
     properties.insert(
@@ -857,6 +1063,52 @@ pub(crate) fn mcp_tool_to_openai_tool(
     })
 }
 
+/// The function tool a `[custom_tools]` entry is offered to the model as.
+pub(crate) fn custom_tool_to_openai_tool(
+    name: String,
+    tool: &CustomToolConfig,
+) -> Result<ResponsesApiTool, serde_json::Error> {
+    function_tool(name, tool.description.clone(), tool.parameters.clone())
+}
+
+/// The function tool a plugin's tool is offered to the model as.
+pub(crate) fn plugin_tool_to_openai_tool(
+    tool: &PluginToolSpec,
+) -> Result<ResponsesApiTool, serde_json::Error> {
+    function_tool(
+        tool.name.clone(),
+        tool.description.clone(),
+        tool.parameters.clone(),
+    )
+}
+
+fn function_tool(
+    name: String,
+    description: String,
+    parameters: Option<JsonValue>,
+) -> Result<ResponsesApiTool, serde_json::Error> {
+    let mut parameters =
+        parameters.unwrap_or_else(|| json!({ "type": "object", "additionalProperties": false }));
+    if let JsonValue::Object(map) = &mut parameters
+        && !map.contains_key("properties")
+    {
+        map.insert("properties".to_string(), json!({}));
+    }
+    sanitize_json_schema(&mut parameters);
+    let parameters = serde_json::from_value::<JsonSchema>(parameters)?;
+    if !matches!(parameters, JsonSchema::Object { .. }) {
+        return Err(serde::de::Error::custom(
+            "the arguments must be described by an object schema",
+        ));
+    }
+    Ok(ResponsesApiTool {
+        name,
+        description,
+        strict: false,
+        parameters,
+    })
+}
+
 /// Sanitize a JSON Schema (as serde_json::Value) so it can fit our limited
 /// JsonSchema enum. This function:
 /// - Ensures every schema object has a "type". If missing, infers it from
@@ -974,17 +1226,23 @@ pub(crate) fn build_specs(
     mcp_tools: Option<HashMap<String, mcp_types::Tool>>,
 ) -> ToolRegistryBuilder {
     use crate::tools::handlers::ApplyPatchHandler;
+    use crate::tools::handlers::ConfigToolHandler;
+    use crate::tools::handlers::FetchUrlHandler;
     use crate::tools::handlers::GrepFilesHandler;
     use crate::tools::handlers::ListDirHandler;
     use crate::tools::handlers::McpHandler;
     use crate::tools::handlers::McpResourceHandler;
     use crate::tools::handlers::PlanHandler;
+    use crate::tools::handlers::PluginToolHandler;
     use crate::tools::handlers::ReadFileHandler;
+    use crate::tools::handlers::SearchCodeHandler;
     use crate::tools::handlers::ShellCommandHandler;
     use crate::tools::handlers::ShellHandler;
+    use crate::tools::handlers::SubAgentsHandler;
     use crate::tools::handlers::TestSyncHandler;
     use crate::tools::handlers::UnifiedExecHandler;
     use crate::tools::handlers::ViewImageHandler;
+    use crate::tools::handlers::WorkspaceStatsHandler;
     use std::sync::Arc;
 
     let mut builder = ToolRegistryBuilder::new();
@@ -1095,6 +1353,68 @@ pub(crate) fn build_specs(
         builder.register_handler("view_image", view_image_handler);
     }
 
+    if config.include_workspace_stats_tool {
+        builder.push_spec_with_parallel_support(create_workspace_stats_tool(), true);
+        builder.register_handler("workspace_stats", Arc::new(WorkspaceStatsHandler));
+    }
+
+    if config.include_search_code_tool {
+        // Searches update a shared index on disk, so they run one at a time.
+        builder.push_spec(create_search_code_tool());
+        builder.register_handler("search_code", Arc::new(SearchCodeHandler));
+    }
+
+    if config.include_fetch_url_tool {
+        builder.push_spec_with_parallel_support(create_fetch_url_tool(), true);
+        builder.register_handler("fetch_url", Arc::new(FetchUrlHandler));
+    }
+
+    if config.include_sub_agent_tools {
+        let sub_agents_handler = Arc::new(SubAgentsHandler);
+        builder.push_spec_with_parallel_support(create_spawn_agent_tool(), true);
+        builder.push_spec(create_wait_agents_tool());
+        builder.register_handler("spawn_agent", sub_agents_handler.clone());
+        builder.register_handler("wait_agents", sub_agents_handler);
+    }
+
+    let mut custom_tools: Vec<(&String, &CustomToolConfig)> = config.custom_tools.iter().collect();
+    custom_tools.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, tool) in custom_tools {
+        if builder.has_tool(name) {
+            tracing::warn!("custom tool {name:?} has the name of a built-in tool; skipping it");
+            continue;
+        }
+        match custom_tool_to_openai_tool(name.clone(), tool) {
+            Ok(converted_tool) => {
+                builder.push_spec(ToolSpec::Function(converted_tool));
+                builder
+                    .register_handler(name.clone(), Arc::new(ConfigToolHandler::new(tool.clone())));
+            }
+            Err(e) => {
+                tracing::error!("Failed to convert custom tool {name:?} to OpenAI tool: {e:?}");
+            }
+        }
+    }
+
+    for tool in &config.plugin_tools {
+        let name = &tool.spec.name;
+        if builder.has_tool(name) {
+            tracing::warn!("plugin tool {name:?} has the name of another tool; skipping it");
+            continue;
+        }
+        match plugin_tool_to_openai_tool(&tool.spec) {
+            Ok(converted_tool) => {
+                // Plugins share no state between calls, so their calls can run in parallel.
+                builder.push_spec_with_parallel_support(ToolSpec::Function(converted_tool), true);
+                builder
+                    .register_handler(name.clone(), Arc::new(PluginToolHandler::new(tool.clone())));
+            }
+            Err(e) => {
+                tracing::error!("Failed to convert plugin tool {name:?} to OpenAI tool: {e:?}");
+            }
+        }
+    }
+
     if let Some(mcp_tools) = mcp_tools {
         let mut entries: Vec<(String, mcp_types::Tool)> = mcp_tools.into_iter().collect();
         entries.sort_by(|a, b| a.0.cmp(&b.0));
@@ -1535,6 +1855,141 @@ mod tests {
         assert!(tools.iter().any(|tool| tool_name(&tool.spec) == "list_dir"));
     }
 
+    #[test]
+    fn test_workspace_stats_tool_follows_feature() {
+        let model_family = find_family_for_model("gpt-5-codex")
+            .expect("gpt-5-codex should be a valid model family");
+        let mut features = Features::with_defaults();
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            features: &features,
+        });
+        let (tools, _) = build_specs(&config, None).build();
+        assert!(
+            !tools
+                .iter()
+                .any(|tool| tool_name(&tool.spec) == "workspace_stats")
+        );
+
+        features.enable(Feature::WorkspaceStatsTool);
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            features: &features,
+        });
+        let (tools, _) = build_specs(&config, None).build();
+        assert!(find_tool(&tools, "workspace_stats").supports_parallel_tool_calls);
+    }
+
+    #[test]
+    fn test_search_code_tool_follows_feature() {
+        let model_family = find_family_for_model("gpt-5-codex")
+            .expect("gpt-5-codex should be a valid model family");
+        let mut features = Features::with_defaults();
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            features: &features,
+        });
+        let (tools, _) = build_specs(&config, None).build();
+        assert!(
+            !tools
+                .iter()
+                .any(|tool| tool_name(&tool.spec) == "search_code")
+        );
+
+        features.enable(Feature::SearchCodeTool);
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            features: &features,
+        });
+        let (tools, _) = build_specs(&config, None).build();
+        assert!(!find_tool(&tools, "search_code").supports_parallel_tool_calls);
+    }
+
+    #[test]
+    fn test_fetch_url_tool_needs_feature_and_network_access() {
+        let model_family = find_family_for_model("gpt-5-codex")
+            .expect("gpt-5-codex should be a valid model family");
+        let mut features = Features::with_defaults();
+        features.enable(Feature::FetchUrlTool);
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            features: &features,
+        });
+        let (tools, _) = build_specs(&config.clone().with_network_access(true), None).build();
+        assert!(find_tool(&tools, "fetch_url").supports_parallel_tool_calls);
+
+        let (tools, _) = build_specs(&config.with_network_access(false), None).build();
+        assert!(
+            !tools
+                .iter()
+                .any(|tool| tool_name(&tool.spec) == "fetch_url")
+        );
+    }
+
+    #[test]
+    fn test_sub_agent_tools_need_the_feature() {
+        let model_family = find_family_for_model("gpt-5-codex")
+            .expect("gpt-5-codex should be a valid model family");
+        let mut features = Features::with_defaults();
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            features: &features,
+        });
+        let (tools, _) = build_specs(&config, None).build();
+        assert!(
+            !tools
+                .iter()
+                .any(|tool| tool_name(&tool.spec) == "spawn_agent")
+        );
+
+        features.enable(Feature::SubAgents);
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            features: &features,
+        });
+        let (tools, _) = build_specs(&config, None).build();
+        assert!(find_tool(&tools, "spawn_agent").supports_parallel_tool_calls);
+        assert!(!find_tool(&tools, "wait_agents").supports_parallel_tool_calls);
+    }
+
+    #[test]
+    fn test_custom_tools_are_offered_unless_they_shadow_a_builtin() {
+        let model_family = find_family_for_model("gpt-5-codex")
+            .expect("gpt-5-codex should be a valid model family");
+        let features = Features::with_defaults();
+        let tool = |description: &str| CustomToolConfig {
+            description: description.to_string(),
+            parameters: None,
+            command: vec!["make".to_string(), "verify".to_string()],
+            cwd: None,
+            timeout_ms: None,
+        };
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            features: &features,
+        })
+        .with_custom_tools(HashMap::from([
+            ("verify".to_string(), tool("Run the verification suite.")),
+            ("update_plan".to_string(), tool("Not the real update_plan.")),
+        ]));
+        let (tools, _) = build_specs(&config, None).build();
+
+        assert_eq!(
+            find_tool(&tools, "verify").spec,
+            ToolSpec::Function(ResponsesApiTool {
+                name: "verify".to_string(),
+                description: "Run the verification suite.".to_string(),
+                strict: false,
+                parameters: JsonSchema::Object {
+                    properties: BTreeMap::new(),
+                    required: None,
+                    additional_properties: Some(false.into()),
+                },
+            })
+        );
+        assert_eq!(find_tool(&tools, "update_plan").spec, PLAN_TOOL.clone());
+    }
+
     #[test]
     fn test_build_specs_mcp_tools_converted() {
         let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
@@ -0,0 +1,126 @@
+//! Append-only record of model usage, stored at
+//! `~/.codex/usage_history.jsonl` with one JSON object per model request:
+//!
+//! ````text
+//! {"ts":<unix_seconds>,"model":"gpt-5.1-codex","provider":"OpenAI","auth":"me@example.com","project":"/src/app","input_tokens":1200,"cached_input_tokens":800,"output_tokens":300,"reasoning_output_tokens":120,"usd":0.0047}
+//! ````
+//!
+//! `usd` is the estimated cost (see [`crate::pricing`]) and is absent for
+//! models without a known price. The daily and monthly budgets and
+//! `codex usage report` read this file. Lines are written the same way as
+//! the limit history, so concurrent sessions do not interleave records, and
+//! are shared with other machines the same way under `shared_state_dir`.
+
+use std::io::Result;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::shared_state;
+
+/// Filename that stores the usage history inside `~/.codex`.
+const USAGE_HISTORY_FILENAME: &str = "usage_history.jsonl";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct UsageEntry {
+    /// When the request completed (Unix seconds).
+    pub ts: i64,
+    pub model: String,
+    /// Name of the model provider the request went to.
+    #[serde(default)]
+    pub provider: String,
+    /// Who was signed in, e.g. the account email or "API key ending in
+    /// 1234"; empty for providers that do not use Codex's credentials.
+    #[serde(default)]
+    pub auth: String,
+    /// Root of the git repository the session ran in, or its working
+    /// directory outside a repository.
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub input_tokens: i64,
+    #[serde(default)]
+    pub cached_input_tokens: i64,
+    #[serde(default)]
+    pub output_tokens: i64,
+    #[serde(default)]
+    pub reasoning_output_tokens: i64,
+    /// Estimated cost in US dollars; `None` when the model has no price.
+    #[serde(default)]
+    pub usd: Option<f64>,
+}
+
+pub fn usage_history_path(codex_home: &Path) -> PathBuf {
+    codex_home.join(USAGE_HISTORY_FILENAME)
+}
+
+pub(crate) fn append_usage(
+    codex_home: &Path,
+    shared_dir: Option<&Path>,
+    entry: &UsageEntry,
+) -> Result<()> {
+    let line = serde_json::to_string(entry).map_err(std::io::Error::other)?;
+    shared_state::append_line(codex_home, shared_dir, USAGE_HISTORY_FILENAME, &line)
+}
+
+/// Reads every recorded request: this machine's oldest first, then those
+/// other machines shared through `shared_dir`. A missing file is an empty
+/// history; lines that fail to parse are skipped.
+pub fn read_usage_history(codex_home: &Path, shared_dir: Option<&Path>) -> Result<Vec<UsageEntry>> {
+    Ok(
+        shared_state::read_lines(codex_home, shared_dir, USAGE_HISTORY_FILENAME)?
+            .iter()
+            .filter_map(|line| serde_json::from_str::<UsageEntry>(line).ok())
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::io::Write;
+
+    #[test]
+    fn appended_entries_are_read_back_and_bad_lines_skipped() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        assert_eq!(
+            read_usage_history(tmp.path(), None).expect("read"),
+            Vec::new()
+        );
+
+        let entry = UsageEntry {
+            ts: 1_700_000_000,
+            model: "gpt-5.1-codex".to_string(),
+            provider: "OpenAI".to_string(),
+            auth: "me@example.com".to_string(),
+            project: Some("/src/app".to_string()),
+            input_tokens: 1200,
+            cached_input_tokens: 800,
+            output_tokens: 300,
+            reasoning_output_tokens: 120,
+            usd: Some(0.0047),
+        };
+        append_usage(tmp.path(), None, &entry).expect("append");
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(usage_history_path(tmp.path()))
+            .and_then(|mut file| file.write_all(b"not json\n{\"ts\":1,\"model\":\"o3\"}\n"))
+            .expect("write");
+
+        let entries = read_usage_history(tmp.path(), None).expect("read");
+        assert_eq!(
+            entries,
+            vec![
+                entry,
+                UsageEntry {
+                    ts: 1,
+                    model: "o3".to_string(),
+                    ..Default::default()
+                }
+            ]
+        );
+    }
+}
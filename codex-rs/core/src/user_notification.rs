@@ -1,22 +1,48 @@
+use codex_protocol::protocol::AuthSwitchReason;
 use serde::Serialize;
 use tracing::error;
 use tracing::warn;
 
+use crate::config::types::NotifyHooks;
+use crate::config::types::NotifyTarget;
+use crate::default_client::create_non_provider_client;
+
 #[derive(Debug, Default)]
 pub(crate) struct UserNotifier {
     notify_command: Option<Vec<String>>,
+    hooks: NotifyHooks,
 }
 
 impl UserNotifier {
     pub(crate) fn notify(&self, notification: &UserNotification) {
-        if let Some(notify_command) = &self.notify_command
-            && !notify_command.is_empty()
-        {
-            self.invoke_notify(notify_command, notification)
+        match notification {
+            UserNotification::AgentTurnComplete { .. } => {
+                if let Some(notify_command) = &self.notify_command
+                    && !notify_command.is_empty()
+                {
+                    Self::invoke_notify(notify_command, notification)
+                }
+            }
+            UserNotification::LimitHit { .. } => {
+                Self::deliver(self.hooks.on_limit_hit.as_ref(), notification)
+            }
+            UserNotification::AuthSwitch { .. } => {
+                Self::deliver(self.hooks.on_auth_switch.as_ref(), notification)
+            }
+        }
+    }
+
+    fn deliver(target: Option<&NotifyTarget>, notification: &UserNotification) {
+        match target {
+            Some(NotifyTarget::Command(command)) if !command.is_empty() => {
+                Self::invoke_notify(command, notification)
+            }
+            Some(NotifyTarget::Webhook(url)) => Self::post_webhook(url.clone(), notification),
+            _ => {}
         }
     }
 
-    fn invoke_notify(&self, notify_command: &[String], notification: &UserNotification) {
+    fn invoke_notify(notify_command: &[String], notification: &UserNotification) {
         let Ok(json) = serde_json::to_string(&notification) else {
             error!("failed to serialise notification payload");
             return;
@@ -34,9 +60,32 @@ impl UserNotifier {
         }
     }
 
-    pub(crate) fn new(notify: Option<Vec<String>>) -> Self {
+    /// POSTs the payload to `url` in the background; failures, and the
+    /// privacy kill switch blocking the request, are logged.
+    fn post_webhook(url: String, notification: &UserNotification) {
+        let client = match create_non_provider_client("Notification webhook") {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("not posting notification webhook: {e}");
+                return;
+            }
+        };
+        let notification = notification.clone();
+        tokio::spawn(async move {
+            match client.post(&url).json(&notification).send().await {
+                Ok(response) if !response.status().is_success() => {
+                    warn!("notification webhook returned {}", response.status());
+                }
+                Ok(_) => {}
+                Err(e) => warn!("failed to post notification webhook: {e}"),
+            }
+        });
+    }
+
+    pub(crate) fn new(notify: Option<Vec<String>>, hooks: NotifyHooks) -> Self {
         Self {
             notify_command: notify,
+            hooks,
         }
     }
 }
@@ -59,6 +108,33 @@ pub(crate) enum UserNotification {
         /// The last message sent by the assistant in the turn.
         last_assistant_message: Option<String>,
     },
+
+    /// A session hit a usage limit (`notify.on_limit_hit`).
+    #[serde(rename_all = "kebab-case")]
+    LimitHit {
+        thread_id: String,
+        /// Who was signed in, e.g. the account email or "API key ending in
+        /// 1234".
+        auth: String,
+        model: String,
+        window_minutes: Option<i64>,
+        /// When the limit resets (Unix seconds), when known.
+        resets_at: Option<i64>,
+        /// One-line summary, which Slack incoming webhooks display as is.
+        text: String,
+    },
+
+    /// Codex switched credentials or providers on its own
+    /// (`notify.on_auth_switch`).
+    #[serde(rename_all = "kebab-case")]
+    AuthSwitch {
+        thread_id: String,
+        from: String,
+        to: String,
+        reason: AuthSwitchReason,
+        /// One-line summary, which Slack incoming webhooks display as is.
+        text: String,
+    },
 }
 
 #[cfg(test)]
@@ -84,4 +160,33 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_limit_hit_and_auth_switch_notifications() -> Result<()> {
+        let notification = UserNotification::LimitHit {
+            thread_id: "b5f6c1c2-1111-2222-3333-444455556666".to_string(),
+            auth: "team@example.com".to_string(),
+            model: "gpt-5.1-codex".to_string(),
+            window_minutes: Some(300),
+            resets_at: Some(1_700_000_000),
+            text: "Codex hit the 5h usage limit for team@example.com.".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_string(&notification)?,
+            r#"{"type":"limit-hit","thread-id":"b5f6c1c2-1111-2222-3333-444455556666","auth":"team@example.com","model":"gpt-5.1-codex","window-minutes":300,"resets-at":1700000000,"text":"Codex hit the 5h usage limit for team@example.com."}"#
+        );
+
+        let notification = UserNotification::AuthSwitch {
+            thread_id: "b5f6c1c2-1111-2222-3333-444455556666".to_string(),
+            from: "team@example.com".to_string(),
+            to: "API key ending in 1234".to_string(),
+            reason: AuthSwitchReason::UsageLimitReached,
+            text: "Codex switched from team@example.com to API key ending in 1234 (usage limit reached).".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_string(&notification)?,
+            r#"{"type":"auth-switch","thread-id":"b5f6c1c2-1111-2222-3333-444455556666","from":"team@example.com","to":"API key ending in 1234","reason":"usage_limit_reached","text":"Codex switched from team@example.com to API key ending in 1234 (usage limit reached)."}"#
+        );
+        Ok(())
+    }
 }
@@ -10,8 +10,13 @@ const INITIAL_DELAY_MS: u64 = 200;
 const BACKOFF_FACTOR: f64 = 2.0;
 
 pub(crate) fn backoff(attempt: u64) -> Duration {
+    backoff_from(Duration::from_millis(INITIAL_DELAY_MS), attempt)
+}
+
+/// Like [`backoff`], starting from `initial` instead of 200ms.
+pub(crate) fn backoff_from(initial: Duration, attempt: u64) -> Duration {
     let exp = BACKOFF_FACTOR.powi(attempt.saturating_sub(1) as i32);
-    let base = (INITIAL_DELAY_MS as f64 * exp) as u64;
+    let base = (initial.as_millis() as f64 * exp) as u64;
     let jitter = rand::rng().random_range(0.9..1.1);
     Duration::from_millis((base as f64 * jitter) as u64)
 }
@@ -54,6 +54,11 @@ async fn run_stream_with_bytes(sse_body: &[u8]) -> Vec<ResponseEvent> {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        max_request_body_bytes: None,
+        supports_images: None,
+        azure: None,
+        gateway: None,
+        tls: None,
         requires_openai_auth: false,
     };
 
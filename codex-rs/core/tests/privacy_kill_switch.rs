@@ -0,0 +1,29 @@
+//! The privacy kill switch is a process-wide latch, so these tests run in a
+//! binary of their own.
+
+use codex_core::default_client::build_provider_reqwest_client;
+use codex_core::default_client::build_reqwest_client;
+use codex_core::default_client::create_client;
+use codex_core::default_client::create_provider_client;
+use codex_core::default_client::disable_non_provider_network;
+use codex_core::default_client::reqwest_client_builder;
+
+#[test]
+fn kill_switch_blocks_non_provider_clients() {
+    assert!(create_client().is_ok());
+
+    disable_non_provider_network();
+
+    let err = create_client().expect_err("create_client while disabled");
+    assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+    assert!(
+        err.to_string()
+            .contains("privacy.disable_all_network_except_providers")
+    );
+    assert!(build_reqwest_client().is_err());
+    assert!(reqwest_client_builder().is_err());
+
+    // Model provider and sign-in traffic is still allowed.
+    let _ = create_provider_client();
+    let _ = build_provider_reqwest_client();
+}
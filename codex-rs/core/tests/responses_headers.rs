@@ -47,6 +47,11 @@ async fn responses_stream_includes_subagent_header_on_review() {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        max_request_body_bytes: None,
+        supports_images: None,
+        azure: None,
+        gateway: None,
+        tls: None,
         requires_openai_auth: false,
     };
 
@@ -135,6 +140,11 @@ async fn responses_stream_includes_subagent_header_on_other() {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        max_request_body_bytes: None,
+        supports_images: None,
+        azure: None,
+        gateway: None,
+        tls: None,
         requires_openai_auth: false,
     };
 
@@ -2,8 +2,10 @@ use assert_matches::assert_matches;
 use std::sync::Arc;
 use std::time::Duration;
 
+use codex_core::features::Feature;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
 use codex_protocol::user_input::UserInput;
 use core_test_support::responses::ev_completed;
 use core_test_support::responses::ev_function_call;
@@ -157,3 +159,157 @@ async fn interrupt_tool_records_history_entries() {
         "expected at least one tenth of a second of elapsed time, got {secs}"
     );
 }
+
+/// With `auto_commit`, an interrupted turn still ends with a commit of what
+/// it changed, so the work tree is not left with a half-finished turn.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn interrupt_commits_the_partial_turn_with_auto_commit() {
+    let args = json!({
+        "command": "echo partial > notes.txt && sleep 60",
+        "timeout_ms": 60_000
+    })
+    .to_string();
+    let body = sse(vec![
+        ev_response_created("resp-partial"),
+        ev_function_call("call_partial", "shell_command", &args),
+        ev_completed("resp-partial"),
+    ]);
+
+    let server = start_mock_server().await;
+    mount_sse_once(&server, body).await;
+
+    let fixture = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(|config| {
+            config.features.enable(Feature::AutoCommit);
+            config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+        })
+        .build(&server)
+        .await
+        .unwrap();
+    let repo = fixture.cwd_path();
+    for args in [
+        &["init", "--initial-branch=main"][..],
+        &["config", "user.name", "Codex Tests"],
+        &["config", "user.email", "codex-tests@example.com"],
+        &["commit", "--allow-empty", "-m", "init"],
+    ] {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(repo)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?} failed");
+    }
+    let codex = Arc::clone(&fixture.codex);
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "write the notes".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::ExecCommandBegin(_))).await;
+    // Give the command time to write the file before interrupting it.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    codex.submit(Op::Interrupt).await.unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnAborted(_))).await;
+
+    let log = std::process::Command::new("git")
+        .args(["log", "-1", "--format=%B", "--name-only"])
+        .current_dir(repo)
+        .output()
+        .unwrap();
+    let log = String::from_utf8(log.stdout).unwrap();
+    assert!(
+        log.starts_with("codex: turn ") && log.contains(": write the notes\n"),
+        "unexpected commit: {log}"
+    );
+    assert!(
+        log.contains("Interrupted before the turn finished."),
+        "{log}"
+    );
+    assert!(log.contains("notes.txt"), "{log}");
+}
+
+/// Without `auto_commit`, an interrupted turn's edits are reverted to the
+/// snapshot taken when it started, so the work tree is not left with a
+/// half-finished turn either.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn interrupt_reverts_the_partial_turn_without_auto_commit() {
+    let args = json!({
+        "command": "echo partial > notes.txt && sleep 60",
+        "timeout_ms": 60_000
+    })
+    .to_string();
+    let body = sse(vec![
+        ev_response_created("resp-partial"),
+        ev_function_call("call_partial", "shell_command", &args),
+        ev_completed("resp-partial"),
+    ]);
+
+    let server = start_mock_server().await;
+    mount_sse_once(&server, body).await;
+
+    let fixture = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(|config| {
+            config.features.disable(Feature::AutoCommit);
+            config.features.enable(Feature::GhostCommit);
+            config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+        })
+        .build(&server)
+        .await
+        .unwrap();
+    let repo = fixture.cwd_path();
+    for args in [
+        &["init", "--initial-branch=main"][..],
+        &["config", "user.name", "Codex Tests"],
+        &["config", "user.email", "codex-tests@example.com"],
+        &["commit", "--allow-empty", "-m", "init"],
+    ] {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(repo)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?} failed");
+    }
+    let codex = Arc::clone(&fixture.codex);
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "write the notes".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::ExecCommandBegin(_))).await;
+    // Give the command time to write the file before interrupting it.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(repo.join("notes.txt").exists());
+    codex.submit(Op::Interrupt).await.unwrap();
+
+    wait_for_event(&codex, |ev| {
+        matches!(
+            ev,
+            EventMsg::BackgroundEvent(event)
+                if event.message.starts_with("Interrupted turn reverted to snapshot ")
+        )
+    })
+    .await;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnAborted(_))).await;
+    assert!(!repo.join("notes.txt").exists());
+
+    let log = std::process::Command::new("git")
+        .args(["log", "--format=%s"])
+        .current_dir(repo)
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8(log.stdout).unwrap(), "init\n");
+}
@@ -14,6 +14,7 @@ use codex_core::ResponseItem;
 use codex_core::WireApi;
 use codex_core::auth::AuthCredentialsStoreMode;
 use codex_core::built_in_model_providers;
+use codex_core::config::types::LimitCapAction;
 use codex_core::error::CodexErr;
 use codex_core::features::Feature;
 use codex_core::model_family::find_family_for_model;
@@ -1005,6 +1006,11 @@ async fn azure_responses_request_includes_store_and_reasoning_ids() {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        max_request_body_bytes: None,
+        supports_images: None,
+        azure: None,
+        gateway: None,
+        tls: None,
         requires_openai_auth: false,
     };
 
@@ -1181,6 +1187,11 @@ async fn token_count_includes_rate_limits_snapshot() {
         rate_limit_json,
         json!({
             "info": null,
+            "cost": {
+                "turn_usd": 0.0,
+                "session_usd": 0.0,
+                "unpriced_tokens": 0
+            },
             "rate_limits": {
                 "primary": {
                     "used_percent": 12.5,
@@ -1211,6 +1222,11 @@ async fn token_count_includes_rate_limits_snapshot() {
     pretty_assertions::assert_eq!(
         final_json,
         json!({
+            "cost": {
+                "turn_usd": 0.00015375,
+                "session_usd": 0.00015375,
+                "unpriced_tokens": 0
+            },
             "info": {
                 "total_token_usage": {
                     "input_tokens": 123,
@@ -1333,6 +1349,11 @@ async fn usage_limit_error_emits_rate_limit_event() -> anyhow::Result<()> {
         event_json,
         json!({
             "info": null,
+            "cost": {
+                "turn_usd": 0.0,
+                "session_usd": 0.0,
+                "unpriced_tokens": 0
+            },
             "rate_limits": expected_limits
         })
     );
@@ -1350,6 +1371,72 @@ async fn usage_limit_error_emits_rate_limit_event() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn usage_limit_switches_to_the_downgrade_model_when_configured() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+    let server = MockServer::start().await;
+
+    let downgraded = responses::mount_sse_once_match(
+        &server,
+        body_string_contains(r#""model":"gpt-5.1-codex-mini""#),
+        responses::sse(vec![
+            responses::ev_response_created("resp-1"),
+            responses::ev_assistant_message("msg-1", "done on the smaller model"),
+            responses::ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(ResponseTemplate::new(429).set_body_json(json!({
+            "error": {
+                "type": "usage_limit_reached",
+                "message": "limit reached",
+                "plan_type": "pro"
+            }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(|config| {
+            config.limits.on_cap = LimitCapAction::DowngradeModel;
+            config.limits.downgrade_model = Some("gpt-5.1-codex-mini".to_string());
+        })
+        .build(&server)
+        .await?;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await?;
+
+    let warning = wait_for_event(&codex, |msg| matches!(msg, EventMsg::Warning(_))).await;
+    let EventMsg::Warning(warning) = warning else {
+        unreachable!();
+    };
+    assert!(
+        warning
+            .message
+            .ends_with("Switching to `gpt-5.1-codex-mini` for the rest of this session."),
+        "unexpected warning: {}",
+        warning.message
+    );
+    wait_for_event(&codex, |msg| matches!(msg, EventMsg::TaskComplete(_))).await;
+    // The mock sees every request, including the one rejected with 429.
+    let last_model = downgraded
+        .requests()
+        .last()
+        .map(|request| request.body_json()["model"].clone());
+    assert_eq!(last_model, Some(json!("gpt-5.1-codex-mini")));
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn context_window_error_sets_total_tokens_to_model_window() -> anyhow::Result<()> {
     skip_if_no_network!(Ok(()));
@@ -1495,6 +1582,11 @@ async fn azure_overrides_assign_properties_used_for_responses_url() {
         request_max_retries: None,
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
+        max_request_body_bytes: None,
+        supports_images: None,
+        azure: None,
+        gateway: None,
+        tls: None,
         requires_openai_auth: false,
     };
 
@@ -1573,6 +1665,11 @@ async fn env_var_overrides_loaded_auth() {
         request_max_retries: None,
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
+        max_request_body_bytes: None,
+        supports_images: None,
+        azure: None,
+        gateway: None,
+        tls: None,
         requires_openai_auth: false,
     };
 
@@ -42,6 +42,7 @@ mod otel;
 mod prompt_caching;
 mod quota_exceeded;
 mod read_file;
+mod redaction;
 mod resume;
 mod review;
 mod rmcp_client;
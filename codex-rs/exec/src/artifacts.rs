@@ -0,0 +1,102 @@
+//! `codex exec --artifacts-dir`: the files a CI job can attach to its build
+//! without parsing the terminal output.
+//!
+//! - `events.jsonl`: the events `--json` prints, written as they happen.
+//! - `last_message.txt`: the agent's final message.
+//! - `changes.diff`: the unified diff of everything the run changed; empty
+//!   when nothing changed.
+
+use std::fs::File;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_core::config::Config;
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::SessionConfiguredEvent;
+use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::TurnDiffEvent;
+
+use crate::event_processor::CodexStatus;
+use crate::event_processor::EventProcessor;
+use crate::event_processor_with_jsonl_output::EventProcessorWithJsonOutput;
+
+const EVENTS_FILE: &str = "events.jsonl";
+const LAST_MESSAGE_FILE: &str = "last_message.txt";
+const DIFF_FILE: &str = "changes.diff";
+
+/// Passes events on to `inner` and records the artifacts in `dir`.
+pub(crate) struct EventProcessorWithArtifacts {
+    inner: Box<dyn EventProcessor>,
+    dir: PathBuf,
+    events: EventProcessorWithJsonOutput,
+    last_message: Option<String>,
+    unified_diff: Option<String>,
+}
+
+impl EventProcessorWithArtifacts {
+    /// Creates `dir` if needed and starts the event log in it, replacing the
+    /// artifacts of an earlier run.
+    pub(crate) fn create(inner: Box<dyn EventProcessor>, dir: &Path) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(dir).map_err(|err| {
+            anyhow::anyhow!(
+                "failed to create artifacts directory {}: {err}",
+                dir.display()
+            )
+        })?;
+        let events_path = dir.join(EVENTS_FILE);
+        let events = File::create(&events_path)
+            .map_err(|err| anyhow::anyhow!("failed to create {}: {err}", events_path.display()))?;
+        Ok(Self {
+            inner,
+            dir: dir.to_path_buf(),
+            events: EventProcessorWithJsonOutput::with_writer(None, Box::new(events)),
+            last_message: None,
+            unified_diff: None,
+        })
+    }
+
+    fn write(&self, name: &str, contents: &str) {
+        let path = self.dir.join(name);
+        if let Err(err) = std::fs::write(&path, contents) {
+            eprintln!("Failed to write artifact {}: {err}", path.display());
+        }
+    }
+}
+
+impl EventProcessor for EventProcessorWithArtifacts {
+    fn print_config_summary(
+        &mut self,
+        config: &Config,
+        prompt: &str,
+        session_configured: &SessionConfiguredEvent,
+    ) {
+        self.events
+            .print_config_summary(config, prompt, session_configured);
+        self.inner
+            .print_config_summary(config, prompt, session_configured);
+    }
+
+    fn process_event(&mut self, event: Event) -> CodexStatus {
+        match &event.msg {
+            EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => {
+                self.unified_diff = Some(unified_diff.clone());
+            }
+            EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
+                self.last_message = last_agent_message.clone();
+            }
+            _ => {}
+        }
+        self.events.process_event(event.clone());
+        self.inner.process_event(event)
+    }
+
+    fn print_final_output(&mut self) {
+        self.write(
+            LAST_MESSAGE_FILE,
+            self.last_message.as_deref().unwrap_or_default(),
+        );
+        self.write(DIFF_FILE, self.unified_diff.as_deref().unwrap_or_default());
+        self.inner.print_final_output();
+    }
+}
@@ -0,0 +1,400 @@
+//! `codex exec --batch`: a YAML list of tasks, each run in a fresh session
+//! with its own working directory, model, and sandbox, and a report of how
+//! each one went.
+//!
+//! ```yaml
+//! - name: api
+//!   prompt: Replace the deprecated logger in this package.
+//!   cwd: packages/api
+//!   model: gpt-5-codex
+//!   sandbox: workspace-write
+//! - prompt: Replace the deprecated logger in this package.
+//!   cwd: packages/web
+//! ```
+
+use std::fmt::Write as _;
+use std::io::Read;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Context;
+use codex_common::elapsed::format_duration;
+use codex_core::config::ConfigOverrides;
+use codex_core::protocol::TokenCost;
+use codex_protocol::config_types::SandboxMode;
+use serde::Deserialize;
+
+use crate::RunOutcome;
+use crate::cli::FailOn;
+use crate::exit_status;
+
+/// Longest label taken from a prompt for a task without a name.
+const LABEL_CHARS: usize = 48;
+
+/// One entry of the batch file. Fields left out fall back to the `codex
+/// exec` options the batch was started with.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BatchTask {
+    /// Label in the report; defaults to the start of the prompt.
+    #[serde(default)]
+    pub(crate) name: Option<String>,
+    pub(crate) prompt: String,
+    /// Working directory, relative to the directory of the batch file.
+    #[serde(default)]
+    pub(crate) cwd: Option<PathBuf>,
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+    #[serde(default)]
+    pub(crate) sandbox: Option<SandboxMode>,
+}
+
+impl BatchTask {
+    /// `base` with this task's working directory, model, and sandbox.
+    pub(crate) fn overrides(&self, base: &ConfigOverrides) -> ConfigOverrides {
+        let mut overrides = base.clone();
+        if let Some(cwd) = &self.cwd {
+            overrides.cwd = Some(cwd.canonicalize().unwrap_or_else(|_| cwd.clone()));
+        }
+        if let Some(model) = &self.model {
+            overrides.model = Some(model.clone());
+        }
+        if let Some(sandbox) = self.sandbox {
+            overrides.sandbox_mode = Some(sandbox);
+        }
+        overrides
+    }
+
+    pub(crate) fn label(&self) -> String {
+        if let Some(name) = self.name.as_deref().map(str::trim)
+            && !name.is_empty()
+        {
+            return name.to_string();
+        }
+        let first_line = self.prompt.trim().lines().next().unwrap_or_default();
+        if first_line.chars().count() > LABEL_CHARS {
+            let cut: String = first_line.chars().take(LABEL_CHARS - 1).collect();
+            format!("{}…", cut.trim_end())
+        } else {
+            first_line.to_string()
+        }
+    }
+}
+
+/// Reads the tasks from `path`, or from stdin when it is `-`.
+pub(crate) fn load_tasks(path: &Path) -> anyhow::Result<Vec<BatchTask>> {
+    if path == Path::new("-") {
+        let mut contents = String::new();
+        std::io::stdin()
+            .read_to_string(&mut contents)
+            .context("failed to read the batch from stdin")?;
+        return parse_tasks(&contents, Path::new("")).context("invalid batch on stdin");
+    }
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read batch file {}", path.display()))?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    parse_tasks(&contents, base_dir)
+        .with_context(|| format!("invalid batch file {}", path.display()))
+}
+
+fn parse_tasks(contents: &str, base_dir: &Path) -> anyhow::Result<Vec<BatchTask>> {
+    let mut tasks: Vec<BatchTask> = serde_yaml::from_str(contents)?;
+    if tasks.is_empty() {
+        anyhow::bail!("the batch has no tasks");
+    }
+    for (index, task) in tasks.iter_mut().enumerate() {
+        if task.prompt.trim().is_empty() {
+            anyhow::bail!("task {} has an empty prompt", index + 1);
+        }
+        if let Some(cwd) = task.cwd.as_mut()
+            && cwd.is_relative()
+        {
+            *cwd = base_dir.join(&*cwd);
+        }
+    }
+    Ok(tasks)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TaskStatus {
+    Succeeded,
+    Failed(String),
+    Interrupted,
+    /// Not started because the batch stopped first.
+    Skipped,
+}
+
+/// Files and lines a task changed, counted from its turn diff.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct DiffStat {
+    pub(crate) files: usize,
+    pub(crate) added: usize,
+    pub(crate) removed: usize,
+}
+
+impl DiffStat {
+    pub(crate) fn from_unified_diff(unified_diff: &str) -> Self {
+        let mut stat = Self::default();
+        for line in unified_diff.lines() {
+            if line.starts_with("diff --git ") {
+                stat.files += 1;
+            } else if line.starts_with("+++ ") || line.starts_with("--- ") {
+                continue;
+            } else if line.starts_with('+') {
+                stat.added += 1;
+            } else if line.starts_with('-') {
+                stat.removed += 1;
+            }
+        }
+        stat
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TaskReport {
+    pub(crate) label: String,
+    pub(crate) status: TaskStatus,
+    pub(crate) diff: DiffStat,
+    pub(crate) cost: Option<TokenCost>,
+    pub(crate) elapsed: Duration,
+    pub(crate) session_id: Option<String>,
+    /// The exit status the task would have had as a single `codex exec` run.
+    pub(crate) exit_code: i32,
+}
+
+impl TaskReport {
+    pub(crate) fn new(
+        label: String,
+        elapsed: Duration,
+        outcome: &anyhow::Result<RunOutcome>,
+        fail_on: &[FailOn],
+    ) -> Self {
+        let outcome = match outcome {
+            Ok(outcome) => outcome,
+            Err(err) => {
+                return Self {
+                    label,
+                    status: TaskStatus::Failed(format!("{err:#}")),
+                    diff: DiffStat::default(),
+                    cost: None,
+                    elapsed,
+                    session_id: None,
+                    exit_code: exit_status::MODEL_ERROR,
+                };
+            }
+        };
+        let failure = exit_status::failure(outcome, fail_on);
+        let status = match &failure {
+            None => TaskStatus::Succeeded,
+            Some(exit_status::Failure::Interrupted) => TaskStatus::Interrupted,
+            Some(failure) => TaskStatus::Failed(failure.message()),
+        };
+        Self {
+            label,
+            status,
+            diff: outcome
+                .unified_diff
+                .as_deref()
+                .map(DiffStat::from_unified_diff)
+                .unwrap_or_default(),
+            cost: outcome.cost.clone(),
+            elapsed,
+            session_id: Some(outcome.conversation_id.to_string()),
+            exit_code: failure.map_or(0, |failure| failure.exit_code()),
+        }
+    }
+
+    pub(crate) fn skipped(label: String) -> Self {
+        Self {
+            label,
+            status: TaskStatus::Skipped,
+            diff: DiffStat::default(),
+            cost: None,
+            elapsed: Duration::ZERO,
+            session_id: None,
+            exit_code: 0,
+        }
+    }
+}
+
+/// The exit status of the batch: that of the first task that did not
+/// succeed, or the interrupt's when Ctrl-C stopped it.
+pub(crate) fn exit_code(reports: &[TaskReport]) -> Option<i32> {
+    if reports
+        .iter()
+        .any(|report| report.status == TaskStatus::Interrupted)
+    {
+        return Some(exit_status::INTERRUPTED);
+    }
+    reports
+        .iter()
+        .map(|report| report.exit_code)
+        .find(|code| *code != 0)
+}
+
+/// The summary printed when the batch ends.
+pub(crate) fn render_report(reports: &[TaskReport]) -> String {
+    let count = |wanted: fn(&TaskStatus) -> bool| {
+        reports
+            .iter()
+            .filter(|report| wanted(&report.status))
+            .count()
+    };
+    let succeeded = count(|status| matches!(status, TaskStatus::Succeeded));
+    let failed = count(|status| matches!(status, TaskStatus::Failed(_)));
+    let interrupted = count(|status| matches!(status, TaskStatus::Interrupted));
+    let skipped = count(|status| matches!(status, TaskStatus::Skipped));
+
+    let mut out = format!("Batch finished: {succeeded} succeeded, {failed} failed");
+    if interrupted > 0 {
+        let _ = write!(out, ", {interrupted} interrupted");
+    }
+    if skipped > 0 {
+        let _ = write!(out, ", {skipped} skipped");
+    }
+    out.push_str(".\n");
+
+    for (index, report) in reports.iter().enumerate() {
+        let (mark, detail) = match &report.status {
+            TaskStatus::Succeeded => ("✓", String::new()),
+            TaskStatus::Failed(message) => ("✗", format!("failed: {message}; ")),
+            TaskStatus::Interrupted => ("✗", "interrupted; ".to_string()),
+            TaskStatus::Skipped => {
+                let _ = writeln!(out, "  - {}. {}: skipped", index + 1, report.label);
+                continue;
+            }
+        };
+        let diff = match report.diff {
+            DiffStat { files: 0, .. } => "no changes".to_string(),
+            DiffStat {
+                files,
+                added,
+                removed,
+            } => format!(
+                "{files} file{} changed, +{added} -{removed}",
+                if files == 1 { "" } else { "s" }
+            ),
+        };
+        let _ = write!(
+            out,
+            "  {mark} {}. {}: {detail}{diff}, {}",
+            index + 1,
+            report.label,
+            format_duration(report.elapsed)
+        );
+        if let Some(cost) = &report.cost {
+            let _ = write!(out, ", ${:.2}", cost.session_usd);
+        }
+        if let Some(session_id) = &report.session_id {
+            let _ = write!(out, " (session {session_id})");
+        }
+        out.push('\n');
+    }
+
+    let costs: Vec<&TokenCost> = reports.iter().filter_map(|r| r.cost.as_ref()).collect();
+    if !costs.is_empty() {
+        let usd: f64 = costs.iter().map(|cost| cost.session_usd).sum();
+        let unpriced: i64 = costs.iter().map(|cost| cost.unpriced_tokens).sum();
+        let _ = write!(out, "Estimated cost: ${usd:.2}");
+        if unpriced > 0 {
+            let _ = write!(
+                out,
+                " ({unpriced} tokens used with unpriced models are not included)"
+            );
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_tasks_relative_to_the_batch_file() {
+        let yaml = "\
+- name: api
+  prompt: Replace the logger.
+  cwd: packages/api
+  model: gpt-5-codex
+  sandbox: workspace-write
+- prompt: |
+    Replace the logger in the web package, then run its tests and fix anything that breaks.
+  cwd: /abs/web
+";
+        let tasks = parse_tasks(yaml, Path::new("/repo")).expect("parse");
+        assert_eq!(
+            tasks[0],
+            BatchTask {
+                name: Some("api".to_string()),
+                prompt: "Replace the logger.".to_string(),
+                cwd: Some(PathBuf::from("/repo/packages/api")),
+                model: Some("gpt-5-codex".to_string()),
+                sandbox: Some(SandboxMode::WorkspaceWrite),
+            }
+        );
+        assert_eq!(tasks[1].cwd, Some(PathBuf::from("/abs/web")));
+        assert_eq!(
+            tasks[1].label(),
+            "Replace the logger in the web package, then run…"
+        );
+
+        assert!(parse_tasks("[]", Path::new("")).is_err());
+        assert!(parse_tasks("- prompt: '  '", Path::new("")).is_err());
+        assert!(parse_tasks("- prompt: hi\n  sandbox: open", Path::new("")).is_err());
+    }
+
+    #[test]
+    fn report_lists_each_task_with_its_diff_and_cost() {
+        let diff = "\
+diff --git a/src/a.rs b/src/a.rs
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1,2 +1,2 @@
+-old
++new
++more
+";
+        let cost = |usd: f64, unpriced_tokens: i64| TokenCost {
+            turn_usd: usd,
+            session_usd: usd,
+            unpriced_tokens,
+        };
+        let reports = vec![
+            TaskReport {
+                label: "api".to_string(),
+                status: TaskStatus::Succeeded,
+                diff: DiffStat::from_unified_diff(diff),
+                cost: Some(cost(0.25, 0)),
+                elapsed: Duration::from_secs(75),
+                session_id: Some("s1".to_string()),
+                exit_code: 0,
+            },
+            TaskReport {
+                label: "web".to_string(),
+                status: TaskStatus::Failed("stream disconnected".to_string()),
+                diff: DiffStat::default(),
+                cost: Some(cost(0.1, 1200)),
+                elapsed: Duration::from_millis(1500),
+                session_id: Some("s2".to_string()),
+                exit_code: exit_status::MODEL_ERROR,
+            },
+            TaskReport::skipped("docs".to_string()),
+        ];
+        assert_eq!(
+            render_report(&reports),
+            "\
+Batch finished: 1 succeeded, 1 failed, 1 skipped.
+  ✓ 1. api: 1 file changed, +2 -1, 1m 15s, $0.25 (session s1)
+  ✗ 2. web: failed: stream disconnected; no changes, 1.50s, $0.10 (session s2)
+  - 3. docs: skipped
+Estimated cost: $0.35 (1200 tokens used with unpriced models are not included)
+"
+        );
+        assert_eq!(exit_code(&reports), Some(exit_status::MODEL_ERROR));
+        assert_eq!(exit_code(&reports[..1]), None);
+    }
+}
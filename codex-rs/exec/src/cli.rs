@@ -2,6 +2,7 @@ use clap::Parser;
 use clap::ValueEnum;
 use codex_common::CliConfigOverrides;
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser, Debug)]
 #[command(version)]
@@ -62,6 +63,22 @@ pub struct Cli {
     #[arg(long = "add-dir", value_name = "DIR", value_hint = clap::ValueHint::DirPath)]
     pub add_dir: Vec<PathBuf>,
 
+    /// A directory the session spans, with its own AGENTS.md and git
+    /// repository. Repeat for each root; the first is the working directory
+    /// unless --cd is given.
+    #[arg(long = "root", value_name = "DIR", value_hint = clap::ValueHint::DirPath)]
+    pub root: Vec<PathBuf>,
+
+    /// Markdown file whose contents replace the built-in system prompt for
+    /// this session. Takes precedence over `system_prompt` in config.toml.
+    #[arg(long = "system-prompt-file", value_name = "FILE")]
+    pub system_prompt_file: Option<PathBuf>,
+
+    /// Add the `--system-prompt-file` instructions after the built-in system
+    /// prompt instead of replacing it.
+    #[arg(long = "append-system-prompt", requires = "system_prompt_file")]
+    pub append_system_prompt: bool,
+
     /// Path to a JSON Schema file describing the model's final response shape.
     #[arg(long = "output-schema", value_name = "FILE")]
     pub output_schema: Option<PathBuf>,
@@ -81,6 +98,55 @@ pub struct Cli {
     #[arg(long = "output-last-message", short = 'o', value_name = "FILE")]
     pub last_message_file: Option<PathBuf>,
 
+    /// Directory to write the final message, the diff of all changes, and
+    /// the JSONL event log to, for CI jobs to keep.
+    #[arg(long = "artifacts-dir", value_name = "DIR")]
+    pub artifacts_dir: Option<PathBuf>,
+
+    /// When a usage limit is hit and no other credentials are left, wait for
+    /// it to reset and resume instead of failing the run.
+    #[arg(long = "wait-for-reset", default_value_t = false)]
+    pub wait_for_reset: bool,
+
+    /// YAML file listing tasks to run one after another, each in a fresh
+    /// session, followed by a summary report. Use `-` to read it from stdin.
+    #[arg(
+        long = "batch",
+        value_name = "FILE",
+        conflicts_with_all = ["prompt", "images", "last_message_file"]
+    )]
+    pub batch: Option<PathBuf>,
+
+    /// Model requests the agent may make before the run stops with a
+    /// summary of what it attempted.
+    #[arg(long = "max-turns", value_name = "N")]
+    pub max_turns: Option<u32>,
+
+    /// Output tokens the agent may generate before the run stops.
+    #[arg(long = "max-output-tokens", value_name = "N")]
+    pub max_output_tokens: Option<i64>,
+
+    /// How long the run may take, e.g. `90s`, `30m`, or `1h30m`, before it
+    /// stops.
+    #[arg(long = "max-duration", value_name = "DURATION", value_parser = parse_duration)]
+    pub max_duration: Option<Duration>,
+
+    /// Outcomes that fail the run with their own exit status even when the
+    /// agent finished, e.g. `--fail-on patch-rejected,tests-failed`.
+    #[arg(
+        long = "fail-on",
+        value_name = "CONDITION",
+        value_enum,
+        value_delimiter = ','
+    )]
+    pub fail_on: Vec<FailOn>,
+
+    /// Assemble the first request and report its estimated tokens, the files
+    /// it exposes, the tools, and the sandbox policy, without calling the
+    /// model.
+    #[arg(long = "dry-run", default_value_t = false, conflicts_with = "batch")]
+    pub dry_run: bool,
+
     /// Initial instructions for the agent. If not provided as an argument (or
     /// if `-` is used), instructions are read from stdin.
     #[arg(value_name = "PROMPT", value_hint = clap::ValueHint::Other)]
@@ -147,6 +213,18 @@ pub struct ReviewArgs {
     pub prompt: Option<String>,
 }
 
+/// Outcomes that fail a run only when `--fail-on` asks for them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum FailOn {
+    /// A patch could not be applied.
+    PatchRejected,
+    /// The last test command the agent ran exited with a non-zero status.
+    TestsFailed,
+    /// The sandbox blocked a command, even if the agent carried on without it.
+    SandboxDenied,
+}
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
 #[value(rename_all = "kebab-case")]
 pub enum Color {
@@ -155,3 +233,58 @@ pub enum Color {
     #[default]
     Auto,
 }
+
+/// Parses `90`, `90s`, `30m`, `2h`, or a combination such as `1h30m`; a
+/// number without a unit is seconds.
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration `{value}`; use e.g. `90s`, `30m`, or `1h30m`");
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>()
+        && secs > 0
+    {
+        return Ok(Duration::from_secs(secs));
+    }
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        let amount: u64 = digits.parse().map_err(|_| invalid())?;
+        total = amount
+            .checked_mul(unit)
+            .and_then(|secs| total.checked_add(secs))
+            .ok_or_else(invalid)?;
+        digits.clear();
+    }
+    if !digits.is_empty() || total == 0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_durations_with_units() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("45s"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert!(parse_duration("1d").is_err());
+        assert!(parse_duration("1h30").is_err());
+        assert!(parse_duration("0m").is_err());
+        assert!(parse_duration("0").is_err());
+        assert!(parse_duration("").is_err());
+    }
+}
@@ -3,8 +3,10 @@ use codex_common::elapsed::format_elapsed;
 use codex_core::config::Config;
 use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
+use codex_core::protocol::AuthSwitchedEvent;
 use codex_core::protocol::BackgroundEventEvent;
 use codex_core::protocol::DeprecationNoticeEvent;
+use codex_core::protocol::DryRunReportEvent;
 use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
@@ -35,6 +37,7 @@ use crate::event_processor::CodexStatus;
 use crate::event_processor::EventProcessor;
 use crate::event_processor::handle_last_message;
 use codex_common::create_config_summary_entries;
+use codex_common::summarize_sandbox_policy;
 use codex_protocol::plan_tool::StepStatus;
 use codex_protocol::plan_tool::UpdatePlanArgs;
 
@@ -111,6 +114,91 @@ impl EventProcessorWithHumanOutput {
     }
 }
 
+impl EventProcessorWithHumanOutput {
+    fn print_dry_run_report(&self, report: &DryRunReportEvent) {
+        let tokens = |count: u64| format_with_separators(i64::try_from(count).unwrap_or(i64::MAX));
+        let context_window = report
+            .model_context_window
+            .map(|window| {
+                format!(
+                    " of the {} token context window",
+                    format_with_separators(window)
+                )
+            })
+            .unwrap_or_default();
+        eprintln!(
+            "{}",
+            "dry run: nothing was sent to the model"
+                .style(self.magenta)
+                .style(self.italic)
+        );
+        eprintln!(
+            "{} ~{} tokens{context_window} (instructions {}, tools {}, input {})",
+            "prompt:".style(self.bold),
+            tokens(report.estimated_prompt_tokens),
+            tokens(report.instructions_tokens),
+            tokens(report.tools_tokens),
+            tokens(report.input_tokens),
+        );
+
+        let list = |title: &str, entries: Vec<String>| {
+            if entries.is_empty() {
+                eprintln!("{} none", format!("{title}:").style(self.bold));
+                return;
+            }
+            eprintln!("{}", format!("{title}:").style(self.bold));
+            for entry in entries {
+                eprintln!("  {entry}");
+            }
+        };
+        let paths = |paths: &[PathBuf]| -> Vec<String> {
+            paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect()
+        };
+        list("instruction files", paths(&report.instruction_files));
+        list(
+            "pinned files",
+            report
+                .pinned_files
+                .iter()
+                .map(|pin| match &pin.error {
+                    Some(error) => format!("{} (not sent: {error})", pin.path.display()),
+                    None => format!(
+                        "{} ({} file{}, ~{} tokens)",
+                        pin.path.display(),
+                        pin.files,
+                        if pin.files == 1 { "" } else { "s" },
+                        tokens(pin.tokens)
+                    ),
+                })
+                .collect(),
+        );
+        list("images", paths(&report.images));
+        list("writable roots", paths(&report.writable_roots));
+        eprintln!(
+            "{} {}",
+            "tools:".style(self.bold),
+            if report.tools.is_empty() {
+                "none".to_string()
+            } else {
+                report.tools.join(", ")
+            }
+        );
+        eprintln!(
+            "{} {}",
+            "sandbox:".style(self.bold),
+            summarize_sandbox_policy(&report.sandbox_policy)
+        );
+        eprintln!(
+            "{} {}",
+            "approval:".style(self.bold),
+            report.approval_policy
+        );
+    }
+}
+
 struct PatchApplyBegin {
     start_time: Instant,
     auto_approved: bool,
@@ -172,6 +260,14 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     "warning:".style(self.yellow).style(self.bold)
                 );
             }
+            EventMsg::AuthSwitched(AuthSwitchedEvent { from, to, reason }) => {
+                ts_msg!(
+                    self,
+                    "{} switched from {from} to {to} ({})",
+                    "auth:".style(self.yellow).style(self.bold),
+                    reason.description()
+                );
+            }
             EventMsg::DeprecationNotice(DeprecationNoticeEvent { summary, details }) => {
                 ts_msg!(
                     self,
@@ -250,6 +346,10 @@ impl EventProcessor for EventProcessorWithHumanOutput {
 
                 return CodexStatus::InitiateShutdown;
             }
+            EventMsg::DryRunReport(report) => {
+                self.print_dry_run_report(&report);
+                return CodexStatus::InitiateShutdown;
+            }
             EventMsg::TokenCount(ev) => {
                 self.last_total_token_usage = ev.info;
             }
@@ -570,6 +670,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             | EventMsg::GetHistoryEntryResponse(_)
             | EventMsg::McpListToolsResponse(_)
             | EventMsg::ListCustomPromptsResponse(_)
+            | EventMsg::PinnedFiles(_)
             | EventMsg::RawResponseItem(_)
             | EventMsg::UserMessage(_)
             | EventMsg::EnteredReviewMode(_)
@@ -583,6 +684,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             | EventMsg::ReasoningContentDelta(_)
             | EventMsg::ReasoningRawContentDelta(_)
             | EventMsg::UndoCompleted(_)
+            | EventMsg::CheckpointCreated(_)
             | EventMsg::ListModelsResponse(_)
             | EventMsg::UndoStarted(_) => {}
         }
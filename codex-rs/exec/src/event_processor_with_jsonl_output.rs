@@ -1,4 +1,6 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::io::Write;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicU64;
 
@@ -6,8 +8,11 @@ use crate::event_processor::CodexStatus;
 use crate::event_processor::EventProcessor;
 use crate::event_processor::handle_last_message;
 use crate::exec_events::AgentMessageItem;
+use crate::exec_events::AuthSwitchedEvent;
 use crate::exec_events::CommandExecutionItem;
 use crate::exec_events::CommandExecutionStatus;
+use crate::exec_events::DryRunPinnedFile;
+use crate::exec_events::DryRunReportEvent;
 use crate::exec_events::ErrorItem;
 use crate::exec_events::FileChangeItem;
 use crate::exec_events::FileUpdateChange;
@@ -21,6 +26,7 @@ use crate::exec_events::McpToolCallStatus;
 use crate::exec_events::PatchApplyStatus;
 use crate::exec_events::PatchChangeKind;
 use crate::exec_events::ReasoningItem;
+use crate::exec_events::SCHEMA_VERSION;
 use crate::exec_events::ThreadErrorEvent;
 use crate::exec_events::ThreadEvent;
 use crate::exec_events::ThreadItem;
@@ -57,6 +63,8 @@ use tracing::warn;
 
 pub struct EventProcessorWithJsonOutput {
     last_message_path: Option<PathBuf>,
+    /// Where the JSONL goes; stdout unless built with [`Self::with_writer`].
+    out: Box<dyn Write>,
     next_event_id: AtomicU64,
     // Tracks running commands by call_id, including the associated item id.
     running_commands: HashMap<String, RunningCommand>,
@@ -66,6 +74,7 @@ pub struct EventProcessorWithJsonOutput {
     last_total_token_usage: Option<codex_core::protocol::TokenUsage>,
     running_mcp_tool_calls: HashMap<String, RunningMcpToolCall>,
     last_critical_error: Option<ThreadErrorEvent>,
+    tags: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone)]
@@ -90,8 +99,14 @@ struct RunningMcpToolCall {
 
 impl EventProcessorWithJsonOutput {
     pub fn new(last_message_path: Option<PathBuf>) -> Self {
+        Self::with_writer(last_message_path, Box::new(std::io::stdout()))
+    }
+
+    /// Writes the events to `out` instead of stdout.
+    pub fn with_writer(last_message_path: Option<PathBuf>, out: Box<dyn Write>) -> Self {
         Self {
             last_message_path,
+            out,
             next_event_id: AtomicU64::new(0),
             running_commands: HashMap::new(),
             running_patch_applies: HashMap::new(),
@@ -99,6 +114,7 @@ impl EventProcessorWithJsonOutput {
             last_total_token_usage: None,
             running_mcp_tool_calls: HashMap::new(),
             last_critical_error: None,
+            tags: BTreeMap::new(),
         }
     }
 
@@ -139,10 +155,16 @@ impl EventProcessorWithJsonOutput {
                 };
                 vec![ThreadEvent::ItemCompleted(ItemCompletedEvent { item })]
             }
+            EventMsg::AuthSwitched(ev) => vec![ThreadEvent::AuthSwitched(AuthSwitchedEvent {
+                from: ev.from.clone(),
+                to: ev.to.clone(),
+                reason: ev.reason,
+            })],
             EventMsg::StreamError(ev) => vec![ThreadEvent::Error(ThreadErrorEvent {
                 message: ev.message.clone(),
             })],
             EventMsg::PlanUpdate(ev) => self.handle_plan_update(ev),
+            EventMsg::DryRunReport(ev) => vec![ThreadEvent::DryRunReport(dry_run_report(ev))],
             _ => Vec::new(),
         }
     }
@@ -157,7 +179,9 @@ impl EventProcessorWithJsonOutput {
 
     fn handle_session_configured(&self, payload: &SessionConfiguredEvent) -> Vec<ThreadEvent> {
         vec![ThreadEvent::ThreadStarted(ThreadStartedEvent {
+            schema_version: SCHEMA_VERSION,
             thread_id: payload.session_id.to_string(),
+            tags: self.tags.clone(),
         })]
     }
 
@@ -427,9 +451,11 @@ impl EventProcessorWithJsonOutput {
         vec![ThreadEvent::ItemStarted(ItemStartedEvent { item })]
     }
 
-    fn handle_task_started(&mut self, _: &TaskStartedEvent) -> Vec<ThreadEvent> {
+    fn handle_task_started(&mut self, ev: &TaskStartedEvent) -> Vec<ThreadEvent> {
         self.last_critical_error = None;
-        vec![ThreadEvent::TurnStarted(TurnStartedEvent {})]
+        vec![ThreadEvent::TurnStarted(TurnStartedEvent {
+            auth: ev.auth.clone(),
+        })]
     }
 
     fn handle_task_complete(&mut self) -> Vec<ThreadEvent> {
@@ -466,20 +492,22 @@ impl EventProcessorWithJsonOutput {
 }
 
 impl EventProcessor for EventProcessorWithJsonOutput {
-    fn print_config_summary(&mut self, _: &Config, _: &str, ev: &SessionConfiguredEvent) {
+    fn print_config_summary(&mut self, config: &Config, _: &str, ev: &SessionConfiguredEvent) {
+        self.tags = config.tags.clone();
         self.process_event(Event {
             id: "".to_string(),
             msg: EventMsg::SessionConfigured(ev.clone()),
         });
     }
 
-    #[allow(clippy::print_stdout)]
     fn process_event(&mut self, event: Event) -> CodexStatus {
         let aggregated = self.collect_thread_events(&event);
         for conv_event in aggregated {
             match serde_json::to_string(&conv_event) {
                 Ok(line) => {
-                    println!("{line}");
+                    if let Err(e) = writeln!(self.out, "{line}").and_then(|()| self.out.flush()) {
+                        error!("Failed to write event: {e:?}");
+                    }
                 }
                 Err(e) => {
                     error!("Failed to serialize event: {e:?}");
@@ -489,13 +517,50 @@ impl EventProcessor for EventProcessorWithJsonOutput {
 
         let Event { msg, .. } = event;
 
-        if let EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) = msg {
-            if let Some(output_file) = self.last_message_path.as_deref() {
-                handle_last_message(last_agent_message.as_deref(), output_file);
+        match msg {
+            EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
+                if let Some(output_file) = self.last_message_path.as_deref() {
+                    handle_last_message(last_agent_message.as_deref(), output_file);
+                }
+                CodexStatus::InitiateShutdown
             }
-            CodexStatus::InitiateShutdown
-        } else {
-            CodexStatus::Running
+            EventMsg::DryRunReport(_) => CodexStatus::InitiateShutdown,
+            _ => CodexStatus::Running,
         }
     }
 }
+
+fn dry_run_report(report: &codex_core::protocol::DryRunReportEvent) -> DryRunReportEvent {
+    let tokens = |count: u64| i64::try_from(count).unwrap_or(i64::MAX);
+    let paths = |paths: &[PathBuf]| -> Vec<String> {
+        paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect()
+    };
+    DryRunReportEvent {
+        model: report.model.clone(),
+        model_context_window: report.model_context_window,
+        estimated_prompt_tokens: tokens(report.estimated_prompt_tokens),
+        instructions_tokens: tokens(report.instructions_tokens),
+        tools_tokens: tokens(report.tools_tokens),
+        input_tokens: tokens(report.input_tokens),
+        instruction_files: paths(&report.instruction_files),
+        pinned_files: report
+            .pinned_files
+            .iter()
+            .map(|pin| DryRunPinnedFile {
+                path: pin.path.display().to_string(),
+                files: pin.files,
+                tokens: tokens(pin.tokens),
+                error: pin.error.clone(),
+            })
+            .collect(),
+        images: paths(&report.images),
+        tools: report.tools.clone(),
+        sandbox: report.sandbox_policy.to_string(),
+        network_access: report.sandbox_policy.has_full_network_access(),
+        writable_roots: paths(&report.writable_roots),
+        approval_policy: report.approval_policy.to_string(),
+    }
+}
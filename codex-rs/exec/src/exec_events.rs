@@ -1,9 +1,19 @@
+pub use codex_protocol::protocol::AuthSwitchReason;
 use mcp_types::ContentBlock as McpContentBlock;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
 use ts_rs::TS;
 
+/// Version of the `codex exec --json` event schema, sent in
+/// `thread.started`.
+///
+/// Adding an event type, an item type, or an optional field keeps the
+/// version, so consumers must ignore types and fields they do not know.
+/// Removing or renaming any of them, or changing what one means, bumps it.
+pub const SCHEMA_VERSION: u32 = 1;
+
 /// Top-level JSONL events emitted by codex exec
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 #[serde(tag = "type")]
@@ -33,17 +43,38 @@ pub enum ThreadEvent {
     /// Represents an unrecoverable error emitted directly by the event stream.
     #[serde(rename = "error")]
     Error(ThreadErrorEvent),
+    /// Codex switched to different credentials, e.g. because the active API
+    /// key ran out of quota.
+    #[serde(rename = "auth.switched")]
+    AuthSwitched(AuthSwitchedEvent),
+    /// What `--dry-run` found the first request would contain. Emitted
+    /// instead of a turn.
+    #[serde(rename = "dry_run.report")]
+    DryRunReport(DryRunReportEvent),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 pub struct ThreadStartedEvent {
+    /// [`SCHEMA_VERSION`] of the events that follow; 0 in streams from
+    /// before the schema was versioned.
+    #[serde(default)]
+    pub schema_version: u32,
     /// The identified of the new thread. Can be used to resume the thread later.
     pub thread_id: String,
+    /// Tags the session was started with via `--tag` or `[tags]`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, Default)]
 
-pub struct TurnStartedEvent {}
+pub struct TurnStartedEvent {
+    /// Credentials the turn's requests start out with; see `auth.switched`
+    /// for changes during the turn.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub auth: Option<String>,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 pub struct TurnCompletedEvent {
@@ -87,6 +118,57 @@ pub struct ThreadErrorEvent {
     pub message: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+pub struct AuthSwitchedEvent {
+    /// Redacted description of the previous credentials.
+    pub from: String,
+    /// Redacted description of the credentials now in use.
+    pub to: String,
+    pub reason: AuthSwitchReason,
+}
+
+/// Token counts are estimates from the size of each part of the request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+pub struct DryRunReportEvent {
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub model_context_window: Option<i64>,
+    /// The sum of the instructions, tools, and input tokens.
+    pub estimated_prompt_tokens: i64,
+    pub instructions_tokens: i64,
+    pub tools_tokens: i64,
+    /// The conversation so far, the context sent with every request, and
+    /// the prompt.
+    pub input_tokens: i64,
+    /// The `AGENTS.md` files whose contents are sent.
+    pub instruction_files: Vec<String>,
+    pub pinned_files: Vec<DryRunPinnedFile>,
+    /// Local images attached to the prompt.
+    pub images: Vec<String>,
+    /// Names of the tools the model could call.
+    pub tools: Vec<String>,
+    /// `read-only`, `workspace-write`, or `danger-full-access`.
+    pub sandbox: String,
+    pub network_access: bool,
+    /// Where commands could write; empty when the sandbox is read-only or
+    /// there is no sandbox.
+    pub writable_roots: Vec<String>,
+    pub approval_policy: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+pub struct DryRunPinnedFile {
+    pub path: String,
+    /// Files the pin covers: 1 for a file, the files read for a directory.
+    pub files: usize,
+    pub tokens: i64,
+    /// Why the pin is not sent, e.g. the file no longer exists.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<String>,
+}
+
 /// Canonical representation of a thread item and its domain-specific payload.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 pub struct ThreadItem {
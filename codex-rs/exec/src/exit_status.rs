@@ -0,0 +1,306 @@
+//! How `codex exec` reports the way a run ended through its exit status, so
+//! CI can branch on the outcome without parsing the output.
+//!
+//! | Status | Meaning |
+//! | --- | --- |
+//! | 0 | The task finished. |
+//! | 1 | The model or the provider failed the run. |
+//! | 3 | A usage limit was hit and no other credentials were left. |
+//! | 4 | The sandbox blocked a command the turn could not do without, or one `--fail-on sandbox-denied` watches for. |
+//! | 5 | The agent declared that it could not complete the task. |
+//! | 6 | A patch could not be applied (`--fail-on patch-rejected`). |
+//! | 7 | The last test run failed (`--fail-on tests-failed`). |
+//! | 8 | A task limit (`--max-turns`, `--max-output-tokens`, or `--max-duration`) stopped the run. |
+//! | 130 | Ctrl-C stopped the run. |
+//!
+//! Status 2 is left to argument errors, which clap reports with it.
+
+use codex_core::protocol::CodexErrorInfo;
+use codex_core::protocol::ExecCommandEndEvent;
+
+use crate::RunOutcome;
+use crate::cli::FailOn;
+
+pub(crate) const MODEL_ERROR: i32 = 1;
+pub(crate) const USAGE_LIMIT: i32 = 3;
+pub(crate) const SANDBOX_DENIED: i32 = 4;
+pub(crate) const TASK_FAILED: i32 = 5;
+pub(crate) const PATCH_REJECTED: i32 = 6;
+pub(crate) const TESTS_FAILED: i32 = 7;
+pub(crate) const RUN_LIMIT: i32 = 8;
+pub(crate) const INTERRUPTED: i32 = 130;
+
+/// Start of the final-message line with which the agent declares that it
+/// could not complete the task.
+const TASK_FAILED_PREFIX: &str = "TASK FAILED:";
+
+/// Added to the developer instructions of runs without an output schema so
+/// the agent knows how to declare a failure.
+pub(crate) const TASK_FAILED_INSTRUCTIONS: &str = "If you cannot complete the task, end your final message with a line that starts with `TASK FAILED:` followed by the reason.";
+
+/// Why a run did not succeed.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Failure {
+    Interrupted,
+    UsageLimit(String),
+    Sandbox(String),
+    Model(String),
+    RunLimit(String),
+    TaskFailed(String),
+    FailOn(FailOn),
+}
+
+impl Failure {
+    pub(crate) fn exit_code(&self) -> i32 {
+        match self {
+            Failure::Interrupted => INTERRUPTED,
+            Failure::UsageLimit(_) => USAGE_LIMIT,
+            Failure::Sandbox(_) | Failure::FailOn(FailOn::SandboxDenied) => SANDBOX_DENIED,
+            Failure::Model(_) => MODEL_ERROR,
+            Failure::RunLimit(_) => RUN_LIMIT,
+            Failure::TaskFailed(_) => TASK_FAILED,
+            Failure::FailOn(FailOn::PatchRejected) => PATCH_REJECTED,
+            Failure::FailOn(FailOn::TestsFailed) => TESTS_FAILED,
+        }
+    }
+
+    pub(crate) fn message(&self) -> String {
+        match self {
+            Failure::Interrupted => "interrupted".to_string(),
+            Failure::UsageLimit(message)
+            | Failure::Sandbox(message)
+            | Failure::Model(message)
+            | Failure::RunLimit(message) => message.clone(),
+            Failure::TaskFailed(reason) => format!("task failed: {reason}"),
+            Failure::FailOn(FailOn::PatchRejected) => "a patch was rejected".to_string(),
+            Failure::FailOn(FailOn::TestsFailed) => "tests failed".to_string(),
+            Failure::FailOn(FailOn::SandboxDenied) => "the sandbox blocked a command".to_string(),
+        }
+    }
+}
+
+/// The most important reason `outcome` failed, if it did: an interrupt, then
+/// a run error, then the agent's own verdict, then the `fail_on` conditions
+/// in the order given.
+pub(crate) fn failure(outcome: &RunOutcome, fail_on: &[FailOn]) -> Option<Failure> {
+    if outcome.interrupted {
+        return Some(Failure::Interrupted);
+    }
+    if let Some(message) = &outcome.error {
+        let message = message.clone();
+        return Some(match outcome.error_info {
+            Some(CodexErrorInfo::UsageLimitExceeded) => Failure::UsageLimit(message),
+            Some(CodexErrorInfo::SandboxError) => Failure::Sandbox(message),
+            Some(CodexErrorInfo::RunLimitReached) => Failure::RunLimit(message),
+            _ => Failure::Model(message),
+        });
+    }
+    if let Some(reason) = &outcome.task_failure {
+        return Some(Failure::TaskFailed(reason.clone()));
+    }
+    fail_on
+        .iter()
+        .find(|condition| match condition {
+            FailOn::PatchRejected => outcome.patch_rejected,
+            FailOn::TestsFailed => outcome.tests_failed,
+            FailOn::SandboxDenied => outcome.sandbox_denied,
+        })
+        .map(|condition| Failure::FailOn(*condition))
+}
+
+/// The reason the agent gave for not completing the task, if the last line
+/// of its final message declares a failure.
+pub(crate) fn declared_failure(last_agent_message: &str) -> Option<String> {
+    let last_line = last_agent_message
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())?;
+    let reason = last_line
+        .trim()
+        .trim_matches(['*', '_', '`'])
+        .strip_prefix(TASK_FAILED_PREFIX)?
+        .trim();
+    Some(if reason.is_empty() {
+        "no reason given".to_string()
+    } else {
+        reason.to_string()
+    })
+}
+
+/// Whether `event` ended a run of a project's tests.
+pub(crate) fn is_test_run(event: &ExecCommandEndEvent) -> bool {
+    let command = event.command.join(" ");
+    command
+        .split(['\n', ';', '&', '|', '(', ')'])
+        .any(is_test_command)
+}
+
+/// Test runners recognized at the start of a command, after environment
+/// assignments and a shell wrapper.
+const TEST_COMMANDS: &[&[&str]] = &[
+    &["cargo", "test"],
+    &["cargo", "nextest"],
+    &["npm", "test"],
+    &["npm", "run", "test"],
+    &["pnpm", "test"],
+    &["pnpm", "run", "test"],
+    &["yarn", "test"],
+    &["bun", "test"],
+    &["npx", "jest"],
+    &["npx", "vitest"],
+    &["jest"],
+    &["vitest"],
+    &["pytest"],
+    &["python", "-m", "pytest"],
+    &["python3", "-m", "pytest"],
+    &["python", "-m", "unittest"],
+    &["python3", "-m", "unittest"],
+    &["tox"],
+    &["go", "test"],
+    &["make", "test"],
+    &["make", "check"],
+    &["mvn", "test"],
+    &["mvn", "verify"],
+    &["gradle", "test"],
+    &["./gradlew", "test"],
+    &["rspec"],
+    &["bundle", "exec", "rspec"],
+    &["dotnet", "test"],
+    &["mix", "test"],
+    &["ctest"],
+];
+
+fn is_test_command(segment: &str) -> bool {
+    let words: Vec<&str> = segment
+        .split_whitespace()
+        .map(|word| word.trim_matches(['"', '\'']))
+        .filter(|word| !word.is_empty())
+        .collect();
+    let wrapper = words
+        .iter()
+        .take_while(|word| {
+            let name = word.rsplit('/').next().unwrap_or_default();
+            matches!(name, "bash" | "sh" | "zsh" | "-c" | "-lc" | "env" | "time")
+                || (word.contains('=') && !word.starts_with('-'))
+        })
+        .count();
+    let words = &words[wrapper..];
+    TEST_COMMANDS.iter().any(|runner| {
+        words.len() >= runner.len()
+            && runner.iter().zip(words).all(|(expected, word)| {
+                word == expected || word.starts_with(&format!("{expected}:"))
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::ConversationId;
+    use pretty_assertions::assert_eq;
+
+    fn outcome() -> RunOutcome {
+        RunOutcome {
+            conversation_id: ConversationId::new(),
+            error: None,
+            error_info: None,
+            interrupted: false,
+            unified_diff: None,
+            cost: None,
+            task_failure: None,
+            patch_rejected: false,
+            tests_failed: false,
+            sandbox_denied: false,
+        }
+    }
+
+    #[test]
+    fn exit_codes_follow_the_most_important_failure() {
+        let code = |outcome: &RunOutcome, fail_on: &[FailOn]| {
+            failure(outcome, fail_on).map_or(0, |failure| failure.exit_code())
+        };
+        let all = [
+            FailOn::PatchRejected,
+            FailOn::TestsFailed,
+            FailOn::SandboxDenied,
+        ];
+
+        let mut run = outcome();
+        assert_eq!(code(&run, &all), 0);
+
+        run.tests_failed = true;
+        run.sandbox_denied = true;
+        assert_eq!(code(&run, &[]), 0);
+        assert_eq!(code(&run, &[FailOn::PatchRejected]), 0);
+        assert_eq!(code(&run, &all), TESTS_FAILED);
+        assert_eq!(code(&run, &[FailOn::SandboxDenied]), SANDBOX_DENIED);
+        run.patch_rejected = true;
+        assert_eq!(code(&run, &all), PATCH_REJECTED);
+
+        run.task_failure = Some("the API is not reachable".to_string());
+        assert_eq!(code(&run, &all), TASK_FAILED);
+
+        run.error = Some("stream disconnected".to_string());
+        assert_eq!(code(&run, &all), MODEL_ERROR);
+        run.error_info = Some(CodexErrorInfo::UsageLimitExceeded);
+        assert_eq!(code(&run, &all), USAGE_LIMIT);
+        run.error_info = Some(CodexErrorInfo::SandboxError);
+        assert_eq!(code(&run, &all), SANDBOX_DENIED);
+        run.error_info = Some(CodexErrorInfo::RunLimitReached);
+        assert_eq!(code(&run, &all), RUN_LIMIT);
+
+        run.interrupted = true;
+        assert_eq!(code(&run, &all), INTERRUPTED);
+    }
+
+    #[test]
+    fn reads_the_declared_failure_from_the_last_line() {
+        assert_eq!(
+            declared_failure("I tried.\n\nTASK FAILED: the API is not reachable\n"),
+            Some("the API is not reachable".to_string())
+        );
+        assert_eq!(
+            declared_failure("**TASK FAILED:**"),
+            Some("no reason given".to_string())
+        );
+        assert_eq!(
+            declared_failure("TASK FAILED: at first\nThen I fixed it."),
+            None
+        );
+        assert_eq!(declared_failure("All tests pass."), None);
+    }
+
+    #[test]
+    fn recognizes_test_commands_behind_shells_and_env() {
+        let is_test = |command: &[&str]| {
+            is_test_run(&ExecCommandEndEvent {
+                call_id: String::new(),
+                process_id: None,
+                turn_id: String::new(),
+                command: command.iter().map(ToString::to_string).collect(),
+                cwd: std::path::PathBuf::new(),
+                parsed_cmd: Vec::new(),
+                source: Default::default(),
+                interaction_input: None,
+                stdout: String::new(),
+                stderr: String::new(),
+                aggregated_output: String::new(),
+                exit_code: 0,
+                duration: std::time::Duration::ZERO,
+                formatted_output: String::new(),
+                sandbox_denied: false,
+            })
+        };
+        assert!(is_test(&["bash", "-lc", "cargo test -p codex-core"]));
+        assert!(is_test(&[
+            "bash",
+            "-lc",
+            "cd web && CI=1 npm run test:unit"
+        ]));
+        assert!(is_test(&["python3", "-m", "pytest", "tests/"]));
+        assert!(is_test(&["/bin/zsh", "-lc", "go test ./..."]));
+        assert!(!is_test(&["bash", "-lc", "cargo build"]));
+        assert!(!is_test(&["rg", "cargo test"]));
+        assert!(!is_test(&["bash", "-lc", "cat tests/test_api.py"]));
+    }
+}
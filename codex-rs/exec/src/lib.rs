@@ -4,14 +4,21 @@
 // For both modes, any other output must be written to stderr.
 #![deny(clippy::print_stdout)]
 
+mod artifacts;
+mod batch;
 mod cli;
 mod event_processor;
 mod event_processor_with_human_output;
 pub mod event_processor_with_jsonl_output;
 pub mod exec_events;
+mod exit_status;
+mod preflight;
 
+use artifacts::EventProcessorWithArtifacts;
 pub use cli::Cli;
+pub use cli::Color;
 pub use cli::Command;
+pub use cli::FailOn;
 pub use cli::ReviewArgs;
 use codex_common::oss::ensure_oss_provider_ready;
 use codex_common::oss::get_default_model_for_oss_provider;
@@ -26,14 +33,29 @@ use codex_core::config::ConfigOverrides;
 use codex_core::config::find_codex_home;
 use codex_core::config::load_config_as_toml_with_cli_overrides;
 use codex_core::config::resolve_oss_provider;
+use codex_core::config::types::LimitCapAction;
+use codex_core::config::types::PreflightAction;
+use codex_core::config::types::RunLimits;
+use codex_core::config::types::SystemPrompt;
+use codex_core::config::types::SystemPromptMode;
+use codex_core::default_client::apply_privacy;
 use codex_core::git_info::get_git_repo_root;
 use codex_core::protocol::AskForApproval;
+use codex_core::protocol::CodexErrorInfo;
+use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::Op;
+use codex_core::protocol::PatchApplyEndEvent;
 use codex_core::protocol::ReviewRequest;
 use codex_core::protocol::ReviewTarget;
 use codex_core::protocol::SessionSource;
+use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::TokenCost;
+use codex_core::protocol::TokenCountEvent;
+use codex_core::protocol::TurnDiffEvent;
+use codex_core::redact::RedactingWriter;
+use codex_protocol::ConversationId;
 use codex_protocol::approvals::ElicitationAction;
 use codex_protocol::config_types::SandboxMode;
 use codex_protocol::user_input::UserInput;
@@ -43,7 +65,12 @@ use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
 use serde_json::Value;
 use std::io::IsTerminal;
 use std::io::Read;
+use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
 use supports_color::Stream;
 use tracing::debug;
 use tracing::error;
@@ -65,6 +92,11 @@ enum InitialOperation {
     Review {
         review_request: ReviewRequest,
     },
+    /// `--dry-run`: report what the first request of the turn would contain.
+    DryRun {
+        items: Vec<UserInput>,
+        output_schema: Option<Value>,
+    },
 }
 
 pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()> {
@@ -84,8 +116,19 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         cwd,
         skip_git_repo_check,
         add_dir,
+        root,
+        system_prompt_file,
+        append_system_prompt,
         color,
         last_message_file,
+        artifacts_dir,
+        wait_for_reset,
+        batch,
+        max_turns,
+        max_output_tokens,
+        max_duration,
+        fail_on,
+        dry_run,
         json: json_mode,
         sandbox_mode: sandbox_mode_cli_arg,
         prompt,
@@ -112,7 +155,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
 
     let fmt_layer = tracing_subscriber::fmt::layer()
         .with_ansi(stderr_with_ansi)
-        .with_writer(std::io::stderr)
+        .with_writer(|| RedactingWriter::new(std::io::stderr()))
         .with_filter(env_filter);
 
     let sandbox_mode = if full_auto {
@@ -135,26 +178,26 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
 
     // we load config.toml here to determine project state.
     #[allow(clippy::print_stderr)]
-    let config_toml = {
-        let codex_home = match find_codex_home() {
-            Ok(codex_home) => codex_home,
-            Err(err) => {
-                eprintln!("Error finding codex home: {err}");
-                std::process::exit(1);
-            }
-        };
-
+    let codex_home = match find_codex_home() {
+        Ok(codex_home) => codex_home,
+        Err(err) => {
+            eprintln!("Error finding codex home: {err}");
+            std::process::exit(1);
+        }
+    };
+    #[allow(clippy::print_stderr)]
+    let config_toml =
         match load_config_as_toml_with_cli_overrides(&codex_home, cli_kv_overrides.clone()).await {
             Ok(config_toml) => config_toml,
             Err(err) => {
                 eprintln!("Error loading config.toml: {err}");
                 std::process::exit(1);
             }
-        }
-    };
+        };
 
     let model_provider = if oss {
         let resolved = resolve_oss_provider(
+            &codex_home,
             oss_provider.as_deref(),
             &config_toml,
             config_profile.clone(),
@@ -195,6 +238,15 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         model_provider: model_provider.clone(),
         codex_linux_sandbox_exe,
         base_instructions: None,
+        system_prompt: system_prompt_file.map(|file| SystemPrompt {
+            text: None,
+            file: Some(file),
+            mode: if append_system_prompt {
+                SystemPromptMode::Append
+            } else {
+                SystemPromptMode::Replace
+            },
+        }),
         developer_instructions: None,
         compact_prompt: None,
         include_apply_patch_tool: None,
@@ -202,9 +254,22 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         tools_web_search_request: None,
         experimental_sandbox_command_assessment: None,
         additional_writable_roots: add_dir,
+        roots: root,
     };
 
-    let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides).await?;
+    let mut config =
+        Config::load_with_cli_overrides(cli_kv_overrides.clone(), overrides.clone()).await?;
+    apply_privacy(&config.privacy);
+    if wait_for_reset {
+        config.limits.on_cap = LimitCapAction::Wait;
+    }
+    let run_limits = RunLimits {
+        max_turns,
+        max_output_tokens,
+        max_duration,
+    };
+    config.run_limits = run_limits;
+    config.dry_run = dry_run;
 
     if let Err(err) = enforce_login_restrictions(&config).await {
         eprintln!("{err}");
@@ -235,15 +300,6 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         let _ = tracing_subscriber::registry().with(fmt_layer).try_init();
     }
 
-    let mut event_processor: Box<dyn EventProcessor> = match json_mode {
-        true => Box::new(EventProcessorWithJsonOutput::new(last_message_file.clone())),
-        _ => Box::new(EventProcessorWithHumanOutput::create_with_ansi(
-            stdout_with_ansi,
-            &config,
-            last_message_file.clone(),
-        )),
-    };
-
     if oss {
         // We're in the oss section, so provider_id should be Some
         // Let's handle None case gracefully though just in case
@@ -261,14 +317,8 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
             .map_err(|e| anyhow::anyhow!("OSS setup failed: {e}"))?;
     }
 
-    let default_cwd = config.cwd.to_path_buf();
-    let default_approval_policy = config.approval_policy;
-    let default_sandbox_policy = config.sandbox_policy.clone();
-    let default_model = config.model.clone();
-    let default_effort = config.model_reasoning_effort;
-    let default_summary = config.model_reasoning_summary;
-
-    if !skip_git_repo_check && get_git_repo_root(&default_cwd).is_none() {
+    // A batch checks each task's working directory instead.
+    if batch.is_none() && !skip_git_repo_check && get_git_repo_root(&config.cwd).is_none() {
         eprintln!("Not inside a trusted directory and --skip-git-repo-check was not specified.");
         std::process::exit(1);
     }
@@ -278,29 +328,108 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         true,
         config.cli_auth_credentials_store_mode,
     );
+
+    // A dry run makes no request the quota would be spent on.
+    if !dry_run
+        && let Some(message) = preflight::check_quota(&config, auth_manager.auth().as_ref()).await
+    {
+        match config.preflight.on_low {
+            PreflightAction::Warn => eprintln!("Warning: {message}"),
+            PreflightAction::Abort => {
+                eprintln!("{message} Not starting the run (`preflight.on_low = \"abort\"`).");
+                std::process::exit(1);
+            }
+        }
+    }
+
     let conversation_manager = ConversationManager::new(auth_manager.clone(), SessionSource::Exec);
 
-    // Handle resume subcommand by resolving a rollout path and using explicit resume API.
-    let NewConversation {
-        conversation_id: _,
-        conversation,
-        session_configured,
-    } = if let Some(ExecCommand::Resume(args)) = command.as_ref() {
-        let resume_path = resolve_resume_path(&config, args).await?;
+    if let Some(batch_path) = batch {
+        if command.is_some() {
+            anyhow::bail!("--batch cannot be combined with `resume` or `review`");
+        }
+        let tasks = batch::load_tasks(&batch_path)?;
+        let output_schema = load_output_schema(output_schema_path);
+        let total = tasks.len();
+        let mut reports = Vec::with_capacity(total);
+        let mut stopped = false;
+        for (index, task) in tasks.into_iter().enumerate() {
+            let label = task.label();
+            if stopped {
+                reports.push(batch::TaskReport::skipped(label));
+                continue;
+            }
+            if let Some(message) = codex_core::recorded_cap_reached(&config) {
+                eprintln!("Stopping the batch: {message}");
+                stopped = true;
+                reports.push(batch::TaskReport::skipped(label));
+                continue;
+            }
 
-        if let Some(path) = resume_path {
-            conversation_manager
-                .resume_conversation_from_rollout(config.clone(), path, auth_manager.clone())
-                .await?
-        } else {
-            conversation_manager
-                .new_conversation(config.clone())
-                .await?
+            eprintln!("Task {}/{total}: {label}", index + 1);
+            let started = Instant::now();
+            let outcome: anyhow::Result<RunOutcome> = async {
+                let mut task_config = Config::load_with_cli_overrides(
+                    cli_kv_overrides.clone(),
+                    task.overrides(&overrides),
+                )
+                .await?;
+                if wait_for_reset {
+                    task_config.limits.on_cap = LimitCapAction::Wait;
+                }
+                task_config.run_limits = run_limits;
+                if !skip_git_repo_check && get_git_repo_root(&task_config.cwd).is_none() {
+                    anyhow::bail!(
+                        "{} is not inside a Git repository and --skip-git-repo-check was not specified",
+                        task_config.cwd.display()
+                    );
+                }
+                let task_artifacts_dir = artifacts_dir
+                    .as_ref()
+                    .map(|dir| dir.join(format!("task-{}", index + 1)));
+                let mut event_processor = new_event_processor(
+                    json_mode,
+                    stdout_with_ansi,
+                    &task_config,
+                    None,
+                    task_artifacts_dir.as_deref(),
+                )?;
+                let initial_operation = InitialOperation::UserTurn {
+                    items: vec![UserInput::Text {
+                        text: task.prompt.clone(),
+                    }],
+                    output_schema: output_schema.clone(),
+                };
+                run_conversation(
+                    &conversation_manager,
+                    &auth_manager,
+                    &task_config,
+                    None,
+                    initial_operation,
+                    &task.prompt,
+                    event_processor.as_mut(),
+                )
+                .await
+            }
+            .await;
+            let report = batch::TaskReport::new(label, started.elapsed(), &outcome, &fail_on);
+            if report.status == batch::TaskStatus::Interrupted {
+                stopped = true;
+            }
+            reports.push(report);
         }
-    } else {
-        conversation_manager
-            .new_conversation(config.clone())
-            .await?
+
+        eprint!("{}", batch::render_report(&reports));
+        if let Some(code) = batch::exit_code(&reports) {
+            std::process::exit(code);
+        }
+        return Ok(());
+    }
+
+    // Handle resume subcommand by resolving a rollout path and using explicit resume API.
+    let resume_path = match command.as_ref() {
+        Some(ExecCommand::Resume(args)) => resolve_resume_path(&config, args).await?,
+        _ => None,
     };
     let (initial_operation, prompt_summary) = match (command, prompt, images) {
         (Some(ExecCommand::Review(review_cli)), _, _) => {
@@ -357,26 +486,167 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         }
     };
 
+    let initial_operation = match initial_operation {
+        InitialOperation::UserTurn {
+            items,
+            output_schema,
+        } if dry_run => InitialOperation::DryRun {
+            items,
+            output_schema,
+        },
+        InitialOperation::Review { .. } if dry_run => {
+            anyhow::bail!("--dry-run cannot be combined with `review`");
+        }
+        initial_operation => initial_operation,
+    };
+
+    let mut event_processor = new_event_processor(
+        json_mode,
+        stdout_with_ansi,
+        &config,
+        last_message_file,
+        artifacts_dir.as_deref(),
+    )?;
+    let outcome = run_conversation(
+        &conversation_manager,
+        &auth_manager,
+        &config,
+        resume_path,
+        initial_operation,
+        &prompt_summary,
+        event_processor.as_mut(),
+    )
+    .await?;
+    // Exit with a status that tells scripts how the run ended; see
+    // `exit_status` for the codes.
+    if let Some(failure) = exit_status::failure(&outcome, &fail_on) {
+        if matches!(failure, exit_status::Failure::FailOn(_)) {
+            eprintln!("Failing the run: {}", failure.message());
+        }
+        std::process::exit(failure.exit_code());
+    }
+
+    Ok(())
+}
+
+fn new_event_processor(
+    json_mode: bool,
+    stdout_with_ansi: bool,
+    config: &Config,
+    last_message_file: Option<PathBuf>,
+    artifacts_dir: Option<&Path>,
+) -> anyhow::Result<Box<dyn EventProcessor>> {
+    let event_processor: Box<dyn EventProcessor> = if json_mode {
+        Box::new(EventProcessorWithJsonOutput::new(last_message_file))
+    } else {
+        Box::new(EventProcessorWithHumanOutput::create_with_ansi(
+            stdout_with_ansi,
+            config,
+            last_message_file,
+        ))
+    };
+    Ok(match artifacts_dir {
+        Some(dir) => Box::new(EventProcessorWithArtifacts::create(event_processor, dir)?),
+        None => event_processor,
+    })
+}
+
+/// How a session run by [`run_conversation`] ended.
+pub(crate) struct RunOutcome {
+    pub(crate) conversation_id: ConversationId,
+    /// The last error the server reported, if any.
+    pub(crate) error: Option<String>,
+    pub(crate) error_info: Option<CodexErrorInfo>,
+    /// Whether Ctrl-C stopped the run.
+    pub(crate) interrupted: bool,
+    /// Everything the session changed, from the last turn diff.
+    pub(crate) unified_diff: Option<String>,
+    pub(crate) cost: Option<TokenCost>,
+    /// The reason the agent gave for not completing the task.
+    pub(crate) task_failure: Option<String>,
+    /// Whether a patch could not be applied.
+    pub(crate) patch_rejected: bool,
+    /// Whether the last test run failed.
+    pub(crate) tests_failed: bool,
+    /// Whether the sandbox blocked a command.
+    pub(crate) sandbox_denied: bool,
+}
+
+/// Starts a session, or resumes the one recorded at `resume_path`, submits
+/// `initial_operation`, and feeds the events to `event_processor` until the
+/// session shuts down.
+async fn run_conversation(
+    conversation_manager: &ConversationManager,
+    auth_manager: &Arc<AuthManager>,
+    config: &Config,
+    resume_path: Option<PathBuf>,
+    initial_operation: InitialOperation,
+    prompt_summary: &str,
+    event_processor: &mut dyn EventProcessor,
+) -> anyhow::Result<RunOutcome> {
+    // A final message held to an output schema has no room for the failure
+    // line, and a review reports its findings instead.
+    let mut config = config.clone();
+    if let InitialOperation::UserTurn {
+        output_schema: None,
+        ..
+    }
+    | InitialOperation::DryRun {
+        output_schema: None,
+        ..
+    } = &initial_operation
+    {
+        config.developer_instructions = Some(match config.developer_instructions.take() {
+            Some(instructions) => {
+                format!(
+                    "{instructions}\n\n{}",
+                    exit_status::TASK_FAILED_INSTRUCTIONS
+                )
+            }
+            None => exit_status::TASK_FAILED_INSTRUCTIONS.to_string(),
+        });
+    }
+    let config = &config;
+    let NewConversation {
+        conversation_id,
+        conversation,
+        session_configured,
+    } = if let Some(path) = resume_path {
+        conversation_manager
+            .resume_conversation_from_rollout(config.clone(), path, auth_manager.clone())
+            .await?
+    } else {
+        conversation_manager
+            .new_conversation(config.clone())
+            .await?
+    };
+
     // Print the effective configuration and initial request so users can see what Codex
     // is using.
-    event_processor.print_config_summary(&config, &prompt_summary, &session_configured);
+    event_processor.print_config_summary(config, prompt_summary, &session_configured);
 
     info!("Codex initialized with event: {session_configured:?}");
 
+    let interrupted = Arc::new(AtomicBool::new(false));
     let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Event>();
     {
         let conversation = conversation.clone();
+        let interrupted = Arc::clone(&interrupted);
         tokio::spawn(async move {
             loop {
                 tokio::select! {
                     _ = tokio::signal::ctrl_c() => {
+                        if interrupted.swap(true, Ordering::Relaxed) {
+                            eprintln!("Exiting without waiting for the session to be saved.");
+                            std::process::exit(exit_status::INTERRUPTED);
+                        }
                         tracing::debug!("Keyboard interrupt");
-                        // Immediately notify Codex to abort any in‑flight task.
+                        eprintln!("Interrupting; saving the session (Ctrl-C again to exit now)...");
+                        // Aborting the task stops its tools and records what the
+                        // turn did so far; shutting down then flushes the rollout.
+                        // Events keep flowing until `ShutdownComplete`.
                         conversation.submit(Op::Interrupt).await.ok();
-
-                        // Exit the inner loop and return to the main input prompt. The codex
-                        // will emit a `TurnInterrupted` (Error) event which is drained later.
-                        break;
+                        conversation.submit(Op::Shutdown).await.ok();
                     }
                     res = conversation.next_event() => match res {
                         Ok(event) => {
@@ -410,12 +680,12 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
             let task_id = conversation
                 .submit(Op::UserTurn {
                     items,
-                    cwd: default_cwd,
-                    approval_policy: default_approval_policy,
-                    sandbox_policy: default_sandbox_policy,
-                    model: default_model,
-                    effort: default_effort,
-                    summary: default_summary,
+                    cwd: config.cwd.to_path_buf(),
+                    approval_policy: config.approval_policy,
+                    sandbox_policy: config.sandbox_policy.clone(),
+                    model: config.model.clone(),
+                    effort: config.model_reasoning_effort,
+                    summary: config.model_reasoning_summary,
                     final_output_json_schema: output_schema,
                 })
                 .await?;
@@ -427,12 +697,30 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
             info!("Sent review request with event ID: {task_id}");
             task_id
         }
+        InitialOperation::DryRun {
+            items,
+            output_schema,
+        } => {
+            let task_id = conversation
+                .submit(Op::DryRun {
+                    items,
+                    final_output_json_schema: output_schema,
+                })
+                .await?;
+            info!("Sent dry run with event ID: {task_id}");
+            task_id
+        }
     };
 
     // Run the loop until the task is complete.
-    // Track whether a fatal error was reported by the server so we can
-    // exit with a non-zero status for automation-friendly signaling.
-    let mut error_seen = false;
+    let mut error = None;
+    let mut error_info = None;
+    let mut unified_diff = None;
+    let mut cost = None;
+    let mut task_failure = None;
+    let mut patch_rejected = false;
+    let mut tests_failed = false;
+    let mut sandbox_denied = false;
     while let Some(event) = rx.recv().await {
         if let EventMsg::ElicitationRequest(ev) = &event.msg {
             // Automatically cancel elicitation requests in exec mode.
@@ -444,14 +732,47 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
                 })
                 .await?;
         }
-        if matches!(event.msg, EventMsg::Error(_)) {
-            error_seen = true;
+        match &event.msg {
+            EventMsg::Error(ErrorEvent {
+                message,
+                codex_error_info,
+            }) => {
+                error = Some(message.clone());
+                error_info = codex_error_info.clone();
+            }
+            EventMsg::ExecCommandEnd(end) => {
+                sandbox_denied |= end.sandbox_denied;
+                if exit_status::is_test_run(end) {
+                    tests_failed = end.exit_code != 0;
+                }
+            }
+            EventMsg::PatchApplyEnd(PatchApplyEndEvent { success, .. }) => {
+                patch_rejected |= !success;
+            }
+            EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
+                task_failure = last_agent_message
+                    .as_deref()
+                    .and_then(exit_status::declared_failure);
+            }
+            EventMsg::TurnDiff(TurnDiffEvent { unified_diff: diff }) => {
+                unified_diff = Some(diff.clone());
+            }
+            EventMsg::TokenCount(TokenCountEvent {
+                cost: Some(current),
+                ..
+            }) => cost = Some(current.clone()),
+            _ => {}
         }
         let shutdown: CodexStatus = event_processor.process_event(event);
         match shutdown {
             CodexStatus::Running => continue,
             CodexStatus::InitiateShutdown => {
-                conversation.submit(Op::Shutdown).await?;
+                // After Ctrl-C the session may already have shut down.
+                if let Err(err) = conversation.submit(Op::Shutdown).await
+                    && !interrupted.load(Ordering::Relaxed)
+                {
+                    return Err(err.into());
+                }
             }
             CodexStatus::Shutdown => {
                 break;
@@ -459,11 +780,23 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         }
     }
     event_processor.print_final_output();
-    if error_seen {
-        std::process::exit(1);
+    let interrupted = interrupted.load(Ordering::Relaxed);
+    if interrupted {
+        eprintln!("To continue this session, run `codex exec resume {conversation_id}`.");
     }
 
-    Ok(())
+    Ok(RunOutcome {
+        conversation_id,
+        error,
+        error_info,
+        interrupted,
+        unified_diff,
+        cost,
+        task_failure,
+        patch_rejected,
+        tests_failed,
+        sandbox_denied,
+    })
 }
 
 async fn resolve_resume_path(
@@ -0,0 +1,165 @@
+//! Quota check before a run starts, from `[preflight]` in `config.toml`.
+//!
+//! With ChatGPT sign-in, the remaining share of each usage window comes from
+//! the usage endpoint `codex limits` reads. Without it, or when that request
+//! fails, a limit hit recorded for the same credentials that has not reset
+//! yet counts as no quota left. API keys have no remaining-quota endpoint, so
+//! for them only recorded limit hits are checked.
+
+use codex_app_server_protocol::AuthMode;
+use codex_backend_client::Client as BackendClient;
+use codex_core::CodexAuth;
+use codex_core::config::Config;
+use codex_core::limit_history::LimitHitEntry;
+use codex_core::limit_history::read_limit_history;
+use codex_core::rate_limits::unix_now;
+use codex_core::state_dir::state_dir;
+use codex_protocol::protocol::RateLimitSnapshot;
+use tracing::warn;
+
+/// Remaining quota and when it is replenished.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Quota {
+    remaining_percent: f64,
+    resets_at: Option<i64>,
+}
+
+/// Checks the remaining quota of `auth` against
+/// `preflight.min_remaining_percent` and returns a message when it is below
+/// the threshold.
+pub(crate) async fn check_quota(config: &Config, auth: Option<&CodexAuth>) -> Option<String> {
+    let threshold = config.preflight.min_remaining_percent?;
+    if !config.model_provider.requires_openai_auth {
+        return None;
+    }
+    let auth = auth?;
+    let identity = auth.identity_label();
+    let now = unix_now();
+
+    let live = if auth.mode == AuthMode::ChatGPT {
+        match fetch_rate_limits(config, auth).await {
+            Ok(snapshot) => quota_from_snapshot(&snapshot),
+            Err(err) => {
+                warn!("preflight could not fetch usage limits: {err}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let quota = live.or_else(|| {
+        let entries = read_limit_history(
+            &state_dir(&config.codex_home),
+            config.shared_state_dir.as_deref(),
+        )
+        .inspect_err(|err| warn!("failed to read limit history: {err}"))
+        .unwrap_or_default();
+        recorded_quota(&entries, &identity, now)
+    })?;
+
+    (quota.remaining_percent < threshold)
+        .then(|| low_quota_message(&identity, threshold, quota, now))
+}
+
+async fn fetch_rate_limits(config: &Config, auth: &CodexAuth) -> anyhow::Result<RateLimitSnapshot> {
+    let client = BackendClient::from_auth(config.chatgpt_base_url.clone(), auth).await?;
+    client.get_rate_limits().await
+}
+
+/// Quota left in the most used window of `snapshot`.
+fn quota_from_snapshot(snapshot: &RateLimitSnapshot) -> Option<Quota> {
+    [snapshot.primary.as_ref(), snapshot.secondary.as_ref()]
+        .into_iter()
+        .flatten()
+        .max_by(|a, b| a.used_percent.total_cmp(&b.used_percent))
+        .map(|window| Quota {
+            remaining_percent: (100.0 - window.used_percent).clamp(0.0, 100.0),
+            resets_at: window.resets_at,
+        })
+}
+
+/// No quota until the latest recorded limit hit for `identity` resets.
+fn recorded_quota(entries: &[LimitHitEntry], identity: &str, now: i64) -> Option<Quota> {
+    entries
+        .iter()
+        .filter(|entry| entry.auth == identity)
+        .filter_map(|entry| entry.resets_at)
+        .filter(|resets_at| *resets_at > now)
+        .max()
+        .map(|resets_at| Quota {
+            remaining_percent: 0.0,
+            resets_at: Some(resets_at),
+        })
+}
+
+fn low_quota_message(identity: &str, threshold: f64, quota: Quota, now: i64) -> String {
+    let resets = quota
+        .resets_at
+        .map(|at| {
+            let minutes = (at - now).max(0) / 60;
+            format!(" It resets in {}h {}m.", minutes / 60, minutes % 60)
+        })
+        .unwrap_or_default();
+    format!(
+        "Only {:.0}% of the usage limit for {identity} remains, below `preflight.min_remaining_percent` ({threshold}%).{resets}",
+        quota.remaining_percent
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::RateLimitWindow;
+    use pretty_assertions::assert_eq;
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn most_used_window_sets_the_remaining_quota() {
+        let window = |used_percent, resets_at| RateLimitWindow {
+            used_percent,
+            window_minutes: None,
+            resets_at: Some(resets_at),
+        };
+        let snapshot = RateLimitSnapshot {
+            primary: Some(window(40.0, NOW + 3600)),
+            secondary: Some(window(92.0, NOW + 2 * 3600 + 5 * 60)),
+            credits: None,
+            request_limits: None,
+        };
+        let quota = quota_from_snapshot(&snapshot).expect("quota");
+        assert!((quota.remaining_percent - 8.0).abs() < 1e-9);
+        assert_eq!(
+            low_quota_message("me@example.com", 20.0, quota, NOW),
+            "Only 8% of the usage limit for me@example.com remains, below `preflight.min_remaining_percent` (20%). It resets in 2h 5m."
+        );
+    }
+
+    #[test]
+    fn unreset_limit_hits_for_the_same_credentials_leave_no_quota() {
+        let hit = |auth: &str, resets_at| LimitHitEntry {
+            ts: NOW - 60,
+            auth: auth.to_string(),
+            model: "gpt-5.1-codex".to_string(),
+            window_minutes: Some(300),
+            resets_at: Some(resets_at),
+        };
+        let entries = vec![
+            hit("API key ending in 1234", NOW - 1),
+            hit("someone-else@example.com", NOW + 600),
+        ];
+        assert_eq!(
+            recorded_quota(&entries, "API key ending in 1234", NOW),
+            None
+        );
+
+        let entries = [entries, vec![hit("API key ending in 1234", NOW + 600)]].concat();
+        assert_eq!(
+            recorded_quota(&entries, "API key ending in 1234", NOW),
+            Some(Quota {
+                remaining_percent: 0.0,
+                resets_at: Some(NOW + 600),
+            })
+        );
+    }
+}
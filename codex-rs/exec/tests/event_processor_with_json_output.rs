@@ -19,9 +19,13 @@ use codex_core::protocol::WarningEvent;
 use codex_core::protocol::WebSearchEndEvent;
 use codex_exec::event_processor_with_jsonl_output::EventProcessorWithJsonOutput;
 use codex_exec::exec_events::AgentMessageItem;
+use codex_exec::exec_events::AuthSwitchReason;
+use codex_exec::exec_events::AuthSwitchedEvent;
 use codex_exec::exec_events::CommandExecutionItem;
 use codex_exec::exec_events::CommandExecutionStatus;
 use codex_exec::exec_events::ErrorItem;
+use codex_exec::exec_events::FileChangeItem;
+use codex_exec::exec_events::FileUpdateChange;
 use codex_exec::exec_events::ItemCompletedEvent;
 use codex_exec::exec_events::ItemStartedEvent;
 use codex_exec::exec_events::ItemUpdatedEvent;
@@ -32,6 +36,7 @@ use codex_exec::exec_events::McpToolCallStatus;
 use codex_exec::exec_events::PatchApplyStatus;
 use codex_exec::exec_events::PatchChangeKind;
 use codex_exec::exec_events::ReasoningItem;
+use codex_exec::exec_events::SCHEMA_VERSION;
 use codex_exec::exec_events::ThreadErrorEvent;
 use codex_exec::exec_events::ThreadEvent;
 use codex_exec::exec_events::ThreadItem;
@@ -90,7 +95,9 @@ fn session_configured_produces_thread_started_event() {
     assert_eq!(
         out,
         vec![ThreadEvent::ThreadStarted(ThreadStartedEvent {
+            schema_version: SCHEMA_VERSION,
             thread_id: "67e55044-10b1-426f-9247-bb680e5fe0c8".to_string(),
+            tags: Default::default(),
         })]
     );
 }
@@ -102,10 +109,37 @@ fn task_started_produces_turn_started_event() {
         "t1",
         EventMsg::TaskStarted(codex_core::protocol::TaskStartedEvent {
             model_context_window: Some(32_000),
+            auth: None,
         }),
     ));
 
-    assert_eq!(out, vec![ThreadEvent::TurnStarted(TurnStartedEvent {})]);
+    assert_eq!(
+        out,
+        vec![ThreadEvent::TurnStarted(TurnStartedEvent::default())]
+    );
+}
+
+#[test]
+fn task_started_reports_the_credentials_in_use() {
+    let mut ep = EventProcessorWithJsonOutput::new(None);
+    let out = ep.collect_thread_events(&event(
+        "t1",
+        EventMsg::TaskStarted(codex_core::protocol::TaskStartedEvent {
+            model_context_window: Some(32_000),
+            auth: Some("chatgpt (me@example.com)".to_string()),
+        }),
+    ));
+
+    assert_eq!(
+        out,
+        vec![ThreadEvent::TurnStarted(TurnStartedEvent {
+            auth: Some("chatgpt (me@example.com)".to_string()),
+        })]
+    );
+    assert_eq!(
+        serde_json::to_value(&out[0]).expect("serialize"),
+        json!({"type": "turn.started", "auth": "chatgpt (me@example.com)"})
+    );
 }
 
 #[test]
@@ -573,6 +607,36 @@ fn warning_event_produces_error_item() {
     );
 }
 
+#[test]
+fn auth_switched_event_is_forwarded() {
+    let mut ep = EventProcessorWithJsonOutput::new(None);
+    let out = ep.collect_thread_events(&event(
+        "e1",
+        EventMsg::AuthSwitched(codex_core::protocol::AuthSwitchedEvent {
+            from: "API key ending in 1111".to_string(),
+            to: "API key ending in 2222".to_string(),
+            reason: AuthSwitchReason::QuotaExceeded,
+        }),
+    ));
+    assert_eq!(
+        out,
+        vec![ThreadEvent::AuthSwitched(AuthSwitchedEvent {
+            from: "API key ending in 1111".to_string(),
+            to: "API key ending in 2222".to_string(),
+            reason: AuthSwitchReason::QuotaExceeded,
+        })]
+    );
+    assert_eq!(
+        serde_json::to_value(&out[0]).expect("serialize"),
+        json!({
+            "type": "auth.switched",
+            "from": "API key ending in 1111",
+            "to": "API key ending in 2222",
+            "reason": "quota_exceeded",
+        })
+    );
+}
+
 #[test]
 fn stream_error_event_produces_error() {
     let mut ep = EventProcessorWithJsonOutput::new(None);
@@ -680,6 +744,7 @@ fn exec_command_end_success_produces_completed_command_item() {
             exit_code: 0,
             duration: Duration::from_millis(5),
             formatted_output: String::new(),
+            sandbox_denied: false,
         }),
     );
     let out_ok = ep.collect_thread_events(&end_ok);
@@ -753,6 +818,7 @@ fn exec_command_end_failure_produces_failed_command_item() {
             exit_code: 1,
             duration: Duration::from_millis(2),
             formatted_output: String::new(),
+            sandbox_denied: false,
         }),
     );
     let out_fail = ep.collect_thread_events(&end_fail);
@@ -794,6 +860,7 @@ fn exec_command_end_without_begin_is_ignored() {
             exit_code: 0,
             duration: Duration::from_millis(1),
             formatted_output: String::new(),
+            sandbox_denied: false,
         }),
     );
     let out = ep.collect_thread_events(&end_only);
@@ -964,6 +1031,7 @@ fn task_complete_produces_turn_completed_with_usage() {
         EventMsg::TokenCount(codex_core::protocol::TokenCountEvent {
             info: Some(info),
             rate_limits: None,
+            cost: None,
         }),
     );
     assert!(ep.collect_thread_events(&token_count_event).is_empty());
@@ -987,3 +1055,86 @@ fn task_complete_produces_turn_completed_with_usage() {
         })]
     );
 }
+
+/// The `--json` wire format is versioned by `SCHEMA_VERSION`. A change that
+/// breaks this test must bump the version and update docs/exec.md.
+#[test]
+fn events_keep_their_wire_format() {
+    let item = |id: &str, details: ThreadItemDetails| ThreadItem {
+        id: id.to_string(),
+        details,
+    };
+    let events = vec![
+        ThreadEvent::ThreadStarted(ThreadStartedEvent {
+            schema_version: SCHEMA_VERSION,
+            thread_id: "t1".to_string(),
+            tags: Default::default(),
+        }),
+        ThreadEvent::TurnStarted(TurnStartedEvent::default()),
+        ThreadEvent::ItemStarted(ItemStartedEvent {
+            item: item(
+                "item_0",
+                ThreadItemDetails::CommandExecution(CommandExecutionItem {
+                    command: "ls".to_string(),
+                    aggregated_output: String::new(),
+                    exit_code: None,
+                    status: CommandExecutionStatus::InProgress,
+                }),
+            ),
+        }),
+        ThreadEvent::ItemCompleted(ItemCompletedEvent {
+            item: item(
+                "item_1",
+                ThreadItemDetails::FileChange(FileChangeItem {
+                    changes: vec![FileUpdateChange {
+                        path: "src/lib.rs".to_string(),
+                        kind: PatchChangeKind::Update,
+                    }],
+                    status: PatchApplyStatus::Completed,
+                }),
+            ),
+        }),
+        ThreadEvent::AuthSwitched(AuthSwitchedEvent {
+            from: "key 1".to_string(),
+            to: "key 2".to_string(),
+            reason: AuthSwitchReason::QuotaExceeded,
+        }),
+        ThreadEvent::ItemCompleted(ItemCompletedEvent {
+            item: item(
+                "item_2",
+                ThreadItemDetails::AgentMessage(AgentMessageItem {
+                    text: "done".to_string(),
+                }),
+            ),
+        }),
+        ThreadEvent::TurnCompleted(TurnCompletedEvent {
+            usage: Usage {
+                input_tokens: 10,
+                cached_input_tokens: 4,
+                output_tokens: 2,
+            },
+        }),
+        ThreadEvent::TurnFailed(TurnFailedEvent {
+            error: ThreadErrorEvent {
+                message: "boom".to_string(),
+            },
+        }),
+    ];
+    let lines: Vec<String> = events
+        .iter()
+        .map(|event| serde_json::to_string(event).expect("serialize"))
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            r#"{"type":"thread.started","schema_version":1,"thread_id":"t1"}"#,
+            r#"{"type":"turn.started"}"#,
+            r#"{"type":"item.started","item":{"id":"item_0","type":"command_execution","command":"ls","aggregated_output":"","exit_code":null,"status":"in_progress"}}"#,
+            r#"{"type":"item.completed","item":{"id":"item_1","type":"file_change","changes":[{"path":"src/lib.rs","kind":"update"}],"status":"completed"}}"#,
+            r#"{"type":"auth.switched","from":"key 1","to":"key 2","reason":"quota_exceeded"}"#,
+            r#"{"type":"item.completed","item":{"id":"item_2","type":"agent_message","text":"done"}}"#,
+            r#"{"type":"turn.completed","usage":{"input_tokens":10,"cached_input_tokens":4,"output_tokens":2}}"#,
+            r#"{"type":"turn.failed","error":{"message":"boom"}}"#,
+        ]
+    );
+}
@@ -0,0 +1,51 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use core_test_support::responses;
+use core_test_support::test_codex_exec::test_codex_exec;
+use serde_json::Value;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn exec_writes_artifacts_to_the_directory() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+    let server = responses::start_mock_server().await;
+    let body = responses::sse(vec![
+        responses::ev_response_created("resp1"),
+        responses::ev_assistant_message("m1", "fixture hello"),
+        responses::ev_completed("resp1"),
+    ]);
+    responses::mount_sse_once(&server, body).await;
+
+    let artifacts_dir = test.cwd_path().join("out/artifacts");
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("--artifacts-dir")
+        .arg(&artifacts_dir)
+        .arg("tell me a joke")
+        .assert()
+        .success();
+
+    assert_eq!(
+        std::fs::read_to_string(artifacts_dir.join("last_message.txt"))?,
+        "fixture hello"
+    );
+    assert_eq!(
+        std::fs::read_to_string(artifacts_dir.join("changes.diff"))?,
+        ""
+    );
+    let events: Vec<Value> = std::fs::read_to_string(artifacts_dir.join("events.jsonl"))?
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("event json"))
+        .collect();
+    let types: Vec<&str> = events
+        .iter()
+        .map(|event| event["type"].as_str().expect("event type"))
+        .collect();
+    assert_eq!(types.first(), Some(&"thread.started"));
+    assert_eq!(types.last(), Some(&"turn.completed"));
+    assert!(events.iter().any(|event| {
+        event["type"] == "item.completed" && event["item"]["text"] == "fixture hello"
+    }));
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use core_test_support::responses;
+use core_test_support::test_codex_exec::test_codex_exec;
+use predicates::str::contains;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn exec_batch_runs_each_task_in_its_own_session() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+    std::fs::create_dir(test.cwd_path().join("web"))?;
+    let batch_path = test.cwd_path().join("tasks.yaml");
+    std::fs::write(
+        &batch_path,
+        "\
+- name: api
+  prompt: migrate the api package
+- prompt: migrate the web package
+  cwd: web
+  model: gpt-5.1-codex
+",
+    )?;
+
+    let server = responses::start_mock_server().await;
+    let body = |id: &str| {
+        responses::sse(vec![
+            responses::ev_response_created(id),
+            responses::ev_assistant_message(&format!("{id}-msg"), "done"),
+            responses::ev_completed(id),
+        ])
+    };
+    let response_mock =
+        responses::mount_sse_sequence(&server, vec![body("resp1"), body("resp2")]).await;
+
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("-m")
+        .arg("gpt-5.1")
+        .arg("--batch")
+        .arg(&batch_path)
+        .assert()
+        .success()
+        .stderr(contains("Batch finished: 2 succeeded, 0 failed."))
+        .stderr(contains("✓ 1. api: no changes"))
+        .stderr(contains("✓ 2. migrate the web package: no changes"));
+
+    let requests = response_mock.requests();
+    assert_eq!(requests.len(), 2);
+    let first = requests[0].body_json();
+    let second = requests[1].body_json();
+    assert_eq!(first["model"], "gpt-5.1");
+    assert_eq!(second["model"], "gpt-5.1-codex");
+    assert!(
+        requests[0]
+            .message_input_texts("user")
+            .iter()
+            .any(|text| text == "migrate the api package")
+    );
+    // Each task starts a fresh session, so the second request does not carry
+    // the first task's prompt.
+    let second_texts = requests[1].message_input_texts("user");
+    assert!(
+        second_texts
+            .iter()
+            .any(|text| text == "migrate the web package")
+    );
+    assert!(
+        !second_texts
+            .iter()
+            .any(|text| text == "migrate the api package")
+    );
+    assert_ne!(first["prompt_cache_key"], second["prompt_cache_key"]);
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use core_test_support::responses;
+use core_test_support::test_codex_exec::test_codex_exec;
+use predicates::str::contains;
+use serde_json::Value;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn dry_run_reports_the_request_without_calling_the_model() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+    std::fs::write(
+        test.cwd_path().join("AGENTS.md"),
+        "Run `cargo test` before finishing.",
+    )?;
+
+    let server = responses::start_mock_server().await;
+    let response_mock = responses::mount_sse_once(
+        &server,
+        responses::sse(vec![
+            responses::ev_response_created("resp1"),
+            responses::ev_assistant_message("msg1", "done"),
+            responses::ev_completed("resp1"),
+        ]),
+    )
+    .await;
+
+    let output = test
+        .cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("-m")
+        .arg("gpt-5.1-codex")
+        .arg("--dry-run")
+        .arg("--json")
+        .arg("count the lines of code")
+        .output()?;
+    assert!(output.status.success(), "dry run failed: {output:?}");
+
+    let stdout = String::from_utf8(output.stdout)?;
+    let report: Value = stdout
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).expect("event json"))
+        .find(|event| event["type"] == "dry_run.report")
+        .expect("dry_run.report event");
+    assert_eq!(report["model"], "gpt-5.1-codex");
+    assert_eq!(report["sandbox"], "read-only");
+    assert_eq!(report["approval_policy"], "never");
+    assert!(report["estimated_prompt_tokens"].as_i64().unwrap() > 0);
+    assert!(
+        report["tools"]
+            .as_array()
+            .unwrap()
+            .contains(&Value::from("shell_command")),
+        "tools: {}",
+        report["tools"]
+    );
+    let instruction_files = report["instruction_files"].as_array().unwrap();
+    assert_eq!(instruction_files.len(), 1);
+    assert!(
+        instruction_files[0]
+            .as_str()
+            .unwrap()
+            .ends_with("AGENTS.md")
+    );
+    assert!(
+        !stdout.contains("turn.started"),
+        "a dry run starts no turn: {stdout}"
+    );
+    assert_eq!(response_mock.requests().len(), 0);
+
+    // The human output lists the same report.
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("-m")
+        .arg("gpt-5.1-codex")
+        .arg("--dry-run")
+        .arg("count the lines of code")
+        .assert()
+        .success()
+        .stderr(contains("dry run: nothing was sent to the model"))
+        .stderr(contains("instruction files:"))
+        .stderr(contains("shell_command"));
+    assert_eq!(response_mock.requests().len(), 0);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn dry_run_runs_no_hooks_and_starts_no_mcp_servers() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+    let server = responses::start_mock_server().await;
+
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("-c")
+        .arg(r#"hooks.session_start=["sh", "-c", "touch hook-ran"]"#)
+        .arg("-c")
+        .arg(r#"mcp_servers.probe.command="sh""#)
+        .arg("-c")
+        .arg(r#"mcp_servers.probe.args=["-c", "touch mcp-started"]"#)
+        .arg("--dry-run")
+        .arg("count the lines of code")
+        .assert()
+        .success()
+        .stderr(contains("dry run: nothing was sent to the model"));
+    assert!(!test.cwd_path().join("hook-ran").exists());
+    assert!(!test.cwd_path().join("mcp-started").exists());
+
+    Ok(())
+}
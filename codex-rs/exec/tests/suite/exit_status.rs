@@ -0,0 +1,84 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use core_test_support::responses;
+use core_test_support::test_codex_exec::test_codex_exec;
+use predicates::str::contains;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn exits_with_task_failed_when_the_agent_declares_a_failure() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+
+    let server = responses::start_mock_server().await;
+    let body = responses::sse(vec![
+        responses::ev_response_created("resp1"),
+        responses::ev_assistant_message(
+            "m1",
+            "I could not finish.\n\nTASK FAILED: the staging database is unreachable",
+        ),
+        responses::ev_completed("resp1"),
+    ]);
+    let response_mock = responses::mount_sse_once(&server, body).await;
+
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("migrate the schema")
+        .assert()
+        .code(5);
+
+    let request = response_mock.single_request();
+    assert!(
+        request
+            .message_input_texts("developer")
+            .iter()
+            .any(|text| text.contains("TASK FAILED:"))
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn fail_on_tests_failed_checks_the_last_test_run() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+
+    let server = responses::start_mock_server().await;
+    let bodies = vec![
+        responses::sse(vec![
+            responses::ev_response_created("resp1"),
+            responses::ev_shell_command_call("call1", "pytest tests/"),
+            responses::ev_completed("resp1"),
+        ]),
+        responses::sse(vec![
+            responses::ev_response_created("resp2"),
+            responses::ev_assistant_message("m2", "The tests still fail."),
+            responses::ev_completed("resp2"),
+        ]),
+    ];
+    responses::mount_sse_sequence(&server, bodies.clone()).await;
+
+    // The run itself finishes, so without `--fail-on` it succeeds.
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("--dangerously-bypass-approvals-and-sandbox")
+        .arg("-m")
+        .arg("gpt-5.1-codex")
+        .arg("fix the tests")
+        .assert()
+        .success();
+
+    server.reset().await;
+    responses::mount_sse_sequence(&server, bodies).await;
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("--dangerously-bypass-approvals-and-sandbox")
+        .arg("-m")
+        .arg("gpt-5.1-codex")
+        .arg("--fail-on")
+        .arg("patch-rejected,tests-failed")
+        .arg("fix the tests")
+        .assert()
+        .code(7)
+        .stderr(contains("Failing the run: tests failed"));
+
+    Ok(())
+}
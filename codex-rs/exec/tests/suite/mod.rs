@@ -1,9 +1,14 @@
 // Aggregates all former standalone integration tests as modules.
 mod add_dir;
 mod apply_patch;
+mod artifacts;
 mod auth_env;
+mod batch;
+mod dry_run;
+mod exit_status;
 mod originator;
 mod output_schema;
 mod resume;
+mod run_limits;
 mod sandbox;
 mod server_error_exit;
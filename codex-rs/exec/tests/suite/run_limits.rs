@@ -0,0 +1,41 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use core_test_support::responses;
+use core_test_support::test_codex_exec::test_codex_exec;
+use predicates::str::contains;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn max_turns_stops_the_run_with_a_summary() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+
+    let server = responses::start_mock_server().await;
+    let tool_call = |id: &str| {
+        responses::sse(vec![
+            responses::ev_response_created(id),
+            responses::ev_shell_command_call(&format!("{id}-call"), "echo still looping"),
+            responses::ev_completed(id),
+        ])
+    };
+    let response_mock =
+        responses::mount_sse_sequence(&server, vec![tool_call("resp1"), tool_call("resp2")]).await;
+
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("-m")
+        .arg("gpt-5.1-codex")
+        .arg("--max-turns")
+        .arg("2")
+        .arg("keep going")
+        .assert()
+        .code(8)
+        .stderr(contains("reached the limit of 2 turns."))
+        .stderr(contains("The agent made 2 tool calls."))
+        .stderr(contains(
+            "Last commands: `echo still looping`, `echo still looping`",
+        ));
+
+    assert_eq!(response_mock.requests().len(), 2);
+
+    Ok(())
+}
@@ -162,7 +162,9 @@ pub fn run(
         // Follow symlinks to search their contents.
         .follow_links(true)
         // Don't require git to be present to apply to apply git-related ignore rules.
-        .require_git(false);
+        .require_git(false)
+        // Paths the user keeps out of the model's view are not offered either.
+        .add_custom_ignore_filename(".codexignore");
     if !respect_gitignore {
         walk_builder
             .git_ignore(false)
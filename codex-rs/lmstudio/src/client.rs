@@ -1,5 +1,6 @@
 use codex_core::LMSTUDIO_OSS_PROVIDER_ID;
 use codex_core::config::Config;
+use reqwest::header::HeaderMap;
 use std::io;
 use std::path::Path;
 
@@ -7,6 +8,9 @@ use std::path::Path;
 pub struct LMStudioClient {
     client: reqwest::Client,
     base_url: String,
+    /// Sent with every request: the provider's `http_headers` and its API key
+    /// or bearer token.
+    headers: HeaderMap,
 }
 
 const LMSTUDIO_CONNECTION_ERROR: &str = "LM Studio is not responding. Install from https://lmstudio.ai/download and run 'lms server start'.";
@@ -29,14 +33,18 @@ impl LMStudioClient {
             )
         })?;
 
-        let client = reqwest::Client::builder()
+        let client = provider
+            .reqwest_client_builder()
+            .map_err(io::Error::other)?
             .connect_timeout(std::time::Duration::from_secs(5))
             .build()
-            .unwrap_or_else(|_| reqwest::Client::new());
+            .map_err(io::Error::other)?;
+        let headers = provider.request_headers().map_err(io::Error::other)?;
 
         let client = LMStudioClient {
             client,
             base_url: base_url.to_string(),
+            headers,
         };
         client.check_server().await?;
 
@@ -45,7 +53,12 @@ impl LMStudioClient {
 
     async fn check_server(&self) -> io::Result<()> {
         let url = format!("{}/models", self.base_url.trim_end_matches('/'));
-        let response = self.client.get(&url).send().await;
+        let response = self
+            .client
+            .get(&url)
+            .headers(self.headers.clone())
+            .send()
+            .await;
 
         if let Ok(resp) = response {
             if resp.status().is_success() {
@@ -74,6 +87,7 @@ impl LMStudioClient {
         let response = self
             .client
             .post(&url)
+            .headers(self.headers.clone())
             .header("Content-Type", "application/json")
             .json(&request_body)
             .send()
@@ -97,6 +111,7 @@ impl LMStudioClient {
         let response = self
             .client
             .get(&url)
+            .headers(self.headers.clone())
             .send()
             .await
             .map_err(|e| io::Error::other(format!("Request failed: {e}")))?;
@@ -199,6 +214,7 @@ impl LMStudioClient {
         Self {
             client,
             base_url: host_root.into(),
+            headers: HeaderMap::new(),
         }
     }
 }
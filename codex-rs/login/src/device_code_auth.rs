@@ -7,6 +7,7 @@ use std::time::Duration;
 use std::time::Instant;
 
 use crate::pkce::PkceCodes;
+use crate::qr;
 use crate::server::ServerOptions;
 use std::io;
 
@@ -14,11 +15,18 @@ const ANSI_BLUE: &str = "\x1b[94m";
 const ANSI_GRAY: &str = "\x1b[90m";
 const ANSI_RESET: &str = "\x1b[0m";
 
+/// Page where the one-time code is entered, on any device.
+const VERIFICATION_URL: &str = "https://auth.openai.com/codex/device";
+
 #[derive(Deserialize)]
 struct UserCodeResp {
     device_auth_id: String,
     #[serde(alias = "user_code", alias = "usercode")]
     user_code: String,
+    /// The verification page with the code filled in, when the server
+    /// provides one.
+    #[serde(default)]
+    verification_uri_complete: Option<String>,
     #[serde(default, deserialize_with = "deserialize_interval")]
     interval: u64,
 }
@@ -137,26 +145,68 @@ async fn poll_for_token(
     }
 }
 
-fn print_device_code_prompt(code: &str) {
+impl UserCodeResp {
+    /// The link the QR code opens: the verification page with the code
+    /// filled in, so nothing has to be typed on the phone.
+    fn verification_link(&self) -> String {
+        self.verification_uri_complete.clone().unwrap_or_else(|| {
+            format!(
+                "{VERIFICATION_URL}?user_code={}",
+                urlencoding::encode(&self.user_code)
+            )
+        })
+    }
+}
+
+fn print_device_code_prompt(code: &str, qr_link: &str, show_qr_code: bool, color: bool) {
+    let (blue, gray, reset) = if color {
+        (ANSI_BLUE, ANSI_GRAY, ANSI_RESET)
+    } else {
+        ("", "", "")
+    };
+    let qr = match show_qr_code.then(|| qr::render(qr_link, color)).flatten() {
+        Some(qr) => {
+            let lines: String = qr.lines().map(|line| format!("\n   {line}")).collect();
+            lines + "\n"
+        }
+        None => String::new(),
+    };
     println!(
-        "\nWelcome to Codex [v{ANSI_GRAY}{version}{ANSI_RESET}]\n{ANSI_GRAY}OpenAI's command-line coding agent{ANSI_RESET}\n\
+        "\nWelcome to Codex [v{gray}{version}{reset}]\n{gray}OpenAI's command-line coding agent{reset}\n\
 \nFollow these steps to sign in with ChatGPT using device code authorization:\n\
-\n1. Open this link in your browser\n   {ANSI_BLUE}https://auth.openai.com/codex/device{ANSI_RESET}\n\
-\n2. Enter this one-time code {ANSI_GRAY}(expires in 15 minutes){ANSI_RESET}\n   {ANSI_BLUE}{code}{ANSI_RESET}\n\
-\n{ANSI_GRAY}Device codes are a common phishing target. Never share this code.{ANSI_RESET}\n",
+\n1. Open this link in your browser{scan}\n   {blue}{VERIFICATION_URL}{reset}\n{qr}\
+\n2. Enter this one-time code {gray}(expires in 15 minutes){reset}\n   {blue}{code}{reset}\n\
+\n{gray}Device codes are a common phishing target. Never share this code.{reset}\n",
         version = env!("CARGO_PKG_VERSION"),
-        code = code
+        code = code,
+        scan = if qr.is_empty() {
+            ""
+        } else {
+            ", or scan the QR code with your phone"
+        },
     );
 }
 
-/// Full device code login flow.
+/// Full device code login flow, with the prompt printed as plain text.
 pub async fn run_device_code_login(opts: ServerOptions) -> std::io::Result<()> {
+    run_device_code_login_with_qr_code(opts, false, false).await
+}
+
+/// Device code login that also shows the verification link as a QR code
+/// when `show_qr_code` is set, so it can be opened on a phone while this
+/// machine only waits for the approval. `color` is the caller's decision on
+/// ANSI colors in the prompt, e.g. from `--color`.
+pub async fn run_device_code_login_with_qr_code(
+    opts: ServerOptions,
+    show_qr_code: bool,
+    color: bool,
+) -> std::io::Result<()> {
     let client = reqwest::Client::new();
     let base_url = opts.issuer.trim_end_matches('/');
     let api_base_url = format!("{}/api/accounts", opts.issuer.trim_end_matches('/'));
     let uc = request_user_code(&client, &api_base_url, &opts.client_id).await?;
 
-    print_device_code_prompt(&uc.user_code);
+    print_device_code_prompt(&uc.user_code, &uc.verification_link(), show_qr_code, color);
 
     let code_resp = poll_for_token(
         &client,
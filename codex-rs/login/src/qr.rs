@@ -0,0 +1,97 @@
+//! The device login link as a QR code in the terminal, so it can be opened
+//! on a phone.
+
+use qrcode::Color;
+use qrcode::EcLevel;
+use qrcode::QrCode;
+use qrcode::render::unicode::Dense1x2;
+
+/// Light modules around the code; scanners need it to find the code.
+const QUIET_ZONE: usize = 4;
+
+const DARK_FG: &str = "30";
+const LIGHT_FG: &str = "97";
+const DARK_BG: &str = "40";
+const LIGHT_BG: &str = "107";
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// `text` as half blocks, two rows of modules per line. With `color`, the
+/// modules are drawn black on white so the code reads the same on light and
+/// dark terminal themes; without it, dark modules are drawn as filled
+/// blocks. `None` when `text` does not fit in a QR code.
+pub(crate) fn render(text: &str, color: bool) -> Option<String> {
+    let code = QrCode::with_error_correction_level(text, EcLevel::L).ok()?;
+    if !color {
+        return Some(
+            code.render::<Dense1x2>()
+                .dark_color(Dense1x2::Dark)
+                .light_color(Dense1x2::Light)
+                .quiet_zone(true)
+                .build(),
+        );
+    }
+
+    let width = code.width();
+    let colors = code.to_colors();
+    let span = width + 2 * QUIET_ZONE;
+    let dark = |x: usize, y: usize| {
+        let inside = |at: usize| (QUIET_ZONE..QUIET_ZONE + width).contains(&at);
+        inside(x) && inside(y) && colors[(y - QUIET_ZONE) * width + x - QUIET_ZONE] == Color::Dark
+    };
+    let mut lines = Vec::new();
+    for y in (0..span).step_by(2) {
+        let mut line = String::new();
+        for x in 0..span {
+            // The upper module is the glyph, the lower one the background.
+            let fg = if dark(x, y) { DARK_FG } else { LIGHT_FG };
+            let bg = if y + 1 < span && dark(x, y + 1) {
+                DARK_BG
+            } else {
+                LIGHT_BG
+            };
+            line.push_str(&format!("\x1b[{fg};{bg}m▀"));
+        }
+        line.push_str(ANSI_RESET);
+        lines.push(line);
+    }
+    Some(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const LINK: &str = "https://auth.openai.com/codex/device?user_code=ABCD-1234";
+
+    fn width(text: &str) -> usize {
+        QrCode::with_error_correction_level(text, EcLevel::L)
+            .expect("encode")
+            .width()
+    }
+
+    #[test]
+    fn dark_modules_are_drawn_dark() {
+        let rendered = render(LINK, true).expect("render");
+        let lines: Vec<&str> = rendered.lines().collect();
+        let span = width(LINK) + 2 * QUIET_ZONE;
+        assert_eq!(lines.len(), span.div_ceil(2));
+        assert!(lines.iter().all(|line| line.matches('▀').count() == span));
+
+        let cells: Vec<&str> = lines[QUIET_ZONE / 2].split('▀').collect();
+        // The quiet zone is light; the top-left finder pattern starts dark.
+        assert_eq!(cells[QUIET_ZONE - 1], "\x1b[97;107m");
+        assert_eq!(cells[QUIET_ZONE], "\x1b[30;40m");
+    }
+
+    #[test]
+    fn renders_without_color() {
+        let rendered = render(LINK, false).expect("render");
+        assert!(!rendered.contains('\x1b'));
+        assert_eq!(
+            rendered.lines().count(),
+            (width(LINK) + 2 * QUIET_ZONE).div_ceil(2)
+        );
+        assert_eq!(render(&"a".repeat(3000), false), None);
+    }
+}
@@ -14,8 +14,12 @@ use crate::pkce::PkceCodes;
 use crate::pkce::generate_pkce;
 use base64::Engine;
 use chrono::Utc;
+use codex_core::audit_log;
+use codex_core::audit_log::AuditEntry;
+use codex_core::audit_log::AuditEvent;
 use codex_core::auth::AuthCredentialsStoreMode;
 use codex_core::auth::AuthDotJson;
+use codex_core::auth::owning_codex_home;
 use codex_core::auth::save_auth;
 use codex_core::default_client::originator;
 use codex_core::token_data::TokenData;
@@ -558,12 +562,22 @@ pub(crate) async fn persist_tokens_async(
         {
             tokens.account_id = Some(acc.to_string());
         }
+        let identity = tokens
+            .id_token
+            .email
+            .clone()
+            .unwrap_or_else(|| "ChatGPT account".to_string());
         let auth = AuthDotJson {
             openai_api_key: api_key,
             tokens: Some(tokens),
             last_refresh: Some(Utc::now()),
         };
-        save_auth(&codex_home, &auth, auth_credentials_store_mode)
+        save_auth(&codex_home, &auth, auth_credentials_store_mode)?;
+        audit_log::record(
+            owning_codex_home(&codex_home),
+            &AuditEntry::now(AuditEvent::Login, identity).detail("ChatGPT"),
+        );
+        Ok(())
     })
     .await
     .map_err(|e| io::Error::other(format!("persist task failed: {e}")))?
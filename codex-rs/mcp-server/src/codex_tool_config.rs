@@ -164,6 +164,7 @@ impl CodexToolCallParam {
             model_provider: None,
             codex_linux_sandbox_exe,
             base_instructions,
+            system_prompt: None,
             developer_instructions,
             compact_prompt,
             include_apply_patch_tool: None,
@@ -171,6 +172,7 @@ impl CodexToolCallParam {
             tools_web_search_request: None,
             experimental_sandbox_command_assessment: None,
             additional_writable_roots: Vec::new(),
+            roots: Vec::new(),
         };
 
         let cli_overrides = cli_overrides
@@ -7,6 +7,10 @@ use crate::codex_tool_config::create_tool_for_codex_tool_call_param;
 use crate::codex_tool_config::create_tool_for_codex_tool_call_reply_param;
 use crate::error_code::INVALID_REQUEST_ERROR_CODE;
 use crate::outgoing_message::OutgoingMessageSender;
+use crate::session_tools;
+use crate::session_tools::create_tool_for_codex_sessions_param;
+use crate::session_tools::create_tool_for_codex_status;
+use crate::session_tools::create_tool_for_codex_transcript_param;
 use codex_protocol::ConversationId;
 use codex_protocol::protocol::SessionSource;
 
@@ -40,6 +44,8 @@ pub(crate) struct MessageProcessor {
     outgoing: Arc<OutgoingMessageSender>,
     initialized: bool,
     codex_linux_sandbox_exe: Option<PathBuf>,
+    config: Arc<Config>,
+    auth_manager: Arc<AuthManager>,
     conversation_manager: Arc<ConversationManager>,
     running_requests_id_to_codex_uuid: Arc<Mutex<HashMap<RequestId, ConversationId>>>,
 }
@@ -58,12 +64,16 @@ impl MessageProcessor {
             false,
             config.cli_auth_credentials_store_mode,
         );
-        let conversation_manager =
-            Arc::new(ConversationManager::new(auth_manager, SessionSource::Mcp));
+        let conversation_manager = Arc::new(ConversationManager::new(
+            auth_manager.clone(),
+            SessionSource::Mcp,
+        ));
         Self {
             outgoing,
             initialized: false,
             codex_linux_sandbox_exe,
+            config,
+            auth_manager,
             conversation_manager,
             running_requests_id_to_codex_uuid: Arc::new(Mutex::new(HashMap::new())),
         }
@@ -306,6 +316,9 @@ impl MessageProcessor {
             tools: vec![
                 create_tool_for_codex_tool_call_param(),
                 create_tool_for_codex_tool_call_reply_param(),
+                create_tool_for_codex_sessions_param(),
+                create_tool_for_codex_transcript_param(),
+                create_tool_for_codex_status(),
             ],
             next_cursor: None,
         };
@@ -328,6 +341,21 @@ impl MessageProcessor {
                 self.handle_tool_call_codex_session_reply(id, arguments)
                     .await
             }
+            "codex-sessions" => {
+                let result = session_tools::list_sessions(&self.config, arguments).await;
+                self.send_response::<mcp_types::CallToolRequest>(id, result)
+                    .await;
+            }
+            "codex-transcript" => {
+                let result = session_tools::read_transcript(&self.config, arguments).await;
+                self.send_response::<mcp_types::CallToolRequest>(id, result)
+                    .await;
+            }
+            "codex-status" => {
+                let result = session_tools::status(&self.config, &self.auth_manager);
+                self.send_response::<mcp_types::CallToolRequest>(id, result)
+                    .await;
+            }
             _ => {
                 let result = CallToolResult {
                     content: vec![ContentBlock::TextContent(TextContent {
@@ -0,0 +1,429 @@
+//! Tools that let MCP clients see what Codex has done and can do: the
+//! saved sessions, the transcript of one of them, and who is signed in with
+//! which usage limits hit. New tasks are started with the `codex` tool and
+//! continued with `codex-reply`.
+
+use std::path::Path;
+
+use codex_core::AuthManager;
+use codex_core::RolloutRecorder;
+use codex_core::TranscriptEntry;
+use codex_core::config::Config;
+use codex_core::limit_history::read_limit_history;
+use codex_core::parse_cursor;
+use codex_core::rate_limits::unix_now;
+use codex_core::read_transcript;
+use codex_core::redact::redact_secrets;
+use codex_core::state_dir::state_dir;
+use codex_protocol::items::TurnItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::SessionMetaLine;
+use codex_protocol::protocol::USER_MESSAGE_BEGIN;
+use mcp_types::CallToolResult;
+use mcp_types::ContentBlock;
+use mcp_types::TextContent;
+use mcp_types::Tool;
+use mcp_types::ToolInputSchema;
+use schemars::JsonSchema;
+use schemars::r#gen::SchemaSettings;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::json;
+
+const DEFAULT_SESSIONS_LIMIT: usize = 20;
+const MAX_SESSIONS_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexSessionsParam {
+    /// Maximum number of sessions to return, most recent first (default 20,
+    /// at most 100).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+
+    /// The `nextCursor` of the previous page, to list the sessions after it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexTranscriptParam {
+    /// The conversation id of the session.
+    pub conversation_id: String,
+}
+
+pub(crate) fn create_tool_for_codex_sessions_param() -> Tool {
+    Tool {
+        name: "codex-sessions".to_string(),
+        title: Some("Codex Sessions".to_string()),
+        input_schema: input_schema_for::<CodexSessionsParam>(),
+        output_schema: None,
+        description: Some(
+            "List saved Codex sessions, most recent first, with the conversation id, start time, \
+             working directory, and first prompt of each."
+                .to_string(),
+        ),
+        annotations: None,
+    }
+}
+
+pub(crate) fn create_tool_for_codex_transcript_param() -> Tool {
+    Tool {
+        name: "codex-transcript".to_string(),
+        title: Some("Codex Transcript".to_string()),
+        input_schema: input_schema_for::<CodexTranscriptParam>(),
+        output_schema: None,
+        description: Some(
+            "Fetch the prompts and replies of a saved Codex session by its conversation id."
+                .to_string(),
+        ),
+        annotations: None,
+    }
+}
+
+pub(crate) fn create_tool_for_codex_status() -> Tool {
+    Tool {
+        name: "codex-status".to_string(),
+        title: Some("Codex Status".to_string()),
+        input_schema: ToolInputSchema {
+            properties: None,
+            required: None,
+            r#type: "object".to_string(),
+        },
+        output_schema: None,
+        description: Some(
+            "Show which account Codex is signed in with and the usage limits it has hit that \
+             have not reset yet."
+                .to_string(),
+        ),
+        annotations: None,
+    }
+}
+
+fn input_schema_for<T: JsonSchema>() -> ToolInputSchema {
+    let schema = SchemaSettings::draft2019_09()
+        .with(|s| {
+            s.inline_subschemas = true;
+            s.option_add_null_type = false;
+        })
+        .into_generator()
+        .into_root_schema_for::<T>();
+
+    #[expect(clippy::expect_used)]
+    let schema_value = serde_json::to_value(&schema).expect("tool schema should serialise to JSON");
+
+    serde_json::from_value::<ToolInputSchema>(schema_value).unwrap_or_else(|e| {
+        panic!("failed to create Tool from schema: {e}");
+    })
+}
+
+pub(crate) async fn list_sessions(config: &Config, arguments: Option<Value>) -> CallToolResult {
+    let params = match arguments {
+        Some(arguments) => match serde_json::from_value::<CodexSessionsParam>(arguments) {
+            Ok(params) => params,
+            Err(e) => {
+                return error_result(format!("Failed to parse codex-sessions arguments: {e}"));
+            }
+        },
+        None => CodexSessionsParam::default(),
+    };
+    match sessions_json(&config.codex_home, &config.model_provider_id, params).await {
+        Ok(sessions) => json_result(sessions),
+        Err(e) => error_result(format!("Failed to list sessions: {e}")),
+    }
+}
+
+async fn sessions_json(
+    codex_home: &Path,
+    default_provider: &str,
+    params: CodexSessionsParam,
+) -> std::io::Result<Value> {
+    let limit = params.limit.map_or(DEFAULT_SESSIONS_LIMIT, |limit| {
+        (limit as usize).clamp(1, MAX_SESSIONS_LIMIT)
+    });
+    let cursor = params.cursor.as_deref().and_then(parse_cursor);
+    // Sessions from every source, including those started through this
+    // server, and with every provider.
+    let page = RolloutRecorder::list_conversations(
+        codex_home,
+        limit,
+        cursor.as_ref(),
+        &[],
+        None,
+        default_provider,
+    )
+    .await?;
+
+    let sessions: Vec<Value> = page
+        .items
+        .into_iter()
+        .filter_map(|item| {
+            let SessionMetaLine { meta, .. } =
+                serde_json::from_value(item.head.first()?.clone()).ok()?;
+            Some(json!({
+                "conversationId": meta.id.to_string(),
+                "startedAt": meta.timestamp,
+                "updatedAt": item.updated_at,
+                "cwd": meta.cwd,
+                "source": meta.source,
+                "modelProvider": meta.model_provider.unwrap_or_else(|| default_provider.to_string()),
+                "preview": preview(&item.head),
+                "path": item.path,
+            }))
+        })
+        .collect();
+    let next_cursor = page
+        .next_cursor
+        .and_then(|cursor| serde_json::to_value(&cursor).ok())
+        .and_then(|value| value.as_str().map(str::to_owned));
+    Ok(json!({
+        "sessions": sessions,
+        "nextCursor": next_cursor,
+    }))
+}
+
+/// The first prompt of a session, without the context Codex adds before it.
+fn preview(head: &[Value]) -> Option<String> {
+    let message = head
+        .iter()
+        .filter_map(|value| serde_json::from_value::<ResponseItem>(value.clone()).ok())
+        .find_map(|item| match codex_core::parse_turn_item(&item) {
+            Some(TurnItem::UserMessage(user)) => Some(user.message()),
+            _ => None,
+        })?;
+    let preview = match message.find(USER_MESSAGE_BEGIN) {
+        Some(idx) => &message[idx + USER_MESSAGE_BEGIN.len()..],
+        None => message.as_str(),
+    };
+    Some(preview.trim().to_string())
+}
+
+pub(crate) async fn read_transcript(config: &Config, arguments: Option<Value>) -> CallToolResult {
+    let CodexTranscriptParam { conversation_id } = match arguments
+        .map(serde_json::from_value::<CodexTranscriptParam>)
+    {
+        Some(Ok(params)) => params,
+        Some(Err(e)) => {
+            return error_result(format!("Failed to parse codex-transcript arguments: {e}"));
+        }
+        None => {
+            return error_result(
+                "Missing arguments for codex-transcript tool-call; the `conversationId` field is required."
+                    .to_string(),
+            );
+        }
+    };
+    match transcript_json(&config.codex_home, &conversation_id).await {
+        Ok(Some(transcript)) => json_result(transcript),
+        Ok(None) => error_result(format!(
+            "No saved session found for conversation_id: {conversation_id}"
+        )),
+        Err(e) => error_result(format!("Failed to read session {conversation_id}: {e}")),
+    }
+}
+
+/// The prompts and replies of a session, with the secrets this process
+/// knows about redacted, since MCP clients may log what they receive.
+async fn transcript_json(
+    codex_home: &Path,
+    conversation_id: &str,
+) -> std::io::Result<Option<Value>> {
+    let Some((path, mut transcript)) = read_transcript(codex_home, conversation_id).await? else {
+        return Ok(None);
+    };
+    transcript.redact(|text| redact_secrets(text).into_owned());
+    let messages: Vec<Value> = transcript
+        .entries
+        .into_iter()
+        .filter_map(|entry| match entry {
+            TranscriptEntry::Prompt { text, .. } => Some(json!({ "role": "user", "text": text })),
+            TranscriptEntry::Reply(text) => Some(json!({ "role": "assistant", "text": text })),
+            _ => None,
+        })
+        .collect();
+    Ok(Some(json!({
+        "conversationId": conversation_id,
+        "startedAt": transcript.started_at,
+        "cwd": transcript.cwd,
+        "path": path,
+        "messages": messages,
+    })))
+}
+
+pub(crate) fn status(config: &Config, auth_manager: &AuthManager) -> CallToolResult {
+    let now = unix_now();
+    let active_limits = match read_limit_history(
+        &state_dir(&config.codex_home),
+        config.shared_state_dir.as_deref(),
+    ) {
+        Ok(entries) => entries,
+        Err(e) => return error_result(format!("Failed to read the limit history: {e}")),
+    };
+    let active_limits: Vec<Value> = active_limits
+        .into_iter()
+        .filter(|hit| hit.resets_at.is_some_and(|at| at > now))
+        .map(|hit| {
+            json!({
+                "auth": hit.auth,
+                "model": hit.model,
+                "hitAt": hit.ts,
+                "resetsAt": hit.resets_at,
+            })
+        })
+        .collect();
+    let auth = auth_manager.auth().map(|auth| {
+        json!({
+            "mode": auth.mode,
+            "identity": auth.identity_label(),
+            "plan": auth.raw_plan_type(),
+        })
+    });
+    json_result(json!({
+        "auth": auth,
+        "model": config.model,
+        "modelProvider": config.model_provider_id,
+        "activeLimits": active_limits,
+    }))
+}
+
+/// The result as JSON, both structured and as text for clients that only
+/// read the content.
+fn json_result(value: Value) -> CallToolResult {
+    CallToolResult {
+        content: vec![ContentBlock::TextContent(TextContent {
+            r#type: "text".to_string(),
+            text: serde_json::to_string_pretty(&value).unwrap_or_else(|_| value.to_string()),
+            annotations: None,
+        })],
+        is_error: None,
+        structured_content: Some(value),
+    }
+}
+
+fn error_result(text: String) -> CallToolResult {
+    CallToolResult {
+        content: vec![ContentBlock::TextContent(TextContent {
+            r#type: "text".to_string(),
+            text,
+            annotations: None,
+        })],
+        is_error: Some(true),
+        structured_content: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::redact::REDACTED;
+    use codex_core::redact::register_secret;
+    use pretty_assertions::assert_eq;
+
+    const CONVERSATION_ID: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+
+    fn write_rollout(codex_home: &Path, prompt: &str, reply: &str) -> std::path::PathBuf {
+        let dir = codex_home.join("sessions/2025/01/02");
+        std::fs::create_dir_all(&dir).expect("create sessions dir");
+        let path = dir.join(format!(
+            "rollout-2025-01-02T03-04-05-{CONVERSATION_ID}.jsonl"
+        ));
+        let timestamp = "2025-01-02T03:04:05Z";
+        let lines = [
+            json!({
+                "timestamp": timestamp,
+                "type": "session_meta",
+                "payload": {
+                    "id": CONVERSATION_ID,
+                    "timestamp": timestamp,
+                    "cwd": "/repo",
+                    "originator": "codex",
+                    "cli_version": "0.0.0",
+                    "instructions": null,
+                    "source": "cli",
+                    "model_provider": "openai",
+                },
+            }),
+            json!({
+                "timestamp": timestamp,
+                "type": "response_item",
+                "payload": {
+                    "type": "message",
+                    "role": "user",
+                    "content": [{"type": "input_text", "text": prompt}],
+                },
+            }),
+            json!({
+                "timestamp": timestamp,
+                "type": "event_msg",
+                "payload": {"type": "user_message", "message": prompt},
+            }),
+            json!({
+                "timestamp": timestamp,
+                "type": "event_msg",
+                "payload": {"type": "agent_message", "message": reply},
+            }),
+        ];
+        let contents: Vec<String> = lines.iter().map(Value::to_string).collect();
+        std::fs::write(&path, contents.join("\n") + "\n").expect("write rollout");
+        path
+    }
+
+    #[tokio::test]
+    async fn sessions_are_listed_from_rollouts() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        write_rollout(codex_home.path(), "fix the build", "The build passes now.");
+
+        let sessions = sessions_json(codex_home.path(), "openai", CodexSessionsParam::default())
+            .await
+            .expect("list sessions");
+        let session = &sessions["sessions"][0];
+        assert_eq!(session["conversationId"], json!(CONVERSATION_ID));
+        assert_eq!(session["cwd"], json!("/repo"));
+        assert_eq!(session["preview"], json!("fix the build"));
+        assert_eq!(sessions["nextCursor"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn transcripts_are_read_with_secrets_redacted() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let secret = "sk-session-tools-test-secret";
+        register_secret(secret);
+        let path = write_rollout(
+            codex_home.path(),
+            &format!("use {secret} to fix the build"),
+            "The build passes now.",
+        );
+
+        let transcript = transcript_json(codex_home.path(), CONVERSATION_ID)
+            .await
+            .expect("read transcript");
+        assert_eq!(
+            transcript,
+            Some(json!({
+                "conversationId": CONVERSATION_ID,
+                "startedAt": "2025-01-02T03:04:05Z",
+                "cwd": "/repo",
+                "path": path,
+                "messages": [
+                    {"role": "user", "text": format!("use {REDACTED} to fix the build")},
+                    {"role": "assistant", "text": "The build passes now."},
+                ],
+            }))
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_sessions_have_no_transcript() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        write_rollout(codex_home.path(), "fix the build", "The build passes now.");
+
+        assert_eq!(
+            transcript_json(codex_home.path(), "00000000-0000-0000-0000-000000000000")
+                .await
+                .expect("look up transcript"),
+            None
+        );
+    }
+}
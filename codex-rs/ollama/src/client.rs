@@ -1,6 +1,7 @@
 use bytes::BytesMut;
 use futures::StreamExt;
 use futures::stream::BoxStream;
+use reqwest::header::HeaderMap;
 use serde_json::Value as JsonValue;
 use std::collections::VecDeque;
 use std::io;
@@ -17,10 +18,13 @@ use codex_core::config::Config;
 
 const OLLAMA_CONNECTION_ERROR: &str = "No running Ollama server detected. Start it with: `ollama serve` (after installing). Install instructions: https://github.com/ollama/ollama?tab=readme-ov-file#ollama";
 
-/// Client for interacting with a local Ollama instance.
+/// Client for interacting with an Ollama instance, local or behind a proxy.
 pub struct OllamaClient {
     client: reqwest::Client,
     host_root: String,
+    /// Sent with every request: the provider's `http_headers` and its API key
+    /// or bearer token.
+    headers: HeaderMap,
     uses_openai_compat: bool,
 }
 
@@ -63,13 +67,17 @@ impl OllamaClient {
             || matches!(provider.wire_api, WireApi::Chat)
                 && is_openai_compatible_base_url(base_url);
         let host_root = base_url_to_host_root(base_url);
-        let client = reqwest::Client::builder()
+        let client = provider
+            .reqwest_client_builder()
+            .map_err(io::Error::other)?
             .connect_timeout(std::time::Duration::from_secs(5))
             .build()
-            .unwrap_or_else(|_| reqwest::Client::new());
+            .map_err(io::Error::other)?;
+        let headers = provider.request_headers().map_err(io::Error::other)?;
         let client = Self {
             client,
             host_root,
+            headers,
             uses_openai_compat,
         };
         client.probe_server().await?;
@@ -83,10 +91,16 @@ impl OllamaClient {
         } else {
             format!("{}/api/tags", self.host_root.trim_end_matches('/'))
         };
-        let resp = self.client.get(url).send().await.map_err(|err| {
-            tracing::warn!("Failed to connect to Ollama server: {err:?}");
-            io::Error::other(OLLAMA_CONNECTION_ERROR)
-        })?;
+        let resp = self
+            .client
+            .get(url)
+            .headers(self.headers.clone())
+            .send()
+            .await
+            .map_err(|err| {
+                tracing::warn!("Failed to connect to Ollama server: {err:?}");
+                io::Error::other(OLLAMA_CONNECTION_ERROR)
+            })?;
         if resp.status().is_success() {
             Ok(())
         } else {
@@ -105,6 +119,7 @@ impl OllamaClient {
         let resp = self
             .client
             .get(tags_url)
+            .headers(self.headers.clone())
             .send()
             .await
             .map_err(io::Error::other)?;
@@ -135,6 +150,7 @@ impl OllamaClient {
         let resp = self
             .client
             .post(url)
+            .headers(self.headers.clone())
             .json(&serde_json::json!({"model": model, "stream": true}))
             .send()
             .await
@@ -228,6 +244,7 @@ impl OllamaClient {
         Self {
             client,
             host_root: host_root.into(),
+            headers: HeaderMap::new(),
             uses_openai_compat: false,
         }
     }
@@ -347,4 +364,39 @@ mod tests {
             .expect("expected error");
         assert_eq!(OLLAMA_CONNECTION_ERROR, err.to_string());
     }
+
+    #[tokio::test]
+    async fn test_requests_to_remote_server_carry_provider_credentials() {
+        let server = wiremock::MockServer::start().await;
+        for path in ["/v1/models", "/api/tags"] {
+            wiremock::Mock::given(wiremock::matchers::method("GET"))
+                .and(wiremock::matchers::path(path))
+                .and(wiremock::matchers::header("authorization", "Bearer secret"))
+                .and(wiremock::matchers::header("x-tenant", "research"))
+                .respond_with(
+                    wiremock::ResponseTemplate::new(200)
+                        .set_body_raw(r#"{"models":[{"name":"qwen3"}]}"#, "application/json"),
+                )
+                .mount(&server)
+                .await;
+        }
+
+        let mut provider = codex_core::create_oss_provider_with_base_url(
+            &format!("{}/v1", server.uri()),
+            WireApi::Chat,
+        );
+        provider.experimental_bearer_token = Some("secret".to_string());
+        provider.http_headers = Some(
+            [("x-tenant".to_string(), "research".to_string())]
+                .into_iter()
+                .collect(),
+        );
+        let client = OllamaClient::try_from_provider(&provider)
+            .await
+            .expect("probe with credentials");
+        assert_eq!(
+            client.fetch_models().await.expect("fetch models"),
+            vec!["qwen3".to_string()]
+        );
+    }
 }
@@ -16,6 +16,7 @@ use reqwest::Error;
 use reqwest::Response;
 use serde::Serialize;
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::time::Duration;
 use std::time::Instant;
@@ -40,6 +41,8 @@ pub struct OtelEventMetadata {
     log_user_prompts: bool,
     app_version: &'static str,
     terminal_type: String,
+    /// Session tags rendered as `key=value` pairs joined by commas.
+    tags: String,
 }
 
 #[derive(Debug, Clone)]
@@ -70,6 +73,7 @@ impl OtelEventManager {
                 log_user_prompts,
                 app_version: env!("CARGO_PKG_VERSION"),
                 terminal_type,
+                tags: String::new(),
             },
         }
     }
@@ -81,6 +85,16 @@ impl OtelEventManager {
         manager
     }
 
+    /// Attaches session tags, such as `team=payments`, to every event.
+    pub fn with_tags(mut self, tags: &BTreeMap<String, String>) -> Self {
+        self.metadata.tags = tags
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+            .join(",");
+        self
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn conversation_starts(
         &self,
@@ -99,6 +113,7 @@ impl OtelEventManager {
             event.name = "codex.conversation_starts",
             event.timestamp = %timestamp(),
             conversation.id = %self.metadata.conversation_id,
+            session.tags = %self.metadata.tags,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -148,6 +163,7 @@ impl OtelEventManager {
             event.name = "codex.api_request",
             event.timestamp = %timestamp(),
             conversation.id = %self.metadata.conversation_id,
+            session.tags = %self.metadata.tags,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -216,6 +232,7 @@ impl OtelEventManager {
             event.timestamp = %timestamp(),
             event.kind = %kind,
             conversation.id = %self.metadata.conversation_id,
+            session.tags = %self.metadata.tags,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -238,6 +255,7 @@ impl OtelEventManager {
                 event.timestamp = %timestamp(),
                 event.kind = %kind,
                 conversation.id = %self.metadata.conversation_id,
+                session.tags = %self.metadata.tags,
                 app.version = %self.metadata.app_version,
                 auth_mode = self.metadata.auth_mode,
                 user.account_id = self.metadata.account_id,
@@ -253,6 +271,7 @@ impl OtelEventManager {
                 event.name = "codex.sse_event",
                 event.timestamp = %timestamp(),
                 conversation.id = %self.metadata.conversation_id,
+                session.tags = %self.metadata.tags,
                 app.version = %self.metadata.app_version,
                 auth_mode = self.metadata.auth_mode,
                 user.account_id = self.metadata.account_id,
@@ -276,6 +295,7 @@ impl OtelEventManager {
             event.kind = %"response.completed",
             event.timestamp = %timestamp(),
             conversation.id = %self.metadata.conversation_id,
+            session.tags = %self.metadata.tags,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -301,6 +321,7 @@ impl OtelEventManager {
             event.timestamp = %timestamp(),
             event.kind = %"response.completed",
             conversation.id = %self.metadata.conversation_id,
+            session.tags = %self.metadata.tags,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -336,6 +357,7 @@ impl OtelEventManager {
             event.name = "codex.user_prompt",
             event.timestamp = %timestamp(),
             conversation.id = %self.metadata.conversation_id,
+            session.tags = %self.metadata.tags,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -360,6 +382,7 @@ impl OtelEventManager {
             event.name = "codex.tool_decision",
             event.timestamp = %timestamp(),
             conversation.id = %self.metadata.conversation_id,
+            session.tags = %self.metadata.tags,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -388,6 +411,7 @@ impl OtelEventManager {
             event.name = "codex.sandbox_assessment",
             event.timestamp = %timestamp(),
             conversation.id = %self.metadata.conversation_id,
+            session.tags = %self.metadata.tags,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -408,6 +432,7 @@ impl OtelEventManager {
             event.name = "codex.sandbox_assessment_latency",
             event.timestamp = %timestamp(),
             conversation.id = %self.metadata.conversation_id,
+            session.tags = %self.metadata.tags,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -448,6 +473,7 @@ impl OtelEventManager {
             event.name = "codex.tool_result",
             event.timestamp = %timestamp(),
             conversation.id = %self.metadata.conversation_id,
+            session.tags = %self.metadata.tags,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id= self.metadata.account_id,
@@ -473,6 +499,7 @@ impl OtelEventManager {
             event.name = "codex.tool_result",
             event.timestamp = %timestamp(),
             conversation.id = %self.metadata.conversation_id,
+            session.tags = %self.metadata.tags,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -503,6 +530,7 @@ impl OtelEventManager {
             event.name = "codex.tool_result",
             event.timestamp = %timestamp(),
             conversation.id = %self.metadata.conversation_id,
+            session.tags = %self.metadata.tags,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -88,3 +88,30 @@ pub struct ApplyPatchApprovalRequestEvent {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub grant_root: Option<PathBuf>,
 }
+
+/// The user's decision on one hunk of a patch reviewed hunk by hunk.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct PatchHunkDecision {
+    pub path: PathBuf,
+    /// Index of the hunk in the file's change: the `@@` sections of an
+    /// update's unified diff, or `0` for an added or deleted file.
+    pub hunk: usize,
+    pub decision: HunkDecision,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type")]
+pub enum HunkDecision {
+    Accept,
+    /// Leave the hunk out; the comment is passed to the model.
+    Reject {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        comment: Option<String>,
+    },
+    /// Apply `diff` instead: the hunk's lines as rewritten by the user,
+    /// each still starting with ` `, `-`, or `+`.
+    Edit {
+        diff: String,
+    },
+}
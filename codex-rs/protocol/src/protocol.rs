@@ -3,6 +3,7 @@
 //! Uses a SQ (Submission Queue) / EQ (Event Queue) pattern to asynchronously communicate
 //! between user and agent.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fmt;
 use std::path::Path;
@@ -40,6 +41,8 @@ use ts_rs::TS;
 pub use crate::approvals::ApplyPatchApprovalRequestEvent;
 pub use crate::approvals::ElicitationAction;
 pub use crate::approvals::ExecApprovalRequestEvent;
+pub use crate::approvals::HunkDecision;
+pub use crate::approvals::PatchHunkDecision;
 pub use crate::approvals::SandboxCommandAssessment;
 pub use crate::approvals::SandboxRiskLevel;
 
@@ -157,6 +160,14 @@ pub enum Op {
         decision: ReviewDecision,
     },
 
+    /// Approve only some hunks of a code patch. Hunks without a decision
+    /// are rejected.
+    PatchHunkReview {
+        /// The id of the submission we are approving
+        id: String,
+        hunks: Vec<PatchHunkDecision>,
+    },
+
     /// Resolve an MCP elicitation request.
     ResolveElicitation {
         /// Name of the MCP server that issued the request.
@@ -194,6 +205,31 @@ pub enum Op {
     /// Request Codex to undo a turn (turn are stacked so it is the same effect as CMD + Z).
     Undo,
 
+    /// Save the conversation so far and a snapshot of the working tree as a
+    /// named checkpoint that `codex restore <name>` rolls back to.
+    Checkpoint { name: String },
+
+    /// Keep a file or directory in context for the rest of the session.
+    /// Relative paths are resolved against the session's working directory.
+    /// Replies with `EventMsg::PinnedFiles`.
+    Pin { path: PathBuf },
+
+    /// Stop keeping a pinned file or directory in context. Replies with
+    /// `EventMsg::PinnedFiles`.
+    Unpin { path: PathBuf },
+
+    /// Request the pinned files and the tokens each of them uses. Replies
+    /// with `EventMsg::PinnedFiles`.
+    ListPins,
+
+    /// Assemble the request a `UserTurn` with these items would send first,
+    /// without sending it or recording anything. Uses the current turn
+    /// context. Replies with `EventMsg::DryRunReport`.
+    DryRun {
+        items: Vec<UserInput>,
+        final_output_json_schema: Option<Value>,
+    },
+
     /// Request a code review from the agent.
     Review { review_request: ReviewRequest },
 
@@ -459,6 +495,10 @@ pub enum EventMsg {
     /// indicates the task continued but the user should still be notified.
     Warning(WarningEvent),
 
+    /// Codex switched to different credentials mid-session, e.g. after the
+    /// active API key ran out of quota.
+    AuthSwitched(AuthSwitchedEvent),
+
     /// Conversation history was compacted (either automatically or manually).
     ContextCompacted(ContextCompactedEvent),
 
@@ -539,6 +579,8 @@ pub enum EventMsg {
 
     UndoCompleted(UndoCompletedEvent),
 
+    CheckpointCreated(CheckpointCreatedEvent),
+
     /// Notification that a model stream experienced an error or disconnect
     /// and the system is handling it (e.g., retrying with backoff).
     StreamError(StreamErrorEvent),
@@ -561,6 +603,12 @@ pub enum EventMsg {
     /// List of custom prompts available to the agent.
     ListCustomPromptsResponse(ListCustomPromptsResponseEvent),
 
+    /// The files and directories pinned in context.
+    PinnedFiles(PinnedFilesEvent),
+
+    /// What the request of an `Op::DryRun` would contain.
+    DryRunReport(DryRunReportEvent),
+
     PlanUpdate(UpdatePlanArgs),
 
     TurnAborted(TurnAbortedEvent),
@@ -611,6 +659,8 @@ pub enum CodexErrorInfo {
     ResponseTooManyFailedAttempts {
         http_status_code: Option<u16>,
     },
+    /// A task limit such as `codex exec --max-turns` stopped the task.
+    RunLimitReached,
     Other,
 }
 
@@ -747,6 +797,46 @@ pub struct WarningEvent {
     pub message: String,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct AuthSwitchedEvent {
+    /// Redacted description of the credentials used before the switch.
+    pub from: String,
+    /// Redacted description of the credentials used from now on.
+    pub to: String,
+    pub reason: AuthSwitchReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthSwitchReason {
+    QuotaExceeded,
+    UsageLimitReached,
+    RateLimited,
+    /// The provider kept failing with server errors.
+    ProviderError,
+    /// A `fallback` entry higher in the chain is usable again.
+    LimitReset,
+    /// The ChatGPT access token expired and could not be refreshed.
+    RefreshFailed,
+    /// The provider refused the API key, e.g. because it was revoked.
+    CredentialsRejected,
+}
+
+impl AuthSwitchReason {
+    /// Short human-readable explanation, e.g. for a banner.
+    pub fn description(self) -> &'static str {
+        match self {
+            AuthSwitchReason::QuotaExceeded => "quota exceeded",
+            AuthSwitchReason::UsageLimitReached => "usage limit reached",
+            AuthSwitchReason::RateLimited => "rate limited",
+            AuthSwitchReason::ProviderError => "provider error",
+            AuthSwitchReason::LimitReset => "limit reset",
+            AuthSwitchReason::RefreshFailed => "token refresh failed",
+            AuthSwitchReason::CredentialsRejected => "credentials rejected",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ContextCompactedEvent;
 
@@ -758,9 +848,15 @@ pub struct TaskCompleteEvent {
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TaskStartedEvent {
     pub model_context_window: Option<i64>,
+    /// Credentials the turn's requests start out with, e.g. the active
+    /// `fallback` entry and its account, or the signed-in account. Later
+    /// switches within the turn are reported as `AuthSwitched`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub auth: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Default, JsonSchema, TS)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Default, JsonSchema, TS)]
 pub struct TokenUsage {
     #[ts(type = "number")]
     pub input_tokens: i64,
@@ -841,6 +937,25 @@ impl TokenUsageInfo {
 pub struct TokenCountEvent {
     pub info: Option<TokenUsageInfo>,
     pub rate_limits: Option<RateLimitSnapshot>,
+    /// Estimated cost so far; absent in rollouts recorded before costs were
+    /// tracked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub cost: Option<TokenCost>,
+}
+
+/// Estimated cost of model usage in US dollars, priced with the built-in
+/// price table or `model_prices` from `config.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct TokenCost {
+    /// Cost of the current turn so far.
+    pub turn_usd: f64,
+    /// Cost of the whole session, including any resumed history.
+    pub session_usd: f64,
+    /// Tokens used with models that have no known price; they are not
+    /// included in the costs above.
+    #[ts(type = "number")]
+    pub unpriced_tokens: i64,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
@@ -848,6 +963,11 @@ pub struct RateLimitSnapshot {
     pub primary: Option<RateLimitWindow>,
     pub secondary: Option<RateLimitWindow>,
     pub credits: Option<CreditsSnapshot>,
+    /// Request and token budgets of API-key providers; absent for ChatGPT
+    /// sign-in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub request_limits: Option<RequestRateLimits>,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
@@ -869,6 +989,23 @@ pub struct CreditsSnapshot {
     pub balance: Option<String>,
 }
 
+/// What is left of the provider's per-minute budgets, from the
+/// `x-ratelimit-*` response headers.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct RequestRateLimits {
+    #[ts(type = "number | null")]
+    pub remaining_requests: Option<i64>,
+    #[ts(type = "number | null")]
+    pub limit_requests: Option<i64>,
+    #[ts(type = "number | null")]
+    pub remaining_tokens: Option<i64>,
+    #[ts(type = "number | null")]
+    pub limit_tokens: Option<i64>,
+    /// Unix timestamp (seconds since epoch) when both budgets are full again.
+    #[ts(type = "number | null")]
+    pub resets_at: Option<i64>,
+}
+
 // Includes prompts, tools and space to call compact.
 const BASELINE_TOKENS: i64 = 12000;
 
@@ -1176,6 +1313,19 @@ pub struct SessionMeta {
     #[serde(default)]
     pub source: SessionSource,
     pub model_provider: Option<String>,
+    /// Key/value labels the session was started with, for spend attribution.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+    /// Set when the session was forked from another one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forked_from: Option<ForkedFrom>,
+}
+
+/// The session a fork branched from and how many of its turns it kept.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema, TS)]
+pub struct ForkedFrom {
+    pub id: ConversationId,
+    pub turn: usize,
 }
 
 impl Default for SessionMeta {
@@ -1189,6 +1339,8 @@ impl Default for SessionMeta {
             instructions: None,
             source: SessionSource::default(),
             model_provider: None,
+            tags: BTreeMap::new(),
+            forked_from: None,
         }
     }
 }
@@ -1424,6 +1576,9 @@ pub struct ExecCommandEndEvent {
     pub duration: Duration,
     /// Formatted output from the command, as seen by the model.
     pub formatted_output: String,
+    /// Whether the sandbox blocked the command.
+    #[serde(default)]
+    pub sandbox_denied: bool,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
@@ -1482,6 +1637,16 @@ pub struct UndoCompletedEvent {
     pub message: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct CheckpointCreatedEvent {
+    pub name: String,
+    /// User turns of the conversation the checkpoint covers.
+    pub turns: usize,
+    /// Whether the working tree was snapshotted; false outside a git
+    /// repository.
+    pub snapshot: bool,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct StreamErrorEvent {
     pub message: String,
@@ -1612,6 +1777,57 @@ pub struct ListCustomPromptsResponseEvent {
     pub custom_prompts: Vec<CustomPrompt>,
 }
 
+/// Response payload for `Op::Pin`, `Op::Unpin`, and `Op::ListPins`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct PinnedFilesEvent {
+    pub pins: Vec<PinnedFile>,
+}
+
+/// Response payload for `Op::DryRun`. Token counts are estimates from the
+/// size of each part, not a tokenizer count.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct DryRunReportEvent {
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub model_context_window: Option<i64>,
+    /// The sum of the three parts below.
+    pub estimated_prompt_tokens: u64,
+    /// The system instructions.
+    pub instructions_tokens: u64,
+    /// The definitions of the tools.
+    pub tools_tokens: u64,
+    /// The conversation so far, the context sent with every request, and
+    /// the new input.
+    pub input_tokens: u64,
+    /// The `AGENTS.md` files whose contents are sent.
+    pub instruction_files: Vec<PathBuf>,
+    pub pinned_files: Vec<PinnedFile>,
+    /// Local images attached to the input.
+    pub images: Vec<PathBuf>,
+    /// Names of the tools the model could call.
+    pub tools: Vec<String>,
+    pub approval_policy: AskForApproval,
+    pub sandbox_policy: SandboxPolicy,
+    /// Where commands could write under `sandbox_policy`; empty when it is
+    /// read-only or has no sandbox.
+    pub writable_roots: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct PinnedFile {
+    /// The path as it was pinned.
+    pub path: PathBuf,
+    /// Files the pin covers: 1 for a file, the files read for a directory.
+    pub files: usize,
+    /// Approximate tokens the pin adds to every request.
+    pub tokens: u64,
+    /// Why the pin is not sent, e.g. the file no longer exists.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct SessionConfiguredEvent {
     /// Name left as session_id instead of conversation_id for backwards compatibility.
@@ -0,0 +1,264 @@
+//! OAuth login through the device authorization grant (RFC 8628), for
+//! machines where a browser cannot reach Codex's local callback server,
+//! such as over SSH. Codex registers itself with the authorization server
+//! (RFC 7591), shows a code for the user to enter on any device, and polls
+//! until the user approves it.
+//! https://datatracker.ietf.org/doc/html/rfc8628
+
+use std::collections::HashMap;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use reqwest::Client;
+use reqwest::ClientBuilder;
+use reqwest::Url;
+use rmcp::transport::auth::OAuthTokenResponse;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::OAuthCredentialsStoreMode;
+use crate::StoredOAuthTokens;
+use crate::WrappedOAuthTokenResponse;
+use crate::auth_status::discovery_paths;
+use crate::oauth::compute_expires_at_millis;
+use crate::save_oauth_tokens;
+use crate::utils::apply_default_headers;
+use crate::utils::build_default_headers;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+/// Used when the server does not say how often to poll.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// RFC 8628 section 3.5: back off by five seconds on `slow_down`.
+const SLOW_DOWN_STEP: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Deserialize)]
+struct AuthorizationServerMetadata {
+    token_endpoint: String,
+    #[serde(default)]
+    device_authorization_endpoint: Option<String>,
+    #[serde(default)]
+    registration_endpoint: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClientRegistration {
+    client_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceAuthorization {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    #[serde(default)]
+    verification_uri_complete: Option<String>,
+    expires_in: u64,
+    #[serde(default)]
+    interval: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenError {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+#[derive(Debug)]
+enum Poll {
+    Granted(Box<OAuthTokenResponse>),
+    Pending,
+    SlowDown,
+}
+
+pub async fn perform_device_code_login(
+    server_name: &str,
+    server_url: &str,
+    store_mode: OAuthCredentialsStoreMode,
+    http_headers: Option<HashMap<String, String>>,
+    env_http_headers: Option<HashMap<String, String>>,
+    scopes: &[String],
+) -> Result<()> {
+    let default_headers = build_default_headers(http_headers, env_http_headers)?;
+    let client = apply_default_headers(
+        ClientBuilder::new().timeout(REQUEST_TIMEOUT),
+        &default_headers,
+    )
+    .build()?;
+
+    let metadata = discover_metadata(&client, server_url)
+        .await?
+        .ok_or_else(|| anyhow!("`{server_name}` does not advertise OAuth support"))?;
+    let Some(device_authorization_endpoint) = metadata.device_authorization_endpoint else {
+        bail!(
+            "`{server_name}` does not support device code login; run `codex mcp login {server_name}` without `--device-code`"
+        );
+    };
+    let Some(registration_endpoint) = metadata.registration_endpoint else {
+        bail!("`{server_name}` does not support dynamic client registration");
+    };
+
+    let registration: ClientRegistration = client
+        .post(registration_endpoint)
+        .json(&json!({
+            "client_name": "Codex",
+            "grant_types": [DEVICE_CODE_GRANT, "refresh_token"],
+            "token_endpoint_auth_method": "none",
+        }))
+        .send()
+        .await?
+        .error_for_status()
+        .context("client registration failed")?
+        .json()
+        .await?;
+    let client_id = registration.client_id;
+
+    let mut form = vec![("client_id", client_id.clone())];
+    if !scopes.is_empty() {
+        form.push(("scope", scopes.join(" ")));
+    }
+    let authorization: DeviceAuthorization = client
+        .post(device_authorization_endpoint)
+        .form(&form)
+        .send()
+        .await?
+        .error_for_status()
+        .context("device authorization request failed")?
+        .json()
+        .await?;
+
+    println!(
+        "Authorize `{server_name}` by visiting {} and entering the code: {}",
+        authorization.verification_uri, authorization.user_code
+    );
+    if let Some(complete) = &authorization.verification_uri_complete {
+        println!("(Or open {complete} to skip entering the code.)");
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(authorization.expires_in);
+    let mut interval = authorization
+        .interval
+        .map_or(DEFAULT_POLL_INTERVAL, Duration::from_secs);
+    let credentials = loop {
+        tokio::time::sleep(interval).await;
+        if Instant::now() >= deadline {
+            bail!("the code expired before it was approved; run the login again");
+        }
+        let response = client
+            .post(&metadata.token_endpoint)
+            .form(&[
+                ("grant_type", DEVICE_CODE_GRANT),
+                ("device_code", authorization.device_code.as_str()),
+                ("client_id", client_id.as_str()),
+            ])
+            .send()
+            .await?;
+        let success = response.status().is_success();
+        let body = response.text().await?;
+        match token_poll_result(success, &body)? {
+            Poll::Granted(credentials) => break *credentials,
+            Poll::Pending => {}
+            Poll::SlowDown => interval += SLOW_DOWN_STEP,
+        }
+    };
+
+    let expires_at = compute_expires_at_millis(&credentials);
+    let stored = StoredOAuthTokens {
+        server_name: server_name.to_string(),
+        url: server_url.to_string(),
+        client_id,
+        token_response: WrappedOAuthTokenResponse(credentials),
+        expires_at,
+    };
+    save_oauth_tokens(server_name, &stored, store_mode)
+}
+
+async fn discover_metadata(
+    client: &Client,
+    server_url: &str,
+) -> Result<Option<AuthorizationServerMetadata>> {
+    let base_url = Url::parse(server_url)?;
+    for candidate_path in discovery_paths(base_url.path()) {
+        let mut discovery_url = base_url.clone();
+        discovery_url.set_path(&candidate_path);
+        let Ok(response) = client.get(discovery_url).send().await else {
+            continue;
+        };
+        if !response.status().is_success() {
+            continue;
+        }
+        if let Ok(metadata) = response.json::<AuthorizationServerMetadata>().await {
+            return Ok(Some(metadata));
+        }
+    }
+    Ok(None)
+}
+
+/// Interprets a response of the token endpoint while the user has yet to
+/// approve the code (RFC 8628 section 3.5).
+fn token_poll_result(success: bool, body: &str) -> Result<Poll> {
+    if success {
+        let credentials: OAuthTokenResponse =
+            serde_json::from_str(body).context("invalid token response")?;
+        return Ok(Poll::Granted(Box::new(credentials)));
+    }
+    let error: TokenError =
+        serde_json::from_str(body).with_context(|| format!("token request failed: {body}"))?;
+    match error.error.as_str() {
+        "authorization_pending" => Ok(Poll::Pending),
+        "slow_down" => Ok(Poll::SlowDown),
+        "access_denied" => bail!("the login was denied"),
+        "expired_token" => bail!("the code expired before it was approved; run the login again"),
+        other => match error.error_description {
+            Some(description) => bail!("token request failed: {other}: {description}"),
+            None => bail!("token request failed: {other}"),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oauth2::TokenResponse;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn token_responses_are_classified() {
+        assert!(matches!(
+            token_poll_result(false, r#"{"error":"authorization_pending"}"#),
+            Ok(Poll::Pending)
+        ));
+        assert!(matches!(
+            token_poll_result(false, r#"{"error":"slow_down"}"#),
+            Ok(Poll::SlowDown)
+        ));
+        let err = token_poll_result(
+            false,
+            r#"{"error":"invalid_client","error_description":"unknown client"}"#,
+        )
+        .expect_err("other errors end the login");
+        assert_eq!(
+            err.to_string(),
+            "token request failed: invalid_client: unknown client"
+        );
+
+        let Ok(Poll::Granted(credentials)) = token_poll_result(
+            true,
+            r#"{"access_token":"abc","token_type":"Bearer","expires_in":3600,"refresh_token":"def"}"#,
+        ) else {
+            panic!("expected a token");
+        };
+        assert_eq!(credentials.access_token().secret(), "abc");
+        assert_eq!(
+            credentials
+                .refresh_token()
+                .map(|token| token.secret().as_str()),
+            Some("def")
+        );
+    }
+}
@@ -1,15 +1,18 @@
 mod auth_status;
+mod device_code_login;
 mod find_codex_home;
 mod logging_client_handler;
 mod oauth;
 mod perform_oauth_login;
 mod program_resolver;
 mod rmcp_client;
+mod sse_transport;
 mod utils;
 
 pub use auth_status::determine_streamable_http_auth_status;
 pub use auth_status::supports_oauth_login;
 pub use codex_protocol::protocol::McpAuthStatus;
+pub use device_code_login::perform_device_code_login;
 pub use oauth::OAuthCredentialsStoreMode;
 pub use oauth::StoredOAuthTokens;
 pub use oauth::WrappedOAuthTokenResponse;
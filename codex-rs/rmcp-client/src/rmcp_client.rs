@@ -9,6 +9,7 @@ use std::time::Duration;
 use anyhow::Result;
 use anyhow::anyhow;
 use futures::FutureExt;
+use futures::TryFutureExt;
 use futures::future::BoxFuture;
 use mcp_types::CallToolRequestParams;
 use mcp_types::CallToolResult;
@@ -23,7 +24,9 @@ use mcp_types::ListToolsResult;
 use mcp_types::ReadResourceRequestParams;
 use mcp_types::ReadResourceResult;
 use mcp_types::RequestId;
+use reqwest::header::AUTHORIZATION;
 use reqwest::header::HeaderMap;
+use reqwest::header::HeaderValue;
 use rmcp::model::CallToolRequestParam;
 use rmcp::model::ClientNotification;
 use rmcp::model::CreateElicitationRequestParam;
@@ -55,6 +58,7 @@ use crate::oauth::OAuthCredentialsStoreMode;
 use crate::oauth::OAuthPersistor;
 use crate::oauth::StoredOAuthTokens;
 use crate::program_resolver;
+use crate::sse_transport;
 use crate::utils::apply_default_headers;
 use crate::utils::build_default_headers;
 use crate::utils::convert_call_tool_result;
@@ -72,6 +76,10 @@ enum PendingTransport {
         transport: StreamableHttpClientTransport<AuthClient<reqwest::Client>>,
         oauth_persistor: OAuthPersistor,
     },
+    Sse {
+        client: reqwest::Client,
+        url: String,
+    },
 }
 
 enum ClientState {
@@ -209,6 +217,33 @@ impl RmcpClient {
         })
     }
 
+    /// Client for a server speaking the HTTP+SSE transport. The event stream
+    /// is opened during [`Self::initialize`], so it counts against the
+    /// startup timeout.
+    pub async fn new_sse_client(
+        url: &str,
+        bearer_token: Option<String>,
+        http_headers: Option<HashMap<String, String>>,
+        env_http_headers: Option<HashMap<String, String>>,
+    ) -> Result<Self> {
+        let mut default_headers = build_default_headers(http_headers, env_http_headers)?;
+        if let Some(bearer_token) = bearer_token {
+            default_headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {bearer_token}"))?,
+            );
+        }
+        let client = apply_default_headers(reqwest::Client::builder(), &default_headers).build()?;
+        Ok(Self {
+            state: Mutex::new(ClientState::Connecting {
+                transport: Some(PendingTransport::Sse {
+                    client,
+                    url: url.to_string(),
+                }),
+            }),
+        })
+    }
+
     /// Perform the initialization handshake with the MCP server.
     /// https://modelcontextprotocol.io/specification/2025-06-18/basic/lifecycle#initialization
     pub async fn initialize(
@@ -225,20 +260,37 @@ impl RmcpClient {
             match &mut *guard {
                 ClientState::Connecting { transport } => match transport.take() {
                     Some(PendingTransport::ChildProcess(transport)) => (
-                        service::serve_client(client_handler.clone(), transport).boxed(),
+                        service::serve_client(client_handler.clone(), transport)
+                            .map_err(anyhow::Error::from)
+                            .boxed(),
                         None,
                     ),
                     Some(PendingTransport::StreamableHttp { transport }) => (
-                        service::serve_client(client_handler.clone(), transport).boxed(),
+                        service::serve_client(client_handler.clone(), transport)
+                            .map_err(anyhow::Error::from)
+                            .boxed(),
                         None,
                     ),
                     Some(PendingTransport::StreamableHttpWithOAuth {
                         transport,
                         oauth_persistor,
                     }) => (
-                        service::serve_client(client_handler.clone(), transport).boxed(),
+                        service::serve_client(client_handler.clone(), transport)
+                            .map_err(anyhow::Error::from)
+                            .boxed(),
                         Some(oauth_persistor),
                     ),
+                    Some(PendingTransport::Sse { client, url }) => {
+                        let client_handler = client_handler.clone();
+                        (
+                            async move {
+                                let transport = sse_transport::connect(client, &url).await?;
+                                anyhow::Ok(service::serve_client(client_handler, transport).await?)
+                            }
+                            .boxed(),
+                            None,
+                        )
+                    }
                     None => return Err(anyhow!("client already initializing")),
                 },
                 ClientState::Ready { .. } => return Err(anyhow!("client already initialized")),
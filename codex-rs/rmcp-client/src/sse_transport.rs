@@ -0,0 +1,227 @@
+//! Client side of the HTTP+SSE transport of the 2024-11-05 protocol
+//! revision, which many hosted MCP servers still speak. The server sends
+//! its messages as `message` events on an event stream opened with a GET;
+//! the stream's first event, `endpoint`, names the URL the client POSTs its
+//! own messages to.
+//!
+//! `rmcp` no longer ships this transport, so [`connect`] bridges it onto a
+//! pair of in-memory pipes carrying newline-delimited JSON, the framing
+//! `rmcp` uses for stdio servers.
+
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use futures::Stream;
+use futures::StreamExt;
+use reqwest::Client;
+use reqwest::Response;
+use reqwest::Url;
+use reqwest::header::ACCEPT;
+use reqwest::header::CONTENT_TYPE;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::io::DuplexStream;
+use tracing::warn;
+
+const PIPE_CAPACITY: usize = 64 * 1024;
+
+/// Opens the event stream at `url` and waits for the server to announce its
+/// endpoint. Returns the reading and writing ends to serve the client on.
+pub(crate) async fn connect(client: Client, url: &str) -> Result<(DuplexStream, DuplexStream)> {
+    let url = Url::parse(url)?;
+    let response = client
+        .get(url.clone())
+        .header(ACCEPT, "text/event-stream")
+        .send()
+        .await?
+        .error_for_status()?;
+    let mut events = EventStream::new(Box::pin(response.bytes_stream()));
+    let endpoint = loop {
+        match events.next().await? {
+            Some(event) if event.name == "endpoint" => break url.join(event.data.trim())?,
+            Some(_) => {}
+            None => return Err(anyhow!("event stream closed before announcing an endpoint")),
+        }
+    };
+
+    let (incoming, mut server_messages) = tokio::io::duplex(PIPE_CAPACITY);
+    let (outgoing, client_messages) = tokio::io::duplex(PIPE_CAPACITY);
+
+    tokio::spawn(async move {
+        loop {
+            match events.next().await {
+                Ok(Some(event)) if event.name == "message" => {
+                    // Each message must stay on one line; newlines inside
+                    // JSON are only whitespace.
+                    let mut line = event.data.replace(['\r', '\n'], " ");
+                    line.push('\n');
+                    if server_messages.write_all(line.as_bytes()).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(Some(_)) => {}
+                Ok(None) => break,
+                Err(error) => {
+                    warn!("MCP event stream failed: {error}");
+                    break;
+                }
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(client_messages).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let result = client
+                .post(endpoint.clone())
+                .header(CONTENT_TYPE, "application/json")
+                .body(line)
+                .send()
+                .await
+                .and_then(Response::error_for_status);
+            if let Err(error) = result {
+                warn!("failed to send message to MCP server at {endpoint}: {error}");
+            }
+        }
+    });
+
+    Ok((incoming, outgoing))
+}
+
+#[derive(Debug, PartialEq)]
+struct Event {
+    name: String,
+    data: String,
+}
+
+struct EventStream<S> {
+    bytes: S,
+    parser: EventParser,
+    pending: VecDeque<Event>,
+}
+
+impl<S, B> EventStream<S>
+where
+    S: Stream<Item = reqwest::Result<B>> + Unpin,
+    B: AsRef<[u8]>,
+{
+    fn new(bytes: S) -> Self {
+        Self {
+            bytes,
+            parser: EventParser::default(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    async fn next(&mut self) -> Result<Option<Event>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(Some(event));
+            }
+            match self.bytes.next().await {
+                Some(chunk) => self.pending.extend(self.parser.push(chunk?.as_ref())),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Incremental parser for `text/event-stream` bodies.
+/// https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation
+#[derive(Default)]
+struct EventParser {
+    line: Vec<u8>,
+    name: Option<String>,
+    data: String,
+}
+
+impl EventParser {
+    /// Feeds the next chunk of the body and returns the events it completes.
+    fn push(&mut self, bytes: &[u8]) -> Vec<Event> {
+        let mut events = Vec::new();
+        for &byte in bytes {
+            if byte != b'\n' {
+                self.line.push(byte);
+                continue;
+            }
+            let mut line = std::mem::take(&mut self.line);
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            let line = String::from_utf8_lossy(&line);
+            if line.is_empty() {
+                if let Some(event) = self.dispatch() {
+                    events.push(event);
+                }
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line.as_ref(), ""),
+            };
+            match field {
+                "event" => self.name = Some(value.to_string()),
+                "data" => {
+                    self.data.push_str(value);
+                    self.data.push('\n');
+                }
+                _ => {}
+            }
+        }
+        events
+    }
+
+    fn dispatch(&mut self) -> Option<Event> {
+        let name = self.name.take();
+        let mut data = std::mem::take(&mut self.data);
+        if data.is_empty() {
+            return None;
+        }
+        data.pop();
+        Some(Event {
+            name: name.unwrap_or_else(|| "message".to_string()),
+            data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn event(name: &str, data: &str) -> Event {
+        Event {
+            name: name.to_string(),
+            data: data.to_string(),
+        }
+    }
+
+    #[test]
+    fn events_are_parsed_across_chunks() {
+        let mut parser = EventParser::default();
+        assert_eq!(
+            parser.push(b"event: endpoint\r\ndata: /messages?session=1\r\n\r\n: ping\n\neve"),
+            vec![event("endpoint", "/messages?session=1")]
+        );
+        assert_eq!(
+            parser.push(b"nt: message\ndata: {\"jsonrpc\":\ndata: \"2.0\"}\n"),
+            Vec::new()
+        );
+        assert_eq!(
+            parser.push(b"\ndata:{}\n\n"),
+            vec![
+                event("message", "{\"jsonrpc\":\n\"2.0\"}"),
+                event("message", "{}"),
+            ]
+        );
+    }
+}
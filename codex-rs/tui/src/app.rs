@@ -69,6 +69,11 @@ pub struct AppExitInfo {
     pub token_usage: TokenUsage,
     pub conversation_id: Option<ConversationId>,
     pub update_action: Option<UpdateAction>,
+    /// Version `update_action` is expected to install, when known.
+    pub update_version: Option<String>,
+    /// Termination signal that ended the session; the caller should exit with `128 + signal`
+    /// once the terminal has been restored.
+    pub exit_signal: Option<i32>,
 }
 
 fn session_summary(
@@ -187,6 +192,8 @@ async fn handle_model_migration_prompt_if_needed(
                     token_usage: TokenUsage::default(),
                     conversation_id: None,
                     update_action: None,
+                    update_version: None,
+                    exit_signal: None,
                 });
             }
         }
@@ -244,6 +251,17 @@ impl App {
         }
     }
 
+    /// Shut the session down after a termination signal so the rollout is flushed and the
+    /// session hooks run. Returns whether the loop should keep running until `ShutdownComplete`.
+    fn shutdown_for_signal(&mut self) -> bool {
+        if self.chat_widget.conversation_id().is_none() {
+            return false;
+        }
+        self.suppress_shutdown_complete = false;
+        self.chat_widget.submit_op(Op::Shutdown);
+        true
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn run(
         tui: &mut tui::Tui,
@@ -281,6 +299,8 @@ impl App {
                         token_usage: TokenUsage::default(),
                         conversation_id: None,
                         update_action: None,
+                        update_version: None,
+                        exit_signal: None,
                     });
                 }
                 SkillErrorPromptOutcome::Continue => {}
@@ -340,6 +360,25 @@ impl App {
                     resumed.session_configured,
                 )
             }
+            ResumeSelection::Fork { path, turns } => {
+                let forked = conversation_manager
+                    .fork_conversation_after_turn(turns, config.clone(), path.clone())
+                    .await
+                    .wrap_err_with(|| format!("Failed to fork session from {}", path.display()))?;
+                let init = crate::chatwidget::ChatWidgetInit {
+                    config: config.clone(),
+                    frame_requester: tui.frame_requester(),
+                    app_event_tx: app_event_tx.clone(),
+                    initial_prompt: initial_prompt.clone(),
+                    initial_images: initial_images.clone(),
+                    enhanced_keys_supported,
+                    auth_manager: auth_manager.clone(),
+                    feedback: feedback.clone(),
+                    skills: skills.clone(),
+                    is_first_run,
+                };
+                ChatWidget::new_from_existing(init, forked.conversation, forked.session_configured)
+            }
         };
 
         chat_widget.maybe_prompt_windows_sandbox_enable();
@@ -411,6 +450,7 @@ impl App {
 
         tui.frame_requester().schedule_frame();
 
+        let mut exit_signal = None;
         while select! {
             Some(event) = app_event_rx.recv() => {
                 app.handle_event(tui, event).await?
@@ -418,12 +458,18 @@ impl App {
             Some(event) = tui_events.next() => {
                 app.handle_tui_event(tui, event).await?
             }
+            signal = tui::termination_requested() => {
+                exit_signal = Some(signal);
+                app.shutdown_for_signal()
+            }
         } {}
         tui.terminal.clear()?;
         Ok(AppExitInfo {
             token_usage: app.token_usage(),
             conversation_id: app.chat_widget.conversation_id(),
             update_action: app.pending_update_action,
+            update_version: None,
+            exit_signal,
         })
     }
 
@@ -507,6 +553,7 @@ impl App {
                     &self.config.codex_home,
                     &self.config.model_provider_id,
                     false,
+                    self.config.tui_timestamp_format,
                 )
                 .await?
                 {
@@ -564,7 +611,9 @@ impl App {
                             }
                         }
                     }
-                    ResumeSelection::Exit | ResumeSelection::StartFresh => {}
+                    ResumeSelection::Exit
+                    | ResumeSelection::StartFresh
+                    | ResumeSelection::Fork { .. } => {}
                 }
 
                 // Leaving alt-screen may blank the inline viewport; force a redraw either way.
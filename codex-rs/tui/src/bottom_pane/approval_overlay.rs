@@ -5,6 +5,7 @@ use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::bottom_pane::BottomPaneView;
 use crate::bottom_pane::CancellationEvent;
+use crate::bottom_pane::hunk_review::HunkReview;
 use crate::bottom_pane::list_selection_view::ListSelectionView;
 use crate::bottom_pane::list_selection_view::SelectionItem;
 use crate::bottom_pane::list_selection_view::SelectionViewParams;
@@ -19,6 +20,7 @@ use crate::render::renderable::Renderable;
 use codex_core::protocol::ElicitationAction;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::Op;
+use codex_core::protocol::PatchHunkDecision;
 use codex_core::protocol::ReviewDecision;
 use codex_core::protocol::SandboxCommandAssessment;
 use codex_core::protocol::SandboxRiskLevel;
@@ -65,6 +67,8 @@ pub(crate) struct ApprovalOverlay {
     app_event_tx: AppEventSender,
     list: ListSelectionView,
     options: Vec<ApprovalOption>,
+    /// Set while the current patch is reviewed hunk by hunk.
+    hunk_review: Option<HunkReview>,
     current_complete: bool,
     done: bool,
 }
@@ -78,6 +82,7 @@ impl ApprovalOverlay {
             app_event_tx: app_event_tx.clone(),
             list: ListSelectionView::new(Default::default(), app_event_tx),
             options: Vec::new(),
+            hunk_review: None,
             current_complete: false,
             done: false,
         };
@@ -93,6 +98,7 @@ impl ApprovalOverlay {
         self.current_request = Some(request.clone());
         let ApprovalRequestState { variant, header } = ApprovalRequestState::from(request);
         self.current_variant = Some(variant.clone());
+        self.hunk_review = None;
         self.current_complete = false;
         let (options, params) = Self::build_options(variant, header);
         self.options = options;
@@ -159,6 +165,10 @@ impl ApprovalOverlay {
         let Some(option) = self.options.get(actual_idx) else {
             return;
         };
+        if matches!(option.decision, ApprovalDecision::ReviewHunks) {
+            self.start_hunk_review();
+            return;
+        }
         if let Some(variant) = self.current_variant.as_ref() {
             match (&variant, &option.decision) {
                 (ApprovalVariant::Exec { id, command }, ApprovalDecision::Review(decision)) => {
@@ -200,6 +210,38 @@ impl ApprovalOverlay {
         }));
     }
 
+    fn start_hunk_review(&mut self) {
+        let Some(ApprovalRequest::ApplyPatch {
+            id, cwd, changes, ..
+        }) = self.current_request.as_ref()
+        else {
+            return;
+        };
+        let review = HunkReview::new(changes, cwd);
+        if review.is_empty() {
+            // Nothing to pick from, e.g. a rename without edits.
+            let id = id.clone();
+            self.handle_patch_decision(&id, ReviewDecision::Approved);
+            self.current_complete = true;
+            self.advance_queue();
+        } else {
+            self.hunk_review = Some(review);
+        }
+    }
+
+    fn finish_hunk_review(&mut self, hunks: Vec<PatchHunkDecision>) {
+        self.hunk_review = None;
+        if let Some(ApprovalVariant::ApplyPatch { id }) = self.current_variant.as_ref() {
+            self.app_event_tx
+                .send(AppEvent::CodexOp(Op::PatchHunkReview {
+                    id: id.clone(),
+                    hunks,
+                }));
+        }
+        self.current_complete = true;
+        self.advance_queue();
+    }
+
     fn handle_elicitation_decision(
         &self,
         server_name: &str,
@@ -256,6 +298,12 @@ impl ApprovalOverlay {
 
 impl BottomPaneView for ApprovalOverlay {
     fn handle_key_event(&mut self, key_event: KeyEvent) {
+        if let Some(review) = self.hunk_review.as_mut() {
+            if let Some(hunks) = review.handle_key_event(key_event) {
+                self.finish_hunk_review(hunks);
+            }
+            return;
+        }
         if self.try_handle_shortcut(&key_event) {
             return;
         }
@@ -269,6 +317,12 @@ impl BottomPaneView for ApprovalOverlay {
         if self.done {
             return CancellationEvent::Handled;
         }
+        if let Some(review) = self.hunk_review.as_mut()
+            && review.close_input()
+        {
+            return CancellationEvent::Handled;
+        }
+        self.hunk_review = None;
         if !self.current_complete
             && let Some(variant) = self.current_variant.as_ref()
         {
@@ -300,6 +354,12 @@ impl BottomPaneView for ApprovalOverlay {
         self.done
     }
 
+    fn handle_paste(&mut self, pasted: String) -> bool {
+        self.hunk_review
+            .as_mut()
+            .is_some_and(|review| review.handle_paste(&pasted))
+    }
+
     fn try_consume_approval_request(
         &mut self,
         request: ApprovalRequest,
@@ -311,15 +371,24 @@ impl BottomPaneView for ApprovalOverlay {
 
 impl Renderable for ApprovalOverlay {
     fn desired_height(&self, width: u16) -> u16 {
-        self.list.desired_height(width)
+        match self.hunk_review.as_ref() {
+            Some(review) => review.desired_height(width),
+            None => self.list.desired_height(width),
+        }
     }
 
     fn render(&self, area: Rect, buf: &mut Buffer) {
-        self.list.render(area, buf);
+        match self.hunk_review.as_ref() {
+            Some(review) => review.render(area, buf),
+            None => self.list.render(area, buf),
+        }
     }
 
     fn cursor_pos(&self, area: Rect) -> Option<(u16, u16)> {
-        self.list.cursor_pos(area)
+        match self.hunk_review.as_ref() {
+            Some(review) => review.cursor_pos(area),
+            None => self.list.cursor_pos(area),
+        }
     }
 }
 
@@ -444,6 +513,8 @@ enum ApprovalVariant {
 #[derive(Clone)]
 enum ApprovalDecision {
     Review(ReviewDecision),
+    /// Decide on each hunk of the patch separately.
+    ReviewHunks,
     McpElicitation(ElicitationAction),
 }
 
@@ -494,6 +565,12 @@ fn patch_options() -> Vec<ApprovalOption> {
             display_shortcut: None,
             additional_shortcuts: vec![key_hint::plain(KeyCode::Char('y'))],
         },
+        ApprovalOption {
+            label: "Review each hunk".to_string(),
+            decision: ApprovalDecision::ReviewHunks,
+            display_shortcut: None,
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('r'))],
+        },
         ApprovalOption {
             label: "No, and tell Codex what to do differently".to_string(),
             decision: ApprovalDecision::Review(ReviewDecision::Abort),
@@ -571,6 +648,48 @@ mod tests {
         assert!(saw_op, "expected approval decision to emit an op");
     }
 
+    #[test]
+    fn hunk_review_sends_per_hunk_decisions() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let path = PathBuf::from("/repo/README.md");
+        let changes = HashMap::from([(
+            path.clone(),
+            FileChange::Add {
+                content: "hello\n".to_string(),
+            },
+        )]);
+        let request = ApprovalRequest::ApplyPatch {
+            id: "sub-1".to_string(),
+            reason: None,
+            cwd: PathBuf::from("/repo"),
+            changes,
+        };
+        let mut view = ApprovalOverlay::new(request, tx);
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE));
+        assert!(!view.is_complete(), "review starts with the first hunk");
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE));
+        assert!(view.is_complete());
+
+        let mut review = None;
+        while let Ok(ev) = rx.try_recv() {
+            if let AppEvent::CodexOp(Op::PatchHunkReview { id, hunks }) = ev {
+                review = Some((id, hunks));
+            }
+        }
+        assert_eq!(
+            review,
+            Some((
+                "sub-1".to_string(),
+                vec![PatchHunkDecision {
+                    path,
+                    hunk: 0,
+                    decision: codex_core::protocol::HunkDecision::Reject { comment: None },
+                }]
+            ))
+        );
+    }
+
     #[test]
     fn header_includes_command_snippet() {
         let (tx, _rx) = unbounded_channel::<AppEvent>();
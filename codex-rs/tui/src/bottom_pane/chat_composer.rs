@@ -22,6 +22,7 @@ use super::command_popup::CommandPopup;
 use super::file_search_popup::FileSearchPopup;
 use super::footer::FooterMode;
 use super::footer::FooterProps;
+use super::footer::FooterQuota;
 use super::footer::esc_hint_mode;
 use super::footer::footer_height;
 use super::footer::render_footer;
@@ -117,6 +118,8 @@ pub(crate) struct ChatComposer {
     footer_hint_override: Option<Vec<(String, String)>>,
     context_window_percent: Option<i64>,
     context_window_used_tokens: Option<i64>,
+    session_cost_usd: Option<f64>,
+    quota: Option<FooterQuota>,
     skills: Option<Vec<SkillMetadata>>,
     dismissed_skill_popup_token: Option<String>,
 }
@@ -165,6 +168,8 @@ impl ChatComposer {
             footer_hint_override: None,
             context_window_percent: None,
             context_window_used_tokens: None,
+            session_cost_usd: None,
+            quota: None,
             skills: None,
             dismissed_skill_popup_token: None,
         };
@@ -1529,6 +1534,8 @@ impl ChatComposer {
             is_task_running: self.is_task_running,
             context_window_percent: self.context_window_percent,
             context_window_used_tokens: self.context_window_used_tokens,
+            session_cost_usd: self.session_cost_usd,
+            quota: self.quota,
         }
     }
 
@@ -1725,6 +1732,18 @@ impl ChatComposer {
         self.context_window_used_tokens = used_tokens;
     }
 
+    pub(crate) fn set_session_cost(&mut self, usd: Option<f64>) {
+        self.session_cost_usd = usd;
+    }
+
+    pub(crate) fn set_quota(&mut self, quota: Option<FooterQuota>) {
+        self.quota = quota;
+    }
+
+    pub(crate) fn quota(&self) -> Option<FooterQuota> {
+        self.quota
+    }
+
     pub(crate) fn set_esc_backtrack_hint(&mut self, show: bool) {
         self.esc_backtrack_hint = show;
         if show {
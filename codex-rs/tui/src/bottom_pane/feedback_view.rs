@@ -19,6 +19,7 @@ use crate::app_event::FeedbackCategory;
 use crate::app_event_sender::AppEventSender;
 use crate::history_cell;
 use crate::render::renderable::Renderable;
+use codex_core::default_client::check_non_provider_network;
 use codex_core::protocol::SessionSource;
 
 use super::CancellationEvent;
@@ -77,17 +78,21 @@ impl FeedbackNoteView {
 
         let mut thread_id = self.snapshot.thread_id.clone();
 
-        let result = self.snapshot.upload_feedback(
-            classification,
-            reason_opt,
-            self.include_logs,
-            if self.include_logs {
-                rollout_path_ref
-            } else {
-                None
-            },
-            Some(SessionSource::Cli),
-        );
+        let result = check_non_provider_network("Feedback upload")
+            .map_err(anyhow::Error::from)
+            .and_then(|()| {
+                self.snapshot.upload_feedback(
+                    classification,
+                    reason_opt,
+                    self.include_logs,
+                    if self.include_logs {
+                        rollout_path_ref
+                    } else {
+                        None
+                    },
+                    Some(SessionSource::Cli),
+                )
+            });
 
         match result {
             Ok(()) => {
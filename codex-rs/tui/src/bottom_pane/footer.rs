@@ -1,8 +1,10 @@
 use crate::key_hint;
 use crate::key_hint::KeyBinding;
 use crate::render::line_utils::prefix_lines;
+use crate::status::format_countdown;
 use crate::status::format_tokens_compact;
 use crate::ui_consts::FOOTER_INDENT_COLS;
+use codex_core::protocol::RateLimitSnapshot;
 use crossterm::event::KeyCode;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
@@ -11,6 +13,7 @@ use ratatui::text::Line;
 use ratatui::text::Span;
 use ratatui::widgets::Paragraph;
 use ratatui::widgets::Widget;
+use std::time::Duration;
 
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct FooterProps {
@@ -20,6 +23,107 @@ pub(crate) struct FooterProps {
     pub(crate) is_task_running: bool,
     pub(crate) context_window_percent: Option<i64>,
     pub(crate) context_window_used_tokens: Option<i64>,
+    /// Estimated cost of the session so far, in US dollars.
+    pub(crate) session_cost_usd: Option<f64>,
+    pub(crate) quota: Option<FooterQuota>,
+}
+
+/// Usage-limit headroom from the most recent response, shown after the
+/// context indicator so a limit is visible before it is reached.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct FooterQuota {
+    /// Share left of the most used usage-limit window.
+    pub(crate) remaining_percent: Option<f64>,
+    /// Requests and tokens left in an API-key provider's current window.
+    pub(crate) remaining_requests: Option<i64>,
+    pub(crate) remaining_tokens: Option<i64>,
+    /// When the limit shown resets (Unix seconds).
+    pub(crate) resets_at: Option<i64>,
+    /// A limit is used up; only the countdown to `resets_at` is shown.
+    pub(crate) exhausted: bool,
+}
+
+impl FooterQuota {
+    pub(crate) fn from_snapshot(snapshot: &RateLimitSnapshot) -> Option<Self> {
+        let windows: Vec<_> = [snapshot.primary.as_ref(), snapshot.secondary.as_ref()]
+            .into_iter()
+            .flatten()
+            .collect();
+        // When several limits are used up, the one that resets last decides
+        // when requests work again.
+        let exhausted_until = windows
+            .iter()
+            .filter(|window| window.used_percent >= 100.0)
+            .map(|window| window.resets_at)
+            .chain(snapshot.request_limits.iter().filter_map(|limits| {
+                (limits.remaining_requests == Some(0) || limits.remaining_tokens == Some(0))
+                    .then_some(limits.resets_at)
+            }))
+            .max();
+        if let Some(resets_at) = exhausted_until {
+            return Some(Self {
+                resets_at,
+                exhausted: true,
+                ..Default::default()
+            });
+        }
+
+        let most_used = windows
+            .iter()
+            .max_by(|a, b| a.used_percent.total_cmp(&b.used_percent));
+        let requests = snapshot.request_limits.as_ref();
+        if most_used.is_none() && requests.is_none() {
+            return None;
+        }
+        Some(Self {
+            remaining_percent: most_used.map(|window| (100.0 - window.used_percent).max(0.0)),
+            remaining_requests: requests.and_then(|limits| limits.remaining_requests),
+            remaining_tokens: requests.and_then(|limits| limits.remaining_tokens),
+            resets_at: most_used
+                .and_then(|window| window.resets_at)
+                .or_else(|| requests.and_then(|limits| limits.resets_at)),
+            exhausted: false,
+        })
+    }
+
+    /// How soon the footer needs redrawing to keep the countdown current.
+    pub(crate) fn refresh_in(&self, now: i64) -> Option<Duration> {
+        let left = self.resets_at? - now;
+        if left <= 0 {
+            None
+        } else if left <= 60 {
+            Some(Duration::from_secs(1))
+        } else {
+            Some(Duration::from_secs(30))
+        }
+    }
+
+    /// E.g. "38% quota left, resets in 2h 10m", "4.99K requests, 612K
+    /// tokens left, resets in <1m", or "limit reached, resets in 41m".
+    fn text(&self, now: i64) -> String {
+        let mut text = if self.exhausted {
+            "limit reached".to_string()
+        } else {
+            let mut left = Vec::new();
+            if let Some(percent) = self.remaining_percent {
+                left.push(format!("{percent:.0}% quota"));
+            }
+            if let Some(requests) = self.remaining_requests {
+                left.push(format!("{} requests", format_tokens_compact(requests)));
+            }
+            if let Some(tokens) = self.remaining_tokens {
+                left.push(format!("{} tokens", format_tokens_compact(tokens)));
+            }
+            format!("{} left", left.join(", "))
+        };
+        if let Some(resets_at) = self.resets_at.filter(|resets_at| *resets_at > now) {
+            text.push_str(", resets in ");
+            text.push_str(&format_countdown(chrono::Duration::seconds(
+                resets_at - now,
+            )));
+        }
+        text
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -86,6 +190,8 @@ fn footer_lines(props: FooterProps) -> Vec<Line<'static>> {
             let mut line = context_window_line(
                 props.context_window_percent,
                 props.context_window_used_tokens,
+                props.session_cost_usd,
+                props.quota,
             );
             line.push_span(" · ".dim());
             line.extend(vec![
@@ -102,6 +208,8 @@ fn footer_lines(props: FooterProps) -> Vec<Line<'static>> {
         FooterMode::ContextOnly => vec![context_window_line(
             props.context_window_percent,
             props.context_window_used_tokens,
+            props.session_cost_usd,
+            props.quota,
         )],
     }
 }
@@ -229,18 +337,46 @@ fn build_columns(entries: Vec<Line<'static>>) -> Vec<Line<'static>> {
         .collect()
 }
 
-fn context_window_line(percent: Option<i64>, used_tokens: Option<i64>) -> Line<'static> {
-    if let Some(percent) = percent {
+fn context_window_line(
+    percent: Option<i64>,
+    used_tokens: Option<i64>,
+    session_cost_usd: Option<f64>,
+    quota: Option<FooterQuota>,
+) -> Line<'static> {
+    let context = if let Some(percent) = percent {
         let percent = percent.clamp(0, 100);
-        return Line::from(vec![Span::from(format!("{percent}% context left")).dim()]);
-    }
-
-    if let Some(tokens) = used_tokens {
+        format!("{percent}% context left")
+    } else if let Some(tokens) = used_tokens {
         let used_fmt = format_tokens_compact(tokens);
-        return Line::from(vec![Span::from(format!("{used_fmt} used")).dim()]);
+        format!("{used_fmt} used")
+    } else {
+        "100% context left".to_string()
+    };
+
+    let mut line = Line::from(vec![Span::from(context).dim()]);
+    if let Some(usd) = session_cost_usd {
+        line.push_span(" · ".dim());
+        line.push_span(Span::from(format_cost_usd(usd)).dim());
+    }
+    if let Some(quota) = quota {
+        let text = quota.text(chrono::Utc::now().timestamp());
+        line.push_span(" · ".dim());
+        line.push_span(if quota.exhausted {
+            Span::from(text).red()
+        } else {
+            Span::from(text).dim()
+        });
     }
+    line
+}
 
-    Line::from(vec![Span::from("100% context left").dim()])
+/// "$0.42", or "<$0.01" for costs that would round to zero.
+fn format_cost_usd(usd: f64) -> String {
+    if usd < 0.01 {
+        "<$0.01".to_string()
+    } else {
+        format!("${usd:.2}")
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -418,6 +554,8 @@ mod tests {
                 is_task_running: false,
                 context_window_percent: None,
                 context_window_used_tokens: None,
+                session_cost_usd: None,
+                quota: None,
             },
         );
 
@@ -430,6 +568,8 @@ mod tests {
                 is_task_running: false,
                 context_window_percent: None,
                 context_window_used_tokens: None,
+                session_cost_usd: None,
+                quota: None,
             },
         );
 
@@ -442,6 +582,8 @@ mod tests {
                 is_task_running: false,
                 context_window_percent: None,
                 context_window_used_tokens: None,
+                session_cost_usd: None,
+                quota: None,
             },
         );
 
@@ -454,6 +596,8 @@ mod tests {
                 is_task_running: true,
                 context_window_percent: None,
                 context_window_used_tokens: None,
+                session_cost_usd: None,
+                quota: None,
             },
         );
 
@@ -466,6 +610,8 @@ mod tests {
                 is_task_running: false,
                 context_window_percent: None,
                 context_window_used_tokens: None,
+                session_cost_usd: None,
+                quota: None,
             },
         );
 
@@ -478,6 +624,8 @@ mod tests {
                 is_task_running: false,
                 context_window_percent: None,
                 context_window_used_tokens: None,
+                session_cost_usd: None,
+                quota: None,
             },
         );
 
@@ -490,6 +638,8 @@ mod tests {
                 is_task_running: true,
                 context_window_percent: Some(72),
                 context_window_used_tokens: None,
+                session_cost_usd: None,
+                quota: None,
             },
         );
 
@@ -502,7 +652,120 @@ mod tests {
                 is_task_running: false,
                 context_window_percent: None,
                 context_window_used_tokens: Some(123_456),
+                session_cost_usd: None,
+                quota: None,
+            },
+        );
+
+        snapshot_footer(
+            "footer_context_with_session_cost",
+            FooterProps {
+                mode: FooterMode::ShortcutSummary,
+                esc_backtrack_hint: false,
+                use_shift_enter_hint: false,
+                is_task_running: false,
+                context_window_percent: Some(72),
+                context_window_used_tokens: None,
+                session_cost_usd: Some(1.234),
+                quota: None,
+            },
+        );
+
+        // The footer counts down from the real clock; the extra 30 seconds
+        // keep the rounded-down countdown stable while the test runs.
+        let now = chrono::Utc::now().timestamp();
+        snapshot_footer(
+            "footer_context_with_quota",
+            FooterProps {
+                mode: FooterMode::ShortcutSummary,
+                esc_backtrack_hint: false,
+                use_shift_enter_hint: false,
+                is_task_running: false,
+                context_window_percent: Some(72),
+                context_window_used_tokens: None,
+                session_cost_usd: None,
+                quota: Some(FooterQuota {
+                    remaining_percent: Some(38.0),
+                    resets_at: Some(now + 7_830),
+                    ..Default::default()
+                }),
+            },
+        );
+
+        snapshot_footer(
+            "footer_quota_limit_reached",
+            FooterProps {
+                mode: FooterMode::ShortcutSummary,
+                esc_backtrack_hint: false,
+                use_shift_enter_hint: false,
+                is_task_running: false,
+                context_window_percent: None,
+                context_window_used_tokens: None,
+                session_cost_usd: None,
+                quota: Some(FooterQuota {
+                    resets_at: Some(now + 2_490),
+                    exhausted: true,
+                    ..Default::default()
+                }),
             },
         );
     }
+
+    #[test]
+    fn quota_shows_what_is_left_and_counts_down_to_the_reset() {
+        use codex_core::protocol::RateLimitWindow;
+        use codex_core::protocol::RequestRateLimits;
+        use pretty_assertions::assert_eq;
+
+        const NOW: i64 = 1_700_000_000;
+        let window = |used_percent, resets_in| RateLimitWindow {
+            used_percent,
+            window_minutes: Some(300),
+            resets_at: Some(NOW + resets_in),
+        };
+        let chatgpt = RateLimitSnapshot {
+            primary: Some(window(62.0, 7_800)),
+            secondary: Some(window(20.0, 300_000)),
+            credits: None,
+            request_limits: None,
+        };
+        let quota = FooterQuota::from_snapshot(&chatgpt).expect("quota");
+        assert_eq!(quota.text(NOW), "38% quota left, resets in 2h 10m");
+        assert_eq!(quota.refresh_in(NOW), Some(Duration::from_secs(30)));
+
+        let limited = RateLimitSnapshot {
+            primary: Some(window(100.0, 2_500)),
+            ..chatgpt
+        };
+        let quota = FooterQuota::from_snapshot(&limited).expect("quota");
+        assert!(quota.exhausted);
+        assert_eq!(quota.text(NOW), "limit reached, resets in 41m");
+        assert_eq!(quota.text(NOW + 2_500), "limit reached");
+        assert_eq!(quota.refresh_in(NOW + 2_500), None);
+
+        let api_key = RateLimitSnapshot {
+            primary: None,
+            secondary: None,
+            credits: None,
+            request_limits: Some(RequestRateLimits {
+                remaining_requests: Some(4_990),
+                limit_requests: Some(5_000),
+                remaining_tokens: Some(612_000),
+                limit_tokens: Some(800_000),
+                resets_at: Some(NOW + 12),
+            }),
+        };
+        let quota = FooterQuota::from_snapshot(&api_key).expect("quota");
+        assert_eq!(
+            quota.text(NOW),
+            "4.99K requests, 612K tokens left, resets in <1m"
+        );
+        assert_eq!(quota.refresh_in(NOW), Some(Duration::from_secs(1)));
+
+        let empty = RateLimitSnapshot {
+            request_limits: None,
+            ..api_key
+        };
+        assert_eq!(FooterQuota::from_snapshot(&empty), None);
+    }
 }
@@ -0,0 +1,474 @@
+//! Hunk-by-hunk review of a proposed patch, shown inside the approval
+//! overlay. Each hunk is accepted, rejected (optionally with a comment for
+//! the model), or edited; once every hunk has a decision the review is sent
+//! as [`Op::PatchHunkReview`](codex_core::protocol::Op::PatchHunkReview).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_core::patch_review::file_change_hunks;
+use codex_core::protocol::FileChange;
+use codex_core::protocol::HunkDecision;
+use codex_core::protocol::PatchHunkDecision;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyEventKind;
+use crossterm::event::KeyModifiers;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::StatefulWidgetRef;
+use ratatui::widgets::Widget;
+
+use crate::diff_render::display_path_for;
+
+use super::textarea::TextArea;
+use super::textarea::TextAreaState;
+
+/// Diff lines shown per hunk; longer hunks are cut off.
+const MAX_HUNK_LINES: usize = 16;
+const MAX_INPUT_HEIGHT: u16 = 8;
+
+struct ReviewHunk {
+    path: PathBuf,
+    display_path: String,
+    /// Index of the hunk within its file.
+    index: usize,
+    file_hunks: usize,
+    text: String,
+    /// Whether the hunk comes from an update, whose `@@` header is not part
+    /// of the editable lines.
+    has_header: bool,
+    editable: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum InputKind {
+    Comment,
+    Edit,
+}
+
+struct HunkInput {
+    kind: InputKind,
+    textarea: TextArea,
+    state: RefCell<TextAreaState>,
+}
+
+pub(crate) struct HunkReview {
+    hunks: Vec<ReviewHunk>,
+    decisions: Vec<Option<HunkDecision>>,
+    current: usize,
+    input: Option<HunkInput>,
+}
+
+impl HunkReview {
+    pub(crate) fn new(changes: &HashMap<PathBuf, FileChange>, cwd: &Path) -> Self {
+        let mut paths: Vec<&PathBuf> = changes.keys().collect();
+        paths.sort();
+        let mut hunks = Vec::new();
+        for path in paths {
+            let change = &changes[path];
+            let file_hunks = file_change_hunks(change);
+            let count = file_hunks.len();
+            for (index, text) in file_hunks.into_iter().enumerate() {
+                hunks.push(ReviewHunk {
+                    path: path.clone(),
+                    display_path: display_path_for(path, cwd),
+                    index,
+                    file_hunks: count,
+                    text,
+                    has_header: matches!(change, FileChange::Update { .. }),
+                    editable: !matches!(change, FileChange::Delete { .. }),
+                });
+            }
+        }
+        Self {
+            decisions: vec![None; hunks.len()],
+            hunks,
+            current: 0,
+            input: None,
+        }
+    }
+
+    /// Handles a key; returns the decisions once every hunk has one. Esc is
+    /// handled by [`Self::close_input`].
+    pub(crate) fn handle_key_event(
+        &mut self,
+        key_event: KeyEvent,
+    ) -> Option<Vec<PatchHunkDecision>> {
+        if key_event.kind != KeyEventKind::Press {
+            return None;
+        }
+        if let Some(input) = self.input.as_mut() {
+            match key_event {
+                KeyEvent {
+                    code: KeyCode::Enter,
+                    modifiers: KeyModifiers::NONE,
+                    ..
+                } => {
+                    let text = input.textarea.text().to_string();
+                    let decision = match input.kind {
+                        InputKind::Comment => HunkDecision::Reject {
+                            comment: Some(text.trim().to_string()).filter(|text| !text.is_empty()),
+                        },
+                        InputKind::Edit => HunkDecision::Edit { diff: text },
+                    };
+                    self.input = None;
+                    return self.decide(decision);
+                }
+                other => input.textarea.input(other),
+            }
+            return None;
+        }
+
+        match key_event.code {
+            KeyCode::Char('y') => self.decide(HunkDecision::Accept),
+            KeyCode::Char('n') => self.decide(HunkDecision::Reject { comment: None }),
+            KeyCode::Char('c') => {
+                self.open_input(InputKind::Comment, "");
+                None
+            }
+            KeyCode::Char('e') => {
+                if let Some(hunk) = self.hunks.get(self.current)
+                    && hunk.editable
+                {
+                    let lines = if hunk.has_header {
+                        hunk.text.split_once('\n').map_or("", |(_, rest)| rest)
+                    } else {
+                        hunk.text.as_str()
+                    };
+                    let lines = lines.trim_end_matches('\n').to_string();
+                    self.open_input(InputKind::Edit, &lines);
+                }
+                None
+            }
+            KeyCode::Char('a') => {
+                for decision in self.decisions.iter_mut().skip(self.current) {
+                    if decision.is_none() {
+                        *decision = Some(HunkDecision::Accept);
+                    }
+                }
+                self.current = self.hunks.len();
+                Some(self.collect())
+            }
+            KeyCode::Left | KeyCode::Char('p') => {
+                self.current = self.current.saturating_sub(1);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+
+    pub(crate) fn handle_paste(&mut self, pasted: &str) -> bool {
+        match self.input.as_mut() {
+            Some(input) => {
+                input.textarea.insert_str(pasted);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Closes an open comment or edit; returns `false` when none was open.
+    pub(crate) fn close_input(&mut self) -> bool {
+        self.input.take().is_some()
+    }
+
+    fn open_input(&mut self, kind: InputKind, text: &str) {
+        let mut textarea = TextArea::new();
+        textarea.set_text(text);
+        textarea.set_cursor(0);
+        self.input = Some(HunkInput {
+            kind,
+            textarea,
+            state: RefCell::new(TextAreaState::default()),
+        });
+    }
+
+    fn decide(&mut self, decision: HunkDecision) -> Option<Vec<PatchHunkDecision>> {
+        if let Some(slot) = self.decisions.get_mut(self.current) {
+            *slot = Some(decision);
+        }
+        self.current += 1;
+        (self.current >= self.hunks.len()).then(|| self.collect())
+    }
+
+    fn collect(&self) -> Vec<PatchHunkDecision> {
+        self.hunks
+            .iter()
+            .zip(&self.decisions)
+            .filter_map(|(hunk, decision)| {
+                decision.clone().map(|decision| PatchHunkDecision {
+                    path: hunk.path.clone(),
+                    hunk: hunk.index,
+                    decision,
+                })
+            })
+            .collect()
+    }
+
+    fn header_lines(&self) -> Vec<Line<'static>> {
+        let Some(hunk) = self.hunks.get(self.current) else {
+            return Vec::new();
+        };
+        let mut lines = vec![
+            Line::from(vec![
+                format!("Hunk {} of {}: ", self.current + 1, self.hunks.len()).bold(),
+                hunk.display_path.clone().into(),
+                if hunk.file_hunks > 1 {
+                    format!(" ({}/{})", hunk.index + 1, hunk.file_hunks).dim()
+                } else {
+                    "".into()
+                },
+            ]),
+            Line::from(""),
+        ];
+        let diff_lines: Vec<&str> = hunk.text.lines().collect();
+        for line in diff_lines.iter().take(MAX_HUNK_LINES) {
+            let line = line.to_string();
+            lines.push(if line.starts_with("@@") {
+                line.cyan().into()
+            } else if line.starts_with('+') {
+                line.green().into()
+            } else if line.starts_with('-') {
+                line.red().into()
+            } else {
+                line.into()
+            });
+        }
+        if diff_lines.len() > MAX_HUNK_LINES {
+            lines.push(
+                format!("… {} more lines", diff_lines.len() - MAX_HUNK_LINES)
+                    .dim()
+                    .into(),
+            );
+        }
+        lines.push(Line::from(""));
+        match self.input.as_ref().map(|input| input.kind) {
+            Some(InputKind::Comment) => {
+                lines.push("Why reject this hunk? (sent to Codex)".bold().into());
+            }
+            Some(InputKind::Edit) => lines.push(
+                "Edit the hunk, keeping each line's leading ` `, `-`, or `+`"
+                    .bold()
+                    .into(),
+            ),
+            None => {}
+        }
+        lines
+    }
+
+    fn hint_line(&self) -> Line<'static> {
+        let key = |key: &'static str| Span::from(key).cyan();
+        let label = |label: &'static str| Span::from(label).dim();
+        if self.input.is_some() {
+            return Line::from(vec![
+                key("enter"),
+                label(" submit  "),
+                key("shift+enter"),
+                label(" newline  "),
+                key("esc"),
+                label(" back"),
+            ]);
+        }
+        let mut spans = vec![
+            key("y"),
+            label(" accept  "),
+            key("n"),
+            label(" reject  "),
+            key("c"),
+            label(" reject with comment  "),
+        ];
+        if self
+            .hunks
+            .get(self.current)
+            .is_some_and(|hunk| hunk.editable)
+        {
+            spans.extend([key("e"), label(" edit  ")]);
+        }
+        spans.extend([key("a"), label(" accept rest  ")]);
+        if self.current > 0 {
+            spans.extend([key("←"), label(" back  ")]);
+        }
+        spans.extend([key("esc"), label(" cancel")]);
+        Line::from(spans)
+    }
+
+    fn input_height(&self, width: u16) -> u16 {
+        self.input.as_ref().map_or(0, |input| {
+            input
+                .textarea
+                .desired_height(width.saturating_sub(2))
+                .clamp(1, MAX_INPUT_HEIGHT)
+        })
+    }
+
+    pub(crate) fn desired_height(&self, width: u16) -> u16 {
+        let header = self.header_lines().len() as u16;
+        let input_height = self.input_height(width);
+        // The input is followed by a blank line before the hints.
+        let input_block = if input_height > 0 {
+            input_height + 1
+        } else {
+            0
+        };
+        header + input_block + 1
+    }
+
+    fn input_area(&self, area: Rect) -> Option<Rect> {
+        self.input.as_ref()?;
+        let top = self.header_lines().len() as u16;
+        Some(Rect {
+            x: area.x.saturating_add(2),
+            y: area.y.saturating_add(top),
+            width: area.width.saturating_sub(2),
+            height: self
+                .input_height(area.width)
+                .min(area.height.saturating_sub(top)),
+        })
+    }
+
+    pub(crate) fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        let header = self.header_lines();
+        let header_height = (header.len() as u16).min(area.height);
+        Paragraph::new(header).render(
+            Rect {
+                height: header_height,
+                ..area
+            },
+            buf,
+        );
+        let mut hint_y = area.y.saturating_add(header_height);
+        if let (Some(input), Some(input_area)) = (self.input.as_ref(), self.input_area(area)) {
+            for row in 0..input_area.height {
+                Span::from("▌ ").cyan().render(
+                    Rect {
+                        x: area.x,
+                        y: input_area.y.saturating_add(row),
+                        width: 2.min(area.width),
+                        height: 1,
+                    },
+                    buf,
+                );
+            }
+            let mut state = input.state.borrow_mut();
+            StatefulWidgetRef::render_ref(&(&input.textarea), input_area, buf, &mut state);
+            hint_y = input_area.bottom().saturating_add(1);
+        }
+        if hint_y < area.bottom() {
+            self.hint_line().render(
+                Rect {
+                    y: hint_y,
+                    height: 1,
+                    ..area
+                },
+                buf,
+            );
+        }
+    }
+
+    pub(crate) fn cursor_pos(&self, area: Rect) -> Option<(u16, u16)> {
+        let input = self.input.as_ref()?;
+        let input_area = self.input_area(area)?;
+        let state = *input.state.borrow();
+        input.textarea.cursor_pos_with_state(input_area, state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn review() -> HunkReview {
+        let changes = HashMap::from([
+            (
+                PathBuf::from("/repo/a.txt"),
+                FileChange::Update {
+                    unified_diff:
+                        "@@ -1,2 +1,2 @@\n one\n-two\n+TWO\n@@ -9,2 +9,2 @@\n nine\n-ten\n+TEN\n"
+                            .to_string(),
+                    move_path: None,
+                },
+            ),
+            (
+                PathBuf::from("/repo/b.txt"),
+                FileChange::Delete {
+                    content: "gone\n".to_string(),
+                },
+            ),
+        ]);
+        HunkReview::new(&changes, Path::new("/repo"))
+    }
+
+    #[test]
+    fn every_hunk_gets_a_decision() {
+        let mut review = review();
+        assert_eq!(review.handle_key_event(key(KeyCode::Char('y'))), None);
+        assert_eq!(review.handle_key_event(key(KeyCode::Char('c'))), None);
+        review.handle_paste("ten stays");
+        assert_eq!(review.handle_key_event(key(KeyCode::Enter)), None);
+        assert_eq!(
+            review.handle_key_event(key(KeyCode::Char('n'))),
+            Some(vec![
+                PatchHunkDecision {
+                    path: PathBuf::from("/repo/a.txt"),
+                    hunk: 0,
+                    decision: HunkDecision::Accept,
+                },
+                PatchHunkDecision {
+                    path: PathBuf::from("/repo/a.txt"),
+                    hunk: 1,
+                    decision: HunkDecision::Reject {
+                        comment: Some("ten stays".to_string()),
+                    },
+                },
+                PatchHunkDecision {
+                    path: PathBuf::from("/repo/b.txt"),
+                    hunk: 0,
+                    decision: HunkDecision::Reject { comment: None },
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn editing_starts_from_the_hunk_lines() {
+        let mut review = review();
+        review.handle_key_event(key(KeyCode::Char('e')));
+        review.handle_paste("+2\n");
+        assert_eq!(
+            review.handle_key_event(key(KeyCode::Char('a'))),
+            None,
+            "keys go to the editor while it is open"
+        );
+        review.handle_key_event(key(KeyCode::Enter));
+        let decisions = review.handle_key_event(key(KeyCode::Char('a')));
+        assert_eq!(
+            decisions.and_then(|decisions| decisions.into_iter().next()),
+            Some(PatchHunkDecision {
+                path: PathBuf::from("/repo/a.txt"),
+                hunk: 0,
+                decision: HunkDecision::Edit {
+                    diff: "+2\na one\n-two\n+TWO".to_string(),
+                },
+            })
+        );
+    }
+}
@@ -2,6 +2,7 @@
 use std::path::PathBuf;
 
 use crate::app_event_sender::AppEventSender;
+use crate::bottom_pane::plan_checklist::PlanChecklist;
 use crate::bottom_pane::queued_user_messages::QueuedUserMessages;
 use crate::render::renderable::FlexRenderable;
 use crate::render::renderable::Renderable;
@@ -10,6 +11,7 @@ use crate::tui::FrameRequester;
 use bottom_pane_view::BottomPaneView;
 use codex_core::skills::model::SkillMetadata;
 use codex_file_search::FileMatch;
+use codex_protocol::plan_tool::PlanItemArg;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use ratatui::buffer::Buffer;
@@ -26,6 +28,8 @@ mod command_popup;
 pub mod custom_prompt_view;
 mod file_search_popup;
 mod footer;
+mod hunk_review;
+pub(crate) use footer::FooterQuota;
 mod list_selection_view;
 mod prompt_args;
 mod skill_popup;
@@ -34,6 +38,7 @@ mod feedback_view;
 pub(crate) use feedback_view::feedback_selection_params;
 pub(crate) use feedback_view::feedback_upload_consent_params;
 mod paste_burst;
+mod plan_checklist;
 pub mod popup_consts;
 mod queued_user_messages;
 mod scroll_state;
@@ -75,6 +80,8 @@ pub(crate) struct BottomPane {
 
     /// Inline status indicator shown above the composer while a task is running.
     status: Option<StatusIndicatorWidget>,
+    /// The current plan, shown as a checklist above the composer until it is done.
+    plan_checklist: PlanChecklist,
     /// Queued user messages to show above the composer while a turn is running.
     queued_user_messages: QueuedUserMessages,
     context_window_percent: Option<i64>,
@@ -122,6 +129,7 @@ impl BottomPane {
             is_task_running: false,
             ctrl_c_quit_hint: false,
             status: None,
+            plan_checklist: PlanChecklist::new(),
             queued_user_messages: QueuedUserMessages::new(),
             esc_backtrack_hint: false,
             animations_enabled,
@@ -371,12 +379,31 @@ impl BottomPane {
         self.request_redraw();
     }
 
+    /// Estimated cost of the session so far, shown next to the context
+    /// indicator.
+    pub(crate) fn set_session_cost(&mut self, usd: Option<f64>) {
+        self.composer.set_session_cost(usd);
+        self.request_redraw();
+    }
+
+    /// Usage-limit headroom from the latest response, shown in the footer.
+    pub(crate) fn set_quota(&mut self, quota: Option<FooterQuota>) {
+        self.composer.set_quota(quota);
+        self.request_redraw();
+    }
+
     /// Show a generic list selection view with the provided items.
     pub(crate) fn show_selection_view(&mut self, params: list_selection_view::SelectionViewParams) {
         let view = list_selection_view::ListSelectionView::new(params, self.app_event_tx.clone());
         self.push_view(Box::new(view));
     }
 
+    /// Update the plan checklist shown above the composer.
+    pub(crate) fn set_plan(&mut self, plan: Vec<PlanItemArg>) {
+        self.plan_checklist.set_plan(plan);
+        self.request_redraw();
+    }
+
     /// Update the queued messages preview shown above the composer.
     pub(crate) fn set_queued_user_messages(&mut self, queued: Vec<String>) {
         self.queued_user_messages.messages = queued;
@@ -513,8 +540,12 @@ impl BottomPane {
             if let Some(status) = &self.status {
                 flex.push(0, RenderableItem::Borrowed(status));
             }
+            flex.push(1, RenderableItem::Borrowed(&self.plan_checklist));
             flex.push(1, RenderableItem::Borrowed(&self.queued_user_messages));
-            if self.status.is_some() || !self.queued_user_messages.messages.is_empty() {
+            if self.status.is_some()
+                || self.plan_checklist.is_visible()
+                || !self.queued_user_messages.messages.is_empty()
+            {
                 flex.push(0, RenderableItem::Owned("".into()));
             }
             let mut flex2 = FlexRenderable::new();
@@ -527,6 +558,14 @@ impl BottomPane {
 
 impl Renderable for BottomPane {
     fn render(&self, area: Rect, buf: &mut Buffer) {
+        // Keep the limit countdown in the footer ticking.
+        if let Some(delay) = self
+            .composer
+            .quota()
+            .and_then(|quota| quota.refresh_in(chrono::Utc::now().timestamp()))
+        {
+            self.request_redraw_in(delay);
+        }
         self.as_renderable().render(area, buf);
     }
     fn desired_height(&self, width: u16) -> u16 {
@@ -0,0 +1,201 @@
+use codex_protocol::plan_tool::PlanItemArg;
+use codex_protocol::plan_tool::StepStatus;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Paragraph;
+
+use crate::render::renderable::Renderable;
+use crate::text_formatting::truncate_text;
+
+/// Most steps shown at once; the rest are summarized.
+const MAX_STEPS: usize = 8;
+
+/// Widget that shows the current plan as a checklist above the composer
+/// until every step is completed.
+pub(crate) struct PlanChecklist {
+    plan: Vec<PlanItemArg>,
+}
+
+impl PlanChecklist {
+    pub(crate) fn new() -> Self {
+        Self { plan: Vec::new() }
+    }
+
+    pub(crate) fn set_plan(&mut self, plan: Vec<PlanItemArg>) {
+        self.plan = plan;
+    }
+
+    pub(crate) fn is_visible(&self) -> bool {
+        self.plan
+            .iter()
+            .any(|item| !matches!(item.status, StepStatus::Completed))
+    }
+
+    fn as_renderable(&self, width: u16) -> Box<dyn Renderable> {
+        if !self.is_visible() || width < 8 {
+            return Box::new(());
+        }
+
+        let done = self
+            .plan
+            .iter()
+            .filter(|item| matches!(item.status, StepStatus::Completed))
+            .count();
+        let mut lines = vec![Line::from(vec![
+            "  Plan ".bold(),
+            format!("{done}/{}", self.plan.len()).dim(),
+        ])];
+
+        // Keep the first unfinished step in view, with one finished step
+        // before it for context.
+        let first_open = self
+            .plan
+            .iter()
+            .position(|item| !matches!(item.status, StepStatus::Completed))
+            .unwrap_or_default();
+        let start = first_open
+            .saturating_sub(1)
+            .min(self.plan.len().saturating_sub(MAX_STEPS));
+        let end = (start + MAX_STEPS).min(self.plan.len());
+        if start > 0 {
+            lines.push(Line::from(format!("    … {start} done").dim()));
+        }
+        let step_width = (width as usize).saturating_sub(6).max(1);
+        for item in &self.plan[start..end] {
+            let (box_str, style) = match item.status {
+                StepStatus::Completed => ("✔ ", Style::default().crossed_out().dim()),
+                StepStatus::InProgress => ("▸ ", Style::default().cyan().bold()),
+                StepStatus::Pending => ("□ ", Style::default().dim()),
+            };
+            lines.push(Line::from(vec![
+                "    ".into(),
+                box_str.into(),
+                Span::styled(truncate_text(&item.step, step_width), style),
+            ]));
+        }
+        if end < self.plan.len() {
+            lines.push(Line::from(
+                format!("    … {} more", self.plan.len() - end).dim(),
+            ));
+        }
+
+        Paragraph::new(lines).into()
+    }
+}
+
+impl Renderable for PlanChecklist {
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+
+        self.as_renderable(area.width).render(area, buf);
+    }
+
+    fn desired_height(&self, width: u16) -> u16 {
+        self.as_renderable(width).desired_height(width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
+
+    fn step(step: &str, status: StepStatus) -> PlanItemArg {
+        PlanItemArg {
+            step: step.to_string(),
+            status,
+        }
+    }
+
+    fn rendered(checklist: &PlanChecklist, width: u16) -> Vec<String> {
+        let height = checklist.desired_height(width);
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        checklist.render(area, &mut buf);
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn hidden_until_there_is_an_unfinished_step() {
+        let mut checklist = PlanChecklist::new();
+        assert_eq!(checklist.desired_height(40), 0);
+
+        checklist.set_plan(vec![step("Write the parser", StepStatus::Completed)]);
+        assert_eq!(checklist.desired_height(40), 0);
+
+        checklist.set_plan(vec![
+            step("Write the parser", StepStatus::Completed),
+            step("Add tests", StepStatus::InProgress),
+            step("Update the docs", StepStatus::Pending),
+        ]);
+        assert_eq!(
+            rendered(&checklist, 40),
+            vec![
+                "  Plan 1/3",
+                "    ✔ Write the parser",
+                "    ▸ Add tests",
+                "    □ Update the docs",
+            ]
+        );
+    }
+
+    #[test]
+    fn checklist_snapshot() {
+        let mut checklist = PlanChecklist::new();
+        checklist.set_plan(vec![
+            step("Read the failing test", StepStatus::Completed),
+            step("Fix the off-by-one in the pager", StepStatus::InProgress),
+            step("Run the test suite", StepStatus::Pending),
+            step(
+                "Update the changelog with a note about the pager fix",
+                StepStatus::Pending,
+            ),
+        ]);
+        assert_snapshot!("plan_checklist", rendered(&checklist, 40).join("\n"));
+    }
+
+    #[test]
+    fn long_plans_stay_near_the_current_step() {
+        let mut plan: Vec<PlanItemArg> = (1..=12)
+            .map(|n| step(&format!("Step {n}"), StepStatus::Pending))
+            .collect();
+        for item in &mut plan[..4] {
+            item.status = StepStatus::Completed;
+        }
+        plan[4].status = StepStatus::InProgress;
+        let mut checklist = PlanChecklist::new();
+        checklist.set_plan(plan);
+
+        assert_eq!(
+            rendered(&checklist, 40),
+            vec![
+                "  Plan 4/12",
+                "    … 3 done",
+                "    ✔ Step 4",
+                "    ▸ Step 5",
+                "    □ Step 6",
+                "    □ Step 7",
+                "    □ Step 8",
+                "    □ Step 9",
+                "    □ Step 10",
+                "    □ Step 11",
+                "    … 1 more",
+            ]
+        );
+    }
+}
@@ -1,15 +1,9 @@
+use codex_core::custom_prompts::expand_prompt_variables;
+use codex_core::custom_prompts::prompt_variable_names;
 use codex_protocol::custom_prompts::CustomPrompt;
 use codex_protocol::custom_prompts::PROMPTS_CMD_PREFIX;
-use lazy_static::lazy_static;
-use regex_lite::Regex;
 use shlex::Shlex;
 use std::collections::HashMap;
-use std::collections::HashSet;
-
-lazy_static! {
-    static ref PROMPT_ARG_REGEX: Regex =
-        Regex::new(r"\$[A-Z][A-Z0-9_]*").unwrap_or_else(|_| std::process::abort());
-}
 
 #[derive(Debug)]
 pub enum PromptArgsError {
@@ -83,27 +77,11 @@ pub fn parse_positional_args(rest: &str) -> Vec<String> {
 
 /// Extracts the unique placeholder variable names from a prompt template.
 ///
-/// A placeholder is any token that matches the pattern `$[A-Z][A-Z0-9_]*`
-/// (for example `$USER`). The function returns the variable names without
+/// A placeholder is either `{{name}}` or an uppercase `$NAME` (for example
+/// `$USER`). The function returns the variable names without the braces or
 /// the leading `$`, de-duplicated and in the order of first appearance.
 pub fn prompt_argument_names(content: &str) -> Vec<String> {
-    let mut seen = HashSet::new();
-    let mut names = Vec::new();
-    for m in PROMPT_ARG_REGEX.find_iter(content) {
-        if m.start() > 0 && content.as_bytes()[m.start() - 1] == b'$' {
-            continue;
-        }
-        let name = &content[m.start() + 1..m.end()];
-        // Exclude special positional aggregate token from named args.
-        if name == "ARGUMENTS" {
-            continue;
-        }
-        let name = name.to_string();
-        if seen.insert(name.clone()) {
-            names.push(name);
-        }
-    }
-    names
+    prompt_variable_names(content)
 }
 
 /// Parses the `key=value` pairs that follow a custom prompt name.
@@ -168,22 +146,7 @@ pub fn expand_custom_prompt(
                 missing,
             });
         }
-        let content = &prompt.content;
-        let replaced = PROMPT_ARG_REGEX.replace_all(content, |caps: &regex_lite::Captures<'_>| {
-            if let Some(matched) = caps.get(0)
-                && matched.start() > 0
-                && content.as_bytes()[matched.start() - 1] == b'$'
-            {
-                return matched.as_str().to_string();
-            }
-            let whole = &caps[0];
-            let key = &whole[1..];
-            inputs
-                .get(key)
-                .cloned()
-                .unwrap_or_else(|| whole.to_string())
-        });
-        return Ok(Some(replaced.into_owned()));
+        return Ok(Some(expand_prompt_variables(&prompt.content, &inputs)));
     }
 
     // Otherwise, treat it as numeric/positional placeholder prompt (or none).
@@ -329,6 +292,27 @@ mod tests {
         assert_eq!(out, Some("Review Alice changes on main".to_string()));
     }
 
+    #[test]
+    fn braced_variables_expand() {
+        let prompts = vec![CustomPrompt {
+            name: "migration".to_string(),
+            path: "/tmp/migration.md".to_string().into(),
+            content: "Write a migration adding {{ column }} to {{table}}.".to_string(),
+            description: None,
+            argument_hint: None,
+        }];
+
+        let out = expand_custom_prompt(
+            "/prompts:migration table=users column=\"last_seen timestamp\"",
+            &prompts,
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            Some("Write a migration adding last_seen timestamp to users.".to_string())
+        );
+    }
+
     #[test]
     fn quoted_values_ok() {
         let prompts = vec![CustomPrompt {
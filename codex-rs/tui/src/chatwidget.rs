@@ -19,7 +19,9 @@ use codex_core::protocol::AgentReasoningEvent;
 use codex_core::protocol::AgentReasoningRawContentDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
+use codex_core::protocol::AuthSwitchedEvent;
 use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::CheckpointCreatedEvent;
 use codex_core::protocol::CreditsSnapshot;
 use codex_core::protocol::DeprecationNoticeEvent;
 use codex_core::protocol::ErrorEvent;
@@ -39,6 +41,7 @@ use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
+use codex_core::protocol::PinnedFilesEvent;
 use codex_core::protocol::RateLimitSnapshot;
 use codex_core::protocol::ReviewRequest;
 use codex_core::protocol::ReviewTarget;
@@ -82,6 +85,7 @@ use crate::bottom_pane::ApprovalRequest;
 use crate::bottom_pane::BottomPane;
 use crate::bottom_pane::BottomPaneParams;
 use crate::bottom_pane::CancellationEvent;
+use crate::bottom_pane::FooterQuota;
 use crate::bottom_pane::InputResult;
 use crate::bottom_pane::SelectionAction;
 use crate::bottom_pane::SelectionItem;
@@ -613,7 +617,14 @@ impl ChatWidget {
                 self.rate_limit_switch_prompt = RateLimitSwitchPromptState::Pending;
             }
 
-            let display = crate::status::rate_limit_snapshot_display(&snapshot, Local::now());
+            self.bottom_pane
+                .set_quota(FooterQuota::from_snapshot(&snapshot));
+
+            let display = crate::status::rate_limit_snapshot_display(
+                &snapshot,
+                Local::now(),
+                self.config.tui_timestamp_format,
+            );
             self.rate_limit_snapshot = Some(display);
 
             if !warnings.is_empty() {
@@ -624,6 +635,7 @@ impl ChatWidget {
             }
         } else {
             self.rate_limit_snapshot = None;
+            self.bottom_pane.set_quota(None);
         }
     }
     /// Finalize any active exec as failed and stop/clear running UI state.
@@ -653,6 +665,14 @@ impl ChatWidget {
         self.request_redraw();
     }
 
+    fn on_auth_switched(&mut self, ev: AuthSwitchedEvent) {
+        let AuthSwitchedEvent { from, to, reason } = ev;
+        self.on_warning(format!(
+            "Switched from {from} to {to} ({}). Usage is now billed to the new credentials.",
+            reason.description()
+        ));
+    }
+
     fn on_mcp_startup_update(&mut self, ev: McpStartupUpdateEvent) {
         let mut status = self.mcp_startup_status.take().unwrap_or_default();
         if let McpStartupStatus::Failed { error } = &ev.status {
@@ -760,6 +780,7 @@ impl ChatWidget {
     }
 
     fn on_plan_update(&mut self, update: UpdatePlanArgs) {
+        self.bottom_pane.set_plan(update.plan.clone());
         self.add_to_history(history_cell::new_plan_update(update));
     }
 
@@ -797,9 +818,18 @@ impl ChatWidget {
 
     fn on_exec_command_output_delta(
         &mut self,
-        _ev: codex_core::protocol::ExecCommandOutputDeltaEvent,
+        ev: codex_core::protocol::ExecCommandOutputDeltaEvent,
     ) {
-        // TODO: Handle streaming exec output if/when implemented
+        // Output of a call whose begin event is still queued, or that runs
+        // outside the active cell, only shows once it ends.
+        if let Some(cell) = self
+            .active_cell
+            .as_mut()
+            .and_then(|c| c.as_any_mut().downcast_mut::<ExecCell>())
+            && cell.append_live_output(&ev.call_id, &ev.chunk)
+        {
+            self.request_redraw();
+        }
     }
 
     fn on_patch_apply_begin(&mut self, event: PatchApplyBeginEvent) {
@@ -913,6 +943,24 @@ impl ChatWidget {
         }
     }
 
+    fn on_checkpoint_created(&mut self, event: CheckpointCreatedEvent) {
+        let CheckpointCreatedEvent {
+            name,
+            turns,
+            snapshot,
+        } = event;
+        let contents = if snapshot {
+            "the conversation and working tree"
+        } else {
+            "the conversation"
+        };
+        let plural = if turns == 1 { "" } else { "s" };
+        self.add_info_message(
+            format!("Saved checkpoint {name} of {contents} ({turns} turn{plural})."),
+            Some(format!("Roll back to it with `codex restore {name}`.")),
+        );
+    }
+
     fn on_stream_error(&mut self, message: String) {
         if self.retry_status_header.is_none() {
             self.retry_status_header = Some(self.current_status_header.clone());
@@ -1531,6 +1579,9 @@ impl ChatWidget {
             SlashCommand::Undo => {
                 self.app_event_tx.send(AppEvent::CodexOp(Op::Undo));
             }
+            SlashCommand::Checkpoint => {
+                self.show_checkpoint_prompt();
+            }
             SlashCommand::Diff => {
                 self.add_diff_in_progress();
                 let tx = self.app_event_tx.clone();
@@ -1551,6 +1602,15 @@ impl ChatWidget {
             SlashCommand::Mention => {
                 self.insert_str("@");
             }
+            // Both list the current pins first, with the tokens they use.
+            SlashCommand::Pin => {
+                self.app_event_tx.send(AppEvent::CodexOp(Op::ListPins));
+                self.show_pin_prompt();
+            }
+            SlashCommand::Unpin => {
+                self.app_event_tx.send(AppEvent::CodexOp(Op::ListPins));
+                self.show_unpin_prompt();
+            }
             SlashCommand::Skills => {
                 self.insert_str("$");
             }
@@ -1774,10 +1834,16 @@ impl ChatWidget {
                 self.on_task_complete(last_agent_message)
             }
             EventMsg::TokenCount(ev) => {
+                self.bottom_pane.set_session_cost(
+                    ev.cost
+                        .map(|cost| cost.session_usd)
+                        .filter(|usd| *usd > 0.0),
+                );
                 self.set_token_info(ev.info);
                 self.on_rate_limit_snapshot(ev.rate_limits);
             }
             EventMsg::Warning(WarningEvent { message }) => self.on_warning(message),
+            EventMsg::AuthSwitched(ev) => self.on_auth_switched(ev),
             EventMsg::Error(ErrorEvent { message, .. }) => self.on_error(message),
             EventMsg::McpStartupUpdate(ev) => self.on_mcp_startup_update(ev),
             EventMsg::McpStartupComplete(ev) => self.on_mcp_startup_complete(ev),
@@ -1816,6 +1882,7 @@ impl ChatWidget {
             EventMsg::GetHistoryEntryResponse(ev) => self.on_get_history_entry_response(ev),
             EventMsg::McpListToolsResponse(ev) => self.on_list_mcp_tools(ev),
             EventMsg::ListCustomPromptsResponse(ev) => self.on_list_custom_prompts(ev),
+            EventMsg::PinnedFiles(ev) => self.on_pinned_files(ev),
             EventMsg::ShutdownComplete => self.on_shutdown_complete(),
             EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => self.on_turn_diff(unified_diff),
             EventMsg::DeprecationNotice(ev) => self.on_deprecation_notice(ev),
@@ -1824,6 +1891,7 @@ impl ChatWidget {
             }
             EventMsg::UndoStarted(ev) => self.on_undo_started(ev),
             EventMsg::UndoCompleted(ev) => self.on_undo_completed(ev),
+            EventMsg::CheckpointCreated(ev) => self.on_checkpoint_created(ev),
             EventMsg::StreamError(StreamErrorEvent { message, .. }) => {
                 self.on_stream_error(message)
             }
@@ -1843,6 +1911,7 @@ impl ChatWidget {
             | EventMsg::AgentMessageContentDelta(_)
             | EventMsg::ReasoningContentDelta(_)
             | EventMsg::ListModelsResponse(_)
+            | EventMsg::DryRunReport(_)
             | EventMsg::ReasoningRawContentDelta(_) => {}
         }
     }
@@ -2915,6 +2984,11 @@ impl ChatWidget {
         self.bottom_pane.set_custom_prompts(ev.custom_prompts);
     }
 
+    fn on_pinned_files(&mut self, ev: PinnedFilesEvent) {
+        self.add_to_history(history_cell::new_pinned_files_output(&ev.pins));
+        self.request_redraw();
+    }
+
     pub(crate) fn open_review_popup(&mut self) {
         let mut items: Vec<SelectionItem> = Vec::new();
 
@@ -3050,6 +3124,53 @@ impl ChatWidget {
         });
     }
 
+    pub(crate) fn show_checkpoint_prompt(&mut self) {
+        let tx = self.app_event_tx.clone();
+        let view = CustomPromptView::new(
+            "Name this checkpoint".to_string(),
+            "Type a name like before-refactor and press Enter".to_string(),
+            None,
+            Box::new(move |name: String| {
+                let name = name.trim().to_string();
+                if name.is_empty() {
+                    return;
+                }
+                tx.send(AppEvent::CodexOp(Op::Checkpoint { name }));
+            }),
+        );
+        self.bottom_pane.show_view(Box::new(view));
+    }
+
+    pub(crate) fn show_pin_prompt(&mut self) {
+        let tx = self.app_event_tx.clone();
+        let view = CustomPromptView::new(
+            "Pin a file or directory".to_string(),
+            "Type a path like docs/architecture.md and press Enter".to_string(),
+            None,
+            Box::new(move |path: String| {
+                tx.send(AppEvent::CodexOp(Op::Pin {
+                    path: PathBuf::from(path),
+                }));
+            }),
+        );
+        self.bottom_pane.show_view(Box::new(view));
+    }
+
+    pub(crate) fn show_unpin_prompt(&mut self) {
+        let tx = self.app_event_tx.clone();
+        let view = CustomPromptView::new(
+            "Unpin a file or directory".to_string(),
+            "Type the path as it was pinned and press Enter".to_string(),
+            None,
+            Box::new(move |path: String| {
+                tx.send(AppEvent::CodexOp(Op::Unpin {
+                    path: PathBuf::from(path),
+                }));
+            }),
+        );
+        self.bottom_pane.show_view(Box::new(view));
+    }
+
     pub(crate) fn show_review_custom_prompt(&mut self) {
         let tx = self.app_event_tx.clone();
         let view = CustomPromptView::new(
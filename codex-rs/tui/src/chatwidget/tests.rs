@@ -15,6 +15,8 @@ use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningDeltaEvent;
 use codex_core::protocol::AgentReasoningEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
+use codex_core::protocol::AuthSwitchReason;
+use codex_core::protocol::AuthSwitchedEvent;
 use codex_core::protocol::BackgroundEventEvent;
 use codex_core::protocol::CreditsSnapshot;
 use codex_core::protocol::Event;
@@ -89,6 +91,7 @@ fn snapshot(percent: f64) -> RateLimitSnapshot {
         }),
         secondary: None,
         credits: None,
+        request_limits: None,
     }
 }
 
@@ -236,6 +239,7 @@ fn review_restores_context_window_indicator() {
         msg: EventMsg::TokenCount(TokenCountEvent {
             info: Some(make_token_info(pre_review_tokens, context_window)),
             rate_limits: None,
+            cost: None,
         }),
     });
     assert_eq!(chat.bottom_pane.context_window_percent(), Some(30));
@@ -255,6 +259,7 @@ fn review_restores_context_window_indicator() {
         msg: EventMsg::TokenCount(TokenCountEvent {
             info: Some(make_token_info(review_tokens, context_window)),
             rate_limits: None,
+            cost: None,
         }),
     });
     assert_eq!(chat.bottom_pane.context_window_percent(), Some(97));
@@ -284,6 +289,7 @@ fn token_count_none_resets_context_indicator() {
         msg: EventMsg::TokenCount(TokenCountEvent {
             info: Some(make_token_info(pre_compact_tokens, context_window)),
             rate_limits: None,
+            cost: None,
         }),
     });
     assert_eq!(chat.bottom_pane.context_window_percent(), Some(30));
@@ -293,6 +299,7 @@ fn token_count_none_resets_context_indicator() {
         msg: EventMsg::TokenCount(TokenCountEvent {
             info: None,
             rate_limits: None,
+            cost: None,
         }),
     });
     assert_eq!(chat.bottom_pane.context_window_percent(), None);
@@ -323,6 +330,7 @@ fn context_indicator_shows_used_tokens_when_window_unknown() {
         msg: EventMsg::TokenCount(TokenCountEvent {
             info: Some(token_info),
             rate_limits: None,
+            cost: None,
         }),
     });
 
@@ -537,6 +545,7 @@ fn rate_limit_snapshot_keeps_prior_credits_when_missing_from_headers() {
             unlimited: false,
             balance: Some("17.5".to_string()),
         }),
+        request_limits: None,
     }));
     let initial_balance = chat
         .rate_limit_snapshot
@@ -553,6 +562,7 @@ fn rate_limit_snapshot_keeps_prior_credits_when_missing_from_headers() {
         }),
         secondary: None,
         credits: None,
+        request_limits: None,
     }));
 
     let display = chat
@@ -868,6 +878,7 @@ fn end_exec(
             exit_code,
             duration: std::time::Duration::from_millis(5),
             formatted_output: aggregated,
+            sandbox_denied: false,
         }),
     });
 }
@@ -1497,6 +1508,7 @@ fn interrupted_turn_error_message_snapshot() {
         id: "task-1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            auth: None,
         }),
     });
 
@@ -2189,6 +2201,7 @@ fn ui_snapshots_small_heights_task_running() {
         id: "task-1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            auth: None,
         }),
     });
     chat.handle_codex_event(Event {
@@ -2220,6 +2233,7 @@ fn status_widget_and_approval_modal_snapshot() {
         id: "task-1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            auth: None,
         }),
     });
     // Provide a deterministic header for the status line.
@@ -2267,6 +2281,7 @@ fn status_widget_active_snapshot() {
         id: "task-1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            auth: None,
         }),
     });
     // Provide a deterministic header via a bold reasoning chunk.
@@ -2810,6 +2825,27 @@ fn warning_event_adds_warning_history_cell() {
     );
 }
 
+#[test]
+fn auth_switched_event_adds_warning_history_cell() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
+    chat.handle_codex_event(Event {
+        id: "sub-1".into(),
+        msg: EventMsg::AuthSwitched(AuthSwitchedEvent {
+            from: "API key ending in 1111".to_string(),
+            to: "API key ending in 2222".to_string(),
+            reason: AuthSwitchReason::RateLimited,
+        }),
+    });
+
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1, "expected one warning history cell");
+    let rendered = lines_to_single_string(&cells[0]);
+    assert!(
+        rendered.contains("Switched from API key ending in 1111"),
+        "auth switch cell missing content: {rendered}"
+    );
+}
+
 #[test]
 fn multiple_agent_messages_in_single_turn_emit_multiple_headers() {
     let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
@@ -2819,6 +2855,7 @@ fn multiple_agent_messages_in_single_turn_emit_multiple_headers() {
         id: "s1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            auth: None,
         }),
     });
 
@@ -3007,12 +3044,14 @@ fn chatwidget_exec_and_status_layout_vt100_snapshot() {
             exit_code: 0,
             duration: std::time::Duration::from_millis(16000),
             formatted_output: String::new(),
+            sandbox_denied: false,
         }),
     });
     chat.handle_codex_event(Event {
         id: "t1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            auth: None,
         }),
     });
     chat.handle_codex_event(Event {
@@ -3057,6 +3096,7 @@ fn chatwidget_markdown_code_blocks_vt100_snapshot() {
         id: "t1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            auth: None,
         }),
     });
     // Build a vt100 visual from the history insertions only (no UI overlay)
@@ -3145,6 +3185,7 @@ fn chatwidget_tall() {
         id: "t1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            auth: None,
         }),
     });
     for i in 0..30 {
@@ -2,6 +2,8 @@ use clap::Parser;
 use clap::ValueHint;
 use codex_common::ApprovalModeCliArg;
 use codex_common::CliConfigOverrides;
+use codex_core::config::types::SystemPrompt;
+use codex_core::config::types::SystemPromptMode;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
@@ -32,6 +34,11 @@ pub struct Cli {
     #[clap(skip)]
     pub resume_show_all: bool,
 
+    /// Internal: fork the session named by `resume_session_id` after this
+    /// many turns instead of resuming it. Set by `codex fork`.
+    #[clap(skip)]
+    pub fork_at_turn: Option<usize>,
+
     /// Model the agent should use.
     #[arg(long, short = 'm')]
     pub model: Option<String>,
@@ -85,6 +92,38 @@ pub struct Cli {
     #[arg(long = "add-dir", value_name = "DIR", value_hint = ValueHint::DirPath)]
     pub add_dir: Vec<PathBuf>,
 
+    /// A directory the session spans, with its own AGENTS.md and git
+    /// repository. Repeat for each root; the first is the working directory
+    /// unless --cd is given.
+    #[arg(long = "root", value_name = "DIR", value_hint = ValueHint::DirPath)]
+    pub root: Vec<PathBuf>,
+
+    /// Markdown file whose contents replace the built-in system prompt for
+    /// this session. Takes precedence over `system_prompt` in config.toml.
+    #[arg(long = "system-prompt-file", value_name = "FILE")]
+    pub system_prompt_file: Option<PathBuf>,
+
+    /// Add the `--system-prompt-file` instructions after the built-in system
+    /// prompt instead of replacing it.
+    #[arg(long = "append-system-prompt", requires = "system_prompt_file")]
+    pub append_system_prompt: bool,
+
     #[clap(skip)]
     pub config_overrides: CliConfigOverrides,
 }
+
+impl Cli {
+    /// The `system_prompt` override given by `--system-prompt-file`.
+    pub fn system_prompt(&self) -> Option<SystemPrompt> {
+        let file = self.system_prompt_file.clone()?;
+        Some(SystemPrompt {
+            text: None,
+            file: Some(file),
+            mode: if self.append_system_prompt {
+                SystemPromptMode::Append
+            } else {
+                SystemPromptMode::Replace
+            },
+        })
+    }
+}
@@ -0,0 +1,630 @@
+//! Form-based editor behind `codex config edit --tui`.
+//!
+//! Each field maps to one key in config.toml. Only the fields the user
+//! changes are written back, through [`apply_to_str`], so comments and layout
+//! elsewhere in the file survive.
+
+use std::io;
+
+use codex_core::built_in_model_providers;
+use codex_core::config::ConfigToml;
+use codex_core::config::edit::ConfigEdit;
+use codex_core::config::edit::apply_to_str;
+use crossterm::event::Event;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyEventKind;
+use crossterm::event::KeyModifiers;
+use crossterm::event::{self};
+use crossterm::execute;
+use crossterm::terminal::EnterAlternateScreen;
+use crossterm::terminal::LeaveAlternateScreen;
+use crossterm::terminal::disable_raw_mode;
+use crossterm::terminal::enable_raw_mode;
+use ratatui::Terminal;
+use ratatui::backend::CrosstermBackend;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::layout::Rect;
+use ratatui::prelude::*;
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Widget;
+use ratatui::widgets::WidgetRef;
+use ratatui::widgets::Wrap;
+
+const REASONING_EFFORTS: &[&str] = &["none", "minimal", "low", "medium", "high", "xhigh"];
+const OSS_PROVIDERS: &[&str] = &["lmstudio", "ollama"];
+const SANDBOX_MODES: &[&str] = &["read-only", "workspace-write", "danger-full-access"];
+const APPROVAL_POLICIES: &[&str] = &["untrusted", "on-failure", "on-request", "never"];
+
+#[derive(Clone, Debug, PartialEq)]
+enum FieldValue {
+    Str(String),
+    Bool(bool),
+}
+
+enum FieldKind {
+    Text,
+    /// Cycles through the options and back to unset.
+    Choice(Vec<String>),
+    /// `default` is what Codex uses while the key is unset.
+    Bool {
+        default: bool,
+    },
+}
+
+struct Field {
+    group: &'static str,
+    segments: Vec<String>,
+    doc: String,
+    kind: FieldKind,
+    initial: Option<FieldValue>,
+    value: Option<FieldValue>,
+}
+
+impl Field {
+    fn new(
+        document: &toml::Value,
+        group: &'static str,
+        segments: &[&str],
+        doc: impl Into<String>,
+        kind: FieldKind,
+    ) -> Self {
+        let segments: Vec<String> = segments.iter().map(ToString::to_string).collect();
+        let initial = lookup(document, &segments).map(|value| match value {
+            toml::Value::String(s) => FieldValue::Str(s.clone()),
+            toml::Value::Boolean(b) => FieldValue::Bool(*b),
+            other => FieldValue::Str(other.to_string()),
+        });
+        Self {
+            group,
+            segments,
+            doc: doc.into(),
+            kind,
+            value: initial.clone(),
+            initial,
+        }
+    }
+
+    fn key(&self) -> String {
+        self.segments.join(".")
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.value != self.initial
+    }
+
+    fn display_value(&self) -> Span<'static> {
+        match (&self.value, &self.kind) {
+            (Some(FieldValue::Str(s)), _) => Span::from(format!("\"{s}\"")),
+            (Some(FieldValue::Bool(b)), _) => Span::from(b.to_string()),
+            (None, FieldKind::Bool { default }) => format!("{default} (default)").dim(),
+            (None, _) => "(unset)".dim(),
+        }
+    }
+
+    /// Moves a choice or toggles a bool; text fields are edited with Enter.
+    fn step(&mut self, forward: bool) {
+        match &self.kind {
+            FieldKind::Text => {}
+            FieldKind::Bool { default } => {
+                let current = match self.value {
+                    Some(FieldValue::Bool(b)) => b,
+                    _ => *default,
+                };
+                self.value = Some(FieldValue::Bool(!current));
+            }
+            FieldKind::Choice(options) => {
+                // Position 0 is "unset", followed by each option.
+                let len = options.len() + 1;
+                let current = match &self.value {
+                    Some(FieldValue::Str(s)) => options
+                        .iter()
+                        .position(|option| option == s)
+                        .map_or(0, |idx| idx + 1),
+                    _ => 0,
+                };
+                let next = if forward {
+                    (current + 1) % len
+                } else {
+                    (current + len - 1) % len
+                };
+                self.value = (next > 0).then(|| FieldValue::Str(options[next - 1].clone()));
+            }
+        }
+    }
+
+    fn edit(&self) -> Option<ConfigEdit> {
+        if !self.is_dirty() {
+            return None;
+        }
+        let segments = self.segments.clone();
+        Some(match &self.value {
+            Some(FieldValue::Str(s)) => ConfigEdit::SetPath {
+                segments,
+                value: toml_edit::value(s.as_str()),
+            },
+            Some(FieldValue::Bool(b)) => ConfigEdit::SetPath {
+                segments,
+                value: toml_edit::value(*b),
+            },
+            None => ConfigEdit::ClearPath { segments },
+        })
+    }
+}
+
+fn lookup<'a>(document: &'a toml::Value, segments: &[String]) -> Option<&'a toml::Value> {
+    segments
+        .iter()
+        .try_fold(document, |value, segment| value.get(segment.as_str()))
+}
+
+fn table_keys(document: &toml::Value, key: &str) -> Vec<String> {
+    let mut keys: Vec<String> = document
+        .get(key)
+        .and_then(toml::Value::as_table)
+        .map(|table| table.keys().cloned().collect())
+        .unwrap_or_default();
+    keys.sort();
+    keys
+}
+
+fn validate_text(field: &Field, text: &str) -> Result<(), String> {
+    if field.segments == ["model"] && text.chars().any(char::is_whitespace) {
+        return Err("model names cannot contain whitespace".to_string());
+    }
+    Ok(())
+}
+
+enum Outcome {
+    Save(String),
+    Cancel,
+}
+
+struct ConfigEditor {
+    original: String,
+    fields: Vec<Field>,
+    selected: usize,
+    /// Buffer for the text field being edited, if any.
+    editing: Option<String>,
+    error: Option<String>,
+    confirm_discard: bool,
+}
+
+impl ConfigEditor {
+    fn new(original: &str) -> io::Result<Self> {
+        let document: toml::Value = toml::from_str(original).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("config.toml does not parse, fix it with `codex config edit`: {err}"),
+            )
+        })?;
+
+        let mut providers: Vec<String> = built_in_model_providers().into_keys().collect();
+        for name in table_keys(&document, "model_providers") {
+            if !providers.contains(&name) {
+                providers.push(name);
+            }
+        }
+        providers.sort();
+
+        let mut fields = vec![
+            Field::new(
+                &document,
+                "Model",
+                &["model"],
+                "Model used for new sessions, for example `gpt-5.1-codex`. Leave empty to use the built-in default.",
+                FieldKind::Text,
+            ),
+            Field::new(
+                &document,
+                "Model",
+                &["model_reasoning_effort"],
+                "Reasoning effort for models that support it. Unset uses the model's default.",
+                FieldKind::Choice(REASONING_EFFORTS.iter().map(ToString::to_string).collect()),
+            ),
+            Field::new(
+                &document,
+                "Sandbox",
+                &["sandbox_mode"],
+                "What commands the agent runs may touch. `read-only` blocks writes, `workspace-write` allows writes inside the workspace, `danger-full-access` turns the sandbox off.",
+                FieldKind::Choice(SANDBOX_MODES.iter().map(ToString::to_string).collect()),
+            ),
+            Field::new(
+                &document,
+                "Sandbox",
+                &["sandbox_workspace_write", "network_access"],
+                "Allow network access for commands when `sandbox_mode` is `workspace-write`.",
+                FieldKind::Bool { default: false },
+            ),
+            Field::new(
+                &document,
+                "Approvals",
+                &["approval_policy"],
+                "When Codex asks before running a command: `untrusted` for anything not known safe, `on-failure` after a sandboxed command fails, `on-request` when the model asks, `never` not at all.",
+                FieldKind::Choice(APPROVAL_POLICIES.iter().map(ToString::to_string).collect()),
+            ),
+            Field::new(
+                &document,
+                "Providers",
+                &["model_provider"],
+                "Entry from `[model_providers]`, or a built-in provider, that serves `model`. Unset means `openai`.",
+                FieldKind::Choice(providers),
+            ),
+            Field::new(
+                &document,
+                "Providers",
+                &["oss_provider"],
+                "Local server used by `--oss`. Unset asks on first use.",
+                FieldKind::Choice(OSS_PROVIDERS.iter().map(ToString::to_string).collect()),
+            ),
+        ];
+        for name in table_keys(&document, "mcp_servers") {
+            fields.push(Field::new(
+                &document,
+                "MCP servers",
+                &["mcp_servers", name.as_str(), "enabled"],
+                format!(
+                    "Start the `{name}` MCP server with each session. Turn it off to keep its definition without launching it."
+                ),
+                FieldKind::Bool { default: true },
+            ));
+        }
+
+        Ok(Self {
+            original: original.to_string(),
+            fields,
+            selected: 0,
+            editing: None,
+            error: None,
+            confirm_discard: false,
+        })
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.fields.iter().any(Field::is_dirty)
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) -> Option<Outcome> {
+        if key.kind != KeyEventKind::Press {
+            return None;
+        }
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Some(Outcome::Cancel);
+        }
+        if self.editing.is_some() {
+            self.handle_text_key(key);
+            return None;
+        }
+
+        let confirm_discard = std::mem::take(&mut self.confirm_discard);
+        self.error = None;
+        let field = &mut self.fields[self.selected];
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.fields.len() - 1);
+            }
+            KeyCode::Left => field.step(false),
+            KeyCode::Right => field.step(true),
+            KeyCode::Enter | KeyCode::Char(' ') => match field.kind {
+                FieldKind::Text => {
+                    self.editing = Some(match &field.value {
+                        Some(FieldValue::Str(s)) => s.clone(),
+                        _ => String::new(),
+                    });
+                }
+                _ => field.step(true),
+            },
+            KeyCode::Backspace | KeyCode::Delete => field.value = None,
+            KeyCode::Char('s') => return self.save(),
+            KeyCode::Esc | KeyCode::Char('q') => {
+                if !self.is_dirty() || confirm_discard {
+                    return Some(Outcome::Cancel);
+                }
+                self.confirm_discard = true;
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn handle_text_key(&mut self, key: KeyEvent) {
+        let Some(buffer) = self.editing.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => buffer.push(c),
+            KeyCode::Backspace => {
+                buffer.pop();
+            }
+            KeyCode::Esc => {
+                self.editing = None;
+                self.error = None;
+            }
+            KeyCode::Enter => {
+                let text = buffer.trim().to_string();
+                let field = &mut self.fields[self.selected];
+                if let Err(err) = validate_text(field, &text) {
+                    self.error = Some(err);
+                    return;
+                }
+                field.value = (!text.is_empty()).then_some(FieldValue::Str(text));
+                self.editing = None;
+                self.error = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns `None` and shows the error when the result would not load.
+    fn save(&mut self) -> Option<Outcome> {
+        match self.render_document() {
+            Ok(None) => Some(Outcome::Cancel),
+            Ok(Some(contents)) => Some(Outcome::Save(contents)),
+            Err(err) => {
+                self.error = Some(err);
+                None
+            }
+        }
+    }
+
+    fn render_document(&self) -> Result<Option<String>, String> {
+        let edits: Vec<ConfigEdit> = self.fields.iter().filter_map(Field::edit).collect();
+        let Some(contents) =
+            apply_to_str(&self.original, None, &edits).map_err(|err| err.to_string())?
+        else {
+            return Ok(None);
+        };
+        toml::from_str::<ConfigToml>(&contents)
+            .map_err(|err| format!("config.toml would be invalid: {err}"))?;
+        Ok(Some(contents))
+    }
+
+    fn list_lines(&self) -> (Vec<Line<'static>>, usize) {
+        let key_width = self
+            .fields
+            .iter()
+            .map(|field| field.key().len())
+            .max()
+            .unwrap_or(0);
+        let mut lines = Vec::new();
+        let mut selected_line = 0;
+        let mut group = "";
+        for (idx, field) in self.fields.iter().enumerate() {
+            if field.group != group {
+                if !lines.is_empty() {
+                    lines.push(Line::from(""));
+                }
+                lines.push(Line::from(field.group.bold()));
+                group = field.group;
+            }
+            let marker = if field.is_dirty() { "* " } else { "  " };
+            let value = match &self.editing {
+                Some(buffer) if idx == self.selected => Span::from(format!("{buffer}▏")).cyan(),
+                _ => field.display_value(),
+            };
+            let mut line = Line::from(vec![
+                marker.into(),
+                format!("{:<key_width$}  ", field.key()).into(),
+                value,
+            ]);
+            if idx == self.selected {
+                selected_line = lines.len();
+                line = line.style(Style::new().reversed());
+            }
+            lines.push(line);
+        }
+        (lines, selected_line)
+    }
+
+    fn footer_lines(&self) -> Vec<Line<'static>> {
+        let field = &self.fields[self.selected];
+        let mut lines = vec![Line::from(field.doc.clone()).italic().dim()];
+        if let Some(error) = &self.error {
+            lines.push(Line::from(error.clone()).red());
+        } else if self.confirm_discard {
+            lines.push(
+                Line::from("Unsaved changes. Press q again to discard them, or s to save.")
+                    .magenta(),
+            );
+        }
+        lines
+    }
+}
+
+impl WidgetRef for &ConfigEditor {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let [title_area, list_area, doc_area, hint_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Min(1),
+            Constraint::Length(4),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        Line::from(vec!["Edit ".into(), "config.toml".bold()]).render(title_area, buf);
+
+        let (lines, selected_line) = self.list_lines();
+        let scroll = selected_line.saturating_sub(list_area.height.saturating_sub(1) as usize);
+        Paragraph::new(lines)
+            .scroll((scroll as u16, 0))
+            .render(list_area, buf);
+
+        Paragraph::new(self.footer_lines())
+            .wrap(Wrap { trim: false })
+            .render(doc_area, buf);
+
+        let hint = if self.editing.is_some() {
+            "enter keep • esc cancel"
+        } else {
+            "↑/↓ select • ←/→ change • enter edit • backspace unset • s save • q quit"
+        };
+        Line::from(hint).dim().render(hint_area, buf);
+    }
+}
+
+/// Shows the form for `original` and returns the contents to write, or `None`
+/// when the user quit or changed nothing.
+pub fn run_config_editor(original: &str) -> io::Result<Option<String>> {
+    let mut editor = ConfigEditor::new(original)?;
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    let result = loop {
+        if let Err(err) = terminal.draw(|f| {
+            (&editor).render_ref(f.area(), f.buffer_mut());
+        }) {
+            break Err(err);
+        }
+
+        match event::read() {
+            Ok(Event::Key(key_event)) => match editor.handle_key_event(key_event) {
+                Some(Outcome::Save(contents)) => break Ok(Some(contents)),
+                Some(Outcome::Cancel) => break Ok(None),
+                None => {}
+            },
+            Ok(_) => {}
+            Err(err) => break Err(err),
+        }
+    };
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
+    use ratatui::backend::TestBackend;
+
+    fn press(editor: &mut ConfigEditor, code: KeyCode) -> Option<Outcome> {
+        editor.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn select(editor: &mut ConfigEditor, key: &str) {
+        editor.selected = editor
+            .fields
+            .iter()
+            .position(|field| field.key() == key)
+            .unwrap_or_else(|| panic!("no field {key}"));
+    }
+
+    fn snapshot_editor(name: &str, editor: &ConfigEditor) {
+        let mut terminal = Terminal::new(TestBackend::new(100, 24)).expect("terminal");
+        terminal
+            .draw(|f| editor.render_ref(f.area(), f.buffer_mut()))
+            .expect("draw");
+        assert_snapshot!(name, terminal.backend());
+    }
+
+    fn saved(outcome: Option<Outcome>) -> String {
+        match outcome {
+            Some(Outcome::Save(contents)) => contents,
+            _ => panic!("expected the form to save"),
+        }
+    }
+
+    #[test]
+    fn only_changed_fields_are_written() {
+        let original =
+            "# keep me\nmodel = \"gpt-5.1\"\n\n[mcp_servers.docs]\ncommand = \"docs-mcp\"\n";
+        let mut editor = ConfigEditor::new(original).expect("editor");
+
+        select(&mut editor, "approval_policy");
+        press(&mut editor, KeyCode::Right);
+        press(&mut editor, KeyCode::Right);
+        select(&mut editor, "mcp_servers.docs.enabled");
+        press(&mut editor, KeyCode::Enter);
+
+        assert_eq!(
+            saved(press(&mut editor, KeyCode::Char('s'))),
+            "# keep me\nmodel = \"gpt-5.1\"\napproval_policy = \"on-failure\"\n\n[mcp_servers.docs]\ncommand = \"docs-mcp\"\nenabled = false\n"
+        );
+    }
+
+    #[test]
+    fn text_fields_are_validated_and_cleared_when_empty() {
+        let mut editor = ConfigEditor::new("model = \"gpt-5.1\"\n").expect("editor");
+        select(&mut editor, "model");
+
+        press(&mut editor, KeyCode::Enter);
+        press(&mut editor, KeyCode::Char(' '));
+        press(&mut editor, KeyCode::Char('x'));
+        press(&mut editor, KeyCode::Enter);
+        assert!(editor.error.is_some());
+        assert!(editor.editing.is_some());
+
+        for _ in 0.."gpt-5.1 x".len() {
+            press(&mut editor, KeyCode::Backspace);
+        }
+        press(&mut editor, KeyCode::Enter);
+        assert_eq!(editor.editing, None);
+        assert_eq!(saved(press(&mut editor, KeyCode::Char('s'))), "");
+    }
+
+    #[test]
+    fn quitting_with_changes_asks_first() {
+        let mut editor = ConfigEditor::new("").expect("editor");
+        select(&mut editor, "sandbox_mode");
+        press(&mut editor, KeyCode::Right);
+
+        assert!(press(&mut editor, KeyCode::Char('q')).is_none());
+        assert!(matches!(
+            press(&mut editor, KeyCode::Char('q')),
+            Some(Outcome::Cancel)
+        ));
+    }
+
+    #[test]
+    fn form_snapshot() {
+        let original = "model = \"gpt-5.1\"\n\n[mcp_servers.docs]\ncommand = \"docs-mcp\"\n";
+        let mut editor = ConfigEditor::new(original).expect("editor");
+        select(&mut editor, "approval_policy");
+        press(&mut editor, KeyCode::Right);
+        select(&mut editor, "oss_provider");
+
+        snapshot_editor("config_editor_form", &editor);
+    }
+
+    #[test]
+    fn validation_error_snapshot() {
+        let mut editor = ConfigEditor::new("model = \"gpt-5.1\"\n").expect("editor");
+        select(&mut editor, "model");
+        press(&mut editor, KeyCode::Enter);
+        press(&mut editor, KeyCode::Char(' '));
+        press(&mut editor, KeyCode::Char('x'));
+        press(&mut editor, KeyCode::Enter);
+
+        snapshot_editor("config_editor_validation_error", &editor);
+    }
+
+    #[test]
+    fn quit_prompt_snapshot() {
+        let mut editor = ConfigEditor::new("").expect("editor");
+        select(&mut editor, "oss_provider");
+        press(&mut editor, KeyCode::Right);
+        press(&mut editor, KeyCode::Char('q'));
+
+        snapshot_editor("config_editor_quit_prompt", &editor);
+    }
+
+    #[test]
+    fn unparsable_config_is_rejected() {
+        assert!(ConfigEditor::new("model = \n").is_err());
+    }
+}
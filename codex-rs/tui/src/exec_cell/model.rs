@@ -4,6 +4,10 @@ use std::time::Instant;
 use codex_core::protocol::ExecCommandSource;
 use codex_protocol::parse_command::ParsedCommand;
 
+/// Most bytes of streamed output kept per running call; only the last few
+/// lines are shown.
+const MAX_LIVE_OUTPUT_BYTES: usize = 64 * 1024;
+
 #[derive(Clone, Debug, Default)]
 pub(crate) struct CommandOutput {
     pub(crate) exit_code: i32,
@@ -23,6 +27,8 @@ pub(crate) struct ExecCall {
     pub(crate) start_time: Option<Instant>,
     pub(crate) duration: Option<Duration>,
     pub(crate) interaction_input: Option<String>,
+    /// The tail of stdout and stderr streamed while the call runs.
+    pub(crate) live_output: Vec<u8>,
 }
 
 #[derive(Debug)]
@@ -56,6 +62,7 @@ impl ExecCell {
             start_time: Some(Instant::now()),
             duration: None,
             interaction_input,
+            live_output: Vec::new(),
         };
         if self.is_exploring_cell() && Self::is_exploring_call(&call) {
             Some(Self {
@@ -77,7 +84,32 @@ impl ExecCell {
             call.output = Some(output);
             call.duration = Some(duration);
             call.start_time = None;
+            call.live_output = Vec::new();
+        }
+    }
+
+    /// Records a chunk of output of the running call `call_id`. Returns
+    /// whether the cell has such a call.
+    pub(crate) fn append_live_output(&mut self, call_id: &str, chunk: &[u8]) -> bool {
+        let Some(call) = self
+            .calls
+            .iter_mut()
+            .rev()
+            .find(|c| c.call_id == call_id && c.output.is_none())
+        else {
+            return false;
+        };
+        call.live_output.extend_from_slice(chunk);
+        if call.live_output.len() > MAX_LIVE_OUTPUT_BYTES {
+            let excess = call.live_output.len() - MAX_LIVE_OUTPUT_BYTES;
+            // Drop whole lines so the kept tail does not start mid-character.
+            let cut = call.live_output[excess..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(excess, |pos| excess + pos + 1);
+            call.live_output.drain(..cut);
         }
+        true
     }
 
     pub(crate) fn should_flush(&self) -> bool {
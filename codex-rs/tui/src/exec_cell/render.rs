@@ -52,6 +52,7 @@ pub(crate) fn new_active_exec_command(
             start_time: Some(Instant::now()),
             duration: None,
             interaction_input,
+            live_output: Vec::new(),
         },
         animations_enabled,
     )
@@ -476,11 +477,48 @@ impl ExecCell {
                     ));
                 }
             }
+        } else if !call.live_output.is_empty() {
+            let display_limit = if call.is_user_shell_command() {
+                USER_SHELL_TOOL_CALL_MAX_LINES
+            } else {
+                layout.output_max_lines
+            };
+            let live_output = Self::live_output_lines(call, width, display_limit);
+            if !live_output.is_empty() {
+                lines.extend(prefix_lines(
+                    live_output,
+                    Span::from(layout.output_block.initial_prefix).dim(),
+                    Span::from(layout.output_block.subsequent_prefix),
+                ));
+            }
         }
 
         lines
     }
 
+    /// The last `max` screen lines of what a running call has printed so far.
+    fn live_output_lines(call: &ExecCall, width: u16, max: usize) -> Vec<Line<'static>> {
+        let text = String::from_utf8_lossy(&call.live_output);
+        let output_wrap_width = EXEC_DISPLAY_LAYOUT.output_block.wrap_width(width);
+        let output_opts =
+            RtOptions::new(output_wrap_width).word_splitter(WordSplitter::NoHyphenation);
+        let mut wrapped: Vec<Line<'static>> = Vec::new();
+        // Skip lines that cannot be shown anyway; a line is never shorter
+        // than one screen line.
+        let raw_lines: Vec<&str> = text.lines().collect();
+        for raw in &raw_lines[raw_lines.len().saturating_sub(max)..] {
+            // Progress bars redraw their line with `\r`; show its latest state.
+            let raw = raw.rsplit('\r').next().unwrap_or(raw);
+            let mut line = ansi_escape_line(raw);
+            line.spans.iter_mut().for_each(|span| {
+                span.style = span.style.add_modifier(Modifier::DIM);
+            });
+            push_owned_lines(&word_wrap_line(&line, output_opts.clone()), &mut wrapped);
+        }
+        let start = wrapped.len().saturating_sub(max);
+        wrapped.split_off(start)
+    }
+
     fn limit_lines_from_start(lines: &[Line<'static>], keep: usize) -> Vec<Line<'static>> {
         if lines.len() <= keep {
             return lines.to_vec();
@@ -669,6 +707,7 @@ mod tests {
             start_time: None,
             duration: None,
             interaction_input: None,
+            live_output: Vec::new(),
         };
 
         let cell = ExecCell::new(call, false);
@@ -692,4 +731,43 @@ mod tests {
             "expected at most {USER_SHELL_TOOL_CALL_MAX_LINES} screen lines of user shell output, got {output_screen_lines}",
         );
     }
+
+    #[test]
+    fn running_command_shows_tail_of_live_output() {
+        let mut cell = new_active_exec_command(
+            "call-id".to_string(),
+            vec!["bash".into(), "-lc".into(), "cargo test".into()],
+            Vec::new(),
+            ExecCommandSource::Agent,
+            None,
+            false,
+        );
+        assert!(cell.append_live_output("call-id", b"test a ... ok\ntest b ... ok\ntest c"));
+        assert!(cell.append_live_output("call-id", b" ... ok\ntest d ... ok\ntest e ... ok\n"));
+        assert!(cell.append_live_output("call-id", b"building 10%\rbuilding 50%\n"));
+        assert!(!cell.append_live_output("other-call", b"ignored\n"));
+
+        let rendered: Vec<String> = cell
+            .command_display_lines(80)
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect::<String>()
+            })
+            .collect();
+        insta::assert_snapshot!(rendered.join("\n"));
+
+        cell.complete_call(
+            "call-id",
+            CommandOutput {
+                exit_code: 0,
+                aggregated_output: "done\n".to_string(),
+                formatted_output: String::new(),
+            },
+            std::time::Duration::from_secs(1),
+        );
+        assert!(cell.iter_calls().all(|call| call.live_output.is_empty()));
+    }
 }
@@ -30,7 +30,9 @@ use codex_core::config::types::ReasoningSummaryFormat;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::McpAuthStatus;
 use codex_core::protocol::McpInvocation;
+use codex_core::protocol::PinnedFile;
 use codex_core::protocol::SessionConfiguredEvent;
+use codex_protocol::num_format::format_si_suffix;
 use codex_protocol::openai_models::ReasoningEffort as ReasoningEffortConfig;
 use codex_protocol::plan_tool::PlanItemArg;
 use codex_protocol::plan_tool::StepStatus;
@@ -1186,6 +1188,12 @@ pub(crate) fn new_mcp_tools_output(
                 http_headers,
                 env_http_headers,
                 ..
+            }
+            | McpServerTransportConfig::Sse {
+                url,
+                http_headers,
+                env_http_headers,
+                ..
             } => {
                 lines.push(vec!["    • URL: ".into(), url.clone().into()].into());
                 if let Some(headers) = http_headers.as_ref()
@@ -1280,6 +1288,50 @@ pub(crate) fn new_info_event(message: String, hint: Option<String>) -> PlainHist
     PlainHistoryCell { lines }
 }
 
+/// The pinned files and the tokens each of them adds to every request.
+pub(crate) fn new_pinned_files_output(pins: &[PinnedFile]) -> PlainHistoryCell {
+    if pins.is_empty() {
+        return new_info_event(
+            "Nothing is pinned.".to_string(),
+            Some("Pin a file or directory with /pin.".to_string()),
+        );
+    }
+    let total: u64 = pins.iter().map(|pin| pin.tokens).sum();
+    let mut lines: Vec<Line<'static>> = vec![
+        vec![
+            "• ".dim(),
+            format!(
+                "Pinned in context: about {} tokens per request",
+                format_si_suffix(total as i64)
+            )
+            .into(),
+        ]
+        .into(),
+    ];
+    for (i, pin) in pins.iter().enumerate() {
+        let prefix = if i == 0 { "  └ " } else { "    " };
+        let mut line: Vec<Span<'static>> = vec![
+            prefix.dim(),
+            pin.path.display().to_string().into(),
+            " ".into(),
+        ];
+        match &pin.error {
+            Some(error) => line.push(format!("not sent: {error}").red()),
+            None => {
+                let files = if pin.files == 1 {
+                    String::new()
+                } else {
+                    format!("{} files, ", pin.files)
+                };
+                let tokens = format_si_suffix(pin.tokens as i64);
+                line.push(format!("{files}{tokens} tokens").dim());
+            }
+        }
+        lines.push(line.into());
+    }
+    PlainHistoryCell { lines }
+}
+
 pub(crate) fn new_error_event(message: String) -> PlainHistoryCell {
     // Use a hair space (U+200A) to create a subtle, near-invisible separation
     // before the text. VS16 is intentionally omitted to keep spacing tighter
@@ -1542,6 +1594,34 @@ mod tests {
         render_lines(&cell.transcript_lines(u16::MAX))
     }
 
+    #[test]
+    fn pinned_files_output_lists_tokens_per_pin() {
+        let pin = |path: &str, files: usize, tokens: u64, error: Option<&str>| PinnedFile {
+            path: PathBuf::from(path),
+            files,
+            tokens,
+            error: error.map(str::to_string),
+        };
+        let cell = new_pinned_files_output(&[
+            pin("schema.sql", 1, 120, None),
+            pin("docs", 3, 30, None),
+            pin("gone.md", 0, 0, Some("cannot read gone.md")),
+        ]);
+        assert_eq!(
+            render_lines(&cell.display_lines(80)),
+            vec![
+                "• Pinned in context: about 150 tokens per request",
+                "  └ schema.sql 120 tokens",
+                "    docs 3 files, 30 tokens",
+                "    gone.md not sent: cannot read gone.md",
+            ]
+        );
+        assert_eq!(
+            render_lines(&new_pinned_files_output(&[]).display_lines(80)),
+            vec!["• Nothing is pinned. Pin a file or directory with /pin."]
+        );
+    }
+
     #[test]
     fn mcp_tools_output_masks_sensitive_values() {
         let mut config = test_config();
@@ -1916,6 +1996,7 @@ mod tests {
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
+                live_output: Vec::new(),
             },
             true,
         );
@@ -1943,6 +2024,7 @@ mod tests {
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
+                live_output: Vec::new(),
             },
             true,
         );
@@ -2012,6 +2094,7 @@ mod tests {
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
+                live_output: Vec::new(),
             },
             true,
         );
@@ -2036,6 +2119,7 @@ mod tests {
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
+                live_output: Vec::new(),
             },
             true,
         );
@@ -2062,6 +2146,7 @@ mod tests {
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
+                live_output: Vec::new(),
             },
             true,
         );
@@ -2086,6 +2171,7 @@ mod tests {
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
+                live_output: Vec::new(),
             },
             true,
         );
@@ -2109,6 +2195,7 @@ mod tests {
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
+                live_output: Vec::new(),
             },
             true,
         );
@@ -2133,6 +2220,7 @@ mod tests {
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
+                live_output: Vec::new(),
             },
             true,
         );
@@ -2157,6 +2245,7 @@ mod tests {
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
+                live_output: Vec::new(),
             },
             true,
         );
@@ -2207,6 +2296,7 @@ mod tests {
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
+                live_output: Vec::new(),
             },
             true,
         );
@@ -19,14 +19,19 @@ use codex_core::config::ConfigOverrides;
 use codex_core::config::find_codex_home;
 use codex_core::config::load_config_as_toml_with_cli_overrides;
 use codex_core::config::resolve_oss_provider;
+use codex_core::default_client::apply_privacy;
 use codex_core::find_conversation_path_by_id_str;
 use codex_core::get_platform_sandbox;
 use codex_core::protocol::AskForApproval;
+use codex_core::read_resumed_session_settings;
+use codex_core::redact::RedactingWriter;
 use codex_protocol::config_types::SandboxMode;
 use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
 use std::fs::OpenOptions;
+use std::path::Path;
 use std::path::PathBuf;
 use tracing::error;
+use tracing::warn;
 use tracing_appender::non_blocking;
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::filter::Targets;
@@ -43,6 +48,7 @@ mod chatwidget;
 mod cli;
 mod clipboard_paste;
 mod color;
+pub mod config_editor;
 pub mod custom_terminal;
 mod diff_render;
 mod exec_cell;
@@ -165,6 +171,7 @@ pub async fn run_main(
 
     let model_provider_override = if cli.oss {
         let resolved = resolve_oss_provider(
+            &codex_home,
             cli.oss_provider.as_deref(),
             &config_toml,
             cli.config_profile.clone(),
@@ -213,6 +220,7 @@ pub async fn run_main(
         config_profile: cli.config_profile.clone(),
         codex_linux_sandbox_exe,
         base_instructions: None,
+        system_prompt: cli.system_prompt(),
         developer_instructions: None,
         compact_prompt: None,
         include_apply_patch_tool: None,
@@ -220,9 +228,11 @@ pub async fn run_main(
         tools_web_search_request: None,
         experimental_sandbox_command_assessment: None,
         additional_writable_roots: additional_dirs,
+        roots: cli.root.clone(),
     };
 
     let config = load_config_or_exit(cli_kv_overrides.clone(), overrides.clone()).await;
+    apply_privacy(&config.privacy);
 
     if let Some(warning) = add_dir_warning_message(&cli.add_dir, &config.sandbox_policy) {
         #[allow(clippy::print_stderr)]
@@ -257,8 +267,8 @@ pub async fn run_main(
 
     let log_file = log_file_opts.open(log_dir.join("codex-tui.log"))?;
 
-    // Wrap file in non‑blocking writer.
-    let (non_blocking, _guard) = non_blocking(log_file);
+    // Wrap file in non‑blocking writer, keeping project API keys out of it.
+    let (non_blocking, _guard) = non_blocking(RedactingWriter::new(log_file));
 
     // use RUST_LOG env var, default to info for codex crates.
     let env_filter = || {
@@ -357,6 +367,8 @@ async fn run_ratatui_app(
         prev_hook(info);
     }));
     let mut terminal = tui::init()?;
+    // Restore the terminal on every exit path, including early returns via `?`.
+    let _restore_guard = RestoreTerminalOnDrop;
     terminal.clear()?;
 
     let mut tui = Tui::new(terminal);
@@ -369,12 +381,14 @@ async fn run_ratatui_app(
         if !skip_update_prompt {
             match update_prompt::run_update_prompt_if_needed(&mut tui, &initial_config).await? {
                 UpdatePromptOutcome::Continue => {}
-                UpdatePromptOutcome::RunUpdate(action) => {
+                UpdatePromptOutcome::RunUpdate(action, version) => {
                     crate::tui::restore()?;
                     return Ok(AppExitInfo {
                         token_usage: codex_core::protocol::TokenUsage::default(),
                         conversation_id: None,
                         update_action: Some(action),
+                        update_version: Some(version),
+                        exit_signal: None,
                     });
                 }
             }
@@ -414,6 +428,8 @@ async fn run_ratatui_app(
                 token_usage: codex_core::protocol::TokenUsage::default(),
                 conversation_id: None,
                 update_action: None,
+                update_version: None,
+                exit_signal: None,
             });
         }
         // if the user acknowledged windows or made an explicit decision ato trust the directory, reload the config accordingly
@@ -422,7 +438,7 @@ async fn run_ratatui_app(
             .map(|d| d == TrustDirectorySelection::Trust)
             .unwrap_or(false)
         {
-            load_config_or_exit(cli_kv_overrides, overrides).await
+            load_config_or_exit(cli_kv_overrides.clone(), overrides.clone()).await
         } else {
             initial_config
         }
@@ -433,7 +449,10 @@ async fn run_ratatui_app(
     // Determine resume behavior: explicit id, then resume last, then picker.
     let resume_selection = if let Some(id_str) = cli.resume_session_id.as_deref() {
         match find_conversation_path_by_id_str(&config.codex_home, id_str).await? {
-            Some(path) => resume_picker::ResumeSelection::Resume(path),
+            Some(path) => match cli.fork_at_turn {
+                Some(turns) => resume_picker::ResumeSelection::Fork { path, turns },
+                None => resume_picker::ResumeSelection::Resume(path),
+            },
             None => {
                 error!("Error finding conversation path: {id_str}");
                 restore();
@@ -449,6 +468,8 @@ async fn run_ratatui_app(
                     token_usage: codex_core::protocol::TokenUsage::default(),
                     conversation_id: None,
                     update_action: None,
+                    update_version: None,
+                    exit_signal: None,
                 });
             }
         }
@@ -477,6 +498,7 @@ async fn run_ratatui_app(
             &config.codex_home,
             &config.model_provider_id,
             cli.resume_show_all,
+            config.tui_timestamp_format,
         )
         .await?
         {
@@ -487,6 +509,8 @@ async fn run_ratatui_app(
                     token_usage: codex_core::protocol::TokenUsage::default(),
                     conversation_id: None,
                     update_action: None,
+                    update_version: None,
+                    exit_signal: None,
                 });
             }
             other => other,
@@ -495,6 +519,14 @@ async fn run_ratatui_app(
         resume_picker::ResumeSelection::StartFresh
     };
 
+    let config = match &resume_selection {
+        resume_picker::ResumeSelection::Resume(path)
+        | resume_picker::ResumeSelection::Fork { path, .. } => {
+            restore_resumed_settings(path, config, &cli_kv_overrides, &overrides).await
+        }
+        _ => config,
+    };
+
     let Cli { prompt, images, .. } = cli;
 
     let app_result = App::run(
@@ -522,13 +554,21 @@ async fn run_ratatui_app(
     reason = "TUI should no longer be displayed, so we can write to stderr."
 )]
 fn restore() {
-    if let Err(err) = tui::restore() {
+    if let Err(err) = tui::restore_on_exit() {
         eprintln!(
             "failed to restore terminal. Run `reset` or restart your terminal to recover: {err}"
         );
     }
 }
 
+struct RestoreTerminalOnDrop;
+
+impl Drop for RestoreTerminalOnDrop {
+    fn drop(&mut self) {
+        let _ = tui::restore_on_exit();
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LoginStatus {
     AuthMode(AuthMode),
@@ -567,6 +607,48 @@ async fn load_config_or_exit(
     }
 }
 
+/// Reloads `config` with the working directory, model, and approval policy
+/// the resumed session last ran with, and restores its sandbox policy, so it
+/// continues where it left off. A setting given on the command line, as a
+/// flag or a `-c` override, keeps the value given.
+async fn restore_resumed_settings(
+    path: &Path,
+    config: Config,
+    cli_kv_overrides: &[(String, toml::Value)],
+    overrides: &ConfigOverrides,
+) -> Config {
+    let settings = match read_resumed_session_settings(path).await {
+        Ok(Some(settings)) => settings,
+        Ok(None) => return config,
+        Err(err) => {
+            warn!("failed to read settings of {}: {err}", path.display());
+            return config;
+        }
+    };
+    let overridden = |key: &str| cli_kv_overrides.iter().any(|(k, _)| k == key);
+
+    let mut overrides = overrides.clone();
+    // The directory may have been removed since; start where we are then.
+    if overrides.cwd.is_none() && settings.cwd.is_dir() {
+        overrides.cwd = Some(settings.cwd);
+    }
+    if overrides.model.is_none() && !overridden("model") {
+        overrides.model = settings.model;
+    }
+    if overrides.approval_policy.is_none() && !overridden("approval_policy") {
+        overrides.approval_policy = settings.approval_policy;
+    }
+    let sandbox_policy = settings
+        .sandbox_policy
+        .filter(|_| overrides.sandbox_mode.is_none() && !overridden("sandbox_mode"));
+
+    let mut config = load_config_or_exit(cli_kv_overrides.to_vec(), overrides).await;
+    if let Some(sandbox_policy) = sandbox_policy {
+        config.sandbox_policy = sandbox_policy;
+    }
+    config
+}
+
 /// Determine if user has configured a sandbox / approval policy,
 /// or if the current cwd project is already trusted. If not, we need to
 /// show the trust screen.
@@ -4,12 +4,15 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use chrono::DateTime;
+use chrono::Local;
+use chrono::SecondsFormat;
 use chrono::Utc;
 use codex_core::ConversationItem;
 use codex_core::ConversationsPage;
 use codex_core::Cursor;
 use codex_core::INTERACTIVE_SESSION_SOURCES;
 use codex_core::RolloutRecorder;
+use codex_core::config::types::TimestampFormat;
 use codex_protocol::items::TurnItem;
 use color_eyre::eyre::Result;
 use crossterm::event::KeyCode;
@@ -42,6 +45,11 @@ const LOAD_NEAR_THRESHOLD: usize = 5;
 pub enum ResumeSelection {
     StartFresh,
     Resume(PathBuf),
+    /// Start a new session from the first `turns` turns of the one at `path`.
+    Fork {
+        path: PathBuf,
+        turns: usize,
+    },
     Exit,
 }
 
@@ -72,6 +80,7 @@ pub async fn run_resume_picker(
     codex_home: &Path,
     default_provider: &str,
     show_all: bool,
+    timestamp_format: TimestampFormat,
 ) -> Result<ResumeSelection> {
     let alt = AltScreenGuard::enter(tui);
     let (bg_tx, bg_rx) = mpsc::unbounded_channel();
@@ -113,6 +122,7 @@ pub async fn run_resume_picker(
         show_all,
         filter_cwd,
     );
+    state.timestamp_format = timestamp_format;
     state.start_initial_load();
     state.request_frame();
 
@@ -189,6 +199,7 @@ struct PickerState {
     default_provider: String,
     show_all: bool,
     filter_cwd: Option<PathBuf>,
+    timestamp_format: TimestampFormat,
 }
 
 struct PaginationState {
@@ -282,6 +293,7 @@ impl PickerState {
             default_provider,
             show_all,
             filter_cwd,
+            timestamp_format: TimestampFormat::default(),
         }
     }
 
@@ -727,7 +739,8 @@ fn draw_picker(tui: &mut Tui, state: &PickerState) -> std::io::Result<()> {
         };
         frame.render_widget_ref(Line::from(q), search);
 
-        let metrics = calculate_column_metrics(&state.filtered_rows, state.show_all);
+        let metrics =
+            calculate_column_metrics(&state.filtered_rows, state.show_all, state.timestamp_format);
 
         // Column headers and list
         render_column_headers(frame, columns, &metrics);
@@ -933,11 +946,15 @@ fn human_time_ago(ts: DateTime<Utc>) -> String {
     }
 }
 
-fn format_updated_label(row: &Row) -> String {
-    match (row.updated_at, row.created_at) {
-        (Some(updated), _) => human_time_ago(updated),
-        (None, Some(created)) => human_time_ago(created),
-        (None, None) => "-".to_string(),
+fn format_updated_label(row: &Row, timestamp_format: TimestampFormat) -> String {
+    let Some(ts) = row.updated_at.or(row.created_at) else {
+        return "-".to_string();
+    };
+    match timestamp_format {
+        TimestampFormat::Auto | TimestampFormat::Relative => human_time_ago(ts),
+        TimestampFormat::Iso8601 => ts
+            .with_timezone(&Local)
+            .to_rfc3339_opts(SecondsFormat::Secs, false),
     }
 }
 
@@ -989,7 +1006,11 @@ struct ColumnMetrics {
     labels: Vec<(String, String, String)>,
 }
 
-fn calculate_column_metrics(rows: &[Row], include_cwd: bool) -> ColumnMetrics {
+fn calculate_column_metrics(
+    rows: &[Row],
+    include_cwd: bool,
+    timestamp_format: TimestampFormat,
+) -> ColumnMetrics {
     fn right_elide(s: &str, max: usize) -> String {
         if s.chars().count() <= max {
             return s.to_string();
@@ -1019,7 +1040,7 @@ fn calculate_column_metrics(rows: &[Row], include_cwd: bool) -> ColumnMetrics {
     };
 
     for row in rows {
-        let updated = format_updated_label(row);
+        let updated = format_updated_label(row, timestamp_format);
         let branch_raw = row.git_branch.clone().unwrap_or_default();
         let branch = right_elide(&branch_raw, 24);
         let cwd = if include_cwd {
@@ -1241,7 +1262,8 @@ mod tests {
         state.scroll_top = 0;
         state.update_view_rows(3);
 
-        let metrics = calculate_column_metrics(&state.filtered_rows, state.show_all);
+        let metrics =
+            calculate_column_metrics(&state.filtered_rows, state.show_all, state.timestamp_format);
 
         let width: u16 = 80;
         let height: u16 = 6;
@@ -1374,7 +1396,8 @@ mod tests {
         state.scroll_top = 0;
         state.update_view_rows(4);
 
-        let metrics = calculate_column_metrics(&state.filtered_rows, state.show_all);
+        let metrics =
+            calculate_column_metrics(&state.filtered_rows, state.show_all, state.timestamp_format);
 
         let width: u16 = 80;
         let height: u16 = 9;
@@ -21,8 +21,11 @@ pub enum SlashCommand {
     Init,
     Compact,
     Undo,
+    Checkpoint,
     Diff,
     Mention,
+    Pin,
+    Unpin,
     Status,
     Mcp,
     Logout,
@@ -43,10 +46,13 @@ impl SlashCommand {
             SlashCommand::Compact => "summarize conversation to prevent hitting the context limit",
             SlashCommand::Review => "review my current changes and find issues",
             SlashCommand::Resume => "resume a saved chat",
-            SlashCommand::Undo => "ask Codex to undo a turn",
+            SlashCommand::Undo => "undo the last turn and its file changes",
+            SlashCommand::Checkpoint => "save a named checkpoint to restore later",
             SlashCommand::Quit | SlashCommand::Exit => "exit Codex",
             SlashCommand::Diff => "show git diff (including untracked files)",
             SlashCommand::Mention => "mention a file",
+            SlashCommand::Pin => "keep a file or directory in context",
+            SlashCommand::Unpin => "stop keeping a pinned file in context",
             SlashCommand::Skills => "use skills to improve how Codex performs specific tasks",
             SlashCommand::Status => "show current session configuration and token usage",
             SlashCommand::Model => "choose what model and reasoning effort to use",
@@ -72,12 +78,15 @@ impl SlashCommand {
             | SlashCommand::Init
             | SlashCommand::Compact
             | SlashCommand::Undo
+            | SlashCommand::Checkpoint
             | SlashCommand::Model
             | SlashCommand::Approvals
             | SlashCommand::Review
             | SlashCommand::Logout => false,
             SlashCommand::Diff
             | SlashCommand::Mention
+            | SlashCommand::Pin
+            | SlashCommand::Unpin
             | SlashCommand::Skills
             | SlashCommand::Status
             | SlashCommand::Mcp
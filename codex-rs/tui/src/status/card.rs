@@ -59,6 +59,8 @@ struct StatusHistoryCell {
     approval: String,
     sandbox: String,
     agents_summary: String,
+    /// The project `.codex/config.toml` that chose the model and credentials.
+    project_config: Option<PathBuf>,
     account: Option<StatusAccountDisplay>,
     session_id: Option<String>,
     token_usage: StatusTokenUsageData,
@@ -136,6 +138,7 @@ impl StatusHistoryCell {
             approval,
             sandbox,
             agents_summary,
+            project_config: config.project_config.clone(),
             account,
             session_id,
             token_usage,
@@ -317,6 +320,9 @@ impl HistoryCell for StatusHistoryCell {
                 .collect();
         let mut seen: BTreeSet<String> = labels.iter().cloned().collect();
 
+        if self.project_config.is_some() {
+            push_label(&mut labels, &mut seen, "Config");
+        }
         if account_value.is_some() {
             push_label(&mut labels, &mut seen, "Account");
         }
@@ -364,6 +370,15 @@ impl HistoryCell for StatusHistoryCell {
         lines.push(formatter.line("Sandbox", vec![Span::from(self.sandbox.clone())]));
         lines.push(formatter.line("Agents.md", vec![Span::from(self.agents_summary.clone())]));
 
+        if let Some(project_config) = self.project_config.as_ref() {
+            lines.push(formatter.line(
+                "Config",
+                vec![
+                    Span::from(format_directory_display(project_config, Some(value_width))),
+                    Span::from(" (project: model and credentials)").dim(),
+                ],
+            ));
+        }
         if let Some(account_value) = account_value {
             lines.push(formatter.line("Account", vec![Span::from(account_value)]));
         }
@@ -2,9 +2,11 @@ use crate::exec_command::relativize_to_home;
 use crate::text_formatting;
 use chrono::DateTime;
 use chrono::Local;
+use chrono::SecondsFormat;
 use codex_app_server_protocol::AuthMode;
 use codex_core::AuthManager;
 use codex_core::config::Config;
+use codex_core::config::types::TimestampFormat;
 use codex_core::project_doc::discover_project_doc_paths;
 use std::path::Path;
 use unicode_width::UnicodeWidthStr;
@@ -169,6 +171,23 @@ pub(crate) fn format_reset_timestamp(dt: DateTime<Local>, captured_at: DateTime<
     }
 }
 
+pub(crate) fn format_reset_timestamp_as(
+    dt: DateTime<Local>,
+    captured_at: DateTime<Local>,
+    format: TimestampFormat,
+) -> String {
+    match format {
+        TimestampFormat::Auto => format_reset_timestamp(dt, captured_at),
+        TimestampFormat::Relative => format!("in {}", format_countdown(dt - captured_at)),
+        TimestampFormat::Iso8601 => dt.to_rfc3339_opts(SecondsFormat::Secs, false),
+    }
+}
+
+/// Compact countdown such as "2d 4h", "3h 12m", or "45m".
+pub(crate) fn format_countdown(remaining: chrono::Duration) -> String {
+    codex_core::rate_limits::format_countdown(remaining.num_seconds())
+}
+
 pub(crate) fn title_case(s: &str) -> String {
     if s.is_empty() {
         return String::new();
@@ -1,10 +1,11 @@
 use crate::chatwidget::get_limits_duration;
 
-use super::helpers::format_reset_timestamp;
+use super::helpers::format_reset_timestamp_as;
 use chrono::DateTime;
 use chrono::Duration as ChronoDuration;
 use chrono::Local;
 use chrono::Utc;
+use codex_core::config::types::TimestampFormat;
 use codex_core::protocol::CreditsSnapshot as CoreCreditsSnapshot;
 use codex_core::protocol::RateLimitSnapshot;
 use codex_core::protocol::RateLimitWindow;
@@ -45,12 +46,16 @@ pub(crate) struct RateLimitWindowDisplay {
 }
 
 impl RateLimitWindowDisplay {
-    fn from_window(window: &RateLimitWindow, captured_at: DateTime<Local>) -> Self {
+    fn from_window(
+        window: &RateLimitWindow,
+        captured_at: DateTime<Local>,
+        timestamp_format: TimestampFormat,
+    ) -> Self {
         let resets_at = window
             .resets_at
             .and_then(|seconds| DateTime::<Utc>::from_timestamp(seconds, 0))
             .map(|dt| dt.with_timezone(&Local))
-            .map(|dt| format_reset_timestamp(dt, captured_at));
+            .map(|dt| format_reset_timestamp_as(dt, captured_at, timestamp_format));
 
         Self {
             used_percent: window.used_percent,
@@ -78,17 +83,16 @@ pub(crate) struct CreditsSnapshotDisplay {
 pub(crate) fn rate_limit_snapshot_display(
     snapshot: &RateLimitSnapshot,
     captured_at: DateTime<Local>,
+    timestamp_format: TimestampFormat,
 ) -> RateLimitSnapshotDisplay {
     RateLimitSnapshotDisplay {
         captured_at,
-        primary: snapshot
-            .primary
-            .as_ref()
-            .map(|window| RateLimitWindowDisplay::from_window(window, captured_at)),
-        secondary: snapshot
-            .secondary
-            .as_ref()
-            .map(|window| RateLimitWindowDisplay::from_window(window, captured_at)),
+        primary: snapshot.primary.as_ref().map(|window| {
+            RateLimitWindowDisplay::from_window(window, captured_at, timestamp_format)
+        }),
+        secondary: snapshot.secondary.as_ref().map(|window| {
+            RateLimitWindowDisplay::from_window(window, captured_at, timestamp_format)
+        }),
         credits: snapshot.credits.as_ref().map(CreditsSnapshotDisplay::from),
     }
 }
@@ -1,13 +1,16 @@
+use super::helpers::format_reset_timestamp_as;
 use super::new_status_output;
 use super::rate_limit_snapshot_display;
 use crate::history_cell::HistoryCell;
 use chrono::Duration as ChronoDuration;
+use chrono::Local;
 use chrono::TimeZone;
 use chrono::Utc;
 use codex_core::AuthManager;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
 use codex_core::config::ConfigToml;
+use codex_core::config::types::TimestampFormat;
 use codex_core::protocol::CreditsSnapshot;
 use codex_core::protocol::RateLimitSnapshot;
 use codex_core::protocol::RateLimitWindow;
@@ -120,8 +123,9 @@ fn status_snapshot_includes_reasoning_details() {
             resets_at: Some(reset_at_from(&captured_at, 1_200)),
         }),
         credits: None,
+        request_limits: None,
     };
-    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
+    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at, TimestampFormat::Auto);
 
     let composite = new_status_output(
         &config,
@@ -171,8 +175,9 @@ fn status_snapshot_includes_monthly_limit() {
         }),
         secondary: None,
         credits: None,
+        request_limits: None,
     };
-    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
+    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at, TimestampFormat::Auto);
 
     let composite = new_status_output(
         &config,
@@ -211,8 +216,9 @@ fn status_snapshot_shows_unlimited_credits() {
             unlimited: true,
             balance: None,
         }),
+        request_limits: None,
     };
-    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
+    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at, TimestampFormat::Auto);
     let composite = new_status_output(
         &config,
         &auth_manager,
@@ -249,8 +255,9 @@ fn status_snapshot_shows_positive_credits() {
             unlimited: false,
             balance: Some("12.5".to_string()),
         }),
+        request_limits: None,
     };
-    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
+    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at, TimestampFormat::Auto);
     let composite = new_status_output(
         &config,
         &auth_manager,
@@ -287,8 +294,9 @@ fn status_snapshot_hides_zero_credits() {
             unlimited: false,
             balance: Some("0".to_string()),
         }),
+        request_limits: None,
     };
-    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
+    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at, TimestampFormat::Auto);
     let composite = new_status_output(
         &config,
         &auth_manager,
@@ -323,8 +331,9 @@ fn status_snapshot_hides_when_has_no_credits_flag() {
             unlimited: true,
             balance: None,
         }),
+        request_limits: None,
     };
-    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
+    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at, TimestampFormat::Auto);
     let composite = new_status_output(
         &config,
         &auth_manager,
@@ -341,6 +350,31 @@ fn status_snapshot_hides_when_has_no_credits_flag() {
     );
 }
 
+#[test]
+fn status_card_shows_project_config_that_chose_credentials() {
+    let temp_home = TempDir::new().expect("temp home");
+    let mut config = test_config(&temp_home);
+    config.cwd = PathBuf::from("/workspace/tests");
+    config.project_config = Some(PathBuf::from("/workspace/tests/.codex/config.toml"));
+
+    let auth_manager = test_auth_manager(&config);
+    let usage = TokenUsage::default();
+    let now = chrono::Local
+        .with_ymd_and_hms(2024, 1, 1, 0, 0, 0)
+        .single()
+        .expect("timestamp");
+
+    let composite = new_status_output(&config, &auth_manager, &usage, None, &None, None, now);
+    let rendered = render_lines(&composite.display_lines(120));
+
+    assert!(
+        rendered.iter().any(|line| line.contains("Config:")
+            && line.contains("/workspace/tests/.codex/config.toml")
+            && line.contains("(project: model and credentials)")),
+        "project config should be shown, got: {rendered:?}"
+    );
+}
+
 #[test]
 fn status_card_token_usage_excludes_cached_tokens() {
     let temp_home = TempDir::new().expect("temp home");
@@ -410,8 +444,9 @@ fn status_snapshot_truncates_in_narrow_terminal() {
         }),
         secondary: None,
         credits: None,
+        request_limits: None,
     };
-    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
+    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at, TimestampFormat::Auto);
 
     let composite = new_status_output(
         &config,
@@ -509,8 +544,9 @@ fn status_snapshot_includes_credits_and_limits() {
             unlimited: false,
             balance: Some("37.5".to_string()),
         }),
+        request_limits: None,
     };
-    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
+    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at, TimestampFormat::Auto);
 
     let composite = new_status_output(
         &config,
@@ -551,12 +587,13 @@ fn status_snapshot_shows_empty_limits_message() {
         primary: None,
         secondary: None,
         credits: None,
+        request_limits: None,
     };
     let captured_at = chrono::Local
         .with_ymd_and_hms(2024, 6, 7, 8, 9, 10)
         .single()
         .expect("timestamp");
-    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
+    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at, TimestampFormat::Auto);
 
     let composite = new_status_output(
         &config,
@@ -609,8 +646,9 @@ fn status_snapshot_shows_stale_limits_message() {
             resets_at: Some(reset_at_from(&captured_at, 1_800)),
         }),
         credits: None,
+        request_limits: None,
     };
-    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
+    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at, TimestampFormat::Auto);
     let now = captured_at + ChronoDuration::minutes(20);
 
     let composite = new_status_output(
@@ -668,8 +706,9 @@ fn status_snapshot_cached_limits_hide_credits_without_flag() {
             unlimited: false,
             balance: Some("80".to_string()),
         }),
+        request_limits: None,
     };
-    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
+    let rate_display = rate_limit_snapshot_display(&snapshot, captured_at, TimestampFormat::Auto);
     let now = captured_at + ChronoDuration::minutes(20);
 
     let composite = new_status_output(
@@ -742,3 +781,33 @@ fn status_context_window_uses_last_usage() {
         "context line should not use total aggregated tokens, got: {context_line}"
     );
 }
+
+#[test]
+fn reset_timestamp_honors_timestamp_format() {
+    let captured_at = Local
+        .with_ymd_and_hms(2024, 1, 2, 3, 4, 5)
+        .single()
+        .expect("timestamp");
+    let resets_at = captured_at + ChronoDuration::minutes(3 * 60 + 12);
+
+    assert_eq!(
+        format_reset_timestamp_as(resets_at, captured_at, TimestampFormat::Auto),
+        "06:16"
+    );
+    assert_eq!(
+        format_reset_timestamp_as(resets_at, captured_at, TimestampFormat::Relative),
+        "in 3h 12m"
+    );
+    assert_eq!(
+        format_reset_timestamp_as(
+            captured_at + ChronoDuration::days(2),
+            captured_at,
+            TimestampFormat::Relative
+        ),
+        "in 2d 0h"
+    );
+    assert_eq!(
+        format_reset_timestamp_as(resets_at, captured_at, TimestampFormat::Iso8601),
+        resets_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, false)
+    );
+}
@@ -7,7 +7,11 @@ use std::io::stdout;
 use std::panic;
 use std::pin::Pin;
 use std::sync::Arc;
+#[cfg(unix)]
+use std::sync::LazyLock;
 use std::sync::atomic::AtomicBool;
+#[cfg(unix)]
+use std::sync::atomic::AtomicI32;
 use std::sync::atomic::Ordering;
 
 use crossterm::Command;
@@ -51,6 +55,22 @@ mod job_control;
 /// A type alias for the terminal type used in this application
 pub type Terminal = CustomTerminal<CrosstermBackend<Stdout>>;
 
+/// Process-wide mirror of whether the alternate screen is currently entered, so that teardown
+/// paths without access to the `Tui` (panic hook, signal handlers) can leave it as well.
+static ALT_SCREEN_ENTERED: AtomicBool = AtomicBool::new(false);
+
+/// Raw value of the first termination signal received, or 0 when none has arrived yet.
+#[cfg(unix)]
+static TERMINATION_SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+/// Wakes the app loop when a termination signal arrives so it can shut the session down.
+#[cfg(unix)]
+static TERMINATION_NOTIFY: LazyLock<tokio::sync::Notify> = LazyLock::new(tokio::sync::Notify::new);
+
+/// How long the app loop gets to shut the session down before a signal forces an exit anyway.
+#[cfg(unix)]
+const TERMINATION_GRACE: std::time::Duration = std::time::Duration::from_secs(10);
+
 pub fn set_modes() -> Result<()> {
     execute!(stdout(), EnableBracketedPaste)?;
 
@@ -128,6 +148,18 @@ pub fn restore() -> Result<()> {
     Ok(())
 }
 
+/// Restore the terminal when the TUI is going away for good.
+///
+/// In addition to undoing `set_modes`, this leaves the alternate screen if an overlay was still
+/// showing. Safe to call more than once.
+pub fn restore_on_exit() -> Result<()> {
+    if ALT_SCREEN_ENTERED.swap(false, Ordering::Relaxed) {
+        let _ = execute!(stdout(), DisableAlternateScroll);
+        let _ = execute!(stdout(), LeaveAlternateScreen);
+    }
+    restore()
+}
+
 /// Initialize the terminal (inline viewport; history stays in normal scrollback)
 pub fn init() -> Result<Terminal> {
     if !stdin().is_terminal() {
@@ -139,6 +171,8 @@ pub fn init() -> Result<Terminal> {
     set_modes()?;
 
     set_panic_hook();
+    #[cfg(unix)]
+    install_termination_handlers();
 
     let backend = CrosstermBackend::new(stdout());
     let tui = CustomTerminal::with_options(backend)?;
@@ -148,11 +182,71 @@ pub fn init() -> Result<Terminal> {
 fn set_panic_hook() {
     let hook = panic::take_hook();
     panic::set_hook(Box::new(move |panic_info| {
-        let _ = restore(); // ignore any errors as we are already failing
+        let _ = restore_on_exit(); // ignore any errors as we are already failing
         hook(panic_info);
     }));
 }
 
+/// Shut down gracefully on SIGTERM, SIGHUP, or an externally delivered SIGINT.
+///
+/// Raw mode swallows Ctrl-C as a key event, so these only fire when another process (or a closing
+/// terminal) asks us to stop. The first signal is handed to the app loop via
+/// [`termination_requested`] so the session can flush its rollout and run its shutdown hooks; the
+/// caller then exits with `128 + signal`. A second signal, or the app loop failing to finish within
+/// [`TERMINATION_GRACE`], restores the terminal and exits immediately.
+#[cfg(unix)]
+fn install_termination_handlers() {
+    use tokio::signal::unix::SignalKind;
+    use tokio::signal::unix::signal;
+
+    let Ok(handle) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    for kind in [
+        SignalKind::terminate(),
+        SignalKind::hangup(),
+        SignalKind::interrupt(),
+    ] {
+        let Ok(mut stream) = signal(kind) else {
+            continue;
+        };
+        handle.spawn(async move {
+            if stream.recv().await.is_some() {
+                let signal = kind.as_raw_value();
+                if TERMINATION_SIGNAL
+                    .compare_exchange(0, signal, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_err()
+                {
+                    // A shutdown is already underway; stop waiting for it.
+                    force_exit();
+                }
+                TERMINATION_NOTIFY.notify_one();
+                let _ = tokio::time::timeout(TERMINATION_GRACE, stream.recv()).await;
+                force_exit();
+            }
+        });
+    }
+}
+
+#[cfg(unix)]
+fn force_exit() -> ! {
+    let _ = restore_on_exit();
+    std::process::exit(128 + TERMINATION_SIGNAL.load(Ordering::SeqCst));
+}
+
+/// Resolves with the raw signal number once a termination signal has been received.
+pub(crate) async fn termination_requested() -> i32 {
+    #[cfg(unix)]
+    {
+        TERMINATION_NOTIFY.notified().await;
+        TERMINATION_SIGNAL.load(Ordering::SeqCst)
+    }
+    #[cfg(not(unix))]
+    {
+        std::future::pending().await
+    }
+}
+
 #[derive(Debug)]
 pub enum TuiEvent {
     Key(KeyEvent),
@@ -302,6 +396,7 @@ impl Tui {
             let _ = self.terminal.clear();
         }
         self.alt_screen_active.store(true, Ordering::Relaxed);
+        ALT_SCREEN_ENTERED.store(true, Ordering::Relaxed);
         Ok(())
     }
 
@@ -314,6 +409,7 @@ impl Tui {
             self.terminal.set_viewport_area(saved);
         }
         self.alt_screen_active.store(false, Ordering::Relaxed);
+        ALT_SCREEN_ENTERED.store(false, Ordering::Relaxed);
         Ok(())
     }
 
@@ -1,3 +1,6 @@
+use std::path::Path;
+use std::path::PathBuf;
+
 /// Update action the CLI should perform after the TUI exits.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UpdateAction {
@@ -19,6 +22,44 @@ impl UpdateAction {
         }
     }
 
+    /// Returns the command that installs `version` into `dir` without touching
+    /// the current installation, so the new binary can be checked before the
+    /// update replaces it, along with the path of the staged `codex`. `None`
+    /// when the package manager cannot install into a separate directory.
+    pub fn staging_command(self, version: &str, dir: &Path) -> Option<(Vec<String>, PathBuf)> {
+        match self {
+            UpdateAction::NpmGlobalLatest => {
+                let args = vec![
+                    "npm".to_string(),
+                    "install".to_string(),
+                    "--prefix".to_string(),
+                    dir.display().to_string(),
+                    "--no-save".to_string(),
+                    format!("@openai/codex@{version}"),
+                ];
+                let bin = if cfg!(windows) { "codex.cmd" } else { "codex" };
+                Some((args, dir.join("node_modules").join(".bin").join(bin)))
+            }
+            UpdateAction::BunGlobalLatest | UpdateAction::BrewUpgrade => None,
+        }
+    }
+
+    /// Returns the command that reinstalls `version`, used to roll back an
+    /// update whose binary fails its self-test. `None` when the package manager
+    /// cannot install a specific version.
+    pub fn rollback_command(self, version: &str) -> Option<Vec<String>> {
+        let (command, _) = self.command_args();
+        match self {
+            UpdateAction::NpmGlobalLatest | UpdateAction::BunGlobalLatest => Some(vec![
+                command.to_string(),
+                "install".to_string(),
+                "-g".to_string(),
+                format!("@openai/codex@{version}"),
+            ]),
+            UpdateAction::BrewUpgrade => None,
+        }
+    }
+
     /// Returns string representation of the command-line arguments for invoking the update.
     pub fn command_str(self) -> String {
         let (command, args) = self.command_args();
@@ -65,6 +106,41 @@ fn detect_update_action(
 mod tests {
     use super::*;
 
+    #[test]
+    fn stages_and_rolls_back_npm_installs_at_a_pinned_version() {
+        let dir = Path::new("/tmp/codex-update");
+        let (args, bin) = UpdateAction::NpmGlobalLatest
+            .staging_command("0.60.0", dir)
+            .expect("npm can stage");
+        assert_eq!(
+            args,
+            vec![
+                "npm",
+                "install",
+                "--prefix",
+                "/tmp/codex-update",
+                "--no-save",
+                "@openai/codex@0.60.0"
+            ]
+        );
+        assert!(bin.starts_with(dir.join("node_modules").join(".bin")));
+        assert_eq!(
+            UpdateAction::BrewUpgrade.staging_command("0.60.0", dir),
+            None
+        );
+
+        assert_eq!(
+            UpdateAction::BunGlobalLatest.rollback_command("0.59.0"),
+            Some(vec![
+                "bun".to_string(),
+                "install".to_string(),
+                "-g".to_string(),
+                "@openai/codex@0.59.0".to_string()
+            ])
+        );
+        assert_eq!(UpdateAction::BrewUpgrade.rollback_command("0.59.0"), None);
+    }
+
     #[test]
     fn detects_update_action_without_env_mutation() {
         assert_eq!(
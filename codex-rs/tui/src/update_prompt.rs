@@ -29,7 +29,8 @@ use tokio_stream::StreamExt;
 
 pub(crate) enum UpdatePromptOutcome {
     Continue,
-    RunUpdate(UpdateAction),
+    /// Run `UpdateAction` to install the given version.
+    RunUpdate(UpdateAction, String),
 }
 
 pub(crate) async fn run_update_prompt_if_needed(
@@ -71,7 +72,10 @@ pub(crate) async fn run_update_prompt_if_needed(
     match screen.selection() {
         Some(UpdateSelection::UpdateNow) => {
             tui.terminal.clear()?;
-            Ok(UpdatePromptOutcome::RunUpdate(update_action))
+            Ok(UpdatePromptOutcome::RunUpdate(
+                update_action,
+                latest_version,
+            ))
         }
         Some(UpdateSelection::NotNow) | None => Ok(UpdatePromptOutcome::Continue),
         Some(UpdateSelection::DontRemind) => {
@@ -5,8 +5,9 @@ use crate::update_action::UpdateAction;
 use chrono::DateTime;
 use chrono::Duration;
 use chrono::Utc;
+use codex_core::ProviderTlsConfig;
 use codex_core::config::Config;
-use codex_core::default_client::create_client;
+use codex_core::default_client::create_non_provider_client_with_tls;
 use serde::Deserialize;
 use serde::Serialize;
 use std::path::Path;
@@ -29,8 +30,11 @@ pub fn get_upgrade_version(config: &Config) -> Option<String> {
         // Refresh the cached latest version in the background so TUI startup
         // isn’t blocked by a network call. The UI reads the previously cached
         // value (if any) for this run; the next run shows the banner if needed.
+        // The check goes through the same proxy as model requests, so it
+        // uses the provider's TLS settings.
+        let tls = config.model_provider.tls.clone();
         tokio::spawn(async move {
-            check_for_update(&version_file)
+            check_for_update(&version_file, tls.as_ref())
                 .await
                 .inspect_err(|e| tracing::error!("Failed to update version: {e}"))
         });
@@ -74,10 +78,13 @@ fn read_version_info(version_file: &Path) -> anyhow::Result<VersionInfo> {
     Ok(serde_json::from_str(&contents)?)
 }
 
-async fn check_for_update(version_file: &Path) -> anyhow::Result<()> {
+async fn check_for_update(
+    version_file: &Path,
+    tls: Option<&ProviderTlsConfig>,
+) -> anyhow::Result<()> {
     let latest_version = match update_action::get_update_action() {
         Some(UpdateAction::BrewUpgrade) => {
-            let cask_contents = create_client()
+            let cask_contents = create_non_provider_client_with_tls("Update check", tls)?
                 .get(HOMEBREW_CASK_URL)
                 .send()
                 .await?
@@ -89,7 +96,7 @@ async fn check_for_update(version_file: &Path) -> anyhow::Result<()> {
         _ => {
             let ReleaseInfo {
                 tag_name: latest_tag_name,
-            } = create_client()
+            } = create_non_provider_client_with_tls("Update check", tls)?
                 .get(LATEST_RELEASE_URL)
                 .send()
                 .await?
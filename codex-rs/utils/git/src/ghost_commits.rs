@@ -316,6 +316,53 @@ pub fn restore_to_commit(repo_path: &Path, commit_id: &str) -> Result<(), GitToo
     restore_to_commit_inner(repo_root.as_path(), repo_prefix.as_deref(), commit_id)
 }
 
+/// Paths (relative to the repository root) that differ between the ghost
+/// commit and the working tree under `repo_path`: what restoring it would
+/// revert, whether a patch or a command changed them.
+pub fn ghost_commit_changed_paths(
+    repo_path: &Path,
+    commit: &GhostCommit,
+) -> Result<Vec<PathBuf>, GitToolingError> {
+    // Snapshot the current state the same way so both trees include the
+    // same untracked files.
+    let current = create_ghost_commit(&CreateGhostCommitOptions::new(repo_path))?;
+    let repo_root = resolve_repository_root(repo_path)?;
+    let repo_prefix = repo_subdir(repo_root.as_path(), repo_path);
+    let mut args = vec![
+        OsString::from("diff"),
+        OsString::from("--name-only"),
+        OsString::from("-z"),
+        OsString::from(commit.id()),
+        OsString::from(current.id()),
+    ];
+    if let Some(prefix) = repo_prefix.as_deref() {
+        args.extend([OsString::from("--"), prefix.as_os_str().to_os_string()]);
+    }
+    let output = run_git_for_stdout_all(repo_root.as_path(), args, None)?;
+    Ok(output
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Point `ref_name` (e.g. `refs/codex/checkpoints/<name>`) at the ghost
+/// commit so `git gc` keeps it for as long as the ref exists.
+pub fn pin_ghost_commit(
+    repo_path: &Path,
+    ref_name: &str,
+    commit: &GhostCommit,
+) -> Result<(), GitToolingError> {
+    ensure_git_repository(repo_path)?;
+
+    let repo_root = resolve_repository_root(repo_path)?;
+    run_git_for_status(
+        repo_root.as_path(),
+        ["update-ref", ref_name, commit.id()],
+        None,
+    )
+}
+
 /// Restores the working tree and index to the given commit using `git restore`.
 /// The repository root and optional repository-relative prefix limit the restore scope.
 fn restore_to_commit_inner(
@@ -924,6 +971,52 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    /// Files edited, created, or deleted since the snapshot are reported.
+    fn changed_paths_lists_files_a_restore_reverts() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path();
+        init_test_repo(repo);
+        std::fs::write(repo.join("edited.txt"), "before\n")?;
+        std::fs::write(repo.join("deleted.txt"), "gone soon\n")?;
+        std::fs::write(repo.join("untouched.txt"), "same\n")?;
+        let ghost = create_ghost_commit(&CreateGhostCommitOptions::new(repo))?;
+
+        std::fs::write(repo.join("edited.txt"), "after\n")?;
+        std::fs::remove_file(repo.join("deleted.txt"))?;
+        std::fs::write(repo.join("created.txt"), "new\n")?;
+
+        assert_eq!(
+            ghost_commit_changed_paths(repo, &ghost)?,
+            vec![
+                PathBuf::from("created.txt"),
+                PathBuf::from("deleted.txt"),
+                PathBuf::from("edited.txt"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    /// Pinned ghost commits are reachable from the given ref.
+    fn pin_ghost_commit_points_ref_at_commit() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path();
+        init_test_repo(repo);
+        std::fs::write(repo.join("tracked.txt"), "contents\n")?;
+
+        let ghost = create_ghost_commit(&CreateGhostCommitOptions::new(repo))?;
+        pin_ghost_commit(repo, "refs/codex/checkpoints/before-refactor", &ghost)?;
+        let pinned = run_git_stdout(
+            repo,
+            &["rev-parse", "refs/codex/checkpoints/before-refactor"],
+        );
+        assert_eq!(pinned, ghost.id());
+
+        Ok(())
+    }
+
     #[test]
     /// Rejects force-included paths that escape the repository.
     fn create_ghost_commit_rejects_force_include_parent_path() {
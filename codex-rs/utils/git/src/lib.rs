@@ -22,6 +22,8 @@ pub use ghost_commits::LargeUntrackedDir;
 pub use ghost_commits::capture_ghost_snapshot_report;
 pub use ghost_commits::create_ghost_commit;
 pub use ghost_commits::create_ghost_commit_with_report;
+pub use ghost_commits::ghost_commit_changed_paths;
+pub use ghost_commits::pin_ghost_commit;
 pub use ghost_commits::restore_ghost_commit;
 pub use ghost_commits::restore_to_commit;
 pub use platform::create_symlink;
@@ -5,6 +5,8 @@ import type { ThreadItem } from "./items";
 /** Emitted when a new thread is started as the first event. */
 export type ThreadStartedEvent = {
   type: "thread.started";
+  /** Version of the event schema; see "Schema version and compatibility" in docs/exec.md. */
+  schema_version: number;
   /** The identifier of the new thread. Can be used to resume the thread later. */
   thread_id: string;
 };
@@ -15,6 +17,8 @@ export type ThreadStartedEvent = {
  */
 export type TurnStartedEvent = {
   type: "turn.started";
+  /** The credentials the turn's requests start out with, e.g. `chatgpt (me@example.com)`. */
+  auth?: string;
 };
 
 /** Describes the usage of tokens during a turn. */
@@ -68,6 +72,61 @@ export type ThreadErrorEvent = {
   message: string;
 };
 
+/** Codex switched to different credentials, e.g. because the active API key ran out of quota. */
+export type AuthSwitchedEvent = {
+  type: "auth.switched";
+  /** Redacted description of the previous credentials. */
+  from: string;
+  /** Redacted description of the credentials now in use. */
+  to: string;
+  reason:
+    | "quota_exceeded"
+    | "usage_limit_reached"
+    | "rate_limited"
+    | "provider_error"
+    | "limit_reset"
+    | "refresh_failed"
+    | "credentials_rejected";
+};
+
+/** A file or directory pinned in context. */
+export type DryRunPinnedFile = {
+  path: string;
+  /** Files the pin covers: 1 for a file, the files read for a directory. */
+  files: number;
+  tokens: number;
+  /** Why the pin is not sent, e.g. the file no longer exists. */
+  error?: string;
+};
+
+/**
+ * What `codex exec --dry-run` found the first request would contain. Emitted instead of a turn.
+ * Token counts are estimates from the size of each part of the request.
+ */
+export type DryRunReportEvent = {
+  type: "dry_run.report";
+  model: string;
+  model_context_window?: number;
+  /** The sum of the instructions, tools, and input tokens. */
+  estimated_prompt_tokens: number;
+  instructions_tokens: number;
+  tools_tokens: number;
+  /** The conversation so far, the context sent with every request, and the prompt. */
+  input_tokens: number;
+  /** The `AGENTS.md` files whose contents are sent. */
+  instruction_files: string[];
+  pinned_files: DryRunPinnedFile[];
+  /** Local images attached to the prompt. */
+  images: string[];
+  /** Names of the tools the model could call. */
+  tools: string[];
+  sandbox: "read-only" | "workspace-write" | "danger-full-access";
+  network_access: boolean;
+  /** Where commands could write; empty when the sandbox is read-only or there is no sandbox. */
+  writable_roots: string[];
+  approval_policy: string;
+};
+
 /** Top-level JSONL events emitted by codex exec. */
 export type ThreadEvent =
   | ThreadStartedEvent
@@ -77,4 +136,6 @@ export type ThreadEvent =
   | ItemStartedEvent
   | ItemUpdatedEvent
   | ItemCompletedEvent
-  | ThreadErrorEvent;
+  | ThreadErrorEvent
+  | AuthSwitchedEvent
+  | DryRunReportEvent;
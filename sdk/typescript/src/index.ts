@@ -9,6 +9,9 @@ export type {
   ItemCompletedEvent,
   ThreadError,
   ThreadErrorEvent,
+  AuthSwitchedEvent,
+  DryRunReportEvent,
+  DryRunPinnedFile,
   Usage,
 } from "./events";
 export type {
@@ -36,6 +36,7 @@ describe("Codex", () => {
       expect(events).toEqual([
         {
           type: "thread.started",
+          schema_version: 1,
           thread_id: expect.any(String),
         },
         {